@@ -0,0 +1,34 @@
+use std::{future::Future, io};
+
+pub use tokio_util::sync::CancellationToken;
+
+tokio::task_local! {
+    static TOKEN: CancellationToken;
+}
+
+/// The token of the tool call running on this task, cancelled when the client
+/// sends `notifications/cancelled` for it. Outside a call it is a fresh token
+/// that is never cancelled. Task-locals do not reach the blocking pool or
+/// rayon, so take the token before handing work to them.
+pub fn current() -> CancellationToken {
+    TOKEN.try_with(CancellationToken::clone).unwrap_or_default()
+}
+
+pub fn cancelled_error() -> io::Error {
+    io::Error::new(io::ErrorKind::Interrupted, "cancelled by the client")
+}
+
+/// Fail with `cancelled_error` once `token` is cancelled, for long loops to
+/// call between steps
+pub fn check(token: &CancellationToken) -> io::Result<()> {
+    if token.is_cancelled() {
+        Err(cancelled_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Run a tool call with `token` as its `current` token
+pub async fn scope<F: Future>(token: CancellationToken, call: F) -> F::Output {
+    TOKEN.scope(token, call).await
+}
@@ -0,0 +1,286 @@
+use std::{
+    fs::OpenOptions,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    cli::CommandArguments,
+    config::{PolicySource, ServerConfig},
+    external_tools::ExternalToolRegistry,
+    fs_service::{patterns::PathPatterns, remote::RemoteStorage, roots::Access, utils::expand_home, FileSystemService},
+    plugins::PluginRegistry,
+    tools::FileSystemTools,
+};
+
+/// One allowed or blocked directory as given and as it resolves
+#[derive(Debug, Clone, Serialize)]
+pub struct DirectoryCheck {
+    pub requested: String,
+    /// Canonical path, when the directory exists
+    pub resolved: Option<PathBuf>,
+    pub exists: bool,
+    pub is_dir: bool,
+    /// Set on allowed directories, from their `:ro` or `:rw` suffix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access: Option<Access>,
+}
+
+/// A directory the server writes to, probed by creating and removing a file
+#[derive(Debug, Clone, Serialize)]
+pub struct WritableCheck {
+    pub purpose: String,
+    pub path: PathBuf,
+    pub writable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The result of `check-config`: `ok` is false when any error was found
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReport {
+    pub ok: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    pub allowed_directories: Vec<DirectoryCheck>,
+    pub blocked_directories: Vec<DirectoryCheck>,
+    pub writable_directories: Vec<WritableCheck>,
+    /// The settings the server would run with
+    pub settings: Value,
+}
+
+fn check_directory(requested: &str) -> DirectoryCheck {
+    let path = expand_home(PathBuf::from(requested));
+    let resolved = std::fs::canonicalize(&path).ok();
+    DirectoryCheck {
+        requested: requested.to_string(),
+        is_dir: resolved.as_ref().is_some_and(|p| p.is_dir()),
+        exists: resolved.is_some(),
+        resolved,
+        access: None,
+    }
+}
+
+fn check_allowed_directory(requested: &str) -> DirectoryCheck {
+    let (dir, access) = Access::split(requested);
+    DirectoryCheck { requested: requested.to_string(), access: Some(access), ..check_directory(dir) }
+}
+
+fn check_writable(purpose: &str, dir: &Path) -> WritableCheck {
+    let probe = dir.join(format!(".aichemistforge-write-check-{}", std::process::id()));
+    let result = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .and_then(|_| std::fs::remove_file(&probe));
+    WritableCheck {
+        purpose: purpose.to_string(),
+        path: dir.to_path_buf(),
+        writable: result.is_ok(),
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+// The resolved path, or the requested one for directories that do not exist
+fn effective(check: &DirectoryCheck) -> PathBuf {
+    let requested = match check.access {
+        Some(_) => Access::split(&check.requested).0,
+        None => &check.requested,
+    };
+    check.resolved.clone().unwrap_or_else(|| expand_home(PathBuf::from(requested)))
+}
+
+/// Load everything `args` points at without starting the server: resolve the
+/// allowed and blocked directories, report missing, duplicate and overlapping
+/// entries, parse the config, plugin and remote storage files, and probe the
+/// directories the server writes to.
+pub fn check_config(args: &CommandArguments) -> ConfigReport {
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+
+    // The config's [policy] table overrides the command-line directories
+    let config = args.config.as_ref().and_then(|path| match ServerConfig::load(path) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            errors.push(format!("config {}: {}", path.display(), e));
+            None
+        }
+    });
+    let policy = PolicySource::new(args).resolve(&config.clone().unwrap_or_default());
+
+    // Glob patterns match paths that need not exist; only their syntax is checked
+    if let Err(e) = FileSystemService::try_new(&policy.allowed_directories, &policy.blocked_directories) {
+        errors.push(format!("policy: {}", e));
+    }
+    let (allowed_dirs, _) = PathPatterns::partition(&policy.allowed_directories);
+    let (blocked_dirs, _) = PathPatterns::partition(&policy.blocked_directories);
+    let allowed: Vec<DirectoryCheck> = allowed_dirs.into_iter().map(|dir| check_allowed_directory(dir)).collect();
+    let blocked: Vec<DirectoryCheck> = blocked_dirs.into_iter().map(|dir| check_directory(dir)).collect();
+
+    for check in &allowed {
+        if !check.exists {
+            errors.push(format!("allowed directory {} does not exist", check.requested));
+        } else if !check.is_dir {
+            errors.push(format!("allowed directory {} is not a directory", check.requested));
+        }
+    }
+    for check in &blocked {
+        if !check.exists {
+            warnings.push(format!("blocked directory {} does not exist", check.requested));
+        }
+    }
+    for (i, a) in allowed.iter().enumerate() {
+        let a_path = effective(a);
+        for b in &allowed[i + 1..] {
+            let b_path = effective(b);
+            if a_path == b_path {
+                warnings.push(format!("allowed directories {} and {} are the same directory", a.requested, b.requested));
+            } else if (a_path.starts_with(&b_path) || b_path.starts_with(&a_path)) && a.access == b.access {
+                warnings.push(format!("allowed directories {} and {} overlap; the inner one is redundant", a.requested, b.requested));
+            }
+        }
+        for b in &blocked {
+            let b_path = effective(b);
+            if a_path.starts_with(&b_path) {
+                errors.push(format!("allowed directory {} is inside blocked directory {}", a.requested, b.requested));
+            }
+        }
+    }
+    if !allowed.is_empty() {
+        for b in &blocked {
+            let b_path = effective(b);
+            if !allowed.iter().any(|a| b_path.starts_with(effective(a))) {
+                warnings.push(format!("blocked directory {} is outside every allowed directory and has no effect", b.requested));
+            }
+        }
+    }
+
+    let mut reserved: Vec<String> = FileSystemTools::tools(crate::tools::ToolStyle::Both).into_iter().map(|tool| tool.name).collect();
+    let mut external_tools = Vec::new();
+    if let (Some(path), Some(config)) = (&args.config, &config) {
+        match ExternalToolRegistry::from_config(config, &reserved) {
+            Ok(registry) => {
+                external_tools = registry.describe();
+                reserved.extend(registry.names());
+            }
+            Err(e) => errors.push(format!("config {}: {}", path.display(), e)),
+        }
+    }
+    let http_transport = matches!(args.transport, crate::server::Transport::Sse | crate::server::Transport::StreamableHttp);
+    if http_transport && !cfg!(feature = "http") {
+        errors.push(format!("--transport {} needs a build with the http feature", args.transport.name()));
+    }
+    let mut plugins = Vec::new();
+    if let Some(dir) = &args.plugins_dir {
+        match PluginRegistry::load_dir(dir, &reserved) {
+            Ok(registry) => plugins = registry.describe(),
+            Err(e) => errors.push(format!("plugins {}: {}", dir.display(), e)),
+        }
+    }
+    let mut remote_storage = Vec::new();
+    if let Some(path) = &args.remote_storage {
+        match RemoteStorage::from_config_file(path) {
+            Ok(storage) => remote_storage = storage.describe(),
+            Err(e) => errors.push(format!("remote storage {}: {}", path.display(), e)),
+        }
+    }
+
+    let mut writable = Vec::new();
+    if let Some(cache) = &args.metadata_cache {
+        let dir = cache.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        writable.push(check_writable("metadata cache", dir));
+    }
+    if args.sandbox {
+        writable.push(check_writable("sandbox overlay", &std::env::temp_dir()));
+    }
+    if let Some(dir) = &args.scratch_dir {
+        let scratch = expand_home(dir.clone());
+        let scratch = std::fs::canonicalize(&scratch).unwrap_or(scratch);
+        if !allowed.is_empty() && !allowed.iter().any(|a| scratch.starts_with(effective(a))) {
+            errors.push(format!("scratch directory {} is outside every allowed directory", dir.display()));
+        }
+        if let Some(b) = blocked.iter().find(|b| scratch.starts_with(effective(b))) {
+            errors.push(format!("scratch directory {} is inside blocked directory {}", dir.display(), b.requested));
+        }
+        let owner = allowed.iter().filter(|a| scratch.starts_with(effective(a))).max_by_key(|a| effective(a).components().count());
+        if let Some(a) = owner.filter(|a| a.access == Some(Access::ReadOnly)) {
+            errors.push(format!("scratch directory {} is inside read-only directory {}", dir.display(), a.requested));
+        }
+        if scratch.is_dir() {
+            writable.push(check_writable("scratch", &scratch));
+        }
+    }
+    if let Some(file) = &args.tags_file {
+        let file = expand_home(file.clone());
+        let dir = file.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        // A missing directory is created on the first tag
+        if dir.is_dir() {
+            writable.push(check_writable("tags", dir));
+        }
+    }
+    if let Some(file) = &args.log_file {
+        let dir = file.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        writable.push(check_writable("log file", dir));
+    }
+    if let Some(file) = &args.metrics_file {
+        let dir = file.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        writable.push(check_writable("metrics file", dir));
+    }
+    for check in &writable {
+        if !check.writable {
+            errors.push(format!(
+                "{} directory {} is not writable: {}",
+                check.purpose,
+                check.path.display(),
+                check.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+    }
+
+    let settings = json!({
+        "mode": if allowed.is_empty() { "unrestricted" } else { "allowlist" },
+        "tool_style": format!("{:?}", args.tool_style).to_lowercase(),
+        "transport": args.transport.name(),
+        "http_bind": args.http_bind.to_string(),
+        "tcp_bind": args.tcp_bind.to_string(),
+        "shutdown_grace_secs": args.shutdown_grace_secs,
+        "max_message_bytes": args.max_message_bytes,
+        "log_level": format!("{:?}", args.log_level()).to_lowercase(),
+        "log_file": args.log_file,
+        "metrics_file": args.metrics_file,
+        "readonly": policy.readonly,
+        "sandbox": args.sandbox,
+        "dry_run": args.dry_run,
+        "respect_locks": args.respect_locks,
+        "symlinks": args.symlinks,
+        "backup": args.backup,
+        "undo_history": args.undo_history,
+        "trash": !args.no_trash,
+        "trash_dir": args.trash_dir,
+        "scratch_dir": args.scratch_dir,
+        "tags_file": args.tags_file,
+        "client_roots": !args.ignore_client_roots,
+        "metadata_cache": args.metadata_cache,
+        "slow_op_threshold_ms": args.slow_op_threshold_ms,
+        "download_allow": args.download_allow,
+        "download_max_bytes": args.download_max_bytes,
+        "io_limit": policy.io_limit.filter(|limit| *limit > 0),
+        "max_read_bytes": policy.max_read_bytes.filter(|limit| *limit > 0),
+        "config": args.config,
+        "external_tools": external_tools,
+        "plugins": plugins,
+        "remote_storage": remote_storage,
+    });
+
+    ConfigReport {
+        ok: errors.is_empty(),
+        errors,
+        warnings,
+        allowed_directories: allowed,
+        blocked_directories: blocked,
+        writable_directories: writable,
+        settings,
+    }
+}
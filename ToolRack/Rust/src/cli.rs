@@ -1,10 +1,14 @@
-use clap::{arg, command, Parser};
+use anyhow::Context;
+use clap::{arg, command, parser::ValueSource, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use std::{ffi::OsString, path::PathBuf, str::FromStr};
 
 #[derive(Parser, Debug)]
 #[command(name =  env!("CARGO_PKG_NAME"))]
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(about = "A lightning-fast, asynchronous, and lightweight MCP server designed for efficient handling of various filesystem operations",
 long_about = None)]
+// `server [OPTIONS] [DIRS]... check-config` ends the directory list at the subcommand
+#[command(subcommand_precedence_over_arg = true)]
 pub struct CommandArguments {
     #[arg(
         long,
@@ -19,11 +23,467 @@ pub struct CommandArguments {
         help = "List of directories that are permitted for the operation. Leave empty for unrestricted access (except blocked directories)."
     )]
     pub allowed_directories: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "SQLite database used to cache file hashes between scans.",
+        long_help = "Path to a SQLite database (created if missing) that records file size, mtime and SHA-256. Duplicate, compare and merge scans reuse a cached hash while size and mtime are unchanged. Requires the metadata-cache feature."
+    )]
+    pub metadata_cache: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "MS",
+        default_value_t = crate::profiler::DEFAULT_SLOW_THRESHOLD_MS,
+        help = "Tool calls taking at least this many milliseconds are kept in the get_slow_operations log."
+    )]
+    pub slow_op_threshold_ms: u64,
+
+    #[arg(
+        long,
+        num_args = 0..,
+        value_delimiter = ',',
+        value_name = "DOMAIN_OR_URL",
+        help = "Comma-separated domains or URL prefixes download_file may fetch. Downloads are disabled when empty.",
+        long_help = "Domains (example.com, which also admits its subdomains) or URL prefixes (https://example.com/releases/) that download_file may fetch from. Redirects must stay within the list. Example: --download-allow 'github.com,objects.githubusercontent.com'"
+    )]
+    pub download_allow: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = crate::fs_service::download::DEFAULT_DOWNLOAD_MAX_BYTES,
+        help = "Largest file download_file will write."
+    )]
+    pub download_max_bytes: u64,
+
+    #[arg(
+        long,
+        value_name = "BYTES_PER_SEC",
+        help = "Cap the bytes per second read by bulk copies, archives and hashing. Unlimited when unset or 0.",
+        long_help = "Throttle the streaming copy (copy_files, merges, batches, sandbox commits, concat and split), zip and hashing (duplicate, compare and merge scans) code paths to this many bytes per second, shared across concurrent calls, so long maintenance runs leave the machine usable. Example: --io-limit 20000000 for about 20 MB/s."
+    )]
+    pub io_limit: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        help = "Refuse to read files larger than this whole. Unlimited when unset or 0.",
+        long_help = "Largest file read_file, read_multiple_files and read_media_file will read whole (head, tail and summarize reads included). Larger files get an error naming their size and pointing at read_file_lines or a byte-range read (read_file with offset_bytes and length_bytes), which are not limited. Example: --max-read-bytes 52428800 for 50 MB."
+    )]
+    pub max_read_bytes: Option<u64>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "JSON file enabling s3:// and sftp:// storage backends.",
+        long_help = "JSON file listing remote stores that s3://bucket/key and sftp://host/path resolve to, e.g. {\"backends\": [{\"type\": \"s3\", \"bucket\": \"artifacts\", \"region\": \"us-east-1\", \"read_only\": true}]}. Requires the s3 or sftp feature."
+    )]
+    pub remote_storage: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Refuse every tool call that would change files.",
+        long_help = "Run in read-only mode: writes, edits, deletes, moves, copies, archive creation and extraction, new directories, downloads, tags and the other operations that change files are refused with an error, and so are external tools and plugins with the write capability. Reads, listings and searches work as usual. The [policy] table of --config can turn it on or off, and is re-read on SIGHUP or reload_policy."
+    )]
+    pub readonly: bool,
+
+    #[arg(
+        long,
+        help = "Hold every change in a temporary overlay until commit_sandbox writes it to disk.",
+        long_help = "Run in sandbox mode: writes, edits, moves, copies, deletes and new directories go to a copy-on-write overlay instead of the real files. diff_sandbox shows what would change and commit_sandbox applies it; anything not committed is discarded when the server exits. Operations that cannot be sandboxed (batch_operations, download_file, applying plans, ...) are refused."
+    )]
+    pub sandbox: bool,
+
+    #[arg(
+        long,
+        conflicts_with = "sandbox",
+        help = "Only preview changes: write-class tool calls report what they would do and leave the disk untouched.",
+        long_help = "Run in dry-run mode: every tool call works on a throwaway copy-on-write overlay, and calls that would change files return a preview instead (diffs for writes and edits, file lists for deletes, copies and moves). Nothing is ever written. Operations that cannot be previewed (batch_operations, download_file, applying plans, external tools, ...) are refused. The set_dry_run tool can turn the mode on at runtime, but not off when this flag is given."
+    )]
+    pub dry_run: bool,
+
+    #[arg(
+        long,
+        help = "Refuse writes, edits, moves, copies and deletes of paths another session has locked with acquire_lock.",
+        long_help = "Make this server honour the advisory locks taken with acquire_lock: writing, editing, moving, copying onto or deleting a path fails while another session holds an unexpired lock on it. Without this flag the locks only inform agents that check them."
+    )]
+    pub respect_locks: bool,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = crate::fs_service::symlinks::SymlinkPolicy::FollowWithinRoot,
+        help = "What to do with symbolic links: deny, no-follow or follow-within-root (the default).",
+        long_help = "deny: refuse every path that is or passes through a symbolic link inside the allowed directories; directory trees, searches and copies leave links out. no-follow: work on links themselves (delete, move, copy as a link, get_file_info) but never read, write or walk through them. follow-within-root: follow a link only when its target is inside the allowed directories; other links are listed and copied as links."
+    )]
+    pub symlinks: crate::fs_service::symlinks::SymlinkPolicy,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = crate::fs_service::backup::BackupMode::Off,
+        help = "Keep what write_file, edit_file, apply_patch and delete_file replace or remove: off (the default), sibling or dir.",
+        long_help = "sibling: copy a file to name.bak beside it before it is overwritten or edited, keeping only the last version; a deleted file or directory is renamed to name.bak instead of removed. dir: keep every version as .aichemist_backups/name.<UTC time> in the file's directory (left out of directory walks by default). Changes made in a --sandbox are not backed up until they are committed."
+    )]
+    pub backup: crate::fs_service::backup::BackupMode,
+
+    #[arg(
+        long,
+        value_name = "STEPS",
+        default_value_t = crate::fs_service::undo::DEFAULT_UNDO_HISTORY,
+        help = "How many writes, edits, moves and deletes undo_last_operation can take back. 0 turns undo off.",
+        long_help = "Journal this many of the latest write_file, edit_file, apply_patch, move_file and delete_file changes so undo_last_operation and undo_to_step can reverse them. Overwritten files are copied (up to 64 MiB each) and deleted items moved into a journal directory under the system temp directory, which is removed when the server exits. 0 keeps no journal, and deletes are final again."
+    )]
+    pub undo_history: usize,
+
+    #[arg(
+        long,
+        help = "Make delete_file remove things for good instead of moving them to the trash.",
+        long_help = "By default delete_file moves files and directories to the user's trash: the freedesktop.org trash on Linux and the BSDs ($XDG_DATA_HOME/Trash, or $topdir/.Trash-$uid for an item on another mount), the Trash on macOS, the Recycle Bin on Windows. An item that cannot be trashed is not deleted unless the call passes permanent: true. With --no-trash every delete is permanent, kept only by --backup or the undo journal."
+    )]
+    pub no_trash: bool,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Use DIR, laid out like a freedesktop.org trash, instead of the user's trash.",
+        long_help = "Move deleted items to DIR/files, with a DIR/info/<name>.trashinfo recording where each came from, instead of the user's trash. An item on another file system than DIR is copied in and then removed. Useful in containers, or to keep the trash on the same file system as the allowed directories."
+    )]
+    pub trash_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Where create_temp_file and create_temp_dir put scratch paths.",
+        long_help = "Root for the scratch paths made by create_temp_file and create_temp_dir; each session works in its own session-<id> subdirectory, removed when the server exits. Must pass the allow/block rules. Defaults to .aichemist-scratch in the first allowed directory, or the system temp directory when access is unrestricted."
+    )]
+    pub scratch_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "JSON file holding the tags and notes of tag_path and annotate_path.",
+        long_help = "JSON file where tag_path and annotate_path record tags and notes on paths, so later sessions can query them with list_tags and find_by_tag. Sessions pointed at the same file share their tags. Defaults to ~/.aichemistforge/tags.json."
+    )]
+    pub tags_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Do not add the directories the MCP client reports as roots to the allowlist.",
+        long_help = "By default the server asks clients that support roots for their workspace folders (roots/list) after initialization, and again when they announce a change, and allows those directories alongside the ones given on the command line. With no directories on the command line, that limits access to the client's roots. This flag keeps the allowlist to the command line only."
+    )]
+    pub ignore_client_roots: bool,
+
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Directory of WASM plugins (*.wasm, with optional *.json manifests) to expose as tools.",
+        long_help = "Load every WASI command module (*.wasm) in DIR as an extra tool. A sibling <name>.json manifest may set the tool name, description, input_schema and the capabilities (read, write, list) granting the plugin access to files through the server's path checks. Requires the plugins feature."
+    )]
+    pub plugins_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "TOML configuration file, e.g. declaring external command tools.",
+        long_help = "TOML file with server configuration. Its [[tools]] tables declare extra tools that run a program listed in allowed_commands (no shell) with an argv template filled from the tool arguments, inside a working directory that must pass the allow/block rules, and killed after timeout_secs."
+    )]
+    pub config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = crate::tools::ToolStyle::Grouped,
+        help = "Expose the grouped operation tools, one flat tool per operation, or both.",
+        long_help = "grouped: the five operation-enum tools (single_file_operations, ...) gated by operation modes. flat: one small tool per operation (read_file, write_file, ...) with only that operation's parameters and no operation modes. both: all of them."
+    )]
+    pub tool_style: crate::tools::ToolStyle,
+
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = crate::server::Transport::Stdio,
+        help = "How clients connect: stdio (the default), sse, streamable-http or tcp.",
+        long_help = "stdio: JSON-RPC lines on stdin and stdout, for clients that launch the server. sse: the MCP HTTP+SSE transport on --http-bind, for web-based clients; they open an event stream with GET /sse and post messages to the /message endpoint it announces. streamable-http: the MCP Streamable HTTP transport on --http-bind, one /mcp endpoint taking POSTed messages and answering with JSON or an event stream, with sessions in the Mcp-Session-Id header; suits deployment behind a reverse proxy. The HTTP transports require the http feature. tcp: JSON-RPC lines, as on stdio, over connections to --tcp-bind, for agents on other machines."
+    )]
+    pub transport: crate::server::Transport,
+
+    #[arg(
+        long,
+        value_name = "ADDR",
+        default_value = crate::server::DEFAULT_HTTP_BIND,
+        help = "Address the HTTP transports listen on.",
+        long_help = "Address and port the sse and streamable-http transports listen on. The default only accepts connections from this machine; binding another address exposes the server without authentication."
+    )]
+    pub http_bind: std::net::SocketAddr,
+
+    #[arg(
+        long,
+        value_name = "ADDR",
+        default_value = crate::server::DEFAULT_TCP_BIND,
+        help = "Address the tcp transport listens on.",
+        long_help = "Address and port the tcp transport listens on, e.g. 0.0.0.0:8765 to accept agents on other machines. Connections are neither authenticated nor encrypted, so limit the port to trusted hosts with a firewall or VPN."
+    )]
+    pub tcp_bind: std::net::SocketAddr,
+
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = crate::server::DEFAULT_SHUTDOWN_GRACE_SECS,
+        help = "How long shutdown waits for running tool calls before cancelling them.",
+        long_help = "On SIGINT or SIGTERM, or when the client closes stdin, the server stops taking requests and gives the tool calls already running this many seconds to finish and send their responses. Calls still running after that are cancelled: they stop at their next check and remove partly written archives. A second signal exits at once."
+    )]
+    pub shutdown_grace_secs: u64,
+
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = crate::server::DEFAULT_MAX_MESSAGE_BYTES,
+        help = "Largest JSON-RPC message the server accepts.",
+        long_help = "Refuse incoming JSON-RPC messages (lines on stdio and tcp, request bodies on the HTTP transports) larger than this many bytes. Oversized messages are skipped as they arrive instead of being buffered, and answered with an Invalid Request error. The default is 64 MiB; large file contents count towards it."
+    )]
+    pub max_message_bytes: usize,
+
+    #[arg(
+        long,
+        value_enum,
+        value_name = "LEVEL",
+        help = "What to write to stderr: error, warn, info (default), debug or trace.",
+        long_help = "What to write to stderr. info (the default) covers startup and configuration messages. debug adds one line per JSON-RPC message with its method, id and size. trace logs full message payloads, including file contents, so keep it for local debugging. When neither this nor AICHEMIST_LOG_LEVEL is set, a plain level in RUST_LOG (e.g. RUST_LOG=warn) is used."
+    )]
+    pub log_level: Option<crate::logging::LogLevel>,
+
+    #[arg(long, conflicts_with = "log_level", help = "Only write errors to stderr; same as --log-level error.")]
+    pub quiet: bool,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Append log messages to this file instead of writing them to stderr.",
+        long_help = "Append log messages, at the --log-level verbosity and with UTC timestamps, to PATH instead of writing them to stderr. Useful when the MCP client discards the server's stderr."
+    )]
+    pub log_file: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Write the server_stats counters to this file as JSON every minute and on shutdown.",
+        long_help = "Write the counters reported by the server_stats tool (per-tool calls, errors, latencies and bytes read and written) to PATH as JSON every minute and once more on shutdown. The file is replaced in one step, so readers never see a partial snapshot."
+    )]
+    pub metrics_file: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Validate the options and the files they point to, print a JSON report of
+    /// the effective settings and exit with status 1 if anything is wrong.
+    /// Options and directories go before the subcommand.
+    CheckConfig,
+}
+
+/// Prefix of the environment variables that stand in for command-line flags
+pub const ENV_PREFIX: &str = "AICHEMIST_";
+
+// Whether a flag was given on the command line, which beats its environment variable
+fn from_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+fn parse_env<T: FromStr>(name: &str, value: OsString) -> anyhow::Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    let value = value.to_str().with_context(|| format!("{}{} is not valid UTF-8", ENV_PREFIX, name))?;
+    value
+        .trim()
+        .parse()
+        .map_err(|e| anyhow::anyhow!("{}{}={}: {}", ENV_PREFIX, name, value, e))
+}
+
+fn parse_env_bool(name: &str, value: OsString) -> anyhow::Result<bool> {
+    match value.to_string_lossy().trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(true),
+        "" | "0" | "false" | "no" | "off" => Ok(false),
+        other => Err(anyhow::anyhow!("{}{}={}: expected true or false", ENV_PREFIX, name, other)),
+    }
+}
+
+// Directory lists use the platform's PATH separator (';' on Windows, ':'
+// elsewhere), so Windows paths containing commas need no quoting. A `ro` or
+// `rw` entry is the access suffix of the directory before it, which ':' split off.
+fn split_dirs(value: &OsString) -> Vec<String> {
+    let mut dirs: Vec<String> = Vec::new();
+    for path in std::env::split_paths(value).filter(|path| !path.as_os_str().is_empty()) {
+        let path = path.to_string_lossy().into_owned();
+        match dirs.last_mut() {
+            Some(last) if path == "ro" || path == "rw" => {
+                last.push(':');
+                last.push_str(&path);
+            }
+            _ => dirs.push(path),
+        }
+    }
+    dirs
 }
 
 impl CommandArguments {
+    /// Parse the process arguments, filling flags that were not given from
+    /// `AICHEMIST_*` environment variables. Command-line flags take precedence
+    /// over environment variables, which take precedence over defaults.
     pub fn parse_from_env() -> anyhow::Result<Self> {
-        let args = Self::parse();
-        Ok(args)
+        Self::parse_with_env(std::env::args_os(), |name| std::env::var_os(name))
+    }
+
+    /// [`parse_from_env`](Self::parse_from_env) with explicit arguments and a
+    /// lookup for the variables (named without the `AICHEMIST_` prefix)
+    pub fn parse_with_env<I, T>(args: I, env: impl Fn(&str) -> Option<OsString>) -> anyhow::Result<Self>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let matches = Self::command().get_matches_from(args);
+        let mut parsed = Self::from_arg_matches(&matches)?;
+        let var = |name: &str, id: &str| {
+            if from_command_line(&matches, id) {
+                None
+            } else {
+                env(&format!("{}{}", ENV_PREFIX, name))
+            }
+        };
+
+        if let Some(value) = var("ALLOWED_DIRS", "allowed_directories") {
+            parsed.allowed_directories = split_dirs(&value);
+        }
+        if let Some(value) = var("BLOCKED_DIRS", "blocked_directories") {
+            parsed.blocked_directories = split_dirs(&value);
+        }
+        if let Some(value) = var("CONFIG", "config") {
+            parsed.config = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("METADATA_CACHE", "metadata_cache") {
+            parsed.metadata_cache = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("SLOW_OP_THRESHOLD_MS", "slow_op_threshold_ms") {
+            parsed.slow_op_threshold_ms = parse_env("SLOW_OP_THRESHOLD_MS", value)?;
+        }
+        if let Some(value) = var("DOWNLOAD_ALLOW", "download_allow") {
+            parsed.download_allow = value
+                .to_string_lossy()
+                .split(',')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Some(value) = var("DOWNLOAD_MAX_BYTES", "download_max_bytes") {
+            parsed.download_max_bytes = parse_env("DOWNLOAD_MAX_BYTES", value)?;
+        }
+        if let Some(value) = var("IO_LIMIT", "io_limit") {
+            parsed.io_limit = Some(parse_env("IO_LIMIT", value)?);
+        }
+        if let Some(value) = var("MAX_READ_BYTES", "max_read_bytes") {
+            parsed.max_read_bytes = Some(parse_env("MAX_READ_BYTES", value)?);
+        }
+        if let Some(value) = var("REMOTE_STORAGE", "remote_storage") {
+            parsed.remote_storage = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("READONLY", "readonly") {
+            parsed.readonly = parse_env_bool("READONLY", value)?;
+        }
+        if let Some(value) = var("SANDBOX", "sandbox") {
+            parsed.sandbox = parse_env_bool("SANDBOX", value)?;
+        }
+        if let Some(value) = var("DRY_RUN", "dry_run") {
+            parsed.dry_run = parse_env_bool("DRY_RUN", value)?;
+        }
+        if let Some(value) = var("RESPECT_LOCKS", "respect_locks") {
+            parsed.respect_locks = parse_env_bool("RESPECT_LOCKS", value)?;
+        }
+        if let Some(value) = var("SCRATCH_DIR", "scratch_dir") {
+            parsed.scratch_dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("TAGS_FILE", "tags_file") {
+            parsed.tags_file = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("IGNORE_CLIENT_ROOTS", "ignore_client_roots") {
+            parsed.ignore_client_roots = parse_env_bool("IGNORE_CLIENT_ROOTS", value)?;
+        }
+        if let Some(value) = var("LOG_FILE", "log_file") {
+            parsed.log_file = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("METRICS_FILE", "metrics_file") {
+            parsed.metrics_file = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("PLUGINS_DIR", "plugins_dir") {
+            parsed.plugins_dir = Some(PathBuf::from(value));
+        }
+        if parsed.log_level.is_none() && !parsed.quiet {
+            if let Some(value) = env(&format!("{}LOG_LEVEL", ENV_PREFIX)) {
+                let name = value.to_string_lossy();
+                parsed.log_level = Some(
+                    crate::logging::LogLevel::from_name(&name)
+                        .ok_or_else(|| anyhow::anyhow!("{}LOG_LEVEL={}: expected error, warn, info, debug or trace", ENV_PREFIX, name))?,
+                );
+            } else if let Some(value) = env("RUST_LOG") {
+                // Only a bare level; per-module filters are not supported
+                parsed.log_level = crate::logging::LogLevel::from_name(&value.to_string_lossy());
+            }
+        }
+        if let Some(value) = var("TRANSPORT", "transport") {
+            let transport = value.to_string_lossy();
+            parsed.transport = crate::server::Transport::from_str(transport.trim(), true)
+                .map_err(|e| anyhow::anyhow!("{}TRANSPORT={}: {}", ENV_PREFIX, transport, e))?;
+        }
+        if let Some(value) = var("HTTP_BIND", "http_bind") {
+            parsed.http_bind = parse_env("HTTP_BIND", value)?;
+        }
+        if let Some(value) = var("TCP_BIND", "tcp_bind") {
+            parsed.tcp_bind = parse_env("TCP_BIND", value)?;
+        }
+        if let Some(value) = var("SHUTDOWN_GRACE_SECS", "shutdown_grace_secs") {
+            parsed.shutdown_grace_secs = parse_env("SHUTDOWN_GRACE_SECS", value)?;
+        }
+        if let Some(value) = var("MAX_MESSAGE_BYTES", "max_message_bytes") {
+            parsed.max_message_bytes = parse_env("MAX_MESSAGE_BYTES", value)?;
+        }
+        if let Some(value) = var("SYMLINKS", "symlinks") {
+            let policy = value.to_string_lossy();
+            parsed.symlinks = crate::fs_service::symlinks::SymlinkPolicy::from_str(policy.trim(), true)
+                .map_err(|e| anyhow::anyhow!("{}SYMLINKS={}: {}", ENV_PREFIX, policy, e))?;
+        }
+        if let Some(value) = var("BACKUP", "backup") {
+            let mode = value.to_string_lossy();
+            parsed.backup = crate::fs_service::backup::BackupMode::from_str(mode.trim(), true)
+                .map_err(|e| anyhow::anyhow!("{}BACKUP={}: {}", ENV_PREFIX, mode, e))?;
+        }
+        if let Some(value) = var("UNDO_HISTORY", "undo_history") {
+            parsed.undo_history = parse_env("UNDO_HISTORY", value)?;
+        }
+        if let Some(value) = var("NO_TRASH", "no_trash") {
+            parsed.no_trash = parse_env_bool("NO_TRASH", value)?;
+        }
+        if let Some(value) = var("TRASH_DIR", "trash_dir") {
+            parsed.trash_dir = Some(PathBuf::from(value));
+        }
+        if let Some(value) = var("TOOL_STYLE", "tool_style") {
+            let style = value.to_string_lossy();
+            parsed.tool_style = crate::tools::ToolStyle::from_str(style.trim(), true)
+                .map_err(|e| anyhow::anyhow!("{}TOOL_STYLE={}: {}", ENV_PREFIX, style, e))?;
+        }
+        Ok(parsed)
+    }
+
+    /// The effective stderr verbosity
+    pub fn log_level(&self) -> crate::logging::LogLevel {
+        if self.quiet {
+            crate::logging::LogLevel::Error
+        } else {
+            self.log_level.unwrap_or_default()
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,156 @@
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// How long a request to the client may wait for its response. Sampling can
+/// involve the user approving the request, so this is generous.
+pub const CLIENT_REQUEST_TIMEOUT: Duration = Duration::from_secs(180);
+
+type Pending = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
+
+/// The connected MCP client, for requests flowing from server to client
+/// (sampling). Responses are matched to requests by id.
+#[derive(Default)]
+pub struct ClientPeer {
+    outgoing: Mutex<Option<mpsc::UnboundedSender<Value>>>,
+    pending: Mutex<Pending>,
+    next_id: AtomicU64,
+    capabilities: Mutex<HashMap<String, Value>>,
+    initialized: AtomicBool,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+impl ClientPeer {
+    /// Route outgoing messages to the transport
+    pub fn connect(&self, outgoing: mpsc::UnboundedSender<Value>) {
+        *lock(&self.outgoing) = Some(outgoing);
+    }
+
+    /// Drop the transport and fail every request still waiting for a response
+    pub fn disconnect(&self) {
+        *lock(&self.outgoing) = None;
+        for (_, waiter) in lock(&self.pending).drain() {
+            let _ = waiter.send(Err("client disconnected".to_string()));
+        }
+    }
+
+    /// `disconnect`, unless another transport session has connected since
+    /// `outgoing` did
+    pub fn disconnect_from(&self, outgoing: &mpsc::UnboundedSender<Value>) {
+        let current = lock(&self.outgoing).as_ref().is_some_and(|connected| connected.same_channel(outgoing));
+        if current {
+            self.disconnect();
+        }
+    }
+
+    /// Capabilities the client declared in `initialize`
+    pub fn set_capabilities(&self, capabilities: HashMap<String, Value>) {
+        *lock(&self.capabilities) = capabilities;
+        self.initialized.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the client has sent `initialize`, before which it expects no
+    /// notifications
+    pub fn is_initialized(&self) -> bool {
+        self.initialized.load(Ordering::Relaxed)
+    }
+
+    pub fn supports(&self, capability: &str) -> bool {
+        lock(&self.capabilities).contains_key(capability)
+    }
+
+    /// Send a notification to the client. Returns false when no client is
+    /// connected to receive it.
+    pub fn notify(&self, method: &str, params: Value) -> bool {
+        lock(&self.outgoing)
+            .as_ref()
+            .is_some_and(|outgoing| outgoing.send(json!({ "jsonrpc": "2.0", "method": method, "params": params })).is_ok())
+    }
+
+    /// Send a request to the client and wait for its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        // Distinct from client-chosen ids, which are usually small integers
+        let wire_id = format!("server-{}", id);
+        let (sender, receiver) = oneshot::channel();
+        lock(&self.pending).insert(id, sender);
+
+        let sent = lock(&self.outgoing).as_ref().is_some_and(|outgoing| {
+            outgoing
+                .send(json!({ "jsonrpc": "2.0", "id": wire_id, "method": method, "params": params }))
+                .is_ok()
+        });
+        if !sent {
+            lock(&self.pending).remove(&id);
+            return Err("no client is connected".to_string());
+        }
+
+        match tokio::time::timeout(CLIENT_REQUEST_TIMEOUT, receiver).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("client disconnected".to_string()),
+            Err(_) => {
+                lock(&self.pending).remove(&id);
+                Err(format!("client did not answer {} within {} s", method, CLIENT_REQUEST_TIMEOUT.as_secs()))
+            }
+        }
+    }
+
+    /// Deliver `message` to the request waiting for it. Returns false when it is
+    /// not a response to one of our requests.
+    pub fn handle_response(&self, message: &Value) -> bool {
+        if message.get("method").is_some() {
+            return false;
+        }
+        let Some(id) = message
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|id| id.strip_prefix("server-"))
+            .and_then(|id| id.parse::<u64>().ok())
+        else {
+            return false;
+        };
+        let Some(waiter) = lock(&self.pending).remove(&id) else {
+            // Late answer to a request that already timed out
+            return true;
+        };
+        let result = match (message.get("result"), message.get("error")) {
+            (Some(result), _) => Ok(result.clone()),
+            (None, Some(error)) => Err(error
+                .get("message")
+                .and_then(Value::as_str)
+                .unwrap_or("client returned an error")
+                .to_string()),
+            (None, None) => Err("client response had neither result nor error".to_string()),
+        };
+        let _ = waiter.send(result);
+        true
+    }
+
+    /// Ask the client's model for a completion via `sampling/createMessage`
+    pub async fn create_message(&self, system_prompt: &str, prompt: &str, max_tokens: u32) -> Result<String, String> {
+        if !self.supports("sampling") {
+            return Err("the client does not support sampling".to_string());
+        }
+        let result = self
+            .request(
+                "sampling/createMessage",
+                json!({
+                    "messages": [{ "role": "user", "content": { "type": "text", "text": prompt } }],
+                    "systemPrompt": system_prompt,
+                    "includeContext": "none",
+                    "maxTokens": max_tokens,
+                }),
+            )
+            .await?;
+        result["content"]["text"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "sampling result did not contain text".to_string())
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    cli::CommandArguments,
+    error::ServiceResult,
+    external_tools::ExternalToolConfig,
+    fs_service::{throttle, utils::format_bytes, FileSystemService},
+};
+
+/// The TOML file given with --config, e.g.
+///
+/// ```toml
+/// allowed_commands = ["cargo"]
+///
+/// [[tools]]
+/// name = "cargo_check"
+/// description = "Run cargo check in a crate"
+/// command = ["cargo", "check", "--message-format", "short"]
+/// working_dir = "{path}"
+/// path_arguments = ["path"]
+/// timeout_secs = 300
+/// input_schema = { type = "object", properties = { path = { type = "string" } }, required = ["path"] }
+///
+/// [policy]
+/// allowed_directories = ["~/projects", "/srv/shared"]
+/// blocked_directories = ["~/projects/secrets"]
+/// io_limit = 10485760
+/// max_read_bytes = 52428800
+/// readonly = false
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Programs external tools may run; each tool's `command[0]` must be listed
+    pub allowed_commands: Vec<String>,
+    /// Extra tools backed by external commands
+    pub tools: Vec<ExternalToolConfig>,
+    /// Security settings that can be reloaded without a restart
+    pub policy: PolicyConfig,
+}
+
+/// The `[policy]` table. Each setting given replaces its command-line value,
+/// and is re-read on SIGHUP or a reload_policy call.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PolicyConfig {
+    /// An empty list means unrestricted access
+    pub allowed_directories: Option<Vec<String>>,
+    pub blocked_directories: Option<Vec<String>>,
+    /// Bytes per second; 0 turns the limit off
+    pub io_limit: Option<u64>,
+    /// Largest file read whole; 0 turns the limit off
+    pub max_read_bytes: Option<u64>,
+    /// Refuse every tool call that would change files
+    pub readonly: Option<bool>,
+}
+
+impl ServerConfig {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        toml::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// The security policy in effect
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Policy {
+    pub allowed_directories: Vec<String>,
+    pub blocked_directories: Vec<String>,
+    pub io_limit: Option<u64>,
+    pub max_read_bytes: Option<u64>,
+    pub readonly: bool,
+}
+
+impl Policy {
+    /// Make `fs_service` and the I/O throttle follow this policy; `readonly`
+    /// is up to the caller, which checks it before running a tool. Nothing
+    /// changes when a pattern is invalid.
+    pub fn apply(&self, fs_service: &FileSystemService) -> ServiceResult<()> {
+        fs_service.set_directory_policy(&self.allowed_directories, &self.blocked_directories)?;
+        throttle::set_io_limit(self.io_limit);
+        fs_service.set_max_read_bytes(self.max_read_bytes);
+        Ok(())
+    }
+
+    /// One line for the log and the reload_policy result
+    pub fn summary(&self) -> String {
+        let list = |dirs: &[String], empty: &str| if dirs.is_empty() { empty.to_string() } else { dirs.join(", ") };
+        format!(
+            "{}; allowed directories: {}; blocked directories: {}; IO limit: {}; read limit: {}",
+            if self.readonly { "read-only" } else { "read/write" },
+            list(&self.allowed_directories, "ALL (unrestricted)"),
+            list(&self.blocked_directories, "NONE"),
+            match self.io_limit.filter(|limit| *limit > 0) {
+                Some(limit) => format!("{}/s", format_bytes(limit)),
+                None => "unlimited".to_string(),
+            },
+            match self.max_read_bytes.filter(|limit| *limit > 0) {
+                Some(limit) => format_bytes(limit),
+                None => "unlimited".to_string(),
+            }
+        )
+    }
+}
+
+/// Where the policy comes from: the command line, overridden by the
+/// `[policy]` table of the --config file
+#[derive(Debug, Clone, Default)]
+pub struct PolicySource {
+    config: Option<PathBuf>,
+    command_line: Policy,
+}
+
+impl PolicySource {
+    pub fn new(args: &CommandArguments) -> Self {
+        Self {
+            config: args.config.clone(),
+            command_line: Policy {
+                allowed_directories: args.allowed_directories.clone(),
+                blocked_directories: args.blocked_directories.clone(),
+                io_limit: args.io_limit,
+                max_read_bytes: args.max_read_bytes,
+                readonly: args.readonly,
+            },
+        }
+    }
+
+    /// The policy once `config`'s `[policy]` table is applied
+    pub fn resolve(&self, config: &ServerConfig) -> Policy {
+        let (command_line, table) = (self.command_line.clone(), &config.policy);
+        Policy {
+            allowed_directories: table.allowed_directories.clone().unwrap_or(command_line.allowed_directories),
+            blocked_directories: table.blocked_directories.clone().unwrap_or(command_line.blocked_directories),
+            io_limit: table.io_limit.or(command_line.io_limit),
+            max_read_bytes: table.max_read_bytes.or(command_line.max_read_bytes),
+            readonly: table.readonly.unwrap_or(command_line.readonly),
+        }
+    }
+
+    /// Re-read the config file and resolve the policy it gives now
+    pub fn reload(&self) -> io::Result<Policy> {
+        let Some(path) = &self.config else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "the server was started without --config, so there is no policy file to reload",
+            ));
+        };
+        Ok(self.resolve(&ServerConfig::load(path)?))
+    }
+}
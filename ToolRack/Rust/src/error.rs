@@ -1,4 +1,6 @@
 use thiserror::Error;
+
+use crate::fs_service::utils::format_bytes;
 pub type ServiceResult<T> = core::result::Result<T, ServiceError>;
 
 #[derive(Debug, Error)]
@@ -7,6 +9,18 @@ pub enum ServiceError {
     Io(#[from] std::io::Error),
     #[error("Path is outside allowed directories")]
     PathNotAllowed,
+    #[error("{0} is in a read-only directory")]
+    ReadOnlyPath(String),
+    #[error("{0} is a symbolic link, which the symlink policy ({1}) does not allow here")]
+    SymlinkRefused(String, &'static str),
+    #[error(
+        "{0} is {size}, limit is {limit} (--max-read-bytes); use read_file_lines or a byte-range read (read_file with offset_bytes and length_bytes)",
+        size = format_bytes(*.1),
+        limit = format_bytes(*.2)
+    )]
+    FileTooLarge(String, u64, u64),
+    #[error("{0} is binary ({1}), not text; use {2} to look at it")]
+    BinaryFile(String, String, String),
     #[error("Directory already exists")]
     DirectoryAlreadyExists,
     #[error("File not found: {0}")]
@@ -0,0 +1,274 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::Duration,
+};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    config::ServerConfig,
+    fs_service::FileSystemService,
+    mcp_types::{CallToolError, CallToolResult, Content, TextContent, Tool},
+    plugins::valid_tool_name,
+};
+
+/// Timeout for tools that do not set `timeout_secs`
+pub const DEFAULT_EXTERNAL_TOOL_TIMEOUT_SECS: u64 = 60;
+/// Output kept from a command's stdout and from its stderr
+const EXTERNAL_TOOL_MAX_OUTPUT: usize = 1024 * 1024;
+
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([A-Za-z_][A-Za-z0-9_]*)\}").unwrap());
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_EXTERNAL_TOOL_TIMEOUT_SECS
+}
+
+/// One `[[tools]]` entry of the --config file
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExternalToolConfig {
+    pub name: String,
+    pub description: Option<String>,
+    /// JSON Schema of the tool arguments; defaults to any object
+    pub input_schema: Option<Value>,
+    /// Program and arguments. A token that is exactly `{arg}` becomes the
+    /// argument's value (one token per element for arrays, none when the
+    /// argument is absent); `{arg}` inside a longer token is substituted in place.
+    pub command: Vec<String>,
+    /// Directory the command runs in, as a template; must be inside the allowed
+    /// directories. Defaults to the first allowed directory.
+    pub working_dir: Option<String>,
+    /// Arguments holding paths: checked against the allow/block rules and
+    /// passed on as absolute paths
+    #[serde(default)]
+    pub path_arguments: Vec<String>,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// An allow-listed external command exposed as an MCP tool
+#[derive(Debug, Clone)]
+pub struct ExternalTool {
+    config: ExternalToolConfig,
+}
+
+impl ExternalTool {
+    pub fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    pub fn tool_definition(&self) -> Tool {
+        Tool {
+            name: self.config.name.clone(),
+            description: self.config.description.clone(),
+            input_schema: self.config.input_schema.clone().unwrap_or_else(|| serde_json::json!({ "type": "object" })),
+            output_schema: None,
+        }
+    }
+
+    // The values an argument expands to, with path arguments resolved
+    async fn argument_values(
+        &self,
+        fs_service: &FileSystemService,
+        arguments: &Map<String, Value>,
+        name: &str,
+    ) -> Result<Vec<String>, CallToolError> {
+        let scalar = |value: &Value| match value {
+            Value::String(s) => Ok(s.clone()),
+            Value::Number(n) => Ok(n.to_string()),
+            Value::Bool(b) => Ok(b.to_string()),
+            _ => Err(CallToolError::new(format!("argument '{}' must be a string, number, boolean or array of them", name))),
+        };
+        let values = match arguments.get(name) {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Array(items)) => items.iter().map(scalar).collect::<Result<_, _>>()?,
+            Some(value) => vec![scalar(value)?],
+        };
+        if !self.config.path_arguments.iter().any(|arg| arg == name) {
+            return Ok(values);
+        }
+        let mut resolved = Vec::with_capacity(values.len());
+        for value in values {
+            let path = fs_service.validate_path(Path::new(&value)).await.map_err(CallToolError::new)?;
+            resolved.push(path.display().to_string());
+        }
+        Ok(resolved)
+    }
+
+    // Substitute every `{arg}` in a token that is not a placeholder by itself
+    async fn substitute(
+        &self,
+        fs_service: &FileSystemService,
+        arguments: &Map<String, Value>,
+        template: &str,
+    ) -> Result<String, CallToolError> {
+        let mut expanded = String::new();
+        let mut last = 0;
+        for captures in PLACEHOLDER.captures_iter(template) {
+            let (whole, name) = (captures.get(0).unwrap(), &captures[1]);
+            let mut values = self.argument_values(fs_service, arguments, name).await?;
+            if values.len() != 1 {
+                return Err(CallToolError::new(format!("argument '{}' is required and must be a single value", name)));
+            }
+            expanded.push_str(&template[last..whole.start()]);
+            expanded.push_str(&values.remove(0));
+            last = whole.end();
+        }
+        expanded.push_str(&template[last..]);
+        Ok(expanded)
+    }
+
+    async fn argv(&self, fs_service: &FileSystemService, arguments: &Map<String, Value>) -> Result<Vec<String>, CallToolError> {
+        let mut argv = vec![self.config.command[0].clone()];
+        for token in &self.config.command[1..] {
+            match PLACEHOLDER.captures(token).filter(|c| c[0].len() == token.len()) {
+                Some(captures) => {
+                    for value in self.argument_values(fs_service, arguments, &captures[1]).await? {
+                        // A value standing alone must not turn into an option of the command
+                        if value.starts_with('-') {
+                            return Err(CallToolError::new(format!("argument '{}' may not start with '-'", &captures[1])));
+                        }
+                        argv.push(value);
+                    }
+                }
+                None => argv.push(self.substitute(fs_service, arguments, token).await?),
+            }
+        }
+        Ok(argv)
+    }
+
+    async fn working_dir(&self, fs_service: &FileSystemService, arguments: &Map<String, Value>) -> Result<PathBuf, CallToolError> {
+        let requested = match &self.config.working_dir {
+            Some(template) => PathBuf::from(self.substitute(fs_service, arguments, template).await?),
+            None => fs_service.allowed_directories().first().cloned().ok_or_else(|| {
+                CallToolError::new(format!(
+                    "{} has no working_dir and the server has no allowed directories to default to",
+                    self.config.name
+                ))
+            })?,
+        };
+        let dir = fs_service.validate_path(&requested).await.map_err(CallToolError::new)?;
+        if !dir.is_dir() {
+            return Err(CallToolError::new(format!("working directory {} is not a directory", dir.display())));
+        }
+        Ok(dir)
+    }
+
+    /// Run the command with `arguments` filled into its template. Exit status 0
+    /// is a successful result; anything else is a tool error with both streams.
+    pub async fn call(&self, fs_service: &FileSystemService, arguments: Value) -> Result<CallToolResult, CallToolError> {
+        // The command writes to disk directly, out of reach of the overlay
+        fs_service.ensure_unsandboxed(&self.config.name).map_err(CallToolError::new)?;
+        let arguments = match arguments {
+            Value::Object(map) => map,
+            Value::Null => Map::new(),
+            _ => return Err(CallToolError::new("tool arguments must be an object")),
+        };
+        let argv = self.argv(fs_service, &arguments).await?;
+        let dir = self.working_dir(fs_service, &arguments).await?;
+
+        let child = tokio::process::Command::new(&argv[0])
+            .args(&argv[1..])
+            .current_dir(&dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| CallToolError::new(format!("Failed to start {}: {}", argv[0], e)))?;
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let output = tokio::time::timeout(timeout, child.wait_with_output())
+            .await
+            .map_err(|_| CallToolError::new(format!("{} timed out after {} seconds and was killed", self.config.name, self.config.timeout_secs)))?
+            .map_err(CallToolError::new)?;
+
+        let mut text = format!(
+            "$ {}\nWorking directory: {}\nExit status: {}\n",
+            argv.join(" "),
+            dir.display(),
+            output.status.code().map_or_else(|| "killed by signal".to_string(), |code| code.to_string())
+        );
+        for (label, stream) in [("stdout", &output.stdout), ("stderr", &output.stderr)] {
+            if stream.is_empty() {
+                continue;
+            }
+            let kept = &stream[..stream.len().min(EXTERNAL_TOOL_MAX_OUTPUT)];
+            text.push_str(&format!("\n--- {} ---\n{}", label, String::from_utf8_lossy(kept)));
+            if kept.len() < stream.len() {
+                text.push_str(&format!("\n[{} truncated: {} of {} bytes shown]", label, kept.len(), stream.len()));
+            }
+        }
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(!output.status.success()),
+            structured_content: None,
+        })
+    }
+}
+
+/// Tools declared in the `[[tools]]` tables of the --config file
+#[derive(Debug, Default)]
+pub struct ExternalToolRegistry {
+    tools: Vec<ExternalTool>,
+}
+
+impl ExternalToolRegistry {
+    /// Check every declared tool: a valid, unused name (not in `reserved`), a
+    /// program listed in `allowed_commands` and a positive timeout. Any bad
+    /// entry fails the whole config.
+    pub fn from_config(config: &ServerConfig, reserved: &[String]) -> io::Result<Self> {
+        let mut registry = Self::default();
+        for tool in &config.tools {
+            if !valid_tool_name(&tool.name) {
+                return Err(invalid(format!("'{}' is not a valid tool name", tool.name)));
+            }
+            if reserved.contains(&tool.name) || registry.get(&tool.name).is_some() {
+                return Err(invalid(format!("a tool named '{}' already exists", tool.name)));
+            }
+            let Some(program) = tool.command.first() else {
+                return Err(invalid(format!("{}: command is empty", tool.name)));
+            };
+            if !config.allowed_commands.contains(program) {
+                return Err(invalid(format!("{}: '{}' is not in allowed_commands", tool.name, program)));
+            }
+            if tool.timeout_secs == 0 {
+                return Err(invalid(format!("{}: timeout_secs must be at least 1", tool.name)));
+            }
+            registry.tools.push(ExternalTool { config: tool.clone() });
+        }
+        Ok(registry)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ExternalTool> {
+        self.tools.iter().find(|tool| tool.name() == name)
+    }
+
+    pub fn tools(&self) -> Vec<Tool> {
+        self.tools.iter().map(ExternalTool::tool_definition).collect()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.tools.iter().map(|tool| tool.name().to_string()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tools.is_empty()
+    }
+
+    /// Declared tools with their programs, e.g. `cargo_check (cargo)`
+    pub fn describe(&self) -> Vec<String> {
+        self.tools
+            .iter()
+            .map(|tool| format!("{} ({})", tool.config.name, tool.config.command[0]))
+            .collect()
+    }
+}
@@ -1,119 +1,729 @@
+pub mod audit;
+pub mod backup;
+pub mod batch;
+pub mod bulk_delete;
+pub mod cache;
+pub mod checksum;
+pub mod chunked;
+pub mod compare;
+pub mod concat;
+pub mod content_type;
+pub mod disk_space;
+pub mod download;
+pub mod duplicates;
+pub mod edits;
+pub mod encoding;
 pub mod file_info;
+pub mod hexdump;
+pub mod locks;
+// Library API for tests and embedders; the server binary never builds one
+#[allow(dead_code)]
+pub mod memory;
+pub mod merge;
+pub mod mmap;
+pub mod ops;
+pub mod parallel_walk;
+pub mod patch;
+pub mod patterns;
+pub mod permissions;
+pub mod pipeline;
+pub mod plan;
+pub mod profile;
+pub mod remote;
+pub mod rename;
+pub mod resources;
+pub mod roots;
+pub mod sandbox;
+pub mod scan;
+pub mod split;
+pub mod tags;
+pub mod staging;
+pub mod symlinks;
+pub mod temp;
+pub mod throttle;
+pub mod touch;
+pub mod trash;
+pub mod undo;
 pub mod utils;
-
-use file_info::FileInfo;
+pub mod walk;
+pub mod watch;
+pub mod wipe;
+
+use audit::{audit_entry, PermissionAudit};
+use bulk_delete::DeletePreview;
+use batch::{BatchJournal, BatchOperation, BatchReport, BatchStepReport, ValidatedOperation};
+use cache::MetadataCache;
+use chunked::{chunk_len, FileChunk, ReadCursor};
+use compare::{compare_trees, diff_texts, first_difference, CompareMode, DirectoryComparison, FileComparison};
+use checksum::{checksum_file, FileChecksum, HashAlgorithm};
+use content_type::{detect_content_type, looks_like_text, ContentType, SNIFF_BYTES};
+use edits::{apply_line_edit, replace_ignoring_whitespace, EditMatch, EditOutcome};
+use backup::BackupMode;
+use encoding::{DecodedText, TextEncoding};
+use trash::{DeleteMode, Trash};
+use undo::{UndoAction, UndoJournal, UndoStep};
+use patch::{apply_hunks, parse_patch, PatchOutcome};
+use disk_space::DiskSpace;
+use download::{DownloadPolicy, DownloadReport};
+use duplicates::{replace_with_link, verify_extra, DedupeAction, DedupePlan, DuplicateScan};
+use file_info::{count_lines, FileInfo, LinkTarget};
+use locks::LockManager;
+use merge::{ConflictStrategy, MergeAction, MergePlan};
+use parallel_walk::{build_tree, tree_size, TreeEntry, TreeWalkOptions};
+use patterns::PathPatterns;
+use permissions::{NormalizePermissionsOptions, PermissionChange, PermissionReport};
+use pipeline::targets_in;
+use plan::{Plan, PlanDocument};
+use profile::DirectoryProfile;
+use remote::{RemoteEntry, RemotePath, RemoteStorage};
+use rename::{RenamePlan, RenameRule, RENAME_MAX_FILES};
+use resources::{FileResource, FileResourceLink, ResourceBody};
+use roots::{file_uri, path_from_file_uri, Access, Root, RootsReport};
+use sandbox::{ChangeKind, Entry, Sandbox, SandboxChange, SandboxCommit, DELETED_FILES_LISTED};
+use scan::{FileQuery, FileRecord, FileSort, TimeField};
+use staging::{CommitAction, StageOutcome, StagedFile, StagingArea};
+use symlinks::{copy_link, find_symlink, symlink, LinkAction, LinkRules, SymlinkPolicy};
+use tags::{TagStore, TaggedPath};
+use temp::{TempArea, TempEntry, TempKind};
+use touch::{set_times, Touched};
+use walk::WalkFilter;
+use watch::{PathWatches, WatchPoll};
 
 use std::{
+    collections::{HashMap, HashSet},
     env,
     path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
 };
 
 use similar::TextDiff;
-use tokio::fs;
+use std::io::SeekFrom;
+use tokio::{
+    fs,
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt},
+};
+use rayon::prelude::*;
 use utils::{
-    expand_home, normalize_line_endings, normalize_path,
+    expand_home, file_identity, is_default_excluded, long_path, name_text, normalize_line_endings, normalize_path,
+    path_from_text, path_text, strip_verbatim, FileIdentity,
 };
+use serde::Serialize;
+use tokio::{sync::Semaphore, task::JoinSet};
 use walkdir::WalkDir;
 
 use crate::{
+    cancel,
     error::{ServiceError, ServiceResult},
-    tools::EditOperation,
+    logging::log_error,
+    metrics,
+    profiler::{record_phase, Phase},
+    tools::{EditKind, EditOperation},
 };
+use std::time::Instant;
+
+/// Maximum number of files read at the same time by multi-file operations
+const READ_CONCURRENCY: usize = 8;
+/// Caps for glob patterns expanded server-side in multi-file reads
+pub const GLOB_MAX_FILES: usize = 100;
+pub const GLOB_MAX_BYTES: u64 = 10 * 1024 * 1024;
+/// Default combined size budget for a single read_multiple_files call
+pub const READ_MULTIPLE_MAX_TOTAL_BYTES: u64 = 4 * 1024 * 1024;
+/// Files larger than this are never treated as whitespace-only by find_empty_files
+const WHITESPACE_SCAN_MAX_BYTES: u64 = 1024 * 1024;
+
+/// The allowed and blocked directories and patterns, replaced as a whole
+/// when the policy is reloaded
+#[derive(Debug, Clone, Default)]
+struct DirectoryPolicy {
+    allowed: Vec<PathBuf>,
+    /// The allowed directories given with a `:ro` suffix
+    read_only: Vec<PathBuf>,
+    blocked: Vec<PathBuf>,
+    allowed_patterns: PathPatterns,
+    blocked_patterns: PathPatterns,
+}
+
+impl DirectoryPolicy {
+    fn new(allowed_directories: &[String], blocked_directories: &[String]) -> ServiceResult<Self> {
+        // No allowed directories or patterns means unrestricted mode
+        let mut policy = Self::default();
+        let (allowed, allowed_patterns) = PathPatterns::partition(allowed_directories);
+        for entry in allowed {
+            let (dir, access) = Access::split(entry);
+            let dir = strip_verbatim(&expand_home(dir.into()));
+            if access == Access::ReadOnly {
+                policy.read_only.push(dir.clone());
+            }
+            policy.allowed.push(dir);
+        }
+        if let Some(pattern) = allowed_patterns.iter().find(|pattern| pattern.ends_with(":ro") || pattern.ends_with(":rw")) {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{}: `:ro` and `:rw` apply to directories, not patterns", pattern),
+            )));
+        }
+        policy.allowed_patterns = PathPatterns::new(&allowed_patterns)?;
+        let (blocked, blocked_patterns) = PathPatterns::partition(blocked_directories);
+        policy.blocked = blocked.into_iter().map(|dir| strip_verbatim(&expand_home(dir.into()))).collect();
+        policy.blocked_patterns = PathPatterns::new(&blocked_patterns)?;
+        Ok(policy)
+    }
+
+    fn check(&self, allowed: &[PathBuf], requested_path: &Path, follow_final: bool) -> ServiceResult<PathBuf> {
+        check_access(allowed, &self.blocked, &self.allowed_patterns, &self.blocked_patterns, requested_path, follow_final)
+    }
+
+    /// The access of the deepest allowed directory holding `path`, so a
+    /// read/write directory can sit inside a read-only one and the other way
+    /// round. Paths outside them (client roots, unrestricted mode) are
+    /// read/write.
+    fn access(&self, path: &Path) -> Access {
+        let normalized = normalize_path(path);
+        self.allowed
+            .iter()
+            .filter(|dir| normalized.starts_with(dir) || normalized.starts_with(normalize_path(dir)))
+            .max_by_key(|dir| dir.components().count())
+            .map_or(Access::ReadWrite, |dir| {
+                if self.read_only.contains(dir) { Access::ReadOnly } else { Access::ReadWrite }
+            })
+    }
+}
 
 pub struct FileSystemService {
-    allowed_path: Vec<PathBuf>,
-    blocked_path: Vec<PathBuf>,
+    policy: Arc<RwLock<DirectoryPolicy>>,
+    /// Roots the MCP client reported, allowed alongside the policy's directories
+    client_roots: Arc<RwLock<Vec<PathBuf>>>,
+    staging: StagingArea,
+    metadata_cache: MetadataCache,
+    download_policy: DownloadPolicy,
+    remote: RemoteStorage,
+    sandbox: Option<Arc<Sandbox>>,
+    locks: LockManager,
+    temp: TempArea,
+    tags: Arc<TagStore>,
+    /// Set on the throwaway views made by `dry_run_view`
+    dry_run: bool,
+    symlinks: SymlinkPolicy,
+    /// Largest file read whole (--max-read-bytes); 0 for no limit. Shared, as
+    /// reloading the policy changes it
+    max_read_bytes: Arc<AtomicU64>,
+    backups: BackupMode,
+    undo: Arc<UndoJournal>,
+    /// Where delete_file puts things; None deletes them outright
+    trash: Option<Trash>,
+    watches: Arc<PathWatches>,
 }
 
 impl FileSystemService {
     pub fn try_new(allowed_directories: &[String], blocked_directories: &[String]) -> ServiceResult<Self> {
-        let normalized_allowed_dirs: Vec<PathBuf> = if allowed_directories.is_empty() {
-            // If no allowed directories specified, allow all (unrestricted mode)
-            vec![]
-        } else {
-            allowed_directories
-                .iter()
-                .map(|dir| expand_home(dir.into()))
-                .collect()
-        };
+        let policy = DirectoryPolicy::new(allowed_directories, blocked_directories)?;
 
-        let normalized_blocked_dirs: Vec<PathBuf> = blocked_directories
-            .iter()
-            .map(|dir| expand_home(dir.into()))
-            .collect();
+        // Scratch space lives inside the policy so the other tools can use it
+        let scratch_root = match policy.allowed.first() {
+            Some(dir) => dir.join(temp::DEFAULT_SCRATCH_DIR_NAME),
+            None => env::temp_dir().join("aichemistforge-scratch"),
+        };
+        let locks = LockManager::default();
+        let temp = TempArea::new(scratch_root, locks.session());
 
         Ok(Self {
-            allowed_path: normalized_allowed_dirs,
-            blocked_path: normalized_blocked_dirs,
+            policy: Arc::new(RwLock::new(policy)),
+            client_roots: Arc::default(),
+            staging: StagingArea::default(),
+            metadata_cache: MetadataCache::default(),
+            download_policy: DownloadPolicy::default(),
+            remote: RemoteStorage::default(),
+            sandbox: None,
+            locks,
+            temp,
+            tags: Arc::new(TagStore::default()),
+            dry_run: false,
+            symlinks: SymlinkPolicy::default(),
+            max_read_bytes: Arc::default(),
+            backups: BackupMode::Off,
+            undo: Arc::default(),
+            trash: None,
+            watches: Arc::default(),
         })
     }
 
-    pub fn allowed_directories(&self) -> &Vec<PathBuf> {
-        &self.allowed_path
+    /// The allowlist: directories given on the command line, then the
+    /// client's roots. Empty means unrestricted.
+    pub fn allowed_directories(&self) -> Vec<PathBuf> {
+        let client_roots = self.client_roots.read().unwrap_or_else(|e| e.into_inner());
+        let configured = self.policy().allowed.clone();
+        let mut allowed = configured.clone();
+        allowed.extend(client_roots.iter().filter(|root| !configured.contains(root)).cloned());
+        allowed
     }
 
-    pub fn blocked_directories(&self) -> &Vec<PathBuf> {
-        &self.blocked_path
+    fn policy(&self) -> std::sync::RwLockReadGuard<'_, DirectoryPolicy> {
+        self.policy.read().unwrap_or_else(|e| e.into_inner())
     }
-}
 
-impl FileSystemService {
-    pub async fn validate_path(&self, requested_path: &Path) -> ServiceResult<PathBuf> {
-        // Expand ~ to home directory
-        let expanded_path = expand_home(requested_path.to_path_buf());
+    /// Replace the allowed and blocked directories given at startup, e.g. when
+    /// the policy is reloaded. Calls already past path validation finish
+    /// under the old policy; client roots are kept. An invalid pattern leaves
+    /// the current policy in place.
+    pub fn set_directory_policy(&self, allowed_directories: &[String], blocked_directories: &[String]) -> ServiceResult<()> {
+        let policy = DirectoryPolicy::new(allowed_directories, blocked_directories)?;
+        *self.policy.write().unwrap_or_else(|e| e.into_inner()) = policy;
+        Ok(())
+    }
 
-        // Resolve the absolute path
-        let absolute_path = if expanded_path.as_path().is_absolute() {
-            expanded_path.clone()
-        } else {
-            env::current_dir().unwrap().join(&expanded_path)
-        };
+    /// Allow the roots an MCP client reported (`roots/list`), replacing the
+    /// ones it reported before. When no directories were given on the command
+    /// line this turns unrestricted access into access to just these roots.
+    pub fn set_client_roots(&self, roots: Vec<PathBuf>) {
+        *self.client_roots.write().unwrap_or_else(|e| e.into_inner()) = roots;
+    }
+
+    pub fn blocked_directories(&self) -> Vec<PathBuf> {
+        self.policy().blocked.clone()
+    }
+
+    /// Allowed and blocked directories as MCP-style roots
+    pub fn roots(&self) -> RootsReport {
+        let allowed = self.allowed_directories();
+        let policy = self.policy();
+        let access = |dir: &PathBuf| if policy.read_only.contains(dir) { Access::ReadOnly } else { Access::ReadWrite };
+        RootsReport {
+            unrestricted: allowed.is_empty() && policy.allowed_patterns.is_empty(),
+            roots: allowed.iter().map(|p| Root::new(p).with_access(access(p))).collect(),
+            blocked: policy.blocked.iter().map(|p| Root::new(p)).collect(),
+            allowed_patterns: policy.allowed_patterns.patterns().to_vec(),
+            blocked_patterns: policy.blocked_patterns.patterns().to_vec(),
+        }
+    }
+
+    /// Reuse content hashes across scans (duplicate finder, compare, merge)
+    pub fn set_metadata_cache(&mut self, cache: MetadataCache) {
+        self.metadata_cache = cache;
+    }
+
+    pub fn metadata_cache(&self) -> &MetadataCache {
+        &self.metadata_cache
+    }
+
+    /// URLs download_file may fetch, and how much it may write
+    pub fn set_download_policy(&mut self, policy: DownloadPolicy) {
+        self.download_policy = policy;
+    }
+
+    pub fn download_policy(&self) -> &DownloadPolicy {
+        &self.download_policy
+    }
+
+    /// Stores that `s3://` and `sftp://` paths resolve to
+    pub fn set_remote_storage(&mut self, remote: RemoteStorage) {
+        self.remote = remote;
+    }
+
+    pub fn remote_storage(&self) -> &RemoteStorage {
+        &self.remote
+    }
+
+    /// Hold every change in `sandbox` until commit_sandbox instead of writing to disk
+    pub fn set_sandbox(&mut self, sandbox: Sandbox) {
+        self.sandbox = Some(Arc::new(sandbox));
+    }
+
+    pub fn sandbox(&self) -> Option<&Sandbox> {
+        self.sandbox.as_deref()
+    }
+
+    /// Make writes, edits, moves, copies and deletes honour other sessions' locks
+    pub fn set_respect_locks(&mut self, respect_locks: bool) {
+        self.locks.set_respect_locks(respect_locks);
+    }
+
+    pub fn locks(&self) -> &LockManager {
+        &self.locks
+    }
+
+    /// Put this session's temporary paths under `root` instead of the default
+    pub fn set_scratch_dir(&mut self, root: PathBuf) {
+        self.temp = TempArea::new(expand_home(root), self.locks.session());
+    }
+
+    pub fn scratch_dir(&self) -> &Path {
+        self.temp.root()
+    }
+
+    pub fn set_tag_store(&mut self, tags: TagStore) {
+        self.tags = Arc::new(tags);
+    }
+
+    pub fn tag_store(&self) -> &TagStore {
+        &self.tags
+    }
+
+    /// Whether paths may lead through symbolic links, and which (--symlinks)
+    pub fn set_symlink_policy(&mut self, policy: SymlinkPolicy) {
+        self.symlinks = policy;
+    }
 
-        // Normalize the path
-        let normalized_requested = normalize_path(&absolute_path);
+    pub fn symlink_policy(&self) -> SymlinkPolicy {
+        self.symlinks
+    }
+
+    /// Keep what writes, edits and deletes replace or remove (--backup)
+    pub fn set_backup_mode(&mut self, mode: BackupMode) {
+        self.backups = mode;
+    }
+
+    pub fn backup_mode(&self) -> BackupMode {
+        self.backups
+    }
+
+    // Keep what is at `path` as --backup says before a write, edit or delete
+    // replaces it; with `remove` it is moved aside instead. Sandboxed changes
+    // are not real yet, so nothing is kept for them.
+    async fn back_up(&self, path: &Path, remove: bool) -> ServiceResult<Option<PathBuf>> {
+        if self.sandbox.is_some() {
+            return Ok(None);
+        }
+        backup::back_up(path, self.backups, remove).await.map_err(|e| with_path(path, e))
+    }
+
+    /// How many writes, edits, moves and deletes can be undone (--undo-history); 0 keeps none
+    pub fn set_undo_history(&self, steps: usize) {
+        self.undo.set_capacity(steps);
+    }
+
+    pub fn undo_journal(&self) -> &UndoJournal {
+        &self.undo
+    }
+
+    /// Send deleted items to `trash` rather than removing them; None deletes outright
+    pub fn set_trash(&mut self, trash: Option<Trash>) {
+        self.trash = trash;
+    }
+
+    pub fn trash(&self) -> Option<&Trash> {
+        self.trash.as_ref()
+    }
 
-        // Check if path is in blocked directories first
-        if !self.blocked_path.is_empty() {
-            for blocked_dir in &self.blocked_path {
-                if normalized_requested.starts_with(blocked_dir)
-                    || normalized_requested.starts_with(&normalize_path(blocked_dir)) {
-                    return Err(ServiceError::PathNotAllowed);
+    // Take back the newest journal step, after checking its paths against the
+    // policy in effect now
+    pub async fn undo_last_operation(&self) -> ServiceResult<Option<UndoStep>> {
+        self.ensure_unsandboxed("undo")?;
+        let Some(step) = self.undo.history().pop() else {
+            return Ok(None);
+        };
+        for path in step.action.targets() {
+            let valid_path = self.validate_path(path).await?;
+            self.check_writable(&valid_path)?;
+        }
+        Ok(self.undo.undo_last().await?)
+    }
+
+    // Take back every journal step newer than `step`, newest first, stopping
+    // at the first that fails
+    pub async fn undo_to_step(&self, step: u64) -> ServiceResult<Vec<UndoStep>> {
+        let mut undone = Vec::new();
+        while self.undo.last_step().is_some_and(|last| last > step) {
+            match self.undo_last_operation().await {
+                Ok(Some(done)) => undone.push(done),
+                Ok(None) => break,
+                Err(e) if undone.is_empty() => return Err(e),
+                Err(e) => {
+                    let steps: Vec<String> = undone.iter().map(|done| done.step.to_string()).collect();
+                    return Err(ServiceError::Io(std::io::Error::other(format!(
+                        "undid steps {} and then stopped: {}",
+                        steps.join(", "),
+                        e
+                    ))));
                 }
             }
         }
+        Ok(undone)
+    }
+
+    /// Refuse to read files larger than `limit` whole; None or 0 lifts the limit
+    pub fn set_max_read_bytes(&self, limit: Option<u64>) {
+        self.max_read_bytes.store(limit.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn max_read_bytes(&self) -> Option<u64> {
+        Some(self.max_read_bytes.load(Ordering::Relaxed)).filter(|limit| *limit > 0)
+    }
+
+    /// Err when a file of `size` bytes is over --max-read-bytes, before any of
+    /// it is read. Byte-range and line reads are not limited.
+    pub fn check_read_size(&self, path: &Path, size: u64) -> ServiceResult<()> {
+        match self.max_read_bytes() {
+            Some(limit) if size > limit => Err(ServiceError::FileTooLarge(path.display().to_string(), size, limit)),
+            _ => Ok(()),
+        }
+    }
+
+    /// How tree walks and recursive copies treat the links they meet, judged
+    /// against the policy in effect now
+    pub fn link_rules(&self) -> LinkRules {
+        let policy = self.policy().clone();
+        let allowed = self.allowed_directories();
+        LinkRules::new(self.symlinks, move |link| policy.check(&allowed, link, true).is_ok())
+    }
+
+    /// A copy of this service whose changes go to a fresh sandbox of its own,
+    /// for running one call in dry-run mode: diff_sandbox on the copy is what
+    /// the call would have done, and dropping it discards everything. Staged
+    /// paths are copied too, so staging in a dry run does not stick either.
+    pub fn dry_run_view(&self) -> ServiceResult<Self> {
+        Ok(Self {
+            policy: self.policy.clone(),
+            client_roots: self.client_roots.clone(),
+            staging: self.staging.snapshot(),
+            metadata_cache: self.metadata_cache.clone(),
+            download_policy: self.download_policy.clone(),
+            remote: self.remote.clone(),
+            sandbox: Some(Arc::new(Sandbox::create().map_err(ServiceError::Io)?)),
+            locks: self.locks.clone(),
+            temp: self.temp.clone(),
+            tags: self.tags.clone(),
+            dry_run: true,
+            symlinks: self.symlinks,
+            max_read_bytes: self.max_read_bytes.clone(),
+            backups: self.backups,
+            undo: self.undo.clone(),
+            trash: self.trash.clone(),
+            watches: self.watches.clone(),
+        })
+    }
+}
+
+/// Resolve `requested_path` to an absolute path and check it against the
+/// blocklist, then the allowlist (an empty allowlist allows everything)
+pub(crate) fn check_access(
+    allowed_path: &[PathBuf],
+    blocked_path: &[PathBuf],
+    allowed_patterns: &PathPatterns,
+    blocked_patterns: &PathPatterns,
+    requested_path: &Path,
+    follow_final: bool,
+) -> ServiceResult<PathBuf> {
+    // Expand ~ to home directory; a `file://` URI stands for a name path_text encoded
+    let expanded_path = expand_home(path_from_text(requested_path));
+
+    // Resolve the absolute path
+    let absolute_path = if expanded_path.as_path().is_absolute() {
+        expanded_path.clone()
+    } else {
+        env::current_dir().unwrap().join(&expanded_path)
+    };
+
+    // Follow symlinks and `..` first, so a link inside an allowed directory
+    // cannot lead outside it, even to a file that does not exist yet. Without
+    // `follow_final` a link is judged by where it is rather than where it points.
+    let normalized_requested = match (follow_final, absolute_path.parent(), absolute_path.file_name()) {
+        (false, Some(parent), Some(name)) => normalize_path(parent).join(name),
+        _ => normalize_path(&absolute_path),
+    };
+
+    // Check if path is in blocked directories first
+    if !blocked_path.is_empty() {
+        for blocked_dir in blocked_path {
+            if normalized_requested.starts_with(blocked_dir)
+                || normalized_requested.starts_with(&normalize_path(blocked_dir)) {
+                return Err(ServiceError::PathNotAllowed);
+            }
+        }
+    }
+    if blocked_patterns.matches(&normalized_requested) || blocked_patterns.matches(&absolute_path) {
+        return Err(ServiceError::PathNotAllowed);
+    }
+
+    // If there is no allowlist, allow access (unrestricted mode)
+    if allowed_path.is_empty() && allowed_patterns.is_empty() {
+        return Ok(absolute_path);
+    }
+
+    // Otherwise, check allowlist as before
+    if !allowed_path.iter().any(|dir| {
+        normalized_requested.starts_with(dir)
+            || normalized_requested.starts_with(&normalize_path(dir))
+    }) && !allowed_patterns.matches(&normalized_requested) {
+        return Err(ServiceError::PathNotAllowed);
+    }
+
+    Ok(absolute_path)
+}
+
+impl FileSystemService {
+    /// Check `requested_path` against the policy and return it absolute; on
+    /// Windows a path of MAX_PATH or more comes back with the `\\?\` prefix
+    pub async fn validate_path(&self, requested_path: &Path) -> ServiceResult<PathBuf> {
+        let started = Instant::now();
+        let result = self.check_path(requested_path, true).and_then(|path| self.check_links(&path, false).map(|()| long_path(&path)));
+        record_phase(Phase::Validation, started.elapsed());
+        result
+    }
+
+    /// validate_path for operations that act on a link itself rather than what
+    /// it points to (delete, move, inspect): with --symlinks no-follow the
+    /// path may be a link, checked where it is, not where it leads
+    pub async fn validate_link_path(&self, requested_path: &Path) -> ServiceResult<PathBuf> {
+        let started = Instant::now();
+        let follow_final = self.symlinks == SymlinkPolicy::FollowWithinRoot;
+        let result = self.check_path(requested_path, follow_final).and_then(|path| self.check_links(&path, true).map(|()| long_path(&path)));
+        record_phase(Phase::Validation, started.elapsed());
+        result
+    }
 
-        // If allowed_directories is empty, allow access (unrestricted mode)
-        if self.allowed_path.is_empty() {
-            return Ok(absolute_path);
+    fn check_path(&self, requested_path: &Path, follow_final: bool) -> ServiceResult<PathBuf> {
+        if self.client_roots.read().unwrap_or_else(|e| e.into_inner()).is_empty() {
+            let policy = self.policy();
+            return policy.check(&policy.allowed, requested_path, follow_final);
         }
+        self.policy().check(&self.allowed_directories(), requested_path, follow_final)
+    }
 
-        // Otherwise, check allowlist as before
-        if !self.allowed_path.iter().any(|dir| {
-            normalized_requested.starts_with(dir)
-                || normalized_requested.starts_with(&normalize_path(dir))
-        }) {
-            return Err(ServiceError::PathNotAllowed);
+    // Under deny and no-follow, refuse paths through links below their allowed
+    // directory; `link_ok` lets no-follow accept a link as the last component.
+    // Following within the root needs nothing more: check_access judged the target.
+    fn check_links(&self, path: &Path, link_ok: bool) -> ServiceResult<()> {
+        if self.symlinks == SymlinkPolicy::FollowWithinRoot {
+            return Ok(());
+        }
+        let base = self
+            .allowed_directories()
+            .into_iter()
+            .filter(|dir| path.starts_with(dir))
+            .max_by_key(|dir| dir.components().count());
+        let mut link = find_symlink(path, base.as_deref());
+        if link_ok && self.symlinks == SymlinkPolicy::NoFollow && link.as_deref() == Some(path) {
+            link = path.parent().and_then(|parent| find_symlink(parent, base.as_deref()));
+        }
+        match link {
+            Some(link) => Err(ServiceError::SymlinkRefused(link.display().to_string(), self.symlinks.name())),
+            None => Ok(()),
         }
+    }
+
+    /// validate_path, plus what may be done at the path: read-only inside
+    /// directories allowed with `:ro`. Library API; the tools call
+    /// check_writable before changing a path
+    #[allow(dead_code)]
+    pub async fn validate_path_access(&self, requested_path: &Path) -> ServiceResult<(PathBuf, Access)> {
+        let path = self.validate_path(requested_path).await?;
+        let access = self.policy().access(&path);
+        Ok((path, access))
+    }
 
-        Ok(absolute_path)
+    /// Refuse to change `valid_path` when it is in a read-only directory or,
+    /// with --respect-locks, locked by another session
+    pub fn check_writable(&self, valid_path: &Path) -> ServiceResult<()> {
+        if self.policy().access(valid_path) == Access::ReadOnly {
+            return Err(ServiceError::ReadOnlyPath(valid_path.display().to_string()));
+        }
+        Ok(self.locks.check_writable(valid_path)?)
     }
 
     // Separate validation for paths that must exist
     pub async fn validate_existing_path(&self, requested_path: &Path) -> ServiceResult<PathBuf> {
         let path = self.validate_path(requested_path).await?;
+        self.check_exists(path, false)
+    }
+
+    /// validate_link_path for paths that must exist; a dangling link counts
+    pub async fn validate_existing_link_path(&self, requested_path: &Path) -> ServiceResult<PathBuf> {
+        let path = self.validate_link_path(requested_path).await?;
+        self.check_exists(path, true)
+    }
 
-        if !path.exists() {
+    fn check_exists(&self, path: PathBuf, dangling_ok: bool) -> ServiceResult<PathBuf> {
+        let started = Instant::now();
+        let exists = match &self.sandbox {
+            Some(sandbox) => sandbox.lookup(&path) != Entry::Missing,
+            None if dangling_ok => std::fs::symlink_metadata(&path).is_ok(),
+            None => path.exists(),
+        };
+        record_phase(Phase::Validation, started.elapsed());
+        if !exists {
             return Err(ServiceError::FileNotFound(path.display().to_string()));
         }
 
         Ok(path)
     }
 
-    // Get file stats
-    pub async fn get_file_stats(&self, file_path: &Path) -> ServiceResult<FileInfo> {
-        let valid_path = self.validate_existing_path(file_path).await?;
+    // Where the current contents of a validated path are: its overlay file when
+    // the sandbox holds a change for it, otherwise the path itself
+    fn contents_path(&self, valid_path: PathBuf) -> PathBuf {
+        let Some(sandbox) = &self.sandbox else {
+            return valid_path;
+        };
+        match sandbox.lookup(&valid_path) {
+            Entry::File(contents) => contents,
+            // Directories that exist only in the sandbox report the overlay's metadata
+            Entry::Directory if !valid_path.is_dir() => sandbox.overlay_dir().to_path_buf(),
+            _ => valid_path,
+        }
+    }
+
+    // Operations that write to disk without going through the sandbox are refused in
+    // --sandbox mode, and in dry-run mode since there is no way to preview them
+    pub(crate) fn ensure_unsandboxed(&self, operation: &str) -> ServiceResult<()> {
+        if self.dry_run {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("{} writes directly to disk and cannot be previewed in dry-run mode", operation),
+            )));
+        }
+        if self.sandbox.is_some() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("{} writes directly to disk and is not available in --sandbox mode", operation),
+            )));
+        }
+        Ok(())
+    }
+
+    // The sandbox, or an error explaining that the server is not running with one
+    fn require_sandbox(&self) -> ServiceResult<Arc<Sandbox>> {
+        self.sandbox.clone().ok_or_else(|| {
+            ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "The server is not running in --sandbox mode",
+            ))
+        })
+    }
+
+    // The remote store serving an `s3://` or `sftp://` path, or None for local paths
+    fn remote_path(&self, path: &Path, write: bool) -> Option<ServiceResult<RemotePath>> {
+        let resolved = self.remote.resolve(path, write)?;
+        if write {
+            if let Err(e) = self.ensure_unsandboxed("Writing to remote storage") {
+                return Some(Err(e));
+            }
+        }
+        Some(resolved.map_err(ServiceError::Io))
+    }
+
+    // Get file stats. Symlinks are reported as links, with their raw target and
+    // whether it leads inside the allowed directories, unless follow_symlinks is set.
+    pub async fn get_file_stats(&self, file_path: &Path, follow_symlinks: bool) -> ServiceResult<FileInfo> {
+        let valid_path = if follow_symlinks {
+            self.validate_existing_path(file_path).await?
+        } else {
+            // Inspecting a link reads nothing through it, so it is checked
+            // where it is even when its target is outside the allowed directories
+            let path = self.check_path(file_path, false)?;
+            self.check_links(&path, true)?;
+            self.check_exists(long_path(&path), true)?
+        };
+        let valid_path = self.contents_path(valid_path);
 
-        match fs::metadata(&valid_path).await {
+        let metadata = if follow_symlinks {
+            fs::metadata(&valid_path).await
+        } else {
+            fs::symlink_metadata(&valid_path).await
+        };
+
+        match metadata {
             Ok(metadata) => {
                 let size = metadata.len();
                 let created = metadata.created().ok();
@@ -121,6 +731,15 @@ impl FileSystemService {
                 let accessed = metadata.accessed().ok();
                 let is_directory = metadata.is_dir();
                 let is_file = metadata.is_file();
+                let is_symlink = metadata.file_type().is_symlink();
+                let link_target = match is_symlink.then(|| std::fs::read_link(&valid_path)) {
+                    Some(Ok(target)) => {
+                        let resolved = valid_path.parent().map_or_else(|| target.clone(), |parent| parent.join(&target));
+                        let within_allowed = self.validate_path(&resolved).await.is_ok();
+                        Some(LinkTarget { target, within_allowed })
+                    }
+                    _ => None,
+                };
 
                 Ok(FileInfo {
                     size,
@@ -129,6 +748,8 @@ impl FileSystemService {
                     accessed,
                     is_directory,
                     is_file,
+                    is_symlink,
+                    link_target,
                     metadata,
                 })
             },
@@ -141,75 +762,157 @@ impl FileSystemService {
         }
     }
 
-    fn detect_line_ending(&self, text: &str) -> &str {
-        if text.contains("\r\n") {
-            "\r\n"
-        } else if text.contains('\r') {
-            "\r"
-        } else {
-            "\n"
-        }
-    }
-
-    pub async fn read_file(&self, file_path: &Path) -> ServiceResult<String> {
+    // Sniff MIME type and text/binary classification from the file header.
+    // Returns None for directories.
+    pub async fn get_content_type(&self, file_path: &Path) -> ServiceResult<Option<ContentType>> {
         let valid_path = self.validate_existing_path(file_path).await?;
+        let contents = self.contents_path(valid_path.clone());
+
+        if contents.is_dir() {
+            return Ok(None);
+        }
 
-        match tokio::fs::read_to_string(valid_path).await {
-            Ok(content) => Ok(content),
+        let file = match fs::File::open(&contents).await {
+            Ok(file) => file,
             Err(e) => {
-                match e.kind() {
+                return match e.kind() {
                     std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
                     _ => Err(ServiceError::Io(e)),
                 }
             }
+        };
+
+        let mut sample = Vec::with_capacity(SNIFF_BYTES);
+        file.take(SNIFF_BYTES as u64).read_to_end(&mut sample).await?;
+
+        Ok(Some(detect_content_type(&valid_path, &sample)))
+    }
+
+    // Hex digest of a file's content. SHA-256 comes from the metadata cache
+    // while the file is unchanged, when the cache is enabled.
+    pub async fn checksum(&self, path: &Path, algorithm: HashAlgorithm) -> ServiceResult<String> {
+        let valid_path = self.contents_path(self.validate_existing_path(path).await?);
+        let metadata = fs::metadata(&valid_path).await?;
+        if !metadata.is_file() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a file", path.display()),
+            )));
         }
+        let cache = self.metadata_cache.clone();
+        tokio::task::spawn_blocking(move || match algorithm {
+            HashAlgorithm::Sha256 => cache.hash_file(&valid_path, &metadata),
+            _ => checksum_file(&valid_path, algorithm),
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+        .map_err(ServiceError::Io)
     }
 
-    pub async fn create_directory(&self, file_path: &Path) -> ServiceResult<()> {
-        let valid_path = self.validate_path(file_path).await?;
+    // Lines in a text file, streamed rather than read whole
+    pub async fn line_count(&self, path: &Path) -> ServiceResult<u64> {
+        let valid_path = self.contents_path(self.validate_existing_path(path).await?);
+        tokio::task::spawn_blocking(move || count_lines(&valid_path))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+            .map_err(ServiceError::Io)
+    }
 
-        // Check if directory already exists
-        if valid_path.exists() && valid_path.is_dir() {
-            return Err(ServiceError::DirectoryAlreadyExists);
+    // checksum for each of `paths` in order; a file that cannot be hashed is
+    // reported in its entry rather than failing the rest
+    pub async fn checksum_files(&self, paths: &[String], algorithm: HashAlgorithm) -> Vec<FileChecksum> {
+        let mut results = Vec::with_capacity(paths.len());
+        for path in paths {
+            let (checksum, error) = match self.checksum(Path::new(path), algorithm).await {
+                Ok(checksum) => (Some(checksum), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            results.push(FileChecksum { path: path.clone(), checksum, error });
         }
+        results
+    }
 
-        match tokio::fs::create_dir_all(&valid_path).await {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                match e.kind() {
-                    std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
-                    _ => Err(ServiceError::Io(e)),
-                }
-            }
-        }
+    // The validated path a `file://` URI names, which may not exist
+    pub async fn resource_path(&self, uri: &str) -> ServiceResult<PathBuf> {
+        let path = path_from_file_uri(uri).ok_or_else(|| {
+            ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Not a local file URI: {}", uri),
+            ))
+        })?;
+        self.validate_path(&path).await
     }
 
-    pub async fn move_file(&self, src_path: &Path, dest_path: &Path) -> ServiceResult<()> {
-        let valid_src_path = self.validate_existing_path(src_path).await?;
-        let valid_dest_path = self.validate_path(dest_path).await?;
+    // Read a file named by a `file://` URI (the resource template). The path goes
+    // through the same validation as every tool; text comes back as text and
+    // everything else base64-encoded.
+    pub async fn read_resource(&self, uri: &str) -> ServiceResult<FileResource> {
+        let valid_path = self.validate_existing_path(&self.resource_path(uri).await?).await?;
+        let contents = self.contents_path(valid_path.clone());
+        if contents.is_dir() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is a directory; use list_directory to see its contents", valid_path.display()),
+            )));
+        }
 
-        match tokio::fs::rename(&valid_src_path, &valid_dest_path).await {
-            Ok(_) => Ok(()),
+        let data = match fs::read(&contents).await {
+            Ok(data) => data,
             Err(e) => {
-                match e.kind() {
+                return match e.kind() {
                     std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
                     _ => Err(ServiceError::Io(e)),
                 }
             }
-        }
+        };
+        let content_type = detect_content_type(&valid_path, &data[..data.len().min(SNIFF_BYTES)]);
+        let body = match String::from_utf8(data) {
+            Ok(text) if content_type.is_text => ResourceBody::Text(text),
+            Ok(text) => ResourceBody::Blob(base64::Engine::encode(&base64::engine::general_purpose::STANDARD, text)),
+            Err(e) => ResourceBody::Blob(base64::Engine::encode(&base64::engine::general_purpose::STANDARD, e.into_bytes())),
+        };
+
+        Ok(FileResource {
+            uri: file_uri(&valid_path),
+            mime_type: content_type.mime_type,
+            body,
+        })
     }
 
-    pub async fn list_directory(&self, dir_path: &Path) -> ServiceResult<Vec<tokio::fs::DirEntry>> {
-        let valid_path = self.validate_existing_path(dir_path).await?;
+    // Describe a file as a resource link, for results too large to return inline
+    pub async fn resource_link(&self, file_path: &Path) -> ServiceResult<FileResourceLink> {
+        let stats = self.get_file_stats(file_path, true).await?;
+        let content_type = self.get_content_type(file_path).await?;
+        let valid_path = self.validate_path(file_path).await?;
+        Ok(FileResourceLink {
+            uri: file_uri(&valid_path),
+            name: valid_path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| valid_path.display().to_string()),
+            mime_type: content_type.map_or_else(|| "inode/directory".to_string(), |c| c.mime_type),
+            size: stats.size,
+        })
+    }
 
-        match tokio::fs::read_dir(valid_path).await {
-            Ok(mut dir) => {
-                let mut entries = Vec::new();
-                while let Some(entry) = dir.next_entry().await? {
-                    entries.push(entry);
-                }
-                Ok(entries)
-            },
+    // Get capacity of the filesystem containing the path. The path itself does not
+    // need to exist yet (e.g. a copy destination); its deepest existing ancestor is used.
+    pub async fn get_disk_space(&self, path: &Path) -> ServiceResult<DiskSpace> {
+        let valid_path = self.validate_path(path).await?;
+
+        let queried_path = valid_path
+            .ancestors()
+            .find(|p| p.exists())
+            .map(Path::to_path_buf)
+            .ok_or_else(|| ServiceError::FileNotFound(valid_path.display().to_string()))?;
+
+        match fs4::statvfs(&queried_path) {
+            Ok(stats) => Ok(DiskSpace {
+                queried_path,
+                total: stats.total_space(),
+                free: stats.free_space(),
+                available: stats.available_space(),
+            }),
             Err(e) => {
                 match e.kind() {
                     std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
@@ -219,11 +922,45 @@ impl FileSystemService {
         }
     }
 
-    pub async fn write_file(&self, file_path: &Path, content: &String) -> ServiceResult<()> {
-        let valid_path = self.validate_path(file_path).await?;
+    fn detect_line_ending(&self, text: &str) -> &str {
+        if text.contains("\r\n") {
+            "\r\n"
+        } else if text.contains('\r') {
+            "\r"
+        } else {
+            "\n"
+        }
+    }
 
-        match tokio::fs::write(&valid_path, content).await {
-            Ok(_) => Ok(()),
+    pub async fn read_file(&self, file_path: &Path) -> ServiceResult<String> {
+        decode_text(file_path, self.read_file_bytes(file_path).await?)
+    }
+
+    // Read a text file stored in `encoding`, e.g. UTF-16 written by a Windows
+    // tool or a legacy code page
+    pub async fn read_file_as(&self, file_path: &Path, encoding: TextEncoding) -> ServiceResult<DecodedText> {
+        let data = self.read_file_bytes(file_path).await?;
+        encoding::decode(&data, encoding).map_err(|e| with_path(file_path, e))
+    }
+
+    // The whole of a local or remote file, within --max-read-bytes
+    async fn read_file_bytes(&self, file_path: &Path) -> ServiceResult<Vec<u8>> {
+        if let Some(remote) = self.remote_path(file_path, false) {
+            let remote = remote?;
+            let data = remote.backend.read(&remote.key).await?;
+            metrics::record_read(data.len() as u64);
+            return Ok(data);
+        }
+        let valid_path = self.contents_path(self.validate_existing_path(file_path).await?);
+        if let Ok(metadata) = fs::metadata(&valid_path).await {
+            self.check_read_size(file_path, metadata.len())?;
+        }
+
+        match tokio::fs::read(&valid_path).await {
+            Ok(data) => {
+                metrics::record_read(data.len() as u64);
+                Ok(data)
+            }
             Err(e) => {
                 match e.kind() {
                     std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
@@ -233,56 +970,862 @@ impl FileSystemService {
         }
     }
 
-    pub async fn search_files(&self, directory: &Path, pattern: &str, include_content: bool) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
-        let valid_path = self.validate_existing_path(directory).await?;
-        let mut results = Vec::new();
-        let pattern_lower = pattern.to_lowercase();
-
-        fn search_recursive(
-            dir: &Path,
-            pattern: &str,
-            include_content: bool,
-            results: &mut Vec<String>,
-        ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-            for entry in std::fs::read_dir(dir)? {
-                let entry = entry?;
-                let path = entry.path();
-
-                if path.is_dir() {
-                    // Recursively search subdirectories
-                    search_recursive(&path, pattern, include_content, results)?;
-                } else if path.is_file() {
-                    let file_name = path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("")
-                        .to_lowercase();
-
-                    let mut matches = false;
+    // Expand glob patterns (e.g. `src/**/*.rs`) in a list of paths. Plain paths are kept
+    // as-is; matches are sorted, limited to allowed files, and capped by count and total size.
+    pub async fn expand_path_patterns(&self, paths: &[String]) -> ServiceResult<PathExpansion> {
+        self.expand_path_patterns_within(paths, GLOB_MAX_FILES, GLOB_MAX_BYTES).await
+    }
 
-                    // Check filename match
-                    if file_name.contains(pattern) {
-                        matches = true;
-                    }
+    // expand_path_patterns with caps chosen by the caller
+    async fn expand_path_patterns_within(&self, paths: &[String], max_files: usize, max_bytes: u64) -> ServiceResult<PathExpansion> {
+        let mut expansion = PathExpansion::default();
+        let mut matched_files = 0;
+        let mut matched_bytes: u64 = 0;
+        let mut capped = false;
 
-                    // Check content match if requested
-                    if include_content && !matches {
-                        if let Ok(content) = std::fs::read_to_string(&path) {
-                            if content.to_lowercase().contains(pattern) {
-                                matches = true;
-                            }
-                        }
-                    }
+        for pattern in paths {
+            if !utils::is_glob_pattern(pattern) {
+                expansion.paths.push(pattern.clone());
+                continue;
+            }
+            if capped {
+                expansion.notes.push(format!("Skipped '{}': expansion limit already reached", pattern));
+                continue;
+            }
 
-                    if matches {
-                        results.push(path.to_string_lossy().to_string());
-                    }
+            let absolute_pattern = if Path::new(pattern).is_absolute() {
+                pattern.clone()
+            } else {
+                env::current_dir()?.join(pattern).display().to_string()
+            };
+            let entries = glob::glob(&absolute_pattern).map_err(|e| {
+                ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid glob pattern '{}': {}", pattern, e),
+                ))
+            })?;
+
+            let mut matches: Vec<PathBuf> = entries.filter_map(|e| e.ok()).filter(|p| p.is_file()).collect();
+            matches.sort();
+
+            let mut pattern_matched = 0;
+            for path in matches {
+                if self.validate_path(&path).await.is_err() {
+                    continue;
+                }
+                let size = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                if matched_files >= max_files {
+                    expansion.notes.push(format!(
+                        "Stopped expanding '{}': reached the limit of {} files",
+                        pattern, max_files
+                    ));
+                    capped = true;
+                    break;
                 }
+                if matched_bytes.saturating_add(size) > max_bytes {
+                    expansion.notes.push(format!(
+                        "Stopped expanding '{}': matched files exceed {} in total",
+                        pattern,
+                        utils::format_bytes(max_bytes)
+                    ));
+                    capped = true;
+                    break;
+                }
+                matched_files += 1;
+                matched_bytes += size;
+                pattern_matched += 1;
+                expansion.paths.push(path_text(&path));
+            }
+
+            if pattern_matched == 0 && !capped {
+                expansion.notes.push(format!("Pattern '{}' matched no files", pattern));
             }
-            Ok(())
         }
 
-        search_recursive(&valid_path, &pattern_lower, include_content, &mut results)?;
-        Ok(results)
+        Ok(expansion)
+    }
+
+    // Read several files concurrently. Results keep the order of `paths`; a failing
+    // file produces an error entry instead of failing the whole batch. Files are
+    // admitted in order until their combined size would exceed `max_total_bytes`;
+    // that file and everything after it is reported as skipped.
+    pub async fn read_multiple_files(&self, paths: &[String], max_total_bytes: u64) -> Vec<FileReadResult> {
+        let mut results: Vec<FileReadResult> = paths.iter().map(|p| FileReadResult::pending(p)).collect();
+        let semaphore = std::sync::Arc::new(Semaphore::new(READ_CONCURRENCY));
+        let mut join_set = JoinSet::new();
+        let mut budget_used: u64 = 0;
+        let mut budget_exhausted = false;
+
+        for (index, path) in paths.iter().enumerate() {
+            if budget_exhausted {
+                results[index].set_skipped("total size budget already reached".to_string());
+                continue;
+            }
+            let valid_path = match self.validate_existing_path(Path::new(path)).await {
+                Ok(valid_path) => valid_path,
+                Err(e) => {
+                    results[index].set_error(e);
+                    continue;
+                }
+            };
+            let size = match fs::metadata(&valid_path).await {
+                Ok(metadata) => metadata.len(),
+                Err(e) => {
+                    results[index].set_error(ServiceError::Io(e));
+                    continue;
+                }
+            };
+            if let Err(e) = self.check_read_size(Path::new(path), size) {
+                results[index].set_error(e);
+                continue;
+            }
+            if budget_used + size > max_total_bytes {
+                results[index].set_skipped(format!(
+                    "file is {} but only {} of the {} budget remains",
+                    utils::format_bytes(size),
+                    utils::format_bytes(max_total_bytes - budget_used),
+                    utils::format_bytes(max_total_bytes)
+                ));
+                budget_exhausted = true;
+                continue;
+            }
+            budget_used += size;
+            let semaphore = semaphore.clone();
+            let requested = PathBuf::from(path);
+            join_set.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                let content = match tokio::fs::read(&valid_path).await {
+                    Ok(data) => decode_text(&requested, data),
+                    Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
+                    Err(e) => Err(ServiceError::Io(e)),
+                };
+                (index, content)
+            });
+        }
+
+        while let Some(joined) = join_set.join_next().await {
+            match joined {
+                Ok((index, Ok(content))) => results[index].set_content(content),
+                Ok((index, Err(e))) => results[index].set_error(e),
+                Err(e) => log_error!("read_multiple_files task failed: {}", e),
+            }
+        }
+
+        results
+    }
+
+    pub async fn create_directory(&self, file_path: &Path) -> ServiceResult<()> {
+        let valid_path = self.validate_path(file_path).await?;
+        self.check_writable(&valid_path)?;
+
+        if let Some(sandbox) = &self.sandbox {
+            if sandbox.lookup(&valid_path) == Entry::Directory {
+                return Err(ServiceError::DirectoryAlreadyExists);
+            }
+            return Ok(sandbox.create_dir_all(&valid_path)?);
+        }
+
+        // Check if directory already exists
+        if valid_path.exists() && valid_path.is_dir() {
+            return Err(ServiceError::DirectoryAlreadyExists);
+        }
+
+        match tokio::fs::create_dir_all(&valid_path).await {
+            Ok(_) => Ok(()),
+            Err(e) => {
+                match e.kind() {
+                    std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
+                    _ => Err(ServiceError::Io(e)),
+                }
+            }
+        }
+    }
+
+    pub async fn move_file(&self, src_path: &Path, dest_path: &Path) -> ServiceResult<()> {
+        let valid_src_path = self.validate_existing_link_path(src_path).await?;
+        let valid_dest_path = self.validate_path(dest_path).await?;
+        self.check_writable(&valid_src_path)?;
+        self.check_writable(&valid_dest_path)?;
+
+        if let Some(sandbox) = self.sandbox.clone() {
+            return tokio::task::spawn_blocking(move || sandbox.rename(&valid_src_path, &valid_dest_path))
+                .await
+                .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+                .map_err(ServiceError::Io);
+        }
+
+        match tokio::fs::rename(&valid_src_path, &valid_dest_path).await {
+            Ok(_) => {
+                self.undo.record("move_file", UndoAction::MoveBack { from: valid_src_path, to: valid_dest_path });
+                Ok(())
+            }
+            Err(e) => {
+                match e.kind() {
+                    std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
+                    _ => Err(ServiceError::Io(e)),
+                }
+            }
+        }
+    }
+
+    /// Create a symbolic link at `link_path` pointing at `target`. A relative
+    /// target is kept relative, so the link survives moving the tree, and is
+    /// resolved from the link's directory to check it against the policy like
+    /// any other path; it need not exist yet.
+    pub async fn create_symlink(&self, link_path: &Path, target: &Path) -> ServiceResult<()> {
+        let valid_link = self.validate_link_path(link_path).await?;
+        if self.symlinks == SymlinkPolicy::Deny {
+            return Err(ServiceError::SymlinkRefused(valid_link.display().to_string(), self.symlinks.name()));
+        }
+        let resolved = valid_link.parent().map_or_else(|| target.to_path_buf(), |parent| parent.join(target));
+        let valid_target = self.validate_path(&resolved).await?;
+        self.check_writable(&valid_link)?;
+        self.ensure_unsandboxed("create_symlink")?;
+
+        let target = target.to_path_buf();
+        let link = valid_link.clone();
+        tokio::task::spawn_blocking(move || symlink(&target, &link, valid_target.is_dir()))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+            .map_err(|e| with_path(&valid_link, e))?;
+        self.undo.record("create_symlink", UndoAction::Restore { path: valid_link, saved: None });
+        Ok(())
+    }
+
+    /// Create `link_path` as another name for the existing file `target`.
+    /// Writing through either name changes both, so the target must be
+    /// writable as well as the new name.
+    pub async fn create_hardlink(&self, link_path: &Path, target: &Path) -> ServiceResult<()> {
+        let valid_target = self.validate_existing_path(target).await?;
+        let valid_link = self.validate_path(link_path).await?;
+        self.check_writable(&valid_target)?;
+        self.check_writable(&valid_link)?;
+        self.ensure_unsandboxed("create_hardlink")?;
+        if valid_target.is_dir() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is a directory; only files can be hard linked", valid_target.display()),
+            )));
+        }
+
+        match tokio::fs::hard_link(&valid_target, &valid_link).await {
+            Ok(()) => {
+                self.undo.record("create_hardlink", UndoAction::Restore { path: valid_link, saved: None });
+                Ok(())
+            }
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
+                _ => Err(with_path(&valid_link, e)),
+            },
+        }
+    }
+
+    /// Give each of `paths` (glob patterns expand to their sorted matches) the
+    /// name `rule` makes for it, within its own directory. Nothing is renamed
+    /// on a dry run, or when any new name collides or is invalid; the plan
+    /// says which. Each rename is its own undo step.
+    pub async fn rename_files(&self, paths: &[String], rule: &RenameRule, dry_run: bool) -> ServiceResult<RenamePlan> {
+        let expansion = self.expand_path_patterns_within(paths, RENAME_MAX_FILES, u64::MAX).await?;
+        if expansion.paths.len() > RENAME_MAX_FILES {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("rename_files renames at most {} files, got {}", RENAME_MAX_FILES, expansion.paths.len()),
+            )));
+        }
+        let mut files = Vec::with_capacity(expansion.paths.len());
+        for path in &expansion.paths {
+            files.push(self.validate_existing_link_path(Path::new(path)).await?);
+        }
+        let plan = RenamePlan::new(&files, rule);
+        for entry in plan.renames() {
+            self.validate_path(Path::new(&entry.to)).await?;
+        }
+        if dry_run || plan.problems() > 0 || plan.renames().next().is_none() {
+            return Ok(plan);
+        }
+
+        self.ensure_unsandboxed("rename_files")?;
+        for entry in plan.renames() {
+            self.check_writable(Path::new(&entry.from))?;
+            self.check_writable(Path::new(&entry.to))?;
+        }
+        let plan = tokio::task::spawn_blocking(move || plan.execute().map(|_| plan))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+            .map_err(ServiceError::Io)?;
+        for entry in plan.renames() {
+            self.undo.record(
+                "rename_files",
+                UndoAction::MoveBack { from: PathBuf::from(&entry.from), to: PathBuf::from(&entry.to) },
+            );
+        }
+        Ok(RenamePlan { renamed: true, ..plan })
+    }
+
+    pub async fn list_directory(&self, dir_path: &Path) -> ServiceResult<Vec<tokio::fs::DirEntry>> {
+        let valid_path = self.validate_existing_path(dir_path).await?;
+
+        match tokio::fs::read_dir(valid_path).await {
+            Ok(mut dir) => {
+                let mut entries = Vec::new();
+                while let Some(entry) = dir.next_entry().await? {
+                    entries.push(entry);
+                }
+                Ok(entries)
+            },
+            Err(e) => {
+                match e.kind() {
+                    std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
+                    _ => Err(ServiceError::Io(e)),
+                }
+            }
+        }
+    }
+
+    // Fetch `url` into `destination`. The URL and every redirect must pass the
+    // download policy; the body streams into a temporary sibling that is renamed
+    // into place only once it is complete and within the size limit.
+    #[cfg(feature = "download")]
+    pub async fn download_file(&self, url: &str, destination: &Path, overwrite: bool) -> ServiceResult<DownloadReport> {
+        use sha2::{Digest, Sha256};
+        use tokio::io::AsyncWriteExt;
+
+        self.ensure_unsandboxed("download_file")?;
+        let invalid = |message: String| ServiceError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, message));
+        let request_error = |e: reqwest::Error| {
+            let mut message = e.to_string();
+            let mut source = std::error::Error::source(&e);
+            while let Some(cause) = source {
+                message.push_str(&format!(": {}", cause));
+                source = cause.source();
+            }
+            ServiceError::Io(std::io::Error::other(message))
+        };
+
+        self.download_policy.check(url).map_err(invalid)?;
+        let valid_path = self.validate_path(destination).await?;
+        self.check_writable(&valid_path)?;
+        if valid_path.is_dir() {
+            return Err(invalid(format!("{} is a directory; give the file name to download to", valid_path.display())));
+        }
+        if valid_path.exists() && !overwrite {
+            return Err(invalid(format!("{} already exists; pass overwrite: true to replace it", valid_path.display())));
+        }
+        let parent = valid_path.parent().filter(|p| p.is_dir()).ok_or_else(|| {
+            ServiceError::FileNotFound(format!("parent directory of {}", valid_path.display()))
+        })?;
+        let file_name = valid_path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+
+        let policy = self.download_policy.clone();
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() >= 10 {
+                    return attempt.error("too many redirects");
+                }
+                match policy.check(attempt.url().as_str()) {
+                    Ok(()) => attempt.follow(),
+                    Err(reason) => attempt.error(format!("redirect refused: {}", reason)),
+                }
+            }))
+            .build()
+            .map_err(request_error)?;
+
+        let mut response = client.get(url).send().await.map_err(request_error)?;
+        if !response.status().is_success() {
+            return Err(ServiceError::Io(std::io::Error::other(format!("{} answered {}", url, response.status()))));
+        }
+        let max_bytes = self.download_policy.max_bytes;
+        if let Some(length) = response.content_length().filter(|length| *length > max_bytes) {
+            return Err(invalid(format!(
+                "{} is {}, over the download limit of {}",
+                url,
+                utils::format_bytes(length),
+                utils::format_bytes(max_bytes)
+            )));
+        }
+        let final_url = response.url().to_string();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        let temp_path = parent.join(format!(".{}.download-tmp", file_name));
+        let written = async {
+            let mut file = fs::File::create(&temp_path).await?;
+            let mut hasher = Sha256::new();
+            let mut bytes = 0u64;
+            while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+                bytes += chunk.len() as u64;
+                if bytes > max_bytes {
+                    return Err(invalid(format!("{} exceeded the download limit of {}", url, utils::format_bytes(max_bytes))));
+                }
+                hasher.update(&chunk);
+                file.write_all(&chunk).await?;
+            }
+            file.sync_all().await?;
+            drop(file);
+            fs::rename(&temp_path, &valid_path).await?;
+            Ok((bytes, format!("{:x}", hasher.finalize())))
+        }
+        .await;
+        let (bytes, sha256) = written.inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp_path);
+        })?;
+
+        Ok(DownloadReport {
+            url: url.to_string(),
+            final_url,
+            path: valid_path.display().to_string(),
+            bytes,
+            sha256,
+            content_type,
+        })
+    }
+
+    // Join `paths` (glob patterns expand to their sorted matches) into `output`
+    // with `separator` between files. Inputs stream through a temporary sibling
+    // that replaces `output` only once every input has been copied.
+    pub async fn concat_files(
+        &self,
+        paths: &[String],
+        output: &Path,
+        separator: Option<&str>,
+        overwrite: bool,
+    ) -> ServiceResult<concat::ConcatReport> {
+        let invalid = |message: String| ServiceError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, message));
+        let expansion = self
+            .expand_path_patterns_within(paths, concat::CONCAT_MAX_FILES, u64::MAX)
+            .await?;
+        if expansion.paths.is_empty() {
+            return Err(invalid(format!("No files to join: {}", expansion.notes.join("; "))));
+        }
+        if expansion.paths.len() > concat::CONCAT_MAX_FILES {
+            return Err(invalid(format!(
+                "concat_files joins at most {} files, got {}",
+                concat::CONCAT_MAX_FILES,
+                expansion.paths.len()
+            )));
+        }
+
+        let valid_output = self.validate_path(output).await?;
+        self.check_writable(&valid_output)?;
+        let mut inputs = Vec::with_capacity(expansion.paths.len());
+        for path in &expansion.paths {
+            let valid_path = self.validate_existing_path(Path::new(path)).await?;
+            if valid_path == valid_output {
+                return Err(invalid(format!("{} is both an input and the output", valid_path.display())));
+            }
+            let contents = self.contents_path(valid_path.clone());
+            if !fs::metadata(&contents).await?.is_file() {
+                return Err(invalid(format!("{} is not a file", valid_path.display())));
+            }
+            inputs.push((valid_path.display().to_string(), contents));
+        }
+
+        let output_exists = match &self.sandbox {
+            Some(sandbox) => sandbox.lookup(&valid_output) != Entry::Missing,
+            None => valid_output.exists(),
+        };
+        if output_exists && !overwrite {
+            return Err(invalid(format!("{} already exists; pass overwrite: true to replace it", valid_output.display())));
+        }
+        let parent_exists = valid_output.parent().is_some_and(|parent| match &self.sandbox {
+            Some(sandbox) => sandbox.lookup(parent) == Entry::Directory || parent.is_dir(),
+            None => parent.is_dir(),
+        });
+        if !parent_exists {
+            return Err(ServiceError::FileNotFound(format!("parent directory of {}", valid_output.display())));
+        }
+
+        let separator = separator.unwrap_or_default().as_bytes().to_vec();
+        let sandbox = self.sandbox.clone();
+        let destination = valid_output.clone();
+        let (joined, bytes, sha256) = tokio::task::spawn_blocking(move || match sandbox {
+            Some(sandbox) => {
+                let mut buffer = Vec::new();
+                let joined = concat::write_joined(&mut buffer, &inputs, &separator)?;
+                sandbox.write(&destination, &buffer)?;
+                Ok(joined)
+            }
+            None => concat::write_joined_atomically(&destination, &inputs, &separator),
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+        .map_err(|e: std::io::Error| ServiceError::Io(e))?;
+
+        Ok(concat::ConcatReport {
+            output: valid_output.display().to_string(),
+            bytes,
+            sha256,
+            inputs: joined,
+            notes: expansion.notes,
+        })
+    }
+
+    // Make a new empty file (or one holding `content`) with a unique name in
+    // this session's scratch directory. `mode` is the operation mode whose
+    // completion removes it; without one it lasts until the session ends.
+    pub async fn create_temp_file(&self, prefix: &str, suffix: &str, content: Option<&str>, mode: Option<u64>) -> ServiceResult<TempEntry> {
+        let directory = self.prepare_scratch_dir("create_temp_file").await?;
+        let path = directory.join(self.temp.unique_name(prefix, suffix)?);
+        let mut file = fs::OpenOptions::new().write(true).create_new(true).open(&path).await?;
+        if let Some(content) = content {
+            use tokio::io::AsyncWriteExt;
+            file.write_all(content.as_bytes()).await?;
+            file.flush().await?;
+        }
+        Ok(self.record_temp(path, TempKind::File, mode))
+    }
+
+    // create_temp_file for an empty directory
+    pub async fn create_temp_dir(&self, prefix: &str, mode: Option<u64>) -> ServiceResult<TempEntry> {
+        let directory = self.prepare_scratch_dir("create_temp_dir").await?;
+        let path = directory.join(self.temp.unique_name(prefix, "")?);
+        fs::create_dir(&path).await?;
+        Ok(self.record_temp(path, TempKind::Directory, mode))
+    }
+
+    // Delete the temporary paths made during operation mode `mode`
+    pub fn cleanup_temp_for_mode(&self, mode: u64) -> Vec<String> {
+        self.temp.remove_for_mode(mode)
+    }
+
+    // Library API; the tools only report the path they just made
+    #[allow(dead_code)]
+    pub fn temp_entries(&self) -> Vec<TempEntry> {
+        self.temp.entries()
+    }
+
+    // The session's scratch directory, created on first use. It must pass the
+    // access rules like any other path. Temporary paths are always made on
+    // disk, even in --sandbox mode, since they are not part of the workspace.
+    async fn prepare_scratch_dir(&self, operation: &str) -> ServiceResult<PathBuf> {
+        self.ensure_not_dry_run(operation)?;
+        let directory = self.temp.session_dir();
+        let valid_directory = self.validate_path(&directory).await.map_err(|_| {
+            ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!(
+                    "the scratch directory {} is outside the allowed directories; point --scratch-dir inside them",
+                    self.temp.root().display()
+                ),
+            ))
+        })?;
+        self.check_writable(&valid_directory)?;
+        fs::create_dir_all(&valid_directory).await?;
+        Ok(valid_directory)
+    }
+
+    fn record_temp(&self, path: PathBuf, kind: TempKind, mode: Option<u64>) -> TempEntry {
+        let entry = TempEntry {
+            path: path.display().to_string(),
+            kind,
+            mode,
+            created_at: chrono::Utc::now(),
+        };
+        self.temp.record(entry.clone());
+        entry
+    }
+
+    // Operations that keep state outside the workspace (scratch files, the tag
+    // store) write it directly and so are refused in dry-run mode
+    fn ensure_not_dry_run(&self, operation: &str) -> ServiceResult<()> {
+        if self.dry_run {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("{} writes directly to disk and cannot be previewed in dry-run mode", operation),
+            )));
+        }
+        Ok(())
+    }
+
+    // Add `add` to and drop `remove` from the tags of `path`
+    pub async fn tag_path(&self, path: &Path, add: &[String], remove: &[String]) -> ServiceResult<TaggedPath> {
+        self.ensure_not_dry_run("tag_path")?;
+        let valid_path = self.validate_existing_path(path).await?;
+        let (add, remove) = (tags::normalize_tags(add)?, tags::normalize_tags(remove)?);
+        if add.is_empty() && remove.is_empty() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "give tags to add or remove_tags to remove",
+            )));
+        }
+        let metadata = self.tags.update(&valid_path, |metadata| {
+            metadata.tags.extend(add);
+            metadata.tags.retain(|tag| !remove.contains(tag));
+        })?;
+        Ok(TaggedPath { path: valid_path.display().to_string(), exists: true, metadata })
+    }
+
+    // Attach a note to `path`, after dropping the earlier ones with `clear`
+    pub async fn annotate_path(&self, path: &Path, note: &str, clear: bool) -> ServiceResult<TaggedPath> {
+        self.ensure_not_dry_run("annotate_path")?;
+        let valid_path = self.validate_existing_path(path).await?;
+        let note = note.trim();
+        if note.is_empty() && !clear {
+            return Err(ServiceError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, "note is empty")));
+        }
+        let metadata = self.tags.update(&valid_path, |metadata| {
+            if clear {
+                metadata.notes.clear();
+            }
+            if !note.is_empty() {
+                metadata.notes.push(tags::Annotation { note: note.to_string(), created_at: chrono::Utc::now() });
+            }
+        })?;
+        Ok(TaggedPath { path: valid_path.display().to_string(), exists: true, metadata })
+    }
+
+    // Tagged or annotated paths at or below `scope`
+    pub async fn tagged_paths(&self, scope: &Path) -> ServiceResult<Vec<TaggedPath>> {
+        let valid_scope = self.validate_path(scope).await?;
+        Ok(self.tags.under(&valid_scope)?)
+    }
+
+    // Paths at or below `scope` carrying any (or with `match_all`, every) one of `tags`
+    pub async fn find_by_tag(&self, scope: &Path, tags: &[String], match_all: bool) -> ServiceResult<Vec<TaggedPath>> {
+        let wanted = tags::normalize_tags(tags)?;
+        let mut found = self.tagged_paths(scope).await?;
+        found.retain(|tagged| {
+            if match_all {
+                wanted.is_subset(&tagged.metadata.tags)
+            } else {
+                !wanted.is_disjoint(&tagged.metadata.tags)
+            }
+        });
+        Ok(found)
+    }
+
+    // Take or renew the advisory lock on `path` (which need not exist yet) for
+    // `ttl_secs` seconds
+    pub async fn acquire_lock(&self, path: &Path, owner: Option<&str>, ttl_secs: Option<u64>) -> ServiceResult<locks::LockGrant> {
+        let ttl_secs = ttl_secs.unwrap_or(locks::DEFAULT_LOCK_TTL_SECS);
+        if !(1..=locks::MAX_LOCK_TTL_SECS).contains(&ttl_secs) {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("ttl_seconds must be between 1 and {}", locks::MAX_LOCK_TTL_SECS),
+            )));
+        }
+        self.ensure_lockable(path, "acquire_lock")?;
+        let valid_path = self.validate_path(path).await?;
+        let locks = self.locks.clone();
+        let owner = owner.map(str::to_string);
+        Ok(tokio::task::spawn_blocking(move || locks.acquire(&valid_path, owner.as_deref(), std::time::Duration::from_secs(ttl_secs)))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))??)
+    }
+
+    // Drop the advisory lock on `path`; another session's live lock needs `force`
+    pub async fn release_lock(&self, path: &Path, force: bool) -> ServiceResult<Option<locks::LockInfo>> {
+        self.ensure_lockable(path, "release_lock")?;
+        let valid_path = self.validate_path(path).await?;
+        Ok(self.locks.release(&valid_path, force)?)
+    }
+
+    // Lock files are real files shared with other sessions, so they are not
+    // previewed, and remote stores have nowhere to put them
+    fn ensure_lockable(&self, path: &Path, operation: &str) -> ServiceResult<()> {
+        if RemoteStorage::is_remote(path) {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("{} only works on local paths", operation),
+            )));
+        }
+        if self.dry_run {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("{} writes lock files directly to disk and cannot be previewed in dry-run mode", operation),
+            )));
+        }
+        Ok(())
+    }
+
+    /// Start buffering create, modify, delete and rename events at `path`,
+    /// and below it when `recursive`, for poll_watch. Changes to blocked
+    /// paths are left out. Returns the watch id.
+    pub async fn start_watch(&self, path: &Path, recursive: bool) -> ServiceResult<String> {
+        let valid_path = self.validate_existing_path(path).await?;
+        let policy = self.policy().clone();
+        let allowed = self.allowed_directories();
+        self.watches
+            .start(&valid_path, recursive, move |path| policy.check(&allowed, path, false).is_ok())
+            .map_err(ServiceError::Io)
+    }
+
+    /// The events of watch `id` since its last poll
+    pub fn poll_watch(&self, id: &str) -> ServiceResult<WatchPoll> {
+        self.watches.poll(id).ok_or_else(|| unknown_watch(id))
+    }
+
+    pub fn stop_watch(&self, id: &str) -> ServiceResult<()> {
+        if self.watches.stop(id) { Ok(()) } else { Err(unknown_watch(id)) }
+    }
+
+    #[cfg(not(feature = "download"))]
+    pub async fn download_file(&self, _url: &str, _destination: &Path, _overwrite: bool) -> ServiceResult<DownloadReport> {
+        Err(ServiceError::Io(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "this build does not include the download feature",
+        )))
+    }
+
+    pub async fn write_file(&self, file_path: &Path, content: &String) -> ServiceResult<()> {
+        if let Some(remote) = self.remote_path(file_path, true) {
+            let remote = remote?;
+            remote.backend.write(&remote.key, content.as_bytes().to_vec()).await?;
+            metrics::record_written(content.len() as u64);
+            return Ok(());
+        }
+        let valid_path = self.validate_path(file_path).await?;
+        self.check_writable(&valid_path)?;
+
+        if let Some(sandbox) = &self.sandbox {
+            return Ok(sandbox.write(&valid_path, content.as_bytes())?);
+        }
+        self.undo.before_write(&valid_path, "write_file").await.map_err(|e| with_path(&valid_path, e))?;
+        self.back_up(&valid_path, false).await?;
+
+        match tokio::fs::write(&valid_path, content).await {
+            Ok(_) => {
+                metrics::record_written(content.len() as u64);
+                Ok(())
+            }
+            Err(e) => {
+                match e.kind() {
+                    std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
+                    _ => Err(ServiceError::Io(e)),
+                }
+            }
+        }
+    }
+
+    /// Create `file_path` as an empty file, or set the access and modification
+    /// times of what is already there to now. Existing content is never
+    /// changed, unlike writing an empty file over it.
+    pub async fn touch(&self, file_path: &Path) -> ServiceResult<Touched> {
+        let valid_path = self.validate_path(file_path).await?;
+        self.check_writable(&valid_path)?;
+
+        if let Some(sandbox) = &self.sandbox {
+            if sandbox.lookup(&valid_path) != Entry::Missing {
+                return Ok(Touched::Unchanged);
+            }
+            sandbox.write(&valid_path, &[])?;
+            return Ok(Touched::Created);
+        }
+
+        let path = valid_path.clone();
+        let touched = tokio::task::spawn_blocking(move || {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(_) => Ok(Touched::Created),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    set_times(&path, std::time::SystemTime::now()).map(|_| Touched::Updated)
+                }
+                Err(e) => Err(e),
+            }
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => ServiceError::PermissionDenied,
+            _ => with_path(&valid_path, e),
+        })?;
+        // Undoing a created file removes it; new times are not worth a step
+        if touched == Touched::Created {
+            self.undo.record("touch_file", UndoAction::Restore { path: valid_path, saved: None });
+        }
+        Ok(touched)
+    }
+
+    // Walk `directory` on the blocking pool and return files whose name (or
+    // relative path, for patterns containing '/') matches. Matching is
+    // case-insensitive in every mode.
+    pub async fn search_files(&self, directory: &Path, options: SearchFilesOptions) -> Result<SearchFilesOutcome, Box<dyn std::error::Error + Send + Sync>> {
+        let valid_path = self.validate_existing_path(directory).await?;
+
+        let glob_pattern = match options.match_mode {
+            SearchMatchMode::Glob => Some(glob::Pattern::new(&options.pattern)?),
+            SearchMatchMode::Substring => None,
+            SearchMatchMode::Auto if utils::is_glob_pattern(&options.pattern) => {
+                Some(glob::Pattern::new(&options.pattern)?)
+            }
+            SearchMatchMode::Auto => None,
+        };
+
+        let cancel = cancel::current();
+        let links = self.link_rules();
+        let follow_links = options.follow_symlinks && links.policy() == SymlinkPolicy::FollowWithinRoot;
+        let outcome = tokio::task::spawn_blocking(move || {
+            let match_options = glob::MatchOptions {
+                case_sensitive: false,
+                require_literal_separator: true,
+                require_literal_leading_dot: false,
+            };
+            let pattern_lower = options.pattern.to_lowercase();
+            let match_full_path = options.pattern.contains('/');
+            let mut outcome = SearchFilesOutcome::default();
+
+            let walker = WalkDir::new(&valid_path)
+                .min_depth(1)
+                .follow_links(follow_links)
+                .sort_by_file_name()
+                .into_iter()
+                .filter_entry(|e| {
+                    // Links leading outside the allowed directories are not followed
+                    let followable = !follow_links || !e.path_is_symlink() || links.action(e.path()) == LinkAction::Follow;
+                    followable && (options.include_defaults_excluded || e.depth() == 0 || !is_default_excluded(e.file_name()))
+                });
+            for entry in walker {
+                cancel::check(&cancel)?;
+                // walkdir detects links back to an ancestor when following symlinks;
+                // report those and skip other unreadable entries rather than aborting
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        if let (Some(ancestor), Some(path)) = (e.loop_ancestor(), e.path()) {
+                            outcome.warnings.push(format!(
+                                "Skipped symlink loop: {} points back to {}",
+                                path.display(),
+                                ancestor.display()
+                            ));
+                        }
+                        continue;
+                    }
+                };
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let path = entry.path();
+                let candidate = if match_full_path {
+                    path.strip_prefix(&valid_path)
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                        .replace('\\', "/")
+                } else {
+                    entry.file_name().to_string_lossy().into_owned()
+                };
+
+                let mut matches = match &glob_pattern {
+                    Some(glob) => glob.matches_with(&candidate, match_options),
+                    None => candidate.to_lowercase().contains(&pattern_lower),
+                };
+
+                if options.include_content && !matches {
+                    if let Ok(content) = std::fs::read_to_string(path) {
+                        matches = content.to_lowercase().contains(&pattern_lower);
+                    }
+                }
+
+                if matches {
+                    outcome.files.push(path_text(path));
+                }
+            }
+            Ok::<_, std::io::Error>(outcome)
+        })
+        .await??;
+
+        Ok(outcome)
     }
 
     pub fn create_unified_diff(
@@ -312,6 +1855,8 @@ impl FileSystemService {
         format!("Index: {}\n{}\n{}", file_name, "=".repeat(68), patch)
     }
 
+    // Library API for UTF-8 files; edit_file goes through apply_file_edits_as
+    #[allow(dead_code)]
     pub async fn apply_file_edits(
         &self,
         file_path: &Path,
@@ -319,24 +1864,73 @@ impl FileSystemService {
         dry_run: Option<bool>,
         save_to: Option<&Path>,
     ) -> ServiceResult<String> {
+        let outcome = self.apply_file_edits_as(file_path, edits, dry_run, save_to, TextEncoding::default()).await?;
+        if !outcome.all_applied() {
+            return Err(with_path(file_path, std::io::Error::new(std::io::ErrorKind::InvalidData, outcome.failures())));
+        }
+        Ok(outcome.diff)
+    }
+
+    // apply_file_edits on a file stored in `encoding`; the result is written
+    // back in the encoding the file was read in, byte order mark included
+    pub async fn apply_file_edits_as(
+        &self,
+        file_path: &Path,
+        edits: Vec<EditOperation>,
+        dry_run: Option<bool>,
+        save_to: Option<&Path>,
+        encoding: TextEncoding,
+    ) -> ServiceResult<EditOutcome> {
         let valid_path = self.validate_existing_path(file_path).await?;
 
         // Read file content and normalize line endings
-        let content_str = tokio::fs::read_to_string(&valid_path).await?;
-        let original_line_ending = self.detect_line_ending(&content_str);
-        let content_str = normalize_line_endings(&content_str);
+        let data = tokio::fs::read(self.contents_path(valid_path.clone())).await?;
+        metrics::record_read(data.len() as u64);
+        let decoded = encoding::decode(&data, encoding).map_err(|e| with_path(file_path, e))?;
+        let content_str = decoded.text.as_str();
+        let original_line_ending = self.detect_line_ending(content_str);
+        let content_str = normalize_line_endings(content_str);
 
         // Apply edits sequentially
         let mut modified_content = content_str.clone();
+        let mut matches = Vec::with_capacity(edits.len());
 
-        for edit in edits {
+        for (index, edit) in edits.iter().enumerate() {
             let normalized_old = normalize_line_endings(&edit.old_text);
             let normalized_new = normalize_line_endings(&edit.new_text);
 
-            // Apply simple string replacement
-            if modified_content.contains(&normalized_old) {
-                modified_content = modified_content.replacen(&normalized_old, &normalized_new, 1);
+            if edit.kind != EditKind::Replace {
+                modified_content = apply_line_edit(&modified_content, edit, &normalized_new).map_err(|e| {
+                    with_path(file_path, std::io::Error::new(e.kind(), format!("edit {}: {}", index + 1, e)))
+                })?;
+                matches.push(EditMatch::Lines);
+                continue;
+            }
+            if normalized_old.is_empty() {
+                return Err(ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("edit {}: oldText is required for replace edits", index + 1),
+                )));
             }
+
+            // Exact text first, then the same lines with their whitespace
+            // ignored; either way it must pick out one place
+            let found = match modified_content.matches(normalized_old.as_str()).count() {
+                1 => {
+                    modified_content = modified_content.replacen(&normalized_old, &normalized_new, 1);
+                    EditMatch::Exact
+                }
+                0 => match replace_ignoring_whitespace(&modified_content, &normalized_old, &normalized_new) {
+                    Ok(replaced) => {
+                        modified_content = replaced;
+                        EditMatch::IgnoringWhitespace
+                    }
+                    Err(0) => EditMatch::NotFound,
+                    Err(occurrences) => EditMatch::Ambiguous { occurrences },
+                },
+                occurrences => EditMatch::Ambiguous { occurrences },
+            };
+            matches.push(found);
         }
 
         let diff = self.create_unified_diff(
@@ -344,20 +1938,10 @@ impl FileSystemService {
             &modified_content,
             Some(valid_path.display().to_string()),
         );
+        let outcome = EditOutcome { diff: fenced_diff(&diff), matches };
 
-        // Format diff with appropriate number of backticks
-        let mut num_backticks = 3;
-        while diff.contains(&"`".repeat(num_backticks)) {
-            num_backticks += 1;
-        }
-        let formatted_diff = format!(
-            "{}diff\n{}{}\n\n",
-            "`".repeat(num_backticks),
-            diff,
-            "`".repeat(num_backticks)
-        );
-
-        let is_dry_run = dry_run.unwrap_or(false);
+        // A call with an edit that did not apply changes nothing
+        let is_dry_run = dry_run.unwrap_or(false) || !outcome.all_applied();
 
         if !is_dry_run {
             let target_path = if let Some(save_to) = save_to {
@@ -366,136 +1950,1198 @@ impl FileSystemService {
                 valid_path
             };
             let modified_content = modified_content.replace("\n", original_line_ending);
+            let modified_content =
+                encoding::encode(&modified_content, decoded.encoding, decoded.bom).map_err(|e| with_path(file_path, e))?;
+            self.write_edited(&target_path, modified_content, "edit_file").await?;
+        }
+
+        Ok(outcome)
+    }
+
+    // Apply a unified diff to a file. Each hunk is looked for near the line its
+    // header names; when the context has drifted, up to `fuzz` context lines
+    // are ignored at each end. Nothing is written unless every hunk applies.
+    pub async fn apply_patch(&self, file_path: &Path, patch: &str, fuzz: usize, dry_run: bool) -> ServiceResult<PatchOutcome> {
+        let hunks = parse_patch(patch)?;
+        let valid_path = self.validate_existing_path(file_path).await?;
+        let data = tokio::fs::read(self.contents_path(valid_path.clone())).await?;
+        metrics::record_read(data.len() as u64);
+        let decoded = encoding::decode(&data, TextEncoding::default()).map_err(|e| with_path(file_path, e))?;
+        let original_line_ending = self.detect_line_ending(&decoded.text);
+        let original = normalize_line_endings(&decoded.text);
+
+        let (patched, applied) = apply_hunks(&original, &hunks, fuzz).map_err(|e| with_path(file_path, e))?;
+        let diff = fenced_diff(&self.create_unified_diff(&original, &patched, Some(valid_path.display().to_string())));
+        if !dry_run {
+            let patched = patched.replace('\n', original_line_ending);
+            let patched = encoding::encode(&patched, decoded.encoding, decoded.bom).map_err(|e| with_path(file_path, e))?;
+            self.write_edited(&valid_path, patched, "apply_patch").await?;
+        }
+        Ok(PatchOutcome { diff, hunks: applied })
+    }
+
+    // Replace a file's content with an edited version, through the sandbox
+    // when there is one
+    async fn write_edited(&self, target_path: &Path, content: Vec<u8>, operation: &str) -> ServiceResult<()> {
+        self.check_writable(target_path)?;
+        if let Some(sandbox) = &self.sandbox {
+            sandbox.write(target_path, &content)?;
+            return Ok(());
+        }
+        self.undo.before_write(target_path, operation).await.map_err(|e| with_path(target_path, e))?;
+        self.back_up(target_path, false).await?;
+
+        let written = content.len() as u64;
+        match tokio::fs::write(target_path, content).await {
+            Ok(_) => {
+                metrics::record_written(written);
+                Ok(())
+            }
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
+                _ => Err(ServiceError::Io(e)),
+            },
+        }
+    }
+
+    // Indented listing of everything under `path`. Sibling directories are read
+    // in parallel on a bounded pool; entries are listed in name order.
+    pub async fn generate_directory_tree(&self, path: &Path, include_hidden: bool, max_depth: u32, follow_symlinks: bool, include_defaults_excluded: bool) -> ServiceResult<String> {
+        let valid_path = self.validate_existing_path(path).await?;
+        let options = TreeWalkOptions {
+            follow_symlinks,
+            links: self.link_rules(),
+            include_defaults_excluded,
+            include_hidden,
+            max_depth: (max_depth > 0).then_some(max_depth as usize),
+        };
+
+        let root = valid_path.clone();
+        let cancel = cancel::current();
+        let entries = tokio::task::spawn_blocking(move || build_tree(&root, &options, &cancel))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))??;
 
-            match tokio::fs::write(&target_path, modified_content).await {
-                Ok(_) => {},
+        let mut tree_lines = vec![format!("{}/", name_text(&valid_path))];
+        render_tree(&valid_path, &entries, 1, &mut tree_lines);
+        Ok(tree_lines.join("\n"))
+    }
+
+    // Copy a file or directory tree. Symlinks are copied as their targets; links
+    // that lead back to a directory already being copied are skipped and returned
+    // as warnings instead of recursing forever.
+    // List an `s3://` or `sftp://` directory (a key prefix on S3)
+    pub async fn list_remote_directory(&self, dir_path: &Path) -> ServiceResult<Vec<RemoteEntry>> {
+        let remote = self.remote_path(dir_path, false).ok_or_else(|| {
+            ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a remote path", dir_path.display()),
+            ))
+        })??;
+        Ok(remote.backend.list(&remote.key).await?)
+    }
+
+    // Copy a single file where either end is remote storage
+    async fn copy_remote_file(&self, src_path: &Path, dest_path: &Path) -> ServiceResult<()> {
+        let data = match self.remote_path(src_path, false) {
+            Some(remote) => {
+                let remote = remote?;
+                remote.backend.read(&remote.key).await?
+            }
+            None => {
+                let valid_src_path = self.validate_existing_path(src_path).await?;
+                if valid_src_path.is_dir() {
+                    return Err(ServiceError::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Only single files can be copied to or from remote storage",
+                    )));
+                }
+                fs::read(&valid_src_path).await?
+            }
+        };
+        match self.remote_path(dest_path, true) {
+            Some(remote) => {
+                let remote = remote?;
+                remote.backend.write(&remote.key, data).await?;
+            }
+            None => {
+                let valid_dest_path = self.validate_path(dest_path).await?;
+                self.check_writable(&valid_dest_path)?;
+                match &self.sandbox {
+                    Some(sandbox) => sandbox.write(&valid_dest_path, &data)?,
+                    None => fs::write(&valid_dest_path, data).await?,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn copy_file(&self, src_path: &Path, dest_path: &Path) -> ServiceResult<Vec<String>> {
+        if RemoteStorage::is_remote(src_path) || RemoteStorage::is_remote(dest_path) {
+            self.copy_remote_file(src_path, dest_path).await?;
+            return Ok(Vec::new());
+        }
+        // With no-follow a link is copied as a link
+        let valid_src_path = match self.symlinks {
+            SymlinkPolicy::NoFollow => self.validate_existing_link_path(src_path).await?,
+            _ => self.validate_existing_path(src_path).await?,
+        };
+        let valid_dest_path = self.validate_path(dest_path).await?;
+        self.check_writable(&valid_dest_path)?;
+        let mut warnings = Vec::new();
+
+        if let Some(sandbox) = self.sandbox.clone() {
+            tokio::task::spawn_blocking(move || sandbox.copy(&valid_src_path, &valid_dest_path))
+                .await
+                .map_err(|e| ServiceError::Io(std::io::Error::other(e)))??;
+            return Ok(warnings);
+        }
+
+        if std::fs::symlink_metadata(&valid_src_path).is_ok_and(|m| m.file_type().is_symlink())
+            && self.symlinks == SymlinkPolicy::NoFollow
+        {
+            copy_link(&valid_src_path, &valid_dest_path)?;
+        } else if valid_src_path.is_dir() {
+            if normalize_path(&valid_dest_path).starts_with(normalize_path(&valid_src_path)) {
+                return Err(ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Cannot copy {} into itself", valid_src_path.display()),
+                )));
+            }
+            // For directories, use recursive copy
+            let links = self.link_rules();
+            self.copy_dir_recursive(&valid_src_path, &valid_dest_path, &links, &mut Vec::new(), &mut warnings)
+                .await?;
+        } else {
+            // For files, use simple copy
+            throttle::copy_async(&valid_src_path, &valid_dest_path).await?;
+        }
+
+        Ok(warnings)
+    }
+
+    async fn copy_dir_recursive(
+        &self,
+        src: &Path,
+        dest: &Path,
+        links: &LinkRules,
+        ancestors: &mut Vec<FileIdentity>,
+        warnings: &mut Vec<String>,
+    ) -> ServiceResult<()> {
+        let identity = file_identity(src)?;
+        if ancestors.contains(&identity) {
+            warnings.push(format!(
+                "Skipped {}: symlink loop back to a directory already being copied",
+                src.display()
+            ));
+            return Ok(());
+        }
+        ancestors.push(identity);
+
+        tokio::fs::create_dir_all(dest).await?;
+
+        let mut entries = tokio::fs::read_dir(src).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let src_path = entry.path();
+            let dest_path = dest.join(entry.file_name());
+
+            if entry.file_type().await?.is_symlink() {
+                match links.action(&src_path) {
+                    LinkAction::Follow => {}
+                    LinkAction::Keep => {
+                        copy_link(&src_path, &dest_path)?;
+                        continue;
+                    }
+                    LinkAction::Skip => {
+                        warnings.push(format!("Skipped {}", links.skip_reason(&src_path)));
+                        continue;
+                    }
+                }
+            }
+            if src_path.is_dir() {
+                Box::pin(self.copy_dir_recursive(&src_path, &dest_path, links, ancestors, warnings)).await?;
+            } else {
+                throttle::copy_async(&src_path, &dest_path).await?;
+            }
+        }
+
+        ancestors.pop();
+        Ok(())
+    }
+
+    pub async fn delete_file(&self, file_path: &Path) -> ServiceResult<()> {
+        self.delete_path(file_path, DeleteMode::Trash).await.map(|_| ())
+    }
+
+    /// Delete a file or directory tree: into the trash when one is set and
+    /// `mode` allows, otherwise for good. A trashed item is not backed up, as
+    /// the trash already keeps it. Failing to trash something is an error,
+    /// never a reason to delete it permanently instead. Returns whether the
+    /// item went to the trash.
+    pub async fn delete_path(&self, file_path: &Path, mode: DeleteMode) -> ServiceResult<bool> {
+        if mode == DeleteMode::Secure {
+            self.secure_delete(file_path).await?;
+            return Ok(false);
+        }
+        if let Some(remote) = self.remote_path(file_path, true) {
+            let remote = remote?;
+            remote.backend.delete(&remote.key).await?;
+            return Ok(false);
+        }
+        let valid_path = self.validate_existing_link_path(file_path).await?;
+        self.check_writable(&valid_path)?;
+
+        if let Some(sandbox) = &self.sandbox {
+            sandbox.remove(&valid_path)?;
+            return Ok(false);
+        }
+        if let (DeleteMode::Trash, Some(trash)) = (mode, &self.trash) {
+            let (sent, path) = (trash.clone(), valid_path.clone());
+            let trashed = tokio::task::spawn_blocking(move || sent.send(&path))
+                .await
+                .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+                .map_err(|e| {
+                    let message = format!(
+                        "could not move {} to the trash: {}; pass permanent: true to delete it for good",
+                        valid_path.display(),
+                        e
+                    );
+                    ServiceError::Io(std::io::Error::new(e.kind(), message))
+                })?;
+            let action = match trashed {
+                Some(trashed) => UndoAction::FromTrash { path: valid_path, trashed: trashed.location, info: trashed.info },
+                None => UndoAction::Irreversible {
+                    path: valid_path,
+                    reason: format!("it is in {}; restore it from there", trash.describe()),
+                },
+            };
+            self.undo.record("delete_file", action);
+            return Ok(true);
+        }
+        if let Some(backup) = self.back_up(&valid_path, true).await? {
+            self.undo.record("delete_file", UndoAction::MoveBack { from: valid_path, to: backup });
+            return Ok(false);
+        }
+        if self.undo.trash(&valid_path, "delete_file").await.map_err(|e| with_path(&valid_path, e))?.is_some() {
+            return Ok(false);
+        }
+
+        // A link is removed, never what it points to
+        let is_link = std::fs::symlink_metadata(&valid_path).is_ok_and(|m| m.file_type().is_symlink());
+        match if valid_path.is_dir() && !is_link {
+            tokio::fs::remove_dir_all(&valid_path).await
+        } else {
+            tokio::fs::remove_file(&valid_path).await
+        } {
+            Ok(_) => Ok(false),
+            Err(e) => {
+                match e.kind() {
+                    std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
+                    _ => Err(ServiceError::Io(e)),
+                }
+            }
+        }
+    }
+
+    // Wipe and delete, and wipe the undo journal's copies of what was there.
+    // Backups already taken by --backup are left alone.
+    async fn secure_delete(&self, file_path: &Path) -> ServiceResult<()> {
+        if RemoteStorage::is_remote(file_path) {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "secure delete is not available for remote paths",
+            )));
+        }
+        self.ensure_unsandboxed("secure delete")?;
+        let valid_path = self.validate_existing_link_path(file_path).await?;
+        self.check_writable(&valid_path)?;
+
+        let undo = self.undo.clone();
+        tokio::task::spawn_blocking(move || {
+            wipe::wipe(&valid_path).map_err(|e| with_path(&valid_path, e))?;
+            undo.forget(&valid_path).map_err(ServiceError::Io)
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+    }
+
+    // Add these new methods to the impl FileSystemService block
+    pub async fn calculate_directory_size(&self, root_path: &Path, follow_symlinks: bool, include_defaults_excluded: bool) -> ServiceResult<u64> {
+        let valid_path = self.validate_existing_path(root_path).await?;
+        let options = TreeWalkOptions {
+            follow_symlinks,
+            links: self.link_rules(),
+            include_defaults_excluded,
+            include_hidden: true,
+            max_depth: None,
+        };
+        let cancel = cancel::current();
+        tokio::task::spawn_blocking(move || tree_size(&valid_path, &options, &cancel))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::PermissionDenied => ServiceError::PermissionDenied,
+                _ => ServiceError::Io(e),
+            })
+    }
+
+    // Group files with identical content: candidates are bucketed by size and only
+    // same-size files are hashed (in parallel). Paths that are already hard links
+    // to one another count once, since linking them again would reclaim nothing.
+    // Library API for a single root; the tools go through find_duplicates_across_roots
+    #[allow(dead_code)]
+    pub async fn find_duplicate_files(
+        &self,
+        root_path: &Path,
+        pattern: Option<String>,
+        exclude_patterns: Option<Vec<String>>,
+        min_bytes: Option<u64>,
+        max_bytes: Option<u64>,
+        include_defaults_excluded: bool,
+    ) -> ServiceResult<Vec<Vec<String>>> {
+        let scan = self
+            .find_duplicates_across_roots(
+                &[root_path.to_path_buf()],
+                pattern,
+                exclude_patterns,
+                min_bytes,
+                max_bytes,
+                include_defaults_excluded,
+            )
+            .await?;
+        Ok(scan.groups)
+    }
+
+    // find_duplicate_files over several roots at once, e.g. originals and their
+    // backups on another drive, so a group can hold files from different roots.
+    // Each root is validated on its own; overlapping roots are walked once.
+    pub async fn find_duplicates_across_roots(
+        &self,
+        root_paths: &[PathBuf],
+        pattern: Option<String>,
+        exclude_patterns: Option<Vec<String>>,
+        min_bytes: Option<u64>,
+        max_bytes: Option<u64>,
+        include_defaults_excluded: bool,
+    ) -> ServiceResult<DuplicateScan> {
+        let mut roots = Vec::with_capacity(root_paths.len());
+        for root_path in root_paths {
+            let valid_path = self.validate_existing_path(root_path).await?;
+            if !roots.contains(&valid_path) {
+                roots.push(valid_path);
+            }
+        }
+        let filter = WalkFilter::new(
+            pattern.as_deref(),
+            &exclude_patterns.unwrap_or_default(),
+            include_defaults_excluded,
+        )?;
+
+        let cache = self.metadata_cache.clone();
+        let walk_roots = roots.clone();
+        let cancel = cancel::current();
+        let groups = tokio::task::spawn_blocking(move || {
+            let mut seen = HashSet::new();
+            let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+            for entry in walk_roots.iter().flat_map(|root| filter.walk_files(root)) {
+                cancel::check(&cancel)?;
+                let Ok(metadata) = entry.metadata() else { continue };
+                let size = metadata.len();
+                if min_bytes.is_some_and(|min| size < min) || max_bytes.is_some_and(|max| size > max) {
+                    continue;
+                }
+                if let Ok(identity) = file_identity(entry.path()) {
+                    if !seen.insert(identity) {
+                        continue;
+                    }
+                }
+                by_size.entry(size).or_default().push(entry.into_path());
+            }
+
+            let candidates: Vec<PathBuf> = by_size.into_values().filter(|g| g.len() > 1).flatten().collect();
+            let hashed: Vec<(String, PathBuf)> = candidates
+                .into_par_iter()
+                .filter(|_| !cancel.is_cancelled())
+                .filter_map(|path| cache.content_hash_path(&path).ok().map(|hash| (hash, path)))
+                .collect();
+            // Files skipped after a cancel would otherwise look unique
+            cancel::check(&cancel)?;
+
+            let mut by_hash: HashMap<String, Vec<String>> = HashMap::new();
+            for (hash, path) in hashed {
+                by_hash.entry(hash).or_default().push(path_text(&path));
+            }
+            let mut groups: Vec<Vec<String>> = by_hash
+                .into_values()
+                .filter(|g| g.len() > 1)
+                .map(|mut g| {
+                    g.sort();
+                    g
+                })
+                .collect();
+            groups.sort();
+            Ok::<_, std::io::Error>(groups)
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))??;
+
+        Ok(DuplicateScan { roots, groups })
+    }
+
+    // Carry out a deduplication plan. Each extra is re-hashed against its keeper
+    // first, so files that changed since the plan was made are left alone, and
+    // an extra that turns out to be the keeper itself is refused. Deleted
+    // extras go through delete_path, so the trash, backups and undo apply.
+    pub async fn apply_dedupe_plan(&self, plan: &DedupePlan) -> Vec<StepResult> {
+        if let Err(e) = self.ensure_unsandboxed("Applying a dedupe plan") {
+            return plan.groups.iter().flat_map(|g| &g.extras).map(|extra| StepResult::failed(extra, e.to_string())).collect();
+        }
+        let mut results = Vec::new();
+        let mut validated = Vec::new();
+        for group in &plan.groups {
+            let keep = match self.validate_existing_path(Path::new(&group.keep)).await {
+                Ok(keep) => keep,
                 Err(e) => {
-                    match e.kind() {
-                        std::io::ErrorKind::PermissionDenied => return Err(ServiceError::PermissionDenied),
-                        _ => return Err(ServiceError::Io(e)),
+                    results.extend(group.extras.iter().map(|extra| StepResult::failed(extra, format!("keeper {}: {}", group.keep, e))));
+                    continue;
+                }
+            };
+            for extra in &group.extras {
+                let valid_extra = match self.validate_existing_path(Path::new(extra)).await {
+                    Ok(valid_extra) => valid_extra,
+                    Err(e) => {
+                        results.push(StepResult::failed(extra, e.to_string()));
+                        continue;
                     }
+                };
+                match self.check_writable(&valid_extra) {
+                    Ok(()) => validated.push((keep.clone(), extra.clone(), valid_extra)),
+                    Err(e) => results.push(StepResult::failed(extra, e.to_string())),
+                }
+            }
+        }
+
+        let action = plan.action;
+        for (keep, extra, valid_extra) in validated {
+            let (checked_keep, checked_extra) = (keep.clone(), valid_extra.clone());
+            let applied = match tokio::task::spawn_blocking(move || verify_extra(&checked_keep, &checked_extra)).await {
+                Ok(Ok(())) if action == DedupeAction::DeleteExtras => {
+                    self.delete_path(&valid_extra, DeleteMode::Trash).await.map(|_| ()).map_err(|e| e.to_string())
+                }
+                Ok(Ok(())) => self.link_extra(&keep, &valid_extra, action).await,
+                Ok(Err(e)) => Err(e),
+                Err(e) => Err(e.to_string()),
+            };
+            results.push(match applied {
+                Ok(()) => StepResult::done(&extra, action.describe(&keep)),
+                Err(e) => StepResult::failed(&extra, e),
+            });
+        }
+        results
+    }
+
+    // Replace a verified extra with a link to `keep`, after backing it up
+    // and saving a copy for undo as a write over it would
+    async fn link_extra(&self, keep: &Path, valid_extra: &Path, action: DedupeAction) -> Result<(), String> {
+        self.back_up(valid_extra, false).await.map_err(|e| e.to_string())?;
+        let step = self
+            .undo
+            .before_link(valid_extra, "find_duplicate_files")
+            .await
+            .map_err(|e| with_path(valid_extra, e).to_string())?;
+        let (keep, extra) = (keep.to_path_buf(), valid_extra.to_path_buf());
+        let linked = tokio::task::spawn_blocking(move || replace_with_link(&keep, &extra, action))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|linked| linked.map_err(|e| e.to_string()));
+        if let (Err(_), Some(step)) = (&linked, step) {
+            self.undo.discard(step);
+        }
+        linked
+    }
+
+    // Plan combining the `source` tree into `destination`; nothing is written
+    pub async fn plan_merge(
+        &self,
+        source: &Path,
+        destination: &Path,
+        exclude_patterns: &[String],
+        include_defaults_excluded: bool,
+        strategy: ConflictStrategy,
+    ) -> ServiceResult<MergePlan> {
+        let valid_source = self.validate_existing_path(source).await?;
+        let valid_destination = self.validate_path(destination).await?;
+        if !tokio::fs::metadata(&valid_source).await?.is_dir() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a directory", valid_source.display()),
+            )));
+        }
+        if valid_destination.starts_with(&valid_source) || valid_source.starts_with(&valid_destination) {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "source and destination must not contain one another",
+            )));
+        }
+        let filter = WalkFilter::new(None, exclude_patterns, include_defaults_excluded)?;
+
+        let cache = self.metadata_cache.clone();
+        tokio::task::spawn_blocking(move || {
+            MergePlan::build(&valid_source, &valid_destination, &filter, strategy, &cache)
+        })
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?
+            .map_err(ServiceError::Io)
+    }
+
+    // Carry out a merge plan in order. Every path is re-validated, and a failed
+    // directory step does not stop the remaining steps.
+    pub async fn apply_merge_plan(&self, plan: &MergePlan) -> Vec<StepResult> {
+        if let Err(e) = self.ensure_unsandboxed("Applying a merge plan") {
+            return plan
+                .steps
+                .iter()
+                .filter(|s| s.action != MergeAction::Skip)
+                .map(|step| StepResult::failed(&step.destination, e.to_string()))
+                .collect();
+        }
+        let mut results = Vec::new();
+        let mut validated = Vec::new();
+        for step in plan.steps.iter().filter(|s| s.action != MergeAction::Skip) {
+            let source = match step.action {
+                MergeAction::CreateDirectory => Ok(PathBuf::from(&step.source)),
+                _ => self.validate_existing_path(Path::new(&step.source)).await,
+            };
+            let destination = match self.validate_path(Path::new(&step.destination)).await {
+                Ok(destination) => self.check_writable(&destination).map(|()| destination),
+                Err(e) => Err(e),
+            };
+            match (source, destination) {
+                (Ok(source), Ok(destination)) => validated.push((step.clone(), source, destination)),
+                (Err(e), _) | (_, Err(e)) => results.push(StepResult::failed(&step.destination, e.to_string())),
+            }
+        }
+
+        let applied = tokio::task::spawn_blocking(move || {
+            validated
+                .into_iter()
+                .map(|(step, source, destination)| match merge::apply_step(&step, &source, &destination) {
+                    Ok(()) => StepResult::done(&step.destination, format!("{:?}: {}", step.action, step.reason)),
+                    Err(e) => StepResult::failed(&step.destination, e.to_string()),
+                })
+                .collect::<Vec<_>>()
+        })
+        .await;
+
+        match applied {
+            Ok(applied) => results.extend(applied),
+            Err(e) => log_error!("apply_merge_plan task failed: {}", e),
+        }
+        results
+    }
+
+    // chmod -R with separate file and directory modes. Entries are collected
+    // first and directories are changed deepest-first, so a restrictive
+    // directory mode cannot cut the traversal short.
+    pub async fn normalize_permissions(
+        &self,
+        path: &Path,
+        options: &NormalizePermissionsOptions,
+    ) -> ServiceResult<PermissionReport> {
+        if cfg!(not(unix)) {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "normalize_permissions requires Unix permission modes",
+            )));
+        }
+        if !options.dry_run {
+            self.ensure_unsandboxed("normalize_permissions")?;
+        }
+        let valid_path = self.validate_existing_path(path).await?;
+        if !options.dry_run {
+            self.check_writable(&valid_path)?;
+        }
+        let filter = WalkFilter::new(None, &options.exclude_patterns, options.include_defaults_excluded)?;
+        let options = options.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let mut unchanged = 0;
+            let mut planned: Vec<(PathBuf, bool, u32, u32, usize)> = Vec::new();
+            for entry in filter.walk_entries(&valid_path) {
+                let file_type = entry.file_type();
+                let target = if file_type.is_dir() {
+                    options.dir_mode
+                } else if file_type.is_file() {
+                    options.file_mode
+                } else {
+                    None
+                };
+                let (Some(target), Ok(metadata)) = (target, entry.metadata()) else {
+                    continue;
+                };
+                let current = permissions::current_mode(&metadata);
+                if current == target {
+                    unchanged += 1;
+                } else {
+                    planned.push((entry.path().to_path_buf(), file_type.is_dir(), current, target, entry.depth()));
                 }
             }
+            // Files before directories, deeper directories before their parents
+            planned.sort_by_key(|(_, is_dir, _, _, depth)| (*is_dir, std::cmp::Reverse(*depth)));
+
+            let changes = planned
+                .into_iter()
+                .map(|(path, is_dir, current, target, _)| {
+                    let error = if options.dry_run {
+                        None
+                    } else {
+                        permissions::set_mode(&path, target).err().map(|e| e.to_string())
+                    };
+                    PermissionChange {
+                        path: path.display().to_string(),
+                        is_dir,
+                        from: permissions::format_mode(current),
+                        to: permissions::format_mode(target),
+                        error,
+                    }
+                })
+                .collect();
+            PermissionReport { root: valid_path.display().to_string(), dry_run: options.dry_run, changes, unchanged }
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))
+    }
+
+    // Run `operations` in order. If a step fails, every earlier step is undone
+    // and the report says which step failed. All paths are validated before
+    // anything is touched.
+    pub async fn batch_operations(&self, operations: &[BatchOperation]) -> ServiceResult<BatchReport> {
+        self.ensure_unsandboxed("batch_operations")?;
+        let mut validated = Vec::with_capacity(operations.len());
+        for operation in operations {
+            let mut paths = Vec::new();
+            for path in operation.paths() {
+                paths.push(self.validate_path(Path::new(path)).await?);
+            }
+            let operation = (operation.describe(), ValidatedOperation::new(operation, &paths));
+            for path in operation.1.written_paths() {
+                self.check_writable(path)?;
+            }
+            validated.push(operation);
         }
 
-        Ok(formatted_diff)
+        let links = self.link_rules();
+        tokio::task::spawn_blocking(move || {
+            let mut journal = BatchJournal::new().with_link_rules(links);
+            let mut steps = Vec::with_capacity(validated.len());
+            for (index, (description, operation)) in validated.into_iter().enumerate() {
+                let result = journal.apply(&operation, index);
+                let failed = result.is_err();
+                steps.push(BatchStepReport {
+                    index,
+                    operation: description,
+                    ok: !failed,
+                    error: result.err().map(|e| e.to_string()),
+                });
+                if failed {
+                    return BatchReport {
+                        committed: false,
+                        steps,
+                        failed_step: Some(index),
+                        rollback_errors: journal.rollback(),
+                        cleanup_warnings: Vec::new(),
+                    };
+                }
+            }
+            BatchReport {
+                committed: true,
+                steps,
+                failed_step: None,
+                rollback_errors: Vec::new(),
+                cleanup_warnings: journal.commit(),
+            }
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))
     }
 
-    pub async fn generate_directory_tree(&self, path: &Path, include_hidden: bool, max_depth: u32) -> ServiceResult<String> {
-        let valid_path = self.validate_existing_path(path).await?;
-
-        let mut tree_lines = Vec::new();
-        tree_lines.push(format!("{}/", valid_path.file_name().unwrap_or_default().to_string_lossy()));
+    // Add existing files or directories (glob patterns allowed) to the staging area
+    pub async fn stage_files(&self, paths: &[String]) -> ServiceResult<StageOutcome> {
+        let expansion = self.expand_path_patterns(paths).await?;
+        let mut validated = Vec::with_capacity(expansion.paths.len());
+        for path in &expansion.paths {
+            validated.push(self.validate_existing_path(Path::new(path)).await?);
+        }
+        let added = self.staging.add(validated.clone());
+        let already_staged = validated
+            .iter()
+            .filter(|p| !added.contains(p))
+            .map(|p| p.display().to_string())
+            .collect();
+        Ok(StageOutcome {
+            added: added.iter().map(|p| p.display().to_string()).collect(),
+            already_staged,
+            total_staged: self.staging.list().len(),
+            notes: expansion.notes,
+        })
+    }
 
-        let walker = if max_depth > 0 {
-            WalkDir::new(&valid_path).max_depth(max_depth as usize)
+    // Remove paths from the staging area; an empty list clears it. Returns the
+    // paths that were removed.
+    pub async fn unstage_files(&self, paths: &[String]) -> ServiceResult<Vec<String>> {
+        let removed = if paths.is_empty() {
+            let all = self.staging.list();
+            self.staging.clear();
+            all
         } else {
-            WalkDir::new(&valid_path)
+            let mut validated = Vec::with_capacity(paths.len());
+            for path in paths {
+                validated.push(self.validate_path(Path::new(path)).await?);
+            }
+            self.staging.remove(&validated)
         };
+        Ok(removed.iter().map(|p| p.display().to_string()).collect())
+    }
 
-        for entry in walker.into_iter().filter_map(|e| e.ok()) {
-            if entry.path() == valid_path {
-                continue;
+    pub async fn list_staged(&self) -> Vec<StagedFile> {
+        let mut files = Vec::new();
+        for path in self.staging.list() {
+            let metadata = fs::metadata(&path).await.ok();
+            files.push(StagedFile {
+                path: path.display().to_string(),
+                size: metadata.as_ref().map(|m| m.len()),
+                is_dir: metadata.is_some_and(|m| m.is_dir()),
+            });
+        }
+        files
+    }
+
+    // Copy or move every staged path into `destination` as one batch, so a
+    // failure part-way leaves nothing changed. The staging area is emptied only
+    // when the batch commits.
+    pub async fn commit_staged(&self, destination: &Path, action: CommitAction) -> ServiceResult<BatchReport> {
+        let staged: Vec<String> = self.staging.list().iter().map(|p| p.display().to_string()).collect();
+        if staged.is_empty() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "Nothing is staged",
+            )));
+        }
+        let directory = self.validate_path(destination).await?;
+        let targets = targets_in(&directory, &staged)?;
+        for (source, target) in staged.iter().zip(&targets) {
+            if Path::new(source) == target {
+                return Err(ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} is already in {}", source, directory.display()),
+                )));
+            }
+        }
+        let mut existing = Vec::new();
+        for target in &targets {
+            if fs::symlink_metadata(target).await.is_ok() {
+                existing.push(target.display().to_string());
             }
+        }
+        if !existing.is_empty() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Destination already contains: {}", existing.join(", ")),
+            )));
+        }
 
-            let file_name = entry.file_name().to_string_lossy();
+        let mut operations = vec![BatchOperation::Mkdir { path: directory.display().to_string() }];
+        for (source, target) in staged.into_iter().zip(targets) {
+            let destination = target.display().to_string();
+            operations.push(match action {
+                CommitAction::Copy => BatchOperation::Copy { source, destination },
+                CommitAction::Move => BatchOperation::Move { source, destination },
+            });
+        }
+        let report = self.batch_operations(&operations).await?;
+        if report.committed {
+            self.staging.clear();
+        }
+        Ok(report)
+    }
 
-            // Skip hidden files if not requested
-            if !include_hidden && file_name.starts_with('.') {
-                continue;
-            }
+    // Everything the sandbox would change on disk, with a unified diff for each
+    // added or modified text file
+    pub async fn diff_sandbox(&self) -> ServiceResult<Vec<SandboxChange>> {
+        let sandbox = self.require_sandbox()?;
+        let pending = tokio::task::spawn_blocking(move || sandbox.pending())
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?;
+
+        let mut changes = Vec::with_capacity(pending.len());
+        for change in pending {
+            let (size, diff) = match &change.contents {
+                Some(contents) => {
+                    let after = fs::read(contents).await?;
+                    let before = if change.kind == ChangeKind::Modified { fs::read(&change.path).await? } else { Vec::new() };
+                    let diff = match (String::from_utf8(before), std::str::from_utf8(&after)) {
+                        (Ok(before), Ok(after)) => {
+                            Some(self.create_unified_diff(&before, after, Some(change.path.display().to_string())))
+                        }
+                        _ => None,
+                    };
+                    (Some(after.len() as u64), diff)
+                }
+                None => (None, None),
+            };
+            let (removed_files, removed_file_count) = match change.kind {
+                ChangeKind::Deleted | ChangeKind::ReplacedDirectory if change.path.is_dir() => {
+                    let root = change.path.clone();
+                    let files: Vec<String> = tokio::task::spawn_blocking(move || {
+                        WalkDir::new(&root)
+                            .into_iter()
+                            .flatten()
+                            .filter(|entry| !entry.file_type().is_dir())
+                            .map(|entry| entry.path().display().to_string())
+                            .collect()
+                    })
+                    .await
+                    .map_err(|e| ServiceError::Io(std::io::Error::other(e)))?;
+                    let count = files.len();
+                    (files.into_iter().take(DELETED_FILES_LISTED).collect(), Some(count))
+                }
+                _ => (Vec::new(), None),
+            };
+            changes.push(SandboxChange {
+                path: change.path.display().to_string(),
+                kind: change.kind,
+                size,
+                diff,
+                removed_files,
+                removed_file_count,
+            });
+        }
+        Ok(changes)
+    }
 
-            let depth = entry.depth();
-            let indent = "  ".repeat(depth);
+    // Write sandboxed changes at or below `paths` (all of them when empty) to disk
+    pub async fn commit_sandbox(&self, paths: &[String]) -> ServiceResult<SandboxCommit> {
+        let sandbox = self.require_sandbox()?;
+        let mut selected = Vec::with_capacity(paths.len());
+        for path in paths {
+            let valid_path = self.validate_path(Path::new(path)).await?;
+            self.check_writable(&valid_path)?;
+            selected.push(valid_path);
+        }
+        tokio::task::spawn_blocking(move || sandbox.commit(&selected))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))
+    }
 
-            if entry.file_type().is_dir() {
-                tree_lines.push(format!("{}├── {}/", indent, file_name));
-            } else {
-                tree_lines.push(format!("{}├── {}", indent, file_name));
-            }
+    // Apply a plan document produced earlier by find_duplicate_files or merge_directories
+    pub async fn apply_plan(&self, document: &PlanDocument) -> Vec<StepResult> {
+        match &document.plan {
+            Plan::Dedupe(plan) => self.apply_dedupe_plan(plan).await,
+            Plan::Merge(plan) => self.apply_merge_plan(plan).await,
         }
+    }
 
-        Ok(tree_lines.join("\n"))
+    // Zero-byte files under `path`, or with `whitespace_only` also files that hold
+    // nothing but whitespace.
+    pub async fn find_empty_files(
+        &self,
+        path: &Path,
+        exclude_patterns: Option<Vec<String>>,
+        whitespace_only: bool,
+        include_defaults_excluded: bool,
+    ) -> ServiceResult<Vec<String>> {
+        let valid_path = self.validate_existing_path(path).await?;
+        let filter = WalkFilter::new(None, &exclude_patterns.unwrap_or_default(), include_defaults_excluded)?;
+
+        tokio::task::spawn_blocking(move || {
+            filter
+                .walk_files(&valid_path)
+                .filter(|entry| is_empty_file(entry.path(), whitespace_only))
+                .map(|entry| path_text(entry.path()))
+                .collect()
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))
     }
 
-    pub async fn copy_file(&self, src_path: &Path, dest_path: &Path) -> ServiceResult<()> {
-        let valid_src_path = self.validate_existing_path(src_path).await?;
-        let valid_dest_path = self.validate_path(dest_path).await?;
+    // Delete files reported by find_empty_files, re-checking each one first so a
+    // file written to in the meantime survives.
+    pub async fn delete_empty_files(&self, paths: &[String], whitespace_only: bool) -> Vec<(String, ServiceResult<()>)> {
+        if self.sandbox.is_some() {
+            return paths.iter().map(|path| (path.clone(), self.ensure_unsandboxed("find_empty_files cleanup"))).collect();
+        }
+        let mut results = Vec::with_capacity(paths.len());
+        for path in paths {
+            let result = match self.validate_existing_path(Path::new(path)).await {
+                Ok(valid_path) if is_empty_file(&valid_path, whitespace_only) => match self.check_writable(&valid_path) {
+                    Ok(()) => fs::remove_file(&valid_path).await.map_err(|e| match e.kind() {
+                        std::io::ErrorKind::PermissionDenied => ServiceError::PermissionDenied,
+                        _ => ServiceError::Io(e),
+                    }),
+                    Err(e) => Err(e),
+                },
+                Ok(_) => Err(ServiceError::Io(std::io::Error::other("file is no longer empty"))),
+                Err(e) => Err(e),
+            };
+            results.push((path.clone(), result));
+        }
+        results
+    }
 
-        if valid_src_path.is_dir() {
-            // For directories, use recursive copy
-            self.copy_dir_recursive(&valid_src_path, &valid_dest_path).await?;
-        } else {
-            // For files, use simple copy
-            tokio::fs::copy(&valid_src_path, &valid_dest_path).await?;
+    /// The files under `root` that delete_matching would remove: those whose
+    /// name (or, for patterns with a `/`, relative path) matches the
+    /// query's pattern, which must be given
+    pub async fn preview_delete_matching(&self, root: &Path, query: &FileQuery) -> ServiceResult<DeletePreview> {
+        let pattern = query.pattern.clone().filter(|pattern| !pattern.trim().is_empty()).ok_or_else(|| {
+            ServiceError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, "delete_matching needs a pattern"))
+        })?;
+        let valid_root = self.validate_existing_path(root).await?;
+        let records = self.scan_files(&valid_root, query).await?;
+        Ok(DeletePreview::new(path_text(&valid_root), pattern, records))
+    }
+
+    /// Delete the files a preview listed, provided `confirm_token` is the
+    /// token of the preview and the matching files are still exactly those.
+    /// Each file goes as `mode` says; one failing does not stop the rest.
+    pub async fn delete_matching(
+        &self,
+        root: &Path,
+        query: &FileQuery,
+        confirm_token: &str,
+        mode: DeleteMode,
+    ) -> ServiceResult<(DeletePreview, Vec<StepResult>)> {
+        let preview = self.preview_delete_matching(root, query).await?;
+        if preview.confirm_token != confirm_token.trim() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "confirm_token does not match the files matching {} now ({} files); preview again and confirm the new list",
+                    preview.pattern,
+                    preview.files.len()
+                ),
+            )));
         }
+        let mut results = Vec::with_capacity(preview.files.len());
+        for file in &preview.files {
+            results.push(match self.delete_path(Path::new(file), mode).await {
+                Ok(true) => StepResult::done(file, "moved to the trash".to_string()),
+                Ok(false) => StepResult::done(file, "deleted".to_string()),
+                Err(e) => StepResult::failed(file, e.to_string()),
+            });
+        }
+        Ok((preview, results))
+    }
 
-        Ok(())
+    // Metadata for every file under `path` matching `query`, gathered on the
+    // blocking pool. The building block for the age and size based finders.
+    pub async fn scan_files(&self, path: &Path, query: &FileQuery) -> ServiceResult<Vec<FileRecord>> {
+        let valid_path = self.validate_existing_path(path).await?;
+        let filter = WalkFilter::new(
+            query.pattern.as_deref(),
+            &query.exclude_patterns,
+            query.include_defaults_excluded,
+        )?;
+        let query = query.clone();
+
+        tokio::task::spawn_blocking(move || {
+            filter
+                .walk_files(&valid_path)
+                .filter_map(|entry| {
+                    let metadata = entry.metadata().ok()?;
+                    query.size_matches(metadata.len()).then(|| {
+                        FileRecord::from_metadata(path_text(entry.path()), &metadata, entry.depth())
+                    })
+                })
+                .collect()
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))
     }
 
-    async fn copy_dir_recursive(&self, src: &Path, dest: &Path) -> ServiceResult<()> {
-        tokio::fs::create_dir_all(dest).await?;
+    // Files whose chosen timestamp is at least `older_than_days` old, oldest first
+    pub async fn find_stale_files(
+        &self,
+        path: &Path,
+        query: &FileQuery,
+        older_than_days: u64,
+        time_field: TimeField,
+    ) -> ServiceResult<Vec<FileRecord>> {
+        let now = std::time::SystemTime::now();
+        let mut stale: Vec<FileRecord> = self
+            .scan_files(path, query)
+            .await?
+            .into_iter()
+            .filter(|record| record.age_days(time_field, now).is_some_and(|age| age >= older_than_days))
+            .collect();
+        stale.sort_by_key(|record| record.time(time_field));
+        Ok(stale)
+    }
 
-        let mut entries = tokio::fs::read_dir(src).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            let src_path = entry.path();
-            let dest_path = dest.join(entry.file_name());
+    // Files modified at or after `since`, and up to `until` when given, newest first
+    pub async fn find_recent_changes(
+        &self,
+        path: &Path,
+        query: &FileQuery,
+        since: std::time::SystemTime,
+        until: Option<std::time::SystemTime>,
+    ) -> ServiceResult<Vec<FileRecord>> {
+        let since = chrono::DateTime::<chrono::Local>::from(since);
+        let until = until.map(chrono::DateTime::<chrono::Local>::from);
+        let mut recent: Vec<FileRecord> = self
+            .scan_files(path, query)
+            .await?
+            .into_iter()
+            .filter(|record| {
+                record.modified.is_some_and(|modified| modified >= since && until.is_none_or(|until| modified <= until))
+            })
+            .collect();
+        recent.sort_by_key(|record| std::cmp::Reverse(record.modified));
+        Ok(recent)
+    }
 
-            if src_path.is_dir() {
-                Box::pin(self.copy_dir_recursive(&src_path, &dest_path)).await?;
-            } else {
-                tokio::fs::copy(&src_path, &dest_path).await?;
-            }
-        }
+    // Files modified within [start, end], oldest first; either bound may be open
+    pub async fn find_files_by_mtime(
+        &self,
+        path: &Path,
+        query: &FileQuery,
+        start: Option<std::time::SystemTime>,
+        end: Option<std::time::SystemTime>,
+    ) -> ServiceResult<Vec<FileRecord>> {
+        let start = start.map(chrono::DateTime::<chrono::Local>::from);
+        let end = end.map(chrono::DateTime::<chrono::Local>::from);
+        let mut files: Vec<FileRecord> = self
+            .scan_files(path, query)
+            .await?
+            .into_iter()
+            .filter(|record| {
+                record.modified.is_some_and(|modified| {
+                    start.is_none_or(|start| modified >= start) && end.is_none_or(|end| modified <= end)
+                })
+            })
+            .collect();
+        FileSort::ModifiedAsc.apply(&mut files);
+        Ok(files)
+    }
 
-        Ok(())
+    // Files within the query's size range, in the requested order
+    pub async fn find_files_by_size(&self, path: &Path, query: &FileQuery, sort: FileSort) -> ServiceResult<Vec<FileRecord>> {
+        let mut files = self.scan_files(path, query).await?;
+        sort.apply(&mut files);
+        Ok(files)
     }
 
-    pub async fn delete_file(&self, file_path: &Path) -> ServiceResult<()> {
-        let valid_path = self.validate_existing_path(file_path).await?;
+    // Size, age and depth statistics for the files under `path`
+    pub async fn directory_profile(&self, path: &Path, query: &FileQuery) -> ServiceResult<DirectoryProfile> {
+        let records = self.scan_files(path, query).await?;
+        let root = path_text(&self.validate_existing_path(path).await?);
+        Ok(DirectoryProfile::from_records(root, &records, std::time::SystemTime::now()))
+    }
 
-        match if valid_path.is_dir() {
-            tokio::fs::remove_dir_all(&valid_path).await
-        } else {
-            tokio::fs::remove_file(&valid_path).await
-        } {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                match e.kind() {
-                    std::io::ErrorKind::PermissionDenied => Err(ServiceError::PermissionDenied),
-                    _ => Err(ServiceError::Io(e)),
-                }
+    // World-writable and setuid/setgid entries (Unix) or misleading read-only attributes (Windows)
+    pub async fn audit_permissions(
+        &self,
+        path: &Path,
+        exclude_patterns: &[String],
+        include_defaults_excluded: bool,
+    ) -> ServiceResult<PermissionAudit> {
+        let valid_path = self.validate_existing_path(path).await?;
+        let filter = WalkFilter::new(None, exclude_patterns, include_defaults_excluded)?;
+
+        tokio::task::spawn_blocking(move || {
+            let mut scanned_entries = 0;
+            let mut findings = Vec::new();
+            for entry in filter.walk_entries(&valid_path) {
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                scanned_entries += 1;
+                findings.extend(audit_entry(entry.path(), &metadata));
+            }
+            findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.path.cmp(&b.path)));
+            PermissionAudit { root: valid_path.display().to_string(), scanned_entries, findings }
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))
+    }
+
+    // Entries only in A, only in B, and files that differ between the two trees
+    pub async fn compare_directories(
+        &self,
+        path_a: &Path,
+        path_b: &Path,
+        exclude_patterns: &[String],
+        include_defaults_excluded: bool,
+        mode: CompareMode,
+    ) -> ServiceResult<DirectoryComparison> {
+        let valid_a = self.validate_existing_path(path_a).await?;
+        let valid_b = self.validate_existing_path(path_b).await?;
+        for path in [&valid_a, &valid_b] {
+            if !tokio::fs::metadata(path).await?.is_dir() {
+                return Err(ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} is not a directory", path.display()),
+                )));
             }
         }
+        let filter = WalkFilter::new(None, exclude_patterns, include_defaults_excluded)?;
+
+        let cache = self.metadata_cache.clone();
+        tokio::task::spawn_blocking(move || compare_trees(&valid_a, &valid_b, &filter, mode, &cache))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))
     }
 
-    // Add these new methods to the impl FileSystemService block
-    pub async fn calculate_directory_size(&self, root_path: &Path) -> ServiceResult<u64> {
-        let valid_path = self.validate_existing_path(root_path).await?;
+    // Diff two text files, or find where two binary files first differ.
+    // Text diffs load both files, so --max-read-bytes applies to them.
+    pub async fn compare_files(&self, path_a: &Path, path_b: &Path, context_lines: usize) -> ServiceResult<FileComparison> {
+        let valid_a = self.contents_path(self.validate_existing_path(path_a).await?);
+        let valid_b = self.contents_path(self.validate_existing_path(path_b).await?);
+        let (metadata_a, metadata_b) = (fs::metadata(&valid_a).await?, fs::metadata(&valid_b).await?);
+        for (path, metadata) in [(path_a, &metadata_a), (path_b, &metadata_b)] {
+            if !metadata.is_file() {
+                return Err(ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} is not a file", path.display()),
+                )));
+            }
+        }
+        let mut comparison = FileComparison {
+            path_a: path_text(path_a),
+            path_b: path_text(path_b),
+            size_a: metadata_a.len(),
+            size_b: metadata_b.len(),
+            identical: false,
+            binary: true,
+            diff: None,
+            first_difference: None,
+        };
 
-        let mut total_size = 0;
-        let mut entries = fs::read_dir(&valid_path).await?;
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            if path.is_dir() {
-                total_size += Box::pin(self.calculate_directory_size(&path)).await?;
-            } else {
-                total_size += entry.metadata().await?.len();
+        let mut samples = Vec::new();
+        for path in [&valid_a, &valid_b] {
+            let mut sample = Vec::with_capacity(SNIFF_BYTES);
+            fs::File::open(path).await?.take(SNIFF_BYTES as u64).read_to_end(&mut sample).await?;
+            samples.push(sample);
+        }
+        if samples.iter().all(|sample| looks_like_text(sample)) {
+            self.check_read_size(path_a, comparison.size_a)?;
+            self.check_read_size(path_b, comparison.size_b)?;
+            let (data_a, data_b) = (fs::read(&valid_a).await?, fs::read(&valid_b).await?);
+            metrics::record_read((data_a.len() + data_b.len()) as u64);
+            if let (Ok(text_a), Ok(text_b)) = (std::str::from_utf8(&data_a), std::str::from_utf8(&data_b)) {
+                comparison.binary = false;
+                comparison.identical = text_a == text_b;
+                if !comparison.identical {
+                    comparison.diff = Some(diff_texts(text_a, text_b, &comparison.path_a, &comparison.path_b, context_lines));
+                }
+                return Ok(comparison);
             }
         }
-        Ok(total_size)
-    }
 
-    pub async fn find_duplicate_files(
-        &self,
-        _root_path: &Path,
-        _pattern: Option<String>,
-        _exclude_patterns: Option<Vec<String>>,
-        _min_bytes: Option<u64>,
-        _max_bytes: Option<u64>,
-    ) -> ServiceResult<Vec<Vec<String>>> {
-        // Placeholder implementation
-        Ok(vec![])
+        comparison.first_difference = tokio::task::spawn_blocking(move || first_difference(&valid_a, &valid_b))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))??;
+        comparison.identical = comparison.first_difference.is_none();
+        Ok(comparison)
     }
 
     pub async fn find_empty_directories(
@@ -518,18 +3164,227 @@ impl FileSystemService {
         Ok(content.lines().skip(line_count.saturating_sub(lines)).collect::<Vec<_>>().join("\n"))
     }
 
+    // Read a window of lines without loading the whole file: forward reads stream
+    // from the start (or a byte offset), reverse reads seek backwards from EOF.
+    // `skip` lines are passed over in the reading direction before collecting.
     pub async fn read_file_lines(
         &self,
         path: &Path,
-        offset: usize,
+        origin: LineOrigin,
+        skip: usize,
         limit: Option<usize>,
-    ) -> ServiceResult<String> {
-        let content = self.read_file(path).await?;
-        let lines = content.lines().skip(offset);
-        match limit {
-            Some(l) => Ok(lines.take(l).collect::<Vec<_>>().join("\n")),
-            None => Ok(lines.collect::<Vec<_>>().join("\n")),
+    ) -> ServiceResult<LineWindow> {
+        let valid_path = self.contents_path(self.validate_existing_path(path).await?);
+        let mut file = fs::File::open(&valid_path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => ServiceError::PermissionDenied,
+            _ => ServiceError::Io(e),
+        })?;
+        let file_size = file.metadata().await?.len();
+
+        match origin {
+            LineOrigin::Start => read_lines_forward(file, file_size, 0, skip, limit).await,
+            LineOrigin::ByteOffset(start) => {
+                if start > file_size {
+                    return Err(ServiceError::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("byte_offset {} is past the end of the file ({} bytes)", start, file_size),
+                    )));
+                }
+                file.seek(SeekFrom::Start(start)).await?;
+                read_lines_forward(file, file_size, start, skip, limit).await
+            }
+            LineOrigin::End => read_lines_backward(&mut file, file_size, skip, limit).await,
+        }
+    }
+
+    // Up to `length` raw bytes starting at `offset`, or with `from_end` the
+    // `length` bytes that end `offset` bytes before the end of the file
+    pub async fn read_byte_window(
+        &self,
+        path: &Path,
+        offset: u64,
+        from_end: bool,
+        length: u64,
+    ) -> ServiceResult<hexdump::ByteWindow> {
+        let valid_path = self.contents_path(self.validate_existing_path(path).await?);
+        let mut file = fs::File::open(&valid_path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => ServiceError::PermissionDenied,
+            _ => ServiceError::Io(e),
+        })?;
+        let file_size = file.metadata().await?.len();
+        if offset > file_size {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("byte_offset {} is past the end of the file ({} bytes)", offset, file_size),
+            )));
+        }
+
+        let (start_byte, length) = if from_end {
+            let end = file_size - offset;
+            (end.saturating_sub(length), length.min(end))
+        } else {
+            (offset, length.min(file_size - offset))
+        };
+        file.seek(SeekFrom::Start(start_byte)).await?;
+        let mut bytes = Vec::with_capacity(length as usize);
+        file.take(length).read_to_end(&mut bytes).await?;
+        Ok(hexdump::ByteWindow { bytes, start_byte, file_size })
+    }
+
+    // Up to `length` bytes of text starting at `offset`, read with a seek so
+    // the rest of the file is never loaded. The window is narrowed to whole
+    // UTF-8 characters: a character cut at the start is skipped and one cut
+    // at the end is left for the next read, so consecutive windows join up.
+    pub async fn read_text_window(&self, path: &Path, offset: u64, length: u64) -> ServiceResult<hexdump::ByteWindow> {
+        let mut window = self.read_byte_window(path, offset, false, length).await?;
+        let skipped = match offset {
+            0 => 0,
+            _ => window.bytes.iter().take(3).take_while(|byte| (0x80..0xC0).contains(*byte)).count(),
+        };
+        window.bytes.drain(..skipped);
+        window.start_byte += skipped as u64;
+        if let Err(e) = std::str::from_utf8(&window.bytes) {
+            // Only an incomplete character at the very end is trimmed
+            if e.error_len().is_none() && !window.reached_eof() {
+                window.bytes.truncate(e.valid_up_to());
+            }
+        }
+        metrics::record_read(window.bytes.len() as u64);
+        Ok(window)
+    }
+
+    // Up to `max_bytes` of a text file from `cursor` (or the start), streamed
+    // from disk so a file of any size is read a bounded piece at a time. The
+    // chunk ends at a newline where there is one, and comes with the cursor
+    // for the next chunk.
+    pub async fn read_file_chunk(&self, path: &Path, cursor: Option<ReadCursor>, max_bytes: u64) -> ServiceResult<FileChunk> {
+        let valid_path = self.contents_path(self.validate_existing_path(path).await?);
+        let mut file = fs::File::open(&valid_path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::PermissionDenied => ServiceError::PermissionDenied,
+            _ => ServiceError::Io(e),
+        })?;
+        let metadata = file.metadata().await?;
+        let file_size = metadata.len();
+        let (start_byte, first_line) = match cursor {
+            Some(cursor) if !cursor.matches(&metadata) || cursor.offset > file_size => {
+                return Err(ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("{} has changed since the cursor was issued; read it again from the start", path.display()),
+                )));
+            }
+            Some(cursor) => (cursor.offset, cursor.line),
+            None => (0, 1),
+        };
+
+        file.seek(SeekFrom::Start(start_byte)).await?;
+        let mut bytes = Vec::with_capacity(max_bytes.min(file_size - start_byte) as usize);
+        tokio::io::BufReader::new(file).take(max_bytes).read_to_end(&mut bytes).await?;
+        let mut end_byte = start_byte + bytes.len() as u64;
+        if end_byte < file_size {
+            bytes.truncate(chunk_len(&bytes));
+            end_byte = start_byte + bytes.len() as u64;
+        }
+        metrics::record_read(bytes.len() as u64);
+
+        let text = decode_text(path, bytes)?;
+        let lines = text.bytes().filter(|&byte| byte == b'\n').count() as u64;
+        let next_cursor = (end_byte < file_size).then(|| ReadCursor::new(end_byte, first_line + lines, &metadata));
+        Ok(FileChunk { text, start_byte, end_byte, file_size, first_line, next_cursor })
+    }
+
+    // Cut a file into numbered parts of `split_by` bytes or lines, written to
+    // `destination` (created when missing). Existing parts are only replaced
+    // with `overwrite`. Parts are streamed, so the source is never held in memory.
+    pub async fn split_file(
+        &self,
+        path: &Path,
+        destination: &Path,
+        split_by: split::SplitBy,
+        name_template: Option<&str>,
+        overwrite: bool,
+    ) -> ServiceResult<split::SplitManifest> {
+        let valid_path = self.validate_existing_path(path).await?;
+        let contents = self.contents_path(valid_path.clone());
+        let metadata = fs::metadata(&contents).await?;
+        if !metadata.is_file() {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} is not a file", valid_path.display()),
+            )));
+        }
+        let size = metadata.len();
+        let count = {
+            let contents = contents.clone();
+            tokio::task::spawn_blocking(move || split::count_parts(&contents, size, split_by))
+                .await
+                .map_err(|e| ServiceError::Io(std::io::Error::other(e)))??
+        };
+        if count > split::MAX_SPLIT_PARTS {
+            return Err(ServiceError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("Splitting would write {} parts; at most {} are allowed", count, split::MAX_SPLIT_PARTS),
+            )));
         }
+
+        let directory = self.validate_path(destination).await?;
+        self.check_writable(&directory)?;
+        let template = name_template.unwrap_or(split::DEFAULT_PART_TEMPLATE);
+        let width = count.to_string().len().max(3);
+        let mut parts = Vec::with_capacity(count as usize);
+        for index in 1..=count {
+            let part = directory.join(split::part_name(template, &valid_path, index, width)?);
+            if part == valid_path {
+                return Err(ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Part {} would overwrite the source file", part.display()),
+                )));
+            }
+            let exists = match &self.sandbox {
+                Some(sandbox) => sandbox.lookup(&part) != Entry::Missing,
+                None => part.exists(),
+            };
+            if exists && !overwrite {
+                return Err(ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::AlreadyExists,
+                    format!("{} already exists; set overwrite to replace existing parts", part.display()),
+                )));
+            }
+            parts.push(part);
+        }
+
+        match &self.sandbox {
+            Some(sandbox) if sandbox.lookup(&directory) != Entry::Directory => sandbox.create_dir_all(&directory)?,
+            Some(_) => {}
+            None => fs::create_dir_all(&directory).await?,
+        }
+        let sandbox = self.sandbox.clone();
+        let written = tokio::task::spawn_blocking(move || split::write_parts(&contents, &parts, split_by, sandbox.as_deref()))
+            .await
+            .map_err(|e| ServiceError::Io(std::io::Error::other(e)))??;
+
+        Ok(split::SplitManifest {
+            source: valid_path.display().to_string(),
+            source_bytes: size,
+            split_by,
+            parts: written,
+        })
+    }
+
+    // 1-based number of the line starting at `byte_offset`, found by counting
+    // newlines up to that point (memory-mapped for large offsets).
+    pub async fn line_number_at(&self, path: &Path, byte_offset: u64) -> ServiceResult<usize> {
+        let valid_path = self.contents_path(self.validate_existing_path(path).await?);
+        let newlines = tokio::task::spawn_blocking(move || {
+            let file = std::fs::File::open(&valid_path)?;
+            let mut newlines = 0;
+            mmap::scan_range(&file, 0..byte_offset, |bytes| {
+                newlines += bytes.iter().filter(|b| **b == b'\n').count();
+            })?;
+            Ok::<_, std::io::Error>(newlines)
+        })
+        .await
+        .map_err(|e| ServiceError::Io(std::io::Error::other(e)))??;
+        Ok(newlines + 1)
     }
 
     pub async fn read_media_file(
@@ -537,7 +3392,10 @@ impl FileSystemService {
         path: &Path,
         _max_bytes: Option<usize>,
     ) -> ServiceResult<(infer::Type, String)> {
-        let data = tokio::fs::read(path).await?;
+        let valid_path = self.contents_path(self.validate_existing_path(path).await?);
+        self.check_read_size(path, fs::metadata(&valid_path).await?.len())?;
+        let data = tokio::fs::read(valid_path).await?;
+        metrics::record_read(data.len() as u64);
         if let Some(kind) = infer::get(&data) {
             Ok((kind, base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data)))
         } else {
@@ -575,6 +3433,344 @@ impl FileSystemService {
     }
 }
 
+/// How search_files interprets its pattern
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMatchMode {
+    /// Glob if the pattern contains `*`, `?` or `[`, substring otherwise
+    #[default]
+    Auto,
+    Glob,
+    Substring,
+}
+
+impl std::str::FromStr for SearchMatchMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Self::Auto),
+            "glob" => Ok(Self::Glob),
+            "substring" => Ok(Self::Substring),
+            other => Err(format!("Unknown match_mode '{}': expected auto, glob or substring", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilesOptions {
+    pub pattern: String,
+    pub match_mode: SearchMatchMode,
+    /// Also match files whose content contains the pattern (substring)
+    pub include_content: bool,
+    pub follow_symlinks: bool,
+    /// Descend into directories skipped by default (`.git`, `node_modules`, ...)
+    pub include_defaults_excluded: bool,
+}
+
+/// Outcome of one step when applying a dedupe or merge plan, or of one file
+/// of a delete_matching
+#[derive(Debug, Serialize)]
+pub struct StepResult {
+    pub path: String,
+    pub ok: bool,
+    pub message: String,
+}
+
+impl StepResult {
+    fn done(path: &str, message: String) -> Self {
+        Self { path: path.to_string(), ok: true, message }
+    }
+
+    fn failed(path: &str, message: String) -> Self {
+        Self { path: path.to_string(), ok: false, message }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SearchFilesOutcome {
+    pub files: Vec<String>,
+    /// Symlink loops that were detected and not followed
+    pub warnings: Vec<String>,
+}
+
+/// Where read_file_lines starts reading
+#[derive(Debug, Clone, Copy)]
+pub enum LineOrigin {
+    Start,
+    /// Count backwards from the end of the file
+    End,
+    /// Start at this byte position, e.g. the `end_byte` of a previous read
+    ByteOffset(u64),
+}
+
+#[derive(Debug)]
+pub struct LineWindow {
+    pub lines: Vec<String>,
+    /// Byte position of the first returned line
+    pub start_byte: u64,
+    /// Byte position just past the last returned line (including its newline)
+    pub end_byte: u64,
+    pub file_size: u64,
+}
+
+impl LineWindow {
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn reached_eof(&self) -> bool {
+        self.end_byte >= self.file_size
+    }
+}
+
+const REVERSE_READ_CHUNK: u64 = 8192;
+
+fn is_empty_file(path: &Path, whitespace_only: bool) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else { return false };
+    if metadata.len() == 0 {
+        return true;
+    }
+    if !whitespace_only || metadata.len() > WHITESPACE_SCAN_MAX_BYTES {
+        return false;
+    }
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .is_some_and(|content| content.trim().is_empty())
+}
+
+// A diff in a ```diff fence long enough not to be closed by backticks in it
+fn fenced_diff(diff: &str) -> String {
+    let mut num_backticks = 3;
+    while diff.contains(&"`".repeat(num_backticks)) {
+        num_backticks += 1;
+    }
+    format!("{}diff\n{}{}\n\n", "`".repeat(num_backticks), diff, "`".repeat(num_backticks))
+}
+
+// `e` with the path it concerns in front of its message
+fn unknown_watch(id: &str) -> ServiceError {
+    ServiceError::Io(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("No watch {}; start one with start_watch", id),
+    ))
+}
+
+fn with_path(path: &Path, e: std::io::Error) -> ServiceError {
+    ServiceError::Io(std::io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+}
+
+// A file read whole as text. Binary content (a NUL in the first
+// SNIFF_BYTES, or bytes that are not UTF-8) is refused with an error naming
+// its type and the tool that can show it, rather than returned mangled.
+fn decode_text(path: &Path, data: Vec<u8>) -> ServiceResult<String> {
+    let bytes = match String::from_utf8(data) {
+        Ok(text) if !text.as_bytes()[..text.len().min(SNIFF_BYTES)].contains(&0) => return Ok(text),
+        Ok(text) => text.into_bytes(),
+        Err(e) => e.into_bytes(),
+    };
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    let content_type = detect_content_type(path, sample);
+    let guess = encoding::guess_encoding(sample);
+    let utf16 = guess == encoding_rs::UTF_16LE || guess == encoding_rs::UTF_16BE;
+    let readers = match content_type.suggested_reader() {
+        reader @ ("read_media_file" | "unzip_file") => format!("{} or hexdump_file", reader),
+        // Likely text in UTF-16 or a legacy code page
+        _ if utf16 || !sample.contains(&0) => "read_file with an encoding (e.g. auto), or hexdump_file".to_string(),
+        _ => "hexdump_file".to_string(),
+    };
+    let mime_type = if content_type.is_text { "application/octet-stream".to_string() } else { content_type.mime_type };
+    Err(ServiceError::BinaryFile(path.display().to_string(), mime_type, readers))
+}
+
+fn decode_line(raw: &[u8]) -> String {
+    let raw = raw.strip_suffix(b"\n").unwrap_or(raw);
+    let raw = raw.strip_suffix(b"\r").unwrap_or(raw);
+    String::from_utf8_lossy(raw).into_owned()
+}
+
+async fn read_lines_forward(
+    file: fs::File,
+    file_size: u64,
+    start: u64,
+    skip: usize,
+    limit: Option<usize>,
+) -> ServiceResult<LineWindow> {
+    let mut reader = tokio::io::BufReader::new(file);
+    let mut position = start;
+    let mut buffer = Vec::new();
+
+    for _ in 0..skip {
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        position += read as u64;
+    }
+
+    let mut window = LineWindow { lines: Vec::new(), start_byte: position, end_byte: position, file_size };
+    while limit.is_none_or(|l| window.lines.len() < l) {
+        buffer.clear();
+        let read = reader.read_until(b'\n', &mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        window.end_byte += read as u64;
+        window.lines.push(decode_line(&buffer));
+    }
+    Ok(window)
+}
+
+async fn read_lines_backward(
+    file: &mut fs::File,
+    file_size: u64,
+    skip: usize,
+    limit: Option<usize>,
+) -> ServiceResult<LineWindow> {
+    let needed = limit.map(|l| skip + l);
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut buffer_start = file_size;
+    let mut chunk_size = REVERSE_READ_CHUNK;
+
+    // Pull chunks from the end until the buffer holds enough complete lines. The
+    // first line in the buffer may be cut off unless the buffer reaches offset 0.
+    loop {
+        let spans = line_spans(&buffer);
+        let complete = if buffer_start == 0 { spans.len() } else { spans.len().saturating_sub(1) };
+        if buffer_start == 0 || needed.is_some_and(|n| complete >= n) {
+            break;
+        }
+        let read_len = chunk_size.min(buffer_start);
+        buffer_start -= read_len;
+        let mut chunk = vec![0; read_len as usize];
+        file.seek(SeekFrom::Start(buffer_start)).await?;
+        file.read_exact(&mut chunk).await?;
+        chunk.extend_from_slice(&buffer);
+        buffer = chunk;
+        chunk_size = chunk_size.saturating_mul(2);
+    }
+
+    let mut spans = line_spans(&buffer);
+    if buffer_start > 0 && !spans.is_empty() {
+        spans.remove(0);
+    }
+    let end = spans.len().saturating_sub(skip);
+    let begin = limit.map_or(0, |l| end.saturating_sub(l));
+    let selected = &spans[begin..end];
+
+    let (start_byte, end_byte) = match (selected.first(), selected.last()) {
+        (Some(first), Some(last)) => (buffer_start + first.0 as u64, buffer_start + last.1 as u64),
+        _ => {
+            let boundary = spans.get(end).map_or(file_size, |s| buffer_start + s.0 as u64);
+            (boundary, boundary)
+        }
+    };
+    Ok(LineWindow {
+        lines: selected.iter().map(|&(s, e)| decode_line(&buffer[s..e])).collect(),
+        start_byte,
+        end_byte,
+        file_size,
+    })
+}
+
+/// Append one line per entry of `dir`, in the format generate_directory_tree
+/// has always used
+fn render_tree(dir: &Path, entries: &[TreeEntry], depth: usize, lines: &mut Vec<String>) {
+    let indent = "  ".repeat(depth);
+    for entry in entries {
+        match entry {
+            TreeEntry::Directory { name, children } => {
+                lines.push(format!("{}├── {}/", indent, name_text(&dir.join(name))));
+                render_tree(&dir.join(name), children, depth + 1, lines);
+            }
+            TreeEntry::File { name } => lines.push(format!("{}├── {}", indent, name_text(&dir.join(name)))),
+            TreeEntry::Symlink { name, target } => {
+                lines.push(format!("{}├── {} -> {}", indent, name_text(&dir.join(name)), path_text(target)))
+            }
+            TreeEntry::Loop { name, ancestor } => lines.push(format!(
+                "{}├── {}/ -> [symlink loop to {}]",
+                indent,
+                name_text(&dir.join(name)),
+                path_text(ancestor)
+            )),
+        }
+    }
+}
+
+/// (start, end) byte ranges of each line in `buffer`, with `end` past the newline
+fn line_spans(buffer: &[u8]) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for (i, byte) in buffer.iter().enumerate() {
+        if *byte == b'\n' {
+            spans.push((start, i + 1));
+            start = i + 1;
+        }
+    }
+    if start < buffer.len() {
+        spans.push((start, buffer.len()));
+    }
+    spans
+}
+
+#[derive(Debug, Default)]
+pub struct PathExpansion {
+    pub paths: Vec<String>,
+    /// Patterns that matched nothing or hit the expansion caps
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReadStatus {
+    Ok,
+    Error,
+    /// Not read because the combined size budget was reached
+    Skipped,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReadResult {
+    pub path: String,
+    pub status: ReadStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl FileReadResult {
+    fn pending(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            status: ReadStatus::Error,
+            bytes: None,
+            content: None,
+            error: Some("not read".to_string()),
+        }
+    }
+
+    fn set_content(&mut self, content: String) {
+        self.status = ReadStatus::Ok;
+        self.bytes = Some(content.len() as u64);
+        self.content = Some(content);
+        self.error = None;
+    }
+
+    fn set_error(&mut self, error: ServiceError) {
+        self.status = ReadStatus::Error;
+        self.error = Some(error.to_string());
+    }
+
+    fn set_skipped(&mut self, reason: String) {
+        self.status = ReadStatus::Skipped;
+        self.error = Some(reason);
+    }
+}
+
 // Add the FileSearchResult and Match structs
 #[derive(Debug)]
 pub struct FileSearchResult {
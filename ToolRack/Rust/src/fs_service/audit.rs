@@ -0,0 +1,122 @@
+use std::{fs::Metadata, path::Path};
+
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    /// Any user can modify the file or directory (Unix `o+w`)
+    WorldWritable,
+    /// World-writable directory without the sticky bit: anyone can delete others' files
+    WorldWritableNoSticky,
+    /// Executes with the owner's privileges
+    Setuid,
+    /// Executes with the group's privileges
+    Setgid,
+    /// Windows read-only attribute that does not mean what it appears to
+    #[cfg_attr(not(windows), allow(dead_code))]
+    ReadOnlyAttributeMismatch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Low,
+    Medium,
+    High,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionFinding {
+    pub path: String,
+    pub kind: FindingKind,
+    pub severity: Severity,
+    pub is_dir: bool,
+    /// Octal permission bits, e.g. "4755" (Unix only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    pub detail: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PermissionAudit {
+    pub root: String,
+    pub scanned_entries: usize,
+    pub findings: Vec<PermissionFinding>,
+}
+
+/// Findings for a single entry. Symlinks are never reported since their own
+/// permission bits are not used for access checks.
+pub fn audit_entry(path: &Path, metadata: &Metadata) -> Vec<PermissionFinding> {
+    if metadata.file_type().is_symlink() {
+        return Vec::new();
+    }
+    let mut findings = Vec::new();
+    let finding = |kind, severity, mode: Option<u32>, detail: &str| PermissionFinding {
+        path: path.display().to_string(),
+        kind,
+        severity,
+        is_dir: metadata.is_dir(),
+        mode: mode.map(|m| format!("{:04o}", m & 0o7777)),
+        detail: detail.to_string(),
+    };
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = metadata.permissions().mode();
+        if mode & 0o002 != 0 {
+            if metadata.is_dir() && mode & 0o1000 == 0 {
+                findings.push(finding(
+                    FindingKind::WorldWritableNoSticky,
+                    Severity::High,
+                    Some(mode),
+                    "world-writable directory without sticky bit; any user can delete or replace its entries",
+                ));
+            } else if !metadata.is_dir() {
+                findings.push(finding(
+                    FindingKind::WorldWritable,
+                    Severity::High,
+                    Some(mode),
+                    "world-writable file; any user can modify it",
+                ));
+            }
+        }
+        if metadata.is_file() && mode & 0o4000 != 0 {
+            findings.push(finding(FindingKind::Setuid, Severity::High, Some(mode), "setuid bit set"));
+        }
+        if metadata.is_file() && mode & 0o2000 != 0 {
+            findings.push(finding(FindingKind::Setgid, Severity::Medium, Some(mode), "setgid bit set"));
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        // Windows ignores the read-only attribute on directories: it neither
+        // protects the directory nor its contents
+        if metadata.is_dir() && metadata.permissions().readonly() {
+            findings.push(finding(
+                FindingKind::ReadOnlyAttributeMismatch,
+                Severity::Low,
+                None,
+                "read-only attribute on a directory is ignored by Windows and does not prevent writes",
+            ));
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    let _ = finding;
+
+    findings
+}
@@ -0,0 +1,89 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use serde::Serialize;
+
+/// Directory `--backup dir` keeps its copies in, next to the files they are of
+pub const BACKUP_DIR_NAME: &str = ".aichemist_backups";
+
+/// Whether write_file, edit_file, apply_patch and delete_file keep what they
+/// replace or remove (--backup)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BackupMode {
+    /// Keep nothing
+    #[default]
+    Off,
+    /// Keep the last version as `name.bak` beside the file
+    Sibling,
+    /// Keep every version as `.aichemist_backups/name.<UTC time>` in the
+    /// file's directory
+    Dir,
+}
+
+impl BackupMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Sibling => "sibling",
+            Self::Dir => "dir",
+        }
+    }
+}
+
+/// Where `mode` keeps a backup of `path` taken now
+fn backup_path(path: &Path, mode: BackupMode) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy();
+    match mode {
+        BackupMode::Off => None,
+        BackupMode::Sibling => Some(path.with_file_name(format!("{}.bak", name))),
+        BackupMode::Dir => {
+            let dir = path.parent()?.join(BACKUP_DIR_NAME);
+            let stamped = format!("{}.{}", name, Utc::now().format("%Y%m%dT%H%M%S%.3fZ"));
+            // Two backups within a millisecond get a counter
+            let mut backup = dir.join(&stamped);
+            let mut counter = 1;
+            while backup.symlink_metadata().is_ok() {
+                backup = dir.join(format!("{}-{}", stamped, counter));
+                counter += 1;
+            }
+            Some(backup)
+        }
+    }
+}
+
+/// Keep what is at `path` where `mode` says before it is replaced, or with
+/// `remove`, move it there rather than deleting it. Returns the backup; None
+/// when backups are off or there is no file at `path` to keep.
+pub async fn back_up(path: &Path, mode: BackupMode, remove: bool) -> io::Result<Option<PathBuf>> {
+    let metadata = match tokio::fs::symlink_metadata(path).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    if !remove && !metadata.is_file() {
+        return Ok(None);
+    }
+    let Some(backup) = backup_path(path, mode) else {
+        return Ok(None);
+    };
+    if let Some(dir) = backup.parent() {
+        tokio::fs::create_dir_all(dir).await?;
+    }
+
+    if remove {
+        // The previous sibling backup gives way, even when it is a directory
+        match tokio::fs::symlink_metadata(&backup).await {
+            Ok(old) if old.is_dir() => tokio::fs::remove_dir_all(&backup).await?,
+            Ok(_) => tokio::fs::remove_file(&backup).await?,
+            Err(_) => {}
+        }
+        tokio::fs::rename(path, &backup).await?;
+    } else {
+        tokio::fs::copy(path, &backup).await?;
+    }
+    Ok(Some(backup))
+}
@@ -0,0 +1,275 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    symlinks::{copy_link, LinkAction, LinkRules},
+    utils::{file_identity, FileIdentity},
+};
+
+/// One step of a `batch_operations` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperation {
+    Write { path: String, content: String },
+    Move { source: String, destination: String },
+    Copy { source: String, destination: String },
+    Delete { path: String },
+    Mkdir { path: String },
+}
+
+impl BatchOperation {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Write { path, content } => format!("write {} ({} bytes)", path, content.len()),
+            Self::Move { source, destination } => format!("move {} -> {}", source, destination),
+            Self::Copy { source, destination } => format!("copy {} -> {}", source, destination),
+            Self::Delete { path } => format!("delete {}", path),
+            Self::Mkdir { path } => format!("mkdir {}", path),
+        }
+    }
+
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            Self::Write { path, .. } | Self::Delete { path } | Self::Mkdir { path } => vec![path],
+            Self::Move { source, destination } | Self::Copy { source, destination } => vec![source, destination],
+        }
+    }
+}
+
+/// A batch step with its paths already validated
+#[derive(Debug, Clone)]
+pub enum ValidatedOperation {
+    Write { path: PathBuf, content: String },
+    Move { source: PathBuf, destination: PathBuf },
+    Copy { source: PathBuf, destination: PathBuf },
+    Delete { path: PathBuf },
+    Mkdir { path: PathBuf },
+}
+
+impl ValidatedOperation {
+    pub fn new(operation: &BatchOperation, validated: &[PathBuf]) -> Self {
+        match operation {
+            BatchOperation::Write { content, .. } => Self::Write { path: validated[0].clone(), content: content.clone() },
+            BatchOperation::Move { .. } => {
+                Self::Move { source: validated[0].clone(), destination: validated[1].clone() }
+            }
+            BatchOperation::Copy { .. } => {
+                Self::Copy { source: validated[0].clone(), destination: validated[1].clone() }
+            }
+            BatchOperation::Delete { .. } => Self::Delete { path: validated[0].clone() },
+            BatchOperation::Mkdir { .. } => Self::Mkdir { path: validated[0].clone() },
+        }
+    }
+
+    /// Every path the step changes; a copy leaves its source alone
+    pub fn written_paths(&self) -> Vec<&Path> {
+        match self {
+            Self::Write { path, .. } | Self::Delete { path } | Self::Mkdir { path } => vec![path],
+            Self::Move { source, destination } => vec![source, destination],
+            Self::Copy { destination, .. } => vec![destination],
+        }
+    }
+}
+
+/// How to reverse one completed change
+#[derive(Debug)]
+enum Undo {
+    /// Rename a side-by-side backup back over the original location
+    Restore { backup: PathBuf, original: PathBuf },
+    /// Remove a file or directory the batch created
+    Remove(PathBuf),
+    /// Undo a rename
+    MoveBack { from: PathBuf, to: PathBuf },
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchStepReport {
+    pub index: usize,
+    pub operation: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchReport {
+    /// True when every step succeeded and the changes were kept
+    pub committed: bool,
+    pub steps: Vec<BatchStepReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_step: Option<usize>,
+    /// Undo actions that could not be completed; these locations need manual attention
+    pub rollback_errors: Vec<String>,
+    /// Backups that could not be removed after a successful commit
+    pub cleanup_warnings: Vec<String>,
+}
+
+/// Runs batch steps while journaling how to undo each one. Files that a step
+/// replaces or deletes are renamed to hidden backups next to them rather than
+/// removed, so rolling back is a rename and committing deletes the backups.
+/// The batch is its own safety net: its deletes do not go to the trash, and
+/// nothing it does is backed up by --backup or kept for undo_last_operation.
+pub struct BatchJournal {
+    batch_id: String,
+    undo: Vec<Undo>,
+    backups: Vec<PathBuf>,
+    links: LinkRules,
+}
+
+impl Default for BatchJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchJournal {
+    pub fn new() -> Self {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+        Self {
+            batch_id: format!("{}-{:x}", std::process::id(), nanos),
+            undo: Vec::new(),
+            backups: Vec::new(),
+            links: LinkRules::default(),
+        }
+    }
+
+    /// Copy the links inside copied directories as `links` says; a link it
+    /// refuses fails the step
+    pub fn with_link_rules(mut self, links: LinkRules) -> Self {
+        self.links = links;
+        self
+    }
+
+    /// Move an existing entry out of the way, remembering how to put it back
+    fn back_up(&mut self, path: &Path, step: usize) -> io::Result<()> {
+        if fs::symlink_metadata(path).is_err() {
+            return Ok(());
+        }
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let backup = path.with_file_name(format!(".{}.batch-{}-{}.bak", file_name, self.batch_id, step));
+        fs::rename(path, &backup)?;
+        self.undo.push(Undo::Restore { backup: backup.clone(), original: path.to_path_buf() });
+        self.backups.push(backup);
+        Ok(())
+    }
+
+    pub fn apply(&mut self, operation: &ValidatedOperation, step: usize) -> io::Result<()> {
+        match operation {
+            ValidatedOperation::Write { path, content } => {
+                self.back_up(path, step)?;
+                self.undo.push(Undo::Remove(path.clone()));
+                fs::write(path, content)?;
+            }
+            ValidatedOperation::Copy { source, destination } => {
+                let metadata = fs::metadata(source)?;
+                self.back_up(destination, step)?;
+                // Journaled first so a partial copy is removed on rollback
+                self.undo.push(Undo::Remove(destination.clone()));
+                if metadata.is_dir() {
+                    copy_dir(source, destination, &self.links, &mut Vec::new())?;
+                } else {
+                    super::throttle::copy(source, destination)?;
+                }
+            }
+            ValidatedOperation::Move { source, destination } => {
+                fs::symlink_metadata(source)?;
+                self.back_up(destination, step)?;
+                fs::rename(source, destination)?;
+                self.undo.push(Undo::MoveBack { from: destination.clone(), to: source.clone() });
+            }
+            ValidatedOperation::Delete { path } => {
+                fs::symlink_metadata(path)?;
+                self.back_up(path, step)?;
+            }
+            ValidatedOperation::Mkdir { path } => {
+                if path.is_dir() {
+                    return Ok(());
+                }
+                let first_missing = path
+                    .ancestors()
+                    .take_while(|p| fs::symlink_metadata(p).is_err())
+                    .last()
+                    .unwrap_or(path)
+                    .to_path_buf();
+                fs::create_dir_all(path)?;
+                self.undo.push(Undo::Remove(first_missing));
+            }
+        }
+        Ok(())
+    }
+
+    /// Undo every journaled change, newest first. Returns what could not be undone.
+    pub fn rollback(self) -> Vec<String> {
+        let mut errors = Vec::new();
+        for undo in self.undo.into_iter().rev() {
+            let result = match &undo {
+                Undo::Restore { backup, original } => fs::rename(backup, original),
+                Undo::Remove(path) => remove_any(path),
+                Undo::MoveBack { from, to } => fs::rename(from, to),
+            };
+            if let Err(e) = result {
+                errors.push(format!("{:?}: {}", undo, e));
+            }
+        }
+        errors
+    }
+
+    /// Keep the changes and delete the backups. Returns backups that could not be removed.
+    pub fn commit(self) -> Vec<String> {
+        self.backups
+            .iter()
+            .filter_map(|backup| remove_any(backup).err().map(|e| format!("{}: {}", backup.display(), e)))
+            .collect()
+    }
+}
+
+fn remove_any(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+// `ancestors` are the directories being copied, so a followed link back up
+// to one of them fails the step instead of recursing forever
+fn copy_dir(source: &Path, destination: &Path, links: &LinkRules, ancestors: &mut Vec<FileIdentity>) -> io::Result<()> {
+    let identity = file_identity(source)?;
+    if ancestors.contains(&identity) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is a symlink loop back to a directory already being copied", source.display()),
+        ));
+    }
+    ancestors.push(identity);
+    fs::create_dir(destination)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        let mut file_type = entry.file_type()?;
+        if file_type.is_symlink() {
+            match links.action(&entry.path()) {
+                // What the link points at decides whether to recurse
+                LinkAction::Follow => file_type = fs::metadata(entry.path())?.file_type(),
+                LinkAction::Keep => {
+                    copy_link(&entry.path(), &target)?;
+                    continue;
+                }
+                LinkAction::Skip => return Err(io::Error::new(io::ErrorKind::PermissionDenied, links.skip_reason(&entry.path()))),
+            }
+        }
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target, links, ancestors)?;
+        } else {
+            super::throttle::copy(&entry.path(), &target)?;
+        }
+    }
+    ancestors.pop();
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::scan::FileRecord;
+
+/// The files a delete_matching call would remove. `confirm_token` stands for
+/// exactly this list, sizes included, so the deleting call can check that
+/// nothing was added or changed since the preview.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeletePreview {
+    pub root: String,
+    pub pattern: String,
+    pub files: Vec<String>,
+    pub total_bytes: u64,
+    pub confirm_token: String,
+}
+
+impl DeletePreview {
+    pub fn new(root: String, pattern: String, mut records: Vec<FileRecord>) -> Self {
+        records.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut hasher = Sha256::new();
+        for part in [&root, &pattern] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        for record in &records {
+            hasher.update(record.path.as_bytes());
+            hasher.update(record.size.to_le_bytes());
+        }
+        let digest = hasher.finalize();
+        Self {
+            root,
+            pattern,
+            total_bytes: records.iter().map(|record| record.size).sum(),
+            files: records.into_iter().map(|record| record.path).collect(),
+            confirm_token: digest[..8].iter().map(|byte| format!("{:02x}", byte)).collect(),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use std::{fs::Metadata, io, path::Path};
+
+#[cfg(feature = "metadata-cache")]
+use std::{
+    sync::{Arc, Mutex},
+    time::UNIX_EPOCH,
+};
+
+use super::duplicates::{content_hash, hash_file};
+
+/// Optional on-disk cache of file size, mtime and content hashes keyed by path.
+/// A cached hash is reused only while the file's size and mtime still match,
+/// so repeated scans of a mostly unchanged tree hash only what changed. A
+/// disabled cache hashes every time. Cloning shares the same database.
+#[derive(Clone, Default)]
+pub struct MetadataCache {
+    #[cfg(feature = "metadata-cache")]
+    connection: Option<Arc<Mutex<rusqlite::Connection>>>,
+}
+
+#[cfg(feature = "metadata-cache")]
+fn sql_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(format!("metadata cache: {}", e))
+}
+
+impl MetadataCache {
+    /// Open (or create) the cache database at `path`
+    #[cfg(feature = "metadata-cache")]
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let connection = rusqlite::Connection::open(path).map_err(sql_error)?;
+        connection
+            .execute_batch(
+                "PRAGMA journal_mode = WAL;
+                 CREATE TABLE IF NOT EXISTS files (
+                     path     TEXT PRIMARY KEY,
+                     size     INTEGER NOT NULL,
+                     mtime_ns INTEGER NOT NULL,
+                     sha256   TEXT NOT NULL
+                 );
+                 CREATE TABLE IF NOT EXISTS content_hashes (
+                     path     TEXT PRIMARY KEY,
+                     size     INTEGER NOT NULL,
+                     mtime_ns INTEGER NOT NULL,
+                     blake3   TEXT NOT NULL
+                 );",
+            )
+            .map_err(sql_error)?;
+        Ok(Self { connection: Some(Arc::new(Mutex::new(connection))) })
+    }
+
+    #[cfg(not(feature = "metadata-cache"))]
+    pub fn open(_path: &Path) -> io::Result<Self> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "this build does not include the metadata-cache feature",
+        ))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        #[cfg(feature = "metadata-cache")]
+        return self.connection.is_some();
+        #[cfg(not(feature = "metadata-cache"))]
+        false
+    }
+
+    /// SHA-256 of `path`, served from the cache when `metadata` still matches
+    /// the recorded size and mtime. Cache failures fall back to hashing.
+    pub fn hash_file(&self, path: &Path, metadata: &Metadata) -> io::Result<String> {
+        self.cached(("files", "sha256"), path, metadata, hash_file)
+    }
+
+    /// Hash `path`, reading its metadata first
+    pub fn hash_path(&self, path: &Path) -> io::Result<String> {
+        self.hash_file(path, &std::fs::metadata(path)?)
+    }
+
+    /// BLAKE3 of `path`, which duplicates are grouped by, cached like
+    /// [`hash_file`](Self::hash_file)
+    pub fn content_hash_path(&self, path: &Path) -> io::Result<String> {
+        self.cached(("content_hashes", "blake3"), path, &std::fs::metadata(path)?, content_hash)
+    }
+
+    // `hash` of `path`, looked up in and recorded to `column` of `table`
+    fn cached(
+        &self,
+        (table, column): (&str, &str),
+        path: &Path,
+        metadata: &Metadata,
+        hash: fn(&Path) -> io::Result<String>,
+    ) -> io::Result<String> {
+        #[cfg(feature = "metadata-cache")]
+        if let Some(connection) = &self.connection {
+            let Some(mtime_ns) = mtime_ns(metadata) else {
+                return hash(path);
+            };
+            let key = path.to_string_lossy();
+            let size = metadata.len() as i64;
+            let cached = connection
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .query_row(
+                    &format!("SELECT {} FROM {} WHERE path = ?1 AND size = ?2 AND mtime_ns = ?3", column, table),
+                    rusqlite::params![key, size, mtime_ns],
+                    |row| row.get::<_, String>(0),
+                )
+                .ok();
+            if let Some(hash) = cached {
+                return Ok(hash);
+            }
+            // Hash outside the lock so parallel callers only serialize on the database
+            let hash = hash(path)?;
+            let _ = connection.lock().unwrap_or_else(|e| e.into_inner()).execute(
+                &format!("INSERT OR REPLACE INTO {} (path, size, mtime_ns, {}) VALUES (?1, ?2, ?3, ?4)", table, column),
+                rusqlite::params![key, size, mtime_ns, hash],
+            );
+            return Ok(hash);
+        }
+        let _ = (table, column, metadata);
+        hash(path)
+    }
+}
+
+#[cfg(feature = "metadata-cache")]
+fn mtime_ns(metadata: &Metadata) -> Option<i64> {
+    let since_epoch = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    i64::try_from(since_epoch.as_nanos()).ok()
+}
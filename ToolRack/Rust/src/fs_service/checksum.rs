@@ -0,0 +1,54 @@
+use std::{fs, io, path::Path};
+
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+
+use super::{mmap::scan_range, throttle::visit_throttled};
+
+/// Hash functions checksum_file and checksum_files offer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Sha256,
+    Sha512,
+    /// Only for matching published MD5 sums; not collision resistant
+    Md5,
+}
+
+impl HashAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Md5 => "md5",
+        }
+    }
+}
+
+/// Lowercase hex digest of a file's content, streamed through `algorithm`
+pub fn checksum_file(path: &Path, algorithm: HashAlgorithm) -> io::Result<String> {
+    match algorithm {
+        HashAlgorithm::Sha256 => digest_file::<Sha256>(path),
+        HashAlgorithm::Sha512 => digest_file::<Sha512>(path),
+        HashAlgorithm::Md5 => digest_file::<Md5>(path),
+    }
+}
+
+fn digest_file<D: Digest>(path: &Path) -> io::Result<String> {
+    let file = fs::File::open(path)?;
+    let mut hasher = D::new();
+    scan_range(&file, 0..u64::MAX, |bytes| visit_throttled(bytes, |chunk| hasher.update(chunk)))?;
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// One entry of a checksum_files result
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChecksum {
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
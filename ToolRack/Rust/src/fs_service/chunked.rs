@@ -0,0 +1,100 @@
+use std::{fmt, io, str::FromStr, time::UNIX_EPOCH};
+
+use serde::Serialize;
+
+/// Where the next chunk of a file starts, as handed back to the client by
+/// read_file. It also records the file's modification time, so a cursor
+/// into a file that has since changed is refused rather than resumed at an
+/// offset that may now be in the middle of a character or a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadCursor {
+    pub offset: u64,
+    /// 1-based number of the line starting at `offset`
+    pub line: u64,
+    modified: u128,
+}
+
+impl ReadCursor {
+    pub fn new(offset: u64, line: u64, metadata: &std::fs::Metadata) -> Self {
+        Self { offset, line, modified: Self::stamp(metadata) }
+    }
+
+    /// Whether `metadata` is of the file as it was when the cursor was made
+    pub fn matches(&self, metadata: &std::fs::Metadata) -> bool {
+        self.modified == Self::stamp(metadata)
+    }
+
+    fn stamp(metadata: &std::fs::Metadata) -> u128 {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_nanos())
+    }
+}
+
+/// `offset.line.modified`, the last in hex
+impl fmt::Display for ReadCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{:x}", self.offset, self.line, self.modified)
+    }
+}
+
+impl FromStr for ReadCursor {
+    type Err = io::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid cursor '{}': pass back the cursor a previous read_file returned", text),
+            )
+        };
+        let mut parts = text.trim().split('.');
+        let (Some(offset), Some(line), Some(modified), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        Ok(Self {
+            offset: offset.parse().map_err(|_| invalid())?,
+            line: line.parse().map_err(|_| invalid())?,
+            modified: u128::from_str_radix(modified, 16).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// One bounded piece of a text file, ending at a newline where the file has
+/// one within the limit
+#[derive(Debug, Clone, Serialize)]
+pub struct FileChunk {
+    #[serde(skip)]
+    pub text: String,
+    /// Position of the first byte in the file
+    pub start_byte: u64,
+    /// Position just past the last byte
+    pub end_byte: u64,
+    pub file_size: u64,
+    /// 1-based number of the first line
+    pub first_line: u64,
+    /// Where to continue; None once the end of the file is reached
+    #[serde(skip_serializing_if = "Option::is_none", serialize_with = "serialize_cursor")]
+    pub next_cursor: Option<ReadCursor>,
+}
+
+fn serialize_cursor<S: serde::Serializer>(cursor: &Option<ReadCursor>, serializer: S) -> Result<S::Ok, S::Error> {
+    match cursor {
+        Some(cursor) => serializer.collect_str(cursor),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// How much of `bytes`, read from a longer file, makes a chunk: up to the last
+/// newline, or without one, up to the last whole UTF-8 character
+pub fn chunk_len(bytes: &[u8]) -> usize {
+    if let Some(newline) = bytes.iter().rposition(|&byte| byte == b'\n') {
+        return newline + 1;
+    }
+    match std::str::from_utf8(bytes) {
+        Err(e) if e.error_len().is_none() => e.valid_up_to(),
+        _ => bytes.len(),
+    }
+}
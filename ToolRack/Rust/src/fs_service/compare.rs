@@ -0,0 +1,229 @@
+use std::{
+    collections::BTreeMap,
+    fs::{File, Metadata},
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+use rayon::prelude::*;
+use serde::Serialize;
+use similar::TextDiff;
+
+use super::{cache::MetadataCache, walk::WalkFilter};
+
+/// Modification times closer than this are treated as equal, since copies
+/// across filesystems often lose sub-second precision
+const MTIME_TOLERANCE_SECS: u64 = 1;
+
+/// How files present on both sides are compared
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompareMode {
+    /// Size and modification time
+    #[default]
+    Metadata,
+    /// Size, then SHA-256 of the content
+    Hash,
+}
+
+impl FromStr for CompareMode {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "metadata" => Ok(Self::Metadata),
+            "hash" => Ok(Self::Hash),
+            other => Err(format!("Unknown compare mode '{}': expected metadata or hash", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DifferingEntry {
+    /// Path relative to both roots, '/'-separated
+    pub path: String,
+    /// "type", "size", "modified" or "content"
+    pub reason: &'static str,
+    pub size_a: Option<u64>,
+    pub size_b: Option<u64>,
+    pub modified_a: Option<DateTime<Local>>,
+    pub modified_b: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryComparison {
+    pub path_a: String,
+    pub path_b: String,
+    pub mode: CompareMode,
+    /// Entries missing from B. A missing directory is listed once, without its contents.
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    pub differing: Vec<DifferingEntry>,
+    pub identical_files: usize,
+}
+
+impl DirectoryComparison {
+    pub fn is_identical(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.differing.is_empty()
+    }
+}
+
+/// Two files compared: a unified diff when both are text, the first
+/// differing byte otherwise
+#[derive(Debug, Serialize)]
+pub struct FileComparison {
+    pub path_a: String,
+    pub path_b: String,
+    pub size_a: u64,
+    pub size_b: u64,
+    pub identical: bool,
+    /// Compared byte by byte rather than diffed as text
+    pub binary: bool,
+    /// Unified diff from A to B, for text files that differ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Offset of the first differing byte, for binary files that differ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_difference: Option<u64>,
+}
+
+/// Unified diff from `a` to `b` with `context` unchanged lines around each
+/// change; empty when the texts are equal
+pub fn diff_texts(a: &str, b: &str, label_a: &str, label_b: &str, context: usize) -> String {
+    TextDiff::from_lines(a, b).unified_diff().context_radius(context).header(label_a, label_b).to_string()
+}
+
+/// Offset of the first byte where two files differ, or where the shorter one
+/// ends; None when their content is the same. Both are streamed.
+pub fn first_difference(a: &Path, b: &Path) -> io::Result<Option<u64>> {
+    let (mut a, mut b) = (BufReader::new(File::open(a)?), BufReader::new(File::open(b)?));
+    let mut offset = 0;
+    loop {
+        let (chunk_a, chunk_b) = (a.fill_buf()?, b.fill_buf()?);
+        let len = chunk_a.len().min(chunk_b.len());
+        if len == 0 {
+            return Ok((chunk_a.len() != chunk_b.len()).then_some(offset));
+        }
+        if let Some(index) = chunk_a[..len].iter().zip(&chunk_b[..len]).position(|(x, y)| x != y) {
+            return Ok(Some(offset + index as u64));
+        }
+        a.consume(len);
+        b.consume(len);
+        offset += len as u64;
+    }
+}
+
+struct Entry {
+    path: PathBuf,
+    metadata: Metadata,
+}
+
+fn collect_entries(root: &Path, filter: &WalkFilter) -> BTreeMap<String, Entry> {
+    filter
+        .walk_entries(root)
+        .filter(|e| e.depth() > 0)
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            let relative = e.path().strip_prefix(root).ok()?.to_string_lossy().replace('\\', "/");
+            Some((relative, Entry { path: e.path().to_path_buf(), metadata }))
+        })
+        .collect()
+}
+
+/// Keys of `side` missing from `other`, dropping those under an already listed directory
+fn only_in(side: &BTreeMap<String, Entry>, other: &BTreeMap<String, Entry>) -> Vec<String> {
+    let mut missing: Vec<String> = Vec::new();
+    for key in side.keys().filter(|k| !other.contains_key(*k)) {
+        let covered = missing
+            .last()
+            .is_some_and(|dir| key.strip_prefix(dir.as_str()).is_some_and(|rest| rest.starts_with('/')));
+        if !covered {
+            missing.push(key.clone());
+        }
+    }
+    missing
+}
+
+fn mtimes_differ(a: Option<SystemTime>, b: Option<SystemTime>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let delta = a.duration_since(b).or_else(|_| b.duration_since(a)).unwrap_or_default();
+            delta.as_secs() >= MTIME_TOLERANCE_SECS
+        }
+        (a, b) => a.is_some() != b.is_some(),
+    }
+}
+
+pub fn compare_trees(
+    path_a: &Path,
+    path_b: &Path,
+    filter: &WalkFilter,
+    mode: CompareMode,
+    cache: &MetadataCache,
+) -> DirectoryComparison {
+    let entries_a = collect_entries(path_a, filter);
+    let entries_b = collect_entries(path_b, filter);
+
+    let differing_entry = |path: &str, reason, a: &Entry, b: &Entry| DifferingEntry {
+        path: path.to_string(),
+        reason,
+        size_a: a.metadata.is_file().then_some(a.metadata.len()),
+        size_b: b.metadata.is_file().then_some(b.metadata.len()),
+        modified_a: a.metadata.modified().ok().map(DateTime::from),
+        modified_b: b.metadata.modified().ok().map(DateTime::from),
+    };
+
+    let mut differing = Vec::new();
+    let mut to_hash = Vec::new();
+    let mut identical_files = 0;
+    for (key, a) in &entries_a {
+        let Some(b) = entries_b.get(key) else {
+            continue;
+        };
+        if a.metadata.is_dir() != b.metadata.is_dir() {
+            differing.push(differing_entry(key, "type", a, b));
+        } else if a.metadata.is_dir() {
+            continue;
+        } else if a.metadata.len() != b.metadata.len() {
+            differing.push(differing_entry(key, "size", a, b));
+        } else if mode == CompareMode::Hash {
+            to_hash.push((key, a, b));
+        } else if mtimes_differ(a.metadata.modified().ok(), b.metadata.modified().ok()) {
+            differing.push(differing_entry(key, "modified", a, b));
+        } else {
+            identical_files += 1;
+        }
+    }
+
+    let hashed: Vec<_> = to_hash
+        .par_iter()
+        .map(|(key, a, b)| {
+            let same = matches!(
+                (cache.hash_file(&a.path, &a.metadata), cache.hash_file(&b.path, &b.metadata)),
+                (Ok(x), Ok(y)) if x == y
+            );
+            (key, a, b, same)
+        })
+        .collect();
+    for (key, a, b, same) in hashed {
+        if same {
+            identical_files += 1;
+        } else {
+            differing.push(differing_entry(key, "content", a, b));
+        }
+    }
+    differing.sort_by(|x, y| x.path.cmp(&y.path));
+
+    DirectoryComparison {
+        path_a: path_a.display().to_string(),
+        path_b: path_b.display().to_string(),
+        mode,
+        only_in_a: only_in(&entries_a, &entries_b),
+        only_in_b: only_in(&entries_b, &entries_a),
+        differing,
+        identical_files,
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::throttle::ThrottledReader;
+
+/// Most inputs one concat_files call joins, globs included
+pub const CONCAT_MAX_FILES: usize = 10_000;
+
+/// One joined input, in output order
+#[derive(Debug, Clone, Serialize)]
+pub struct ConcatInput {
+    pub path: String,
+    pub bytes: u64,
+}
+
+/// What concat_files wrote
+#[derive(Debug, Clone, Serialize)]
+pub struct ConcatReport {
+    pub output: String,
+    pub bytes: u64,
+    pub sha256: String,
+    pub inputs: Vec<ConcatInput>,
+    /// Glob patterns that matched nothing or hit the file cap
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+}
+
+/// Copy each of `inputs` into `output` in order, with `separator` between
+/// them. `inputs` pairs each reported path with where its bytes are read
+/// from. Returns per-input sizes, the total and the SHA-256 of the output.
+pub fn write_joined<W: Write>(
+    output: &mut W,
+    inputs: &[(String, PathBuf)],
+    separator: &[u8],
+) -> io::Result<(Vec<ConcatInput>, u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut total = 0u64;
+    let mut joined = Vec::with_capacity(inputs.len());
+    let mut buffer = vec![0u8; 64 * 1024];
+    for (index, (path, contents)) in inputs.iter().enumerate() {
+        if index > 0 && !separator.is_empty() {
+            hasher.update(separator);
+            output.write_all(separator)?;
+            total += separator.len() as u64;
+        }
+        let mut file = std::fs::File::open(contents)
+            .map(ThrottledReader::new)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        let mut bytes = 0u64;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+            output.write_all(&buffer[..read])?;
+            bytes += read as u64;
+        }
+        total += bytes;
+        joined.push(ConcatInput { path: path.clone(), bytes });
+    }
+    output.flush()?;
+    Ok((joined, total, format!("{:x}", hasher.finalize())))
+}
+
+/// Join `inputs` into a temporary sibling of `destination` and rename it
+/// into place, so readers never see a half-written output
+pub fn write_joined_atomically(
+    destination: &Path,
+    inputs: &[(String, PathBuf)],
+    separator: &[u8],
+) -> io::Result<(Vec<ConcatInput>, u64, String)> {
+    let file_name = destination.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = destination.with_file_name(format!(".{}.concat-tmp", file_name));
+    let written = (|| {
+        let mut writer = io::BufWriter::new(std::fs::File::create(&temp_path)?);
+        let joined = write_joined(&mut writer, inputs, separator)?;
+        writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        std::fs::rename(&temp_path, destination)?;
+        Ok(joined)
+    })();
+    written.inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })
+}
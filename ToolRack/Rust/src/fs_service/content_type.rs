@@ -0,0 +1,146 @@
+use std::path::Path;
+
+/// Number of leading bytes inspected when sniffing file content
+pub const SNIFF_BYTES: usize = 8192;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MimeSource {
+    /// Detected from magic bytes in the file header
+    Magic,
+    /// Looked up from the file extension
+    Extension,
+    /// Neither matched; guessed from the text/binary classification
+    Fallback,
+}
+
+impl MimeSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MimeSource::Magic => "magic bytes",
+            MimeSource::Extension => "extension",
+            MimeSource::Fallback => "fallback",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentType {
+    pub mime_type: String,
+    pub source: MimeSource,
+    pub is_text: bool,
+}
+
+impl ContentType {
+    /// Which tool an agent should use to look at the content
+    pub fn suggested_reader(&self) -> &'static str {
+        if self.is_text {
+            return "read_file";
+        }
+        if self.mime_type.starts_with("image/") || self.mime_type.starts_with("audio/") {
+            return "read_media_file";
+        }
+        match self.mime_type.as_str() {
+            "application/zip" => "unzip_file",
+            _ => "none (binary content)",
+        }
+    }
+}
+
+pub fn mime_from_extension(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    let mime = match extension.as_str() {
+        "txt" | "log" | "text" => "text/plain",
+        "md" | "markdown" => "text/markdown",
+        "csv" => "text/csv",
+        "tsv" => "text/tab-separated-values",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "xml" => "application/xml",
+        "json" => "application/json",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        "ini" | "cfg" | "conf" => "text/plain",
+        "js" | "mjs" | "cjs" => "text/javascript",
+        "ts" | "tsx" => "text/typescript",
+        "jsx" => "text/jsx",
+        "py" => "text/x-python",
+        "rs" => "text/x-rust",
+        "go" => "text/x-go",
+        "java" => "text/x-java",
+        "c" | "h" => "text/x-c",
+        "cpp" | "cc" | "cxx" | "hpp" => "text/x-c++",
+        "cs" => "text/x-csharp",
+        "rb" => "text/x-ruby",
+        "php" => "text/x-php",
+        "sh" | "bash" | "zsh" => "application/x-sh",
+        "ps1" => "text/x-powershell",
+        "bat" | "cmd" => "text/x-msdos-batch",
+        "sql" => "application/sql",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        "ico" => "image/vnd.microsoft.icon",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "flac" => "audio/flac",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "tar" => "application/x-tar",
+        "7z" => "application/x-7z-compressed",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "pptx" => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        "exe" | "dll" => "application/vnd.microsoft.portable-executable",
+        "wasm" => "application/wasm",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// Heuristic text check: no NUL bytes and valid UTF-8 (a multi-byte character
+/// cut off at the end of the sample is tolerated).
+pub fn looks_like_text(sample: &[u8]) -> bool {
+    if sample.contains(&0) {
+        return false;
+    }
+    match std::str::from_utf8(sample) {
+        Ok(_) => true,
+        Err(e) => e.error_len().is_none(),
+    }
+}
+
+/// Classify content from a header sample using magic bytes first and the
+/// extension map second.
+pub fn detect_content_type(path: &Path, sample: &[u8]) -> ContentType {
+    let is_text = looks_like_text(sample);
+
+    if let Some(kind) = infer::get(sample) {
+        let is_text = is_text && kind.matcher_type() == infer::MatcherType::Text;
+        return ContentType {
+            mime_type: kind.mime_type().to_string(),
+            source: MimeSource::Magic,
+            is_text,
+        };
+    }
+
+    if let Some(mime) = mime_from_extension(path) {
+        return ContentType {
+            mime_type: mime.to_string(),
+            source: MimeSource::Extension,
+            is_text,
+        };
+    }
+
+    ContentType {
+        mime_type: if is_text { "text/plain" } else { "application/octet-stream" }.to_string(),
+        source: MimeSource::Fallback,
+        is_text,
+    }
+}
@@ -0,0 +1,24 @@
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub struct DiskSpace {
+    /// Path whose filesystem was queried (deepest existing ancestor of the requested path)
+    pub queried_path: PathBuf,
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+}
+
+impl DiskSpace {
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    pub fn used_percent(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.used() as f64 / self.total as f64 * 100.0
+        }
+    }
+}
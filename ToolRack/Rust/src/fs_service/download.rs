@@ -0,0 +1,86 @@
+use serde::Serialize;
+
+/// Largest download accepted unless overridden with --download-max-bytes
+pub const DEFAULT_DOWNLOAD_MAX_BYTES: u64 = 100 * 1024 * 1024;
+
+/// Which URLs download_file may fetch. Each allowlist entry is either a domain
+/// (`example.com`, which also admits its subdomains) or a URL prefix
+/// (`https://example.com/releases/`). An empty allowlist disables downloads.
+#[derive(Debug, Clone)]
+pub struct DownloadPolicy {
+    pub allowed: Vec<String>,
+    pub max_bytes: u64,
+}
+
+impl Default for DownloadPolicy {
+    fn default() -> Self {
+        Self { allowed: Vec::new(), max_bytes: DEFAULT_DOWNLOAD_MAX_BYTES }
+    }
+}
+
+impl DownloadPolicy {
+    pub fn new(allowed: &[String], max_bytes: u64) -> Self {
+        Self {
+            allowed: allowed.iter().map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect(),
+            max_bytes,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.allowed.is_empty()
+    }
+
+    /// Ok when `url` is an http(s) URL admitted by the allowlist, otherwise why not
+    pub fn check(&self, url: &str) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Err("Downloads are disabled; start the server with --download-allow to permit domains".to_string());
+        }
+        let Some((scheme, rest)) = url.split_once("://") else {
+            return Err(format!("Not an absolute URL: {}", url));
+        };
+        let scheme = scheme.to_ascii_lowercase();
+        if scheme != "http" && scheme != "https" {
+            return Err(format!("Only http and https URLs can be downloaded, not {}", scheme));
+        }
+        let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        if authority.contains('@') {
+            return Err("URLs with embedded credentials are not downloaded".to_string());
+        }
+        let host = host_of(authority).to_ascii_lowercase();
+
+        let allowed = self.allowed.iter().any(|entry| {
+            if entry.contains("://") {
+                url.to_ascii_lowercase().starts_with(&entry.to_ascii_lowercase())
+            } else {
+                let domain = entry.trim_start_matches("*.").to_ascii_lowercase();
+                host == domain || host.ends_with(&format!(".{}", domain))
+            }
+        });
+        if allowed {
+            Ok(())
+        } else {
+            Err(format!("{} is not in the download allowlist ({})", url, self.allowed.join(", ")))
+        }
+    }
+}
+
+/// Host part of a URL authority, without port or IPv6 brackets
+fn host_of(authority: &str) -> &str {
+    if let Some(bracketed) = authority.strip_prefix('[') {
+        return bracketed.split(']').next().unwrap_or_default();
+    }
+    authority.split(':').next().unwrap_or_default()
+}
+
+/// What download_file fetched and where it went
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadReport {
+    pub url: String,
+    /// The URL the content came from after redirects
+    pub final_url: String,
+    pub path: String,
+    pub bytes: u64,
+    pub sha256: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+}
@@ -0,0 +1,219 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{mmap::scan_range, throttle::visit_throttled, utils::file_identity};
+
+/// What to do with the extra copies in a duplicate group
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupeAction {
+    /// Replace extras with hard links to the kept file
+    Hardlink,
+    /// Replace extras with symbolic links to the kept file
+    Symlink,
+    /// Remove extras, leaving only the kept file
+    DeleteExtras,
+}
+
+impl FromStr for DedupeAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "hardlink" => Ok(Self::Hardlink),
+            "symlink" => Ok(Self::Symlink),
+            "delete_extras" => Ok(Self::DeleteExtras),
+            other => Err(format!("Unknown action '{}': expected hardlink, symlink or delete_extras", other)),
+        }
+    }
+}
+
+impl DedupeAction {
+    /// Past-tense summary of what happened to an extra
+    pub fn describe(self, keep: &Path) -> String {
+        match self {
+            Self::Hardlink => format!("hard-linked to {}", keep.display()),
+            Self::Symlink => format!("symlinked to {}", keep.display()),
+            Self::DeleteExtras => format!("deleted (kept {})", keep.display()),
+        }
+    }
+}
+
+/// Which file of a duplicate group survives
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeeperRule {
+    /// Earliest modification time
+    Oldest,
+    /// Fewest characters in the path, ties broken alphabetically
+    #[default]
+    ShortestPath,
+}
+
+impl FromStr for KeeperRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "oldest" => Ok(Self::Oldest),
+            "shortest_path" => Ok(Self::ShortestPath),
+            other => Err(format!("Unknown keep rule '{}': expected oldest or shortest_path", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupeGroupPlan {
+    pub keep: String,
+    pub extras: Vec<String>,
+    /// Size of each file in the group
+    pub file_size: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupePlan {
+    pub action: DedupeAction,
+    pub keep_rule: KeeperRule,
+    pub groups: Vec<DedupeGroupPlan>,
+    pub reclaimable_bytes: u64,
+}
+
+impl DedupePlan {
+    pub fn build(groups: &[Vec<String>], action: DedupeAction, keep_rule: KeeperRule) -> io::Result<Self> {
+        let mut plan_groups = Vec::with_capacity(groups.len());
+        for group in groups {
+            if group.len() < 2 {
+                continue;
+            }
+            let keep_index = match keep_rule {
+                KeeperRule::ShortestPath => group
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| a.len().cmp(&b.len()).then_with(|| a.cmp(b)))
+                    .map(|(i, _)| i)
+                    .unwrap_or(0),
+                KeeperRule::Oldest => {
+                    let mut oldest = (0, SystemTime::now());
+                    for (i, path) in group.iter().enumerate() {
+                        let modified = fs::metadata(path)?.modified()?;
+                        if i == 0 || modified < oldest.1 {
+                            oldest = (i, modified);
+                        }
+                    }
+                    oldest.0
+                }
+            };
+            let file_size = fs::metadata(&group[keep_index])?.len();
+            let extras = group
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != keep_index)
+                .map(|(_, p)| p.clone())
+                .collect();
+            plan_groups.push(DedupeGroupPlan { keep: group[keep_index].clone(), extras, file_size });
+        }
+
+        let reclaimable_bytes = plan_groups.iter().map(|g| g.file_size * g.extras.len() as u64).sum();
+        Ok(Self { action, keep_rule, groups: plan_groups, reclaimable_bytes })
+    }
+}
+
+/// Duplicate groups found under one or more search roots
+#[derive(Debug, Clone)]
+pub struct DuplicateScan {
+    /// The validated roots, in the order they were given
+    pub roots: Vec<PathBuf>,
+    pub groups: Vec<Vec<String>>,
+}
+
+impl DuplicateScan {
+    /// The innermost root containing `file`, when roots are nested
+    pub fn root_of(&self, file: &str) -> Option<&Path> {
+        self.roots
+            .iter()
+            .filter(|root| Path::new(file).starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    /// The files of `group` under each root, in root order, leaving out roots
+    /// that hold none of them
+    pub fn split_by_root<'a>(&self, group: &'a [String]) -> Vec<(&Path, Vec<&'a String>)> {
+        self.roots
+            .iter()
+            .map(|root| {
+                let files = group.iter().filter(|file| self.root_of(file) == Some(root.as_path())).collect();
+                (root.as_path(), files)
+            })
+            .filter(|(_, files): &(&Path, Vec<&String>)| !files.is_empty())
+            .collect()
+    }
+}
+
+/// Hex-encoded SHA-256 of a file's content
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    scan_range(&file, 0..u64::MAX, |bytes| visit_throttled(bytes, |chunk| hasher.update(chunk)))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hex-encoded BLAKE3 of a file's content, which is what duplicates are
+/// grouped by: it is several times faster than SHA-256 on large trees
+pub fn content_hash(path: &Path) -> io::Result<String> {
+    let file = fs::File::open(path)?;
+    let mut hasher = blake3::Hasher::new();
+    scan_range(&file, 0..u64::MAX, |bytes| {
+        visit_throttled(bytes, |chunk| {
+            hasher.update(chunk);
+        })
+    })?;
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Check that `extra` is still a separate copy of `keep`: not the same file
+/// under another spelling or through a link, and with the same content.
+/// Hashed fresh, bypassing the metadata cache, since the extra is about to go.
+pub fn verify_extra(keep: &Path, extra: &Path) -> Result<(), String> {
+    if file_identity(keep).map_err(|e| e.to_string())? == file_identity(extra).map_err(|e| e.to_string())? {
+        return Err(format!("this is the kept file {} itself, not a copy of it", keep.display()));
+    }
+    match (content_hash(keep), content_hash(extra)) {
+        (Ok(a), Ok(b)) if a == b => Ok(()),
+        (Ok(_), Ok(_)) => Err("content no longer matches the kept file".to_string()),
+        (Err(e), _) | (_, Err(e)) => Err(e.to_string()),
+    }
+}
+
+/// Replace `extra` with a link to `keep`. The link is created under a temporary
+/// name next to `extra` and renamed over it, so a failure leaves `extra` intact.
+/// Extras to delete go through the service's delete instead.
+pub fn replace_with_link(keep: &Path, extra: &Path, action: DedupeAction) -> io::Result<()> {
+    let file_name = extra.file_name().unwrap_or_default().to_string_lossy();
+    let temp_path = extra.with_file_name(format!(".{}.dedupe-tmp", file_name));
+
+    match action {
+        DedupeAction::Hardlink => fs::hard_link(keep, &temp_path)?,
+        DedupeAction::Symlink => {
+            let target = fs::canonicalize(keep)?;
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(&target, &temp_path)?;
+            #[cfg(windows)]
+            std::os::windows::fs::symlink_file(&target, &temp_path)?;
+        }
+        DedupeAction::DeleteExtras => {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "delete_extras does not replace the extra with a link"))
+        }
+    }
+
+    fs::rename(&temp_path, extra).inspect_err(|_| {
+        let _ = fs::remove_file(&temp_path);
+    })
+}
@@ -0,0 +1,158 @@
+use std::io;
+
+use serde::Serialize;
+
+use crate::tools::{EditKind, EditOperation};
+
+/// How an edit found its place in the file, or why it could not
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum EditMatch {
+    /// oldText occurs verbatim
+    Exact,
+    /// oldText matched line by line once leading and trailing whitespace was
+    /// ignored; newText was re-indented to fit
+    IgnoringWhitespace,
+    /// A line-addressed edit
+    Lines,
+    /// oldText was not found, even ignoring whitespace
+    NotFound,
+    /// oldText occurs in several places, so which one to change is unclear
+    Ambiguous { occurrences: usize },
+}
+
+impl EditMatch {
+    pub fn applied(self) -> bool {
+        !matches!(self, EditMatch::NotFound | EditMatch::Ambiguous { .. })
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            EditMatch::Exact => "matched exactly".to_string(),
+            EditMatch::IgnoringWhitespace => "matched after ignoring indentation and trailing whitespace".to_string(),
+            EditMatch::Lines => "applied by line number".to_string(),
+            EditMatch::NotFound => "oldText not found".to_string(),
+            EditMatch::Ambiguous { occurrences } => {
+                format!("oldText matches {} places; include surrounding lines so it matches one", occurrences)
+            }
+        }
+    }
+}
+
+/// The diff of an edit_file call and how each of its edits matched. The file
+/// is only written when every edit applied.
+#[derive(Debug, Clone, Serialize)]
+pub struct EditOutcome {
+    pub diff: String,
+    pub matches: Vec<EditMatch>,
+}
+
+impl EditOutcome {
+    pub fn all_applied(&self) -> bool {
+        self.matches.iter().all(|found| found.applied())
+    }
+
+    /// "edit N: why" for each edit that did not apply, one per line
+    pub fn failures(&self) -> String {
+        self.matches
+            .iter()
+            .enumerate()
+            .filter(|(_, found)| !found.applied())
+            .map(|(index, found)| format!("edit {}: {}", index + 1, found.describe()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn indentation(line: &str) -> &str {
+    &line[..line.len() - line.trim_start().len()]
+}
+
+/// `content` with `old` replaced by `new` where `old` matches whole lines once
+/// leading and trailing whitespace is ignored on each. `new` keeps its
+/// indentation relative to `old`'s first line, rebased on the matched line's.
+/// Err holds how many places matched when that is not exactly one.
+pub fn replace_ignoring_whitespace(content: &str, old: &str, new: &str) -> Result<String, usize> {
+    let old_lines: Vec<&str> = old.strip_suffix('\n').unwrap_or(old).split('\n').collect();
+    // Blank lines alone would match anywhere
+    if old_lines.iter().all(|line| line.trim().is_empty()) {
+        return Err(0);
+    }
+    let mut lines: Vec<&str> = content.split('\n').collect();
+    let last_start = (lines.len() + 1).checked_sub(old_lines.len()).ok_or(0usize)?;
+    let starts: Vec<usize> = (0..last_start)
+        .filter(|&start| {
+            lines[start..start + old_lines.len()].iter().zip(&old_lines).all(|(line, old)| line.trim() == old.trim())
+        })
+        .collect();
+    let [start] = starts[..] else {
+        return Err(starts.len());
+    };
+
+    let (file_indent, old_indent) = (indentation(lines[start]), indentation(old_lines[0]));
+    let rebased: Vec<String> = if new.is_empty() {
+        Vec::new()
+    } else {
+        new.strip_suffix('\n')
+            .unwrap_or(new)
+            .split('\n')
+            .map(|line| match line.strip_prefix(old_indent) {
+                _ if line.trim().is_empty() => String::new(),
+                Some(rest) => format!("{}{}", file_indent, rest),
+                None => format!("{}{}", file_indent, line.trim_start()),
+            })
+            .collect()
+    };
+    lines.splice(start..start + old_lines.len(), rebased.iter().map(String::as_str));
+    Ok(lines.join("\n"))
+}
+
+/// `content` with a line-addressed edit applied. `new_text` should already
+/// use `\n` line endings; a missing final newline is added so it stays on
+/// lines of its own.
+pub fn apply_line_edit(content: &str, edit: &EditOperation, new_text: &str) -> io::Result<String> {
+    let mut lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let count = lines.len();
+    let mut new_text = new_text.to_string();
+    if !new_text.is_empty() && !new_text.ends_with('\n') {
+        new_text.push('\n');
+    }
+
+    let (start, end) = match edit.kind {
+        EditKind::Replace => return Err(invalid("apply_line_edit needs a line edit, not replace".to_string())),
+        EditKind::InsertAtLine => {
+            let line = edit.line.ok_or_else(|| invalid("insert_at_line needs line".to_string()))?;
+            if line == 0 || line > count + 1 {
+                return Err(invalid(format!("line {} is outside the file, which has {} lines (use {} to append)", line, count, count + 1)));
+            }
+            (line, line - 1)
+        }
+        EditKind::ReplaceLines | EditKind::DeleteLines => {
+            let name = if edit.kind == EditKind::ReplaceLines { "replace_lines" } else { "delete_lines" };
+            let start = edit.start.ok_or_else(|| invalid(format!("{} needs start", name)))?;
+            let end = edit.end.unwrap_or(start);
+            if start == 0 || end < start || end > count {
+                return Err(invalid(format!("lines {}-{} are outside the file, which has {} lines", start, end, count)));
+            }
+            if edit.kind == EditKind::DeleteLines {
+                new_text.clear();
+            }
+            (start, end)
+        }
+    };
+
+    // Text added after a last line without a newline would run onto it
+    let last_line_open = lines.last().is_some_and(|line| !line.ends_with('\n'));
+    if start > count && last_line_open && !new_text.is_empty() {
+        new_text.insert(0, '\n');
+    }
+    if end == count && last_line_open && new_text.ends_with('\n') {
+        new_text.pop();
+    }
+    lines.splice(start - 1..end, std::iter::once(new_text.as_str()));
+    Ok(lines.concat())
+}
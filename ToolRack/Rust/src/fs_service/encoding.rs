@@ -0,0 +1,143 @@
+use std::{io, str::FromStr};
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+use super::content_type::SNIFF_BYTES;
+
+/// The `encoding` argument of read_file and edit_file: a WHATWG label such as
+/// `utf-8`, `utf-16le`, `latin-1` or `shift_jis`, or `auto` to guess. A byte
+/// order mark in the file always wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextEncoding {
+    Auto,
+    Known(&'static Encoding),
+}
+
+impl Default for TextEncoding {
+    fn default() -> Self {
+        TextEncoding::Known(UTF_8)
+    }
+}
+
+impl FromStr for TextEncoding {
+    type Err = io::Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let label = text.trim();
+        if label.eq_ignore_ascii_case("auto") {
+            return Ok(TextEncoding::Auto);
+        }
+        // "utf-16" alone means little-endian, as Windows writes it; "latin-1"
+        // is not a WHATWG label but is what people type
+        let label = match label.to_ascii_lowercase().as_str() {
+            "utf-16" => "utf-16le",
+            "latin-1" => "latin1",
+            _ => label,
+        };
+        Encoding::for_label(label.as_bytes()).map(TextEncoding::Known).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown encoding '{}': use auto, utf-8, utf-16le, utf-16be, latin-1 or another WHATWG label", text),
+            )
+        })
+    }
+}
+
+/// A file's text and how it was stored, so an edit can be written back the
+/// same way
+#[derive(Debug, Clone)]
+pub struct DecodedText {
+    pub text: String,
+    pub encoding: &'static Encoding,
+    /// The file started with a byte order mark
+    pub bom: bool,
+    /// `auto` picked a legacy code page from what the bytes look like, which
+    /// can be wrong
+    pub guessed: bool,
+}
+
+impl DecodedText {
+    /// The encoding's name, e.g. `UTF-16LE with BOM` or `windows-1251
+    /// (guessed)`
+    pub fn describe(&self) -> String {
+        let suffix = if self.bom {
+            " with BOM"
+        } else if self.guessed {
+            " (guessed; pass the encoding if the text looks wrong)"
+        } else {
+            ""
+        };
+        format!("{}{}", self.encoding.name(), suffix)
+    }
+}
+
+/// Best guess at the encoding of text without a byte order mark: UTF-16 when
+/// NULs fill every other byte of the sample (mostly-ASCII UTF-16 is valid
+/// UTF-8 too), UTF-8 when it decodes, and otherwise the legacy code page
+/// chardetng finds most likely, e.g. Windows-1252 or Shift_JIS
+pub fn guess_encoding(bytes: &[u8]) -> &'static Encoding {
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES) & !1];
+    let pairs = sample.len() / 2;
+    let nul_at = |parity: usize| sample.iter().skip(parity).step_by(2).filter(|&&byte| byte == 0).count();
+    let (even, odd) = (nul_at(0), nul_at(1));
+    if pairs > 0 && odd * 4 > pairs && even * 20 < pairs {
+        UTF_16LE
+    } else if pairs > 0 && even * 4 > pairs && odd * 20 < pairs {
+        UTF_16BE
+    } else if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        let mut detector = EncodingDetector::new();
+        detector.feed(bytes, true);
+        detector.guess(None, false)
+    }
+}
+
+/// Decode `bytes`, or Err naming the encoding they are not valid in
+pub fn decode(bytes: &[u8], encoding: TextEncoding) -> io::Result<DecodedText> {
+    let (encoding, bom_len, guessed) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_len)) => (encoding, bom_len, false),
+        None => match encoding {
+            TextEncoding::Auto => {
+                let encoding = guess_encoding(bytes);
+                (encoding, 0, ![UTF_8, UTF_16LE, UTF_16BE].contains(&encoding))
+            }
+            TextEncoding::Known(encoding) => (encoding, 0, false),
+        },
+    };
+    match encoding.decode_without_bom_handling_and_without_replacement(&bytes[bom_len..]) {
+        Some(text) => Ok(DecodedText { text: text.into_owned(), encoding, bom: bom_len > 0, guessed }),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the content is not valid {}; pass a different encoding, or auto to guess", encoding.name()),
+        )),
+    }
+}
+
+/// `text` in `encoding`, after a byte order mark when `bom`; Err when a
+/// character has no representation in the encoding
+pub fn encode(text: &str, encoding: &'static Encoding, bom: bool) -> io::Result<Vec<u8>> {
+    // encoding_rs only decodes UTF-16, so it is encoded here
+    let utf16 = |to_bytes: fn(u16) -> [u8; 2], mark: [u8; 2]| {
+        let mut bytes = if bom { mark.to_vec() } else { Vec::new() };
+        bytes.extend(text.encode_utf16().flat_map(to_bytes));
+        bytes
+    };
+    if encoding == UTF_16LE {
+        return Ok(utf16(u16::to_le_bytes, [0xFF, 0xFE]));
+    }
+    if encoding == UTF_16BE {
+        return Ok(utf16(u16::to_be_bytes, [0xFE, 0xFF]));
+    }
+    let (bytes, _, unmappable) = encoding.encode(text);
+    if unmappable {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("the new text has characters {} cannot represent", encoding.name()),
+        ));
+    }
+    let mut encoded = if bom && encoding == UTF_8 { vec![0xEF, 0xBB, 0xBF] } else { Vec::new() };
+    encoded.extend_from_slice(&bytes);
+    Ok(encoded)
+}
@@ -1,6 +1,11 @@
 use std::fs::{self};
+use std::io;
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
+use serde::Serialize;
+
+use super::mmap::scan_range;
 use super::utils::{format_permissions, format_system_time};
 
 #[derive(Debug)]
@@ -11,9 +16,20 @@ pub struct FileInfo {
     pub accessed: Option<SystemTime>,
     pub is_directory: bool,
     pub is_file: bool,
+    pub is_symlink: bool,
+    /// Where a symlink points, when it was reported as a link
+    pub link_target: Option<LinkTarget>,
     pub metadata: fs::Metadata,
 }
 
+#[derive(Debug, Clone)]
+pub struct LinkTarget {
+    /// The target as stored in the link, possibly relative to its directory
+    pub target: PathBuf,
+    /// Whether the target is inside the allowed directories and not blocked
+    pub within_allowed: bool,
+}
+
 impl std::fmt::Display for FileInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -24,6 +40,8 @@ modified: {}
 accessed: {}
 isDirectory: {}
 isFile: {}
+isSymlink: {}
+linkTarget: {}
 permissions: {}
 "#,
             self.size,
@@ -32,7 +50,61 @@ permissions: {}
             self.accessed.map_or("".to_string(), format_system_time),
             self.is_directory,
             self.is_file,
+            self.is_symlink,
+            self.link_target.as_ref().map_or("".to_string(), |link| link.target.display().to_string()),
             format_permissions(&self.metadata)
         )
     }
 }
+
+/// Who owns a file (Unix); a name is missing when the id has no account
+#[derive(Debug, Clone, Serialize)]
+pub struct Ownership {
+    pub uid: u32,
+    pub gid: u32,
+    pub user: Option<String>,
+    pub group: Option<String>,
+}
+
+impl std::fmt::Display for Ownership {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.user {
+            Some(user) => write!(f, "{} ({})", user, self.uid)?,
+            None => write!(f, "{}", self.uid)?,
+        }
+        match &self.group {
+            Some(group) => write!(f, ":{} ({})", group, self.gid),
+            None => write!(f, ":{}", self.gid),
+        }
+    }
+}
+
+#[cfg(unix)]
+pub fn ownership(metadata: &fs::Metadata) -> Option<Ownership> {
+    use std::os::unix::fs::MetadataExt;
+    let (uid, gid) = (metadata.uid(), metadata.gid());
+    Some(Ownership {
+        uid,
+        gid,
+        user: uzers::get_user_by_uid(uid).map(|user| user.name().to_string_lossy().into_owned()),
+        group: uzers::get_group_by_gid(gid).map(|group| group.name().to_string_lossy().into_owned()),
+    })
+}
+
+#[cfg(not(unix))]
+pub fn ownership(_metadata: &fs::Metadata) -> Option<Ownership> {
+    None
+}
+
+/// Lines in a file, like `wc -l` but counting a last line without a newline
+pub fn count_lines(path: &Path) -> io::Result<u64> {
+    let file = fs::File::open(path)?;
+    let (mut lines, mut last) = (0u64, b'\n');
+    scan_range(&file, 0..u64::MAX, |bytes| {
+        lines += bytes.iter().filter(|&&byte| byte == b'\n').count() as u64;
+        if let Some(&byte) = bytes.last() {
+            last = byte;
+        }
+    })?;
+    Ok(lines + u64::from(last != b'\n'))
+}
@@ -0,0 +1,59 @@
+use std::fmt::Write;
+
+/// Most bytes one hexdump_file call shows; larger requests are cut to this
+pub const HEXDUMP_MAX_BYTES: u64 = 64 * 1024;
+pub const DEFAULT_HEXDUMP_BYTES: u64 = 256;
+pub const DEFAULT_BYTES_PER_LINE: usize = 16;
+pub const MAX_BYTES_PER_LINE: usize = 64;
+
+/// A run of raw bytes read from a file
+#[derive(Debug)]
+pub struct ByteWindow {
+    pub bytes: Vec<u8>,
+    /// Position of the first byte in the file
+    pub start_byte: u64,
+    pub file_size: u64,
+}
+
+impl ByteWindow {
+    /// Position just past the last byte
+    pub fn end_byte(&self) -> u64 {
+        self.start_byte + self.bytes.len() as u64
+    }
+
+    pub fn reached_eof(&self) -> bool {
+        self.end_byte() >= self.file_size
+    }
+}
+
+/// `hexdump -C` style lines: the offset, `bytes_per_line` bytes in hex (with
+/// an extra space every 8) and, with `ascii`, the printable bytes between bars
+pub fn format_hexdump(window: &ByteWindow, bytes_per_line: usize, ascii: bool) -> String {
+    let bytes_per_line = bytes_per_line.clamp(1, MAX_BYTES_PER_LINE);
+    // Offsets of files past 4 GiB need more than 8 digits
+    let offset_width = format!("{:x}", window.end_byte()).len().max(8);
+    let hex_width = bytes_per_line * 3 + (bytes_per_line - 1) / 8;
+
+    let mut output = String::new();
+    for (i, line) in window.bytes.chunks(bytes_per_line).enumerate() {
+        let offset = window.start_byte + (i * bytes_per_line) as u64;
+        let mut hex = String::with_capacity(hex_width);
+        for (j, byte) in line.iter().enumerate() {
+            if j > 0 && j % 8 == 0 {
+                hex.push(' ');
+            }
+            let _ = write!(hex, "{:02x} ", byte);
+        }
+        let _ = write!(output, "{:0width$x}  {:<hex_width$}", offset, hex, width = offset_width, hex_width = hex_width);
+        if ascii {
+            let printable: String = line
+                .iter()
+                .map(|byte| if byte.is_ascii_graphic() || *byte == b' ' { *byte as char } else { '.' })
+                .collect();
+            let _ = write!(output, " |{}|", printable);
+        }
+        output.truncate(output.trim_end().len());
+        output.push('\n');
+    }
+    output
+}
@@ -0,0 +1,206 @@
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_LOCK_TTL_SECS: u64 = 300;
+/// Longest a lock may be held without being renewed
+pub const MAX_LOCK_TTL_SECS: u64 = 24 * 60 * 60;
+const LOCK_SUFFIX: &str = ".agent-lock";
+
+/// The contents of a lock file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockInfo {
+    /// The locked path
+    pub path: String,
+    /// Who the lock is for, as given by the caller (defaults to the session)
+    pub owner: String,
+    /// The server session holding the lock; only it may renew or release it
+    pub session: String,
+    pub pid: u32,
+    pub acquired_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl LockInfo {
+    /// An expired lock no longer protects anything and may be taken over
+    pub fn is_stale(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+}
+
+/// Result of acquire_lock
+#[derive(Debug, Clone, Serialize)]
+pub struct LockGrant {
+    pub lock: LockInfo,
+    pub lock_file: String,
+    /// This session already held the lock and extended it
+    pub renewed: bool,
+    /// An expired lock left by someone else that was taken over
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced_stale: Option<LockInfo>,
+}
+
+/// Advisory locks shared with other sessions through lock files next to the
+/// locked paths. Nothing stops a process that ignores them; with
+/// `respect_locks` set this server's own writes honour them.
+#[derive(Debug, Clone)]
+pub struct LockManager {
+    session: String,
+    respect_locks: bool,
+}
+
+impl Default for LockManager {
+    fn default() -> Self {
+        let started = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        Self {
+            session: format!("{}-{:x}", std::process::id(), started.as_nanos()),
+            respect_locks: false,
+        }
+    }
+}
+
+fn held_error(lock: &LockInfo) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::ResourceBusy,
+        format!(
+            "{} is locked by {} until {}",
+            lock.path,
+            lock.owner,
+            lock.expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        ),
+    )
+}
+
+impl LockManager {
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+
+    pub fn respects_locks(&self) -> bool {
+        self.respect_locks
+    }
+
+    pub fn set_respect_locks(&mut self, respect_locks: bool) {
+        self.respect_locks = respect_locks;
+    }
+
+    /// The lock file guarding `path`: a hidden sibling named after it
+    pub fn lock_path(path: &Path) -> PathBuf {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!(".{}{}", name, LOCK_SUFFIX))
+    }
+
+    /// The lock on `path`, if there is one
+    pub fn read(path: &Path) -> io::Result<Option<LockInfo>> {
+        let lock_path = Self::lock_path(path);
+        let text = match std::fs::read_to_string(&lock_path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&text).map(Some).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{} is not a readable lock file ({}); remove it by hand if it is left over", lock_path.display(), e),
+            )
+        })
+    }
+
+    /// Take the lock on `path` for `ttl`, or extend it if this session holds
+    /// it. Fails while another session holds an unexpired lock.
+    pub fn acquire(&self, path: &Path, owner: Option<&str>, ttl: Duration) -> io::Result<LockGrant> {
+        let lock_path = Self::lock_path(path);
+        let now = Utc::now();
+        let mut lock = LockInfo {
+            path: path.display().to_string(),
+            owner: owner.map(str::to_string).unwrap_or_else(|| format!("session {}", self.session)),
+            session: self.session.clone(),
+            pid: std::process::id(),
+            acquired_at: now,
+            expires_at: now + chrono::Duration::from_std(ttl).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?,
+        };
+
+        let mut replaced_stale = None;
+        let mut renewed = false;
+        match Self::read(path)? {
+            Some(existing) if existing.session == self.session => {
+                lock.acquired_at = existing.acquired_at;
+                renewed = true;
+            }
+            Some(existing) if existing.is_stale() => {
+                match std::fs::remove_file(&lock_path) {
+                    Ok(()) => {}
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => return Err(e),
+                }
+                replaced_stale = Some(existing);
+            }
+            Some(existing) => return Err(held_error(&existing)),
+            None => {}
+        }
+
+        let contents = serde_json::to_vec_pretty(&lock).map_err(io::Error::other)?;
+        if renewed {
+            std::fs::write(&lock_path, &contents)?;
+        } else {
+            // create_new makes sure two sessions racing for a free lock cannot both win
+            let mut file = match std::fs::OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                    return Err(match Self::read(path)? {
+                        Some(winner) => held_error(&winner),
+                        None => e,
+                    });
+                }
+                Err(e) => return Err(e),
+            };
+            file.write_all(&contents)?;
+            file.sync_all()?;
+        }
+        Ok(LockGrant {
+            lock,
+            lock_file: lock_path.display().to_string(),
+            renewed,
+            replaced_stale,
+        })
+    }
+
+    /// Drop the lock on `path`, returning it, or None when there was none.
+    /// Another session's unexpired lock is only removed with `force`.
+    pub fn release(&self, path: &Path, force: bool) -> io::Result<Option<LockInfo>> {
+        let Some(existing) = Self::read(path)? else {
+            return Ok(None);
+        };
+        if existing.session != self.session && !existing.is_stale() && !force {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{}; pass force: true to break it", held_error(&existing)),
+            ));
+        }
+        match std::fs::remove_file(Self::lock_path(path)) {
+            Ok(()) => Ok(Some(existing)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// With `respect_locks`, refuse to change `path` while another session
+    /// holds an unexpired lock on it
+    pub fn check_writable(&self, path: &Path) -> io::Result<()> {
+        if !self.respect_locks {
+            return Ok(());
+        }
+        match Self::read(path) {
+            Ok(Some(lock)) if lock.session != self.session && !lock.is_stale() => Err(held_error(&lock)),
+            Ok(_) => Ok(()),
+            // A lock file we cannot make sense of still means someone meant to lock the path
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => Err(e),
+            Err(_) => Ok(()),
+        }
+    }
+}
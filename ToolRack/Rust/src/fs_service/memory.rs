@@ -0,0 +1,302 @@
+use std::{
+    collections::BTreeMap,
+    path::{Component, Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+    time::SystemTime,
+};
+
+use async_trait::async_trait;
+
+use super::{
+    check_access,
+    ops::{DirectoryEntry, FileSystemOps},
+    patterns::PathPatterns,
+    utils::expand_home,
+};
+use crate::error::{ServiceError, ServiceResult};
+
+#[derive(Debug, Clone)]
+enum Node {
+    Directory { modified: SystemTime },
+    File { data: Vec<u8>, modified: SystemTime },
+}
+
+/// A filesystem held entirely in memory, for tests and for embedding the tools
+/// without disk access. Paths are absolute (relative paths resolve against the
+/// process working directory, as on disk) and pass the same allow/block rules as
+/// [`FileSystemService`](super::FileSystemService), except that every entry is
+/// a directory, never a glob pattern. The root and every allowed directory
+/// exist from the start.
+pub struct MemoryFileSystem {
+    allowed_path: Vec<PathBuf>,
+    blocked_path: Vec<PathBuf>,
+    nodes: Mutex<BTreeMap<PathBuf, Node>>,
+}
+
+/// What [`MemoryFileSystem::metadata`] reports about an entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryMetadata {
+    pub size: u64,
+    pub is_dir: bool,
+    pub is_file: bool,
+    pub modified: Option<SystemTime>,
+}
+
+/// Resolve `.` and `..` without touching any filesystem
+fn lexical_normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+fn not_found(path: &Path) -> ServiceError {
+    ServiceError::FileNotFound(path.display().to_string())
+}
+
+fn invalid(message: String) -> ServiceError {
+    ServiceError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, message))
+}
+
+impl MemoryFileSystem {
+    pub fn new(allowed_directories: &[String], blocked_directories: &[String]) -> Self {
+        let allowed_path: Vec<PathBuf> = allowed_directories
+            .iter()
+            .map(|dir| lexical_normalize(&expand_home(dir.into())))
+            .collect();
+        let blocked_path = blocked_directories
+            .iter()
+            .map(|dir| lexical_normalize(&expand_home(dir.into())))
+            .collect();
+        let filesystem = Self { allowed_path, blocked_path, nodes: Mutex::new(BTreeMap::new()) };
+        {
+            let mut nodes = filesystem.nodes();
+            for dir in filesystem.allowed_path.iter() {
+                Self::create_ancestors(&mut nodes, &dir.join("_")).ok();
+            }
+            if let Some(root) = std::env::current_dir().ok().and_then(|cwd| cwd.ancestors().last().map(Path::to_path_buf)) {
+                nodes.insert(root, Node::Directory { modified: SystemTime::now() });
+            }
+        }
+        filesystem
+    }
+
+    fn nodes(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Node>> {
+        self.nodes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Seed a file (creating its directories) without the allow/block checks
+    pub fn add_file(&self, path: impl AsRef<Path>, content: impl Into<Vec<u8>>) -> ServiceResult<()> {
+        let path = lexical_normalize(path.as_ref());
+        let mut nodes = self.nodes();
+        Self::create_ancestors(&mut nodes, &path)?;
+        if matches!(nodes.get(&path), Some(Node::Directory { .. })) {
+            return Err(invalid(format!("{} is a directory", path.display())));
+        }
+        nodes.insert(path, Node::File { data: content.into(), modified: SystemTime::now() });
+        Ok(())
+    }
+
+    /// Every file and its contents, in path order
+    pub fn files(&self) -> Vec<(PathBuf, Vec<u8>)> {
+        self.nodes()
+            .iter()
+            .filter_map(|(path, node)| match node {
+                Node::File { data, .. } => Some((path.clone(), data.clone())),
+                Node::Directory { .. } => None,
+            })
+            .collect()
+    }
+
+    /// Directories access is limited to; empty means unrestricted
+    pub fn allowed_directories(&self) -> Vec<PathBuf> {
+        self.allowed_path.clone()
+    }
+
+    /// The absolute path `path` resolves to, if the allow/block rules permit it
+    pub async fn validate_path(&self, path: &Path) -> ServiceResult<PathBuf> {
+        self.check(path)
+    }
+
+    pub async fn read_file(&self, path: &Path) -> ServiceResult<String> {
+        let path = self.check_existing(path)?;
+        match self.nodes().get(&path) {
+            Some(Node::File { data, .. }) => String::from_utf8(data.clone()).map_err(|_| {
+                ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("{} is not UTF-8 text", path.display()),
+                ))
+            }),
+            _ => Err(invalid(format!("{} is a directory", path.display()))),
+        }
+    }
+
+    pub async fn metadata(&self, path: &Path) -> ServiceResult<EntryMetadata> {
+        let path = self.check_existing(path)?;
+        Ok(match &self.nodes()[&path] {
+            Node::File { data, modified } => EntryMetadata {
+                size: data.len() as u64,
+                is_dir: false,
+                is_file: true,
+                modified: Some(*modified),
+            },
+            Node::Directory { modified } => EntryMetadata { size: 0, is_dir: true, is_file: false, modified: Some(*modified) },
+        })
+    }
+
+    fn create_ancestors(nodes: &mut BTreeMap<PathBuf, Node>, path: &Path) -> ServiceResult<()> {
+        let mut ancestors: Vec<&Path> = path.ancestors().skip(1).collect();
+        ancestors.reverse();
+        for ancestor in ancestors {
+            match nodes.get(ancestor) {
+                Some(Node::Directory { .. }) => {}
+                Some(Node::File { .. }) => {
+                    return Err(invalid(format!("{} is a file", ancestor.display())));
+                }
+                None => {
+                    nodes.insert(ancestor.to_path_buf(), Node::Directory { modified: SystemTime::now() });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// `path` and everything below it
+    fn subtree(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> Vec<(PathBuf, Node)> {
+        nodes
+            .range(path.to_path_buf()..)
+            .take_while(|(key, _)| key.starts_with(path))
+            .map(|(key, node)| (key.clone(), node.clone()))
+            .collect()
+    }
+
+    fn check(&self, path: &Path) -> ServiceResult<PathBuf> {
+        let absolute = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            std::env::current_dir().map_err(ServiceError::Io)?.join(path)
+        };
+        let no_patterns = PathPatterns::default();
+        check_access(&self.allowed_path, &self.blocked_path, &no_patterns, &no_patterns, &lexical_normalize(&expand_home(absolute)), true)
+    }
+
+    fn check_existing(&self, path: &Path) -> ServiceResult<PathBuf> {
+        let path = self.check(path)?;
+        if !self.nodes().contains_key(&path) {
+            return Err(not_found(&path));
+        }
+        Ok(path)
+    }
+
+    /// Parent of `path`, which must be an existing directory
+    fn require_parent(nodes: &BTreeMap<PathBuf, Node>, path: &Path) -> ServiceResult<()> {
+        let parent = path.parent().unwrap_or(path);
+        match nodes.get(parent) {
+            Some(Node::Directory { .. }) => Ok(()),
+            Some(Node::File { .. }) => Err(invalid(format!("{} is a file", parent.display()))),
+            None => Err(not_found(parent)),
+        }
+    }
+}
+
+#[async_trait]
+impl FileSystemOps for MemoryFileSystem {
+    async fn write_file(&self, path: &Path, content: &str) -> ServiceResult<()> {
+        let path = self.check(path)?;
+        let mut nodes = self.nodes();
+        Self::require_parent(&nodes, &path)?;
+        if matches!(nodes.get(&path), Some(Node::Directory { .. })) {
+            return Err(invalid(format!("{} is a directory", path.display())));
+        }
+        nodes.insert(path, Node::File { data: content.as_bytes().to_vec(), modified: SystemTime::now() });
+        Ok(())
+    }
+
+    async fn create_directory(&self, path: &Path) -> ServiceResult<()> {
+        let path = self.check(path)?;
+        let mut nodes = self.nodes();
+        match nodes.get(&path) {
+            Some(Node::Directory { .. }) => return Err(ServiceError::DirectoryAlreadyExists),
+            Some(Node::File { .. }) => return Err(invalid(format!("{} is a file", path.display()))),
+            None => {}
+        }
+        Self::create_ancestors(&mut nodes, &path)?;
+        nodes.insert(path, Node::Directory { modified: SystemTime::now() });
+        Ok(())
+    }
+
+    async fn list_directory(&self, path: &Path) -> ServiceResult<Vec<DirectoryEntry>> {
+        let path = self.check_existing(path)?;
+        let nodes = self.nodes();
+        if let Some(Node::File { .. }) = nodes.get(&path) {
+            return Err(invalid(format!("{} is not a directory", path.display())));
+        }
+        Ok(Self::subtree(&nodes, &path)
+            .into_iter()
+            .filter(|(key, _)| key.parent() == Some(path.as_path()))
+            .map(|(key, node)| DirectoryEntry {
+                name: key.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                is_dir: matches!(node, Node::Directory { .. }),
+                size: match node {
+                    Node::File { data, .. } => Some(data.len() as u64),
+                    Node::Directory { .. } => None,
+                },
+            })
+            .collect())
+    }
+
+    async fn move_file(&self, source: &Path, destination: &Path) -> ServiceResult<()> {
+        let source = self.check_existing(source)?;
+        let destination = self.check(destination)?;
+        let mut nodes = self.nodes();
+        Self::require_parent(&nodes, &destination)?;
+        if destination.starts_with(&source) {
+            return Err(invalid(format!("Cannot move {} into itself", source.display())));
+        }
+        if matches!(nodes.get(&destination), Some(Node::Directory { .. })) {
+            return Err(invalid(format!("{} already exists", destination.display())));
+        }
+        for (key, node) in Self::subtree(&nodes, &source) {
+            nodes.remove(&key);
+            let relative = key.strip_prefix(&source).unwrap_or(Path::new(""));
+            nodes.insert(destination.join(relative), node);
+        }
+        Ok(())
+    }
+
+    async fn copy_file(&self, source: &Path, destination: &Path) -> ServiceResult<Vec<String>> {
+        let source = self.check_existing(source)?;
+        let destination = self.check(destination)?;
+        let mut nodes = self.nodes();
+        Self::require_parent(&nodes, &destination)?;
+        let subtree = Self::subtree(&nodes, &source);
+        if subtree.len() > 1 && destination.starts_with(&source) {
+            return Err(invalid(format!("Cannot copy {} into itself", source.display())));
+        }
+        for (key, node) in subtree {
+            let relative = key.strip_prefix(&source).unwrap_or(Path::new(""));
+            let node = match node {
+                Node::File { data, .. } => Node::File { data, modified: SystemTime::now() },
+                Node::Directory { .. } => Node::Directory { modified: SystemTime::now() },
+            };
+            nodes.insert(destination.join(relative), node);
+        }
+        Ok(Vec::new())
+    }
+
+    async fn delete_file(&self, path: &Path) -> ServiceResult<()> {
+        let path = self.check_existing(path)?;
+        let mut nodes = self.nodes();
+        for (key, _) in Self::subtree(&nodes, &path) {
+            nodes.remove(&key);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,230 @@
+use std::{
+    collections::HashSet,
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{cache::MetadataCache, walk::WalkFilter};
+
+/// What to do when a source file collides with a different destination file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// Overwrite when the source has the later modification time
+    KeepNewest,
+    /// Overwrite when the source is larger
+    KeepLargest,
+    /// Keep the destination and copy the source beside it as `name.merged-N.ext`
+    RenameWithSuffix,
+    /// Leave the destination untouched
+    #[default]
+    Skip,
+}
+
+impl FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "keep_newest" => Ok(Self::KeepNewest),
+            "keep_largest" => Ok(Self::KeepLargest),
+            "rename_with_suffix" => Ok(Self::RenameWithSuffix),
+            "skip" => Ok(Self::Skip),
+            other => Err(format!(
+                "Unknown conflict strategy '{}': expected keep_newest, keep_largest, rename_with_suffix or skip",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MergeAction {
+    CreateDirectory,
+    /// Copy a file that does not exist in the destination
+    Copy,
+    /// Replace a conflicting destination file with the source
+    Overwrite,
+    /// Copy the source next to a conflicting file under a new name
+    CopyRenamed,
+    /// Nothing to do: identical, destination kept, or a type conflict
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeStep {
+    pub action: MergeAction,
+    pub source: String,
+    pub destination: String,
+    pub reason: String,
+    /// Bytes written by this step
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergePlan {
+    pub source_root: String,
+    pub destination_root: String,
+    pub strategy: ConflictStrategy,
+    pub steps: Vec<MergeStep>,
+    pub bytes_to_copy: u64,
+}
+
+impl MergePlan {
+    /// Walk `source_root` in name order and decide each entry's fate. Parent
+    /// directories are always planned before their contents.
+    pub fn build(
+        source_root: &Path,
+        destination_root: &Path,
+        filter: &WalkFilter,
+        strategy: ConflictStrategy,
+        cache: &MetadataCache,
+    ) -> io::Result<Self> {
+        let mut steps = Vec::new();
+        let mut blocked_dirs: Vec<PathBuf> = Vec::new();
+        let mut reserved_names = HashSet::new();
+
+        for entry in filter.walk_entries(source_root) {
+            let source = entry.path();
+            if blocked_dirs.iter().any(|dir| source.starts_with(dir)) {
+                continue;
+            }
+            let destination = destination_root.join(source.strip_prefix(source_root).unwrap_or(source));
+            let step = |action, reason: &str, bytes| MergeStep {
+                action,
+                source: source.display().to_string(),
+                destination: destination.display().to_string(),
+                reason: reason.to_string(),
+                bytes,
+            };
+            let existing = fs::symlink_metadata(&destination).ok();
+
+            if entry.file_type().is_symlink() {
+                steps.push(step(MergeAction::Skip, "symbolic links are not merged", 0));
+                continue;
+            }
+            if entry.file_type().is_dir() {
+                match existing {
+                    None => steps.push(step(MergeAction::CreateDirectory, "new directory", 0)),
+                    Some(metadata) if metadata.is_dir() => {}
+                    Some(_) => {
+                        blocked_dirs.push(source.to_path_buf());
+                        steps.push(step(MergeAction::Skip, "destination exists and is not a directory", 0));
+                    }
+                }
+                continue;
+            }
+
+            let source_metadata = entry.metadata().map_err(io::Error::other)?;
+            let size = source_metadata.len();
+            let Some(existing) = existing else {
+                steps.push(step(MergeAction::Copy, "new file", size));
+                continue;
+            };
+            if !existing.is_file() {
+                steps.push(step(MergeAction::Skip, "destination exists and is not a file", 0));
+                continue;
+            }
+            if existing.len() == size
+                && cache.hash_file(source, &source_metadata)? == cache.hash_file(&destination, &existing)?
+            {
+                steps.push(step(MergeAction::Skip, "identical", 0));
+                continue;
+            }
+
+            let planned = match strategy {
+                ConflictStrategy::KeepNewest => {
+                    if source_metadata.modified()? > existing.modified()? {
+                        step(MergeAction::Overwrite, "source is newer", size)
+                    } else {
+                        step(MergeAction::Skip, "destination is newer or the same age", 0)
+                    }
+                }
+                ConflictStrategy::KeepLargest => {
+                    if size > existing.len() {
+                        step(MergeAction::Overwrite, "source is larger", size)
+                    } else {
+                        step(MergeAction::Skip, "destination is at least as large", 0)
+                    }
+                }
+                ConflictStrategy::RenameWithSuffix => {
+                    let renamed = suffixed_name(&destination, &mut reserved_names);
+                    MergeStep {
+                        destination: renamed.display().to_string(),
+                        ..step(MergeAction::CopyRenamed, "conflicting destination kept", size)
+                    }
+                }
+                ConflictStrategy::Skip => step(MergeAction::Skip, "conflict: destination kept", 0),
+            };
+            steps.push(planned);
+        }
+
+        let bytes_to_copy = steps.iter().map(|s| s.bytes).sum();
+        Ok(Self {
+            source_root: source_root.display().to_string(),
+            destination_root: destination_root.display().to_string(),
+            strategy,
+            steps,
+            bytes_to_copy,
+        })
+    }
+
+    pub fn count(&self, action: MergeAction) -> usize {
+        self.steps.iter().filter(|s| s.action == action).count()
+    }
+}
+
+/// First free `name.merged-N.ext` beside `path`
+fn suffixed_name(path: &Path, reserved: &mut HashSet<PathBuf>) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{}.merged-{}{}", stem, n, extension)))
+        .find(|candidate| !candidate.exists() && !reserved.contains(candidate))
+        .inspect(|candidate| {
+            reserved.insert(candidate.clone());
+        })
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Copy `source` to `destination`, keeping the source's modification time so a
+/// later keep_newest merge sees the same age
+fn copy_with_mtime(source: &Path, destination: &Path) -> io::Result<()> {
+    super::throttle::copy(source, destination)?;
+    let modified = fs::metadata(source)?.modified()?;
+    fs::File::options()
+        .write(true)
+        .open(destination)?
+        .set_times(fs::FileTimes::new().set_modified(modified))
+}
+
+/// Carry out one planned step. Copies refuse to replace files that appeared
+/// after planning; overwrites go through a temporary file and a rename.
+pub fn apply_step(step: &MergeStep, source: &Path, destination: &Path) -> io::Result<()> {
+    match step.action {
+        MergeAction::CreateDirectory => fs::create_dir_all(destination),
+        MergeAction::Copy | MergeAction::CopyRenamed => {
+            if fs::symlink_metadata(destination).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "destination was created after the plan was made",
+                ));
+            }
+            copy_with_mtime(source, destination)
+        }
+        MergeAction::Overwrite => {
+            let file_name = destination.file_name().unwrap_or_default().to_string_lossy();
+            let temp_path = destination.with_file_name(format!(".{}.merge-tmp", file_name));
+            copy_with_mtime(source, &temp_path)
+                .and_then(|_| fs::rename(&temp_path, destination))
+                .inspect_err(|_| {
+                    let _ = fs::remove_file(&temp_path);
+                })
+        }
+        MergeAction::Skip => Ok(()),
+    }
+}
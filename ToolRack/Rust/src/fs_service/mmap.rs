@@ -0,0 +1,48 @@
+use std::{
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    ops::Range,
+};
+
+/// Ranges at least this large are memory-mapped when the `mmap` feature is
+/// enabled; below it, mapping costs more than the copy it saves.
+pub const MMAP_THRESHOLD: u64 = 64 * 1024 * 1024;
+/// Buffer size for streamed reads
+const STREAM_CHUNK: usize = 256 * 1024;
+
+/// Feed the bytes of `range` in `file` to `visit`, in order. Large ranges are
+/// mapped and passed as a single slice; others (or when mapping fails) are
+/// streamed through a fixed buffer. `range` is clamped to the file length.
+pub fn scan_range(file: &File, range: Range<u64>, mut visit: impl FnMut(&[u8])) -> io::Result<()> {
+    let end = range.end.min(file.metadata()?.len());
+    let len = end.saturating_sub(range.start);
+    if len == 0 {
+        return Ok(());
+    }
+
+    #[cfg(feature = "mmap")]
+    if len >= MMAP_THRESHOLD {
+        if let Ok(len) = usize::try_from(len) {
+            // SAFETY: the map is read-only and dropped before returning. Another
+            // process truncating the file meanwhile can still fault the read; that
+            // is the documented trade-off of mapping files we do not own.
+            let map = unsafe { memmap2::MmapOptions::new().offset(range.start).len(len).map(file) };
+            if let Ok(map) = map {
+                visit(&map);
+                return Ok(());
+            }
+        }
+    }
+
+    let mut reader = file;
+    reader.seek(SeekFrom::Start(range.start))?;
+    let mut reader = reader.take(len);
+    let mut buffer = vec![0; STREAM_CHUNK];
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        visit(&buffer[..read]);
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+
+use super::{remote::RemoteStorage, trash::DeleteMode, utils::name_text, FileSystemService};
+use crate::error::ServiceResult;
+
+/// One entry of a directory listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryEntry {
+    pub name: String,
+    pub is_dir: bool,
+    /// Size of files; None for directories or when it could not be read
+    pub size: Option<u64>,
+}
+
+/// The file operations behind the write_file, list_directory,
+/// create_directory, move_file, copy_file and delete_file tools, which take
+/// the trait so they can run without touching the disk. [`FileSystemService`]
+/// implements it on disk and [`MemoryFileSystem`](super::memory::MemoryFileSystem)
+/// in memory, with the same allow/block rules. The server handler and every
+/// other tool take a [`FileSystemService`].
+#[async_trait]
+pub trait FileSystemOps: Send + Sync {
+    /// Create or replace a file; its directory must exist
+    async fn write_file(&self, path: &Path, content: &str) -> ServiceResult<()>;
+
+    /// Create a directory and any missing parents
+    async fn create_directory(&self, path: &Path) -> ServiceResult<()>;
+
+    async fn list_directory(&self, path: &Path) -> ServiceResult<Vec<DirectoryEntry>>;
+
+    async fn move_file(&self, source: &Path, destination: &Path) -> ServiceResult<()>;
+
+    /// Copy a file or directory tree, returning warnings about skipped entries
+    async fn copy_file(&self, source: &Path, destination: &Path) -> ServiceResult<Vec<String>>;
+
+    /// Delete a file, or a directory with everything in it
+    async fn delete_file(&self, path: &Path) -> ServiceResult<()>;
+
+    /// Delete as `mode` asks, returning whether the item went to the trash.
+    /// Backends without a trash delete outright.
+    async fn delete_with(&self, path: &Path, mode: DeleteMode) -> ServiceResult<bool> {
+        let _ = mode;
+        self.delete_file(path).await.map(|_| false)
+    }
+}
+
+#[async_trait]
+impl FileSystemOps for FileSystemService {
+    async fn write_file(&self, path: &Path, content: &str) -> ServiceResult<()> {
+        FileSystemService::write_file(self, path, &content.to_string()).await
+    }
+
+    async fn create_directory(&self, path: &Path) -> ServiceResult<()> {
+        FileSystemService::create_directory(self, path).await
+    }
+
+    // Remote paths list through their storage backend; in --sandbox mode the
+    // listing includes pending changes
+    async fn list_directory(&self, path: &Path) -> ServiceResult<Vec<DirectoryEntry>> {
+        if RemoteStorage::is_remote(path) {
+            let entries = self.list_remote_directory(path).await?;
+            return Ok(entries
+                .into_iter()
+                .map(|entry| DirectoryEntry { name: entry.name, is_dir: entry.is_dir, size: entry.size })
+                .collect());
+        }
+        if let Some(sandbox) = self.sandbox() {
+            let valid_path = FileSystemService::validate_path(self, path).await?;
+            return Ok(sandbox.list(&valid_path)?);
+        }
+        let mut listing = Vec::new();
+        for entry in FileSystemService::list_directory(self, path).await? {
+            let metadata = entry.metadata().await.ok();
+            listing.push(DirectoryEntry {
+                name: name_text(&entry.path()),
+                is_dir: metadata.as_ref().is_some_and(|m| m.is_dir()),
+                size: metadata.filter(|m| m.is_file()).map(|m| m.len()),
+            });
+        }
+        Ok(listing)
+    }
+
+    async fn move_file(&self, source: &Path, destination: &Path) -> ServiceResult<()> {
+        FileSystemService::move_file(self, source, destination).await
+    }
+
+    async fn copy_file(&self, source: &Path, destination: &Path) -> ServiceResult<Vec<String>> {
+        FileSystemService::copy_file(self, source, destination).await
+    }
+
+    async fn delete_file(&self, path: &Path) -> ServiceResult<()> {
+        FileSystemService::delete_file(self, path).await
+    }
+
+    async fn delete_with(&self, path: &Path, mode: DeleteMode) -> ServiceResult<bool> {
+        FileSystemService::delete_path(self, path, mode).await
+    }
+}
@@ -0,0 +1,197 @@
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use once_cell::sync::Lazy;
+use rayon::prelude::*;
+
+use crate::cancel::{self, CancellationToken};
+
+use super::symlinks::{LinkAction, LinkRules};
+use super::utils::{file_identity, is_default_excluded, FileIdentity};
+
+/// Worker threads shared by the parallel tree walkers, bounding how many
+/// directories are read at once
+pub const WALK_CONCURRENCY: usize = 8;
+
+static WALK_POOL: Lazy<Option<rayon::ThreadPool>> = Lazy::new(|| {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(WALK_CONCURRENCY)
+        .thread_name(|i| format!("fs-walk-{}", i))
+        .build()
+        .ok()
+});
+
+/// Run `work` on the walker pool, or on the caller's pool if it could not be created
+fn in_walk_pool<R: Send>(work: impl FnOnce() -> R + Send) -> R {
+    match WALK_POOL.as_ref() {
+        Some(pool) => pool.install(work),
+        None => work(),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeWalkOptions {
+    pub follow_symlinks: bool,
+    /// Which links may be followed; denied links are left out
+    pub links: LinkRules,
+    pub include_defaults_excluded: bool,
+    pub include_hidden: bool,
+    /// Deepest level listed (1 = direct children); None for unlimited
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum TreeEntry {
+    Directory { name: OsString, children: Vec<TreeEntry> },
+    File { name: OsString },
+    /// An unfollowed (or broken) symbolic link
+    Symlink { name: OsString, target: PathBuf },
+    /// A followed link back to a directory already on the current path
+    Loop { name: OsString, ancestor: PathBuf },
+}
+
+enum ChildKind {
+    Directory,
+    File(u64),
+    Symlink(PathBuf),
+}
+
+struct Child {
+    path: PathBuf,
+    name: OsString,
+    kind: ChildKind,
+}
+
+/// Entries of `dir` that pass the exclusion rules, sorted by name. Followed
+/// symlinks are reported as what they point to.
+fn read_children(dir: &Path, options: &TreeWalkOptions) -> io::Result<Vec<Child>> {
+    let mut children = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if (!options.include_defaults_excluded && is_default_excluded(&name))
+            || (!options.include_hidden && name.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        let kind = if file_type.is_symlink() {
+            match (options.links.walk_action(&path, options.follow_symlinks), fs::metadata(&path)) {
+                (LinkAction::Skip, _) => continue,
+                (LinkAction::Follow, Ok(metadata)) if metadata.is_dir() => ChildKind::Directory,
+                (LinkAction::Follow, Ok(metadata)) => ChildKind::File(metadata.len()),
+                _ => ChildKind::Symlink(fs::read_link(&path).unwrap_or_default()),
+            }
+        } else if file_type.is_dir() {
+            ChildKind::Directory
+        } else if file_type.is_file() {
+            ChildKind::File(entry.metadata()?.len())
+        } else {
+            // Sockets, FIFOs and devices have no content to count
+            ChildKind::File(0)
+        };
+        children.push(Child { path, name, kind });
+    }
+    children.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(children)
+}
+
+/// Identities of the directories between the root and the current one, used to
+/// stop cycles through followed symlinks
+type Ancestors = Vec<(FileIdentity, PathBuf)>;
+
+fn with_ancestor(ancestors: &Ancestors, dir: &Path, options: &TreeWalkOptions) -> Result<Ancestors, PathBuf> {
+    if !options.follow_symlinks {
+        return Ok(Vec::new());
+    }
+    let Ok(identity) = file_identity(dir) else {
+        return Ok(ancestors.clone());
+    };
+    if let Some((_, path)) = ancestors.iter().find(|(id, _)| *id == identity) {
+        return Err(path.clone());
+    }
+    let mut extended = ancestors.clone();
+    extended.push((identity, dir.to_path_buf()));
+    Ok(extended)
+}
+
+fn tree_children(dir: &Path, depth: usize, options: &TreeWalkOptions, ancestors: &Ancestors, cancel: &CancellationToken) -> Vec<TreeEntry> {
+    // Unreadable directories are shown without contents; after a cancel
+    // nothing more is read and build_tree discards the result
+    if cancel.is_cancelled() {
+        return Vec::new();
+    }
+    let Ok(children) = read_children(dir, options) else {
+        return Vec::new();
+    };
+    children
+        .into_par_iter()
+        .map(|child| match child.kind {
+            ChildKind::Directory => match with_ancestor(ancestors, &child.path, options) {
+                Err(ancestor) => TreeEntry::Loop { name: child.name, ancestor },
+                Ok(ancestors) => {
+                    let children = if options.max_depth.is_none_or(|max| depth < max) {
+                        tree_children(&child.path, depth + 1, options, &ancestors, cancel)
+                    } else {
+                        Vec::new()
+                    };
+                    TreeEntry::Directory { name: child.name, children }
+                }
+            },
+            ChildKind::File(_) => TreeEntry::File { name: child.name },
+            ChildKind::Symlink(target) => TreeEntry::Symlink { name: child.name, target },
+        })
+        .collect()
+}
+
+/// The entries below `root`, reading sibling directories in parallel
+pub fn build_tree(root: &Path, options: &TreeWalkOptions, cancel: &CancellationToken) -> io::Result<Vec<TreeEntry>> {
+    if options.max_depth == Some(0) {
+        return Ok(Vec::new());
+    }
+    let entries = in_walk_pool(|| {
+        let ancestors = with_ancestor(&Vec::new(), root, options).unwrap_or_default();
+        tree_children(root, 1, options, &ancestors, cancel)
+    });
+    cancel::check(cancel)?;
+    Ok(entries)
+}
+
+fn directory_size(dir: &Path, options: &TreeWalkOptions, ancestors: &Ancestors, cancel: &CancellationToken) -> io::Result<u64> {
+    cancel::check(cancel)?;
+    let mut files = 0;
+    let mut directories = Vec::new();
+    for child in read_children(dir, options)? {
+        match child.kind {
+            ChildKind::Directory => directories.push(child.path),
+            ChildKind::File(size) => files += size,
+            ChildKind::Symlink(_) => {}
+        }
+    }
+    let nested = directories
+        .par_iter()
+        .map(|path| match with_ancestor(ancestors, path, options) {
+            // Each directory on a cycle is counted once
+            Err(_) => Ok(0),
+            Ok(ancestors) => directory_size(path, options, &ancestors, cancel),
+        })
+        .try_reduce(|| 0, |a, b| Ok(a + b))?;
+    Ok(files + nested)
+}
+
+/// Total size of the regular files under `root`; symlinks count only when followed
+pub fn tree_size(root: &Path, options: &TreeWalkOptions, cancel: &CancellationToken) -> io::Result<u64> {
+    // The root itself is always followed, as walkdir does
+    let metadata = fs::metadata(root)?;
+    if !metadata.is_dir() {
+        return Ok(if metadata.is_file() { metadata.len() } else { 0 });
+    }
+    in_walk_pool(|| {
+        let ancestors = with_ancestor(&Vec::new(), root, options).unwrap_or_default();
+        directory_size(root, options, &ancestors, cancel)
+    })
+}
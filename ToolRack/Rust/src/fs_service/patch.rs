@@ -0,0 +1,234 @@
+use std::io;
+
+use serde::Serialize;
+
+/// Context lines apply_patch may ignore at each end of a hunk unless told
+/// otherwise, as GNU patch does
+pub const DEFAULT_FUZZ: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+/// One `@@ -a,b +c,d @@` section of a unified diff
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hunk {
+    /// 1-based line in the original file the hunk starts at (the line it
+    /// follows when it removes nothing)
+    pub old_start: usize,
+    pub lines: Vec<HunkLine>,
+    /// A "\ No newline at end of file" marker followed the old or new side
+    pub old_missing_newline: bool,
+    pub new_missing_newline: bool,
+}
+
+impl Hunk {
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+
+    /// Record a "\ No newline at end of file" marker, which follows the line
+    /// it applies to
+    fn mark_missing_newline(&mut self) {
+        match self.lines.last() {
+            Some(HunkLine::Remove(_)) => self.old_missing_newline = true,
+            Some(HunkLine::Add(_)) => self.new_missing_newline = true,
+            _ => {
+                self.old_missing_newline = true;
+                self.new_missing_newline = true;
+            }
+        }
+    }
+
+    /// Context lines before the first change and after the last one
+    fn context_ends(&self) -> (usize, usize) {
+        let is_context = |line: &&HunkLine| matches!(line, HunkLine::Context(_));
+        let leading = self.lines.iter().take_while(is_context).count();
+        let trailing = self.lines.iter().rev().take_while(is_context).count();
+        (leading, trailing)
+    }
+}
+
+/// Where and how a hunk was applied
+#[derive(Debug, Clone, Serialize)]
+pub struct AppliedHunk {
+    /// 1-based line the hunk was expected at, from its header
+    pub expected_line: usize,
+    /// 1-based line it matched at
+    pub line: usize,
+    /// Context lines ignored at each end to make it match
+    pub fuzz: usize,
+}
+
+/// What apply_patch did: the change as a diff, and where each hunk landed
+#[derive(Debug, Clone, Serialize)]
+pub struct PatchOutcome {
+    pub diff: String,
+    pub hunks: Vec<AppliedHunk>,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// `a` or `a,b` from a hunk header; the count defaults to 1
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// The hunks of a unified diff for a single file. `diff`, `Index:`, `---`
+/// and `+++` header lines are skipped; a patch touching several files is
+/// refused.
+pub fn parse_patch(text: &str) -> io::Result<Vec<Hunk>> {
+    let mut hunks = Vec::new();
+    let mut files = 0;
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        if line.starts_with("+++ ") {
+            files += 1;
+            if files > 1 {
+                return Err(invalid("the patch changes more than one file; apply_patch takes one file at a time".to_string()));
+            }
+            continue;
+        }
+        let Some(header) = line.strip_prefix("@@ -") else {
+            continue;
+        };
+        let mut ranges = header.split_whitespace();
+        let old = ranges.next().and_then(parse_range);
+        let new = ranges.next().and_then(|range| range.strip_prefix('+')).and_then(parse_range);
+        let (Some((old_start, mut old_left)), Some((_, mut new_left))) = (old, new) else {
+            return Err(invalid(format!("malformed hunk header: {}", line)));
+        };
+
+        let mut hunk = Hunk { old_start, ..Hunk::default() };
+        while old_left > 0 || new_left > 0 {
+            let Some(body) = lines.next() else {
+                return Err(invalid(format!("hunk at line {} of the original ends early", old_start)));
+            };
+            // Editors and chat clients often strip the space of a blank context line
+            let (marker, text) = body.split_at(body.len().min(1));
+            match marker {
+                " " | "" if old_left > 0 && new_left > 0 => {
+                    hunk.lines.push(HunkLine::Context(text.to_string()));
+                    old_left -= 1;
+                    new_left -= 1;
+                }
+                "-" if old_left > 0 => {
+                    hunk.lines.push(HunkLine::Remove(text.to_string()));
+                    old_left -= 1;
+                }
+                "+" if new_left > 0 => {
+                    hunk.lines.push(HunkLine::Add(text.to_string()));
+                    new_left -= 1;
+                }
+                "\\" => hunk.mark_missing_newline(),
+                _ => return Err(invalid(format!("unexpected line in hunk at line {} of the original: {}", old_start, body))),
+            }
+        }
+        if lines.next_if(|line| line.starts_with('\\')).is_some() {
+            hunk.mark_missing_newline();
+        }
+        hunks.push(hunk);
+    }
+    if hunks.is_empty() {
+        return Err(invalid("no hunks found; expected a unified diff with @@ -a,b +c,d @@ headers".to_string()));
+    }
+    Ok(hunks)
+}
+
+/// Position of `pattern` in `lines` at or after `min_start`, searching
+/// outwards from `expected`
+fn find_block(lines: &[&str], pattern: &[&str], expected: usize, min_start: usize) -> Option<usize> {
+    let last = lines.len().checked_sub(pattern.len())?;
+    if min_start > last {
+        return None;
+    }
+    let expected = expected.clamp(min_start, last);
+    let matches = |start: usize| lines[start..start + pattern.len()] == *pattern;
+    (0..=last.max(expected - min_start)).find_map(|distance| {
+        let after = expected + distance;
+        let before = expected.checked_sub(distance).filter(|&start| start >= min_start);
+        [before, Some(after).filter(|&start| start <= last)].into_iter().flatten().find(|&start| matches(start))
+    })
+}
+
+/// `original` with `hunks` applied in order. Each hunk is looked for near the
+/// line its header names, shifted by how much earlier hunks moved the text;
+/// when the context does not match, up to `max_fuzz` context lines are
+/// ignored at each end. Err names the first hunk that does not apply.
+pub fn apply_hunks(original: &str, hunks: &[Hunk], max_fuzz: usize) -> io::Result<(String, Vec<AppliedHunk>)> {
+    let lines: Vec<&str> = original.lines().collect();
+    let mut output: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut applied = Vec::with_capacity(hunks.len());
+    let mut trailing_newline = original.ends_with('\n') || original.is_empty();
+    let (mut cursor, mut shift) = (0usize, 0isize);
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let (old, new) = (hunk.old_lines(), hunk.new_lines());
+        let (leading, trailing) = hunk.context_ends();
+        let header_start = if old.is_empty() { hunk.old_start } else { hunk.old_start.saturating_sub(1) };
+        let expected = header_start.saturating_add_signed(shift);
+
+        let found = (0..=max_fuzz).find_map(|fuzz| {
+            let (skip_front, skip_back) = (fuzz.min(leading), fuzz.min(trailing));
+            if fuzz > 0 && skip_front + skip_back == 0 {
+                return None;
+            }
+            let pattern = &old[skip_front..old.len() - skip_back];
+            let start = find_block(&lines, pattern, expected + skip_front, cursor)?;
+            Some((start, fuzz, skip_front, skip_back))
+        });
+        let Some((start, fuzz, skip_front, skip_back)) = found else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "hunk {} of {} (@@ -{} @@) does not apply: its context was not found{}",
+                    index + 1,
+                    hunks.len(),
+                    hunk.old_start,
+                    if max_fuzz > 0 { format!(" even ignoring {} context line(s) at each end", max_fuzz) } else { String::new() }
+                ),
+            ));
+        };
+
+        let matched_len = old.len() - skip_front - skip_back;
+        output.extend_from_slice(&lines[cursor..start]);
+        output.extend_from_slice(&new[skip_front..new.len() - skip_back]);
+        cursor = start + matched_len;
+        if cursor == lines.len() && (hunk.old_missing_newline || hunk.new_missing_newline) {
+            trailing_newline = !hunk.new_missing_newline;
+        }
+        shift = start as isize - skip_front as isize - header_start as isize;
+        applied.push(AppliedHunk { expected_line: header_start + 1, line: start - skip_front + 1, fuzz });
+    }
+    output.extend_from_slice(&lines[cursor..]);
+
+    let mut text = output.join("\n");
+    if trailing_newline && !output.is_empty() {
+        text.push('\n');
+    }
+    Ok((text, applied))
+}
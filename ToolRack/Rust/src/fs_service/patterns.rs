@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+
+use super::utils::{expand_home, is_glob_pattern};
+use crate::error::{ServiceError, ServiceResult};
+
+/// Allowed or blocked entries written as glob patterns, such as `**/.env` or
+/// `**/secrets/**`, rather than directories. Patterns are matched against the
+/// whole absolute path: `*` stays within one path component and `**` spans
+/// any number of them. A path matches when it or one of its parent
+/// directories does, so blocking `**/secrets` also blocks everything inside.
+#[derive(Debug, Clone, Default)]
+pub struct PathPatterns {
+    patterns: Vec<String>,
+    set: GlobSet,
+}
+
+impl PathPatterns {
+    /// Split allowed or blocked entries into directories and patterns
+    pub fn partition(entries: &[String]) -> (Vec<&String>, Vec<&String>) {
+        entries.iter().partition(|entry| !is_glob_pattern(entry))
+    }
+
+    pub fn new(patterns: &[&String]) -> ServiceResult<Self> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            let expanded = expand_home(pattern.into());
+            let glob = GlobBuilder::new(&expanded.to_string_lossy())
+                .literal_separator(true)
+                .build()
+                .map_err(|e| {
+                    ServiceError::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("Invalid glob pattern '{}': {}", pattern, e),
+                    ))
+                })?;
+            builder.add(glob);
+        }
+        let set = builder.build().map_err(|e| ServiceError::Io(std::io::Error::new(std::io::ErrorKind::InvalidInput, e)))?;
+        Ok(Self { patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(), set })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn matches(&self, path: &Path) -> bool {
+        !self.is_empty() && path.ancestors().any(|ancestor| self.set.is_match(ancestor))
+    }
+}
@@ -0,0 +1,68 @@
+use std::{fs, io, path::Path};
+
+use serde::Serialize;
+
+/// Parse an octal mode such as "644", "0755" or "2775"
+pub fn parse_mode(value: &str) -> Result<u32, String> {
+    let digits = value.trim().trim_start_matches("0o");
+    u32::from_str_radix(digits, 8)
+        .ok()
+        .filter(|mode| *mode <= 0o7777)
+        .ok_or_else(|| format!("Invalid mode '{}': expected octal digits such as 644 or 0755", value))
+}
+
+pub fn format_mode(mode: u32) -> String {
+    format!("{:04o}", mode & 0o7777)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionChange {
+    pub path: String,
+    pub is_dir: bool,
+    pub from: String,
+    pub to: String,
+    /// Set when applying the change failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PermissionReport {
+    pub root: String,
+    pub dry_run: bool,
+    pub changes: Vec<PermissionChange>,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NormalizePermissionsOptions {
+    /// Mode for regular files; `None` leaves files alone
+    pub file_mode: Option<u32>,
+    /// Mode for directories, including the root; `None` leaves directories alone
+    pub dir_mode: Option<u32>,
+    pub exclude_patterns: Vec<String>,
+    pub include_defaults_excluded: bool,
+    pub dry_run: bool,
+}
+
+#[cfg(unix)]
+pub fn current_mode(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(unix)]
+pub fn set_mode(path: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+pub fn current_mode(_metadata: &fs::Metadata) -> u32 {
+    0
+}
+
+#[cfg(not(unix))]
+pub fn set_mode(_path: &Path, _mode: u32) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "Unix permission modes are not supported on this platform"))
+}
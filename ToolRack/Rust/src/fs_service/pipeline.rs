@@ -0,0 +1,312 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{throttle::ThrottledReader, walk::WalkFilter, FileSystemService, SearchFilesOptions};
+use crate::cancel::{self, CancellationToken};
+use crate::error::{ServiceError, ServiceResult};
+
+/// A declarative sequence of stages, each consuming the file list produced by
+/// an earlier stage. `${name}` in string fields expands to a variable; every
+/// stage also defines `${<output>.count}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pipeline {
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    pub stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Stage {
+    /// Name of the file list this stage consumes; defaults to the previous stage's output
+    #[serde(default)]
+    pub input: Option<String>,
+    /// Name under which this stage's file list is stored; defaults to "stage<N>"
+    #[serde(default)]
+    pub output: Option<String>,
+    #[serde(flatten)]
+    pub kind: StageKind,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "stage", rename_all = "snake_case")]
+pub enum StageKind {
+    /// Files under `path` matching `pattern` (same rules as search_files)
+    Search {
+        path: String,
+        pattern: String,
+        #[serde(default)]
+        match_mode: Option<String>,
+    },
+    /// Narrow the input list by name, size, or modification time
+    Filter {
+        #[serde(default)]
+        pattern: Option<String>,
+        #[serde(default)]
+        exclude: Vec<String>,
+        #[serde(default)]
+        min_bytes: Option<u64>,
+        #[serde(default)]
+        max_bytes: Option<u64>,
+        #[serde(default)]
+        modified_within_hours: Option<f64>,
+    },
+    /// Copy each input file into the `destination` directory
+    Copy { destination: String },
+    /// Move each input file into the `destination` directory
+    Move { destination: String },
+    Delete,
+    /// Write the input files into a ZIP archive, named relative to `base` when given
+    Zip {
+        output_path: String,
+        #[serde(default)]
+        base: Option<String>,
+    },
+}
+
+impl StageKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Search { .. } => "search",
+            Self::Filter { .. } => "filter",
+            Self::Copy { .. } => "copy",
+            Self::Move { .. } => "move",
+            Self::Delete => "delete",
+            Self::Zip { .. } => "zip",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StageReport {
+    pub index: usize,
+    pub stage: &'static str,
+    pub output: String,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PipelineReport {
+    pub dry_run: bool,
+    pub stages: Vec<StageReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_stage: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub variables: BTreeMap<String, String>,
+}
+
+fn invalid_input(message: String) -> ServiceError {
+    ServiceError::Io(io::Error::new(io::ErrorKind::InvalidInput, message))
+}
+
+/// Replace `${name}` references with variable values
+pub fn interpolate(template: &str, variables: &BTreeMap<String, String>) -> ServiceResult<String> {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| invalid_input(format!("Unterminated variable reference in '{}'", template)))?;
+        let name = &rest[start + 2..start + end];
+        let value = variables
+            .get(name)
+            .ok_or_else(|| invalid_input(format!("Unknown pipeline variable '{}'", name)))?;
+        output.push_str(value);
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Destination paths for copying or moving `files` into `directory`
+pub(crate) fn targets_in(directory: &Path, files: &[String]) -> ServiceResult<Vec<PathBuf>> {
+    let mut seen = HashSet::new();
+    files
+        .iter()
+        .map(|file| {
+            let name = Path::new(file)
+                .file_name()
+                .ok_or_else(|| invalid_input(format!("{} has no file name", file)))?;
+            if !seen.insert(name.to_os_string()) {
+                return Err(invalid_input(format!(
+                    "More than one input file is named {}",
+                    name.to_string_lossy()
+                )));
+            }
+            Ok(directory.join(name))
+        })
+        .collect()
+}
+
+fn write_zip(entries: &[(PathBuf, String)], output_path: &Path, cancel: &CancellationToken) -> io::Result<()> {
+    let file = std::fs::File::create(output_path)?;
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let written = entries.iter().try_for_each(|(path, name)| {
+        cancel::check(cancel)?;
+        writer.start_file(name.as_str(), options)?;
+        io::copy(&mut ThrottledReader::new(std::fs::File::open(path)?), &mut writer)?;
+        Ok::<_, io::Error>(())
+    });
+    // A cancelled or failed archive is not left behind half written
+    if let Err(e) = written.and_then(|()| writer.finish().map(drop).map_err(io::Error::from)) {
+        let _ = std::fs::remove_file(output_path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+impl FileSystemService {
+    async fn run_stage(&self, kind: &StageKind, input: &[String], variables: &BTreeMap<String, String>, dry_run: bool) -> ServiceResult<Vec<String>> {
+        let expand = |value: &str| interpolate(value, variables);
+        match kind {
+            StageKind::Search { path, pattern, match_mode } => {
+                let options = SearchFilesOptions {
+                    pattern: expand(pattern)?,
+                    match_mode: match match_mode {
+                        Some(mode) => mode.parse().map_err(invalid_input)?,
+                        None => Default::default(),
+                    },
+                    ..Default::default()
+                };
+                let outcome = self
+                    .search_files(Path::new(&expand(path)?), options)
+                    .await
+                    .map_err(|e| ServiceError::Io(io::Error::other(e.to_string())))?;
+                Ok(outcome.files)
+            }
+            StageKind::Filter { pattern, exclude, min_bytes, max_bytes, modified_within_hours } => {
+                let pattern = pattern.as_deref().map(expand).transpose()?;
+                let filter = WalkFilter::new(pattern.as_deref(), exclude, true)?;
+                let since = modified_within_hours.map(|hours| {
+                    SystemTime::now()
+                        .checked_sub(Duration::from_secs_f64(hours.max(0.0) * 3600.0))
+                        .unwrap_or(SystemTime::UNIX_EPOCH)
+                });
+                let mut kept = Vec::new();
+                for file in input {
+                    let path = Path::new(file);
+                    if !filter.matches_path(Path::new(""), path) {
+                        continue;
+                    }
+                    let Ok(metadata) = tokio::fs::metadata(path).await else {
+                        continue;
+                    };
+                    let size = metadata.len();
+                    let recent = match (since, metadata.modified()) {
+                        (None, _) => true,
+                        (Some(since), Ok(modified)) => modified >= since,
+                        (Some(_), Err(_)) => false,
+                    };
+                    if min_bytes.is_none_or(|min| size >= min) && max_bytes.is_none_or(|max| size <= max) && recent {
+                        kept.push(file.clone());
+                    }
+                }
+                Ok(kept)
+            }
+            StageKind::Copy { destination } | StageKind::Move { destination } => {
+                let directory = self.validate_path(Path::new(&expand(destination)?)).await?;
+                let targets = targets_in(&directory, input)?;
+                if !dry_run {
+                    self.check_writable(&directory)?;
+                    tokio::fs::create_dir_all(&directory).await?;
+                    for (source, target) in input.iter().zip(&targets) {
+                        if matches!(kind, StageKind::Move { .. }) {
+                            self.move_file(Path::new(source), target).await?;
+                        } else {
+                            self.copy_file(Path::new(source), target).await?;
+                        }
+                    }
+                }
+                Ok(targets.iter().map(|t| t.display().to_string()).collect())
+            }
+            StageKind::Delete => {
+                if !dry_run {
+                    for file in input {
+                        self.delete_file(Path::new(file)).await?;
+                    }
+                }
+                Ok(input.to_vec())
+            }
+            StageKind::Zip { output_path, base } => {
+                let output_path = self.validate_path(Path::new(&expand(output_path)?)).await?;
+                let base = base.as_deref().map(expand).transpose()?.map(PathBuf::from);
+                let mut entries = Vec::with_capacity(input.len());
+                for file in input {
+                    // Earlier stages of a dry run only planned their outputs
+                    let path = if dry_run {
+                        self.validate_path(Path::new(file)).await?
+                    } else {
+                        self.validate_existing_path(Path::new(file)).await?
+                    };
+                    let name = match base.as_deref().and_then(|base| path.strip_prefix(base).ok()) {
+                        Some(relative) => relative.to_string_lossy().replace('\\', "/"),
+                        None => path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+                    };
+                    entries.push((path, name));
+                }
+                if !dry_run {
+                    self.check_writable(&output_path)?;
+                    let target = output_path.clone();
+                    let cancel = cancel::current();
+                    tokio::task::spawn_blocking(move || write_zip(&entries, &target, &cancel))
+                        .await
+                        .map_err(|e| ServiceError::Io(io::Error::other(e)))??;
+                }
+                Ok(vec![output_path.display().to_string()])
+            }
+        }
+    }
+
+    // Run each stage in order, stopping at the first failure. Earlier stages are
+    // not undone; batch_operations is the tool for all-or-nothing changes. With
+    // `dry_run`, mutating stages report their would-be outputs without writing.
+    pub async fn run_pipeline(&self, pipeline: &Pipeline, dry_run: bool) -> PipelineReport {
+        let mut variables = pipeline.variables.clone();
+        let mut lists: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut previous: Option<String> = None;
+        let mut report = PipelineReport { dry_run, stages: Vec::new(), failed_stage: None, error: None, variables: BTreeMap::new() };
+        let writes = pipeline.stages.iter().any(|stage| !matches!(stage.kind, StageKind::Search { .. } | StageKind::Filter { .. }));
+        if writes && !dry_run {
+            if let Err(e) = self.ensure_unsandboxed("run_pipeline") {
+                report.error = Some(e.to_string());
+                return report;
+            }
+        }
+
+        for (index, stage) in pipeline.stages.iter().enumerate() {
+            let output = stage.output.clone().unwrap_or_else(|| format!("stage{}", index + 1));
+            let input = match stage.input.as_ref().or(previous.as_ref()) {
+                Some(name) => lists.get(name).cloned().ok_or_else(|| format!("Unknown input list '{}'", name)),
+                None if matches!(stage.kind, StageKind::Search { .. }) => Ok(Vec::new()),
+                None => Err("The first stage must be a search or name an input".to_string()),
+            };
+            let result = match input {
+                Ok(input) => self.run_stage(&stage.kind, &input, &variables, dry_run).await.map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+            match result {
+                Ok(files) => {
+                    variables.insert(format!("{}.count", output), files.len().to_string());
+                    report.stages.push(StageReport { index, stage: stage.kind.name(), output: output.clone(), files: files.clone() });
+                    lists.insert(output.clone(), files);
+                    previous = Some(output);
+                }
+                Err(e) => {
+                    report.failed_stage = Some(index);
+                    report.error = Some(e);
+                    break;
+                }
+            }
+        }
+        report.variables = variables;
+        report
+    }
+}
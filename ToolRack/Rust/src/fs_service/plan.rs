@@ -0,0 +1,46 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::{duplicates::DedupePlan, merge::MergePlan};
+
+/// Current layout of `PlanDocument`; bumped on incompatible changes
+pub const PLAN_VERSION: u32 = 1;
+
+/// A mutation plan produced by an analysis operation, applied later with `apply_plan`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Plan {
+    Dedupe(DedupePlan),
+    Merge(MergePlan),
+}
+
+/// Self-describing wrapper around a `Plan`, safe to store and hand back later
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanDocument {
+    pub version: u32,
+    pub created_at: DateTime<Local>,
+    #[serde(flatten)]
+    pub plan: Plan,
+}
+
+impl PlanDocument {
+    pub fn new(plan: Plan) -> Self {
+        Self { version: PLAN_VERSION, created_at: Local::now(), plan }
+    }
+
+    /// Accept the document either as a JSON object or as a string holding one
+    pub fn from_json(value: serde_json::Value) -> Result<Self, String> {
+        let document: Self = match value {
+            serde_json::Value::String(text) => serde_json::from_str(&text),
+            other => serde_json::from_value(other),
+        }
+        .map_err(|e| format!("Invalid plan document: {}", e))?;
+        if document.version != PLAN_VERSION {
+            return Err(format!(
+                "Unsupported plan version {} (expected {})",
+                document.version, PLAN_VERSION
+            ));
+        }
+        Ok(document)
+    }
+}
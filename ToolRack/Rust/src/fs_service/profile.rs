@@ -0,0 +1,118 @@
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use super::scan::{FileRecord, TimeField};
+
+const KB: u64 = 1024;
+const MB: u64 = 1024 * KB;
+const GB: u64 = 1024 * MB;
+
+/// Upper bounds (exclusive) of the size buckets; the last bucket is open-ended
+const SIZE_BUCKETS: &[(&str, u64)] = &[
+    ("0 B", 1),
+    ("1 B - 1 KB", KB),
+    ("1 KB - 10 KB", 10 * KB),
+    ("10 KB - 100 KB", 100 * KB),
+    ("100 KB - 1 MB", MB),
+    ("1 MB - 10 MB", 10 * MB),
+    ("10 MB - 100 MB", 100 * MB),
+    ("100 MB - 1 GB", GB),
+    ("1 GB+", u64::MAX),
+];
+
+/// Upper bounds (exclusive, in days) of the modification-age buckets
+const AGE_BUCKETS: &[(&str, u64)] = &[
+    ("< 1 day", 1),
+    ("1 - 7 days", 7),
+    ("7 - 30 days", 30),
+    ("30 - 90 days", 90),
+    ("90 - 365 days", 365),
+    ("1 - 2 years", 730),
+    ("2 years+", u64::MAX),
+];
+
+#[derive(Debug, Serialize)]
+pub struct Bucket {
+    pub label: &'static str,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DepthBucket {
+    pub depth: usize,
+    pub files: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DepthStats {
+    pub max: usize,
+    pub mean: f64,
+    pub histogram: Vec<DepthBucket>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryProfile {
+    pub root: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+    pub mean_file_bytes: u64,
+    pub median_file_bytes: u64,
+    pub size_histogram: Vec<Bucket>,
+    pub modified_age_histogram: Vec<Bucket>,
+    pub depth: DepthStats,
+}
+
+impl DirectoryProfile {
+    pub fn from_records(root: String, records: &[FileRecord], now: SystemTime) -> Self {
+        let mut size_histogram: Vec<Bucket> =
+            SIZE_BUCKETS.iter().map(|(label, _)| Bucket { label, files: 0, bytes: 0 }).collect();
+        let mut age_histogram: Vec<Bucket> =
+            AGE_BUCKETS.iter().map(|(label, _)| Bucket { label, files: 0, bytes: 0 }).collect();
+        let mut depth_counts: Vec<usize> = Vec::new();
+
+        for record in records {
+            let size_index = SIZE_BUCKETS.iter().position(|(_, upper)| record.size < *upper).unwrap_or(SIZE_BUCKETS.len() - 1);
+            size_histogram[size_index].files += 1;
+            size_histogram[size_index].bytes += record.size;
+
+            if let Some(age) = record.age_days(TimeField::Modified, now) {
+                let age_index = AGE_BUCKETS.iter().position(|(_, upper)| age < *upper).unwrap_or(AGE_BUCKETS.len() - 1);
+                age_histogram[age_index].files += 1;
+                age_histogram[age_index].bytes += record.size;
+            }
+
+            if depth_counts.len() <= record.depth {
+                depth_counts.resize(record.depth + 1, 0);
+            }
+            depth_counts[record.depth] += 1;
+        }
+
+        let file_count = records.len();
+        let total_bytes: u64 = records.iter().map(|r| r.size).sum();
+        let mut sizes: Vec<u64> = records.iter().map(|r| r.size).collect();
+        sizes.sort_unstable();
+        let depth_sum: usize = records.iter().map(|r| r.depth).sum();
+
+        Self {
+            root,
+            file_count,
+            total_bytes,
+            mean_file_bytes: if file_count == 0 { 0 } else { total_bytes / file_count as u64 },
+            median_file_bytes: sizes.get(file_count / 2).copied().unwrap_or(0),
+            size_histogram,
+            modified_age_histogram: age_histogram,
+            depth: DepthStats {
+                max: depth_counts.len().saturating_sub(1),
+                mean: if file_count == 0 { 0.0 } else { depth_sum as f64 / file_count as f64 },
+                histogram: depth_counts
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, files)| *files > 0)
+                    .map(|(depth, files)| DepthBucket { depth, files })
+                    .collect(),
+            },
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use std::{io, path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[cfg(feature = "s3")]
+pub mod s3;
+#[cfg(feature = "sftp")]
+pub mod sftp;
+
+/// URL schemes routed to remote storage instead of the local disk
+pub const REMOTE_SCHEMES: &[&str] = &["s3", "sftp"];
+
+/// One entry of a remote directory listing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+}
+
+/// A remote store that paths like `s3://bucket/key` resolve to. Paths handed to
+/// a backend are relative to its bucket or host, `/`-separated, and already
+/// checked to stay inside the configured root.
+#[async_trait]
+pub trait StorageBackend: Send + Sync {
+    async fn read(&self, path: &str) -> io::Result<Vec<u8>>;
+    async fn write(&self, path: &str, data: Vec<u8>) -> io::Result<()>;
+    async fn list(&self, path: &str) -> io::Result<Vec<RemoteEntry>>;
+    async fn delete(&self, path: &str) -> io::Result<()>;
+}
+
+/// A path resolved to the backend that serves it
+#[derive(Clone)]
+pub struct RemotePath {
+    pub backend: Arc<dyn StorageBackend>,
+    /// Path within the backend
+    pub key: String,
+}
+
+#[derive(Clone)]
+struct Mount {
+    scheme: String,
+    authority: String,
+    root: String,
+    read_only: bool,
+    backend: Arc<dyn StorageBackend>,
+}
+
+impl Mount {
+    fn describe(&self) -> String {
+        let mut text = format!("{}://{}", self.scheme, self.authority);
+        if !self.root.is_empty() {
+            text.push('/');
+            text.push_str(&self.root);
+        }
+        if self.read_only {
+            text.push_str(" (read-only)");
+        }
+        text
+    }
+}
+
+/// The remote stores enabled in the --remote-storage config. Each is mounted at
+/// a scheme and bucket/host, optionally limited to a root below it.
+#[derive(Clone, Default)]
+pub struct RemoteStorage {
+    mounts: Vec<Mount>,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Split `scheme://authority/rest` for a remote scheme, or None for local paths
+fn split_url(text: &str) -> Option<(&str, &str, &str)> {
+    let (scheme, rest) = text.split_once("://")?;
+    if !REMOTE_SCHEMES.contains(&scheme) {
+        return None;
+    }
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    Some((scheme, authority, path))
+}
+
+/// `/`-joined path without empty or `.` segments; `..` is refused
+fn normalize_key(path: &str) -> io::Result<String> {
+    let mut segments = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return Err(invalid(format!("'..' is not allowed in remote paths: {}", path))),
+            _ => segments.push(segment),
+        }
+    }
+    Ok(segments.join("/"))
+}
+
+impl RemoteStorage {
+    /// True when `path` names a remote store (`s3://...`, `sftp://...`)
+    pub fn is_remote(path: &Path) -> bool {
+        path.to_str().is_some_and(|text| split_url(text).is_some())
+    }
+
+    /// Serve `scheme://authority/root/...` from `backend`
+    #[cfg_attr(not(any(feature = "s3", feature = "sftp")), allow(dead_code))]
+    pub fn mount(
+        &mut self,
+        scheme: &str,
+        authority: &str,
+        root: Option<&str>,
+        read_only: bool,
+        backend: Arc<dyn StorageBackend>,
+    ) -> io::Result<()> {
+        if !REMOTE_SCHEMES.contains(&scheme) {
+            return Err(invalid(format!("Unknown remote storage scheme: {}", scheme)));
+        }
+        self.mounts.push(Mount {
+            scheme: scheme.to_string(),
+            authority: authority.to_string(),
+            root: normalize_key(root.unwrap_or_default())?,
+            read_only,
+            backend,
+        });
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mounts.is_empty()
+    }
+
+    /// Mounted stores, e.g. `s3://artifacts/builds (read-only)`
+    pub fn describe(&self) -> Vec<String> {
+        self.mounts.iter().map(Mount::describe).collect()
+    }
+
+    /// The backend serving `path`, None when `path` is local. Errors when the
+    /// store is not configured, the path leaves its root, or a write targets a
+    /// read-only store.
+    pub fn resolve(&self, path: &Path, write: bool) -> Option<io::Result<RemotePath>> {
+        let text = path.to_str()?;
+        let (scheme, authority, rest) = split_url(text)?;
+        Some(self.resolve_url(text, scheme, authority, rest, write))
+    }
+
+    fn resolve_url(&self, url: &str, scheme: &str, authority: &str, rest: &str, write: bool) -> io::Result<RemotePath> {
+        let key = normalize_key(rest)?;
+        let mounts: Vec<&Mount> = self
+            .mounts
+            .iter()
+            .filter(|m| m.scheme == scheme && m.authority.eq_ignore_ascii_case(authority))
+            .collect();
+        if mounts.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No remote storage is configured for {}://{}; add it to the --remote-storage config", scheme, authority),
+            ));
+        }
+        let mount = mounts
+            .into_iter()
+            .find(|m| m.root.is_empty() || key == m.root || key.starts_with(&format!("{}/", m.root)))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::PermissionDenied, format!("{} is outside the configured remote roots", url))
+            })?;
+        if write && mount.read_only {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("{} is read-only", mount.describe().trim_end_matches(" (read-only)")),
+            ));
+        }
+        Ok(RemotePath { backend: mount.backend.clone(), key })
+    }
+
+    /// Load the stores listed in a JSON config file (see [`RemoteStorageConfig`])
+    pub fn from_config_file(path: &Path) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let config: RemoteStorageConfig = serde_json::from_str(&text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{}: {}", path.display(), e)))?;
+        Self::from_config(config)
+    }
+
+    pub fn from_config(config: RemoteStorageConfig) -> io::Result<Self> {
+        let mut storage = Self::default();
+        for backend in config.backends {
+            match backend {
+                BackendConfig::S3(config) => storage.mount_s3(config)?,
+                BackendConfig::Sftp(config) => storage.mount_sftp(config)?,
+            }
+        }
+        Ok(storage)
+    }
+
+    #[cfg(feature = "s3")]
+    fn mount_s3(&mut self, config: S3Config) -> io::Result<()> {
+        let backend = s3::S3Backend::new(&config)?;
+        self.mount("s3", &config.bucket, config.root.as_deref(), config.read_only, Arc::new(backend))
+    }
+
+    #[cfg(not(feature = "s3"))]
+    fn mount_s3(&mut self, config: S3Config) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("s3://{} is configured but this build does not include the s3 feature", config.bucket),
+        ))
+    }
+
+    #[cfg(feature = "sftp")]
+    fn mount_sftp(&mut self, config: SftpConfig) -> io::Result<()> {
+        let authority = config.authority();
+        let root = config.root.clone();
+        let read_only = config.read_only;
+        self.mount("sftp", &authority, root.as_deref(), read_only, Arc::new(sftp::SftpBackend::new(config)))
+    }
+
+    #[cfg(not(feature = "sftp"))]
+    fn mount_sftp(&mut self, config: SftpConfig) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("sftp://{} is configured but this build does not include the sftp feature", config.host),
+        ))
+    }
+}
+
+/// Contents of the --remote-storage file
+#[derive(Debug, Clone, Deserialize)]
+pub struct RemoteStorageConfig {
+    pub backends: Vec<BackendConfig>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum BackendConfig {
+    S3(S3Config),
+    Sftp(SftpConfig),
+}
+
+/// An S3 (or S3-compatible) bucket. Credentials not given here come from the
+/// usual AWS_* environment variables.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(not(feature = "s3"), allow(dead_code))]
+pub struct S3Config {
+    pub bucket: String,
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Custom endpoint for S3-compatible stores (MinIO, R2, ...)
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+    /// Allow a plain http endpoint
+    #[serde(default)]
+    pub allow_http: bool,
+    /// Only keys under this prefix are reachable
+    #[serde(default)]
+    pub root: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// An SFTP server. The host key must be in ~/.ssh/known_hosts or match
+/// `host_key_sha256`.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(not(feature = "sftp"), allow(dead_code))]
+pub struct SftpConfig {
+    pub host: String,
+    #[serde(default = "default_sftp_port")]
+    pub port: u16,
+    pub username: String,
+    /// Environment variable holding the password
+    #[serde(default)]
+    pub password_env: Option<String>,
+    /// Private key file; without a key or password the SSH agent is used
+    #[serde(default)]
+    pub private_key: Option<String>,
+    /// Base64 SHA-256 fingerprint of the server's host key, as printed by `ssh-keygen -l`
+    #[serde(default)]
+    pub host_key_sha256: Option<String>,
+    /// Only paths under this directory are reachable
+    #[serde(default)]
+    pub root: Option<String>,
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+fn default_sftp_port() -> u16 {
+    22
+}
+
+impl SftpConfig {
+    /// `host` or `host:port` as written in URLs
+    #[cfg_attr(not(feature = "sftp"), allow(dead_code))]
+    pub fn authority(&self) -> String {
+        if self.port == default_sftp_port() {
+            self.host.clone()
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
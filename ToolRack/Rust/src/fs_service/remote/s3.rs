@@ -0,0 +1,81 @@
+use std::io;
+
+use async_trait::async_trait;
+use object_store::{aws::AmazonS3, aws::AmazonS3Builder, path::Path as ObjectPath, ObjectStore, PutPayload};
+
+use super::{RemoteEntry, S3Config, StorageBackend};
+
+/// An S3 bucket accessed through object_store
+pub struct S3Backend {
+    store: AmazonS3,
+}
+
+fn store_error(e: object_store::Error) -> io::Error {
+    match e {
+        object_store::Error::NotFound { path, .. } => io::Error::new(io::ErrorKind::NotFound, format!("Not found: {}", path)),
+        e => io::Error::other(e),
+    }
+}
+
+impl S3Backend {
+    pub fn new(config: &S3Config) -> io::Result<Self> {
+        let mut builder = AmazonS3Builder::from_env()
+            .with_bucket_name(&config.bucket)
+            .with_allow_http(config.allow_http);
+        if let Some(region) = &config.region {
+            builder = builder.with_region(region);
+        }
+        if let Some(endpoint) = &config.endpoint {
+            builder = builder.with_endpoint(endpoint);
+        }
+        if let Some(access_key_id) = &config.access_key_id {
+            builder = builder.with_access_key_id(access_key_id);
+        }
+        if let Some(secret_access_key) = &config.secret_access_key {
+            builder = builder.with_secret_access_key(secret_access_key);
+        }
+        let store = builder.build().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Ok(Self { store })
+    }
+}
+
+#[async_trait]
+impl StorageBackend for S3Backend {
+    async fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let object = self.store.get(&ObjectPath::from(path)).await.map_err(store_error)?;
+        Ok(object.bytes().await.map_err(store_error)?.to_vec())
+    }
+
+    async fn write(&self, path: &str, data: Vec<u8>) -> io::Result<()> {
+        self.store
+            .put(&ObjectPath::from(path), PutPayload::from(data))
+            .await
+            .map_err(store_error)?;
+        Ok(())
+    }
+
+    // S3 has no directories: common prefixes below `path` are listed as such
+    async fn list(&self, path: &str) -> io::Result<Vec<RemoteEntry>> {
+        let prefix = ObjectPath::from(path);
+        let listing = self
+            .store
+            .list_with_delimiter((!path.is_empty()).then_some(&prefix))
+            .await
+            .map_err(store_error)?;
+        let directories = listing.common_prefixes.iter().map(|p| RemoteEntry {
+            name: p.filename().unwrap_or_default().to_string(),
+            is_dir: true,
+            size: None,
+        });
+        let files = listing.objects.iter().map(|o| RemoteEntry {
+            name: o.location.filename().unwrap_or_default().to_string(),
+            is_dir: false,
+            size: Some(o.size),
+        });
+        Ok(directories.chain(files).collect())
+    }
+
+    async fn delete(&self, path: &str) -> io::Result<()> {
+        self.store.delete(&ObjectPath::from(path)).await.map_err(store_error)
+    }
+}
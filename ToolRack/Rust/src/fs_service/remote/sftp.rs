@@ -0,0 +1,168 @@
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use base64::Engine;
+use ssh2::{CheckResult, HashType, KnownHostFileKind, RenameFlags, Session, Sftp};
+
+use super::{RemoteEntry, SftpConfig, StorageBackend};
+
+/// An SFTP server. Each operation opens its own session on a blocking thread,
+/// so no connection is held open between tool calls.
+pub struct SftpBackend {
+    config: SftpConfig,
+}
+
+fn denied(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, message)
+}
+
+impl SftpBackend {
+    pub fn new(config: SftpConfig) -> Self {
+        Self { config }
+    }
+
+    async fn with_sftp<T, F>(&self, operation: F) -> io::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Sftp) -> io::Result<T> + Send + 'static,
+    {
+        let config = self.config.clone();
+        tokio::task::spawn_blocking(move || {
+            let session = connect(&config)?;
+            operation(&session.sftp()?)
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+}
+
+fn connect(config: &SftpConfig) -> io::Result<Session> {
+    let mut session = Session::new()?;
+    session.set_tcp_stream(TcpStream::connect((config.host.as_str(), config.port))?);
+    session.handshake()?;
+    verify_host_key(&session, config)?;
+
+    if let Some(variable) = &config.password_env {
+        let password = std::env::var(variable)
+            .map_err(|_| io::Error::new(io::ErrorKind::NotFound, format!("{} is not set", variable)))?;
+        session.userauth_password(&config.username, &password)?;
+    } else if let Some(key) = &config.private_key {
+        let key = crate::fs_service::utils::expand_home(PathBuf::from(key));
+        session.userauth_pubkey_file(&config.username, None, &key, None)?;
+    } else {
+        session.userauth_agent(&config.username)?;
+    }
+    if !session.authenticated() {
+        return Err(denied(format!("SFTP authentication failed for {}@{}", config.username, config.host)));
+    }
+    Ok(session)
+}
+
+fn verify_host_key(session: &Session, config: &SftpConfig) -> io::Result<()> {
+    if let Some(expected) = &config.host_key_sha256 {
+        let actual = session
+            .host_key_hash(HashType::Sha256)
+            .map(|hash| base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash))
+            .unwrap_or_default();
+        let expected = expected.trim_start_matches("SHA256:").trim_end_matches('=');
+        return if actual == expected {
+            Ok(())
+        } else {
+            Err(denied(format!("Host key of {} is SHA256:{}, not the configured fingerprint", config.host, actual)))
+        };
+    }
+
+    let known_hosts_file = dirs::home_dir().unwrap_or_default().join(".ssh").join("known_hosts");
+    let mut known_hosts = session.known_hosts()?;
+    known_hosts.read_file(&known_hosts_file, KnownHostFileKind::OpenSSH)?;
+    let (key, _) = session
+        .host_key()
+        .ok_or_else(|| denied(format!("{} did not present a host key", config.host)))?;
+    match known_hosts.check_port(&config.host, config.port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::Mismatch => Err(denied(format!("Host key of {} does not match known_hosts", config.host))),
+        CheckResult::NotFound | CheckResult::Failure => Err(denied(format!(
+            "{} is not in {}; connect once with ssh or set host_key_sha256",
+            config.host,
+            known_hosts_file.display()
+        ))),
+    }
+}
+
+fn absolute(path: &str) -> PathBuf {
+    PathBuf::from(format!("/{}", path))
+}
+
+#[async_trait]
+impl StorageBackend for SftpBackend {
+    async fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        let path = absolute(path);
+        self.with_sftp(move |sftp| {
+            let mut data = Vec::new();
+            sftp.open(&path)?.read_to_end(&mut data)?;
+            Ok(data)
+        })
+        .await
+    }
+
+    // Upload beside the target, then rename over it
+    async fn write(&self, path: &str, data: Vec<u8>) -> io::Result<()> {
+        let path = absolute(path);
+        self.with_sftp(move |sftp| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+            let temp = path.with_file_name(format!(".{}.upload-tmp", name));
+            let uploaded = sftp
+                .create(&temp)
+                .map_err(io::Error::from)
+                .and_then(|mut file| file.write_all(&data));
+            if let Err(e) = uploaded {
+                let _ = sftp.unlink(&temp);
+                return Err(e);
+            }
+            let flags = Some(RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE);
+            if sftp.rename(&temp, &path, flags).is_err() {
+                // Servers without overwriting renames need the target gone first
+                let _ = sftp.unlink(&path);
+                sftp.rename(&temp, &path, None).inspect_err(|_| {
+                    let _ = sftp.unlink(&temp);
+                })?;
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    async fn list(&self, path: &str) -> io::Result<Vec<RemoteEntry>> {
+        let path = absolute(path);
+        self.with_sftp(move |sftp| {
+            Ok(sftp
+                .readdir(&path)?
+                .into_iter()
+                .map(|(entry, stat)| RemoteEntry {
+                    name: entry.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                    is_dir: stat.is_dir(),
+                    size: stat.is_file().then_some(stat.size).flatten(),
+                })
+                .collect())
+        })
+        .await
+    }
+
+    // Directories must already be empty
+    async fn delete(&self, path: &str) -> io::Result<()> {
+        let path = absolute(path);
+        self.with_sftp(move |sftp| {
+            if sftp.stat(Path::new(&path))?.is_dir() {
+                sftp.rmdir(&path)?;
+            } else {
+                sftp.unlink(&path)?;
+            }
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,245 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use serde::Serialize;
+
+use super::utils::{name_text, path_text};
+
+/// Most files one rename_files call renames
+pub const RENAME_MAX_FILES: usize = 10_000;
+
+/// How rename_files makes each new name
+#[derive(Debug, Clone)]
+pub enum RenameRule {
+    /// Replace the first match of the regex in the name; the replacement
+    /// may use `$1`, `${1}` and `${name}` for capture groups
+    Regex { regex: Regex, replacement: String },
+    /// A new name built from `{name}`, `{stem}`, `{ext}` and `{n}` (the
+    /// file's 1-based position, `{n:3}` zero-padding it to 3 digits)
+    Template(String),
+}
+
+impl RenameRule {
+    /// A regex rule when `pattern` is given, otherwise a template
+    pub fn new(pattern: Option<&str>, replacement: &str) -> io::Result<Self> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+        match pattern.filter(|pattern| !pattern.is_empty()) {
+            Some(pattern) => Ok(Self::Regex {
+                regex: Regex::new(pattern).map_err(|e| invalid(format!("invalid pattern: {}", e)))?,
+                replacement: replacement.to_string(),
+            }),
+            None => {
+                // Fail on a bad placeholder before anything is planned
+                expand_template(replacement, "x.txt", 1).map_err(invalid)?;
+                Ok(Self::Template(replacement.to_string()))
+            }
+        }
+    }
+
+    /// The new name for `name`, the `index`th file (from 1); None leaves
+    /// it as it is
+    pub fn apply(&self, name: &str, index: usize) -> Result<Option<String>, String> {
+        match self {
+            Self::Regex { regex, replacement } => {
+                Ok(regex.is_match(name).then(|| regex.replace(name, replacement.as_str()).into_owned()))
+            }
+            Self::Template(template) => expand_template(template, name, index).map(Some),
+        }
+    }
+}
+
+fn expand_template(template: &str, name: &str, index: usize) -> Result<String, String> {
+    let (stem, ext) = match name.rfind('.') {
+        Some(dot) if dot > 0 => (&name[..dot], &name[dot + 1..]),
+        _ => (name, ""),
+    };
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        expanded.push_str(&rest[..open]);
+        let close = rest[open..].find('}').ok_or_else(|| format!("unclosed '{{' in template {:?}", template))? + open;
+        let placeholder = &rest[open + 1..close];
+        match placeholder.split_once(':') {
+            None if placeholder == "name" => expanded.push_str(name),
+            None if placeholder == "stem" => expanded.push_str(stem),
+            None if placeholder == "ext" => expanded.push_str(ext),
+            None if placeholder == "n" => expanded.push_str(&index.to_string()),
+            Some(("n", width)) => {
+                let width: usize = width.parse().map_err(|_| format!("invalid width in {{{}}}", placeholder))?;
+                expanded.push_str(&format!("{:0width$}", index, width = width));
+            }
+            _ => return Err(format!("unknown placeholder {{{}}}; use {{name}}, {{stem}}, {{ext}}, {{n}} or {{n:WIDTH}}", placeholder)),
+        }
+        rest = &rest[close + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// What happens to one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RenameStatus {
+    Rename,
+    /// The rule left the name as it was
+    Unchanged,
+    /// Another file would end up with the same name, or one already has it
+    Collision { with: String },
+    Invalid { reason: String },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RenameEntry {
+    pub from: String,
+    pub to: String,
+    #[serde(flatten)]
+    pub status: RenameStatus,
+}
+
+/// Every file's new name, checked for collisions before anything moves
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RenamePlan {
+    pub entries: Vec<RenameEntry>,
+    /// Whether the renames were carried out
+    pub renamed: bool,
+}
+
+impl RenamePlan {
+    /// Work out the new names of `files`, in order, within their directories
+    pub fn new(files: &[PathBuf], rule: &RenameRule) -> Self {
+        let mut entries: Vec<RenameEntry> = files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| {
+                let name = name_text(file);
+                let (to, status) = match rule.apply(&name, i + 1) {
+                    Ok(None) => (file.clone(), RenameStatus::Unchanged),
+                    Ok(Some(new_name)) if new_name == name => (file.clone(), RenameStatus::Unchanged),
+                    Ok(Some(new_name)) => match invalid_name(&new_name) {
+                        Some(reason) => (file.with_file_name(&new_name), RenameStatus::Invalid { reason }),
+                        None => (file.with_file_name(&new_name), RenameStatus::Rename),
+                    },
+                    Err(reason) => (file.clone(), RenameStatus::Invalid { reason }),
+                };
+                RenameEntry { from: path_text(file), to: path_text(&to), status }
+            })
+            .collect();
+
+        // Where every file is now and would be afterwards; a name may be taken
+        // over only when its current owner moves away
+        let moving: HashMap<String, usize> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.status == RenameStatus::Rename)
+            .map(|(i, entry)| (entry.from.clone(), i))
+            .collect();
+        let mut claimed: HashMap<String, usize> = HashMap::new();
+        for i in 0..entries.len() {
+            let target = match &entries[i].status {
+                RenameStatus::Rename => entries[i].to.clone(),
+                RenameStatus::Unchanged => entries[i].from.clone(),
+                _ => continue,
+            };
+            if let Some(&other) = claimed.get(&target) {
+                entries[i].status = RenameStatus::Collision { with: entries[other].from.clone() };
+                continue;
+            }
+            claimed.insert(target.clone(), i);
+            if entries[i].status == RenameStatus::Rename
+                && !moving.contains_key(&target)
+                && fs::symlink_metadata(&target).is_ok()
+                && !same_file(Path::new(&entries[i].from), Path::new(&target))
+            {
+                entries[i].status = RenameStatus::Collision { with: target };
+            }
+        }
+        // A file that stays put blocks any rename onto its name, all along a chain
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for i in 0..entries.len() {
+                if let RenameStatus::Collision { .. } | RenameStatus::Invalid { .. } = entries[i].status {
+                    if let Some(other) = claimed.get(&entries[i].from).copied().filter(|&other| other != i) {
+                        if entries[other].status == RenameStatus::Rename {
+                            entries[other].status = RenameStatus::Collision { with: entries[i].from.clone() };
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        Self { entries, renamed: false }
+    }
+
+    /// Entries that collide or have an invalid new name
+    pub fn problems(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.status, RenameStatus::Collision { .. } | RenameStatus::Invalid { .. }))
+            .count()
+    }
+
+    pub fn renames(&self) -> impl Iterator<Item = &RenameEntry> {
+        self.entries.iter().filter(|entry| entry.status == RenameStatus::Rename)
+    }
+
+    /// Carry out the renames: first every file to a temporary name beside it,
+    /// then to its new name, so swaps and chains work. If any step fails,
+    /// everything is put back.
+    pub fn execute(&self) -> io::Result<()> {
+        let renames: Vec<(PathBuf, PathBuf)> =
+            self.renames().map(|entry| (PathBuf::from(&entry.from), PathBuf::from(&entry.to))).collect();
+        let temporary: Vec<PathBuf> = renames
+            .iter()
+            .enumerate()
+            .map(|(i, (from, _))| from.with_file_name(format!(".{}.rename-{}-{}", name_text(from), std::process::id(), i)))
+            .collect();
+
+        for (i, (from, _)) in renames.iter().enumerate() {
+            if let Err(e) = fs::rename(from, &temporary[i]) {
+                for j in (0..i).rev() {
+                    fs::rename(&temporary[j], &renames[j].0).ok();
+                }
+                return Err(io::Error::new(e.kind(), format!("could not rename {}: {}", from.display(), e)));
+            }
+        }
+        for (i, (_, to)) in renames.iter().enumerate() {
+            let taken = fs::symlink_metadata(to).is_ok();
+            let result = if taken {
+                Err(io::Error::new(io::ErrorKind::AlreadyExists, format!("{} appeared while renaming", to.display())))
+            } else {
+                fs::rename(&temporary[i], to)
+            };
+            if let Err(e) = result {
+                for j in (0..i).rev() {
+                    fs::rename(&renames[j].1, &temporary[j]).ok();
+                }
+                for j in 0..renames.len() {
+                    fs::rename(&temporary[j], &renames[j].0).ok();
+                }
+                return Err(io::Error::new(e.kind(), format!("could not rename to {}: {}; nothing was renamed", to.display(), e)));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid_name(name: &str) -> Option<String> {
+    if name.is_empty() || name == "." || name == ".." {
+        Some(format!("{:?} is not a file name", name))
+    } else if name.contains('/') || name.contains('\\') {
+        Some(format!("{:?} contains a path separator; rename_files only renames within a directory", name))
+    } else {
+        None
+    }
+}
+
+// Whether two paths are one file, as a name differing only in case is on a
+// case-insensitive file system
+fn same_file(a: &Path, b: &Path) -> bool {
+    matches!((fs::canonicalize(a), fs::canonicalize(b)), (Ok(a), Ok(b)) if a == b)
+}
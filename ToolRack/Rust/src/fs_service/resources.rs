@@ -0,0 +1,32 @@
+/// RFC 6570 template clients expand with a path from tool output to read a file
+/// as an MCP resource
+pub const FILE_URI_TEMPLATE: &str = "file:///{path}";
+
+/// Body of a file read through `resources/read`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResourceBody {
+    Text(String),
+    /// Base64-encoded bytes of a binary file
+    Blob(String),
+}
+
+/// A file read through the `file://` resource template
+#[derive(Debug, Clone)]
+pub struct FileResource {
+    pub uri: String,
+    pub mime_type: String,
+    pub body: ResourceBody,
+}
+
+/// Files larger than this are answered with a resource link instead of inline
+/// content by read_file and read_media_file
+pub const MAX_INLINE_READ_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Where a client can fetch a file with `resources/read`, and what it will get
+#[derive(Debug, Clone)]
+pub struct FileResourceLink {
+    pub uri: String,
+    pub name: String,
+    pub mime_type: String,
+    pub size: u64,
+}
@@ -0,0 +1,158 @@
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::os::unix::ffi::OsStringExt;
+
+use serde::Serialize;
+
+/// What tools may do inside an allowed directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum Access {
+    /// Reads only: writes, edits, moves out and deletes are refused
+    #[serde(rename = "ro")]
+    ReadOnly,
+    #[default]
+    #[serde(rename = "rw")]
+    ReadWrite,
+}
+
+impl Access {
+    /// Split an allowed-directory entry such as `/src:ro` or `/tmp/out:rw`
+    /// into the directory and its access; entries without a suffix are
+    /// read/write
+    pub fn split(entry: &str) -> (&str, Access) {
+        if let Some(dir) = entry.strip_suffix(":ro") {
+            (dir, Access::ReadOnly)
+        } else {
+            (entry.strip_suffix(":rw").unwrap_or(entry), Access::ReadWrite)
+        }
+    }
+}
+
+/// One directory the server may access, in the shape of an MCP root
+#[derive(Debug, Clone, Serialize)]
+pub struct Root {
+    pub uri: String,
+    pub name: String,
+    pub path: String,
+    /// Set on allowed directories
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access: Option<Access>,
+}
+
+impl Root {
+    pub fn new(path: &Path) -> Self {
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        Self { uri: file_uri(path), name, path: path.display().to_string(), access: None }
+    }
+
+    pub fn with_access(mut self, access: Access) -> Self {
+        self.access = Some(access);
+        self
+    }
+}
+
+/// The server's access boundaries
+#[derive(Debug, Clone, Serialize)]
+pub struct RootsReport {
+    /// True when no allowlist is configured and only `blocked` applies
+    pub unrestricted: bool,
+    pub roots: Vec<Root>,
+    pub blocked: Vec<Root>,
+    /// Glob patterns allowed besides `roots`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub allowed_patterns: Vec<String>,
+    /// Glob patterns blocked wherever they match, e.g. `**/.env`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub blocked_patterns: Vec<String>,
+}
+
+impl RootsReport {
+    /// One-paragraph summary for clients that show server instructions to the model
+    pub fn describe(&self) -> String {
+        let list = |roots: &[Root]| roots.iter().map(|r| r.path.as_str()).collect::<Vec<_>>().join(", ");
+        let mut text = if self.unrestricted {
+            "File access is not limited to specific directories.".to_string()
+        } else if self.allowed_patterns.is_empty() {
+            format!("File access is limited to these directories: {}.", list(&self.roots))
+        } else {
+            format!(
+                "File access is limited to these directories: {}, and paths matching: {}.",
+                if self.roots.is_empty() { "none".to_string() } else { list(&self.roots) },
+                self.allowed_patterns.join(", ")
+            )
+        };
+        let read_only: Vec<Root> = self.roots.iter().filter(|r| r.access == Some(Access::ReadOnly)).cloned().collect();
+        if !read_only.is_empty() {
+            text.push_str(&format!(" These directories are read-only: {}.", list(&read_only)));
+        }
+        if !self.blocked.is_empty() {
+            text.push_str(&format!(" These directories are always blocked: {}.", list(&self.blocked)));
+        }
+        if !self.blocked_patterns.is_empty() {
+            text.push_str(&format!(" Paths matching these patterns are always blocked: {}.", self.blocked_patterns.join(", ")));
+        }
+        text
+    }
+}
+
+/// `file://` URI for an absolute path, percent-encoding everything outside the
+/// unreserved set. Windows paths become `file:///C:/dir` and UNC paths
+/// `file://server/share/dir`.
+/// Names that are not valid UTF-8 keep their bytes, so the URI names the
+/// path exactly.
+pub fn file_uri(path: &Path) -> String {
+    let bytes: Vec<u8> = path.as_os_str().as_encoded_bytes().iter().map(|&b| if b == b'\\' { b'/' } else { b }).collect();
+    // The server of a UNC path is the URI's host
+    let (mut uri, text) = match bytes.strip_prefix(b"//") {
+        Some(share) => (String::from("file://"), share),
+        None if bytes.starts_with(b"/") => (String::from("file://"), &bytes[..]),
+        None => (String::from("file:///"), &bytes[..]),
+    };
+    for &byte in text {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+/// Inverse of [`file_uri`]: the local path named by a `file://` URI, or None when
+/// the URI is not a local file URI or is not validly percent-encoded. On Unix
+/// the decoded bytes need not be UTF-8; elsewhere they must be.
+/// On Windows a URI with a host names a UNC share, `\\server\share\dir`.
+pub fn path_from_file_uri(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Only local files: an empty host or `localhost`, or a share on Windows
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !rest.starts_with('/') {
+        let (host, share) = rest.split_once('/').filter(|_| cfg!(windows))?;
+        let share = path_from_file_uri(&format!("file:///{}", share))?;
+        return Some(PathBuf::from(format!(r"\\{}{}", host, share.to_string_lossy().replace('/', r"\"))));
+    }
+
+    let mut bytes = Vec::with_capacity(rest.len());
+    let mut input = rest.bytes();
+    while let Some(byte) = input.next() {
+        if byte == b'%' {
+            let hex = [input.next()?, input.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+
+    // `/C:/dir` is a Windows drive path
+    if let [b'/', drive, b':', ..] = bytes[..] {
+        if drive.is_ascii_alphabetic() {
+            bytes.remove(0);
+        }
+    }
+    #[cfg(unix)]
+    return Some(PathBuf::from(std::ffi::OsString::from_vec(bytes)));
+    #[cfg(not(unix))]
+    return String::from_utf8(bytes).ok().map(PathBuf::from);
+}
@@ -0,0 +1,423 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+use super::ops::DirectoryEntry;
+
+/// A pending change to one path
+#[derive(Debug, Clone)]
+enum Change {
+    /// New contents, held in an overlay file
+    File(PathBuf),
+    /// A freshly created directory; nothing on disk below it shows through
+    Directory,
+    Deleted,
+}
+
+/// What a path looks like with the sandbox applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entry {
+    /// A file whose current contents are at the given path (overlay or disk)
+    File(PathBuf),
+    Directory,
+    Missing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    AddedDirectory,
+    /// A directory was deleted and recreated; its old contents go away
+    ReplacedDirectory,
+}
+
+/// One change the sandbox would make to disk
+#[derive(Debug, Clone)]
+pub struct PendingChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+    /// Overlay file with the new contents, for added and modified files
+    pub contents: Option<PathBuf>,
+}
+
+/// A pending change as reported by diff_sandbox
+#[derive(Debug, Serialize)]
+pub struct SandboxChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    /// New size of added and modified files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Unified diff against the file on disk, for text files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+    /// Files on disk that a deleted or replaced directory takes with it, up to
+    /// `DELETED_FILES_LISTED` of them
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed_files: Vec<String>,
+    /// How many files the directory holds in all
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removed_file_count: Option<usize>,
+}
+
+/// Files listed per deleted directory in diff_sandbox and dry-run previews
+pub const DELETED_FILES_LISTED: usize = 100;
+
+#[derive(Debug, Serialize)]
+pub struct SandboxCommit {
+    pub committed: Vec<String>,
+    /// The change that failed, which stopped the commit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<String>,
+    /// Changes still held in the sandbox
+    pub remaining: usize,
+}
+
+/// Copy-on-write overlay used in --sandbox mode. Mutations are recorded against
+/// the original paths and their new contents kept in a private temporary
+/// directory; reads see the overlay first and the disk below it. Nothing on disk
+/// changes until `commit`. The overlay is removed when the sandbox is dropped.
+#[derive(Debug)]
+pub struct Sandbox {
+    dir: PathBuf,
+    changes: Mutex<BTreeMap<PathBuf, Change>>,
+    next_id: AtomicU64,
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, format!("{} does not exist", path.display()))
+}
+
+fn remove_from_disk(path: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+impl Sandbox {
+    /// Create an empty sandbox with its overlay under the system temp directory
+    pub fn create() -> io::Result<Self> {
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos();
+        Self::create_in(&std::env::temp_dir().join(format!("aichemistforge-sandbox-{}-{}", std::process::id(), stamp)))
+    }
+
+    /// Create an empty sandbox keeping its overlay in `dir`, which must not exist
+    pub fn create_in(dir: &Path) -> io::Result<Self> {
+        fs::create_dir_all(dir.parent().unwrap_or(dir))?;
+        fs::create_dir(dir)?;
+        Ok(Self { dir: dir.to_path_buf(), changes: Mutex::new(BTreeMap::new()), next_id: AtomicU64::new(0) })
+    }
+
+    /// Where overlay contents are kept
+    pub fn overlay_dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn changes(&self) -> MutexGuard<'_, BTreeMap<PathBuf, Change>> {
+        self.changes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // A change at or above `path` means the disk below it is not visible
+    fn hidden(changes: &BTreeMap<PathBuf, Change>, path: &Path) -> bool {
+        path.ancestors().any(|ancestor| changes.contains_key(ancestor))
+    }
+
+    fn lookup_in(changes: &BTreeMap<PathBuf, Change>, path: &Path) -> Entry {
+        match changes.get(path) {
+            Some(Change::File(contents)) => return Entry::File(contents.clone()),
+            Some(Change::Directory) => return Entry::Directory,
+            Some(Change::Deleted) => return Entry::Missing,
+            None => {}
+        }
+        if Self::hidden(changes, path) {
+            return Entry::Missing;
+        }
+        match fs::metadata(path) {
+            Ok(metadata) if metadata.is_dir() => Entry::Directory,
+            Ok(_) => Entry::File(path.to_path_buf()),
+            Err(_) => Entry::Missing,
+        }
+    }
+
+    /// `path` as seen through the sandbox
+    pub fn lookup(&self, path: &Path) -> Entry {
+        Self::lookup_in(&self.changes(), path)
+    }
+
+    fn require_parent(changes: &BTreeMap<PathBuf, Change>, path: &Path) -> io::Result<()> {
+        let parent = path.parent().unwrap_or(path);
+        match Self::lookup_in(changes, parent) {
+            Entry::Directory => Ok(()),
+            Entry::File(_) => Err(invalid(format!("{} is a file", parent.display()))),
+            Entry::Missing => Err(not_found(parent)),
+        }
+    }
+
+    // Record a change, removing any overlay file the old change owned
+    fn record(&self, changes: &mut BTreeMap<PathBuf, Change>, path: &Path, change: Change) {
+        if let Some(Change::File(old)) = changes.insert(path.to_path_buf(), change) {
+            fs::remove_file(old).ok();
+        }
+    }
+
+    fn new_overlay_file(&self) -> PathBuf {
+        self.dir.join(self.next_id.fetch_add(1, Ordering::Relaxed).to_string())
+    }
+
+    fn write_locked(&self, changes: &mut BTreeMap<PathBuf, Change>, path: &Path, data: &[u8]) -> io::Result<()> {
+        Self::require_parent(changes, path)?;
+        if Self::lookup_in(changes, path) == Entry::Directory {
+            return Err(invalid(format!("{} is a directory", path.display())));
+        }
+        let contents = self.new_overlay_file();
+        fs::write(&contents, data)?;
+        self.record(changes, path, Change::File(contents));
+        Ok(())
+    }
+
+    /// Create or replace a file; its directory must exist
+    pub fn write(&self, path: &Path, data: &[u8]) -> io::Result<()> {
+        self.write_locked(&mut self.changes(), path, data)
+    }
+
+    /// Create a directory and any missing parents
+    pub fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut changes = self.changes();
+        let mut ancestors: Vec<&Path> = path.ancestors().collect();
+        ancestors.reverse();
+        for ancestor in ancestors {
+            match Self::lookup_in(&changes, ancestor) {
+                Entry::Directory => {}
+                Entry::File(_) => return Err(invalid(format!("{} is a file", ancestor.display()))),
+                Entry::Missing => self.record(&mut changes, ancestor, Change::Directory),
+            }
+        }
+        Ok(())
+    }
+
+    // Drop every change at or below `path`
+    fn forget_subtree(&self, changes: &mut BTreeMap<PathBuf, Change>, path: &Path) {
+        let below: Vec<PathBuf> = changes
+            .range(path.to_path_buf()..)
+            .take_while(|(key, _)| key.starts_with(path))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in below {
+            if let Some(Change::File(contents)) = changes.remove(&key) {
+                fs::remove_file(contents).ok();
+            }
+        }
+    }
+
+    fn remove_locked(&self, changes: &mut BTreeMap<PathBuf, Change>, path: &Path) -> io::Result<()> {
+        if Self::lookup_in(changes, path) == Entry::Missing {
+            return Err(not_found(path));
+        }
+        self.forget_subtree(changes, path);
+        self.record(changes, path, Change::Deleted);
+        Ok(())
+    }
+
+    /// Delete a file, or a directory with everything in it
+    pub fn remove(&self, path: &Path) -> io::Result<()> {
+        self.remove_locked(&mut self.changes(), path)
+    }
+
+    fn children(changes: &BTreeMap<PathBuf, Change>, path: &Path) -> BTreeSet<std::ffi::OsString> {
+        let mut names = BTreeSet::new();
+        if !Self::hidden(changes, path) {
+            if let Ok(entries) = fs::read_dir(path) {
+                names.extend(entries.flatten().map(|entry| entry.file_name()));
+            }
+        }
+        names.extend(
+            changes
+                .range(path.to_path_buf()..)
+                .take_while(|(key, _)| key.starts_with(path))
+                .filter(|(key, _)| key.parent() == Some(path))
+                .filter_map(|(key, _)| key.file_name().map(|name| name.to_os_string())),
+        );
+        names
+    }
+
+    /// Entries of a directory, in name order
+    pub fn list(&self, path: &Path) -> io::Result<Vec<DirectoryEntry>> {
+        let changes = self.changes();
+        match Self::lookup_in(&changes, path) {
+            Entry::Directory => {}
+            Entry::File(_) => return Err(invalid(format!("{} is not a directory", path.display()))),
+            Entry::Missing => return Err(not_found(path)),
+        }
+        let mut listing = Vec::new();
+        for name in Self::children(&changes, path) {
+            let entry = match Self::lookup_in(&changes, &path.join(&name)) {
+                Entry::File(contents) => DirectoryEntry {
+                    name: name.to_string_lossy().into_owned(),
+                    is_dir: false,
+                    size: fs::metadata(contents).ok().map(|m| m.len()),
+                },
+                Entry::Directory => DirectoryEntry { name: name.to_string_lossy().into_owned(), is_dir: true, size: None },
+                Entry::Missing => continue,
+            };
+            listing.push(entry);
+        }
+        Ok(listing)
+    }
+
+    fn copy_locked(&self, changes: &mut BTreeMap<PathBuf, Change>, source: &Path, destination: &Path) -> io::Result<()> {
+        match Self::lookup_in(changes, source) {
+            Entry::Missing => Err(not_found(source)),
+            Entry::File(contents) => {
+                let data = fs::read(contents)?;
+                self.write_locked(changes, destination, &data)
+            }
+            Entry::Directory => {
+                if destination.starts_with(source) {
+                    return Err(invalid(format!("Cannot copy {} into itself", source.display())));
+                }
+                Self::require_parent(changes, destination)?;
+                match Self::lookup_in(changes, destination) {
+                    Entry::Directory => {}
+                    Entry::File(_) => return Err(invalid(format!("{} is a file", destination.display()))),
+                    Entry::Missing => self.record(changes, destination, Change::Directory),
+                }
+                for name in Self::children(changes, source) {
+                    self.copy_locked(changes, &source.join(&name), &destination.join(&name))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Copy a file or directory tree; directories merge into an existing destination
+    pub fn copy(&self, source: &Path, destination: &Path) -> io::Result<()> {
+        self.copy_locked(&mut self.changes(), source, destination)
+    }
+
+    /// Move a file or directory; an existing destination directory is an error
+    pub fn rename(&self, source: &Path, destination: &Path) -> io::Result<()> {
+        let mut changes = self.changes();
+        if Self::lookup_in(&changes, source) == Entry::Missing {
+            return Err(not_found(source));
+        }
+        if destination.starts_with(source) {
+            return Err(invalid(format!("Cannot move {} into itself", source.display())));
+        }
+        if Self::lookup_in(&changes, destination) == Entry::Directory {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} already exists", destination.display()),
+            ));
+        }
+        self.copy_locked(&mut changes, source, destination)?;
+        self.remove_locked(&mut changes, source)
+    }
+
+    fn pending_in(changes: &BTreeMap<PathBuf, Change>, path: &Path, change: &Change) -> Option<PendingChange> {
+        let on_disk = fs::symlink_metadata(path).ok();
+        let (kind, contents) = match change {
+            Change::File(contents) => match &on_disk {
+                Some(metadata) if metadata.is_file() => {
+                    // Writes that left a file as it was are not changes
+                    let unchanged = metadata.len() == fs::metadata(contents).ok()?.len()
+                        && fs::read(path).ok()? == fs::read(contents).ok()?;
+                    if unchanged {
+                        return None;
+                    }
+                    (ChangeKind::Modified, Some(contents.clone()))
+                }
+                _ => (ChangeKind::Added, Some(contents.clone())),
+            },
+            Change::Directory => match &on_disk {
+                Some(_) => (ChangeKind::ReplacedDirectory, None),
+                None => (ChangeKind::AddedDirectory, None),
+            },
+            // Deleting something that only existed in the sandbox changes nothing
+            Change::Deleted if on_disk.is_none() => return None,
+            Change::Deleted => (ChangeKind::Deleted, None),
+        };
+        // Deletions inside a replaced directory are covered by the replacement
+        let covered = matches!(change, Change::Deleted)
+            && path.ancestors().skip(1).any(|ancestor| changes.contains_key(ancestor));
+        (!covered).then(|| PendingChange { path: path.to_path_buf(), kind, contents })
+    }
+
+    /// What committing would change on disk, in path order
+    pub fn pending(&self) -> Vec<PendingChange> {
+        let changes = self.changes();
+        changes.iter().filter_map(|(path, change)| Self::pending_in(&changes, path, change)).collect()
+    }
+
+    /// Apply changes at or below `paths` (everything when empty) to disk, in
+    /// path order so directories exist before their contents. Directories the
+    /// selected changes live in are committed with them. Stops at the first failure.
+    pub fn commit(&self, paths: &[PathBuf]) -> SandboxCommit {
+        let mut changes = self.changes();
+        let selected: Vec<PathBuf> = changes
+            .keys()
+            .filter(|key| {
+                paths.is_empty()
+                    || paths.iter().any(|path| key.starts_with(path))
+                    || paths.iter().any(|path| path.starts_with(key) && matches!(changes.get(*key), Some(Change::Directory)))
+            })
+            .cloned()
+            .collect();
+
+        let mut committed = Vec::new();
+        let mut failed = None;
+        for path in selected {
+            let Some(change) = changes.get(&path).cloned() else {
+                continue;
+            };
+            let result = match &change {
+                Change::File(contents) => (|| {
+                    if fs::symlink_metadata(&path).is_ok_and(|m| m.is_dir()) {
+                        fs::remove_dir_all(&path)?;
+                    }
+                    super::throttle::copy(contents, &path).map(|_| ())
+                })(),
+                Change::Directory => remove_from_disk(&path).and_then(|_| fs::create_dir(&path)),
+                Change::Deleted => remove_from_disk(&path),
+            };
+            match result {
+                Ok(()) => {
+                    if let Change::File(contents) = change {
+                        fs::remove_file(contents).ok();
+                    }
+                    changes.remove(&path);
+                    committed.push(path.display().to_string());
+                }
+                Err(e) => {
+                    failed = Some(format!("{}: {}", path.display(), e));
+                    break;
+                }
+            }
+        }
+        SandboxCommit { committed, failed, remaining: changes.len() }
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
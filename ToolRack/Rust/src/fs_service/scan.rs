@@ -0,0 +1,148 @@
+use std::{str::FromStr, time::SystemTime};
+
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::Serialize;
+
+/// Filters shared by the metadata-based finder operations
+#[derive(Debug, Clone, Default)]
+pub struct FileQuery {
+    /// Glob or substring matched against file names (see `WalkFilter`)
+    pub pattern: Option<String>,
+    pub exclude_patterns: Vec<String>,
+    pub min_bytes: Option<u64>,
+    pub max_bytes: Option<u64>,
+    pub include_defaults_excluded: bool,
+}
+
+impl FileQuery {
+    pub fn size_matches(&self, size: u64) -> bool {
+        self.min_bytes.is_none_or(|min| size >= min) && self.max_bytes.is_none_or(|max| size <= max)
+    }
+}
+
+/// Which timestamp an age-based query looks at
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeField {
+    #[default]
+    Modified,
+    Accessed,
+}
+
+impl FromStr for TimeField {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "modified" => Ok(Self::Modified),
+            "accessed" => Ok(Self::Accessed),
+            other => Err(format!("Unknown time_field '{}': expected modified or accessed", other)),
+        }
+    }
+}
+
+/// Result ordering for the finder operations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FileSort {
+    #[default]
+    SizeDesc,
+    SizeAsc,
+    ModifiedDesc,
+    ModifiedAsc,
+    Path,
+}
+
+impl FileSort {
+    pub fn apply(self, records: &mut [FileRecord]) {
+        match self {
+            Self::SizeDesc => records.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path))),
+            Self::SizeAsc => records.sort_by(|a, b| a.size.cmp(&b.size).then_with(|| a.path.cmp(&b.path))),
+            Self::ModifiedDesc => records.sort_by_key(|r| std::cmp::Reverse(r.modified)),
+            Self::ModifiedAsc => records.sort_by_key(|r| r.modified),
+            Self::Path => records.sort_by(|a, b| a.path.cmp(&b.path)),
+        }
+    }
+}
+
+impl FromStr for FileSort {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "size_desc" => Ok(Self::SizeDesc),
+            "size_asc" => Ok(Self::SizeAsc),
+            "modified_desc" => Ok(Self::ModifiedDesc),
+            "modified_asc" => Ok(Self::ModifiedAsc),
+            "path" => Ok(Self::Path),
+            other => Err(format!(
+                "Unknown sort '{}': expected size_desc, size_asc, modified_desc, modified_asc or path",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileRecord {
+    pub path: String,
+    pub size: u64,
+    pub modified: Option<DateTime<Local>>,
+    pub accessed: Option<DateTime<Local>>,
+    /// Directory depth below the scanned root (1 = direct child)
+    pub depth: usize,
+}
+
+impl FileRecord {
+    pub fn from_metadata(path: String, metadata: &std::fs::Metadata, depth: usize) -> Self {
+        Self {
+            path,
+            size: metadata.len(),
+            modified: metadata.modified().ok().map(DateTime::from),
+            accessed: metadata.accessed().ok().map(DateTime::from),
+            depth,
+        }
+    }
+
+    pub fn time(&self, field: TimeField) -> Option<DateTime<Local>> {
+        match field {
+            TimeField::Modified => self.modified,
+            TimeField::Accessed => self.accessed,
+        }
+    }
+
+    /// Whole days between the chosen timestamp and `now` (0 for future timestamps)
+    pub fn age_days(&self, field: TimeField, now: SystemTime) -> Option<u64> {
+        let time: SystemTime = self.time(field)?.into();
+        Some(now.duration_since(time).map(|d| d.as_secs() / 86_400).unwrap_or(0))
+    }
+}
+
+/// Parse an RFC 3339 timestamp, or a local "YYYY-MM-DD HH:MM[:SS]" / "YYYY-MM-DD"
+pub fn parse_timestamp(value: &str) -> Result<SystemTime, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.into());
+    }
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| format!("Invalid timestamp '{}': expected RFC 3339 or 'YYYY-MM-DD HH:MM:SS'", value))?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(SystemTime::from)
+        .ok_or_else(|| format!("Timestamp '{}' does not exist in the local time zone", value))
+}
+
+/// One line per record: timestamp, size and path
+pub fn format_record_line(record: &FileRecord, field: TimeField) -> String {
+    let time = record
+        .time(field)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    format!("  {}  {:>10}  {}", time, super::utils::format_bytes(record.size), record.path)
+}
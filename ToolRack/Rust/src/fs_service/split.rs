@@ -0,0 +1,171 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::{sandbox::Sandbox, throttle::ThrottledReader};
+
+/// Most parts one split_file call writes
+pub const MAX_SPLIT_PARTS: u64 = 10_000;
+pub const DEFAULT_PART_TEMPLATE: &str = "{name}.{n}";
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Where each part ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitBy {
+    /// Parts of this many bytes; the last one may be shorter
+    Bytes(u64),
+    /// Parts of this many lines, each ending at a newline
+    Lines(u64),
+}
+
+/// One written part
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitPart {
+    pub path: String,
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<u64>,
+    pub sha256: String,
+}
+
+/// What split_file wrote, in order; joining the parts gives back the source
+#[derive(Debug, Clone, Serialize)]
+pub struct SplitManifest {
+    pub source: String,
+    pub source_bytes: u64,
+    pub split_by: SplitBy,
+    pub parts: Vec<SplitPart>,
+}
+
+/// The file name of part `index` (1-based) of `source`. The template may use
+/// `{name}` (file name), `{stem}`, `{ext}` (extension with its dot, or empty)
+/// and `{n}` (the index, zero-padded to `width` digits).
+pub fn part_name(template: &str, source: &Path, index: u64, width: usize) -> io::Result<String> {
+    if !template.contains("{n}") {
+        return Err(invalid(format!("name_template '{}' must contain {{n}}", template)));
+    }
+    let text = |part: Option<&std::ffi::OsStr>| part.map(|p| p.to_string_lossy().into_owned()).unwrap_or_default();
+    let extension = text(source.extension());
+    let name = template
+        .replace("{name}", &text(source.file_name()))
+        .replace("{stem}", &text(source.file_stem()))
+        .replace("{ext}", &if extension.is_empty() { String::new() } else { format!(".{}", extension) })
+        .replace("{n}", &format!("{:0width$}", index, width = width));
+    if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+        return Err(invalid(format!("name_template '{}' must produce a plain file name, got '{}'", template, name)));
+    }
+    Ok(name)
+}
+
+/// Number of parts `split_by` cuts `source` into, read without loading it
+pub fn count_parts(source: &Path, size: u64, split_by: SplitBy) -> io::Result<u64> {
+    match split_by {
+        SplitBy::Bytes(0) | SplitBy::Lines(0) => Err(invalid("part size must be at least 1".to_string())),
+        SplitBy::Bytes(bytes) => Ok(size.div_ceil(bytes)),
+        SplitBy::Lines(lines) => {
+            let file = std::fs::File::open(source)?;
+            let mut newlines = 0u64;
+            let mut last = b'\n';
+            super::mmap::scan_range(&file, 0..size, |bytes| {
+                newlines += bytes.iter().filter(|b| **b == b'\n').count() as u64;
+                if let Some(byte) = bytes.last() {
+                    last = *byte;
+                }
+            })?;
+            // A last line without a newline still counts
+            let total = newlines + u64::from(size > 0 && last != b'\n');
+            Ok(total.div_ceil(lines))
+        }
+    }
+}
+
+/// Parts go to disk as they are read, or into the sandbox once complete
+enum PartSink {
+    Disk(io::BufWriter<std::fs::File>),
+    Sandbox(Vec<u8>),
+}
+
+impl PartSink {
+    fn write_all(&mut self, data: &[u8]) -> io::Result<()> {
+        match self {
+            Self::Disk(writer) => writer.write_all(data),
+            Self::Sandbox(buffer) => {
+                buffer.extend_from_slice(data);
+                Ok(())
+            }
+        }
+    }
+
+    fn finish(self, path: &Path, sandbox: Option<&Sandbox>) -> io::Result<()> {
+        match (self, sandbox) {
+            (Self::Disk(mut writer), _) => writer.flush(),
+            (Self::Sandbox(buffer), Some(sandbox)) => sandbox.write(path, &buffer),
+            (Self::Sandbox(_), None) => unreachable!("sandbox sinks are only made with a sandbox"),
+        }
+    }
+}
+
+/// Cut `contents` (the source's current bytes) into `paths.len()` parts
+/// written to `paths`, returning what each one holds
+pub fn write_parts(
+    contents: &Path,
+    paths: &[PathBuf],
+    split_by: SplitBy,
+    sandbox: Option<&Sandbox>,
+) -> io::Result<Vec<SplitPart>> {
+    let mut reader = BufReader::new(ThrottledReader::new(std::fs::File::open(contents)?));
+    let mut parts = Vec::with_capacity(paths.len());
+    let mut line = Vec::new();
+    for path in paths {
+        let mut sink = match sandbox {
+            Some(_) => PartSink::Sandbox(Vec::new()),
+            None => PartSink::Disk(io::BufWriter::new(std::fs::File::create(path)?)),
+        };
+        let mut hasher = Sha256::new();
+        let mut bytes = 0u64;
+        let mut lines = 0u64;
+        match split_by {
+            SplitBy::Bytes(size) => {
+                let mut chunk = (&mut reader).take(size);
+                let mut buffer = [0u8; 64 * 1024];
+                loop {
+                    let read = chunk.read(&mut buffer)?;
+                    if read == 0 {
+                        break;
+                    }
+                    hasher.update(&buffer[..read]);
+                    sink.write_all(&buffer[..read])?;
+                    bytes += read as u64;
+                }
+            }
+            SplitBy::Lines(count) => {
+                while lines < count {
+                    line.clear();
+                    if reader.read_until(b'\n', &mut line)? == 0 {
+                        break;
+                    }
+                    hasher.update(&line);
+                    sink.write_all(&line)?;
+                    bytes += line.len() as u64;
+                    lines += 1;
+                }
+            }
+        }
+        sink.finish(path, sandbox)?;
+        parts.push(SplitPart {
+            path: path.display().to_string(),
+            bytes,
+            lines: matches!(split_by, SplitBy::Lines(_)).then_some(lines),
+            sha256: format!("{:x}", hasher.finalize()),
+        });
+    }
+    Ok(parts)
+}
@@ -0,0 +1,88 @@
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    str::FromStr,
+    sync::{Mutex, MutexGuard},
+};
+
+use serde::Serialize;
+
+/// What `commit_staged` does with each staged file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CommitAction {
+    #[default]
+    Copy,
+    Move,
+}
+
+impl FromStr for CommitAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "copy" => Ok(Self::Copy),
+            "move" => Ok(Self::Move),
+            other => Err(format!("Unknown action '{}': expected copy or move", other)),
+        }
+    }
+}
+
+/// Paths selected across several calls, waiting for a single commit. Lives for
+/// the lifetime of the server process and is not persisted.
+#[derive(Debug, Default)]
+pub struct StagingArea {
+    entries: Mutex<BTreeSet<PathBuf>>,
+}
+
+impl StagingArea {
+    fn lock(&self) -> MutexGuard<'_, BTreeSet<PathBuf>> {
+        // A panic while holding the lock cannot leave the set half-updated
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Add paths, returning those that were not already staged
+    pub fn add(&self, paths: Vec<PathBuf>) -> Vec<PathBuf> {
+        let mut entries = self.lock();
+        paths.into_iter().filter(|p| entries.insert(p.clone())).collect()
+    }
+
+    /// Remove paths, returning those that were staged
+    pub fn remove(&self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        let mut entries = self.lock();
+        paths.iter().filter(|p| entries.remove(*p)).cloned().collect()
+    }
+
+    pub fn list(&self) -> Vec<PathBuf> {
+        self.lock().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) -> usize {
+        let mut entries = self.lock();
+        let count = entries.len();
+        entries.clear();
+        count
+    }
+
+    /// An independent copy of the staged paths
+    pub fn snapshot(&self) -> Self {
+        Self { entries: Mutex::new(self.lock().clone()) }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StagedFile {
+    pub path: String,
+    /// None when the file has disappeared since it was staged
+    pub size: Option<u64>,
+    pub is_dir: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StageOutcome {
+    pub added: Vec<String>,
+    pub already_staged: Vec<String>,
+    pub total_staged: usize,
+    /// Patterns that matched nothing or hit the expansion caps
+    pub notes: Vec<String>,
+}
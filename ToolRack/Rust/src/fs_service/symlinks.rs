@@ -0,0 +1,130 @@
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Serialize;
+
+/// What the server does with symbolic links (--symlinks)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum SymlinkPolicy {
+    /// Refuse every path that is or passes through a link; walks and copies
+    /// leave links out
+    Deny,
+    /// Work on links themselves: delete, move, copy and inspect them, but never
+    /// read, write or walk through them
+    NoFollow,
+    /// Follow links whose targets stay inside the allowed directories
+    #[default]
+    FollowWithinRoot,
+}
+
+impl SymlinkPolicy {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Deny => "deny",
+            Self::NoFollow => "no-follow",
+            Self::FollowWithinRoot => "follow-within-root",
+        }
+    }
+}
+
+/// What a tree walk or copy does with one link it meets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkAction {
+    /// Treat the link as what it points to
+    Follow,
+    /// Keep the link as a link: list it, or recreate it when copying
+    Keep,
+    /// Leave the link out
+    Skip,
+}
+
+/// The symlink policy plus a snapshot of the allow and block rules, for
+/// deciding about links met while walking a tree on the blocking pool
+#[derive(Clone)]
+pub struct LinkRules {
+    policy: SymlinkPolicy,
+    permits: Arc<dyn Fn(&Path) -> bool + Send + Sync>,
+}
+
+impl LinkRules {
+    pub fn new(policy: SymlinkPolicy, permits: impl Fn(&Path) -> bool + Send + Sync + 'static) -> Self {
+        Self { policy, permits: Arc::new(permits) }
+    }
+
+    pub fn policy(&self) -> SymlinkPolicy {
+        self.policy
+    }
+
+    pub fn action(&self, link: &Path) -> LinkAction {
+        match self.policy {
+            SymlinkPolicy::Deny => LinkAction::Skip,
+            SymlinkPolicy::NoFollow => LinkAction::Keep,
+            // A link leading outside is kept as a link, never followed
+            SymlinkPolicy::FollowWithinRoot if (self.permits)(link) => LinkAction::Follow,
+            SymlinkPolicy::FollowWithinRoot => LinkAction::Keep,
+        }
+    }
+
+    /// `action` for a walk that follows links only when asked to
+    pub fn walk_action(&self, link: &Path, follow: bool) -> LinkAction {
+        match self.policy {
+            SymlinkPolicy::Deny => LinkAction::Skip,
+            _ if !follow => LinkAction::Keep,
+            _ => self.action(link),
+        }
+    }
+
+    /// Why `link` was left out of a copy
+    pub fn skip_reason(&self, link: &Path) -> String {
+        format!("{}: symbolic links are refused (--symlinks {})", link.display(), self.policy.name())
+    }
+}
+
+/// Every link followed, as before there was a policy
+impl Default for LinkRules {
+    fn default() -> Self {
+        Self::new(SymlinkPolicy::FollowWithinRoot, |_| true)
+    }
+}
+
+impl fmt::Debug for LinkRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LinkRules").field("policy", &self.policy).finish_non_exhaustive()
+    }
+}
+
+/// The deepest symbolic link `path` is or passes through below `base`, the
+/// allowed directory it is in; links above it are the operator's business.
+/// Without a base every component is looked at.
+pub fn find_symlink(path: &Path, base: Option<&Path>) -> Option<PathBuf> {
+    path.ancestors()
+        .take_while(|ancestor| base.is_none_or(|base| ancestor.starts_with(base) && *ancestor != base))
+        .find(|ancestor| std::fs::symlink_metadata(ancestor).is_ok_and(|m| m.file_type().is_symlink()))
+        .map(Path::to_path_buf)
+}
+
+/// Recreate the link at `source` as `destination`, pointing at the same target
+pub fn copy_link(source: &Path, destination: &Path) -> std::io::Result<()> {
+    let target = std::fs::read_link(source)?;
+    symlink(&target, destination, source.is_dir())
+}
+
+/// Create `link` pointing at `target`, stored as written. Windows needs to
+/// know whether the target is a directory.
+pub fn symlink(target: &Path, link: &Path, is_dir: bool) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let _ = is_dir;
+        std::os::unix::fs::symlink(target, link)
+    }
+    #[cfg(windows)]
+    return if is_dir {
+        std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        std::os::windows::fs::symlink_file(target, link)
+    };
+}
@@ -0,0 +1,153 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Where tags are kept unless --tags-file says otherwise
+pub const DEFAULT_TAGS_FILE: &str = "~/.aichemistforge/tags.json";
+pub const MAX_TAG_LENGTH: usize = 64;
+
+/// A free-text note attached to a path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Annotation {
+    pub note: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Everything recorded about one path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathMetadata {
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Annotation>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl PathMetadata {
+    fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.notes.is_empty()
+    }
+}
+
+/// A path with its metadata, as returned by queries
+#[derive(Debug, Clone, Serialize)]
+pub struct TaggedPath {
+    pub path: String,
+    /// False once the path has been moved or deleted
+    pub exists: bool,
+    #[serde(flatten)]
+    pub metadata: PathMetadata,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TagFile {
+    #[serde(default)]
+    paths: BTreeMap<String, PathMetadata>,
+}
+
+/// Tags and notes on absolute paths, kept in a JSON file so they outlive the
+/// session. Every change re-reads the file first, so sessions sharing it see
+/// each other's work.
+#[derive(Debug)]
+pub struct TagStore {
+    file: PathBuf,
+    /// Serializes this process's read-modify-write cycles
+    guard: Mutex<()>,
+}
+
+impl Default for TagStore {
+    fn default() -> Self {
+        Self::new(super::utils::expand_home(PathBuf::from(DEFAULT_TAGS_FILE)))
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Trimmed tags, refusing empty, overlong or multi-word ones
+pub fn normalize_tags(tags: &[String]) -> io::Result<BTreeSet<String>> {
+    tags.iter()
+        .map(|tag| {
+            let tag = tag.trim();
+            if tag.is_empty() || tag.len() > MAX_TAG_LENGTH || tag.contains(char::is_whitespace) {
+                Err(invalid(format!(
+                    "'{}' is not a valid tag: use 1 to {} characters without spaces",
+                    tag, MAX_TAG_LENGTH
+                )))
+            } else {
+                Ok(tag.to_string())
+            }
+        })
+        .collect()
+}
+
+impl TagStore {
+    pub fn new(file: PathBuf) -> Self {
+        Self { file, guard: Mutex::new(()) }
+    }
+
+    pub fn file(&self) -> &Path {
+        &self.file
+    }
+
+    fn load(&self) -> io::Result<TagFile> {
+        match std::fs::read_to_string(&self.file) {
+            Ok(text) => serde_json::from_str(&text).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("{} is not a tag store: {}", self.file.display(), e))
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(TagFile::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    // Written to a sibling and renamed over the store, so readers never see half a file
+    fn save(&self, data: &TagFile) -> io::Result<()> {
+        if let Some(parent) = self.file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file_name = self.file.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = self.file.with_file_name(format!(".{}.tmp", file_name));
+        std::fs::write(&temp_path, serde_json::to_vec_pretty(data).map_err(io::Error::other)?)?;
+        std::fs::rename(&temp_path, &self.file).inspect_err(|_| {
+            let _ = std::fs::remove_file(&temp_path);
+        })
+    }
+
+    /// Apply `change` to the metadata of `path` and store the result; paths
+    /// left with no tags or notes are forgotten
+    pub fn update(&self, path: &Path, change: impl FnOnce(&mut PathMetadata)) -> io::Result<PathMetadata> {
+        let _guard = self.guard.lock().unwrap_or_else(|e| e.into_inner());
+        let mut data = self.load()?;
+        let key = path.display().to_string();
+        let mut metadata = data.paths.remove(&key).unwrap_or_default();
+        change(&mut metadata);
+        metadata.updated_at = Some(Utc::now());
+        if !metadata.is_empty() {
+            data.paths.insert(key, metadata.clone());
+        }
+        self.save(&data)?;
+        Ok(metadata)
+    }
+
+    /// Every recorded path at or below `scope`
+    pub fn under(&self, scope: &Path) -> io::Result<Vec<TaggedPath>> {
+        Ok(self
+            .load()?
+            .paths
+            .into_iter()
+            .filter(|(path, _)| Path::new(path).starts_with(scope))
+            .map(|(path, metadata)| TaggedPath {
+                exists: Path::new(&path).exists(),
+                path,
+                metadata,
+            })
+            .collect())
+    }
+}
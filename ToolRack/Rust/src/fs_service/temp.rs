@@ -0,0 +1,123 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Name of the scratch root created inside the first allowed directory
+pub const DEFAULT_SCRATCH_DIR_NAME: &str = ".aichemist-scratch";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TempKind {
+    File,
+    Directory,
+}
+
+/// A path handed out by create_temp_file or create_temp_dir
+#[derive(Debug, Clone, Serialize)]
+pub struct TempEntry {
+    pub path: String,
+    pub kind: TempKind,
+    /// The operation mode whose completion removes it; None lives until the
+    /// session ends
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u64>,
+    pub created_at: DateTime<Utc>,
+}
+
+struct TempState {
+    root: PathBuf,
+    session: String,
+    next: AtomicU64,
+    entries: Mutex<Vec<TempEntry>>,
+}
+
+impl Drop for TempState {
+    // The session's scratch directory goes when the server does
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(self.root.join(format!("session-{}", self.session)));
+    }
+}
+
+/// Uniquely named scratch paths under `<root>/session-<id>`, remembered so
+/// they can be removed with the operation mode that made them. Clones share
+/// the same paths.
+#[derive(Clone)]
+pub struct TempArea {
+    state: Arc<TempState>,
+}
+
+impl TempArea {
+    pub fn new(root: PathBuf, session: &str) -> Self {
+        Self {
+            state: Arc::new(TempState {
+                root,
+                session: session.to_string(),
+                next: AtomicU64::new(1),
+                entries: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.state.root
+    }
+
+    /// Where this session's temporary paths are made
+    pub fn session_dir(&self) -> PathBuf {
+        self.state.root.join(format!("session-{}", self.state.session))
+    }
+
+    /// A name nothing else in the session directory has had
+    pub fn unique_name(&self, prefix: &str, suffix: &str) -> io::Result<String> {
+        for part in [prefix, suffix] {
+            if part.contains(['/', '\\']) || part == ".." {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("'{}' may not contain path separators", part),
+                ));
+            }
+        }
+        let n = self.state.next.fetch_add(1, Ordering::Relaxed);
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+        Ok(format!("{}{}-{:08x}{}", prefix, n, nanos, suffix))
+    }
+
+    pub fn record(&self, entry: TempEntry) {
+        self.state.entries.lock().unwrap().push(entry);
+    }
+
+    pub fn entries(&self) -> Vec<TempEntry> {
+        self.state.entries.lock().unwrap().clone()
+    }
+
+    /// Forget the entries made during operation mode `mode` and delete them,
+    /// returning the paths that were removed
+    pub fn remove_for_mode(&self, mode: u64) -> Vec<String> {
+        let removed: Vec<TempEntry> = {
+            let mut entries = self.state.entries.lock().unwrap();
+            let (removed, kept) = entries.drain(..).partition(|entry| entry.mode == Some(mode));
+            *entries = kept;
+            removed
+        };
+        removed
+            .into_iter()
+            .filter(|entry| {
+                let result = match entry.kind {
+                    TempKind::File => std::fs::remove_file(&entry.path),
+                    TempKind::Directory => std::fs::remove_dir_all(&entry.path),
+                };
+                result.is_ok()
+            })
+            .map(|entry| entry.path)
+            .collect()
+    }
+}
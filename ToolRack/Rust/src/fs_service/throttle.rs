@@ -0,0 +1,119 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use once_cell::sync::Lazy;
+
+use crate::metrics;
+
+/// Throttled work is charged in pieces of at most this many bytes, so one
+/// large buffer cannot run far ahead of the limit
+pub const THROTTLE_CHUNK: usize = 64 * 1024;
+
+/// Bytes per second for bulk copies, archives and hashing; 0 is unlimited
+static LIMIT: AtomicU64 = AtomicU64::new(0);
+/// When the bytes charged so far will have been paid for
+static NEXT_FREE: Lazy<Mutex<Instant>> = Lazy::new(|| Mutex::new(Instant::now()));
+
+/// Cap the bytes per second read by bulk copies, archives and hashing across
+/// the whole server; None lifts the cap
+pub fn set_io_limit(bytes_per_sec: Option<u64>) {
+    LIMIT.store(bytes_per_sec.unwrap_or(0), Ordering::Relaxed);
+}
+
+pub fn io_limit() -> Option<u64> {
+    Some(LIMIT.load(Ordering::Relaxed)).filter(|limit| *limit > 0)
+}
+
+/// Account for `bytes` about to be moved, sleeping the calling thread until
+/// the limit allows them. Concurrent callers share one budget.
+pub fn consume(bytes: usize) {
+    let Some(limit) = io_limit() else {
+        return;
+    };
+    let cost = Duration::from_secs_f64(bytes as f64 / limit as f64);
+    let now = Instant::now();
+    let ready = {
+        let mut next_free = NEXT_FREE.lock().unwrap_or_else(|e| e.into_inner());
+        // Idle time is not saved up for a later burst
+        let start = (*next_free).max(now);
+        *next_free = start + cost;
+        start
+    };
+    if ready > now {
+        std::thread::sleep(ready - now);
+    }
+}
+
+/// Feed `bytes` to `visit` in throttled pieces
+pub fn visit_throttled(bytes: &[u8], mut visit: impl FnMut(&[u8])) {
+    metrics::record_read(bytes.len() as u64);
+    if io_limit().is_none() {
+        visit(bytes);
+        return;
+    }
+    for chunk in bytes.chunks(THROTTLE_CHUNK) {
+        consume(chunk.len());
+        visit(chunk);
+    }
+}
+
+/// A reader whose reads are charged to the throttle
+pub struct ThrottledReader<R> {
+    inner: R,
+}
+
+impl<R: Read> ThrottledReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner }
+    }
+}
+
+impl<R: Read> Read for ThrottledReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let limit = if io_limit().is_some() { buf.len().min(THROTTLE_CHUNK) } else { buf.len() };
+        let read = self.inner.read(&mut buf[..limit])?;
+        consume(read);
+        metrics::record_read(read as u64);
+        Ok(read)
+    }
+}
+
+/// `fs::copy` that honours the throttle. Unthrottled copies go through
+/// `fs::copy` itself so they keep its platform fast paths.
+pub fn copy(source: &Path, destination: &Path) -> io::Result<u64> {
+    if io_limit().is_none() {
+        let copied = fs::copy(source, destination)?;
+        metrics::record_read(copied);
+        metrics::record_written(copied);
+        return Ok(copied);
+    }
+    let mut reader = ThrottledReader::new(fs::File::open(source)?);
+    let mut writer = fs::File::create(destination)?;
+    let copied = io::copy(&mut reader, &mut writer)?;
+    metrics::record_written(copied);
+    // fs::copy carries the permission bits over too
+    writer.set_permissions(reader.inner.metadata()?.permissions())?;
+    Ok(copied)
+}
+
+/// `tokio::fs::copy` that honours the throttle
+pub async fn copy_async(source: &Path, destination: &Path) -> io::Result<u64> {
+    if io_limit().is_none() {
+        let copied = tokio::fs::copy(source, destination).await?;
+        metrics::record_read(copied);
+        metrics::record_written(copied);
+        return Ok(copied);
+    }
+    let (source, destination) = (source.to_path_buf(), destination.to_path_buf());
+    tokio::task::spawn_blocking(move || copy(&source, &destination))
+        .await
+        .map_err(io::Error::other)?
+}
@@ -0,0 +1,39 @@
+use std::{
+    fs::{self, FileTimes},
+    io,
+    path::Path,
+    time::SystemTime,
+};
+
+use serde::Serialize;
+
+/// What touch did to a path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Touched {
+    /// The file did not exist and was created empty
+    Created,
+    /// The file existed; its access and modification times were set
+    Updated,
+    /// The file exists in --sandbox mode, which does not track times
+    Unchanged,
+}
+
+impl Touched {
+    /// e.g. `Created empty file notes.md`
+    pub fn describe(&self, path: &str) -> String {
+        match self {
+            Touched::Created => format!("Created empty file {}", path),
+            Touched::Updated => format!("Updated the access and modification times of {}", path),
+            Touched::Unchanged => format!("{} already exists; --sandbox mode does not track times", path),
+        }
+    }
+}
+
+/// Set both the access and modification time of `path`, a file or directory
+pub fn set_times(path: &Path, time: SystemTime) -> io::Result<()> {
+    // Windows needs write access to change times; elsewhere owning the file
+    // is enough, so read-only files can be touched too
+    let file = if cfg!(windows) { fs::OpenOptions::new().write(true).open(path)? } else { fs::File::open(path)? };
+    file.set_times(FileTimes::new().set_accessed(time).set_modified(time))
+}
@@ -0,0 +1,199 @@
+use std::{
+    ffi::{OsStr, OsString},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Where delete_file puts things unless asked to delete them permanently
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Trash {
+    /// The user's trash, through the `trash` crate: the freedesktop.org
+    /// trash on Linux and the BSDs (the home trash, or `$topdir/.Trash-$uid`
+    /// for an item on another mount), the Trash on macOS and the Recycle Bin
+    /// on Windows
+    System,
+    /// A freedesktop.org trash directory given by --trash-dir, with `files/`
+    /// holding the items and `info/` a `.trashinfo` for each saying where it
+    /// came from. The `trash` crate always picks its own directory, so this
+    /// one is written here.
+    Freedesktop(PathBuf),
+}
+
+/// How delete_file gets rid of something
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeleteMode {
+    /// Into the trash when there is one (--trash), otherwise for good
+    #[default]
+    Trash,
+    /// For good, whatever the trash setting
+    Permanent,
+    /// For good, overwriting file contents with random data first; nothing
+    /// is kept for backup or undo
+    Secure,
+}
+
+/// Where a trashed item ended up, when that is known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trashed {
+    pub location: PathBuf,
+    /// The `.trashinfo` written for it
+    pub info: Option<PathBuf>,
+}
+
+impl Trash {
+    pub fn describe(&self) -> String {
+        match self {
+            Trash::System if cfg!(windows) => "Recycle Bin".to_string(),
+            Trash::System => "the user's trash".to_string(),
+            Trash::Freedesktop(dir) => dir.display().to_string(),
+        }
+    }
+
+    /// Move `path` into the trash. Returns where it went, or None where the
+    /// platform does not say (macOS, Windows).
+    pub fn send(&self, path: &Path) -> io::Result<Option<Trashed>> {
+        let name = path
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "cannot trash a path without a file name"))?;
+        match self {
+            Trash::System => send_system(path),
+            Trash::Freedesktop(dir) => send_freedesktop(dir, path, name).map(Some),
+        }
+    }
+}
+
+fn send_system(path: &Path) -> io::Result<Option<Trashed>> {
+    trash::delete(path).map_err(|e| match e {
+        trash::Error::FileSystem { source, .. } => source,
+        e => io::Error::other(e.to_string()),
+    })?;
+    Ok(find_trashed(path))
+}
+
+// The crate does not say where an item went, so look for the newest
+// .trashinfo naming it among the trash directories it lists
+#[cfg(all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android")))]
+fn find_trashed(path: &Path) -> Option<Trashed> {
+    // The crate records the path with its parent canonicalized
+    let original = match (path.parent().and_then(|p| p.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    };
+    let info = trash::os_limited::list()
+        .ok()?
+        .into_iter()
+        .filter(|item| item.original_path() == original)
+        .map(|item| PathBuf::from(item.id))
+        .max_by_key(|info| fs::metadata(info).and_then(|m| m.modified()).ok())?;
+    let location = info.parent()?.parent()?.join("files").join(info.file_stem()?);
+    Some(Trashed { location, info: Some(info) })
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"), not(target_os = "ios"), not(target_os = "android"))))]
+fn find_trashed(_path: &Path) -> Option<Trashed> {
+    None
+}
+
+// `name`, then `name.2`, `name.3`, ...
+fn candidate(name: &OsStr, n: usize) -> OsString {
+    let mut candidate = name.to_os_string();
+    if n > 1 {
+        candidate.push(format!(".{}", n));
+    }
+    candidate
+}
+
+fn send_freedesktop(dir: &Path, path: &Path, name: &OsStr) -> io::Result<Trashed> {
+    let files = dir.join("files");
+    let info = dir.join("info");
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+    for n in 1.. {
+        let trashed_name = candidate(name, n);
+        let location = files.join(&trashed_name);
+        let mut info_name = trashed_name;
+        info_name.push(".trashinfo");
+        let info_path = info.join(info_name);
+        // Creating the .trashinfo claims the name, as the spec asks
+        let mut info_file = match fs::OpenOptions::new().write(true).create_new(true).open(&info_path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        };
+        if fs::symlink_metadata(&location).is_ok() {
+            fs::remove_file(&info_path).ok();
+            continue;
+        }
+        let written = write!(
+            info_file,
+            "[Trash Info]\nPath={}\nDeletionDate={}\n",
+            encode_path(path),
+            chrono::Local::now().format("%Y-%m-%dT%H:%M:%S")
+        );
+        if let Err(e) = written.and_then(|_| move_item(path, &location)) {
+            fs::remove_file(&info_path).ok();
+            return Err(e);
+        }
+        return Ok(Trashed { location, info: Some(info_path) });
+    }
+    unreachable!()
+}
+
+/// Rename `from` to `to`, or across file systems copy it, links as links, and
+/// remove the original. A copy that fails part way is cleaned up again.
+pub fn move_item(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {}
+        renamed => return renamed,
+    }
+    let is_dir = fs::symlink_metadata(from)?.is_dir();
+    if let Err(e) = copy_item(from, to) {
+        if is_dir { fs::remove_dir_all(to) } else { fs::remove_file(to) }.ok();
+        return Err(e);
+    }
+    if is_dir { fs::remove_dir_all(from) } else { fs::remove_file(from) }
+}
+
+fn copy_item(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(from)?;
+    if metadata.file_type().is_symlink() {
+        super::symlinks::copy_link(from, to)
+    } else if metadata.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_item(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions())
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+// The Path= value: percent-encoded bytes, keeping the slashes
+fn encode_path(path: &Path) -> String {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let bytes = path.to_string_lossy().into_owned().into_bytes();
+    let mut encoded = String::with_capacity(bytes.len());
+    for byte in bytes {
+        if byte.is_ascii_alphanumeric() || b"-_.~/".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Take a trashed item back out to `path`, dropping its `.trashinfo`
+pub async fn restore(trashed: &Trashed, path: &Path) -> io::Result<()> {
+    let (location, destination) = (trashed.location.clone(), path.to_path_buf());
+    tokio::task::spawn_blocking(move || move_item(&location, &destination)).await.map_err(io::Error::other)??;
+    if let Some(info) = &trashed.info {
+        tokio::fs::remove_file(info).await.ok();
+    }
+    Ok(())
+}
@@ -0,0 +1,370 @@
+use std::{
+    collections::VecDeque,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Mutex, MutexGuard,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::trash::Trashed;
+
+/// Steps kept for undo unless --undo-history says otherwise
+pub const DEFAULT_UNDO_HISTORY: usize = 100;
+
+/// Files larger than this are not copied before they are overwritten, so
+/// the write cannot be undone
+pub const MAX_SAVED_BYTES: u64 = 64 * 1024 * 1024;
+
+/// How to take one step back
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoAction {
+    /// Put back the content saved at `saved`, or remove `path` when the
+    /// step created it
+    Restore { path: PathBuf, saved: Option<PathBuf> },
+    /// Replace the link a dedupe put at `path` with the copy of the file
+    /// saved at `saved`
+    Unlink { path: PathBuf, saved: PathBuf },
+    /// Move `to` back to `from`
+    MoveBack { from: PathBuf, to: PathBuf },
+    /// Move the deleted `path`, kept at `trashed`, back into place
+    Untrash { path: PathBuf, trashed: PathBuf },
+    /// Take the deleted `path` back out of the system trash, where it is at
+    /// `trashed` with its `.trashinfo` at `info`
+    FromTrash { path: PathBuf, trashed: PathBuf, info: Option<PathBuf> },
+    /// Nothing was kept, e.g. the file was too large to copy
+    Irreversible { path: PathBuf, reason: String },
+}
+
+impl UndoAction {
+    /// The paths undoing it writes to
+    pub fn targets(&self) -> Vec<&Path> {
+        match self {
+            UndoAction::Restore { path, .. }
+            | UndoAction::Unlink { path, .. }
+            | UndoAction::Untrash { path, .. }
+            | UndoAction::FromTrash { path, .. }
+            | UndoAction::Irreversible { path, .. } => vec![path],
+            UndoAction::MoveBack { from, to } => vec![from, to],
+        }
+    }
+
+    /// Copies the journal keeps for it. What went to the system trash stays
+    /// there when the step is forgotten.
+    fn kept(&self) -> Option<&Path> {
+        match self {
+            UndoAction::Restore { saved: Some(saved), .. } | UndoAction::Unlink { saved, .. } => Some(saved),
+            UndoAction::Untrash { trashed, .. } => Some(trashed),
+            _ => None,
+        }
+    }
+}
+
+/// One change to the file system that can be taken back
+#[derive(Debug, Clone, Serialize)]
+pub struct UndoStep {
+    pub step: u64,
+    pub operation: String,
+    pub time: DateTime<Utc>,
+    pub action: UndoAction,
+}
+
+impl UndoStep {
+    /// e.g. `step 4 (edit_file /src/main.rs)`
+    pub fn describe(&self) -> String {
+        let paths: Vec<String> = self.action.targets().iter().map(|path| path.display().to_string()).collect();
+        format!("step {} ({} {})", self.step, self.operation, paths.join(" -> "))
+    }
+}
+
+/// The recent writes, edits, moves and deletes, newest last, with what is
+/// needed to reverse them: copies of overwritten files and the deleted items
+/// themselves, kept in a directory of the journal's own that goes with it.
+#[derive(Debug)]
+pub struct UndoJournal {
+    dir: PathBuf,
+    capacity: AtomicUsize,
+    next_step: AtomicU64,
+    steps: Mutex<VecDeque<UndoStep>>,
+}
+
+impl Default for UndoJournal {
+    fn default() -> Self {
+        static NEXT_JOURNAL: AtomicU64 = AtomicU64::new(1);
+        let stamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().subsec_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "aichemistforge-undo-{}-{}-{:08x}",
+            std::process::id(),
+            NEXT_JOURNAL.fetch_add(1, Ordering::Relaxed),
+            stamp
+        ));
+        Self {
+            dir,
+            capacity: AtomicUsize::new(DEFAULT_UNDO_HISTORY),
+            next_step: AtomicU64::new(1),
+            steps: Mutex::new(VecDeque::new()),
+        }
+    }
+}
+
+impl Drop for UndoJournal {
+    // What the journal kept goes when the server does, including items
+    // trashed beside their originals, which would otherwise linger as
+    // hidden files in the user's directories
+    fn drop(&mut self) {
+        for step in self.steps().iter() {
+            if let Some(kept) = step.action.kept().filter(|kept| !kept.starts_with(&self.dir)) {
+                remove_any(kept).ok();
+            }
+        }
+        fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+impl UndoJournal {
+    fn steps(&self) -> MutexGuard<'_, VecDeque<UndoStep>> {
+        self.steps.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Keep at most `capacity` steps; 0 turns the journal off
+    pub fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+        self.trim();
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.capacity() > 0
+    }
+
+    /// The steps that can be undone, oldest first
+    pub fn history(&self) -> Vec<UndoStep> {
+        self.steps().iter().cloned().collect()
+    }
+
+    /// The newest step, which undo_last_operation takes back
+    pub fn last_step(&self) -> Option<u64> {
+        self.steps().back().map(|step| step.step)
+    }
+
+    fn allocate(&self) -> u64 {
+        self.next_step.fetch_add(1, Ordering::Relaxed)
+    }
+
+    // Where step `step` keeps its copy of `path`
+    fn keep_path(&self, step: u64, path: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(self.dir.join(format!("{}-{}", step, name)))
+    }
+
+    fn push(&self, step: u64, operation: &str, action: UndoAction) -> u64 {
+        self.steps().push_back(UndoStep { step, operation: operation.to_string(), time: Utc::now(), action });
+        self.trim();
+        step
+    }
+
+    // Forget the oldest steps beyond the capacity, and what they kept
+    fn trim(&self) {
+        let forgotten: Vec<UndoStep> = {
+            let mut steps = self.steps();
+            let excess = steps.len().saturating_sub(self.capacity());
+            steps.drain(..excess).collect()
+        };
+        for step in forgotten {
+            if let Some(kept) = step.action.kept() {
+                remove_any(kept).ok();
+            }
+        }
+    }
+
+    /// Record a change that needs nothing kept to be undone, e.g. a move
+    pub fn record(&self, operation: &str, action: UndoAction) -> Option<u64> {
+        self.enabled().then(|| self.push(self.allocate(), operation, action))
+    }
+
+    /// Save what is at `path` before `operation` overwrites it. A file that
+    /// does not exist yet is removed again by the undo.
+    pub async fn before_write(&self, path: &Path, operation: &str) -> io::Result<Option<u64>> {
+        if !self.enabled() {
+            return Ok(None);
+        }
+        let metadata = match tokio::fs::metadata(path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let step = self.allocate();
+                return Ok(Some(self.push(step, operation, UndoAction::Restore { path: path.to_path_buf(), saved: None })));
+            }
+            Err(e) => return Err(e),
+        };
+        if !metadata.is_file() {
+            return Ok(None);
+        }
+        let step = self.allocate();
+        let action = if metadata.len() > MAX_SAVED_BYTES {
+            UndoAction::Irreversible {
+                path: path.to_path_buf(),
+                reason: format!("the file was over {} bytes, too large to keep a copy of", MAX_SAVED_BYTES),
+            }
+        } else {
+            let saved = self.keep_path(step, path)?;
+            tokio::fs::copy(path, &saved).await?;
+            UndoAction::Restore { path: path.to_path_buf(), saved: Some(saved) }
+        };
+        Ok(Some(self.push(step, operation, action)))
+    }
+
+    /// Save a copy of the file at `path` before `operation` replaces it with
+    /// a link. Undoing the step puts the copy back in place of the link,
+    /// where a plain restore would write through it.
+    pub async fn before_link(&self, path: &Path, operation: &str) -> io::Result<Option<u64>> {
+        if !self.enabled() {
+            return Ok(None);
+        }
+        let step = self.allocate();
+        let action = if tokio::fs::metadata(path).await?.len() > MAX_SAVED_BYTES {
+            UndoAction::Irreversible {
+                path: path.to_path_buf(),
+                reason: format!("the file was over {} bytes, too large to keep a copy of", MAX_SAVED_BYTES),
+            }
+        } else {
+            let saved = self.keep_path(step, path)?;
+            tokio::fs::copy(path, &saved).await?;
+            UndoAction::Unlink { path: path.to_path_buf(), saved }
+        };
+        Ok(Some(self.push(step, operation, action)))
+    }
+
+    /// Drop step `step` again, and what it kept, when the change it was
+    /// recorded for did not happen
+    pub fn discard(&self, step: u64) {
+        let discarded = {
+            let mut steps = self.steps();
+            let position = steps.iter().position(|kept| kept.step == step);
+            position.and_then(|position| steps.remove(position))
+        };
+        if let Some(kept) = discarded.as_ref().and_then(|step| step.action.kept()) {
+            remove_any(kept).ok();
+        }
+    }
+
+    /// Delete `path` by moving it into the journal, or beside itself as a
+    /// hidden `.name.undo-N` when the journal is on another file system.
+    /// Either is removed for good once the step is forgotten or the journal
+    /// dropped.
+    /// Returns the step, or None when the journal is off and nothing was moved.
+    pub async fn trash(&self, path: &Path, operation: &str) -> io::Result<Option<u64>> {
+        if !self.enabled() {
+            return Ok(None);
+        }
+        let step = self.allocate();
+        let mut trashed = self.keep_path(step, path)?;
+        if tokio::fs::rename(path, &trashed).await.is_err() {
+            let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            trashed = path.with_file_name(format!(".{}.undo-{}", name, step));
+            tokio::fs::rename(path, &trashed).await?;
+        }
+        Ok(Some(self.push(step, operation, UndoAction::Untrash { path: path.to_path_buf(), trashed })))
+    }
+
+    /// Drop the steps that touch `path` or anything under it, wiping the
+    /// copies they kept, for a secure delete to leave no trace in the journal
+    pub fn forget(&self, path: &Path) -> io::Result<()> {
+        let forgotten: Vec<UndoStep> = {
+            let mut steps = self.steps();
+            let (forgotten, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut *steps)
+                .into_iter()
+                .partition(|step| step.action.targets().iter().any(|target| target.starts_with(path)));
+            *steps = kept.into();
+            forgotten
+        };
+        for step in forgotten {
+            if let Some(kept) = step.action.kept() {
+                super::wipe::wipe(kept)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Take back the newest step. It stays in the journal when that fails.
+    pub async fn undo_last(&self) -> io::Result<Option<UndoStep>> {
+        let Some(step) = self.steps().pop_back() else {
+            return Ok(None);
+        };
+        match revert(&step.action).await {
+            Ok(()) => Ok(Some(step)),
+            Err(e) => {
+                let message = format!("could not undo {}: {}", step.describe(), e);
+                self.steps().push_back(step);
+                Err(io::Error::new(e.kind(), message))
+            }
+        }
+    }
+}
+
+fn remove_any(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+// Refuse to put something back where something new now is
+async fn ensure_free(path: &Path) -> io::Result<()> {
+    match tokio::fs::symlink_metadata(path).await {
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists again; move it away first", path.display()),
+        )),
+        Err(_) => Ok(()),
+    }
+}
+
+async fn revert(action: &UndoAction) -> io::Result<()> {
+    match action {
+        UndoAction::Restore { path, saved: Some(saved) } => {
+            tokio::fs::copy(saved, path).await?;
+            tokio::fs::remove_file(saved).await
+        }
+        UndoAction::Restore { path, saved: None } => match tokio::fs::remove_file(path).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        },
+        // The copy goes in beside the link and is renamed over it, so a
+        // hard link's other names keep their content
+        UndoAction::Unlink { path, saved } => {
+            let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            let temp_path = path.with_file_name(format!(".{}.undo-tmp", name));
+            tokio::fs::copy(saved, &temp_path).await?;
+            if let Err(e) = tokio::fs::rename(&temp_path, path).await {
+                tokio::fs::remove_file(&temp_path).await.ok();
+                return Err(e);
+            }
+            tokio::fs::remove_file(saved).await
+        }
+        UndoAction::MoveBack { from, to } => {
+            ensure_free(from).await?;
+            tokio::fs::rename(to, from).await
+        }
+        UndoAction::Untrash { path, trashed } => {
+            ensure_free(path).await?;
+            tokio::fs::rename(trashed, path).await
+        }
+        UndoAction::FromTrash { path, trashed, info } => {
+            ensure_free(path).await?;
+            let trashed = Trashed { location: trashed.clone(), info: info.clone() };
+            super::trash::restore(&trashed, path).await
+        }
+        UndoAction::Irreversible { reason, .. } => Err(io::Error::new(io::ErrorKind::Unsupported, reason.clone())),
+    }
+}
@@ -1,12 +1,15 @@
 use std::{
+    ffi::OsStr,
     fs::{self},
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
     time::SystemTime,
 };
 
 use chrono::{DateTime, Local};
 use dirs::home_dir;
 
+use super::roots::{file_uri, path_from_file_uri};
+
 // Add this enum at the top of the file
 #[derive(::serde::Deserialize, ::serde::Serialize, Clone, Debug)]
 pub enum OutputFormat {
@@ -60,8 +63,122 @@ pub fn format_permissions(metadata: &fs::Metadata) -> String {
     }
 }
 
+/// Where `path` really points: its deepest existing ancestor canonicalized,
+/// so every symlink on the way is followed, with the part that does not exist
+/// yet appended and its `.` and `..` applied. A path that does not resolve at
+/// all, e.g. a relative one or a share that cannot be reached, only has its
+/// `.` and `..` applied. On Windows the result is in the form users write,
+/// `C:\dir` or `\\server\share\dir`, not canonicalize's `\\?\` form, so it
+/// compares with the allowed and blocked lists.
 pub fn normalize_path(path: &Path) -> PathBuf {
-    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    for ancestor in path.ancestors() {
+        let Ok(resolved) = ancestor.canonicalize() else {
+            continue;
+        };
+        let mut resolved = strip_verbatim(&resolved);
+        push_components(&mut resolved, path.strip_prefix(ancestor).unwrap_or(Path::new("")));
+        return resolved;
+    }
+    let mut cleaned = PathBuf::new();
+    push_components(&mut cleaned, path);
+    cleaned
+}
+
+/// Append `path` to `base`, applying its `.` and `..` without touching the disk
+fn push_components(base: &mut PathBuf, path: &Path) {
+    for component in path.components() {
+        match component {
+            Component::ParentDir if base.file_name().is_some() => {
+                base.pop();
+            }
+            // `..` of a root is the root; of a relative path, it is kept
+            Component::ParentDir if base.has_root() => {}
+            Component::CurDir => {}
+            other => base.push(other),
+        }
+    }
+}
+
+/// Paths this long need the `\\?\` prefix for the classic Windows file APIs
+pub const MAX_PATH: usize = 260;
+
+/// `path` without its `\\?\` prefix: `\\?\C:\dir` becomes `C:\dir` and
+/// `\\?\UNC\server\share\dir` becomes `\\server\share\dir`. Anything else,
+/// including device paths such as `\\?\Volume{..}`, comes back unchanged.
+pub fn strip_verbatim(path: &Path) -> PathBuf {
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    if let Some(share) = text.strip_prefix(r"\\?\UNC\") {
+        return PathBuf::from(format!(r"\\{}", share));
+    }
+    match text.strip_prefix(r"\\?\") {
+        Some(disk) if disk.as_bytes().get(1) == Some(&b':') => PathBuf::from(disk),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `path` with the `\\?\` prefix that lifts the MAX_PATH limit: `C:\dir`
+/// becomes `\\?\C:\dir` and `\\server\share\dir` becomes
+/// `\\?\UNC\server\share\dir`. The prefix turns off Windows' own clean-up,
+/// so `.` and `..` must already be applied; `/` is replaced by `\`.
+/// Relative and already prefixed paths come back unchanged.
+pub fn to_verbatim(path: &Path) -> PathBuf {
+    let Some(text) = path.to_str() else {
+        return path.to_path_buf();
+    };
+    let text = text.replace('/', r"\");
+    if text.starts_with(r"\\?\") || text.starts_with(r"\\.\") {
+        return path.to_path_buf();
+    }
+    if let Some(share) = text.strip_prefix(r"\\") {
+        return PathBuf::from(format!(r"\\?\UNC\{}", share));
+    }
+    match text.as_bytes() {
+        [_, b':', b'\\', ..] => PathBuf::from(format!(r"\\?\{}", text)),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `path` in a form the file APIs accept at any length: on Windows an
+/// absolute path of MAX_PATH or more is cleaned of `.` and `..` and given the
+/// `\\?\` prefix. Shorter paths, and every path elsewhere, come back unchanged.
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) || !path.is_absolute() || path.as_os_str().len() < MAX_PATH {
+        return path.to_path_buf();
+    }
+    let mut cleaned = PathBuf::new();
+    push_components(&mut cleaned, path);
+    to_verbatim(&cleaned)
+}
+
+/// `path` as text that names it exactly: the path itself when it is valid
+/// Unicode, otherwise its `file://` URI, which keeps every byte of the name.
+/// Path arguments accept the URI back (see [`path_from_text`]).
+pub fn path_text(path: &Path) -> String {
+    match path.to_str() {
+        Some(text) => text.to_string(),
+        None => file_uri(path),
+    }
+}
+
+/// The last component of `path` for a listing: the name itself, or, when it
+/// is not valid Unicode, the whole path as [`path_text`] gives it
+pub fn name_text(path: &Path) -> String {
+    match path.file_name().map(OsStr::to_str) {
+        Some(Some(name)) => name.to_string(),
+        Some(None) => file_uri(path),
+        None => path_text(path),
+    }
+}
+
+/// A path argument as given, or the path a `file://` URI names, so names
+/// [`path_text`] had to encode can be passed back
+pub fn path_from_text(path: &Path) -> PathBuf {
+    path.to_str()
+        .filter(|text| text.starts_with("file://"))
+        .and_then(path_from_file_uri)
+        .unwrap_or_else(|| path.to_path_buf())
 }
 
 pub fn expand_home(path: PathBuf) -> PathBuf {
@@ -96,6 +213,70 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
+/// Build artifacts, VCS metadata and OS junk skipped by recursive traversals
+/// unless `include_defaults_excluded` is set
+pub const DEFAULT_EXCLUDED_NAMES: &[&str] = &[
+    ".git",
+    ".hg",
+    ".svn",
+    "node_modules",
+    "target",
+    "__pycache__",
+    ".pytest_cache",
+    ".mypy_cache",
+    ".DS_Store",
+    "Thumbs.db",
+    "desktop.ini",
+    super::backup::BACKUP_DIR_NAME,
+];
+
+pub fn is_default_excluded(name: &OsStr) -> bool {
+    DEFAULT_EXCLUDED_NAMES.iter().any(|excluded| name == *excluded)
+}
+
+/// Identity of a file or directory independent of the path used to reach it:
+/// (device, inode) on Unix, the canonical path elsewhere
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FileIdentity {
+    #[cfg(unix)]
+    Inode { device: u64, inode: u64 },
+    #[cfg(not(unix))]
+    CanonicalPath(PathBuf),
+}
+
+/// Resolve the identity of `path`, following symlinks
+pub fn file_identity(path: &Path) -> std::io::Result<FileIdentity> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = fs::metadata(path)?;
+        Ok(FileIdentity::Inode { device: metadata.dev(), inode: metadata.ino() })
+    }
+
+    #[cfg(not(unix))]
+    {
+        Ok(FileIdentity::CanonicalPath(fs::canonicalize(path)?))
+    }
+}
+
+pub fn is_glob_pattern(path: &str) -> bool {
+    // The `?` of a `\\?\` prefix is not a wildcard
+    path.strip_prefix(r"\\?\").unwrap_or(path).contains(['*', '?', '['])
+}
+
+/// Prefix each line with its 1-based number, right-aligned and tab-separated
+/// like `cat -n`, starting from `first_line`.
+pub fn number_lines<'a>(lines: impl IntoIterator<Item = &'a str>, first_line: usize) -> String {
+    let lines: Vec<&str> = lines.into_iter().collect();
+    let width = (first_line + lines.len()).saturating_sub(1).max(1).to_string().len();
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| format!("{:>width$}\t{}", first_line + i, line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 pub fn normalize_line_endings(content: &str) -> String {
     content.replace("\r\n", "\n").replace('\r', "\n")
 }
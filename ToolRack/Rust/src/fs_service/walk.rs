@@ -0,0 +1,110 @@
+use std::path::Path;
+
+use walkdir::{DirEntry, WalkDir};
+
+use super::utils::{is_default_excluded, is_glob_pattern};
+use crate::error::{ServiceError, ServiceResult};
+
+const MATCH_OPTIONS: glob::MatchOptions = glob::MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// A name filter: glob when the pattern contains `*`, `?` or `[`, otherwise a
+/// case-insensitive substring. Patterns containing '/' are matched against the
+/// path relative to the traversal root instead of the file name.
+#[derive(Debug, Clone)]
+enum NameMatcher {
+    Glob { pattern: glob::Pattern, full_path: bool },
+    Substring { needle: String, full_path: bool },
+}
+
+impl NameMatcher {
+    fn new(pattern: &str) -> ServiceResult<Self> {
+        let full_path = pattern.contains('/');
+        if is_glob_pattern(pattern) {
+            let pattern = glob::Pattern::new(pattern).map_err(|e| {
+                ServiceError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Invalid glob pattern '{}': {}", pattern, e),
+                ))
+            })?;
+            Ok(Self::Glob { pattern, full_path })
+        } else {
+            Ok(Self::Substring { needle: pattern.to_lowercase(), full_path })
+        }
+    }
+
+    fn matches(&self, root: &Path, path: &Path) -> bool {
+        let candidate = |full_path: bool| {
+            if full_path {
+                path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+            } else {
+                path.file_name().unwrap_or_default().to_string_lossy().into_owned()
+            }
+        };
+        match self {
+            Self::Glob { pattern, full_path } => pattern.matches_with(&candidate(*full_path), MATCH_OPTIONS),
+            Self::Substring { needle, full_path } => candidate(*full_path).to_lowercase().contains(needle),
+        }
+    }
+}
+
+/// Shared include/exclude rules for the recursive finder operations. Symlinks
+/// are not followed.
+#[derive(Debug, Clone, Default)]
+pub struct WalkFilter {
+    include: Option<NameMatcher>,
+    excludes: Vec<NameMatcher>,
+    include_defaults_excluded: bool,
+}
+
+impl WalkFilter {
+    pub fn new(
+        pattern: Option<&str>,
+        exclude_patterns: &[String],
+        include_defaults_excluded: bool,
+    ) -> ServiceResult<Self> {
+        Ok(Self {
+            include: pattern.filter(|p| !p.is_empty()).map(NameMatcher::new).transpose()?,
+            excludes: exclude_patterns.iter().map(|p| NameMatcher::new(p)).collect::<ServiceResult<_>>()?,
+            include_defaults_excluded,
+        })
+    }
+
+    /// Directories and files pruned from the walk entirely
+    pub fn is_excluded(&self, root: &Path, entry: &DirEntry) -> bool {
+        if entry.depth() == 0 {
+            return false;
+        }
+        (!self.include_defaults_excluded && is_default_excluded(entry.file_name()))
+            || self.excludes.iter().any(|m| m.matches(root, entry.path()))
+    }
+
+    pub fn matches_file(&self, root: &Path, path: &Path) -> bool {
+        self.include.as_ref().is_none_or(|m| m.matches(root, path))
+    }
+
+    /// Include and exclude rules applied to a single path, for filtering lists
+    /// that did not come from a walk. Default excludes are not applied.
+    pub fn matches_path(&self, root: &Path, path: &Path) -> bool {
+        self.matches_file(root, path) && !self.excludes.iter().any(|m| m.matches(root, path))
+    }
+
+    /// Every entry under `root` that survives the exclude rules, sorted by name.
+    /// Unreadable entries are skipped.
+    pub fn walk_entries<'a>(&'a self, root: &'a Path) -> impl Iterator<Item = DirEntry> + 'a {
+        WalkDir::new(root)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(move |e| !self.is_excluded(root, e))
+            .filter_map(|e| e.ok())
+    }
+
+    /// Regular files under `root` that pass the include and exclude rules
+    pub fn walk_files<'a>(&'a self, root: &'a Path) -> impl Iterator<Item = DirEntry> + 'a {
+        self.walk_entries(root)
+            .filter(move |e| e.file_type().is_file() && self.matches_file(root, e.path()))
+    }
+}
@@ -0,0 +1,180 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::AtomicU64,
+        Arc, Mutex, MutexGuard,
+    },
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Events a watch keeps between polls; beyond this the oldest are dropped
+/// and counted
+pub const MAX_BUFFERED_EVENTS: usize = 10_000;
+/// Watches a session may hold at once
+pub const MAX_WATCHES: usize = 32;
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchEventKind {
+    Create,
+    Modify,
+    Delete,
+    /// Moved or renamed; reported for the old and the new name where the
+    /// platform tells them apart
+    Rename,
+}
+
+impl WatchEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Create => "create",
+            Self::Modify => "modify",
+            Self::Delete => "delete",
+            Self::Rename => "rename",
+        }
+    }
+
+    #[cfg(feature = "watch")]
+    fn from_notify(kind: &notify::EventKind) -> Option<Self> {
+        use notify::{event::ModifyKind, EventKind};
+        match kind {
+            EventKind::Create(_) => Some(Self::Create),
+            EventKind::Modify(ModifyKind::Name(_)) => Some(Self::Rename),
+            EventKind::Modify(_) => Some(Self::Modify),
+            EventKind::Remove(_) => Some(Self::Delete),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WatchEvent {
+    pub kind: WatchEventKind,
+    pub path: String,
+    pub time: DateTime<Utc>,
+}
+
+/// The events of one watch since the previous poll, oldest first
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchPoll {
+    pub id: String,
+    pub path: String,
+    pub recursive: bool,
+    pub events: Vec<WatchEvent>,
+    /// Events lost because the buffer was full
+    pub dropped: u64,
+}
+
+#[derive(Default)]
+struct Buffer {
+    events: VecDeque<WatchEvent>,
+    dropped: u64,
+}
+
+impl Buffer {
+    // Editors and build tools write in bursts; a repeat of the event just
+    // before it adds nothing
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    fn push(&mut self, event: WatchEvent) {
+        if self.events.back().is_some_and(|last| last.kind == event.kind && last.path == event.path) {
+            return;
+        }
+        if self.events.len() == MAX_BUFFERED_EVENTS {
+            self.events.pop_front();
+            self.dropped += 1;
+        }
+        self.events.push_back(event);
+    }
+}
+
+struct Watch {
+    path: PathBuf,
+    recursive: bool,
+    buffer: Arc<Mutex<Buffer>>,
+    /// Watching stops when this is dropped
+    #[cfg(feature = "watch")]
+    _watcher: notify::RecommendedWatcher,
+}
+
+/// Paths watched for start_watch and poll_watch, each buffering what
+/// changed under it until the next poll
+#[derive(Default)]
+pub struct PathWatches {
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    next_id: AtomicU64,
+    watches: Mutex<HashMap<String, Watch>>,
+}
+
+impl PathWatches {
+    /// Start buffering changes at `path`, and below it when `recursive`.
+    /// Changes to paths `permits` refuses, such as blocked directories, are
+    /// left out.
+    #[cfg(feature = "watch")]
+    pub fn start(&self, path: &Path, recursive: bool, permits: impl Fn(&Path) -> bool + Send + 'static) -> io::Result<String> {
+        use notify::Watcher;
+
+        let mut watches = lock(&self.watches);
+        if watches.len() >= MAX_WATCHES {
+            return Err(io::Error::other(format!(
+                "at most {} paths can be watched at once; stop_watch one first",
+                MAX_WATCHES
+            )));
+        }
+        let buffer = Arc::new(Mutex::new(Buffer::default()));
+        let sink = buffer.clone();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            let Some(kind) = WatchEventKind::from_notify(&event.kind) else {
+                return;
+            };
+            let time = Utc::now();
+            let mut buffer = lock(&sink);
+            for path in event.paths.iter().filter(|path| permits(path)) {
+                buffer.push(WatchEvent { kind, path: path.display().to_string(), time });
+            }
+        })
+        .map_err(io::Error::other)?;
+        let mode = if recursive { notify::RecursiveMode::Recursive } else { notify::RecursiveMode::NonRecursive };
+        watcher.watch(path, mode).map_err(io::Error::other)?;
+
+        let id = format!("watch-{}", self.next_id.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1);
+        watches.insert(id.clone(), Watch { path: path.to_path_buf(), recursive, buffer, _watcher: watcher });
+        Ok(id)
+    }
+
+    #[cfg(not(feature = "watch"))]
+    pub fn start(&self, _path: &Path, _recursive: bool, _permits: impl Fn(&Path) -> bool + Send + 'static) -> io::Result<String> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "this build does not include the watch feature"))
+    }
+
+    /// Take the events buffered for `id` since the last poll, or None for
+    /// an unknown id
+    pub fn poll(&self, id: &str) -> Option<WatchPoll> {
+        let watches = lock(&self.watches);
+        let watch = watches.get(id)?;
+        let mut buffer = lock(&watch.buffer);
+        let Buffer { events, dropped } = std::mem::take(&mut *buffer);
+        Some(WatchPoll {
+            id: id.to_string(),
+            path: watch.path.display().to_string(),
+            recursive: watch.recursive,
+            events: events.into(),
+            dropped,
+        })
+    }
+
+    /// Stop watching; returns false for an unknown id
+    pub fn stop(&self, id: &str) -> bool {
+        lock(&self.watches).remove(id).is_some()
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use walkdir::WalkDir;
+
+const CHUNK: usize = 64 * 1024;
+
+/// Overwrite the regular files at or under `path` with random data, flush
+/// them to disk, then delete `path`. Links are removed, never followed, and
+/// a file with other hard links is refused, as wiping it would wipe them too.
+///
+/// This is best effort: SSDs and flash remap writes and keep old blocks until
+/// they are erased, copy-on-write and journaling file systems (btrfs, ZFS,
+/// APFS, ext4 with data=journal) write the new data elsewhere, and snapshots,
+/// backups and the trash keep their own copies. Only full-disk encryption
+/// protects against those.
+pub fn wipe(path: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(path)?;
+    if metadata.is_dir() {
+        let mut files = Vec::new();
+        for entry in WalkDir::new(path).follow_links(false) {
+            let entry = entry.map_err(io::Error::from)?;
+            if entry.file_type().is_file() {
+                check_links(entry.path(), &entry.metadata().map_err(io::Error::from)?)?;
+                files.push(entry.into_path());
+            }
+        }
+        for file in &files {
+            overwrite(file)?;
+        }
+        fs::remove_dir_all(path)
+    } else {
+        if metadata.is_file() {
+            check_links(path, &metadata)?;
+            overwrite(path)?;
+        }
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(unix)]
+fn check_links(path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    if metadata.nlink() > 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} has {} other hard links; wiping it would destroy their content too",
+                path.display(),
+                metadata.nlink() - 1
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_links(_path: &Path, _metadata: &fs::Metadata) -> io::Result<()> {
+    Ok(())
+}
+
+// One pass of random data over the whole length, synced, then truncated
+fn overwrite(path: &Path) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new().write(true).open(path)?;
+    let mut remaining = file.metadata()?.len();
+    let mut buffer = vec![0u8; CHUNK];
+    while remaining > 0 {
+        let n = remaining.min(CHUNK as u64) as usize;
+        getrandom::fill(&mut buffer[..n]).map_err(|e| io::Error::other(e.to_string()))?;
+        file.write_all(&buffer[..n])?;
+        remaining -= n as u64;
+    }
+    file.sync_all()?;
+    file.set_len(0)?;
+    file.sync_all()
+}
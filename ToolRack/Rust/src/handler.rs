@@ -1,108 +1,563 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
 use serde_json::json;
 
-use crate::{error::ServiceResult, fs_service::FileSystemService, cli::CommandArguments};
+use crate::{
+    cli::CommandArguments,
+    client::ClientPeer,
+    subscriptions::ResourceSubscriptions,
+    error::{ServiceError, ServiceResult},
+    fs_service::{
+        cache::MetadataCache,
+        download::DownloadPolicy,
+        remote::RemoteStorage,
+        resources::{ResourceBody, FILE_URI_TEMPLATE},
+        roots::path_from_file_uri,
+        sandbox::Sandbox,
+        tags::TagStore,
+        throttle,
+        trash::Trash,
+        utils::expand_home,
+        FileSystemService,
+    },
+    config::{Policy, PolicySource, ServerConfig},
+    external_tools::ExternalToolRegistry,
+    logging::{self, log_info, log_warn, LogLevel},
+    metrics,
+    plugins::{Capability, PluginRegistry},
+    prompts,
+    validation::ArgumentValidator,
+};
 use crate::tools::{FileSystemTools, *};
 use crate::tools::operation_mode_management::*;
 use crate::mcp_types::*;
+use crate::profiler::{
+    profile_call, record_if_slow, record_phase, set_slow_threshold, slow_threshold, Phase, SlowOperation,
+};
+use std::time::{Duration, Instant};
+
+/// The `operation` and primary path of a tool call, for the slow-operation log
+fn describe_call(arguments: Option<&serde_json::Value>) -> (Option<String>, Option<String>) {
+    let Some(arguments) = arguments else {
+        return (None, None);
+    };
+    let text = |key: &str| arguments.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let path = ["path", "root_path", "source", "path_a"]
+        .iter()
+        .find_map(|key| text(key))
+        .or_else(|| arguments.get("paths")?.get(0)?.as_str().map(str::to_string));
+    (text("operation"), path)
+}
+
+/// A resource request's failure as a JSON-RPC error
+fn resource_error(e: ServiceError, uri: &str) -> RpcError {
+    let code = match &e {
+        ServiceError::FileNotFound(_) => RESOURCE_NOT_FOUND,
+        ServiceError::PathNotAllowed => INVALID_PARAMS,
+        ServiceError::Io(io) if io.kind() == std::io::ErrorKind::InvalidInput => INVALID_PARAMS,
+        ServiceError::Io(io) if io.kind() == std::io::ErrorKind::Unsupported => METHOD_NOT_FOUND,
+        _ => INTERNAL_ERROR,
+    };
+    RpcError {
+        code,
+        message: e.to_string(),
+        data: Some(json!({ "uri": uri })),
+    }
+}
 
 pub struct MyServerHandler {
-    fs_service: FileSystemService,
+    fs_service: Arc<FileSystemService>,
+    client: Arc<ClientPeer>,
+    subscriptions: ResourceSubscriptions,
+    plugins: PluginRegistry,
+    external_tools: ExternalToolRegistry,
+    tool_style: ToolStyle,
+    /// Checks call arguments against the listed input schemas
+    arguments: ArgumentValidator,
+    dry_run: DryRunMode,
+    /// Allow the directories the client reports with `roots/list`
+    use_client_roots: bool,
+    policy_source: PolicySource,
+    /// Refuse calls that would change files (--readonly)
+    readonly: AtomicBool,
 }
 
 impl MyServerHandler {
     pub fn new(args: &CommandArguments) -> ServiceResult<Self> {
-        let fs_service = FileSystemService::try_new(&args.allowed_directories, &args.blocked_directories)?;
-        Ok(Self {
-            fs_service,
-        })
+        let config = match &args.config {
+            Some(path) => ServerConfig::load(path).map_err(ServiceError::Io)?,
+            None => ServerConfig::default(),
+        };
+        let policy_source = PolicySource::new(args);
+        let policy = policy_source.resolve(&config);
+        let mut fs_service = FileSystemService::try_new(&policy.allowed_directories, &policy.blocked_directories)?;
+        set_slow_threshold(Duration::from_millis(args.slow_op_threshold_ms));
+        throttle::set_io_limit(policy.io_limit);
+        fs_service.set_max_read_bytes(policy.max_read_bytes);
+        fs_service.set_download_policy(DownloadPolicy::new(&args.download_allow, args.download_max_bytes));
+        if let Some(path) = &args.remote_storage {
+            fs_service.set_remote_storage(RemoteStorage::from_config_file(path).map_err(ServiceError::Io)?);
+        }
+        if let Some(path) = &args.metadata_cache {
+            fs_service.set_metadata_cache(MetadataCache::open(path).map_err(ServiceError::Io)?);
+        }
+        fs_service.set_respect_locks(args.respect_locks);
+        fs_service.set_symlink_policy(args.symlinks);
+        fs_service.set_backup_mode(args.backup);
+        fs_service.set_undo_history(args.undo_history);
+        if !args.no_trash {
+            fs_service.set_trash(match &args.trash_dir {
+                Some(dir) => Some(Trash::Freedesktop(expand_home(dir.clone()))),
+                None => Some(Trash::System),
+            });
+        }
+        if let Some(dir) = &args.scratch_dir {
+            fs_service.set_scratch_dir(dir.clone());
+        }
+        if let Some(file) = &args.tags_file {
+            fs_service.set_tag_store(TagStore::new(expand_home(file.clone())));
+        }
+        if args.sandbox {
+            fs_service.set_sandbox(Sandbox::create().map_err(ServiceError::Io)?);
+        }
+        // Reserve every built-in name, whichever --tool-style is in effect
+        let mut reserved: Vec<String> = FileSystemTools::tools(ToolStyle::Both).into_iter().map(|tool| tool.name).collect();
+        let external_tools = ExternalToolRegistry::from_config(&config, &reserved).map_err(ServiceError::Io)?;
+        reserved.extend(external_tools.names());
+        let plugins = match &args.plugins_dir {
+            Some(dir) => PluginRegistry::load_dir(dir, &reserved).map_err(ServiceError::Io)?,
+            None => PluginRegistry::default(),
+        };
+        let client = Arc::new(ClientPeer::default());
+        let log_client = Arc::downgrade(&client);
+        logging::forward_to_client(Some(Box::new(move |level: LogLevel, message: &str| {
+            if let Some(client) = log_client.upgrade().filter(|client| client.is_initialized()) {
+                client.notify(
+                    "notifications/message",
+                    json!({ "level": level.mcp_name(), "logger": "aichemistforge", "data": message }),
+                );
+            }
+        })));
+        let mut handler = Self {
+            fs_service: Arc::new(fs_service),
+            subscriptions: ResourceSubscriptions::new(client.clone()),
+            client,
+            plugins,
+            external_tools,
+            tool_style: args.tool_style,
+            arguments: ArgumentValidator::default(),
+            dry_run: DryRunMode::new(args.dry_run),
+            use_client_roots: !args.ignore_client_roots,
+            policy_source,
+            readonly: AtomicBool::new(policy.readonly),
+        };
+        handler.arguments = ArgumentValidator::new(&handler.tools());
+        Ok(handler)
     }
 
-    pub fn assert_write_access(&self) -> std::result::Result<(), CallToolError> {
-        // Always allow write access since we're in read-write mode
+    /// Every tool this server lists: built-in, external and plugin
+    fn tools(&self) -> Vec<Tool> {
+        let mut tools = FileSystemTools::tools(self.tool_style);
+        tools.extend(self.external_tools.tools());
+        tools.extend(self.plugins.tools());
+        tools
+    }
+
+    /// The connected client, for server-initiated requests
+    pub fn client(&self) -> &ClientPeer {
+        &self.client
+    }
+
+    pub fn is_readonly(&self) -> bool {
+        self.readonly.load(Ordering::Relaxed)
+    }
+
+    /// Refuse `operation` in read-only mode
+    pub fn assert_write_access(&self, operation: &str) -> std::result::Result<(), CallToolError> {
+        if self.is_readonly() {
+            return Err(CallToolError::new(format!(
+                "{} is not allowed: the server is in read-only mode (--readonly), so nothing can be written, moved or deleted",
+                operation
+            )));
+        }
         Ok(())
     }
 
     pub fn startup_message(&self) -> String {
+        let roots = self.fs_service.roots();
         format!(
-            "Secure MCP Filesystem Server running in \"read/write\" mode.\nSecurity model: Allow all except blocked directories.\nAllowed directories: {}\nBlocked directories: {}",
-            if self.fs_service.allowed_directories().is_empty() {
+            "Secure MCP Filesystem Server running in \"{}\" mode.\nSecurity model: Allow all except blocked directories.\nAllowed directories: {}\nBlocked directories: {}\nMetadata cache: {}\nDownloads: {}\nIO limit: {}\nRead limit: {}\nRemote storage: {}\nSandbox: {}\nDry run: {}\nLocks: {} (session {})\nSymlinks: {}\nBackups: {}\nUndo: {}\nTrash: {}\nScratch directory: {}\nTags: {}\nPlugins: {}\nExternal tools: {}",
+            if self.is_readonly() { "read-only" } else { "read/write" },
+            if roots.unrestricted {
                 "ALL (unrestricted)".to_string()
             } else {
-                self.fs_service
-                    .allowed_directories()
-                    .iter()
-                    .map(|p| p.display().to_string())
-                    .collect::<Vec<String>>()
-                    .join(",\n")
+                roots.roots.iter().map(|root| root.path.clone()).chain(roots.allowed_patterns).collect::<Vec<String>>().join(",\n")
             },
-            if self.fs_service.blocked_directories().is_empty() {
+            if roots.blocked.is_empty() && roots.blocked_patterns.is_empty() {
                 "NONE".to_string()
             } else {
                 self.fs_service
                     .blocked_directories()
                     .iter()
                     .map(|p| p.display().to_string())
+                    .chain(roots.blocked_patterns)
                     .collect::<Vec<String>>()
                     .join(",\n")
+            },
+            if self.fs_service.metadata_cache().is_enabled() { "enabled" } else { "disabled" },
+            if self.fs_service.download_policy().is_enabled() {
+                self.fs_service.download_policy().allowed.join(", ")
+            } else {
+                "disabled".to_string()
+            },
+            match throttle::io_limit() {
+                Some(limit) => format!("{}/s", crate::fs_service::utils::format_bytes(limit)),
+                None => "unlimited".to_string(),
+            },
+            match self.fs_service.max_read_bytes() {
+                Some(limit) => crate::fs_service::utils::format_bytes(limit),
+                None => "unlimited".to_string(),
+            },
+            if self.fs_service.remote_storage().is_empty() {
+                "NONE".to_string()
+            } else {
+                self.fs_service.remote_storage().describe().join(", ")
+            },
+            match self.fs_service.sandbox() {
+                Some(sandbox) => format!("enabled (changes held in {} until commit_sandbox)", sandbox.overlay_dir().display()),
+                None => "disabled".to_string(),
+            },
+            if self.dry_run.is_enabled() { "enabled (changes are previewed, never written)" } else { "disabled" },
+            if self.fs_service.locks().respects_locks() { "respected by writes" } else { "advisory" },
+            self.fs_service.locks().session(),
+            self.fs_service.symlink_policy().name(),
+            self.fs_service.backup_mode().name(),
+            match self.fs_service.undo_journal().capacity() {
+                0 => "off".to_string(),
+                steps => format!("last {} steps", steps),
+            },
+            match self.fs_service.trash() {
+                Some(trash) => trash.describe(),
+                None => "off (deletes are permanent)".to_string(),
+            },
+            self.fs_service.scratch_dir().display(),
+            self.fs_service.tag_store().file().display(),
+            if self.plugins.is_empty() {
+                "NONE".to_string()
+            } else {
+                self.plugins.describe().join(", ")
+            },
+            if self.external_tools.is_empty() {
+                "NONE".to_string()
+            } else {
+                self.external_tools.describe().join(", ")
             }
         )
     }
 
+    /// Ask the client for its roots and allow them, replacing the roots it
+    /// reported before. Does nothing when the client does not support roots.
+    pub async fn refresh_client_roots(&self) {
+        if !self.use_client_roots || !self.client.supports("roots") {
+            return;
+        }
+        let result = match self.client.request("roots/list", json!({})).await {
+            Ok(result) => result,
+            Err(e) => {
+                log_warn!("Could not get the client's roots: {}", e);
+                return;
+            }
+        };
+        let mut roots = Vec::new();
+        for root in result["roots"].as_array().into_iter().flatten() {
+            let uri = root["uri"].as_str().unwrap_or_default();
+            match path_from_file_uri(uri) {
+                Some(path) => roots.push(path),
+                None => log_warn!("Ignoring client root {}: not a local file URI", uri),
+            }
+        }
+        log_info!(
+            "Client roots: {}",
+            if roots.is_empty() {
+                "NONE".to_string()
+            } else {
+                roots.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")
+            }
+        );
+        self.fs_service.set_client_roots(roots);
+    }
+
+    /// Re-read the `[policy]` table of --config and apply it. Client sessions
+    /// are kept; calls already past path validation finish under the old
+    /// policy.
+    pub fn reload_policy(&self) -> std::io::Result<Policy> {
+        let policy = match self.policy_source.reload() {
+            Ok(policy) => policy,
+            Err(e) => {
+                log_warn!("Policy not reloaded, keeping the current one: {}", e);
+                return Err(e);
+            }
+        };
+        if let Err(e) = policy.apply(&self.fs_service) {
+            log_warn!("Policy not reloaded, keeping the current one: {}", e);
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()));
+        }
+        self.readonly.store(policy.readonly, Ordering::Relaxed);
+        log_info!("Reloaded the policy: {}", policy.summary());
+        Ok(policy)
+    }
+
     pub async fn handle_list_tools(&self) -> Result<ListToolsResult, RpcError> {
         Ok(ListToolsResult {
-            tools: FileSystemTools::tools(),
+            tools: self.tools(),
             meta: None,
             next_cursor: None,
         })
     }
 
-    pub async fn handle_initialize(&self, _request: InitializeRequest) -> Result<InitializeResult, RpcError> {
+    pub async fn handle_initialize(&self, request: InitializeRequest) -> Result<InitializeResult, RpcError> {
+        let protocol_version = negotiate_protocol_version(&request.params.protocol_version).map_err(|message| {
+            log_warn!("Refusing initialize from {}: {}", request.params.client_info.name, message);
+            RpcError {
+                code: INVALID_PARAMS,
+                message,
+                data: Some(json!({
+                    "requested": request.params.protocol_version,
+                    "supported": SUPPORTED_PROTOCOL_VERSIONS,
+                })),
+            }
+        })?;
+        self.client.set_capabilities(request.params.capabilities);
+
         let mut capabilities = HashMap::new();
         capabilities.insert("tools".to_string(), json!({}));
+        capabilities.insert("resources".to_string(), json!({ "subscribe": ResourceSubscriptions::supported() }));
+        capabilities.insert("prompts".to_string(), json!({}));
+        capabilities.insert("logging".to_string(), json!({}));
+        // MCP roots are a client capability, so the server's own boundaries go
+        // under `experimental` for hosts that want to scope their file pickers
+        let roots = self.fs_service.roots();
+        capabilities.insert("experimental".to_string(), json!({ "roots": &roots }));
 
         Ok(InitializeResult {
-            protocol_version: "2024-11-05".to_string(),
+            protocol_version: protocol_version.to_string(),
             capabilities,
             server_info: ServerInfo {
                 name: "aichemistforge-mcp-server".to_string(),
                 version: "0.1.0".to_string(),
             },
+            instructions: Some(roots.describe()),
+        })
+    }
+
+    // Files are reachable through the template only; there is no fixed resource list
+    pub async fn handle_list_resources(&self) -> Result<ListResourcesResult, RpcError> {
+        Ok(ListResourcesResult {
+            resources: Vec::new(),
+            next_cursor: None,
+        })
+    }
+
+    pub async fn handle_list_resource_templates(&self) -> Result<ListResourceTemplatesResult, RpcError> {
+        Ok(ListResourceTemplatesResult {
+            resource_templates: vec![ResourceTemplate {
+                uri_template: FILE_URI_TEMPLATE.to_string(),
+                name: "file".to_string(),
+                description: Some(
+                    "A file inside the allowed directories, by absolute path. Paths returned by tools can be used as-is.".to_string(),
+                ),
+                mime_type: None,
+            }],
+            next_cursor: None,
+        })
+    }
+
+    pub async fn handle_read_resource(&self, params: ReadResourceParams) -> Result<ReadResourceResult, RpcError> {
+        let resource = self.fs_service.read_resource(&params.uri).await.map_err(|e| resource_error(e, &params.uri))?;
+
+        let (text, blob) = match resource.body {
+            ResourceBody::Text(text) => (Some(text), None),
+            ResourceBody::Blob(blob) => (None, Some(blob)),
+        };
+        Ok(ReadResourceResult {
+            contents: vec![ResourceContents {
+                uri: resource.uri,
+                mime_type: Some(resource.mime_type),
+                text,
+                blob,
+            }],
+        })
+    }
+
+    // Notify the client when the file (or directory) at `uri` changes on disk
+    pub async fn handle_subscribe(&self, params: SubscribeParams) -> Result<serde_json::Value, RpcError> {
+        let path = match self.fs_service.resource_path(&params.uri).await {
+            Ok(path) => self.fs_service.validate_existing_path(&path).await,
+            Err(e) => Err(e),
+        }
+        .map_err(|e| resource_error(e, &params.uri))?;
+        self.subscriptions
+            .subscribe(&params.uri, &path)
+            .map_err(|e| resource_error(ServiceError::Io(e), &params.uri))?;
+        Ok(json!({}))
+    }
+
+    // Unsubscribing from something never subscribed, or since deleted, is not
+    // an error
+    pub async fn handle_unsubscribe(&self, params: SubscribeParams) -> Result<serde_json::Value, RpcError> {
+        let path = self.fs_service.resource_path(&params.uri).await.map_err(|e| resource_error(e, &params.uri))?;
+        self.subscriptions.unsubscribe(&path);
+        Ok(json!({}))
+    }
+
+    // Which log messages are forwarded to the client; stderr keeps --log-level
+    pub async fn handle_set_level(&self, params: SetLevelParams) -> Result<serde_json::Value, RpcError> {
+        let level = LogLevel::from_mcp_name(&params.level).ok_or_else(|| RpcError {
+            code: INVALID_PARAMS,
+            message: format!("Unknown log level '{}'", params.level),
+            data: None,
+        })?;
+        logging::set_client_log_level(level);
+        Ok(json!({}))
+    }
+
+    pub async fn handle_list_prompts(&self) -> Result<ListPromptsResult, RpcError> {
+        Ok(ListPromptsResult {
+            prompts: prompts::list_prompts(),
+            next_cursor: None,
         })
     }
 
+    pub async fn handle_get_prompt(&self, params: GetPromptParams) -> Result<GetPromptResult, RpcError> {
+        prompts::get_prompt(&params, self.tool_style).map_err(|message| RpcError {
+            code: INVALID_PARAMS,
+            message,
+            data: None,
+        })
+    }
+
+    // Run a tool call, counting it in the metrics and logging it to the
+    // slow-operation log when it exceeds the threshold
     pub async fn handle_call_tool(&self, request: CallToolRequest) -> Result<CallToolResult, CallToolError> {
+        let tool = request.params.name.clone();
+        let (operation, path) = describe_call(request.params.arguments.as_ref());
+        let (((result, response_bytes), timings, total), bytes_read, bytes_written) = metrics::measure_io(profile_call(async {
+            let started = Instant::now();
+            let result = self.dispatch_tool(request).await;
+            // Only slow calls pay for measuring how large their response is
+            let response_bytes = (started.elapsed() >= slow_threshold()).then(|| {
+                let started = Instant::now();
+                let bytes = match &result {
+                    Ok(result) => serde_json::to_vec(result).map(|v| v.len()).unwrap_or(0),
+                    Err(e) => e.message.len(),
+                };
+                record_phase(Phase::Formatting, started.elapsed());
+                bytes
+            });
+            (result, response_bytes)
+        }))
+        .await;
+
+        let is_error = result.as_ref().map_or(true, |r| r.is_error.unwrap_or(false));
+        let name = match &operation {
+            Some(operation) => format!("{}.{}", tool, operation),
+            None => tool.clone(),
+        };
+        metrics::record_call(&name, total, is_error, bytes_read, bytes_written);
+        if let Some(response_bytes) = response_bytes {
+            record_if_slow(SlowOperation::new(tool, operation, path, total, timings, response_bytes, is_error));
+        }
+        result
+    }
+
+    async fn dispatch_tool(&self, request: CallToolRequest) -> Result<CallToolResult, CallToolError> {
+        let arguments = request.params.arguments.clone().unwrap_or_else(|| json!({}));
+        if let Err(errors) = self.arguments.validate(&request.params.name, &arguments) {
+            return Ok(CallToolResult {
+                content: vec![Content::Text(TextContent {
+                    text: format!("Invalid arguments for tool '{}':\n- {}", request.params.name, errors.join("\n- ")),
+                })],
+                is_error: Some(true),
+                structured_content: None,
+            });
+        }
+        if !self.dry_run.is_enabled() {
+            return self.run_call(request, &self.fs_service).await;
+        }
+
+        // Dry run: the call works on a sandbox of its own, whose changes are
+        // reported and then thrown away with it
+        let view = Arc::new(self.fs_service.dry_run_view().map_err(CallToolError::new)?);
+        let mut result = self.run_call(request, &view).await?;
+        let changes = view.diff_sandbox().await.map_err(CallToolError::new)?;
+        if !changes.is_empty() {
+            let heading = format!(
+                "Dry run: nothing was written to disk. Outside dry-run mode this call would make {} change(s):",
+                changes.len()
+            );
+            let preview = DiffSandbox::format_changes(&changes, &heading).map_err(CallToolError::new)?;
+            result.content.insert(0, Content::Text(TextContent { text: preview }));
+        }
+        Ok(result)
+    }
+
+    async fn run_call(
+        &self,
+        request: CallToolRequest,
+        fs_service: &Arc<FileSystemService>,
+    ) -> Result<CallToolResult, CallToolError> {
+        if let Some(plugin) = self.plugins.get(&request.params.name) {
+            if plugin.capabilities.contains(&Capability::Write) {
+                self.assert_write_access(&plugin.name)?;
+            }
+            let arguments = request.params.arguments.unwrap_or_else(|| json!({}));
+            return plugin.call(fs_service.clone(), arguments).await;
+        }
+        if let Some(tool) = self.external_tools.get(&request.params.name) {
+            // Whatever the command does is out of our hands
+            self.assert_write_access(&request.params.name)?;
+            let arguments = request.params.arguments.unwrap_or_else(|| json!({}));
+            return tool.call(fs_service, arguments).await;
+        }
+
+        // Grouped tools name the operation in their arguments; flat tools are named after it
+        let arguments = request.params.arguments.clone().unwrap_or_else(|| json!({}));
+        let operation = arguments.get("operation").and_then(|v| v.as_str()).unwrap_or(&request.params.name).to_string();
         let tool_params: FileSystemTools =
-            FileSystemTools::try_from(request.params).map_err(CallToolError::new)?;
+            FileSystemTools::from_call(request.params, self.tool_style).map_err(CallToolError::new)?;
 
-        // Verify write access for tools that modify the file system
-        // Use tool-specific write access checking for better security
-        if tool_params.require_write_access() {
-            self.assert_write_access()?;
+        // Verify write access for the operations that modify the file system
+        if tool_params.require_write_access() && operation_changes_files(&operation, &arguments) {
+            self.assert_write_access(&operation)?;
         }
 
         match tool_params {
             FileSystemTools::SingleFileOperationsTool(params) => {
-                SingleFileOperationsTool::run_tool(params, &self.fs_service).await
+                SingleFileOperationsTool::run_tool(*params, fs_service, &self.client).await
             }
             FileSystemTools::MultipleFileOperationsTool(params) => {
-                MultipleFileOperationsTool::run_tool(params, &self.fs_service).await
+                MultipleFileOperationsTool::run_tool(*params, fs_service).await
             }
             FileSystemTools::DirectoryOperationsTool(params) => {
-                DirectoryOperationsTool::run_tool(params, &self.fs_service).await
+                DirectoryOperationsTool::run_tool(*params, fs_service).await
             }
             FileSystemTools::SearchAndAnalysisTool(params) => {
-                SearchAndAnalysisTool::run_tool(params, &self.fs_service).await
+                SearchAndAnalysisTool::run_tool(*params, fs_service).await
             }
             FileSystemTools::FileManagementTool(params) => {
-                FileManagementTool::run_tool(params, &self.fs_service).await
+                FileManagementTool::run_tool(*params, fs_service).await
             }
             // Operation mode management tools
             FileSystemTools::StartOperationMode(params) => {
-                StartOperationModeTool::run_tool(params).await
+                StartOperationModeTool::run_tool(params, &self.fs_service).await
             }
             FileSystemTools::CompleteCurrentMode(params) => {
-                CompleteCurrentModeTool::run_tool(params).await
+                CompleteCurrentModeTool::run_tool(params, &self.fs_service).await
             }
             FileSystemTools::ListAvailableModes(params) => {
                 ListAvailableModesTool::run_tool(params).await
@@ -110,6 +565,15 @@ impl MyServerHandler {
             FileSystemTools::GetCurrentModeStatus(params) => {
                 GetCurrentModeStatusTool::run_tool(params).await
             }
+            FileSystemTools::GetSlowOperations(params) => {
+                GetSlowOperationsTool::run_tool(params).await
+            }
+            FileSystemTools::ServerStats(params) => params.run_tool().await,
+            FileSystemTools::SetDryRun(params) => params.run_tool(&self.dry_run, &self.fs_service).await,
+            FileSystemTools::ReloadPolicy(params) => params.run_tool(self.reload_policy()).await,
+            FileSystemTools::UndoLastOperation(params) => params.run_tool(fs_service).await,
+            FileSystemTools::UndoToStep(params) => params.run_tool(fs_service).await,
+            FileSystemTools::Flat(call) => call.run_tool(fs_service, &self.client).await,
         }
     }
 }
\ No newline at end of file
@@ -1,14 +1,30 @@
+// The grouped tools' json! schemas nest deeper than the default limit allows
+#![recursion_limit = "256"]
+
 pub mod mcp_types;
 pub mod tools;
 pub mod handler;
+pub mod server;
 pub mod fs_service;
 pub mod cli;
 pub mod error;
 pub mod task_state;
 pub mod retry;
+pub mod profiler;
+pub mod metrics;
+pub mod cancel;
+pub mod client;
+pub mod subscriptions;
+pub mod prompts;
+pub mod plugins;
+pub mod config;
+pub mod external_tools;
+pub mod check_config;
+pub mod logging;
+pub mod validation;
 
 pub use handler::MyServerHandler;
-pub use fs_service::FileSystemService;
+pub use fs_service::{memory::MemoryFileSystem, ops::FileSystemOps, FileSystemService};
 pub use cli::CommandArguments;
 pub use task_state::*;
 
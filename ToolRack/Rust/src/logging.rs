@@ -0,0 +1,336 @@
+// The library and the binary each use a different subset of the macros
+#![allow(unused_macros)]
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Write as _},
+    fs::File,
+    io::Write as _,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering},
+        Mutex, RwLock,
+    },
+};
+
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// How much the server writes to stderr (--log-level)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, clap::ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    /// Startup and configuration messages
+    #[default]
+    Info,
+    /// One line per JSON-RPC message: method, id and size, without the payload
+    Debug,
+    /// Full JSON-RPC payloads, including file contents
+    Trace,
+}
+
+impl LogLevel {
+    /// Parse a level name as used in RUST_LOG (`warn`, `DEBUG`, ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warn" | "warning" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+
+    /// Parse an MCP (syslog) level name as sent in `logging/setLevel`. The
+    /// levels above `error` collapse into it, and `notice` into `info`.
+    pub fn from_mcp_name(name: &str) -> Option<Self> {
+        match name {
+            "debug" => Some(Self::Debug),
+            "info" | "notice" => Some(Self::Info),
+            "warning" => Some(Self::Warn),
+            "error" | "critical" | "alert" | "emergency" => Some(Self::Error),
+            _ => None,
+        }
+    }
+
+    /// The MCP level name for `notifications/message`
+    pub fn mcp_name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warning",
+            Self::Info => "info",
+            Self::Debug | Self::Trace => "debug",
+        }
+    }
+
+    fn from_tracing(level: &tracing::Level) -> Self {
+        match *level {
+            tracing::Level::ERROR => Self::Error,
+            tracing::Level::WARN => Self::Warn,
+            tracing::Level::INFO => Self::Info,
+            tracing::Level::DEBUG => Self::Debug,
+            tracing::Level::TRACE => Self::Trace,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Error => "ERROR",
+            Self::Warn => "WARN",
+            Self::Info => "INFO",
+            Self::Debug => "DEBUG",
+            Self::Trace => "TRACE",
+        }
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+pub fn set_log_level(level: LogLevel) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Where server-side messages go instead of stderr (--log-file)
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Send the crate's `tracing` events to stderr, or to `file` (appended, with
+/// timestamps) when given, and to the client once it asks for them. Other
+/// crates' events (hyper, axum, ...) are dropped.
+pub fn init(level: LogLevel, file: Option<&Path>) -> std::io::Result<()> {
+    set_log_level(level);
+    if let Some(path) = file {
+        let file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+        *LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+    }
+    // Only fails when a subscriber is already installed, which then keeps the events
+    let _ = tracing::subscriber::set_global_default(Logger::default());
+    Ok(())
+}
+
+type Sink = Box<dyn Fn(LogLevel, &str) + Send + Sync>;
+
+/// Where messages are forwarded to the MCP client as `notifications/message`
+static CLIENT_SINK: RwLock<Option<Sink>> = RwLock::new(None);
+static FORWARDING: AtomicBool = AtomicBool::new(false);
+/// The level the client asked for with `logging/setLevel`. Clients that
+/// never ask get nothing, as they may not expect the notifications.
+const CLIENT_LEVEL_UNSET: u8 = u8::MAX;
+static CLIENT_LEVEL: AtomicU8 = AtomicU8::new(CLIENT_LEVEL_UNSET);
+
+/// Forward log messages to the client through `sink`, or stop with None
+pub fn forward_to_client(sink: Option<Sink>) {
+    let mut current = CLIENT_SINK.write().unwrap_or_else(|e| e.into_inner());
+    FORWARDING.store(sink.is_some(), Ordering::Relaxed);
+    *current = sink;
+}
+
+/// The level the client chose with `logging/setLevel`
+pub fn set_client_log_level(level: LogLevel) {
+    CLIENT_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+fn client_enabled(level: LogLevel) -> bool {
+    let client_level = CLIENT_LEVEL.load(Ordering::Relaxed);
+    // Trace output carries whole messages, file contents included
+    FORWARDING.load(Ordering::Relaxed)
+        && client_level != CLIENT_LEVEL_UNSET
+        && level != LogLevel::Trace
+        && level as u8 <= client_level
+}
+
+fn stderr_enabled(level: LogLevel) -> bool {
+    level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+/// Whether messages at `level` are written, to stderr or to the client
+pub fn enabled(level: LogLevel) -> bool {
+    stderr_enabled(level) || client_enabled(level)
+}
+
+fn write(level: LogLevel, line: &str) {
+    if stderr_enabled(level) {
+        let mut file = LOG_FILE.lock().unwrap_or_else(|e| e.into_inner());
+        match file.as_mut() {
+            Some(file) => {
+                let now = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+                let _ = writeln!(file, "{} [{}] {}", now, level.label(), line);
+            }
+            None => eprintln!("[{}] {}", level.label(), line),
+        }
+    }
+    if client_enabled(level) {
+        if let Some(sink) = CLIENT_SINK.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            sink(level, line);
+        }
+    }
+}
+
+/// Collects an event's message and fields as `message key=value ...`
+#[derive(Default)]
+struct Fields {
+    message: String,
+    fields: String,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+struct SpanData {
+    /// ` key=value` pairs, appended to the events inside the span
+    fields: String,
+    parent: Option<span::Id>,
+    references: usize,
+}
+
+thread_local! {
+    /// Spans entered on this thread, innermost last
+    static ENTERED: RefCell<Vec<span::Id>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The subscriber [`init`] installs. Spans (e.g. one per request, carrying
+/// its id and tool name) add their fields to the events logged inside them.
+#[derive(Default)]
+struct Logger {
+    spans: Mutex<HashMap<u64, SpanData>>,
+    next_id: AtomicU64,
+}
+
+impl Logger {
+    fn spans(&self) -> std::sync::MutexGuard<'_, HashMap<u64, SpanData>> {
+        self.spans.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn current() -> Option<span::Id> {
+        ENTERED.with(|entered| entered.borrow().last().cloned())
+    }
+}
+
+impl Subscriber for Logger {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> tracing::subscriber::Interest {
+        // The levels change at runtime, so ours are asked about every time
+        if metadata.target().starts_with(env!("CARGO_CRATE_NAME")) {
+            tracing::subscriber::Interest::sometimes()
+        } else {
+            tracing::subscriber::Interest::never()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        // Spans are kept whatever the level, for the events inside them
+        metadata.is_span() || enabled(LogLevel::from_tracing(metadata.level()))
+    }
+
+    fn new_span(&self, attributes: &span::Attributes<'_>) -> span::Id {
+        let mut fields = Fields::default();
+        attributes.record(&mut fields);
+        let parent = if attributes.is_contextual() { Self::current() } else { attributes.parent().cloned() };
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.spans().insert(id, SpanData { fields: fields.fields, parent, references: 1 });
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, span: &span::Id, values: &span::Record<'_>) {
+        let mut fields = Fields::default();
+        values.record(&mut fields);
+        if let Some(data) = self.spans().get_mut(&span.into_u64()) {
+            data.fields.push_str(&fields.fields);
+        }
+    }
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let mut line = fields.message;
+        line.push_str(&fields.fields);
+
+        let mut span = if event.is_contextual() { Self::current() } else { event.parent().cloned() };
+        let spans = self.spans();
+        while let Some(data) = span.and_then(|id| spans.get(&id.into_u64())) {
+            line.push_str(&data.fields);
+            span = data.parent.clone();
+        }
+        drop(spans);
+        write(LogLevel::from_tracing(event.metadata().level()), &line);
+    }
+
+    fn enter(&self, span: &span::Id) {
+        ENTERED.with(|entered| entered.borrow_mut().push(span.clone()));
+    }
+
+    fn exit(&self, span: &span::Id) {
+        ENTERED.with(|entered| {
+            let mut entered = entered.borrow_mut();
+            if let Some(position) = entered.iter().rposition(|id| id == span) {
+                entered.remove(position);
+            }
+        });
+    }
+
+    fn clone_span(&self, span: &span::Id) -> span::Id {
+        if let Some(data) = self.spans().get_mut(&span.into_u64()) {
+            data.references += 1;
+        }
+        span.clone()
+    }
+
+    fn try_close(&self, span: span::Id) -> bool {
+        let mut spans = self.spans();
+        let Some(data) = spans.get_mut(&span.into_u64()) else {
+            return false;
+        };
+        data.references -= 1;
+        if data.references > 0 {
+            return false;
+        }
+        spans.remove(&span.into_u64());
+        true
+    }
+}
+
+// The macros emit `tracing` events, so they also take structured fields
+// before the message, e.g. `log_info!(tool = name, "...")`
+
+macro_rules! log_error {
+    ($($arg:tt)*) => { ::tracing::error!($($arg)*) };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => { ::tracing::warn!($($arg)*) };
+}
+
+macro_rules! log_info {
+    ($($arg:tt)*) => { ::tracing::info!($($arg)*) };
+}
+
+macro_rules! log_debug {
+    ($($arg:tt)*) => { ::tracing::debug!($($arg)*) };
+}
+
+macro_rules! log_trace {
+    ($($arg:tt)*) => { ::tracing::trace!($($arg)*) };
+}
+
+#[allow(unused_imports)]
+pub(crate) use {log_debug, log_error, log_info, log_trace, log_warn};
@@ -1,3 +1,6 @@
+// The grouped tools' json! schemas nest deeper than the default limit allows
+#![recursion_limit = "256"]
+
 mod handler;
 mod tools;
 mod fs_service;
@@ -7,26 +10,93 @@ mod mcp_types;
 mod server;
 mod task_state;
 mod retry;
+mod profiler;
+mod metrics;
+mod cancel;
+mod client;
+mod subscriptions;
+mod prompts;
+mod plugins;
+mod config;
+mod external_tools;
+mod check_config;
+mod logging;
+mod validation;
 
 use handler::MyServerHandler;
 use cli::CommandArguments;
-use server::McpServer;
+use server::{McpServer, Transport};
 use anyhow::Result;
+use std::time::Duration;
+
+/// How often --metrics-file is rewritten while the server runs
+const METRICS_FILE_INTERVAL: Duration = Duration::from_secs(60);
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    eprintln!("Starting AiChemistForge Rust MCP Server with stdio transport...");
-    eprintln!("Logs will appear on stderr, JSON-RPC communication on stdout");
+fn main() -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let result = runtime.block_on(run());
+    // A read of stdin blocks a thread that cannot be interrupted, so give
+    // cancelled work a moment to clean up rather than waiting on every thread
+    runtime.shutdown_timeout(Duration::from_secs(1));
+    result
+}
 
+async fn run() -> Result<()> {
     // Parse command line arguments
     let args = CommandArguments::parse_from_env()?;
 
+    if args.command == Some(cli::Command::CheckConfig) {
+        let report = check_config::check_config(&args);
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        std::process::exit(if report.ok { 0 } else { 1 });
+    }
+
+    logging::init(args.log_level(), args.log_file.as_deref())
+        .map_err(|e| anyhow::anyhow!("Cannot open the log file: {}", e))?;
+    match args.transport {
+        Transport::Stdio => {
+            logging::log_info!("Starting AiChemistForge Rust MCP Server with stdio transport...");
+            if args.log_file.is_none() {
+                logging::log_info!("Logs will appear on stderr, JSON-RPC communication on stdout");
+            }
+        }
+        Transport::Sse => logging::log_info!("Starting AiChemistForge Rust MCP Server with HTTP+SSE transport..."),
+        Transport::StreamableHttp => {
+            logging::log_info!("Starting AiChemistForge Rust MCP Server with Streamable HTTP transport...")
+        }
+        Transport::Tcp => logging::log_info!("Starting AiChemistForge Rust MCP Server with TCP transport..."),
+    }
+
     // Create the server handler
     let handler = MyServerHandler::new(&args)?;
 
     // Create and run the MCP server
-    let server = McpServer::new(handler);
-    server.run().await?;
+    let server = McpServer::new(handler)
+        .with_shutdown_grace(Duration::from_secs(args.shutdown_grace_secs))
+        .with_max_message_bytes(args.max_message_bytes);
+    tokio::spawn(server::shutdown_on_signal(server.shutdown_token()));
+    #[cfg(unix)]
+    tokio::spawn(server::reload_policy_on_hangup(server.handler()));
+    if let Some(path) = args.metrics_file.clone() {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(METRICS_FILE_INTERVAL);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = metrics::write_snapshot(&path) {
+                    logging::log_warn!("Cannot write the metrics file {}: {}", path.display(), e);
+                }
+            }
+        });
+    }
+    server.serve(args.transport, args.http_bind, args.tcp_bind).await?;
+
+    if let Some(path) = &args.metrics_file {
+        if let Err(e) = metrics::write_snapshot(path) {
+            logging::log_warn!("Cannot write the metrics file {}: {}", path.display(), e);
+        }
+    }
 
+    logging::log_info!("Server stopped");
     Ok(())
 }
@@ -1,6 +1,43 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+// JSON-RPC error codes from the specification
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+/// MCP: `resources/read` named a resource that does not exist
+pub const RESOURCE_NOT_FOUND: i32 = -32002;
+
+/// MCP protocol revisions this server speaks, oldest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", "2025-06-18"];
+pub const LATEST_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Pick the protocol version to answer `initialize` with. A supported version
+/// is echoed back and a newer revision gets the latest one we know, which the
+/// client may still accept. Revisions older than any we support, or strings
+/// that are not a `YYYY-MM-DD` revision at all, cannot be negotiated.
+pub fn negotiate_protocol_version(requested: &str) -> Result<&'static str, String> {
+    if let Some(version) = SUPPORTED_PROTOCOL_VERSIONS.iter().find(|version| **version == requested) {
+        return Ok(version);
+    }
+    let is_revision = requested.len() == 10
+        && requested.char_indices().all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() });
+    if !is_revision {
+        return Err(format!("'{}' is not an MCP protocol version", requested));
+    }
+    // Revisions are dates, so they order as strings
+    if requested < SUPPORTED_PROTOCOL_VERSIONS[0] {
+        return Err(format!(
+            "Protocol version {} is older than any this server supports ({})",
+            requested,
+            SUPPORTED_PROTOCOL_VERSIONS.join(", ")
+        ));
+    }
+    Ok(LATEST_PROTOCOL_VERSION)
+}
+
 // Simple MCP types without external dependencies
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tool {
@@ -8,6 +45,9 @@ pub struct Tool {
     pub description: Option<String>,
     #[serde(rename = "inputSchema")]
     pub input_schema: serde_json::Value,
+    /// JSON Schema of the `structuredContent` the tool returns, if any
+    #[serde(rename = "outputSchema", default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +59,8 @@ pub enum Content {
     ImageContent(ImageContent),
     #[serde(rename = "audio")]
     AudioContent(AudioContent),
+    #[serde(rename = "resource_link")]
+    ResourceLink(ResourceLink),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,11 +94,29 @@ impl AudioContent {
     }
 }
 
+/// Pointer to a resource the client can fetch with `resources/read`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceLink {
+    pub uri: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Size in bytes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallToolResult {
     pub content: Vec<Content>,
     #[serde(rename = "isError", skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
+    /// The result as JSON matching the tool's `outputSchema`, alongside the
+    /// text in `content`
+    #[serde(rename = "structuredContent", default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<serde_json::Value>,
 }
 
 impl CallToolResult {
@@ -64,6 +124,7 @@ impl CallToolResult {
         Self {
             content: content.into_iter().map(|c| Content::ImageContent(c)).collect(),
             is_error: Some(false),
+            structured_content: None,
         }
     }
 
@@ -71,6 +132,7 @@ impl CallToolResult {
         Self {
             content: content.into_iter().map(|c| Content::AudioContent(c)).collect(),
             is_error: Some(false),
+            structured_content: None,
         }
     }
 
@@ -103,6 +165,121 @@ pub struct ListToolsResult {
     pub next_cursor: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListResourcesResult {
+    pub resources: Vec<Resource>,
+    #[serde(rename = "nextCursor", skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceTemplate {
+    #[serde(rename = "uriTemplate")]
+    pub uri_template: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListResourceTemplatesResult {
+    #[serde(rename = "resourceTemplates")]
+    pub resource_templates: Vec<ResourceTemplate>,
+    #[serde(rename = "nextCursor", skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceParams {
+    pub uri: String,
+}
+
+/// Params of `logging/setLevel`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetLevelParams {
+    /// An MCP (syslog) level name: `debug`, `info`, `notice`, `warning`, `error`, ...
+    pub level: String,
+}
+
+/// Params of `resources/subscribe` and `resources/unsubscribe`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscribeParams {
+    pub uri: String,
+}
+
+/// Contents of a resource: exactly one of `text` or `blob` (base64) is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceContents {
+    pub uri: String,
+    #[serde(rename = "mimeType", skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blob: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadResourceResult {
+    pub contents: Vec<ResourceContents>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptArgument {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Prompt {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListPromptsResult {
+    pub prompts: Vec<Prompt>,
+    #[serde(rename = "nextCursor", skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptParams {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptMessage {
+    /// `user` or `assistant`
+    pub role: String,
+    pub content: Content,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetPromptResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InitializeRequest {
     pub params: InitializeParams,
@@ -130,6 +307,9 @@ pub struct InitializeResult {
     pub capabilities: HashMap<String, serde_json::Value>,
     #[serde(rename = "serverInfo")]
     pub server_info: ServerInfo,
+    /// Guidance clients may pass on to the model
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
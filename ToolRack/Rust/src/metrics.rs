@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds of the latency histogram buckets, in milliseconds; slower
+/// calls land in one more bucket past the last
+const LATENCY_BUCKETS_MS: [f64; 12] = [1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0];
+
+/// Bytes moved by the tool call running on this task
+#[derive(Default)]
+struct CallIo {
+    read: Cell<u64>,
+    written: Cell<u64>,
+}
+
+tokio::task_local! {
+    static CALL_IO: CallIo;
+}
+
+// Process-wide totals, including bytes moved on blocking threads, which no
+// single tool call sees
+static BYTES_READ: AtomicU64 = AtomicU64::new(0);
+static BYTES_WRITTEN: AtomicU64 = AtomicU64::new(0);
+
+/// Count `bytes` read from files, towards the running tool call when there is one
+pub fn record_read(bytes: u64) {
+    BYTES_READ.fetch_add(bytes, Ordering::Relaxed);
+    let _ = CALL_IO.try_with(|io| io.read.set(io.read.get() + bytes));
+}
+
+/// Count `bytes` written to files, towards the running tool call when there is one
+pub fn record_written(bytes: u64) {
+    BYTES_WRITTEN.fetch_add(bytes, Ordering::Relaxed);
+    let _ = CALL_IO.try_with(|io| io.written.set(io.written.get() + bytes));
+}
+
+/// Run a tool call, returning the bytes it read and wrote on its own task
+pub async fn measure_io<F: Future>(call: F) -> (F::Output, u64, u64) {
+    CALL_IO
+        .scope(CallIo::default(), async move {
+            let output = call.await;
+            let (read, written) = CALL_IO.with(|io| (io.read.get(), io.written.get()));
+            (output, read, written)
+        })
+        .await
+}
+
+#[derive(Default)]
+struct ToolStats {
+    calls: u64,
+    errors: u64,
+    total: Duration,
+    max: Duration,
+    buckets: [u64; LATENCY_BUCKETS_MS.len() + 1],
+    bytes_read: u64,
+    bytes_written: u64,
+}
+
+impl ToolStats {
+    /// Latency below which `quantile` of the calls finished: the upper bound
+    /// of the histogram bucket it falls in, capped at the slowest call
+    fn percentile_ms(&self, quantile: f64) -> f64 {
+        let max_ms = self.max.as_secs_f64() * 1000.0;
+        let wanted = (self.calls as f64 * quantile).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= wanted {
+                return LATENCY_BUCKETS_MS.get(i).map_or(max_ms, |bound| bound.min(max_ms));
+            }
+        }
+        max_ms
+    }
+}
+
+struct Registry {
+    since: DateTime<Utc>,
+    tools: HashMap<String, ToolStats>,
+}
+
+static REGISTRY: Lazy<Mutex<Registry>> = Lazy::new(|| Mutex::new(Registry { since: Utc::now(), tools: HashMap::new() }));
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Count one finished call of `tool` (`tool.operation` for grouped tools)
+pub fn record_call(tool: &str, elapsed: Duration, is_error: bool, bytes_read: u64, bytes_written: u64) {
+    let mut registry = registry();
+    let stats = registry.tools.entry(tool.to_string()).or_default();
+    stats.calls += 1;
+    stats.errors += u64::from(is_error);
+    stats.total += elapsed;
+    stats.max = stats.max.max(elapsed);
+    let ms = elapsed.as_secs_f64() * 1000.0;
+    let bucket = LATENCY_BUCKETS_MS.iter().position(|bound| ms <= *bound).unwrap_or(LATENCY_BUCKETS_MS.len());
+    stats.buckets[bucket] += 1;
+    stats.bytes_read += bytes_read;
+    stats.bytes_written += bytes_written;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolSummary {
+    pub name: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetricsSnapshot {
+    /// When counting started: server start or the last reset
+    pub since: DateTime<Utc>,
+    pub calls: u64,
+    pub errors: u64,
+    /// All file bytes read and written, including those of archives, batch
+    /// copies and hashing, which run on worker threads and are not
+    /// attributed to a tool
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    /// Busiest tools first
+    pub tools: Vec<ToolSummary>,
+}
+
+/// The counters so far
+pub fn snapshot() -> MetricsSnapshot {
+    let registry = registry();
+    let round = |ms: f64| (ms * 1000.0).round() / 1000.0;
+    let ms = |d: Duration| round(d.as_secs_f64() * 1000.0);
+    let mut tools: Vec<ToolSummary> = registry
+        .tools
+        .iter()
+        .map(|(name, stats)| ToolSummary {
+            name: name.clone(),
+            calls: stats.calls,
+            errors: stats.errors,
+            mean_ms: ms(stats.total.div_f64(stats.calls.max(1) as f64)),
+            p50_ms: round(stats.percentile_ms(0.5)),
+            p95_ms: round(stats.percentile_ms(0.95)),
+            max_ms: ms(stats.max),
+            bytes_read: stats.bytes_read,
+            bytes_written: stats.bytes_written,
+        })
+        .collect();
+    tools.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.name.cmp(&b.name)));
+    MetricsSnapshot {
+        since: registry.since,
+        calls: tools.iter().map(|tool| tool.calls).sum(),
+        errors: tools.iter().map(|tool| tool.errors).sum(),
+        bytes_read: BYTES_READ.load(Ordering::Relaxed),
+        bytes_written: BYTES_WRITTEN.load(Ordering::Relaxed),
+        tools,
+    }
+}
+
+/// Start counting from zero
+pub fn reset() {
+    let mut registry = registry();
+    registry.tools.clear();
+    registry.since = Utc::now();
+    BYTES_READ.store(0, Ordering::Relaxed);
+    BYTES_WRITTEN.store(0, Ordering::Relaxed);
+}
+
+/// Write the current snapshot to `path` as JSON, replacing it in one step
+pub fn write_snapshot(path: &Path) -> std::io::Result<()> {
+    let json = serde_json::to_vec_pretty(&snapshot()).map_err(std::io::Error::other)?;
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    std::fs::write(&partial, json)?;
+    std::fs::rename(&partial, path)
+}
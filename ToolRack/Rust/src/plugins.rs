@@ -0,0 +1,166 @@
+#[cfg(feature = "plugins")]
+mod wasm;
+
+use std::{io, path::Path, sync::Arc};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{
+    fs_service::FileSystemService,
+    mcp_types::{CallToolError, CallToolResult, Tool},
+};
+
+/// Host functions a plugin may call, granted per plugin in its manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Capability {
+    /// `read_file`: read a text file
+    Read,
+    /// `write_file`: create or replace a text file
+    Write,
+    /// `list_directory`: list a directory's entries
+    List,
+}
+
+/// `<name>.json` next to `<name>.wasm`. Every field is optional; a plugin
+/// without a manifest is named after its file and gets no capabilities.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PluginManifest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// JSON Schema of the tool arguments; defaults to any object
+    pub input_schema: Option<Value>,
+    pub capabilities: Vec<Capability>,
+}
+
+/// A WASM module exposed as an MCP tool
+pub struct Plugin {
+    pub name: String,
+    pub description: Option<String>,
+    pub input_schema: Value,
+    pub capabilities: Vec<Capability>,
+    #[cfg(feature = "plugins")]
+    module: wasm::PluginModule,
+}
+
+impl Plugin {
+    pub fn tool_definition(&self) -> Tool {
+        Tool {
+            name: self.name.clone(),
+            description: self.description.clone(),
+            input_schema: self.input_schema.clone(),
+            output_schema: None,
+        }
+    }
+
+    /// Run the plugin with `arguments` on its stdin; what it prints is the result
+    #[cfg(feature = "plugins")]
+    pub async fn call(&self, fs_service: Arc<FileSystemService>, arguments: Value) -> Result<CallToolResult, CallToolError> {
+        self.module.run(fs_service, &self.capabilities, arguments).await
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    pub async fn call(&self, _fs_service: Arc<FileSystemService>, _arguments: Value) -> Result<CallToolResult, CallToolError> {
+        Err(CallToolError::new("this build does not include the plugins feature"))
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+pub(crate) fn valid_tool_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 64 && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Tools loaded from the --plugins-dir directory
+#[derive(Default)]
+pub struct PluginRegistry {
+    plugins: Vec<Plugin>,
+}
+
+impl PluginRegistry {
+    /// Load every `*.wasm` module in `dir`, in name order. Names must not clash
+    /// with `reserved` (the built-in tools) or each other; any bad plugin fails
+    /// the whole load so a typo is not silently dropped.
+    pub fn load_dir(dir: &Path, reserved: &[String]) -> io::Result<Self> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<io::Result<_>>()?;
+        paths.retain(|path| path.extension().is_some_and(|ext| ext == "wasm"));
+        paths.sort();
+
+        let mut registry = Self::default();
+        for path in paths {
+            let manifest_path = path.with_extension("json");
+            let manifest: PluginManifest = if manifest_path.exists() {
+                serde_json::from_str(&std::fs::read_to_string(&manifest_path)?)
+                    .map_err(|e| invalid(format!("{}: {}", manifest_path.display(), e)))?
+            } else {
+                PluginManifest::default()
+            };
+            let name = manifest
+                .name
+                .clone()
+                .unwrap_or_else(|| path.file_stem().unwrap_or_default().to_string_lossy().into_owned());
+            if !valid_tool_name(&name) {
+                return Err(invalid(format!("{}: '{}' is not a valid tool name", path.display(), name)));
+            }
+            if reserved.contains(&name) || registry.get(&name).is_some() {
+                return Err(invalid(format!("{}: a tool named '{}' already exists", path.display(), name)));
+            }
+            registry.plugins.push(Self::load_plugin(&path, name, manifest)?);
+        }
+        Ok(registry)
+    }
+
+    #[cfg(feature = "plugins")]
+    fn load_plugin(path: &Path, name: String, manifest: PluginManifest) -> io::Result<Plugin> {
+        let module = wasm::PluginModule::load(path).map_err(|e| invalid(format!("{}: {:#}", path.display(), e)))?;
+        Ok(Plugin {
+            name,
+            description: manifest.description,
+            input_schema: manifest.input_schema.unwrap_or_else(|| serde_json::json!({ "type": "object" })),
+            capabilities: manifest.capabilities,
+            module,
+        })
+    }
+
+    #[cfg(not(feature = "plugins"))]
+    fn load_plugin(path: &Path, _name: String, _manifest: PluginManifest) -> io::Result<Plugin> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("{} is a plugin but this build does not include the plugins feature", path.display()),
+        ))
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Plugin> {
+        self.plugins.iter().find(|plugin| plugin.name == name)
+    }
+
+    pub fn tools(&self) -> Vec<Tool> {
+        self.plugins.iter().map(Plugin::tool_definition).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Loaded plugins with their capabilities, e.g. `word_count (read, list)`
+    pub fn describe(&self) -> Vec<String> {
+        self.plugins
+            .iter()
+            .map(|plugin| {
+                let capabilities: Vec<String> =
+                    plugin.capabilities.iter().map(|c| format!("{:?}", c).to_lowercase()).collect();
+                if capabilities.is_empty() {
+                    plugin.name.clone()
+                } else {
+                    format!("{} ({})", plugin.name, capabilities.join(", "))
+                }
+            })
+            .collect()
+    }
+}
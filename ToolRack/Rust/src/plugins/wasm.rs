@@ -0,0 +1,266 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Context};
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use tokio::runtime::Handle;
+use wasmtime::{Caller, Config, Engine, InstancePre, Linker, Module, Store, StoreLimits, StoreLimitsBuilder, Trap};
+use wasmtime_wasi::{
+    pipe::{MemoryInputPipe, MemoryOutputPipe},
+    preview1::{self, WasiP1Ctx},
+    I32Exit, WasiCtxBuilder,
+};
+
+use super::Capability;
+use crate::logging::log_info;
+use crate::{
+    fs_service::{ops::FileSystemOps, FileSystemService},
+    mcp_types::{CallToolError, CallToolResult, Content, TextContent},
+};
+
+/// Fuel (roughly one unit per instruction) a single plugin call may burn
+const PLUGIN_FUEL: u64 = 5_000_000_000;
+/// Largest linear memory a plugin instance may grow to
+const PLUGIN_MAX_MEMORY: usize = 256 * 1024 * 1024;
+/// Output kept from a plugin's stdout and from its stderr
+const PLUGIN_MAX_OUTPUT: usize = 4 * 1024 * 1024;
+/// Import module the host functions are provided under
+const HOST_MODULE: &str = "aichemistforge";
+
+static ENGINE: Lazy<Engine> = Lazy::new(|| {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).expect("static wasmtime configuration is valid")
+});
+
+struct HostState {
+    wasi: WasiP1Ctx,
+    limits: StoreLimits,
+    fs_service: Arc<FileSystemService>,
+    capabilities: Vec<Capability>,
+    runtime: Handle,
+    last_error: String,
+}
+
+/// A compiled plugin with its imports resolved. Each call gets a fresh
+/// instance: WASI without preopened directories, environment or network, so
+/// the only way to the filesystem is the granted host functions, which go
+/// through the same path validation as every tool.
+pub struct PluginModule {
+    path: PathBuf,
+    instance_pre: InstancePre<HostState>,
+}
+
+struct Outcome {
+    exit_code: i32,
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+}
+
+fn guest_memory(caller: &mut Caller<'_, HostState>) -> Result<wasmtime::Memory, String> {
+    caller
+        .get_export("memory")
+        .and_then(|export| export.into_memory())
+        .ok_or_else(|| "the plugin does not export its memory".to_string())
+}
+
+fn read_guest(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Result<Vec<u8>, String> {
+    let memory = guest_memory(caller)?;
+    let start = ptr as u32 as usize;
+    let end = start.checked_add(len as u32 as usize).ok_or("buffer out of bounds")?;
+    memory.data(&caller).get(start..end).map(<[u8]>::to_vec).ok_or_else(|| "buffer out of bounds".to_string())
+}
+
+fn read_guest_path(caller: &mut Caller<'_, HostState>, ptr: i32, len: i32) -> Result<PathBuf, String> {
+    String::from_utf8(read_guest(caller, ptr, len)?)
+        .map(PathBuf::from)
+        .map_err(|_| "path is not UTF-8".to_string())
+}
+
+// Copy as much of `data` as fits into the guest's buffer and return its full
+// length, so a guest with too small a buffer can retry with a larger one
+fn write_guest(caller: &mut Caller<'_, HostState>, ptr: i32, capacity: i32, data: &[u8]) -> Result<i64, String> {
+    let memory = guest_memory(caller)?;
+    let count = data.len().min(capacity as u32 as usize);
+    let start = ptr as u32 as usize;
+    memory
+        .data_mut(&mut *caller)
+        .get_mut(start..start + count)
+        .ok_or("buffer out of bounds")?
+        .copy_from_slice(&data[..count]);
+    Ok(data.len() as i64)
+}
+
+fn require(caller: &Caller<'_, HostState>, capability: Capability) -> Result<(), String> {
+    if caller.data().capabilities.contains(&capability) {
+        Ok(())
+    } else {
+        Err(format!("the '{:?}' capability is not granted to this plugin", capability).to_lowercase())
+    }
+}
+
+// Host functions return -1 on failure and keep the message for last_error
+fn finish(caller: &mut Caller<'_, HostState>, result: Result<i64, String>) -> i64 {
+    result.unwrap_or_else(|e| {
+        caller.data_mut().last_error = e;
+        -1
+    })
+}
+
+fn add_host_functions(linker: &mut Linker<HostState>) -> anyhow::Result<()> {
+    linker.func_wrap(
+        HOST_MODULE,
+        "read_file",
+        |mut caller: Caller<'_, HostState>, path_ptr: i32, path_len: i32, out_ptr: i32, out_cap: i32| -> i64 {
+            let result = (|| {
+                require(&caller, Capability::Read)?;
+                let path = read_guest_path(&mut caller, path_ptr, path_len)?;
+                let (fs_service, runtime) = (caller.data().fs_service.clone(), caller.data().runtime.clone());
+                let content = runtime.block_on(fs_service.read_file(&path)).map_err(|e| e.to_string())?;
+                write_guest(&mut caller, out_ptr, out_cap, content.as_bytes())
+            })();
+            finish(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "write_file",
+        |mut caller: Caller<'_, HostState>, path_ptr: i32, path_len: i32, data_ptr: i32, data_len: i32| -> i32 {
+            let result = (|| {
+                require(&caller, Capability::Write)?;
+                let path = read_guest_path(&mut caller, path_ptr, path_len)?;
+                let content = String::from_utf8(read_guest(&mut caller, data_ptr, data_len)?)
+                    .map_err(|_| "content is not UTF-8".to_string())?;
+                let (fs_service, runtime) = (caller.data().fs_service.clone(), caller.data().runtime.clone());
+                runtime.block_on(fs_service.write_file(&path, &content)).map_err(|e| e.to_string())?;
+                Ok(0)
+            })();
+            finish(&mut caller, result) as i32
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "list_directory",
+        |mut caller: Caller<'_, HostState>, path_ptr: i32, path_len: i32, out_ptr: i32, out_cap: i32| -> i64 {
+            let result = (|| {
+                require(&caller, Capability::List)?;
+                let path = read_guest_path(&mut caller, path_ptr, path_len)?;
+                let (fs_service, runtime) = (caller.data().fs_service.clone(), caller.data().runtime.clone());
+                let entries = runtime
+                    .block_on(FileSystemOps::list_directory(fs_service.as_ref(), &path))
+                    .map_err(|e| e.to_string())?;
+                // One name per line; directories end with '/'
+                let listing: String = entries
+                    .iter()
+                    .map(|entry| format!("{}{}\n", entry.name, if entry.is_dir { "/" } else { "" }))
+                    .collect();
+                write_guest(&mut caller, out_ptr, out_cap, listing.as_bytes())
+            })();
+            finish(&mut caller, result)
+        },
+    )?;
+    linker.func_wrap(
+        HOST_MODULE,
+        "last_error",
+        |mut caller: Caller<'_, HostState>, out_ptr: i32, out_cap: i32| -> i64 {
+            let message = caller.data().last_error.clone();
+            let result = write_guest(&mut caller, out_ptr, out_cap, message.as_bytes());
+            finish(&mut caller, result)
+        },
+    )?;
+    Ok(())
+}
+
+impl PluginModule {
+    /// Compile a WASI command module (binary or text format) and resolve its imports
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let module = Module::from_file(&ENGINE, path)?;
+        if !module.exports().any(|export| export.name() == "_start") {
+            return Err(anyhow!("not a WASI command module (no _start export)"));
+        }
+        let mut linker = Linker::new(&ENGINE);
+        preview1::add_to_linker_sync(&mut linker, |state: &mut HostState| &mut state.wasi)?;
+        add_host_functions(&mut linker)?;
+        let instance_pre = linker.instantiate_pre(&module).context("unsupported imports")?;
+        Ok(Self { path: path.to_path_buf(), instance_pre })
+    }
+
+    fn execute(
+        instance_pre: &InstancePre<HostState>,
+        fs_service: Arc<FileSystemService>,
+        capabilities: Vec<Capability>,
+        runtime: Handle,
+        input: Vec<u8>,
+    ) -> anyhow::Result<Outcome> {
+        let stdout = MemoryOutputPipe::new(PLUGIN_MAX_OUTPUT);
+        let stderr = MemoryOutputPipe::new(PLUGIN_MAX_OUTPUT);
+        let wasi = WasiCtxBuilder::new()
+            .stdin(MemoryInputPipe::new(input))
+            .stdout(stdout.clone())
+            .stderr(stderr.clone())
+            .build_p1();
+        let state = HostState {
+            wasi,
+            limits: StoreLimitsBuilder::new().memory_size(PLUGIN_MAX_MEMORY).build(),
+            fs_service,
+            capabilities,
+            runtime,
+            last_error: String::new(),
+        };
+        let mut store = Store::new(instance_pre.module().engine(), state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(PLUGIN_FUEL)?;
+
+        let instance = instance_pre.instantiate(&mut store)?;
+        let start = instance.get_typed_func::<(), ()>(&mut store, "_start")?;
+        let exit_code = match start.call(&mut store, ()) {
+            Ok(()) => 0,
+            Err(e) => match e.downcast_ref::<I32Exit>() {
+                Some(exit) => exit.0,
+                None if e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) => {
+                    return Err(anyhow!("stopped after using its fuel limit ({} instructions)", PLUGIN_FUEL));
+                }
+                None => return Err(e),
+            },
+        };
+        drop(store);
+        Ok(Outcome { exit_code, stdout: stdout.contents().to_vec(), stderr: stderr.contents().to_vec() })
+    }
+
+    /// Run `_start` with the JSON `arguments` on stdin. Exit status 0 makes
+    /// stdout the result; any other status is a tool error carrying both streams.
+    pub async fn run(
+        &self,
+        fs_service: Arc<FileSystemService>,
+        capabilities: &[Capability],
+        arguments: Value,
+    ) -> Result<CallToolResult, CallToolError> {
+        let input = serde_json::to_vec(&arguments).map_err(CallToolError::new)?;
+        let instance_pre = self.instance_pre.clone();
+        let capabilities = capabilities.to_vec();
+        let runtime = Handle::current();
+        let outcome = tokio::task::spawn_blocking(move || Self::execute(&instance_pre, fs_service, capabilities, runtime, input))
+            .await
+            .map_err(CallToolError::new)?
+            .map_err(|e| CallToolError::new(format!("Plugin {} failed: {:#}", self.path.display(), e)))?;
+
+        let stdout = String::from_utf8_lossy(&outcome.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&outcome.stderr).into_owned();
+        let text = if outcome.exit_code == 0 {
+            if !stderr.is_empty() {
+                log_info!("[plugin {}] {}", self.path.display(), stderr.trim_end());
+            }
+            stdout
+        } else {
+            format!("Plugin exited with status {}\n{}{}", outcome.exit_code, stdout, stderr)
+        };
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(outcome.exit_code != 0),
+            structured_content: None,
+        })
+    }
+}
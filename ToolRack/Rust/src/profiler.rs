@@ -0,0 +1,148 @@
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tool calls at least this slow are recorded unless overridden with --slow-op-threshold-ms
+pub const DEFAULT_SLOW_THRESHOLD_MS: u64 = 1000;
+/// Most recent slow calls kept in memory
+pub const SLOW_LOG_CAPACITY: usize = 100;
+
+/// Time spent in each phase of a tool call
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PhaseTimings {
+    pub validation: Duration,
+    pub formatting: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Phase {
+    /// Path validation and permission checks
+    Validation,
+    /// Turning results into the response payload
+    Formatting,
+}
+
+tokio::task_local! {
+    static PHASES: RefCell<PhaseTimings>;
+}
+
+/// Add `elapsed` to `phase` of the tool call running on this task. Outside a
+/// profiled call this does nothing.
+pub fn record_phase(phase: Phase, elapsed: Duration) {
+    let _ = PHASES.try_with(|timings| {
+        let mut timings = timings.borrow_mut();
+        match phase {
+            Phase::Validation => timings.validation += elapsed,
+            Phase::Formatting => timings.formatting += elapsed,
+        }
+    });
+}
+
+/// Run a tool call, collecting the phase timings recorded while it runs
+pub async fn profile_call<F: Future>(call: F) -> (F::Output, PhaseTimings, Duration) {
+    let started = Instant::now();
+    PHASES
+        .scope(RefCell::new(PhaseTimings::default()), async move {
+            let output = call.await;
+            let timings = PHASES.with(|timings| *timings.borrow());
+            (output, timings, started.elapsed())
+        })
+        .await
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowOperation {
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub total_ms: f64,
+    pub validation_ms: f64,
+    /// Everything not attributed to validation or formatting: filesystem work and computation
+    pub io_ms: f64,
+    pub formatting_ms: f64,
+    pub response_bytes: usize,
+    pub is_error: bool,
+}
+
+impl SlowOperation {
+    pub fn new(
+        tool: String,
+        operation: Option<String>,
+        path: Option<String>,
+        total: Duration,
+        timings: PhaseTimings,
+        response_bytes: usize,
+        is_error: bool,
+    ) -> Self {
+        let ms = |d: Duration| (d.as_secs_f64() * 1_000_000.0).round() / 1000.0;
+        let io = total.saturating_sub(timings.validation + timings.formatting);
+        Self {
+            tool,
+            operation,
+            path,
+            started_at: Utc::now() - chrono::Duration::from_std(total).unwrap_or_default(),
+            total_ms: ms(total),
+            validation_ms: ms(timings.validation),
+            io_ms: ms(io),
+            formatting_ms: ms(timings.formatting),
+            response_bytes,
+            is_error,
+        }
+    }
+}
+
+struct SlowLog {
+    threshold: Duration,
+    entries: VecDeque<SlowOperation>,
+}
+
+static SLOW_LOG: Lazy<Mutex<SlowLog>> = Lazy::new(|| {
+    Mutex::new(SlowLog {
+        threshold: Duration::from_millis(DEFAULT_SLOW_THRESHOLD_MS),
+        entries: VecDeque::with_capacity(SLOW_LOG_CAPACITY),
+    })
+});
+
+fn slow_log() -> std::sync::MutexGuard<'static, SlowLog> {
+    SLOW_LOG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+pub fn set_slow_threshold(threshold: Duration) {
+    slow_log().threshold = threshold;
+}
+
+pub fn slow_threshold() -> Duration {
+    slow_log().threshold
+}
+
+/// Keep `operation` if it reached the threshold, dropping the oldest entry when full
+pub fn record_if_slow(operation: SlowOperation) {
+    let mut log = slow_log();
+    if operation.total_ms < log.threshold.as_secs_f64() * 1000.0 {
+        return;
+    }
+    if log.entries.len() == SLOW_LOG_CAPACITY {
+        log.entries.pop_front();
+    }
+    log.entries.push_back(operation);
+}
+
+/// Recorded slow calls, newest first
+pub fn slow_operations(limit: usize) -> Vec<SlowOperation> {
+    slow_log().entries.iter().rev().take(limit).cloned().collect()
+}
+
+pub fn clear_slow_operations() -> usize {
+    let mut log = slow_log();
+    let count = log.entries.len();
+    log.entries.clear();
+    count
+}
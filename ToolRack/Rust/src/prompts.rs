@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use crate::mcp_types::*;
+use crate::tools::ToolStyle;
+
+/// Arguments a prompt was given, by name
+type Arguments = HashMap<String, String>;
+
+/// A guided workflow offered through `prompts/list` and `prompts/get`. The
+/// text it renders walks the model through existing tools.
+struct BuiltinPrompt {
+    name: &'static str,
+    description: &'static str,
+    /// Name, description and whether it is required
+    arguments: &'static [(&'static str, &'static str, bool)],
+    render: fn(&Arguments, &Tools) -> Result<String, String>,
+}
+
+/// Names tools the way the current --tool-style lists them
+struct Tools {
+    style: ToolStyle,
+}
+
+impl Tools {
+    fn call(&self, group: &str, operation: &str) -> String {
+        if self.style.includes_flat() {
+            format!("`{}`", operation)
+        } else {
+            format!("`{}` with operation `{}`", group, operation)
+        }
+    }
+}
+
+fn number(arguments: &Arguments, name: &str, default: u64) -> Result<u64, String> {
+    match arguments.get(name).map(|value| value.trim()) {
+        None | Some("") => Ok(default),
+        Some(value) => value.parse().map_err(|_| format!("{} must be a whole number, got '{}'", name, value)),
+    }
+}
+
+fn summarize_directory(arguments: &Arguments, tools: &Tools) -> Result<String, String> {
+    let path = &arguments["path"];
+    let focus = match arguments.get("focus").filter(|focus| !focus.trim().is_empty()) {
+        Some(focus) => format!(" Pay particular attention to: {}.", focus),
+        None => String::new(),
+    };
+    Ok(format!(
+        "Summarize the directory {path}.{focus}\n\n\
+         1. Call {profile} on {path} for its file types, sizes and ages.\n\
+         2. Call {tree} on {path} with max_depth 2 to see how it is laid out.\n\
+         3. If there is a README or similar top-level document, read it with {read}.\n\n\
+         Then describe what the directory contains, how it is organized, and anything \
+         notable such as the largest areas, generated or vendored content, or files \
+         that look stale.",
+        profile = tools.call("search_and_analysis", "directory_profile"),
+        tree = tools.call("directory_operations", "directory_tree"),
+        read = tools.call("single_file_operations", "read_file"),
+    ))
+}
+
+fn clean_up_duplicates(arguments: &Arguments, tools: &Tools) -> Result<String, String> {
+    let path = &arguments["path"];
+    let action = arguments.get("action").map_or("delete_extras", |action| action.trim());
+    if !["hardlink", "symlink", "delete_extras"].contains(&action) {
+        return Err(format!("action must be hardlink, symlink or delete_extras, got '{}'", action));
+    }
+    let find = tools.call("search_and_analysis", "find_duplicate_files");
+    Ok(format!(
+        "Clean up duplicate files under {path}.\n\n\
+         1. Call {find} with path {path} and no action to list the groups of identical files.\n\
+         2. Show the groups, largest wasted space first, and say which copy of each you \
+         would keep (keep: shortest_path or oldest).\n\
+         3. Only once I confirm, call {find} again with action {action} and dry_run left at \
+         its default to preview the plan, then with dry_run false to apply it.\n\n\
+         Do not modify any file before I have confirmed the plan."
+    ))
+}
+
+fn find_large_files(arguments: &Arguments, tools: &Tools) -> Result<String, String> {
+    let path = &arguments["path"];
+    let min_mb = number(arguments, "min_size_mb", 100)?;
+    Ok(format!(
+        "Find what is taking up space under {path}.\n\n\
+         1. Call {by_size} on {path} with min_bytes {min_bytes}, sort size_desc and limit 50.\n\
+         2. Call {size} on {path} and {disk} for context.\n\n\
+         List the largest files with their sizes and suggest which look safe to remove or \
+         archive (build output, caches, old downloads). Do not delete anything.",
+        min_bytes = min_mb * 1024 * 1024,
+        by_size = tools.call("search_and_analysis", "find_files_by_size"),
+        size = tools.call("directory_operations", "calculate_directory_size"),
+        disk = tools.call("directory_operations", "get_disk_space"),
+    ))
+}
+
+fn review_recent_changes(arguments: &Arguments, tools: &Tools) -> Result<String, String> {
+    let path = &arguments["path"];
+    let hours = number(arguments, "within_hours", 24)?;
+    Ok(format!(
+        "Review what changed under {path} in the last {hours} hours.\n\n\
+         1. Call {recent} on {path} with within_hours {hours}.\n\
+         2. Read the most relevant changed files with {read}.\n\n\
+         Summarize the changes by area and point out anything that looks unfinished or \
+         accidental, such as stray temporary files.",
+        recent = tools.call("search_and_analysis", "find_recent_changes"),
+        read = tools.call("single_file_operations", "read_file"),
+    ))
+}
+
+const PATH: (&str, &str, bool) = ("path", "Absolute path of the directory to work on", true);
+
+static PROMPTS: &[BuiltinPrompt] = &[
+    BuiltinPrompt {
+        name: "summarize_directory",
+        description: "Summarize what a directory contains and how it is organized",
+        arguments: &[PATH, ("focus", "What to pay particular attention to", false)],
+        render: summarize_directory,
+    },
+    BuiltinPrompt {
+        name: "clean_up_duplicates",
+        description: "Find duplicate files under a directory and remove or link them after confirmation",
+        arguments: &[PATH, ("action", "hardlink, symlink or delete_extras (default)", false)],
+        render: clean_up_duplicates,
+    },
+    BuiltinPrompt {
+        name: "find_large_files",
+        description: "Find the largest files under a directory and suggest what to clean up",
+        arguments: &[PATH, ("min_size_mb", "Smallest file size to report, in MB (default 100)", false)],
+        render: find_large_files,
+    },
+    BuiltinPrompt {
+        name: "review_recent_changes",
+        description: "Review the files changed recently under a directory",
+        arguments: &[PATH, ("within_hours", "How far back to look, in hours (default 24)", false)],
+        render: review_recent_changes,
+    },
+];
+
+/// The built-in prompts, for `prompts/list`
+pub fn list_prompts() -> Vec<Prompt> {
+    PROMPTS
+        .iter()
+        .map(|prompt| Prompt {
+            name: prompt.name.to_string(),
+            description: Some(prompt.description.to_string()),
+            arguments: prompt
+                .arguments
+                .iter()
+                .map(|&(name, description, required)| PromptArgument {
+                    name: name.to_string(),
+                    description: Some(description.to_string()),
+                    required,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Render a prompt for `prompts/get`, naming tools as `style` lists them
+pub fn get_prompt(params: &GetPromptParams, style: ToolStyle) -> Result<GetPromptResult, String> {
+    let prompt = PROMPTS
+        .iter()
+        .find(|prompt| prompt.name == params.name)
+        .ok_or_else(|| format!("Unknown prompt: {}", params.name))?;
+    for (name, _, required) in prompt.arguments {
+        if *required && params.arguments.get(*name).is_none_or(|value| value.trim().is_empty()) {
+            return Err(format!("Missing required argument '{}' for prompt {}", name, prompt.name));
+        }
+    }
+
+    let mut text = (prompt.render)(&params.arguments, &Tools { style })?;
+    if !style.includes_flat() {
+        text.push_str(
+            "\n\nThese are operations of the grouped tools: if one is refused, start that tool's operation mode with `start_operation_mode` first.",
+        );
+    }
+    Ok(GetPromptResult {
+        description: Some(prompt.description.to_string()),
+        messages: vec![PromptMessage {
+            role: "user".to_string(),
+            content: Content::Text(TextContent { text }),
+        }],
+    })
+}
@@ -9,6 +9,7 @@ use std::time::Duration;
 use tokio::time::sleep;
 
 use crate::error::ServiceError;
+use crate::logging::{log_error, log_info, log_warn};
 
 /// Retry strategy for backoff calculation
 #[derive(Debug, Clone, Copy)]
@@ -127,6 +128,10 @@ impl RetryConfig {
             },
             // Non-transient errors - don't retry
             ServiceError::PathNotAllowed => false, // Security violation
+            ServiceError::ReadOnlyPath(_) => false, // Policy, not a passing condition
+            ServiceError::SymlinkRefused(..) => false,
+            ServiceError::FileTooLarge(..) => false,
+            ServiceError::BinaryFile(..) => false,
             ServiceError::DirectoryAlreadyExists => false, // Won't change
             ServiceError::FileNotFound(_) => false, // File doesn't exist
             ServiceError::PermissionDenied => true, // Might be temporary file lock
@@ -153,13 +158,28 @@ impl RetryConfig {
 /// ```
 pub async fn retry_with_config<F, Fut, T, E>(
     tool_name: &str,
-    mut operation: F,
+    operation: F,
     config: &RetryConfig,
 ) -> Result<T, E>
 where
     F: FnMut() -> Fut,
     Fut: Future<Output = Result<T, E>>,
     E: std::fmt::Display + From<ServiceError>,
+{
+    retry_when(tool_name, operation, config, |_| true).await
+}
+
+// Retry while `retryable` says the error might pass
+async fn retry_when<F, Fut, T, E>(
+    tool_name: &str,
+    mut operation: F,
+    config: &RetryConfig,
+    retryable: impl Fn(&E) -> bool,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
 {
     let mut last_error: Option<E> = None;
 
@@ -167,8 +187,8 @@ where
         match operation().await {
             Ok(result) => {
                 if attempt > 0 {
-                    eprintln!(
-                        "[INFO] Tool '{}' succeeded on attempt {}/{}",
+                    log_info!(
+                        "Tool '{}' succeeded on attempt {}/{}",
                         tool_name,
                         attempt + 1,
                         config.max_attempts
@@ -176,13 +196,14 @@ where
                 }
                 return Ok(result);
             }
+            Err(error) if !retryable(&error) => return Err(error),
             Err(error) => {
                 last_error = Some(error);
 
                 // Check if we should retry
                 if attempt + 1 >= config.max_attempts {
-                    eprintln!(
-                        "[ERROR] Tool '{}' failed after {} attempts",
+                    log_error!(
+                        "Tool '{}' failed after {} attempts",
                         tool_name,
                         config.max_attempts
                     );
@@ -191,8 +212,8 @@ where
 
                 // Calculate delay and log retry
                 let delay = config.calculate_delay(attempt);
-                eprintln!(
-                    "[WARN] Tool '{}' failed on attempt {}/{}: {}. Retrying in {:?}...",
+                log_warn!(
+                    "Tool '{}' failed on attempt {}/{}: {}. Retrying in {:?}...",
                     tool_name,
                     attempt + 1,
                     config.max_attempts,
@@ -231,7 +252,9 @@ where
     retry_with_config(tool_name, operation, &RetryConfig::default()).await
 }
 
-/// Retry specifically for I/O operations with appropriate defaults
+/// Retry specifically for I/O operations with appropriate defaults. Errors
+/// a retry cannot fix, such as a path outside the allowed directories, are
+/// returned at once.
 pub async fn retry_io_operation<F, Fut, T>(tool_name: &str, operation: F) -> Result<T, ServiceError>
 where
     F: FnMut() -> Fut,
@@ -242,7 +265,7 @@ where
         .with_initial_delay_ms(1000)
         .with_strategy(RetryStrategy::Exponential);
 
-    retry_with_config(tool_name, operation, &config).await
+    retry_when(tool_name, operation, &config, |error| config.is_retryable(error)).await
 }
 
 /// Macro to wrap an async operation with retry logic
@@ -1,86 +1,422 @@
+use crate::cancel::{self, CancellationToken};
 use crate::handler::MyServerHandler;
+use crate::logging::{self, log_debug, log_error, log_info, log_trace, log_warn, LogLevel};
 use crate::mcp_types::*;
 use anyhow::Result;
 use serde_json::{json, Value};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+use tokio_util::task::TaskTracker;
+use tracing::Instrument;
 
-// JSON-RPC error codes from the specification
-const PARSE_ERROR: i32 = -32700;
-const INVALID_REQUEST: i32 = -32600;
-const METHOD_NOT_FOUND: i32 = -32601;
-const INVALID_PARAMS: i32 = -32602;
-const INTERNAL_ERROR: i32 = -32603;
+#[cfg(feature = "http")]
+mod http;
+mod message_size;
+#[cfg(feature = "http")]
+pub mod sse;
+#[cfg(feature = "http")]
+pub mod streamable;
+pub mod tcp;
+
+pub use message_size::DEFAULT_MAX_MESSAGE_BYTES;
+use message_size::{leading_id, read_line_limited, too_large_error, Line};
+
+/// How clients reach the server (--transport)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Transport {
+    /// JSON-RPC messages as lines on stdin and stdout
+    #[default]
+    Stdio,
+    /// The MCP HTTP+SSE transport: events from GET /sse, messages to POST /message
+    Sse,
+    /// The MCP Streamable HTTP transport: one /mcp endpoint with sessions
+    StreamableHttp,
+    /// JSON-RPC messages as lines on TCP connections to --tcp-bind
+    Tcp,
+}
+
+impl Transport {
+    /// The name --transport takes
+    pub fn name(self) -> &'static str {
+        match self {
+            Transport::Stdio => "stdio",
+            Transport::Sse => "sse",
+            Transport::StreamableHttp => "streamable-http",
+            Transport::Tcp => "tcp",
+        }
+    }
+}
+
+/// Where the HTTP transports listen unless --http-bind says otherwise
+pub const DEFAULT_HTTP_BIND: &str = "127.0.0.1:8080";
+/// Where the TCP transport listens unless --tcp-bind says otherwise
+pub const DEFAULT_TCP_BIND: &str = "127.0.0.1:8765";
+
+/// What a JSON-RPC message is without its payload, e.g.
+/// `tools/call "read_file" (id 4)` or `result (id 4)`
+fn summarize_message(message: &Value) -> String {
+    let id = match message.get("id") {
+        Some(id) if !id.is_null() => format!(" (id {})", id),
+        _ => String::new(),
+    };
+    if let Some(method) = message["method"].as_str() {
+        match message["params"]["name"].as_str() {
+            Some(name) if method == "tools/call" => format!("{} {:?}{}", method, name, id),
+            _ => format!("{}{}", method, id),
+        }
+    } else if let Some(code) = message["error"]["code"].as_i64() {
+        format!("error {}{}", code, id)
+    } else {
+        format!("result{}", id)
+    }
+}
+
+// Messages carry whole files, so their text is only logged at trace level;
+// debug gets a one-line summary
+fn log_message(direction: &str, text: &str) {
+    // Forwarded log lines are not logged again, which would forward them again
+    if text.starts_with(r#"{"jsonrpc":"2.0","method":"notifications/message""#) {
+        return;
+    }
+    if logging::enabled(LogLevel::Trace) {
+        log_trace!("{}: {}", direction, text);
+    } else if logging::enabled(LogLevel::Debug) {
+        let summary = serde_json::from_str::<Value>(text)
+            .map(|message| summarize_message(&message))
+            .unwrap_or_else(|_| "unparseable message".to_string());
+        log_debug!("{}: {}, {} bytes", direction, summary, text.len());
+    }
+}
+
+/// The span a request is handled in, so the lines it logs carry its id and
+/// tool or method
+fn request_span(message: &Value) -> tracing::Span {
+    let method = message["method"].as_str().unwrap_or_default();
+    let span = match message["params"]["name"].as_str() {
+        Some(tool) if method == "tools/call" => tracing::info_span!("request", request_id = tracing::field::Empty, tool),
+        _ => tracing::info_span!("request", request_id = tracing::field::Empty, method),
+    };
+    if let Some(id) = message.get("id") {
+        span.record("request_id", tracing::field::display(id));
+    }
+    span
+}
+
+/// JSON-RPC response carrying either `result` or the handler's error
+fn rpc_response<T: serde::Serialize>(id: Option<Value>, result: Result<T, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err(e) => json!({ "jsonrpc": "2.0", "error": e, "id": id }),
+    }
+}
+
+/// How long shutdown waits for running tool calls by default (--shutdown-grace-secs)
+pub const DEFAULT_SHUTDOWN_GRACE_SECS: u64 = 10;
+
+/// Shut the server down through `shutdown` on Ctrl+C or SIGTERM. A second
+/// signal exits at once, without waiting for running tool calls.
+pub async fn shutdown_on_signal(shutdown: CancellationToken) {
+    if stop_signal().await.is_err() {
+        return;
+    }
+    log_info!("Shutting down; signal again to exit without waiting for running tool calls");
+    shutdown.cancel();
+    if stop_signal().await.is_ok() {
+        log_warn!("Exiting immediately");
+        std::process::exit(130);
+    }
+}
+
+async fn stop_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
+
+/// Reload the security policy each time the process gets SIGHUP
+#[cfg(unix)]
+pub async fn reload_policy_on_hangup(handler: Arc<MyServerHandler>) {
+    let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(e) => {
+            log_warn!("Cannot listen for SIGHUP, the policy can only be reloaded with reload_policy: {}", e);
+            return;
+        }
+    };
+    while hangup.recv().await.is_some() {
+        log_info!("SIGHUP received, reloading the policy");
+        // reload_policy logs its outcome
+        let _ = handler.reload_policy();
+    }
+}
 
 pub struct McpServer {
-    handler: MyServerHandler,
+    handler: Arc<MyServerHandler>,
+    /// Tokens of the tool calls still running, by request id. Clients sharing
+    /// the server (TCP, HTTP) are assumed to use distinct ids.
+    in_flight: Mutex<HashMap<String, CancellationToken>>,
+    /// The running tool calls, for shutdown to wait on
+    calls: TaskTracker,
+    /// Cancelled to stop taking requests and shut down
+    shutdown: CancellationToken,
+    shutdown_grace: Duration,
+    /// Largest incoming message, in bytes
+    max_message_bytes: usize,
 }
 
 impl McpServer {
     pub fn new(handler: MyServerHandler) -> Self {
-        Self { handler }
+        Self {
+            handler: Arc::new(handler),
+            in_flight: Mutex::new(HashMap::new()),
+            calls: TaskTracker::new(),
+            shutdown: CancellationToken::new(),
+            shutdown_grace: Duration::from_secs(DEFAULT_SHUTDOWN_GRACE_SECS),
+            max_message_bytes: DEFAULT_MAX_MESSAGE_BYTES,
+        }
     }
 
-    pub async fn run(&self) -> Result<()> {
-        let stdin = tokio::io::stdin();
-        let mut stdout = tokio::io::stdout();
-        let mut reader = BufReader::new(stdin);
-        let mut line = String::new();
+    /// Refuse incoming messages larger than `limit` bytes without buffering them
+    pub fn with_max_message_bytes(mut self, limit: usize) -> Self {
+        self.max_message_bytes = limit;
+        self
+    }
 
-        eprintln!("MCP Server listening on stdin/stdout...");
+    /// How long shutdown lets running tool calls finish before cancelling them
+    pub fn with_shutdown_grace(mut self, grace: Duration) -> Self {
+        self.shutdown_grace = grace;
+        self
+    }
+
+    /// Cancelling this token shuts the server down: transports stop taking
+    /// requests, running tool calls get the grace period to finish and their
+    /// responses are sent, and [`serve`](Self::serve) returns.
+    pub fn handler(&self) -> Arc<MyServerHandler> {
+        self.handler.clone()
+    }
+
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Wait up to the grace period for the running tool calls, then cancel
+    /// the rest, which stop at their next check and clean up after themselves
+    async fn drain(&self) {
+        self.calls.close();
+        if self.calls.is_empty() {
+            return;
+        }
+        log_info!("Waiting up to {:?} for {} running tool call(s)", self.shutdown_grace, self.calls.len());
+        if tokio::time::timeout(self.shutdown_grace, self.calls.wait()).await.is_err() {
+            log_warn!("Cancelling {} tool call(s) still running after {:?}", self.calls.len(), self.shutdown_grace);
+            for token in self.in_flight().values() {
+                token.cancel();
+            }
+            self.calls.wait().await;
+        }
+    }
+
+    fn in_flight(&self) -> std::sync::MutexGuard<'_, HashMap<String, CancellationToken>> {
+        self.in_flight.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // Read requests line by line until stdin closes or the server shuts down
+    pub async fn run(self) -> Result<()> {
+        log_info!("MCP Server listening on stdin/stdout...");
+        // The client closing stdin has gone away: shut down as on a signal
+        Arc::new(self).serve_lines(tokio::io::stdin(), tokio::io::stdout(), "stdin", true).await
+    }
+
+    /// Answer the newline-delimited JSON-RPC messages read from `input` on
+    /// `output` until `input` closes or the server shuts down. `source` names
+    /// it in log messages; `eof_shuts_down` makes `input` closing shut the
+    /// server down.
+    async fn serve_lines(
+        self: &Arc<Self>,
+        input: impl AsyncRead + Unpin,
+        mut output: impl AsyncWrite + Unpin + Send + 'static,
+        source: &str,
+        eof_shuts_down: bool,
+    ) -> Result<()> {
+        let mut reader = BufReader::new(input);
+        let mut line = Vec::new();
+
+        let (outgoing, mut outgoing_rx) = mpsc::unbounded_channel::<Value>();
+        self.handler.client().connect(outgoing.clone());
+        let writer = tokio::spawn(async move {
+            while let Some(message) = outgoing_rx.recv().await {
+                let text = serde_json::to_string(&message)?;
+                log_message("Sending", &text);
+                output.write_all(text.as_bytes()).await?;
+                output.write_all(b"\n").await?;
+                output.flush().await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        });
+        let mut calls = JoinSet::new();
 
         loop {
-            line.clear();
-            match reader.read_line(&mut line).await {
-                Ok(0) => break, // EOF
-                Ok(_) => {
-                    let trimmed = line.trim();
-                    if trimmed.is_empty() {
-                        continue;
+            let read = tokio::select! {
+                read = read_line_limited(&mut reader, &mut line, self.max_message_bytes) => read,
+                _ = self.shutdown.cancelled() => break,
+            };
+            match read {
+                Ok(Line::Eof) => {
+                    if eof_shuts_down {
+                        self.shutdown.cancel();
                     }
-
-                    match self.handle_message(trimmed).await {
-                        Ok(Some(response)) => {
-                            let response_str = serde_json::to_string(&response)?;
-                            stdout.write_all(response_str.as_bytes()).await?;
-                            stdout.write_all(b"\n").await?;
-                            stdout.flush().await?;
-                        }
-                        Ok(None) => {
-                            // No response needed (notification)
-                        }
-                        Err(e) => {
-                            eprintln!("Error handling message: {}", e);
-                            // Try to extract ID from the original message for proper error response
-                            let request_id = self.extract_request_id(trimmed);
-                            let error_response = json!({
-                                "jsonrpc": "2.0",
-                                "error": {
-                                    "code": INTERNAL_ERROR,
-                                    "message": e.to_string()
-                                },
-                                "id": request_id
-                            });
-                            let error_str = serde_json::to_string(&error_response)?;
-                            stdout.write_all(error_str.as_bytes()).await?;
-                            stdout.write_all(b"\n").await?;
-                            stdout.flush().await?;
+                    break;
+                }
+                Ok(Line::TooLong) => {
+                    log_warn!("Refused a message from {} larger than {} bytes", source, self.max_message_bytes);
+                    let _ = outgoing.send(too_large_error(leading_id(&line), self.max_message_bytes));
+                }
+                Ok(Line::Complete) => match std::str::from_utf8(&line) {
+                    Ok(text) => {
+                        if let Some(call) = self.dispatch(text, &outgoing).await {
+                            calls.spawn(call);
                         }
                     }
-                }
+                    Err(e) => {
+                        log_error!("Error reading from {}: {}", source, e);
+                        break;
+                    }
+                },
                 Err(e) => {
-                    eprintln!("Error reading from stdin: {}", e);
+                    log_error!("Error reading from {}: {}", source, e);
                     break;
                 }
             }
         }
 
+        // Nothing will answer requests made to the client any more
+        self.handler.client().disconnect_from(&outgoing);
+        let mut finished = std::pin::pin!(async { while calls.join_next().await.is_some() {} });
+        tokio::select! {
+            _ = &mut finished => {}
+            _ = self.shutdown.cancelled() => {
+                self.drain().await;
+                finished.await;
+            }
+        }
+        // The writer sends the last responses and flushes before it ends
+        drop(outgoing);
+        writer.await??;
         Ok(())
     }
 
-    async fn handle_message(&self, message: &str) -> Result<Option<Value>> {
-        // Debug: Log incoming message
-        eprintln!("DEBUG: Received message: {}", message);
+    /// Handle one incoming message, sending any response to `outgoing`. Tool
+    /// calls come back as a task for the transport to run concurrently, so a
+    /// call can wait on a request it made to the client (sampling) while the
+    /// transport keeps delivering the client's responses; everything else is
+    /// answered in order before this returns.
+    async fn dispatch(
+        self: &Arc<Self>,
+        text: &str,
+        outgoing: &mpsc::UnboundedSender<Value>,
+    ) -> Option<impl Future<Output = ()> + Send + 'static> {
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            return None;
+        }
+        log_message("Received", trimmed);
+        let mut span = tracing::Span::none();
+        if let Ok(message) = serde_json::from_str::<Value>(trimmed) {
+            if self.handler.client().handle_response(&message) {
+                return None;
+            }
+            span = request_span(&message);
+            if message["method"] == "tools/call" {
+                let key = message["id"].to_string();
+                let token = CancellationToken::new();
+                self.in_flight().insert(key.clone(), token.clone());
+                let (server, outgoing, message) = (self.clone(), outgoing.clone(), trimmed.to_string());
+                let call = async move {
+                    let started = std::time::Instant::now();
+                    let response = tokio::select! {
+                        response = cancel::scope(token.clone(), server.respond(&message)) => response,
+                        // The client gave up on the call and expects no response
+                        _ = token.cancelled() => None,
+                    };
+                    server.in_flight().remove(&key);
+                    let outcome = match &response {
+                        None => "cancelled",
+                        Some(response) if response.get("error").is_some() => "error",
+                        Some(response) if response["result"]["isError"] == true => "tool error",
+                        Some(_) => "ok",
+                    };
+                    log_debug!(elapsed_ms = started.elapsed().as_millis() as u64, outcome, "Tool call finished");
+                    if let Some(response) = response {
+                        let _ = outgoing.send(response);
+                    }
+                };
+                return Some(self.calls.track_future(call.instrument(span)));
+            }
+        }
+        if let Some(response) = self.respond(trimmed).instrument(span).await {
+            let _ = outgoing.send(response);
+        }
+        None
+    }
+
+    /// Serve the transport chosen with --transport until it closes or the
+    /// server shuts down
+    pub async fn serve(self, transport: Transport, http_bind: SocketAddr, tcp_bind: SocketAddr) -> Result<()> {
+        match transport {
+            Transport::Stdio => self.run().await,
+            Transport::Tcp => tcp::serve(Arc::new(self), tokio::net::TcpListener::bind(tcp_bind).await?).await,
+            #[cfg(feature = "http")]
+            Transport::Sse => {
+                let listener = tokio::net::TcpListener::bind(http_bind).await?;
+                sse::serve(Arc::new(self), listener).await
+            }
+            #[cfg(feature = "http")]
+            Transport::StreamableHttp => {
+                let listener = tokio::net::TcpListener::bind(http_bind).await?;
+                streamable::serve(Arc::new(self), listener).await
+            }
+            #[cfg(not(feature = "http"))]
+            Transport::Sse | Transport::StreamableHttp => {
+                let _ = http_bind;
+                Err(anyhow::anyhow!("--transport {} needs a build with the http feature", transport.name()))
+            }
+        }
+    }
+
+    /// The response to one incoming message, if it needs one
+    async fn respond(&self, message: &str) -> Option<Value> {
+        match self.handle_message(message).await {
+            Ok(response) => response,
+            Err(e) => {
+                log_error!("Error handling message: {}", e);
+                // Try to extract ID from the original message for proper error response
+                let request_id = self.extract_request_id(message);
+                Some(json!({
+                    "jsonrpc": "2.0",
+                    "error": {
+                        "code": INTERNAL_ERROR,
+                        "message": e.to_string()
+                    },
+                    "id": request_id
+                }))
+            }
+        }
+    }
 
+    async fn handle_message(&self, message: &str) -> Result<Option<Value>> {
         // First, try to extract just the ID in case parsing fails
         let request_id = self.extract_request_id(message);
 
@@ -122,27 +458,8 @@ impl McpServer {
                 match serde_json::from_value::<InitializeParams>(params) {
                     Ok(params) => {
                         let init_request = InitializeRequest { params };
-                        match self.handler.handle_initialize(init_request).await {
-                            Ok(result) => {
-                                let response = json!({
-                                    "jsonrpc": "2.0",
-                                    "result": result,
-                                    "id": id
-                                });
-                                eprintln!("DEBUG: Sending response: {}", serde_json::to_string(&response).unwrap_or_default());
-                                Ok(Some(response))
-                            }
-                            Err(e) => {
-                                Ok(Some(json!({
-                                    "jsonrpc": "2.0",
-                                    "error": {
-                                        "code": e.code,
-                                        "message": e.message
-                                    },
-                                    "id": id
-                                })))
-                            }
-                        }
+                        // Errors carry the supported versions in `data`
+                        Ok(Some(rpc_response(id, self.handler.handle_initialize(init_request).await)))
                     }
                     Err(_) => {
                         Ok(Some(json!({
@@ -157,7 +474,6 @@ impl McpServer {
                 }
             }
             "tools/list" => {
-                eprintln!("DEBUG: Received tools/list request");
                 match self.handler.handle_list_tools().await {
                     Ok(result) => {
                         let response = json!({
@@ -165,7 +481,6 @@ impl McpServer {
                             "result": result,
                             "id": id
                         });
-                        eprintln!("DEBUG: Sending tools/list response: {}", serde_json::to_string(&response).unwrap_or_default());
                         Ok(Some(response))
                     }
                     Err(e) => {
@@ -217,14 +532,108 @@ impl McpServer {
                     }
                 }
             }
+            "resources/list" => Ok(Some(rpc_response(id, self.handler.handle_list_resources().await))),
+            "resources/templates/list" => {
+                Ok(Some(rpc_response(id, self.handler.handle_list_resource_templates().await)))
+            }
+            "resources/read" => {
+                let params = request.get("params").cloned().unwrap_or(json!({}));
+                match serde_json::from_value::<ReadResourceParams>(params) {
+                    Ok(params) => Ok(Some(rpc_response(id, self.handler.handle_read_resource(params).await))),
+                    Err(_) => {
+                        Ok(Some(json!({
+                            "jsonrpc": "2.0",
+                            "error": {
+                                "code": INVALID_PARAMS,
+                                "message": "Invalid params for resources/read"
+                            },
+                            "id": id
+                        })))
+                    }
+                }
+            }
+            "logging/setLevel" => {
+                let params = request.get("params").cloned().unwrap_or(json!({}));
+                match serde_json::from_value::<SetLevelParams>(params) {
+                    Ok(params) => Ok(Some(rpc_response(id, self.handler.handle_set_level(params).await))),
+                    Err(_) => {
+                        Ok(Some(json!({
+                            "jsonrpc": "2.0",
+                            "error": {
+                                "code": INVALID_PARAMS,
+                                "message": "Invalid params for logging/setLevel"
+                            },
+                            "id": id
+                        })))
+                    }
+                }
+            }
+            "prompts/list" => Ok(Some(rpc_response(id, self.handler.handle_list_prompts().await))),
+            "prompts/get" => {
+                let params = request.get("params").cloned().unwrap_or(json!({}));
+                match serde_json::from_value::<GetPromptParams>(params) {
+                    Ok(params) => Ok(Some(rpc_response(id, self.handler.handle_get_prompt(params).await))),
+                    Err(_) => {
+                        Ok(Some(json!({
+                            "jsonrpc": "2.0",
+                            "error": {
+                                "code": INVALID_PARAMS,
+                                "message": "Invalid params for prompts/get"
+                            },
+                            "id": id
+                        })))
+                    }
+                }
+            }
+            "resources/subscribe" | "resources/unsubscribe" => {
+                let params = request.get("params").cloned().unwrap_or(json!({}));
+                match serde_json::from_value::<SubscribeParams>(params) {
+                    Ok(params) if method == "resources/subscribe" => {
+                        Ok(Some(rpc_response(id, self.handler.handle_subscribe(params).await)))
+                    }
+                    Ok(params) => Ok(Some(rpc_response(id, self.handler.handle_unsubscribe(params).await))),
+                    Err(_) => {
+                        Ok(Some(json!({
+                            "jsonrpc": "2.0",
+                            "error": {
+                                "code": INVALID_PARAMS,
+                                "message": format!("Invalid params for {}", method)
+                            },
+                            "id": id
+                        })))
+                    }
+                }
+            }
+            "notifications/cancelled" => {
+                let request_id = &request["params"]["requestId"];
+                match self.in_flight().get(&request_id.to_string()) {
+                    Some(token) => {
+                        log_info!(
+                            "Cancelling request {}: {}",
+                            request_id,
+                            request["params"]["reason"].as_str().unwrap_or("no reason given")
+                        );
+                        token.cancel();
+                    }
+                    // Already answered, or never a tool call
+                    None => log_debug!("Nothing to cancel for request {}", request_id),
+                }
+                Ok(None)
+            }
             "notifications/initialized" => {
                 // Notification - no response needed
-                eprintln!("{}", self.handler.startup_message());
+                log_info!("{}", self.handler.startup_message());
+                self.refresh_client_roots();
+                Ok(None)
+            }
+            "notifications/roots/list_changed" => {
+                self.refresh_client_roots();
                 Ok(None)
             }
             "initialized" => {
                 // Legacy notification format - no response needed
-                eprintln!("{}", self.handler.startup_message());
+                log_info!("{}", self.handler.startup_message());
+                self.refresh_client_roots();
                 Ok(None)
             }
             _ => {
@@ -246,6 +655,13 @@ impl McpServer {
         }
     }
 
+    // The answer to roots/list arrives through the same loop that is handling
+    // this notification, so it is awaited on a task of its own
+    fn refresh_client_roots(&self) {
+        let handler = self.handler.clone();
+        tokio::spawn(async move { handler.refresh_client_roots().await });
+    }
+
     fn extract_request_id(&self, message: &str) -> Value {
         // Try to extract just the ID field, even if the rest fails to parse
         if let Ok(partial) = serde_json::from_str::<Value>(message) {
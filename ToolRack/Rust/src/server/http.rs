@@ -0,0 +1,114 @@
+use std::{
+    convert::Infallible,
+    pin::Pin,
+    sync::atomic::{AtomicU64, Ordering},
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{
+    http::{header, HeaderMap, StatusCode},
+    response::{sse::Event, IntoResponse, Response},
+    extract::DefaultBodyLimit,
+    Router,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream, StreamExt};
+
+use super::{log_message, McpServer};
+use crate::logging::{log_info, log_warn};
+
+// Hard to guess, so one client cannot post into another's session
+pub(super) fn new_session_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = Sha256::new();
+    hasher.update(SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_nanos().to_le_bytes());
+    hasher.update(std::process::id().to_le_bytes());
+    hasher.update(COUNTER.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    format!("{:x}", hasher.finalize())[..32].to_string()
+}
+
+/// Requests from web pages carry an Origin; a server listening on loopback
+/// only answers pages served from loopback, which keeps DNS rebinding out
+pub(super) fn origin_allowed(headers: &HeaderMap, loopback: bool) -> bool {
+    let Some(origin) = headers.get(header::ORIGIN).and_then(|origin| origin.to_str().ok()) else {
+        return true;
+    };
+    if !loopback {
+        return true;
+    }
+    let authority = origin.split_once("://").map_or(origin, |(_, rest)| rest);
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+pub(super) fn forbidden_origin() -> Response {
+    (StatusCode::FORBIDDEN, "Origin not allowed").into_response()
+}
+
+/// An SSE `message` event for each message sent to `receiver`
+pub(super) fn message_events(
+    receiver: mpsc::UnboundedReceiver<Value>,
+) -> impl Stream<Item = Result<Event, Infallible>> + Unpin + Send {
+    UnboundedReceiverStream::new(receiver).map(|message| {
+        let text = message.to_string();
+        log_message("Sending", &text);
+        Ok(Event::default().event("message").data(text))
+    })
+}
+
+/// An event stream that runs `on_close` once it is dropped, which is when
+/// the client disconnects or the stream ends
+pub(super) struct OnClose<S> {
+    stream: S,
+    on_close: Option<Box<dyn FnOnce() + Send>>,
+}
+
+impl<S> OnClose<S> {
+    pub(super) fn new(stream: S, on_close: impl FnOnce() + Send + 'static) -> Self {
+        Self { stream, on_close: Some(Box::new(on_close)) }
+    }
+}
+
+impl<S: Stream + Unpin> Stream for OnClose<S> {
+    type Item = S::Item;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.stream).poll_next(cx)
+    }
+}
+
+impl<S> Drop for OnClose<S> {
+    fn drop(&mut self) {
+        if let Some(on_close) = self.on_close.take() {
+            on_close();
+        }
+    }
+}
+
+/// Serve `app` on `listener` until `server` shuts down
+pub(super) async fn serve_router(
+    server: &McpServer,
+    app: Router,
+    listener: tokio::net::TcpListener,
+    endpoints: &str,
+) -> anyhow::Result<()> {
+    let address = listener.local_addr()?;
+    if !address.ip().is_loopback() {
+        log_warn!("Listening on {}, which other machines can reach; the HTTP transports have no authentication", address);
+    }
+    log_info!("MCP Server listening on http://{} ({})", address, endpoints);
+    let app = app.layer(DefaultBodyLimit::max(server.max_message_bytes));
+    // Dropping the server stops new connections; open ones (and their event
+    // streams) keep running, so responses still reach clients while draining
+    tokio::select! {
+        result = axum::serve(listener, app) => result?,
+        _ = server.shutdown.cancelled() => server.drain().await,
+    }
+    Ok(())
+}
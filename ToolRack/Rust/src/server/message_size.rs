@@ -0,0 +1,107 @@
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt};
+
+use crate::mcp_types::INVALID_REQUEST;
+
+/// Largest JSON-RPC message accepted by default (--max-message-bytes)
+pub const DEFAULT_MAX_MESSAGE_BYTES: usize = 64 * 1024 * 1024;
+
+/// What [`read_line_limited`] read
+pub(super) enum Line {
+    Eof,
+    Complete,
+    /// The line was longer than the limit: its first `limit` bytes are in
+    /// the buffer and the rest was skipped
+    TooLong,
+}
+
+/// Read one newline-terminated line into `line` without ever holding more
+/// than `limit` bytes of it, so an oversized message is skipped rather than
+/// buffered
+pub(super) async fn read_line_limited(
+    reader: &mut (impl AsyncBufRead + Unpin),
+    line: &mut Vec<u8>,
+    limit: usize,
+) -> std::io::Result<Line> {
+    line.clear();
+    let mut too_long = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(if too_long {
+                Line::TooLong
+            } else if line.is_empty() {
+                Line::Eof
+            } else {
+                Line::Complete
+            });
+        }
+        let (used, done) = match available.iter().position(|&byte| byte == b'\n') {
+            Some(end) => (end + 1, true),
+            None => (available.len(), false),
+        };
+        // The newline itself does not count towards the limit
+        let content = used - usize::from(done);
+        if !too_long && line.len() + content > limit {
+            too_long = true;
+            line.extend_from_slice(&available[..limit - line.len()]);
+        } else if !too_long {
+            line.extend_from_slice(&available[..used]);
+        }
+        reader.consume(used);
+        if done {
+            return Ok(if too_long { Line::TooLong } else { Line::Complete });
+        }
+    }
+}
+
+/// The top-level `"id"` of a JSON-RPC message whose start is `prefix`, so an
+/// oversized request can still be answered under its id. Most clients put
+/// the id before the params.
+pub(super) fn leading_id(prefix: &[u8]) -> Value {
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    for (i, &byte) in prefix.iter().enumerate() {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match byte {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b'"' if depth == 1 && prefix[i..].starts_with(b"\"id\"") => {
+                let rest = prefix[i + 4..].trim_ascii_start();
+                if let Some(value) = rest.strip_prefix(b":") {
+                    let mut values = serde_json::Deserializer::from_slice(value).into_iter::<Value>();
+                    if let Some(Ok(id)) = values.next() {
+                        return id;
+                    }
+                }
+                return Value::Null;
+            }
+            b'"' => in_string = true,
+            _ => {}
+        }
+    }
+    Value::Null
+}
+
+/// The JSON-RPC error for a message over `limit` bytes
+pub(super) fn too_large_error(id: Value, limit: usize) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "error": {
+            "code": INVALID_REQUEST,
+            "message": format!(
+                "Message exceeds the {}-byte limit (--max-message-bytes); write large files in smaller pieces",
+                limit
+            ),
+            "data": { "limit": limit },
+        },
+        "id": id,
+    })
+}
@@ -0,0 +1,103 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio_stream::StreamExt;
+
+use super::http::{forbidden_origin, message_events, new_session_id, origin_allowed, serve_router, OnClose};
+use super::McpServer;
+use crate::logging::log_info;
+
+pub const SSE_PATH: &str = "/sse";
+pub const MESSAGE_PATH: &str = "/message";
+
+/// The open event streams, by session id
+struct SseState {
+    server: Arc<McpServer>,
+    sessions: Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>,
+    /// Browsers may only reach a loopback-bound server from a loopback page
+    loopback: bool,
+}
+
+impl SseState {
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, mpsc::UnboundedSender<Value>>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// The session lasts as long as its event stream
+async fn open_stream(State(state): State<Arc<SseState>>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&headers, state.loopback) {
+        return forbidden_origin();
+    }
+    let id = new_session_id();
+    let (outgoing, receiver) = mpsc::unbounded_channel();
+    state.sessions().insert(id.clone(), outgoing.clone());
+    // Requests to the client (sampling) go to the newest session
+    state.server.handler.client().connect(outgoing.clone());
+    log_info!("SSE session {} opened", id);
+
+    // Clients learn where to post before anything else
+    let endpoint = Event::default().event("endpoint").data(format!("{}?sessionId={}", MESSAGE_PATH, id));
+    let events = tokio_stream::once(Ok(endpoint)).chain(message_events(receiver));
+    let stream = OnClose::new(events, move || {
+        state.sessions().remove(&id);
+        state.server.handler.client().disconnect_from(&outgoing);
+        log_info!("SSE session {} closed", id);
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default()).into_response()
+}
+
+#[derive(Deserialize)]
+struct MessageQuery {
+    #[serde(rename = "sessionId")]
+    session_id: String,
+}
+
+// Responses go out on the session's event stream; the POST itself is only
+// acknowledged
+async fn post_message(
+    State(state): State<Arc<SseState>>,
+    Query(query): Query<MessageQuery>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if !origin_allowed(&headers, state.loopback) {
+        return forbidden_origin();
+    }
+    let Some(outgoing) = state.sessions().get(&query.session_id).cloned() else {
+        return (StatusCode::NOT_FOUND, "Unknown or closed session").into_response();
+    };
+    if let Some(call) = state.server.dispatch(&body, &outgoing).await {
+        tokio::spawn(call);
+    }
+    StatusCode::ACCEPTED.into_response()
+}
+
+/// Serve the HTTP+SSE transport on `listener` until the server shuts down
+pub async fn serve(server: Arc<McpServer>, listener: tokio::net::TcpListener) -> anyhow::Result<()> {
+    let state = Arc::new(SseState {
+        server: server.clone(),
+        sessions: Mutex::new(HashMap::new()),
+        loopback: listener.local_addr()?.ip().is_loopback(),
+    });
+    let app = Router::new()
+        .route(SSE_PATH, get(open_stream))
+        .route(MESSAGE_PATH, post(post_message))
+        .with_state(state);
+    serve_router(&server, app, listener, &format!("events from {}, messages to {}", SSE_PATH, MESSAGE_PATH)).await
+}
@@ -0,0 +1,234 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::{rejection::StringRejection, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{
+        sse::{KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::get,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use tokio::{sync::mpsc, task::JoinSet};
+
+use super::http::{forbidden_origin, message_events, new_session_id, origin_allowed, serve_router, OnClose};
+use super::message_size::too_large_error;
+use super::McpServer;
+use crate::logging::log_info;
+use crate::mcp_types::{INVALID_REQUEST, PARSE_ERROR};
+
+pub const MCP_PATH: &str = "/mcp";
+pub const SESSION_HEADER: &str = "mcp-session-id";
+
+#[derive(Default)]
+struct Session {
+    /// The stream opened with GET, for messages that answer no POST
+    stream: Option<mpsc::UnboundedSender<Value>>,
+}
+
+struct StreamableState {
+    server: Arc<McpServer>,
+    sessions: Mutex<HashMap<String, Session>>,
+    /// Browsers may only reach a loopback-bound server from a loopback page
+    loopback: bool,
+}
+
+impl StreamableState {
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, Session>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn rpc_error(status: StatusCode, code: i32, message: &str) -> Response {
+    let body = json!({ "jsonrpc": "2.0", "error": { "code": code, "message": message }, "id": null });
+    (status, Json(body)).into_response()
+}
+
+fn accepts(headers: &HeaderMap, media_type: &str) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .any(|value| value.contains(media_type) || value.contains("*/*"))
+}
+
+fn with_session(mut response: Response, id: &str) -> Response {
+    if let Ok(value) = HeaderValue::from_str(id) {
+        response.headers_mut().insert(SESSION_HEADER, value);
+    }
+    response
+}
+
+const UNKNOWN_SESSION: (StatusCode, &str) = (StatusCode::NOT_FOUND, "Unknown or expired session; send initialize again");
+
+/// The session named by the request's Mcp-Session-Id header, or why it is
+/// refused
+fn existing_session(state: &StreamableState, headers: &HeaderMap) -> Result<String, (StatusCode, &'static str)> {
+    let Some(id) = headers.get(SESSION_HEADER).and_then(|id| id.to_str().ok()) else {
+        return Err((StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header; send initialize first"));
+    };
+    if !state.sessions().contains_key(id) {
+        return Err(UNKNOWN_SESSION);
+    }
+    Ok(id.to_string())
+}
+
+// One message or a batch. Answers come back as a JSON body, or as an event
+// stream when a tool call is among them, so the call can make requests to
+// the client (sampling) before it answers.
+async fn post_messages(
+    State(state): State<Arc<StreamableState>>,
+    headers: HeaderMap,
+    body: Result<String, StringRejection>,
+) -> Response {
+    if !origin_allowed(&headers, state.loopback) {
+        return forbidden_origin();
+    }
+    let body = match body {
+        Ok(body) => body,
+        Err(rejection) if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => {
+            let limit = state.server.max_message_bytes;
+            return (StatusCode::PAYLOAD_TOO_LARGE, Json(too_large_error(Value::Null, limit))).into_response();
+        }
+        Err(rejection) => return rejection.into_response(),
+    };
+    let messages = match serde_json::from_str::<Value>(&body) {
+        Ok(Value::Array(messages)) if !messages.is_empty() => messages,
+        Ok(Value::Array(_)) => return rpc_error(StatusCode::BAD_REQUEST, INVALID_REQUEST, "Empty batch"),
+        Ok(message) => vec![message],
+        Err(_) => return rpc_error(StatusCode::BAD_REQUEST, PARSE_ERROR, "Parse error"),
+    };
+
+    let session = if messages.iter().any(|message| message["method"] == "initialize") {
+        if messages.len() > 1 {
+            return rpc_error(StatusCode::BAD_REQUEST, INVALID_REQUEST, "initialize must be sent on its own");
+        }
+        let id = new_session_id();
+        state.sessions().insert(id.clone(), Session::default());
+        log_info!("HTTP session {} opened", id);
+        id
+    } else {
+        match existing_session(&state, &headers) {
+            Ok(id) => id,
+            Err((status, message)) => return rpc_error(status, INVALID_REQUEST, message),
+        }
+    };
+
+    let has_requests = messages.iter().any(|message| message.get("method").is_some() && message.get("id").is_some());
+    let stream = has_requests
+        && accepts(&headers, "text/event-stream")
+        && messages.iter().any(|message| message["method"] == "tools/call");
+    let (outgoing, mut receiver) = mpsc::unbounded_channel();
+
+    if stream {
+        // Without a GET stream open, requests to the client ride on this one
+        let borrow_peer = state.sessions().get(&session).is_some_and(|session| session.stream.is_none());
+        if borrow_peer {
+            state.server.handler.client().connect(outgoing.clone());
+        }
+        let server = state.server.clone();
+        tokio::spawn(async move {
+            let mut calls = JoinSet::new();
+            for message in &messages {
+                if let Some(call) = server.dispatch(&message.to_string(), &outgoing).await {
+                    calls.spawn(call);
+                }
+            }
+            while calls.join_next().await.is_some() {}
+            // The stream ends once every sender is gone
+            server.handler.client().disconnect_from(&outgoing);
+        });
+        let response = Sse::new(message_events(receiver)).keep_alive(KeepAlive::default()).into_response();
+        return with_session(response, &session);
+    }
+
+    for message in &messages {
+        if let Some(call) = state.server.dispatch(&message.to_string(), &outgoing).await {
+            call.await;
+        }
+    }
+    drop(outgoing);
+    let mut responses = Vec::new();
+    while let Ok(response) = receiver.try_recv() {
+        responses.push(response);
+    }
+    let response = match responses.len() {
+        // Only notifications and responses
+        0 => StatusCode::ACCEPTED.into_response(),
+        1 if messages.len() == 1 => Json(responses.remove(0)).into_response(),
+        _ => Json(Value::Array(responses)).into_response(),
+    };
+    with_session(response, &session)
+}
+
+// A stream for the server's own messages, such as requests to the client
+async fn open_stream(State(state): State<Arc<StreamableState>>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&headers, state.loopback) {
+        return forbidden_origin();
+    }
+    if !accepts(&headers, "text/event-stream") {
+        return (StatusCode::NOT_ACCEPTABLE, "GET opens an event stream; accept text/event-stream").into_response();
+    }
+    let id = match existing_session(&state, &headers) {
+        Ok(id) => id,
+        Err((status, message)) => return rpc_error(status, INVALID_REQUEST, message),
+    };
+    let (outgoing, receiver) = mpsc::unbounded_channel();
+    {
+        let mut sessions = state.sessions();
+        let Some(session) = sessions.get_mut(&id) else {
+            return rpc_error(UNKNOWN_SESSION.0, INVALID_REQUEST, UNKNOWN_SESSION.1);
+        };
+        if session.stream.is_some() {
+            return (StatusCode::CONFLICT, "This session already has an open stream").into_response();
+        }
+        session.stream = Some(outgoing.clone());
+    }
+    // Held weakly here, so closing the session (DELETE) also ends the stream
+    let weak = outgoing.downgrade();
+    state.server.handler.client().connect(outgoing);
+
+    let session_id = id.clone();
+    let stream = OnClose::new(message_events(receiver), move || {
+        if let Some(session) = state.sessions().get_mut(&session_id) {
+            session.stream = None;
+        }
+        if let Some(outgoing) = weak.upgrade() {
+            state.server.handler.client().disconnect_from(&outgoing);
+        }
+    });
+    with_session(Sse::new(stream).keep_alive(KeepAlive::default()).into_response(), &id)
+}
+
+async fn close_session(State(state): State<Arc<StreamableState>>, headers: HeaderMap) -> Response {
+    if !origin_allowed(&headers, state.loopback) {
+        return forbidden_origin();
+    }
+    let id = match existing_session(&state, &headers) {
+        Ok(id) => id,
+        Err((status, message)) => return rpc_error(status, INVALID_REQUEST, message),
+    };
+    if let Some(Session { stream: Some(stream) }) = state.sessions().remove(&id) {
+        state.server.handler.client().disconnect_from(&stream);
+    }
+    log_info!("HTTP session {} closed", id);
+    StatusCode::NO_CONTENT.into_response()
+}
+
+/// Serve the Streamable HTTP transport on `listener` until the server shuts down
+pub async fn serve(server: Arc<McpServer>, listener: tokio::net::TcpListener) -> anyhow::Result<()> {
+    let state = Arc::new(StreamableState {
+        server: server.clone(),
+        sessions: Mutex::new(HashMap::new()),
+        loopback: listener.local_addr()?.ip().is_loopback(),
+    });
+    let app = Router::new()
+        .route(MCP_PATH, get(open_stream).post(post_messages).delete(close_session))
+        .with_state(state);
+    serve_router(&server, app, listener, &format!("endpoint {}", MCP_PATH)).await
+}
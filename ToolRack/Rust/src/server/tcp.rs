@@ -0,0 +1,53 @@
+use std::sync::Arc;
+
+use tokio::net::TcpListener;
+use tokio_util::task::TaskTracker;
+
+use super::McpServer;
+use crate::logging::{log_error, log_info, log_warn};
+
+/// Accept connections on `listener` until the server shuts down, then wait for
+/// the connections to finish their running calls. Each connection speaks
+/// newline-delimited JSON-RPC, like stdio, and they share one server.
+pub async fn serve(server: Arc<McpServer>, listener: TcpListener) -> anyhow::Result<()> {
+    let address = listener.local_addr()?;
+    if !address.ip().is_loopback() {
+        log_warn!(
+            "Listening on {}, which other machines can reach; the TCP transport has no authentication or encryption, so firewall the port to trusted agents",
+            address
+        );
+    }
+    log_info!("MCP Server listening on tcp://{}", address);
+
+    let connections = TaskTracker::new();
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            _ = server.shutdown.cancelled() => break,
+        };
+        let (stream, peer) = match accepted {
+            Ok(accepted) => accepted,
+            // Out of file descriptors and the like; later connections may succeed
+            Err(e) => {
+                log_error!("Error accepting a TCP connection: {}", e);
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+        let _ = stream.set_nodelay(true);
+        let server = server.clone();
+        connections.spawn(async move {
+            log_info!("TCP client {} connected", peer);
+            let (input, output) = stream.into_split();
+            if let Err(e) = server.serve_lines(input, output, &format!("TCP client {}", peer), false).await {
+                log_error!("TCP client {}: {}", peer, e);
+            }
+            log_info!("TCP client {} disconnected", peer);
+        });
+    }
+
+    drop(listener);
+    connections.close();
+    connections.wait().await;
+    Ok(())
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+#[cfg(feature = "watch")]
+use serde_json::json;
+
+use crate::client::ClientPeer;
+#[cfg(feature = "watch")]
+use crate::logging::log_debug;
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// What is subscribed and which directories are watched for it
+#[derive(Default)]
+struct Watched {
+    /// Subscribed paths, with the URI the client subscribed with and the
+    /// directory watched for it
+    uris: HashMap<PathBuf, (String, PathBuf)>,
+    /// Watched directories and how many subscriptions need each
+    dirs: HashMap<PathBuf, usize>,
+}
+
+#[cfg(feature = "watch")]
+impl Watched {
+    /// URIs to report for a change to `path`: the file itself, or the
+    /// subscribed directory it is directly inside
+    fn uris_for(&self, path: &Path) -> Vec<String> {
+        [Some(path), path.parent()].into_iter().flatten().filter_map(|path| Some(self.uris.get(path)?.0.clone())).collect()
+    }
+}
+
+/// The directory watched for a subscription. Files are watched through
+/// their directory, so editors that save by writing a new file and renaming
+/// it over the old one are still noticed.
+#[cfg(feature = "watch")]
+fn watch_dir(path: &Path) -> PathBuf {
+    if path.is_dir() {
+        return path.to_path_buf();
+    }
+    path.parent().map_or_else(|| path.to_path_buf(), Path::to_path_buf)
+}
+
+/// `resources/subscribe`: files (and directories) the client wants to hear
+/// about, with `notifications/resources/updated` sent when one changes on disk
+pub struct ResourceSubscriptions {
+    #[cfg_attr(not(feature = "watch"), allow(dead_code))]
+    client: Arc<ClientPeer>,
+    watched: Arc<Mutex<Watched>>,
+    /// Created with the first subscription
+    #[cfg(feature = "watch")]
+    watcher: Mutex<Option<notify::RecommendedWatcher>>,
+}
+
+impl ResourceSubscriptions {
+    pub fn new(client: Arc<ClientPeer>) -> Self {
+        Self {
+            client,
+            watched: Arc::default(),
+            #[cfg(feature = "watch")]
+            watcher: Mutex::new(None),
+        }
+    }
+
+    /// Whether `resources/subscribe` is available in this build
+    pub fn supported() -> bool {
+        cfg!(feature = "watch")
+    }
+
+    /// Report changes to `path` as changes to `uri`. Subscribing twice is the
+    /// same as subscribing once.
+    #[cfg(feature = "watch")]
+    pub fn subscribe(&self, uri: &str, path: &Path) -> io::Result<()> {
+        use notify::Watcher;
+
+        let mut watcher = lock(&self.watcher);
+        if watcher.is_none() {
+            *watcher = Some(self.new_watcher()?);
+        }
+        let mut watched = lock(&self.watched);
+        if watched.uris.contains_key(path) {
+            return Ok(());
+        }
+        let dir = watch_dir(path);
+        if !watched.dirs.contains_key(&dir) {
+            if let Some(watcher) = watcher.as_mut() {
+                watcher.watch(&dir, notify::RecursiveMode::NonRecursive).map_err(io::Error::other)?;
+            }
+        }
+        *watched.dirs.entry(dir.clone()).or_default() += 1;
+        watched.uris.insert(path.to_path_buf(), (uri.to_string(), dir));
+        Ok(())
+    }
+
+    #[cfg(not(feature = "watch"))]
+    pub fn subscribe(&self, _uri: &str, _path: &Path) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "this build does not include the watch feature"))
+    }
+
+    /// Stop reporting changes to `path`. Returns false when it was not
+    /// subscribed.
+    pub fn unsubscribe(&self, path: &Path) -> bool {
+        // Same lock order as subscribe
+        #[cfg(feature = "watch")]
+        let mut watcher = lock(&self.watcher);
+        let mut watched = lock(&self.watched);
+        let Some((_, dir)) = watched.uris.remove(path) else {
+            return false;
+        };
+        let remaining = watched.dirs.get_mut(&dir).map(|count| {
+            *count -= 1;
+            *count
+        });
+        if remaining == Some(0) {
+            watched.dirs.remove(&dir);
+            #[cfg(feature = "watch")]
+            if let Some(watcher) = watcher.as_mut() {
+                use notify::Watcher;
+                // The directory may already be gone, which ends the watch anyway
+                let _ = watcher.unwatch(&dir);
+            }
+        }
+        true
+    }
+
+    #[cfg(feature = "watch")]
+    fn new_watcher(&self) -> io::Result<notify::RecommendedWatcher> {
+        let (client, watched) = (self.client.clone(), self.watched.clone());
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            let mut uris: Vec<String> = {
+                let watched = lock(&watched);
+                event.paths.iter().flat_map(|path| watched.uris_for(path)).collect()
+            };
+            uris.sort();
+            uris.dedup();
+            for uri in uris {
+                log_debug!("{} changed on disk", uri);
+                client.notify("notifications/resources/updated", json!({ "uri": uri }));
+            }
+        })
+        .map_err(io::Error::other)
+    }
+}
@@ -3,6 +3,7 @@ use serde_json::json;
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 use once_cell::sync::Lazy;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,8 @@ pub struct WorkflowStep {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OperationMode {
+    /// Tells apart successive runs of the same mode
+    pub id: u64,
     pub name: String,
     pub start_time: DateTime<Utc>,
     pub context: HashMap<String, serde_json::Value>,
@@ -24,7 +27,9 @@ pub struct OperationMode {
 
 impl OperationMode {
     pub fn new(name: String, available_tools: Vec<String>) -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
         Self {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
             name,
             start_time: Utc::now(),
             context: HashMap::new(),
@@ -58,6 +63,9 @@ impl OperationMode {
                 step_map.insert("step".to_string(), json!(step.step_name));
                 step_map.insert("timestamp".to_string(), json!(step.timestamp.to_rfc3339()));
                 step_map.insert("summary".to_string(), json!(step.result_summary));
+                if let Some(undo_step) = step.metadata.get("undo_step") {
+                    step_map.insert("undo_step".to_string(), undo_step.clone());
+                }
                 step_map
             })
             .collect();
@@ -97,11 +105,20 @@ pub fn get_operation_mode_tools(mode_name: &str) -> Vec<String> {
             "read_file".to_string(),
             "write_file".to_string(),
             "edit_file".to_string(),
+            "apply_patch".to_string(),
             "get_file_info".to_string(),
             "head_file".to_string(),
             "tail_file".to_string(),
             "read_file_lines".to_string(),
             "read_media_file".to_string(),
+            "summarize_file".to_string(),
+            "download_file".to_string(),
+            "hexdump_file".to_string(),
+            "split_file".to_string(),
+            "checksum_file".to_string(),
+            "tag_path".to_string(),
+            "annotate_path".to_string(),
+            "touch_file".to_string(),
         ],
         "multiple_file_operations" => vec![
             "read_multiple_files".to_string(),
@@ -111,6 +128,15 @@ pub fn get_operation_mode_tools(mode_name: &str) -> Vec<String> {
             "zip_files".to_string(),
             "unzip_file".to_string(),
             "zip_directory".to_string(),
+            "batch_operations".to_string(),
+            "run_pipeline".to_string(),
+            "stage_files".to_string(),
+            "unstage_files".to_string(),
+            "list_staged".to_string(),
+            "commit_staged".to_string(),
+            "concat_files".to_string(),
+            "checksum_files".to_string(),
+            "rename_files".to_string(),
         ],
         "directory_operations" => vec![
             "create_directory".to_string(),
@@ -119,16 +145,44 @@ pub fn get_operation_mode_tools(mode_name: &str) -> Vec<String> {
             "list_directory_with_sizes".to_string(),
             "calculate_directory_size".to_string(),
             "find_empty_directories".to_string(),
+            "find_empty_files".to_string(),
+            "get_disk_space".to_string(),
+            "merge_directories".to_string(),
+            "normalize_permissions".to_string(),
             "delete_file".to_string(), // for directories
         ],
         "search_and_analysis" => vec![
             "search_files".to_string(),
             "search_files_content".to_string(),
             "find_duplicate_files".to_string(),
+            "find_stale_files".to_string(),
+            "find_recent_changes".to_string(),
+            "find_recent_files".to_string(),
+            "directory_profile".to_string(),
+            "find_files_by_size".to_string(),
+            "find_files_by_mtime".to_string(),
+            "audit_permissions".to_string(),
+            "compare_directories".to_string(),
+            "compare_files".to_string(),
+            "list_tags".to_string(),
+            "find_by_tag".to_string(),
         ],
         "file_management" => vec![
             "list_allowed_directories".to_string(),
             "delete_file".to_string(), // for files
+            "delete_matching".to_string(),
+            "apply_plan".to_string(),
+            "diff_sandbox".to_string(),
+            "commit_sandbox".to_string(),
+            "acquire_lock".to_string(),
+            "release_lock".to_string(),
+            "create_temp_file".to_string(),
+            "create_temp_dir".to_string(),
+            "create_symlink".to_string(),
+            "create_hardlink".to_string(),
+            "start_watch".to_string(),
+            "poll_watch".to_string(),
+            "stop_watch".to_string(),
         ],
         _ => vec![],
     }
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{patch::DEFAULT_FUZZ, FileSystemService};
+use std::fmt::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyPatchTool {
+    pub path: String,
+    /// Unified diff for this one file, as written by `diff -u` or `git diff`
+    pub patch: String,
+    /// Context lines that may be ignored at each end of a hunk (default 2)
+    #[serde(default)]
+    pub fuzz: Option<usize>,
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+}
+
+impl ApplyPatchTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let is_dry_run = self.dry_run.unwrap_or(false);
+        let outcome = fs_service
+            .apply_patch(Path::new(&self.path), &self.patch, self.fuzz.unwrap_or(DEFAULT_FUZZ), is_dry_run)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let mut text = if is_dry_run {
+            format!("Preview of patching {}:\n\n", self.path)
+        } else {
+            format!("Successfully patched file: {}\n\n", self.path)
+        };
+        // Only hunks that moved or needed fuzz are worth a line
+        for (index, hunk) in outcome.hunks.iter().enumerate() {
+            if hunk.line != hunk.expected_line || hunk.fuzz > 0 {
+                writeln!(
+                    text,
+                    "Hunk {} applied at line {} (expected {}, fuzz {})",
+                    index + 1,
+                    hunk.line,
+                    hunk.expected_line,
+                    hunk.fuzz
+                )
+                .map_err(CallToolError::new)?;
+            }
+        }
+        text.push_str(&outcome.diff);
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
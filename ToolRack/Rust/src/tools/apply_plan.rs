@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    plan::{Plan, PlanDocument},
+    FileSystemService, StepResult,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyPlan {
+    /// Plan document as emitted with `output_format: "plan"`, as an object or a JSON string
+    pub plan: serde_json::Value,
+    pub output_format: Option<String>,
+}
+
+impl ApplyPlan {
+    fn format_output(document: &PlanDocument, results: &[StepResult], output_format: &str) -> Result<String, String> {
+        let failed = results.iter().filter(|r| !r.ok).count();
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "succeeded": results.len() - failed,
+                "failed": failed,
+                "results": results,
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let kind = match &document.plan {
+                    Plan::Dedupe(_) => "dedupe",
+                    Plan::Merge(_) => "merge",
+                };
+                writeln!(
+                    output,
+                    "Applied {} plan from {}: {} succeeded, {} failed",
+                    kind,
+                    document.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    results.len() - failed,
+                    failed
+                )
+                .map_err(|e| e.to_string())?;
+                for result in results {
+                    let status = if result.ok { "ok" } else { "FAILED" };
+                    writeln!(output, "  [{}] {}: {}", status, result.path, result.message).map_err(|e| e.to_string())?;
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let document = PlanDocument::from_json(self.plan).map_err(CallToolError::new)?;
+        let results = fs_service.apply_plan(&document).await;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&document, &results, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(results.iter().any(|r| !r.ok)),
+            structured_content: None,
+        })
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{audit::PermissionAudit, FileSystemService};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPermissions {
+    pub path: String,
+    pub exclude_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    pub output_format: Option<String>,
+}
+
+impl AuditPermissions {
+    fn format_output(audit: &PermissionAudit, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(audit).map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                if audit.findings.is_empty() {
+                    write!(output, "No permission issues found in {} entries under {}.", audit.scanned_entries, audit.root)
+                        .map_err(|e| e.to_string())?;
+                    return Ok(output);
+                }
+                writeln!(
+                    output,
+                    "{} findings in {} entries under {}:",
+                    audit.findings.len(),
+                    audit.scanned_entries,
+                    audit.root
+                )
+                .map_err(|e| e.to_string())?;
+                for finding in &audit.findings {
+                    writeln!(
+                        output,
+                        "  [{}] {}{}  {}",
+                        finding.severity,
+                        finding.path,
+                        finding.mode.as_deref().map(|m| format!(" ({})", m)).unwrap_or_default(),
+                        finding.detail
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let audit = fs_service
+            .audit_permissions(
+                std::path::Path::new(&self.path),
+                &self.exclude_patterns.unwrap_or_default(),
+                self.include_defaults_excluded,
+            )
+            .await
+            .map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&audit, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
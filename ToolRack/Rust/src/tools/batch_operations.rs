@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    batch::{BatchOperation, BatchReport},
+    FileSystemService,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOperationsTool {
+    /// Steps applied in order; a failure rolls back the steps before it
+    pub operations: Vec<BatchOperation>,
+    pub output_format: Option<String>,
+}
+
+impl BatchOperationsTool {
+    pub(crate) fn format_output(report: &BatchReport, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(report).map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                match report.failed_step {
+                    None => writeln!(output, "Batch committed: {} steps applied.", report.steps.len()),
+                    Some(index) => writeln!(
+                        output,
+                        "Batch rolled back: step {} failed, {} earlier steps were undone.",
+                        index + 1,
+                        index
+                    ),
+                }
+                .map_err(|e| e.to_string())?;
+                for step in &report.steps {
+                    match &step.error {
+                        Some(error) => writeln!(output, "  {}. [FAILED] {}: {}", step.index + 1, step.operation, error),
+                        None => writeln!(output, "  {}. {}", step.index + 1, step.operation),
+                    }
+                    .map_err(|e| e.to_string())?;
+                }
+                if !report.rollback_errors.is_empty() {
+                    writeln!(output, "\nRollback was incomplete; check these locations:").map_err(|e| e.to_string())?;
+                    for error in &report.rollback_errors {
+                        writeln!(output, "  {}", error).map_err(|e| e.to_string())?;
+                    }
+                }
+                for warning in &report.cleanup_warnings {
+                    writeln!(output, "Warning: could not remove backup {}", warning).map_err(|e| e.to_string())?;
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        if self.operations.is_empty() {
+            return Err(CallToolError::new("batch_operations requires at least one operation"));
+        }
+        let report = fs_service.batch_operations(&self.operations).await.map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&report, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(!report.committed),
+            structured_content: None,
+        })
+    }
+}
@@ -7,6 +7,10 @@ use std::path::Path;
 pub struct CalculateDirectorySize {
     pub root_path: String,
     pub output_format: Option<String>,
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
 }
 
 impl CalculateDirectorySize {
@@ -14,7 +18,7 @@ impl CalculateDirectorySize {
 
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
         let total_bytes = fs_service
-            .calculate_directory_size(Path::new(&self.root_path))
+            .calculate_directory_size(Path::new(&self.root_path), self.follow_symlinks, self.include_defaults_excluded)
             .await
             .map_err(CallToolError::new)?;
         let output_content = match self.output_format.as_deref().unwrap_or("human-readable") {
@@ -27,6 +31,7 @@ impl CalculateDirectorySize {
                 text: output_content,
             })],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
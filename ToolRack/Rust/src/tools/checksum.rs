@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{checksum::HashAlgorithm, FileSystemService};
+use std::fmt::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksumFileTool {
+    pub path: String,
+    /// sha256 (default), sha512 or md5
+    #[serde(default)]
+    pub algorithm: HashAlgorithm,
+    /// A published digest to compare against, e.g. from a release page
+    #[serde(default)]
+    pub expected: Option<String>,
+}
+
+impl ChecksumFileTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let checksum = fs_service
+            .checksum(Path::new(&self.path), self.algorithm)
+            .await
+            .map_err(CallToolError::new)?;
+
+        // sha256sum's layout, so the line can be saved and checked with it
+        let mut text = format!("{}  {}\n{}\n", checksum, self.path, self.algorithm.as_str());
+        if let Some(expected) = &self.expected {
+            if expected.trim().eq_ignore_ascii_case(&checksum) {
+                text.push_str("Matches the expected checksum\n");
+            } else {
+                text.push_str(&format!("Does NOT match the expected checksum {}\n", expected.trim()));
+            }
+        }
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksumFilesTool {
+    pub paths: Vec<String>,
+    #[serde(default)]
+    pub algorithm: HashAlgorithm,
+    pub output_format: Option<String>,
+}
+
+impl ChecksumFilesTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        if self.paths.is_empty() {
+            return Err(CallToolError::new("checksum_files requires at least one path"));
+        }
+        let results = fs_service.checksum_files(&self.paths, self.algorithm).await;
+
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&serde_json::json!({
+                "algorithm": self.algorithm,
+                "files": results,
+            }))
+            .map_err(CallToolError::new)?,
+            _ => {
+                let mut output = format!("{}\n", self.algorithm.as_str());
+                for result in &results {
+                    match (&result.checksum, &result.error) {
+                        (Some(checksum), _) => writeln!(output, "{}  {}", checksum, result.path),
+                        (None, error) => writeln!(output, "ERROR  {}: {}", result.path, error.as_deref().unwrap_or("unknown error")),
+                    }
+                    .map_err(CallToolError::new)?;
+                }
+                output
+            }
+        };
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text: content })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{sandbox::SandboxCommit, FileSystemService};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitSandbox {
+    /// Only commit changes at or below these paths; everything when empty
+    pub paths: Vec<String>,
+    pub output_format: Option<String>,
+}
+
+impl CommitSandbox {
+    fn format_output(report: &SandboxCommit, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(report).map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                writeln!(output, "Committed {} change(s) to disk", report.committed.len()).map_err(|e| e.to_string())?;
+                for path in &report.committed {
+                    writeln!(output, "  {}", path).map_err(|e| e.to_string())?;
+                }
+                if let Some(failed) = &report.failed {
+                    writeln!(output, "FAILED: {}", failed).map_err(|e| e.to_string())?;
+                }
+                if report.remaining > 0 {
+                    writeln!(output, "{} change(s) remain in the sandbox", report.remaining).map_err(|e| e.to_string())?;
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let report = fs_service.commit_sandbox(&self.paths).await.map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&report, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(report.failed.is_some()),
+            structured_content: None,
+        })
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    compare::{CompareMode, DirectoryComparison},
+    utils::format_bytes,
+    FileSystemService,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareDirectories {
+    pub path_a: String,
+    pub path_b: String,
+    /// "metadata" (size and mtime, default) or "hash" (size and SHA-256)
+    #[serde(default)]
+    pub compare_mode: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    pub output_format: Option<String>,
+}
+
+impl CompareDirectories {
+    fn format_output(comparison: &DirectoryComparison, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(comparison).map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                if comparison.is_identical() {
+                    write!(
+                        output,
+                        "Directories match: {} identical files in {} and {}.",
+                        comparison.identical_files, comparison.path_a, comparison.path_b
+                    )
+                    .map_err(|e| e.to_string())?;
+                    return Ok(output);
+                }
+                writeln!(output, "A: {}\nB: {}", comparison.path_a, comparison.path_b).map_err(|e| e.to_string())?;
+                for (label, paths) in [("Only in A", &comparison.only_in_a), ("Only in B", &comparison.only_in_b)] {
+                    if paths.is_empty() {
+                        continue;
+                    }
+                    writeln!(output, "\n{} ({}):", label, paths.len()).map_err(|e| e.to_string())?;
+                    for path in paths {
+                        writeln!(output, "  {}", path).map_err(|e| e.to_string())?;
+                    }
+                }
+                if !comparison.differing.is_empty() {
+                    writeln!(output, "\nDiffering ({}):", comparison.differing.len()).map_err(|e| e.to_string())?;
+                    for entry in &comparison.differing {
+                        let size = |s: Option<u64>| s.map(format_bytes).unwrap_or_else(|| "dir".to_string());
+                        writeln!(
+                            output,
+                            "  {}  [{}]  {} vs {}",
+                            entry.path,
+                            entry.reason,
+                            size(entry.size_a),
+                            size(entry.size_b)
+                        )
+                        .map_err(|e| e.to_string())?;
+                    }
+                }
+                writeln!(output, "\n{} identical files", comparison.identical_files).map_err(|e| e.to_string())?;
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let mode: CompareMode = match self.compare_mode.as_deref() {
+            Some(mode) => mode.parse().map_err(CallToolError::new)?,
+            None => CompareMode::default(),
+        };
+
+        let comparison = fs_service
+            .compare_directories(
+                std::path::Path::new(&self.path_a),
+                std::path::Path::new(&self.path_b),
+                &self.exclude_patterns.unwrap_or_default(),
+                self.include_defaults_excluded,
+                mode,
+            )
+            .await
+            .map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&comparison, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
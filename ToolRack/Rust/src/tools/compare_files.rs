@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{utils::format_bytes, FileSystemService};
+use std::path::Path;
+
+/// Unchanged lines shown around each change unless context_lines says otherwise
+pub const DEFAULT_CONTEXT_LINES: usize = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompareFiles {
+    pub path_a: String,
+    pub path_b: String,
+    pub context_lines: Option<usize>,
+    pub output_format: Option<String>,
+}
+
+impl CompareFiles {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let comparison = fs_service
+            .compare_files(
+                Path::new(&self.path_a),
+                Path::new(&self.path_b),
+                self.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES),
+            )
+            .await
+            .map_err(CallToolError::new)?;
+
+        let text = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&comparison).map_err(CallToolError::new)?,
+            _ => {
+                let sizes = format!("{} and {}", format_bytes(comparison.size_a), format_bytes(comparison.size_b));
+                match (&comparison.diff, comparison.first_difference) {
+                    _ if comparison.identical => format!(
+                        "Files are identical: {} and {} ({})",
+                        comparison.path_a,
+                        comparison.path_b,
+                        format_bytes(comparison.size_a)
+                    ),
+                    (Some(diff), _) => diff.clone(),
+                    (None, Some(offset)) => format!(
+                        "Binary files {} and {} ({}) differ from byte {}",
+                        comparison.path_a, comparison.path_b, sizes, offset
+                    ),
+                    (None, None) => format!("Files {} and {} ({}) differ", comparison.path_a, comparison.path_b, sizes),
+                }
+            }
+        };
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
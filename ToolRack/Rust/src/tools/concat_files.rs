@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{utils::format_bytes, FileSystemService};
+use std::fmt::Write;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConcatFilesTool {
+    /// Files to join in order; glob patterns expand to their sorted matches
+    pub paths: Vec<String>,
+    pub output_path: String,
+    /// Written between consecutive files, not after the last one
+    pub separator: Option<String>,
+    pub overwrite: bool,
+    pub output_format: Option<String>,
+}
+
+impl ConcatFilesTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        if self.paths.is_empty() {
+            return Err(CallToolError::new("concat_files requires at least one path"));
+        }
+        let report = fs_service
+            .concat_files(&self.paths, Path::new(&self.output_path), self.separator.as_deref(), self.overwrite)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&report).map_err(CallToolError::new)?,
+            _ => {
+                let mut output = format!(
+                    "Joined {} file(s) into {} ({})\nsha256: {}\n",
+                    report.inputs.len(),
+                    report.output,
+                    format_bytes(report.bytes),
+                    report.sha256
+                );
+                for input in &report.inputs {
+                    writeln!(output, "  {} ({})", input.path, format_bytes(input.bytes)).map_err(CallToolError::new)?;
+                }
+                for note in &report.notes {
+                    writeln!(output, "Note: {}", note).map_err(CallToolError::new)?;
+                }
+                output
+            }
+        };
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text: content })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::ops::FileSystemOps;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,15 +10,23 @@ pub struct CopyFileTool {
 }
 
 impl CopyFileTool {
-    
-
-    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+    pub async fn run_tool(self, fs_service: &dyn FileSystemOps) -> Result<CallToolResult, CallToolError> {
         match fs_service.copy_file(Path::new(&self.source), Path::new(&self.destination)).await {
-            Ok(_) => Ok(CallToolResult {
+            Ok(warnings) => Ok(CallToolResult {
                 content: vec![Content::Text(TextContent {
-                    text: format!("Successfully copied {} to {}", self.source, self.destination),
+                    text: if warnings.is_empty() {
+                        format!("Successfully copied {} to {}", self.source, self.destination)
+                    } else {
+                        format!(
+                            "Copied {} to {} with warnings:\n{}",
+                            self.source,
+                            self.destination,
+                            warnings.join("\n")
+                        )
+                    },
                 })],
                 is_error: Some(false),
+                structured_content: None,
             }),
             Err(e) => Err(CallToolError::new(e)),
         }
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::FileSystemService;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateLinkTool {
+    /// Where the new link goes
+    pub path: String,
+    /// What it points at; a relative symlink target is read from the link's directory
+    pub target: String,
+    /// A hard link instead of a symbolic one
+    #[serde(default)]
+    pub hard: bool,
+}
+
+impl CreateLinkTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let (link, target) = (Path::new(&self.path), Path::new(&self.target));
+        let kind = if self.hard {
+            fs_service.create_hardlink(link, target).await.map_err(CallToolError::new)?;
+            "hard link"
+        } else {
+            fs_service.create_symlink(link, target).await.map_err(CallToolError::new)?;
+            "symbolic link"
+        };
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: format!("Created {} {} -> {}", kind, self.path, self.target),
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
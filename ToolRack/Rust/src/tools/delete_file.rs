@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::{ops::FileSystemOps, trash::DeleteMode};
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,12 +8,18 @@ pub struct DeleteFileTool {
     pub path: String,
     #[serde(default)]
     pub confirm: Option<bool>,
+    /// Delete for good rather than into the trash
+    #[serde(default)]
+    pub permanent: Option<bool>,
+    /// Overwrite file contents with random data before deleting for good
+    #[serde(default)]
+    pub secure: Option<bool>,
 }
 
 impl DeleteFileTool {
     
 
-    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+    pub async fn run_tool(self, fs_service: &dyn FileSystemOps) -> Result<CallToolResult, CallToolError> {
         let confirmed = self.confirm.unwrap_or(false);
 
         if !confirmed {
@@ -22,15 +28,30 @@ impl DeleteFileTool {
                     text: "Delete operation requires confirmation. Set 'confirm: true' to proceed.".to_string(),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             });
         }
 
-        match fs_service.delete_file(Path::new(&self.path)).await {
-            Ok(_) => Ok(CallToolResult {
+        let mode = if self.secure.unwrap_or(false) {
+            DeleteMode::Secure
+        } else if self.permanent.unwrap_or(false) {
+            DeleteMode::Permanent
+        } else {
+            DeleteMode::Trash
+        };
+        match fs_service.delete_with(Path::new(&self.path), mode).await {
+            Ok(trashed) => Ok(CallToolResult {
                 content: vec![Content::Text(TextContent {
-                    text: format!("Successfully deleted: {}", self.path),
+                    text: if trashed {
+                        format!("Moved to the trash: {}", self.path)
+                    } else if mode == DeleteMode::Secure {
+                        format!("Overwrote and deleted: {}", self.path)
+                    } else {
+                        format!("Successfully deleted: {}", self.path)
+                    },
                 })],
                 is_error: Some(false),
+                structured_content: None,
             }),
             Err(e) => Err(CallToolError::new(e)),
         }
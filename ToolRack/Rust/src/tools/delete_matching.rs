@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    bulk_delete::DeletePreview,
+    scan::FileQuery,
+    trash::DeleteMode,
+    utils::format_bytes,
+    FileSystemService, StepResult,
+};
+use std::{fmt::Write, path::Path};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteMatchingTool {
+    /// Directory searched recursively
+    pub path: String,
+    /// Glob matched against file names, or relative paths when it has a `/`
+    pub pattern: String,
+    pub exclude_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    /// Only list the files unless this is explicitly false
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    #[serde(default)]
+    pub confirm: Option<bool>,
+    /// The token a preview returned, standing for the files it listed
+    #[serde(default)]
+    pub confirm_token: Option<String>,
+    #[serde(default)]
+    pub permanent: Option<bool>,
+    #[serde(default)]
+    pub secure: Option<bool>,
+    pub output_format: Option<String>,
+}
+
+impl DeleteMatchingTool {
+    fn format_output(preview: &DeletePreview, results: Option<&[StepResult]>, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "dry_run": results.is_none(),
+                "preview": preview,
+                "results": results,
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                match results {
+                    None => {
+                        writeln!(
+                            output,
+                            "{} files matching {} under {} ({}) would be deleted:",
+                            preview.files.len(),
+                            preview.pattern,
+                            preview.root,
+                            format_bytes(preview.total_bytes)
+                        )
+                        .map_err(|e| e.to_string())?;
+                        for file in &preview.files {
+                            writeln!(output, "  {}", file).map_err(|e| e.to_string())?;
+                        }
+                        if !preview.files.is_empty() {
+                            write!(
+                                output,
+                                "\nNo files were deleted. Re-run with dry_run: false, confirm: true and confirm_token: \"{}\" to delete exactly these files.",
+                                preview.confirm_token
+                            )
+                            .map_err(|e| e.to_string())?;
+                        }
+                    }
+                    Some(results) => {
+                        let failed = results.iter().filter(|r| !r.ok).count();
+                        writeln!(
+                            output,
+                            "Deleted {} of {} files matching {} under {}, {} failed",
+                            results.len() - failed,
+                            results.len(),
+                            preview.pattern,
+                            preview.root,
+                            failed
+                        )
+                        .map_err(|e| e.to_string())?;
+                        for result in results {
+                            let status = if result.ok { "ok" } else { "FAILED" };
+                            writeln!(output, "  [{}] {}: {}", status, result.path, result.message).map_err(|e| e.to_string())?;
+                        }
+                    }
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    fn refuse(text: &str) -> CallToolResult {
+        CallToolResult {
+            content: vec![Content::Text(TextContent { text: text.to_string() })],
+            is_error: Some(true),
+            structured_content: None,
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let query = FileQuery {
+            pattern: Some(self.pattern.clone()),
+            exclude_patterns: self.exclude_patterns.clone().unwrap_or_default(),
+            include_defaults_excluded: self.include_defaults_excluded,
+            ..Default::default()
+        };
+        let root = Path::new(&self.path);
+
+        let (preview, results) = if self.dry_run != Some(false) {
+            (fs_service.preview_delete_matching(root, &query).await.map_err(CallToolError::new)?, None)
+        } else {
+            let Some(token) = self.confirm_token.as_deref().filter(|_| self.confirm == Some(true)) else {
+                return Ok(Self::refuse(
+                    "delete_matching only deletes what a preview listed: call it with dry_run left on first, then again with dry_run: false, confirm: true and the confirm_token the preview returned.",
+                ));
+            };
+            let mode = if self.secure.unwrap_or(false) {
+                DeleteMode::Secure
+            } else if self.permanent.unwrap_or(false) {
+                DeleteMode::Permanent
+            } else {
+                DeleteMode::Trash
+            };
+            let (preview, results) =
+                fs_service.delete_matching(root, &query, token, mode).await.map_err(CallToolError::new)?;
+            (preview, Some(results))
+        };
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let text = Self::format_output(&preview, results.as_deref(), output_format).map_err(CallToolError::new)?;
+        let failed = results.as_ref().is_some_and(|results| results.iter().any(|r| !r.ok));
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(failed),
+            structured_content: Some(json!({
+                "dry_run": results.is_none(),
+                "root": preview.root,
+                "pattern": preview.pattern,
+                "files": preview.files,
+                "total_bytes": preview.total_bytes,
+                "confirm_token": preview.confirm_token,
+                "results": results,
+            })),
+        })
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    sandbox::{ChangeKind, SandboxChange},
+    FileSystemService,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiffSandbox {
+    pub output_format: Option<String>,
+}
+
+impl DiffSandbox {
+    fn format_output(changes: &[SandboxChange], output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(changes).map_err(|e| e.to_string()),
+            _ if changes.is_empty() => Ok("The sandbox holds no changes.".to_string()),
+            _ => Self::format_changes(
+                changes,
+                &format!("{} pending change(s); commit_sandbox writes them to disk:", changes.len()),
+            ),
+        }
+    }
+
+    /// `changes` as text under `heading`: one line per path, the files that
+    /// deleted directories hold, then the diffs
+    pub(crate) fn format_changes(changes: &[SandboxChange], heading: &str) -> Result<String, String> {
+        let mut output = String::new();
+        writeln!(output, "{}", heading).map_err(|e| e.to_string())?;
+        for change in changes {
+            let label = match change.kind {
+                ChangeKind::Added => "added",
+                ChangeKind::Modified => "modified",
+                ChangeKind::Deleted => "deleted",
+                ChangeKind::AddedDirectory => "new directory",
+                ChangeKind::ReplacedDirectory => "replaced directory (previous contents removed)",
+            };
+            match change.size {
+                Some(size) if change.diff.is_none() => writeln!(output, "  [{}] {} ({} bytes, binary)", label, change.path, size),
+                _ => writeln!(output, "  [{}] {}", label, change.path),
+            }
+            .map_err(|e| e.to_string())?;
+            for file in &change.removed_files {
+                writeln!(output, "      - {}", file).map_err(|e| e.to_string())?;
+            }
+            if let Some(count) = change.removed_file_count.filter(|count| *count > change.removed_files.len()) {
+                writeln!(output, "      ... and {} more file(s)", count - change.removed_files.len())
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        for diff in changes.iter().filter_map(|c| c.diff.as_deref()) {
+            write!(output, "\n{}", diff).map_err(|e| e.to_string())?;
+        }
+        Ok(output)
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let changes = fs_service.diff_sandbox().await.map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&changes, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
@@ -17,20 +17,38 @@ pub struct DirectoryOperationsTool {
     pub exclude_patterns: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_symlinks: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_defaults_excluded: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub whitespace_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cleanup: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict_strategy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir_mode: Option<String>,
 }
 
 impl DirectoryOperationsTool {
     pub fn tool_definition() -> Tool {
         Tool {
             name: "directory_operations".to_string(),
-            description: Some("Perform various directory operations including create, list, tree view, size calculation, and finding empty directories.".to_string()),
+            description: Some("Perform various directory operations including create, list, tree view, size calculation, finding empty directories and files, merging directory trees, normalizing permissions, and reporting free disk space.".to_string()),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
                     "operation": {
                         "type": "string",
                         "description": "The operation to perform",
-                        "enum": ["create_directory", "list_directory", "directory_tree", "list_directory_with_sizes", "calculate_directory_size", "find_empty_directories"]
+                        "enum": ["create_directory", "list_directory", "directory_tree", "list_directory_with_sizes", "calculate_directory_size", "find_empty_directories", "find_empty_files", "get_disk_space", "merge_directories", "normalize_permissions"]
                     },
                     "path": {
                         "type": "string",
@@ -48,16 +66,60 @@ impl DirectoryOperationsTool {
                     "exclude_patterns": {
                         "type": "array",
                         "items": { "type": "string" },
-                        "description": "Patterns to exclude from empty directory search"
+                        "description": "Patterns to exclude from empty directory and empty file search, merges and permission normalization"
                     },
                     "output_format": {
                         "type": "string",
-                        "description": "Output format for size calculation",
-                        "enum": ["human-readable", "bytes"]
+                        "description": "Output format: 'human-readable' or 'bytes' for size calculation and disk space, 'text' or 'json' for find_empty_files, merge_directories and normalize_permissions, 'plan' to emit a merge plan document for file_management apply_plan",
+                        "enum": ["human-readable", "bytes", "text", "json", "plan"]
+                    },
+                    "follow_symlinks": {
+                        "type": "boolean",
+                        "description": "Follow symbolic links during tree view and size calculation (loops are detected)",
+                        "default": false
+                    },
+                    "whitespace_only": {
+                        "type": "boolean",
+                        "description": "For find_empty_files: also report files containing only whitespace",
+                        "default": false
+                    },
+                    "cleanup": {
+                        "type": "boolean",
+                        "description": "For find_empty_files: delete the files that were found (each is re-checked before deletion)",
+                        "default": false
+                    },
+                    "destination": {
+                        "type": "string",
+                        "description": "For merge_directories: the directory that path is merged into (created if missing)"
+                    },
+                    "conflict_strategy": {
+                        "type": "string",
+                        "description": "For merge_directories: what to do when a source file differs from an existing destination file",
+                        "enum": ["keep_newest", "keep_largest", "rename_with_suffix", "skip"],
+                        "default": "skip"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "For merge_directories and normalize_permissions: only report what would change unless this is explicitly false",
+                        "default": true
+                    },
+                    "file_mode": {
+                        "type": "string",
+                        "description": "For normalize_permissions: octal mode applied to every file, e.g. '644' (Unix only)"
+                    },
+                    "dir_mode": {
+                        "type": "string",
+                        "description": "For normalize_permissions: octal mode applied to every directory including path, e.g. '755' (Unix only)"
+                    },
+                    "include_defaults_excluded": {
+                        "type": "boolean",
+                        "description": "Also traverse .git, node_modules, target, __pycache__ and OS junk files, which are skipped by default",
+                        "default": false
                     }
                 },
                 "required": ["operation", "path"]
             }),
+            output_schema: None,
         }
     }
 
@@ -72,10 +134,37 @@ impl DirectoryOperationsTool {
                     text: format!("Operation '{}' is not available in the current operation mode. Use 'start_operation_mode' with 'directory_operations' to enable this operation.", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             });
         }
 
-        let result = match self.operation.as_str() {
+        let operation = self.operation.clone();
+        let path = self.path.clone();
+        let result = self.run_operation(fs_service).await;
+
+        // Add workflow step if operation was successful
+        if let Ok(ref call_result) = result {
+            if !call_result.is_error.unwrap_or(false) {
+                let result_json = json!({
+                    "operation": operation.clone(),
+                    "path": path,
+                    "success": true
+                });
+                add_workflow_step(
+                    format!("directory_operations:{}", operation),
+                    result_json,
+                    undo_metadata(fs_service)
+                );
+            }
+        }
+
+        result
+    }
+
+    /// Run the operation without the operation-mode check, for the flat
+    /// tools of `--tool-style flat`
+    pub async fn run_operation(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        match self.operation.as_str() {
             "create_directory" => {
                 let tool = CreateDirectoryTool { path: self.path.clone() };
                 tool.run_tool(fs_service).await
@@ -92,6 +181,8 @@ impl DirectoryOperationsTool {
                     path: self.path.clone(),
                     include_hidden: self.include_hidden.unwrap_or(false),
                     max_depth: self.max_depth.unwrap_or(0),
+                    follow_symlinks: self.follow_symlinks.unwrap_or(false),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
                 };
                 tool.run_tool(fs_service).await
             },
@@ -103,6 +194,8 @@ impl DirectoryOperationsTool {
                 let tool = CalculateDirectorySize {
                     root_path: self.path.clone(),
                     output_format: self.output_format,
+                    follow_symlinks: self.follow_symlinks.unwrap_or(false),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
                 };
                 tool.run_tool(fs_service).await
             },
@@ -114,30 +207,64 @@ impl DirectoryOperationsTool {
                 };
                 tool.run_tool(fs_service).await
             },
+            "find_empty_files" => {
+                let tool = FindEmptyFiles {
+                    path: self.path.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    output_format: self.output_format.clone(),
+                    whitespace_only: self.whitespace_only.unwrap_or(false),
+                    cleanup: self.cleanup.unwrap_or(false),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "get_disk_space" => {
+                let tool = GetDiskSpace {
+                    path: self.path.clone(),
+                    output_format: self.output_format,
+                };
+                tool.run_tool(fs_service).await
+            },
+            "merge_directories" => {
+                let Some(destination) = self.destination.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "destination is required for merge_directories operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = MergeDirectories {
+                    source: self.path.clone(),
+                    destination,
+                    conflict_strategy: self.conflict_strategy.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    dry_run: self.dry_run,
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "normalize_permissions" => {
+                let tool = NormalizePermissions {
+                    path: self.path.clone(),
+                    file_mode: self.file_mode.clone(),
+                    dir_mode: self.dir_mode.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    dry_run: self.dry_run,
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
             _ => Ok(CallToolResult {
                 content: vec![Content::Text(TextContent {
                     text: format!("Unknown operation: {}", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             }),
-        };
-
-        // Add workflow step if operation was successful
-        if let Ok(ref call_result) = result {
-            if !call_result.is_error.unwrap_or(false) {
-                let result_json = json!({
-                    "operation": self.operation.clone(),
-                    "path": self.path.clone(),
-                    "success": true
-                });
-                add_workflow_step(
-                    format!("directory_operations:{}", self.operation),
-                    result_json,
-                    None
-                );
-            }
         }
-
-        result
     }
 }
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{scan::FileQuery, FileSystemService};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryProfileTool {
+    pub path: String,
+    pub pattern: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+}
+
+impl DirectoryProfileTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let query = FileQuery {
+            pattern: self.pattern,
+            exclude_patterns: self.exclude_patterns.unwrap_or_default(),
+            include_defaults_excluded: self.include_defaults_excluded,
+            ..Default::default()
+        };
+
+        let profile = fs_service
+            .directory_profile(std::path::Path::new(&self.path), &query)
+            .await
+            .map_err(CallToolError::new)?;
+        let content = serde_json::to_string_pretty(&profile).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
@@ -13,6 +13,12 @@ pub struct DirectoryTreeTool {
     /// Maximum depth to traverse (0 means unlimited)
     #[serde(default)]
     pub max_depth: u32,
+    /// Descend into symlinked directories (cycles are detected and reported)
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Also show `.git`, `node_modules`, `target` and other default-excluded directories
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
 }
 
 impl DirectoryTreeTool {
@@ -23,10 +29,12 @@ impl DirectoryTreeTool {
         let path = self.path.clone();
         let include_hidden = self.include_hidden;
         let max_depth = self.max_depth;
+        let follow_symlinks = self.follow_symlinks;
+        let include_defaults_excluded = self.include_defaults_excluded;
         match retry_3x("directory_tree", || {
             let p = path.clone();
             async move {
-                fs_service.generate_directory_tree(std::path::Path::new(&p), include_hidden, max_depth).await
+                fs_service.generate_directory_tree(std::path::Path::new(&p), include_hidden, max_depth, follow_symlinks, include_defaults_excluded).await
             }
         }).await {
             Ok(tree) => Ok(CallToolResult {
@@ -34,6 +42,7 @@ impl DirectoryTreeTool {
                     text: tree,
                 })],
                 is_error: Some(false),
+                structured_content: None,
             }),
             Err(e) => Err(CallToolError::new(e)),
         }
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{utils::format_bytes, FileSystemService};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadFile {
+    pub url: String,
+    /// File to write; its directory must exist
+    pub path: String,
+    /// Replace an existing file
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+impl DownloadFile {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let report = fs_service
+            .download_file(&self.url, Path::new(&self.path), self.overwrite)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let mut text = format!(
+            "Downloaded {} to {} ({})\nsha256: {}",
+            report.url,
+            report.path,
+            format_bytes(report.bytes),
+            report.sha256
+        );
+        if report.final_url != report.url {
+            text.push_str(&format!("\nRedirected to: {}", report.final_url));
+        }
+        if let Some(content_type) = &report.content_type {
+            text.push_str(&format!("\nContent-Type: {}", content_type));
+        }
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
@@ -1,7 +1,8 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::{edits::EditMatch, encoding::TextEncoding, FileSystemService};
 use crate::tools::EditOperation;
+use serde_json::json;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +11,9 @@ pub struct EditFileTool {
     pub edits: Vec<EditOperation>,
     #[serde(rename = "dryRun", default, skip_serializing_if = "std::option::Option::is_none")]
     pub dry_run: Option<bool>,
+    /// The file's encoding (`auto` to guess); the edit is saved in it
+    #[serde(default, skip_serializing_if = "std::option::Option::is_none")]
+    pub encoding: Option<String>,
 }
 
 impl EditFileTool {
@@ -17,25 +21,46 @@ impl EditFileTool {
 
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
         let is_dry_run = self.dry_run.unwrap_or(false);
+        let encoding = match &self.encoding {
+            Some(encoding) => encoding.parse::<TextEncoding>().map_err(CallToolError::new)?,
+            None => TextEncoding::default(),
+        };
 
-        match fs_service.apply_file_edits(
+        match fs_service.apply_file_edits_as(
             Path::new(&self.path),
             self.edits,
             Some(is_dry_run),
-            None
+            None,
+            encoding
         ).await {
-            Ok(diff_output) => {
-                let message = if is_dry_run {
-                    format!("Preview of changes to {}:\n\n{}", self.path, diff_output)
+            Ok(outcome) => {
+                // Only edits that needed a fallback or did not apply are worth a line
+                let notes: String = outcome
+                    .matches
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, found)| !matches!(found, EditMatch::Exact | EditMatch::Lines))
+                    .map(|(index, found)| format!("Edit {}: {}\n", index + 1, found.describe()))
+                    .collect();
+                let all_applied = outcome.all_applied();
+                let message = if !all_applied {
+                    format!("No changes written to {}: not every edit applied\n\n{}", self.path, notes)
+                } else if is_dry_run {
+                    format!("Preview of changes to {}:\n\n{}{}", self.path, notes, outcome.diff)
                 } else {
-                    format!("Successfully edited file: {}\n\nChanges applied:\n{}", self.path, diff_output)
+                    format!("Successfully edited file: {}\n\n{}Changes applied:\n{}", self.path, notes, outcome.diff)
                 };
 
                 Ok(CallToolResult {
                     content: vec![Content::Text(TextContent {
                         text: message,
                     })],
-                    is_error: Some(false),
+                    is_error: Some(!all_applied),
+                    structured_content: Some(json!({
+                        "path": self.path,
+                        "written": all_applied && !is_dry_run,
+                        "edits": outcome.matches,
+                    })),
                 })
             }
             Err(e) => Err(CallToolError::new(e)),
@@ -1,9 +1,34 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What an edit does. `replace` (the default) swaps oldText for newText; the
+/// line kinds address 1-based lines of the file as it stands when the edit
+/// runs, so an earlier edit in the same call can move them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditKind {
+    #[default]
+    Replace,
+    /// Insert newText before `line`; one past the last line appends
+    InsertAtLine,
+    /// Replace lines `start` to `end` with newText
+    ReplaceLines,
+    /// Remove lines `start` to `end`
+    DeleteLines,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EditOperation {
-    #[serde(rename = "oldText")]
+    #[serde(rename = "type", default)]
+    pub kind: EditKind,
+    #[serde(rename = "oldText", default)]
     pub old_text: String,
-    #[serde(rename = "newText")]
+    #[serde(rename = "newText", default)]
     pub new_text: String,
-}
\ No newline at end of file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<usize>,
+    /// Last line of the range, inclusive; defaults to `start`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<usize>,
+}
@@ -12,33 +12,158 @@ pub struct FileManagementTool {
     pub path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub confirm: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permanent: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secure: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exclude_patterns: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_defaults_excluded: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirm_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recursive: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watch_id: Option<String>,
 }
 
 impl FileManagementTool {
     pub fn tool_definition() -> Tool {
         Tool {
             name: "file_management".to_string(),
-            description: Some("Perform file management operations including listing allowed directories and deleting files.".to_string()),
+            description: Some("Perform file management operations including listing allowed directories, deleting files (into the trash unless permanent is set) one at a time or by glob after a dry-run preview, applying plans produced by find_duplicate_files or merge_directories, reviewing or committing the changes held by --sandbox mode, advisory locks for coordinating agents that share a workspace, managed temporary files and directories, creating symbolic and hard links, and watching paths for changes made by other processes.".to_string()),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
                     "operation": {
                         "type": "string",
                         "description": "The operation to perform",
-                        "enum": ["list_allowed_directories", "delete_file"]
+                        "enum": ["list_allowed_directories", "delete_file", "delete_matching", "apply_plan", "diff_sandbox", "commit_sandbox", "acquire_lock", "release_lock", "create_temp_file", "create_temp_dir", "create_symlink", "create_hardlink", "start_watch", "poll_watch", "stop_watch"]
                     },
                     "path": {
                         "type": "string",
-                        "description": "File or directory path for delete operation"
+                        "description": "File or directory path for delete, acquire_lock and release_lock operations; for delete_matching, the directory searched; for create_symlink and create_hardlink, where the new link goes; for start_watch, the file or directory to watch"
                     },
                     "confirm": {
                         "type": "boolean",
-                        "description": "Confirmation for delete operation",
+                        "description": "Confirmation for delete_file, and for delete_matching with dry_run false",
                         "default": false
+                    },
+                    "permanent": {
+                        "type": "boolean",
+                        "description": "For delete_file and delete_matching: delete for good instead of moving the item to the trash (the Recycle Bin on Windows), which is the default unless the server runs with --no-trash",
+                        "default": false
+                    },
+                    "secure": {
+                        "type": "boolean",
+                        "description": "For delete_file and delete_matching: overwrite file contents with random data, then delete for good, keeping nothing for backup or undo. Best effort: SSDs, copy-on-write file systems and snapshots may still hold the old data",
+                        "default": false
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "For delete_matching: glob matched against file names, or against paths relative to path when it contains a '/' (e.g. '*.tmp', 'build/**/*.o')"
+                    },
+                    "exclude_patterns": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "For delete_matching: globs of files and directories to leave out"
+                    },
+                    "include_defaults_excluded": {
+                        "type": "boolean",
+                        "description": "For delete_matching: also look inside directories skipped by default (.git, node_modules, ...)",
+                        "default": false
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "For delete_matching: only list the matching files and return a confirm_token, unless this is explicitly false",
+                        "default": true
+                    },
+                    "confirm_token": {
+                        "type": "string",
+                        "description": "For delete_matching with dry_run false: the confirm_token of the preview. Deleting is refused when the matching files are no longer exactly those previewed"
+                    },
+                    "plan": {
+                        "type": ["object", "string"],
+                        "description": "For apply_plan: a plan document emitted with output_format 'plan' by find_duplicate_files or merge_directories"
+                    },
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "For commit_sandbox: only commit changes at or below these paths (default: all)"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "description": "Output format for delete_matching, apply_plan, diff_sandbox, commit_sandbox, acquire_lock and poll_watch results; for list_allowed_directories, 'json' returns roots with file:// URIs and the blocked directories",
+                        "enum": ["text", "json"],
+                        "default": "text"
+                    },
+                    "ttl_seconds": {
+                        "type": "number",
+                        "description": "For acquire_lock: seconds until the lock goes stale unless renewed by acquiring it again (default 300, at most 86400)"
+                    },
+                    "owner": {
+                        "type": "string",
+                        "description": "For acquire_lock: who holds the lock, shown to others who find the path locked (default: this server session)"
+                    },
+                    "force": {
+                        "type": "boolean",
+                        "description": "For release_lock: break a lock held by another session",
+                        "default": false
+                    },
+                    "prefix": {
+                        "type": "string",
+                        "description": "For create_temp_file and create_temp_dir: start of the generated name (default 'tmp')"
+                    },
+                    "suffix": {
+                        "type": "string",
+                        "description": "For create_temp_file: end of the generated name, e.g. '.json'"
+                    },
+                    "content": {
+                        "type": "string",
+                        "description": "For create_temp_file: initial contents (default empty)"
+                    },
+                    "target": {
+                        "type": "string",
+                        "description": "For create_symlink: what the link points at, stored as given; a relative target is read from the link's directory. For create_hardlink: the existing file to give another name. Either way it must be inside the allowed directories"
+                    },
+                    "recursive": {
+                        "type": "boolean",
+                        "description": "For start_watch: also watch everything below the directory",
+                        "default": false
+                    },
+                    "watch_id": {
+                        "type": "string",
+                        "description": "For poll_watch and stop_watch: the id start_watch returned"
                     }
                 },
                 "required": ["operation"]
             }),
+            output_schema: None,
         }
     }
 
@@ -53,12 +178,41 @@ impl FileManagementTool {
                     text: format!("Operation '{}' is not available in the current operation mode. Use 'start_operation_mode' with 'file_management' to enable this operation.", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             });
         }
 
-        let result = match self.operation.as_str() {
+        let operation = self.operation.clone();
+        let path = self.path.clone();
+        let result = self.run_operation(fs_service).await;
+
+        // Add workflow step if operation was successful
+        if let Ok(ref call_result) = result {
+            if !call_result.is_error.unwrap_or(false) {
+                let result_json = json!({
+                    "operation": operation.clone(),
+                    "path": path,
+                    "success": true
+                });
+                add_workflow_step(
+                    format!("file_management:{}", operation),
+                    result_json,
+                    undo_metadata(fs_service)
+                );
+            }
+        }
+
+        result
+    }
+
+    /// Run the operation without the operation-mode check, for the flat
+    /// tools of `--tool-style flat`
+    pub async fn run_operation(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        match self.operation.as_str() {
             "list_allowed_directories" => {
-                let tool = ListAllowedDirectoriesTool {};
+                let tool = ListAllowedDirectoriesTool {
+                    output_format: self.output_format.clone(),
+                };
                 tool.run_tool(fs_service).await
             },
             "delete_file" => {
@@ -68,38 +222,171 @@ impl FileManagementTool {
                             text: "Path is required for delete_file operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = DeleteFileTool {
                     path: self.path.clone().unwrap(),
                     confirm: self.confirm,
+                    permanent: self.permanent,
+                    secure: self.secure,
                 };
                 tool.run_tool(fs_service).await
             },
+            "delete_matching" => {
+                let (Some(path), Some(pattern)) = (self.path.clone(), self.pattern.clone()) else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "path and pattern are required for delete_matching operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = DeleteMatchingTool {
+                    path,
+                    pattern,
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    dry_run: self.dry_run,
+                    confirm: self.confirm,
+                    confirm_token: self.confirm_token.clone(),
+                    permanent: self.permanent,
+                    secure: self.secure,
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "apply_plan" => {
+                let Some(plan) = self.plan.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "plan is required for apply_plan operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = ApplyPlan {
+                    plan,
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "diff_sandbox" => {
+                let tool = DiffSandbox {
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "commit_sandbox" => {
+                let tool = CommitSandbox {
+                    paths: self.paths.clone().unwrap_or_default(),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "acquire_lock" | "release_lock" => {
+                let Some(path) = self.path.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: format!("Path is required for {} operation", self.operation),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                if self.operation == "acquire_lock" {
+                    let tool = AcquireLockTool {
+                        path,
+                        ttl_seconds: self.ttl_seconds,
+                        owner: self.owner.clone(),
+                        output_format: self.output_format.clone(),
+                    };
+                    tool.run_tool(fs_service).await
+                } else {
+                    let tool = ReleaseLockTool {
+                        path,
+                        force: self.force.unwrap_or(false),
+                    };
+                    tool.run_tool(fs_service).await
+                }
+            },
+            "create_temp_file" => {
+                let tool = CreateTempFileTool {
+                    prefix: self.prefix.clone(),
+                    suffix: self.suffix.clone(),
+                    content: self.content.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "create_temp_dir" => {
+                let tool = CreateTempDirTool {
+                    prefix: self.prefix.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "create_symlink" | "create_hardlink" => {
+                let (Some(path), Some(target)) = (self.path.clone(), self.target.clone()) else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: format!("path and target are required for {} operation", self.operation),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = CreateLinkTool {
+                    path,
+                    target,
+                    hard: self.operation == "create_hardlink",
+                };
+                tool.run_tool(fs_service).await
+            },
+            "start_watch" => {
+                let Some(path) = self.path.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "Path is required for start_watch operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = StartWatchTool {
+                    path,
+                    recursive: self.recursive.unwrap_or(false),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "poll_watch" | "stop_watch" => {
+                let Some(watch_id) = self.watch_id.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: format!("watch_id is required for {} operation", self.operation),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                if self.operation == "poll_watch" {
+                    let tool = PollWatchTool {
+                        watch_id,
+                        output_format: self.output_format.clone(),
+                    };
+                    tool.run_tool(fs_service).await
+                } else {
+                    let tool = StopWatchTool { watch_id };
+                    tool.run_tool(fs_service).await
+                }
+            },
             _ => Ok(CallToolResult {
                 content: vec![Content::Text(TextContent {
                     text: format!("Unknown operation: {}", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             }),
-        };
-
-        // Add workflow step if operation was successful
-        if let Ok(ref call_result) = result {
-            if !call_result.is_error.unwrap_or(false) {
-                let result_json = json!({
-                    "operation": self.operation.clone(),
-                    "path": self.path.clone(),
-                    "success": true
-                });
-                add_workflow_step(
-                    format!("file_management:{}", self.operation),
-                    result_json,
-                    None
-                );
-            }
         }
-
-        result
     }
 }
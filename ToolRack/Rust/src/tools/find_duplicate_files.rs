@@ -1,20 +1,105 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
-use std::{collections::BTreeMap, fmt::Write};
+use crate::fs_service::{
+    duplicates::{DedupeAction, DedupePlan, DuplicateScan, KeeperRule},
+    plan::{Plan, PlanDocument},
+    utils::format_bytes,
+    FileSystemService, StepResult,
+};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FindDuplicateFiles {
-    pub root_path: String,
+    /// Directories searched together; groups may span several of them
+    pub root_paths: Vec<String>,
     pub pattern: Option<String>,
     pub exclude_patterns: Option<Vec<String>>,
     pub min_bytes: Option<u64>,
     pub max_bytes: Option<u64>,
     pub output_format: Option<String>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    /// "hardlink", "symlink" or "delete_extras"; omit to only report duplicates
+    #[serde(default)]
+    pub action: Option<String>,
+    /// Which copy survives: "shortest_path" (default) or "oldest"
+    #[serde(default)]
+    pub keep: Option<String>,
+    /// With an action, only show the plan unless this is explicitly false.
+    /// `output_format: "plan"` never applies; it emits a document for `apply_plan`.
+    #[serde(default)]
+    pub dry_run: Option<bool>,
 }
 
 impl FindDuplicateFiles {
-    
+    /// Schema of the `structuredContent` returned next to the report
+    pub fn output_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "roots": { "type": "array", "items": { "type": "string" } },
+                "groups": {
+                    "type": "array",
+                    "description": "Sets of files with identical contents",
+                    "items": { "type": "array", "items": { "type": "string" } }
+                },
+                "plan": { "type": "object", "description": "The dedupe plan, when an action was given" },
+                "dry_run": { "type": "boolean" },
+                "results": { "type": "array", "description": "Outcome of each step, when the plan was applied" }
+            },
+            "required": ["roots", "groups"]
+        })
+    }
+
+    fn format_plan(
+        plan: &DedupePlan,
+        applied: Option<&[StepResult]>,
+        output_format: &str,
+    ) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "dry_run": applied.is_none(),
+                "plan": plan,
+                "results": applied,
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let heading = if applied.is_some() { "Applied" } else { "Dry-run plan for" };
+                writeln!(
+                    output,
+                    "{} {:?} on {} duplicate groups (keeping {:?}), reclaimable: {}",
+                    heading,
+                    plan.action,
+                    plan.groups.len(),
+                    plan.keep_rule,
+                    format_bytes(plan.reclaimable_bytes)
+                )
+                .map_err(|e| e.to_string())?;
+                for group in &plan.groups {
+                    writeln!(output, "\nKeep: {}", group.keep).map_err(|e| e.to_string())?;
+                    for extra in &group.extras {
+                        writeln!(output, "  -> {}", extra).map_err(|e| e.to_string())?;
+                    }
+                }
+                match applied {
+                    Some(results) => {
+                        let failed = results.iter().filter(|r| !r.ok).count();
+                        writeln!(output, "\nResults: {} succeeded, {} failed", results.len() - failed, failed)
+                            .map_err(|e| e.to_string())?;
+                        for result in results {
+                            let status = if result.ok { "ok" } else { "FAILED" };
+                            writeln!(output, "  [{}] {}: {}", status, result.path, result.message)
+                                .map_err(|e| e.to_string())?;
+                        }
+                    }
+                    None => output.push_str("\nNo files were changed. Re-run with dry_run: false to apply this plan.\n"),
+                }
+                Ok(output)
+            }
+        }
+    }
 
     fn format_output(
         duplicate_files: Vec<Vec<String>>,
@@ -50,27 +135,148 @@ impl FindDuplicateFiles {
         }
     }
 
+    // With several roots: how many duplicates each root holds, and each group's
+    // files listed under their roots
+    fn format_cross_root_output(scan: &DuplicateScan, output_format: &str) -> Result<String, String> {
+        let splits: Vec<_> = scan.groups.iter().map(|group| scan.split_by_root(group)).collect();
+        let spanning = splits.iter().filter(|split| split.len() > 1).count();
+        let per_root: Vec<(String, usize, usize, usize)> = scan
+            .roots
+            .iter()
+            .map(|root| {
+                let mut files = 0;
+                let mut groups = 0;
+                let mut shared = 0;
+                for split in &splits {
+                    if let Some((_, in_root)) = split.iter().find(|(r, _)| *r == root.as_path()) {
+                        files += in_root.len();
+                        groups += 1;
+                        if split.len() > 1 {
+                            shared += 1;
+                        }
+                    }
+                }
+                (root.display().to_string(), files, groups, shared)
+            })
+            .collect();
+
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "roots": per_root
+                    .iter()
+                    .map(|(root, files, groups, shared)| json!({
+                        "root": root,
+                        "duplicate_files": files,
+                        "groups": groups,
+                        "groups_spanning_roots": shared,
+                    }))
+                    .collect::<Vec<_>>(),
+                "groups": splits
+                    .iter()
+                    .map(|split| json!({
+                        "spans_roots": split.len() > 1,
+                        "by_root": split
+                            .iter()
+                            .map(|(root, files)| (root.display().to_string(), files.clone()))
+                            .collect::<BTreeMap<_, _>>(),
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                if scan.groups.is_empty() {
+                    writeln!(output, "No duplicate files were found across {} roots.", scan.roots.len())
+                        .map_err(|e| e.to_string())?;
+                } else {
+                    writeln!(
+                        output,
+                        "Found {} sets of duplicate files across {} roots ({} spanning more than one root):",
+                        scan.groups.len(),
+                        scan.roots.len(),
+                        spanning
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+                writeln!(output, "\nPer root:").map_err(|e| e.to_string())?;
+                for (root, files, groups, shared) in &per_root {
+                    writeln!(output, "  {}: {} files in {} groups ({} shared with other roots)", root, files, groups, shared)
+                        .map_err(|e| e.to_string())?;
+                }
+                for (i, split) in splits.iter().enumerate() {
+                    let spans = if split.len() > 1 { format!(" (spans {} roots)", split.len()) } else { String::new() };
+                    writeln!(output, "\nDuplicated Group {}{}:", i + 1, spans).map_err(|e| e.to_string())?;
+                    for (root, files) in split {
+                        writeln!(output, "  {}:", root.display()).map_err(|e| e.to_string())?;
+                        for file in files {
+                            writeln!(output, "    {file}").map_err(|e| e.to_string())?;
+                        }
+                    }
+                }
+                Ok(output)
+            }
+        }
+    }
+
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
-        let duplicate_files = fs_service
-            .find_duplicate_files(
-                std::path::Path::new(&self.root_path),
+        let root_paths: Vec<PathBuf> = self.root_paths.iter().map(PathBuf::from).collect();
+        let scan = fs_service
+            .find_duplicates_across_roots(
+                &root_paths,
                 self.pattern.clone(),
                 self.exclude_patterns.clone(),
                 self.min_bytes.or(Some(1)),
                 self.max_bytes,
+                self.include_defaults_excluded,
             )
             .await
             .map_err(CallToolError::new)?;
+        let duplicate_files = scan.groups.clone();
+        let mut structured = json!({ "roots": scan.roots, "groups": scan.groups });
 
         let output_format = self.output_format.as_deref().unwrap_or("text");
-        let result_content = Self::format_output(duplicate_files, output_format)
-            .map_err(CallToolError::new)?;
+        let result_content = match self.action.as_deref() {
+            None if output_format == "plan" => {
+                return Err(CallToolError::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "output_format 'plan' requires an action",
+                )));
+            }
+            None if scan.roots.len() > 1 => {
+                Self::format_cross_root_output(&scan, output_format).map_err(CallToolError::new)?
+            }
+            None => Self::format_output(duplicate_files, output_format).map_err(CallToolError::new)?,
+            Some(action) => {
+                let action: DedupeAction = action.parse().map_err(CallToolError::new)?;
+                let keep_rule: KeeperRule = match self.keep.as_deref() {
+                    Some(keep) => keep.parse().map_err(CallToolError::new)?,
+                    None => KeeperRule::default(),
+                };
+                let plan = DedupePlan::build(&duplicate_files, action, keep_rule).map_err(CallToolError::new)?;
+
+                if output_format == "plan" {
+                    serde_json::to_string_pretty(&PlanDocument::new(Plan::Dedupe(plan))).map_err(|e| e.to_string())
+                } else if self.dry_run == Some(false) {
+                    let results = fs_service.apply_dedupe_plan(&plan).await;
+                    structured["plan"] = json!(plan);
+                    structured["dry_run"] = json!(false);
+                    structured["results"] = json!(results);
+                    Self::format_plan(&plan, Some(&results), output_format)
+                } else {
+                    structured["plan"] = json!(plan);
+                    structured["dry_run"] = json!(true);
+                    Self::format_plan(&plan, None, output_format)
+                }
+                .map_err(CallToolError::new)?
+            }
+        };
 
         Ok(CallToolResult {
             content: vec![Content::Text(TextContent {
                 text: result_content,
             })],
             is_error: Some(false),
+            structured_content: Some(structured),
         })
     }
 }
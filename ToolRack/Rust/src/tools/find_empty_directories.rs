@@ -60,6 +60,7 @@ impl FindEmptyDirectories {
                 text: content,
             })],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
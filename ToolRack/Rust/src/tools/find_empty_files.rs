@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::FileSystemService;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindEmptyFiles {
+    pub path: String,
+    pub exclude_patterns: Option<Vec<String>>,
+    pub output_format: Option<String>,
+    /// Also report files containing only whitespace
+    #[serde(default)]
+    pub whitespace_only: bool,
+    /// Delete the files that were found
+    #[serde(default)]
+    pub cleanup: bool,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+}
+
+impl FindEmptyFiles {
+    fn format_output(
+        empty_files: &[String],
+        deleted: Option<&[(String, Result<(), String>)]>,
+        output_format: &str,
+    ) -> Result<String, String> {
+        match output_format {
+            "json" => {
+                let document = match deleted {
+                    None => json!(empty_files),
+                    Some(deleted) => json!({
+                        "found": empty_files,
+                        "deleted": deleted.iter().filter(|(_, r)| r.is_ok()).map(|(p, _)| p).collect::<Vec<_>>(),
+                        "failed": deleted
+                            .iter()
+                            .filter_map(|(p, r)| r.as_ref().err().map(|e| json!({ "path": p, "error": e })))
+                            .collect::<Vec<_>>(),
+                    }),
+                };
+                Ok(serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?)
+            }
+            _ => {
+                let mut output = String::new();
+                if empty_files.is_empty() {
+                    output.push_str("No empty files were found.");
+                    return Ok(output);
+                }
+                writeln!(
+                    output,
+                    "Found {} empty {}:",
+                    empty_files.len(),
+                    if empty_files.len() == 1 { "file" } else { "files" }
+                )
+                .map_err(|e| e.to_string())?;
+                match deleted {
+                    None => {
+                        for file in empty_files {
+                            writeln!(output, "  {file}").map_err(|e| e.to_string())?;
+                        }
+                    }
+                    Some(deleted) => {
+                        for (file, result) in deleted {
+                            match result {
+                                Ok(()) => writeln!(output, "  deleted {file}"),
+                                Err(e) => writeln!(output, "  kept {file}: {e}"),
+                            }
+                            .map_err(|e| e.to_string())?;
+                        }
+                    }
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let empty_files = fs_service
+            .find_empty_files(
+                std::path::Path::new(&self.path),
+                self.exclude_patterns,
+                self.whitespace_only,
+                self.include_defaults_excluded,
+            )
+            .await
+            .map_err(CallToolError::new)?;
+
+        let deleted = if self.cleanup {
+            let results = fs_service.delete_empty_files(&empty_files, self.whitespace_only).await;
+            Some(results.into_iter().map(|(p, r)| (p, r.map_err(|e| e.to_string()))).collect::<Vec<_>>())
+        } else {
+            None
+        };
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&empty_files, deleted.as_deref(), output_format)
+            .map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    scan::{format_record_line, parse_timestamp, FileQuery, FileRecord, TimeField},
+    FileSystemService,
+};
+use chrono::{DateTime, Local};
+use std::{fmt::Write, time::SystemTime};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindFilesByMtime {
+    pub path: String,
+    /// Start of the window (inclusive), RFC 3339 or local "YYYY-MM-DD HH:MM:SS"
+    #[serde(default)]
+    pub start: Option<String>,
+    /// End of the window (inclusive), same formats as `start`
+    #[serde(default)]
+    pub end: Option<String>,
+    pub pattern: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    /// Return at most this many files (oldest first)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    pub output_format: Option<String>,
+}
+
+impl FindFilesByMtime {
+    fn window(&self) -> Result<(Option<SystemTime>, Option<SystemTime>), String> {
+        if self.start.is_none() && self.end.is_none() {
+            return Err("find_files_by_mtime requires start, end, or both".to_string());
+        }
+        let start = self.start.as_deref().map(parse_timestamp).transpose()?;
+        let end = self.end.as_deref().map(parse_timestamp).transpose()?;
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                return Err("start must not be later than end".to_string());
+            }
+        }
+        Ok((start, end))
+    }
+
+    fn describe_window(start: Option<SystemTime>, end: Option<SystemTime>) -> String {
+        let format = |t: SystemTime| DateTime::<Local>::from(t).format("%Y-%m-%d %H:%M:%S").to_string();
+        match (start, end) {
+            (Some(start), Some(end)) => format!("between {} and {}", format(start), format(end)),
+            (Some(start), None) => format!("since {}", format(start)),
+            (None, Some(end)) => format!("up to {}", format(end)),
+            (None, None) => String::new(),
+        }
+    }
+
+    fn format_output(
+        files: &[FileRecord],
+        total_matches: usize,
+        start: Option<SystemTime>,
+        end: Option<SystemTime>,
+        output_format: &str,
+    ) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "start": start.map(DateTime::<Local>::from),
+                "end": end.map(DateTime::<Local>::from),
+                "total_matches": total_matches,
+                "files": files,
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let window = Self::describe_window(start, end);
+                if files.is_empty() {
+                    write!(output, "No files were modified {}.", window).map_err(|e| e.to_string())?;
+                    return Ok(output);
+                }
+                writeln!(output, "{} files modified {}, oldest first:", total_matches, window)
+                    .map_err(|e| e.to_string())?;
+                for record in files {
+                    writeln!(output, "{}", format_record_line(record, TimeField::Modified)).map_err(|e| e.to_string())?;
+                }
+                if files.len() < total_matches {
+                    writeln!(output, "  ... {} later changes not shown", total_matches - files.len())
+                        .map_err(|e| e.to_string())?;
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let (start, end) = self.window().map_err(CallToolError::new)?;
+        let query = FileQuery {
+            pattern: self.pattern.clone(),
+            exclude_patterns: self.exclude_patterns.clone().unwrap_or_default(),
+            include_defaults_excluded: self.include_defaults_excluded,
+            ..Default::default()
+        };
+
+        let mut files = fs_service
+            .find_files_by_mtime(std::path::Path::new(&self.path), &query, start, end)
+            .await
+            .map_err(CallToolError::new)?;
+        let total_matches = files.len();
+        if let Some(limit) = self.limit {
+            files.truncate(limit);
+        }
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&files, total_matches, start, end, output_format)
+            .map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
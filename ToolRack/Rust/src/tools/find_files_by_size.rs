@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    scan::{format_record_line, FileQuery, FileRecord, FileSort, TimeField},
+    utils::format_bytes,
+    FileSystemService,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindFilesBySize {
+    pub path: String,
+    pub min_bytes: Option<u64>,
+    pub max_bytes: Option<u64>,
+    pub pattern: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    /// "size_desc" (default), "size_asc", "modified_desc", "modified_asc" or "path"
+    #[serde(default)]
+    pub sort: Option<String>,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    pub output_format: Option<String>,
+}
+
+impl FindFilesBySize {
+    fn format_output(files: &[FileRecord], total_matches: usize, total_bytes: u64, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "total_matches": total_matches,
+                "total_bytes": total_bytes,
+                "files": files,
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                if files.is_empty() {
+                    output.push_str("No files found in the requested size range.");
+                    return Ok(output);
+                }
+                writeln!(output, "Found {} files ({} total):", total_matches, format_bytes(total_bytes))
+                    .map_err(|e| e.to_string())?;
+                for record in files {
+                    writeln!(output, "{}", format_record_line(record, TimeField::Modified)).map_err(|e| e.to_string())?;
+                }
+                if files.len() < total_matches {
+                    writeln!(output, "  ... {} more not shown", total_matches - files.len()).map_err(|e| e.to_string())?;
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let sort: FileSort = match self.sort.as_deref() {
+            Some(sort) => sort.parse().map_err(CallToolError::new)?,
+            None => FileSort::default(),
+        };
+        let query = FileQuery {
+            pattern: self.pattern,
+            exclude_patterns: self.exclude_patterns.unwrap_or_default(),
+            min_bytes: self.min_bytes,
+            max_bytes: self.max_bytes,
+            include_defaults_excluded: self.include_defaults_excluded,
+        };
+
+        let mut files = fs_service
+            .find_files_by_size(std::path::Path::new(&self.path), &query, sort)
+            .await
+            .map_err(CallToolError::new)?;
+        let total_matches = files.len();
+        let total_bytes = files.iter().map(|r| r.size).sum();
+        if let Some(limit) = self.limit {
+            files.truncate(limit);
+        }
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&files, total_matches, total_bytes, output_format)
+            .map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
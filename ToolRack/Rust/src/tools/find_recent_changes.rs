@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    scan::{format_record_line, parse_timestamp, FileQuery, FileRecord, TimeField},
+    FileSystemService,
+};
+use chrono::{DateTime, Local};
+use std::{fmt::Write, time::{Duration, SystemTime}};
+
+/// Window used when neither `since` nor `within_hours` is given
+const DEFAULT_WINDOW_HOURS: f64 = 24.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindRecentChanges {
+    pub path: String,
+    /// Look back this many hours (default 24)
+    #[serde(default)]
+    pub within_hours: Option<f64>,
+    /// Absolute start of the window; takes precedence over `within_hours`
+    #[serde(default)]
+    pub since: Option<String>,
+    /// End of the window (inclusive), e.g. to see what one build step wrote
+    #[serde(default)]
+    pub end: Option<String>,
+    pub pattern: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    /// Return at most this many files (newest first)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    pub output_format: Option<String>,
+}
+
+impl FindRecentChanges {
+    fn window_start(&self) -> Result<SystemTime, String> {
+        if let Some(since) = &self.since {
+            return parse_timestamp(since);
+        }
+        let hours = self.within_hours.unwrap_or(DEFAULT_WINDOW_HOURS);
+        if !hours.is_finite() || hours < 0.0 {
+            return Err(format!("within_hours must be a non-negative number, got {}", hours));
+        }
+        Ok(SystemTime::now()
+            .checked_sub(Duration::from_secs_f64(hours * 3600.0))
+            .unwrap_or(SystemTime::UNIX_EPOCH))
+    }
+
+    fn window_end(&self, since: SystemTime) -> Result<Option<SystemTime>, String> {
+        let Some(end) = self.end.as_deref().map(parse_timestamp).transpose()? else {
+            return Ok(None);
+        };
+        if end < since {
+            return Err("end must not be earlier than the start of the window".to_string());
+        }
+        Ok(Some(end))
+    }
+
+    fn format_output(
+        files: &[FileRecord],
+        total_matches: usize,
+        since: SystemTime,
+        end: Option<SystemTime>,
+        output_format: &str,
+    ) -> Result<String, String> {
+        let since = DateTime::<Local>::from(since);
+        let end = end.map(DateTime::<Local>::from);
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "since": since,
+                "end": end,
+                "total_matches": total_matches,
+                "files": files,
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let format = |time: DateTime<Local>| time.format("%Y-%m-%d %H:%M:%S").to_string();
+                let window = match end {
+                    Some(end) => format!("between {} and {}", format(since), format(end)),
+                    None => format!("since {}", format(since)),
+                };
+                if files.is_empty() {
+                    write!(output, "No files were modified {}.", window).map_err(|e| e.to_string())?;
+                    return Ok(output);
+                }
+                writeln!(output, "{} files modified {}, newest first:", total_matches, window)
+                    .map_err(|e| e.to_string())?;
+                for record in files {
+                    writeln!(output, "{}", format_record_line(record, TimeField::Modified)).map_err(|e| e.to_string())?;
+                }
+                if files.len() < total_matches {
+                    writeln!(output, "  ... {} older changes not shown", total_matches - files.len())
+                        .map_err(|e| e.to_string())?;
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let since = self.window_start().map_err(CallToolError::new)?;
+        let end = self.window_end(since).map_err(CallToolError::new)?;
+        let query = FileQuery {
+            pattern: self.pattern.clone(),
+            exclude_patterns: self.exclude_patterns.clone().unwrap_or_default(),
+            include_defaults_excluded: self.include_defaults_excluded,
+            ..Default::default()
+        };
+
+        let mut files = fs_service
+            .find_recent_changes(std::path::Path::new(&self.path), &query, since, end)
+            .await
+            .map_err(CallToolError::new)?;
+        let total_matches = files.len();
+        if let Some(limit) = self.limit {
+            files.truncate(limit);
+        }
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&files, total_matches, since, end, output_format)
+            .map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
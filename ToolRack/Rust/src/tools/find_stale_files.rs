@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    scan::{format_record_line, FileQuery, FileRecord, TimeField},
+    utils::format_bytes,
+    FileSystemService,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindStaleFiles {
+    pub path: String,
+    /// Minimum age in days for a file to count as stale
+    pub older_than_days: u64,
+    /// "modified" (default) or "accessed"
+    #[serde(default)]
+    pub time_field: Option<String>,
+    pub pattern: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    pub min_bytes: Option<u64>,
+    pub max_bytes: Option<u64>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    pub output_format: Option<String>,
+}
+
+impl FindStaleFiles {
+    fn format_output(
+        stale_files: &[FileRecord],
+        older_than_days: u64,
+        time_field: TimeField,
+        output_format: &str,
+    ) -> Result<String, String> {
+        let reclaimable_bytes: u64 = stale_files.iter().map(|r| r.size).sum();
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "summary": {
+                    "count": stale_files.len(),
+                    "reclaimable_bytes": reclaimable_bytes,
+                    "older_than_days": older_than_days,
+                    "time_field": time_field,
+                },
+                "files": stale_files,
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let verb = match time_field {
+                    TimeField::Modified => "modified",
+                    TimeField::Accessed => "accessed",
+                };
+                if stale_files.is_empty() {
+                    write!(output, "No files found that were not {} in the last {} days.", verb, older_than_days)
+                        .map_err(|e| e.to_string())?;
+                    return Ok(output);
+                }
+                writeln!(
+                    output,
+                    "Found {} files not {} in the last {} days ({} reclaimable), oldest first:",
+                    stale_files.len(),
+                    verb,
+                    older_than_days,
+                    format_bytes(reclaimable_bytes)
+                )
+                .map_err(|e| e.to_string())?;
+                for record in stale_files {
+                    writeln!(output, "{}", format_record_line(record, time_field)).map_err(|e| e.to_string())?;
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let time_field: TimeField = match self.time_field.as_deref() {
+            Some(field) => field.parse().map_err(CallToolError::new)?,
+            None => TimeField::default(),
+        };
+        let query = FileQuery {
+            pattern: self.pattern,
+            exclude_patterns: self.exclude_patterns.unwrap_or_default(),
+            min_bytes: self.min_bytes,
+            max_bytes: self.max_bytes,
+            include_defaults_excluded: self.include_defaults_excluded,
+        };
+
+        let stale_files = fs_service
+            .find_stale_files(std::path::Path::new(&self.path), &query, self.older_than_days, time_field)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&stale_files, self.older_than_days, time_field, output_format)
+            .map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
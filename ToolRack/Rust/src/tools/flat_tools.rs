@@ -0,0 +1,455 @@
+use serde_json::{json, Map, Value};
+
+use crate::client::ClientPeer;
+use crate::fs_service::FileSystemService;
+use crate::mcp_types::{CallToolError, CallToolResult, Tool};
+use crate::tools::*;
+
+/// Which tools `tools/list` exposes (--tool-style)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ToolStyle {
+    /// The five operation-enum tools plus operation-mode management
+    #[default]
+    Grouped,
+    /// One small tool per operation, without operation modes
+    Flat,
+    /// Both sets side by side
+    Both,
+}
+
+impl ToolStyle {
+    pub fn includes_grouped(self) -> bool {
+        self != Self::Flat
+    }
+
+    pub fn includes_flat(self) -> bool {
+        self != Self::Grouped
+    }
+}
+
+/// The grouped tool an operation belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Group {
+    SingleFile,
+    MultipleFile,
+    Directory,
+    SearchAndAnalysis,
+    FileManagement,
+}
+
+impl Group {
+    fn tool_definition(self) -> Tool {
+        match self {
+            Self::SingleFile => SingleFileOperationsTool::tool_definition(),
+            Self::MultipleFile => MultipleFileOperationsTool::tool_definition(),
+            Self::Directory => DirectoryOperationsTool::tool_definition(),
+            Self::SearchAndAnalysis => SearchAndAnalysisTool::tool_definition(),
+            Self::FileManagement => FileManagementTool::tool_definition(),
+        }
+    }
+}
+
+/// Schema of the structured content a flat tool returns, for the operations
+/// that return one. Grouped tools mix operations, so they declare none.
+fn output_schema(name: &str) -> Option<Value> {
+    match name {
+        "get_file_info" => Some(GetFileInfoTool::output_schema()),
+        "list_directory_with_sizes" => Some(ListDirectoryWithSizes::output_schema()),
+        "search_files" => Some(SearchFilesTool::output_schema()),
+        "find_duplicate_files" => Some(FindDuplicateFiles::output_schema()),
+        _ => None,
+    }
+}
+
+/// An operation of a grouped tool exposed as a tool of its own. Its schema is
+/// the grouped tool's schema narrowed to the parameters the operation reads.
+struct FlatOperation {
+    name: &'static str,
+    group: Group,
+    description: &'static str,
+    params: &'static [&'static str],
+    required: &'static [&'static str],
+}
+
+const fn op(
+    name: &'static str,
+    group: Group,
+    description: &'static str,
+    params: &'static [&'static str],
+    required: &'static [&'static str],
+) -> FlatOperation {
+    FlatOperation { name, group, description, params, required }
+}
+
+use Group::*;
+
+const FLAT_OPERATIONS: &[FlatOperation] = &[
+    // single_file_operations
+    op(
+        "read_file",
+        SingleFile,
+        "Read the contents of a text file, or a byte range of it.",
+        &["path", "with_line_numbers", "offset_bytes", "length_bytes", "cursor", "encoding"],
+        &["path"],
+    ),
+    op("write_file", SingleFile, "Create a file or replace its contents.", &["path", "content", "create_parents"], &["path", "content"]),
+    op(
+        "touch_file",
+        SingleFile,
+        "Create an empty file, or update the access and modification times of an existing one without changing its content.",
+        &["path", "create_parents"],
+        &["path"],
+    ),
+    op("edit_file", SingleFile, "Replace exact text, or insert, replace or delete numbered lines, in a file and return a diff of the change.", &["path", "edits", "dry_run", "encoding"], &["path", "edits"]),
+    op(
+        "apply_patch",
+        SingleFile,
+        "Apply a unified diff to a file, matching hunks by context with optional fuzz.",
+        &["path", "patch", "fuzz", "dry_run"],
+        &["path", "patch"],
+    ),
+    op(
+        "get_file_info",
+        SingleFile,
+        "Size, timestamps, permissions, type and MIME type of a file or directory, optionally with its line count, hash and owner.",
+        &["path", "follow_symlinks", "with_line_count", "with_hash", "algorithm", "with_owner"],
+        &["path"],
+    ),
+    op("head_file", SingleFile, "Read the first lines of a file.", &["path", "lines"], &["path", "lines"]),
+    op("tail_file", SingleFile, "Read the last lines of a file.", &["path", "lines"], &["path", "lines"]),
+    op(
+        "hexdump_file",
+        SingleFile,
+        "Show the bytes of a file as hex with an ASCII column, e.g. to inspect binary headers.",
+        &["path", "byte_offset", "from_end", "length", "bytes_per_line", "ascii"],
+        &["path"],
+    ),
+    op(
+        "split_file",
+        SingleFile,
+        "Cut a file into numbered parts by bytes or lines and return a manifest of them.",
+        &["path", "destination", "part_bytes", "part_lines", "name_template", "overwrite"],
+        &["path", "destination"],
+    ),
+    op(
+        "checksum_file",
+        SingleFile,
+        "Hash a file (sha256, sha512 or md5) and optionally compare it with an expected digest.",
+        &["path", "algorithm", "expected"],
+        &["path"],
+    ),
+    op("tag_path", SingleFile, "Add or remove tags on a file or directory; tags persist across sessions.", &["path", "tags", "remove_tags"], &["path"]),
+    op(
+        "annotate_path",
+        SingleFile,
+        "Attach a note to a file or directory that later sessions can read back.",
+        &["path", "note", "clear_notes"],
+        &["path"],
+    ),
+    op(
+        "read_file_lines",
+        SingleFile,
+        "Read a range of lines from a file.",
+        &["path", "offset", "limit", "from_end", "byte_offset", "with_line_numbers"],
+        &["path"],
+    ),
+    op("read_media_file", SingleFile, "Read an image or audio file as base64 data.", &["path", "max_bytes"], &["path"]),
+    op("summarize_file", SingleFile, "Summarize a file using the client's model (sampling).", &["path", "focus", "chunk_chars"], &["path"]),
+    op("download_file", SingleFile, "Download an allow-listed URL into a file.", &["path", "url", "overwrite"], &["path", "url"]),
+    // multiple_file_operations
+    op("read_multiple_files", MultipleFile, "Read several files or glob patterns at once.", &["paths", "output_format", "max_total_bytes"], &["paths"]),
+    op("read_multiple_media_files", MultipleFile, "Read several image or audio files as base64 data.", &["paths", "max_bytes"], &["paths"]),
+    op("copy_files", MultipleFile, "Copy files or directories into a destination.", &["paths", "destination"], &["paths", "destination"]),
+    op("move_files", MultipleFile, "Move or rename files or directories.", &["paths", "destination"], &["paths", "destination"]),
+    op("zip_files", MultipleFile, "Compress files into a zip archive.", &["paths", "output_path"], &["paths", "output_path"]),
+    op("unzip_file", MultipleFile, "Extract a zip archive into a directory.", &["paths", "output_path"], &["paths", "output_path"]),
+    op("zip_directory", MultipleFile, "Compress a directory into a zip archive.", &["paths", "output_path"], &["paths", "output_path"]),
+    op("batch_operations", MultipleFile, "Apply several file operations atomically. Deletes and overwrites skip the trash, backups and undo once the batch commits.", &["operations", "output_format"], &["operations"]),
+    op("run_pipeline", MultipleFile, "Run a find, filter and act pipeline over files.", &["pipeline", "dry_run", "output_format"], &["pipeline"]),
+    op("stage_files", MultipleFile, "Add files to the staging area.", &["paths", "output_format"], &["paths"]),
+    op("unstage_files", MultipleFile, "Remove files from the staging area.", &["paths"], &[]),
+    op("list_staged", MultipleFile, "List the staged files.", &["output_format"], &[]),
+    op("commit_staged", MultipleFile, "Copy or move the staged files into a destination.", &["destination", "action", "output_format"], &["destination"]),
+    op(
+        "concat_files",
+        MultipleFile,
+        "Join files or a sorted glob into one output file, written atomically.",
+        &["paths", "output_path", "separator", "overwrite", "output_format"],
+        &["paths", "output_path"],
+    ),
+    op("checksum_files", MultipleFile, "Hash several files, reporting failures per file.", &["paths", "algorithm", "output_format"], &["paths"]),
+    op(
+        "rename_files",
+        MultipleFile,
+        "Rename many files at once with a regex substitution or a name template, checking for collisions. Shows the new names unless dry_run is false.",
+        &["paths", "pattern", "replacement", "dry_run", "output_format"],
+        &["paths", "replacement"],
+    ),
+    // directory_operations
+    op("create_directory", Directory, "Create a directory and any missing parents.", &["path"], &["path"]),
+    op("list_directory", Directory, "List the entries of a directory.", &["path"], &["path"]),
+    op(
+        "directory_tree",
+        Directory,
+        "Recursive JSON tree of a directory.",
+        &["path", "max_depth", "include_hidden", "follow_symlinks", "include_defaults_excluded"],
+        &["path"],
+    ),
+    op("list_directory_with_sizes", Directory, "List a directory with file sizes and totals.", &["path"], &["path"]),
+    op(
+        "calculate_directory_size",
+        Directory,
+        "Total size of a directory tree.",
+        &["path", "output_format", "follow_symlinks", "include_defaults_excluded"],
+        &["path"],
+    ),
+    op("find_empty_directories", Directory, "Find empty directories under a path.", &["path", "exclude_patterns"], &["path"]),
+    op(
+        "find_empty_files",
+        Directory,
+        "Find empty files under a path, optionally deleting them.",
+        &["path", "exclude_patterns", "include_defaults_excluded", "whitespace_only", "cleanup", "output_format"],
+        &["path"],
+    ),
+    op("get_disk_space", Directory, "Free and total space of the filesystem holding a path.", &["path", "output_format"], &["path"]),
+    op(
+        "merge_directories",
+        Directory,
+        "Merge one directory tree into another.",
+        &["path", "destination", "conflict_strategy", "dry_run", "exclude_patterns", "include_defaults_excluded", "output_format"],
+        &["path", "destination"],
+    ),
+    op(
+        "normalize_permissions",
+        Directory,
+        "Set consistent file and directory modes under a path.",
+        &["path", "file_mode", "dir_mode", "dry_run", "exclude_patterns", "include_defaults_excluded", "output_format"],
+        &["path"],
+    ),
+    // search_and_analysis
+    op(
+        "search_files",
+        SearchAndAnalysis,
+        "Find files whose names match a pattern.",
+        &["path", "pattern", "match_mode", "include_content", "follow_symlinks", "include_defaults_excluded"],
+        &["path", "pattern"],
+    ),
+    op(
+        "search_files_content",
+        SearchAndAnalysis,
+        "Search the contents of files matching a pattern.",
+        &["path", "pattern", "query", "is_regex", "exclude_patterns", "min_bytes", "max_bytes"],
+        &["path", "pattern", "query"],
+    ),
+    op(
+        "find_duplicate_files",
+        SearchAndAnalysis,
+        "Find files with identical contents under one or more roots, optionally deduplicating them.",
+        &["path", "paths", "pattern", "exclude_patterns", "include_defaults_excluded", "min_bytes", "max_bytes", "action", "keep", "dry_run", "output_format"],
+        &["path"],
+    ),
+    op(
+        "find_stale_files",
+        SearchAndAnalysis,
+        "Find files not modified or accessed for a number of days.",
+        &["path", "older_than_days", "time_field", "pattern", "exclude_patterns", "include_defaults_excluded", "min_bytes", "max_bytes", "output_format"],
+        &["path", "older_than_days"],
+    ),
+    op(
+        "find_recent_changes",
+        SearchAndAnalysis,
+        "Find files changed recently, newest first: in the last within_hours, or between since and end.",
+        &["path", "within_hours", "since", "end", "limit", "pattern", "exclude_patterns", "include_defaults_excluded", "output_format"],
+        &["path"],
+    ),
+    op(
+        "find_recent_files",
+        SearchAndAnalysis,
+        "Another name for find_recent_changes: files modified in the last within_hours, or between since and end, newest first.",
+        &["path", "within_hours", "since", "end", "limit", "pattern", "exclude_patterns", "include_defaults_excluded", "output_format"],
+        &["path"],
+    ),
+    op(
+        "directory_profile",
+        SearchAndAnalysis,
+        "Summary of file types, sizes and ages under a path.",
+        &["path", "pattern", "exclude_patterns", "include_defaults_excluded"],
+        &["path"],
+    ),
+    op(
+        "find_files_by_size",
+        SearchAndAnalysis,
+        "Find files within a size range.",
+        &["path", "min_bytes", "max_bytes", "sort", "limit", "pattern", "exclude_patterns", "include_defaults_excluded", "output_format"],
+        &["path"],
+    ),
+    op(
+        "find_files_by_mtime",
+        SearchAndAnalysis,
+        "Find files modified within a time range.",
+        &["path", "start", "end", "limit", "pattern", "exclude_patterns", "include_defaults_excluded", "output_format"],
+        &["path"],
+    ),
+    op(
+        "audit_permissions",
+        SearchAndAnalysis,
+        "Report files with risky permissions.",
+        &["path", "exclude_patterns", "include_defaults_excluded", "output_format"],
+        &["path"],
+    ),
+    op(
+        "compare_directories",
+        SearchAndAnalysis,
+        "Compare two directory trees.",
+        &["path", "other_path", "compare_mode", "exclude_patterns", "include_defaults_excluded", "output_format"],
+        &["path", "other_path"],
+    ),
+    op(
+        "compare_files",
+        SearchAndAnalysis,
+        "Unified diff of two text files, or the first differing byte of two binary files.",
+        &["path", "other_path", "context_lines", "output_format"],
+        &["path", "other_path"],
+    ),
+    op("list_tags", SearchAndAnalysis, "List the tags in use under a directory with their counts.", &["path", "output_format"], &["path"]),
+    op(
+        "find_by_tag",
+        SearchAndAnalysis,
+        "Find tagged paths under a directory, with their tags and notes.",
+        &["path", "tags", "match_all", "output_format"],
+        &["path", "tags"],
+    ),
+    // file_management
+    op("list_allowed_directories", FileManagement, "List the directories this server may access.", &["output_format"], &[]),
+    op("delete_file", FileManagement, "Delete a file, or a directory with everything in it: into the trash unless permanent is set, or overwritten with random data first when secure is set.", &["path", "confirm", "permanent", "secure"], &["path"]),
+    op(
+        "delete_matching",
+        FileManagement,
+        "Delete the files under a directory matching a glob: list them first (dry_run, the default), then delete exactly that list with dry_run false, confirm true and the confirm_token of the preview.",
+        &["path", "pattern", "exclude_patterns", "include_defaults_excluded", "dry_run", "confirm", "confirm_token", "permanent", "secure", "output_format"],
+        &["path", "pattern"],
+    ),
+    op("apply_plan", FileManagement, "Apply a plan produced by a dry run.", &["plan", "output_format"], &["plan"]),
+    op("diff_sandbox", FileManagement, "Show the changes pending in --sandbox mode.", &["output_format"], &[]),
+    op("commit_sandbox", FileManagement, "Write pending --sandbox changes to disk.", &["paths", "output_format"], &[]),
+    op(
+        "acquire_lock",
+        FileManagement,
+        "Take or renew an advisory lock on a path so other agents know it is being edited.",
+        &["path", "ttl_seconds", "owner", "output_format"],
+        &["path"],
+    ),
+    op("release_lock", FileManagement, "Release an advisory lock taken with acquire_lock.", &["path", "force"], &["path"]),
+    op(
+        "create_temp_file",
+        FileManagement,
+        "Create a uniquely named scratch file that is cleaned up automatically.",
+        &["prefix", "suffix", "content"],
+        &[],
+    ),
+    op("create_temp_dir", FileManagement, "Create a uniquely named scratch directory that is cleaned up automatically.", &["prefix"], &[]),
+    op("create_symlink", FileManagement, "Create a symbolic link at path pointing at target.", &["path", "target"], &["path", "target"]),
+    op("create_hardlink", FileManagement, "Give the existing file target a second name at path.", &["path", "target"], &["path", "target"]),
+    op(
+        "start_watch",
+        FileManagement,
+        "Start recording create, modify, delete and rename events at a path, e.g. to see what a build writes. Returns a watch_id for poll_watch.",
+        &["path", "recursive"],
+        &["path"],
+    ),
+    op("poll_watch", FileManagement, "Return the changes a watch recorded since the last poll.", &["watch_id", "output_format"], &["watch_id"]),
+    op("stop_watch", FileManagement, "Stop a watch started with start_watch.", &["watch_id"], &["watch_id"]),
+];
+
+fn find(name: &str) -> Option<&'static FlatOperation> {
+    FLAT_OPERATIONS.iter().find(|operation| operation.name == name)
+}
+
+impl FlatOperation {
+    fn tool_definition(&self) -> Tool {
+        let grouped = self.group.tool_definition();
+        let properties = &grouped.input_schema["properties"];
+        let selected: Map<String, Value> = self
+            .params
+            .iter()
+            .filter_map(|param| Some((param.to_string(), properties.get(*param)?.clone())))
+            .collect();
+        Tool {
+            name: self.name.to_string(),
+            description: Some(self.description.to_string()),
+            input_schema: json!({
+                "type": "object",
+                "properties": selected,
+                "required": self.required,
+            }),
+            output_schema: output_schema(self.name),
+        }
+    }
+}
+
+/// One tool per grouped-tool operation, named after the operation
+pub fn flat_tool_definitions() -> Vec<Tool> {
+    FLAT_OPERATIONS.iter().map(FlatOperation::tool_definition).collect()
+}
+
+/// Whether a call of `operation` (a flat tool name, or a grouped tool's
+/// `operation`) with `arguments` can change files, for --readonly
+pub fn operation_changes_files(operation: &str, arguments: &Value) -> bool {
+    let flag = |key: &str| arguments.get(key).and_then(Value::as_bool);
+    match operation {
+        "write_file" | "touch_file" | "split_file" | "tag_path" | "annotate_path" | "download_file" | "copy_files" | "move_files"
+        | "zip_files" | "unzip_file" | "zip_directory" | "batch_operations" | "commit_staged" | "concat_files"
+        | "create_directory" | "delete_file" | "apply_plan" | "commit_sandbox" | "acquire_lock"
+        | "create_temp_file" | "create_temp_dir" | "create_symlink" | "create_hardlink" | "undo_last_operation" | "undo_to_step" => true,
+        "edit_file" | "apply_patch" | "run_pipeline" => flag("dry_run") != Some(true),
+        // These only report unless dry_run is explicitly false
+        "merge_directories" | "normalize_permissions" | "delete_matching" | "rename_files" => flag("dry_run") == Some(false),
+        "find_duplicate_files" => arguments.get("action").is_some_and(|action| !action.is_null()) && flag("dry_run") == Some(false),
+        "find_empty_files" => flag("cleanup") == Some(true),
+        _ => false,
+    }
+}
+
+/// A call to a flat tool, parsed into the grouped tool it belongs to
+#[derive(Debug, Clone)]
+pub enum FlatToolCall {
+    SingleFile(Box<SingleFileOperationsTool>),
+    MultipleFile(Box<MultipleFileOperationsTool>),
+    Directory(Box<DirectoryOperationsTool>),
+    SearchAndAnalysis(Box<SearchAndAnalysisTool>),
+    FileManagement(Box<FileManagementTool>),
+}
+
+impl FlatToolCall {
+    /// Parse a call to the flat tool `name`, or None when there is no such tool
+    pub fn parse(name: &str, arguments: Option<Value>) -> Option<Result<Self, String>> {
+        let operation = find(name)?;
+        let mut arguments = match arguments {
+            None | Some(Value::Null) => Map::new(),
+            Some(Value::Object(map)) => map,
+            Some(_) => return Some(Err("tool arguments must be an object".to_string())),
+        };
+        arguments.insert("operation".to_string(), Value::String(name.to_string()));
+        let arguments = Value::Object(arguments);
+        fn parse<T: serde::de::DeserializeOwned>(arguments: Value) -> Result<Box<T>, String> {
+            serde_json::from_value(arguments).map_err(|e| e.to_string())
+        }
+        Some(match operation.group {
+            SingleFile => parse(arguments).map(Self::SingleFile),
+            MultipleFile => parse(arguments).map(Self::MultipleFile),
+            Directory => parse(arguments).map(Self::Directory),
+            SearchAndAnalysis => parse(arguments).map(Self::SearchAndAnalysis),
+            FileManagement => parse(arguments).map(Self::FileManagement),
+        })
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService, client: &ClientPeer) -> Result<CallToolResult, CallToolError> {
+        match self {
+            Self::SingleFile(tool) => tool.run_operation(fs_service, client).await,
+            Self::MultipleFile(tool) => tool.run_operation(fs_service).await,
+            Self::Directory(tool) => tool.run_operation(fs_service).await,
+            Self::SearchAndAnalysis(tool) => tool.run_operation(fs_service).await,
+            Self::FileManagement(tool) => tool.run_operation(fs_service).await,
+        }
+    }
+}
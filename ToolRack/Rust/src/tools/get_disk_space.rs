@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{FileSystemService, utils::format_bytes};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetDiskSpace {
+    pub path: String,
+    pub output_format: Option<String>,
+}
+
+impl GetDiskSpace {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let space = fs_service
+            .get_disk_space(Path::new(&self.path))
+            .await
+            .map_err(CallToolError::new)?;
+
+        let format_size = |bytes: u64| match self.output_format.as_deref().unwrap_or("human-readable") {
+            "bytes" => format!("{bytes}"),
+            _ => format_bytes(bytes),
+        };
+
+        let output_content = format!(
+            "Disk space for filesystem containing: {}\nTotal: {}\nUsed: {} ({:.1}%)\nAvailable: {}\n",
+            space.queried_path.display(),
+            format_size(space.total),
+            format_size(space.used()),
+            space.used_percent(),
+            format_size(space.available),
+        );
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: output_content,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
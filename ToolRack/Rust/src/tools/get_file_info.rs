@@ -1,22 +1,86 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::{checksum::HashAlgorithm, file_info::ownership, FileSystemService};
 use crate::fs_service::utils::{format_bytes, format_system_time, format_permissions};
+use serde_json::{json, Value};
 use std::path::Path;
+use std::time::SystemTime;
+
+fn rfc3339(time: Option<SystemTime>) -> Option<String> {
+    time.map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetFileInfoTool {
     pub path: String,
+    /// Report on the symlink target instead of the link itself
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// Count the lines of a text file
+    #[serde(default)]
+    pub with_line_count: bool,
+    /// Hash a file's content with `algorithm`
+    #[serde(default)]
+    pub with_hash: bool,
+    #[serde(default)]
+    pub algorithm: HashAlgorithm,
+    /// Owner and group (Unix)
+    #[serde(default)]
+    pub with_owner: bool,
 }
 
 impl GetFileInfoTool {
-    
+    /// Schema of the `structuredContent` returned next to the text report
+    pub fn output_schema() -> Value {
+        let time = json!({ "type": ["string", "null"], "description": "RFC 3339, UTC" });
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "type": { "type": "string", "enum": ["file", "directory", "symlink"] },
+                "size": { "type": "integer" },
+                "permissions": { "type": "string" },
+                "is_symlink": { "type": "boolean" },
+                "link_target": { "type": ["string", "null"], "description": "The symlink's target as stored, possibly relative to its directory" },
+                "target_within_allowed": { "type": ["boolean", "null"], "description": "Whether the symlink leads inside the allowed directories" },
+                "created": time,
+                "modified": time,
+                "accessed": time,
+                "mime_type": { "type": ["string", "null"] },
+                "is_text": { "type": ["boolean", "null"] },
+                "line_count": { "type": ["integer", "null"], "description": "With with_line_count, for text files" },
+                "hash": {
+                    "type": ["object", "null"],
+                    "properties": { "algorithm": { "type": "string" }, "digest": { "type": "string" } },
+                    "description": "With with_hash, for files"
+                },
+                "owner": {
+                    "type": ["object", "null"],
+                    "properties": {
+                        "uid": { "type": "integer" },
+                        "gid": { "type": "integer" },
+                        "user": { "type": ["string", "null"] },
+                        "group": { "type": ["string", "null"] }
+                    },
+                    "description": "With with_owner, on Unix"
+                }
+            },
+            "required": ["path", "type", "size", "permissions"]
+        })
+    }
 
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
-        match fs_service.get_file_stats(Path::new(&self.path)).await {
+        match fs_service.get_file_stats(Path::new(&self.path), self.follow_symlinks).await {
             Ok(file_info) => {
+                let kind = if file_info.is_symlink {
+                    "Symlink"
+                } else if file_info.is_directory {
+                    "Directory"
+                } else {
+                    "File"
+                };
                 let mut info_text = format!("File Information for: {}\n", self.path);
-                info_text.push_str(&format!("Type: {}\n", if file_info.is_directory { "Directory" } else { "File" }));
+                info_text.push_str(&format!("Type: {}\n", kind));
                 info_text.push_str(&format!("Size: {} ({})\n", format_bytes(file_info.size), file_info.size));
                 info_text.push_str(&format!("Permissions: {}\n", format_permissions(&file_info.metadata)));
 
@@ -29,12 +93,93 @@ impl GetFileInfoTool {
                 if let Some(accessed) = file_info.accessed {
                     info_text.push_str(&format!("Accessed: {}\n", format_system_time(accessed)));
                 }
+                if let Some(link) = &file_info.link_target {
+                    info_text.push_str(&format!(
+                        "Link target: {} ({})\n",
+                        link.target.display(),
+                        if link.within_allowed { "inside the allowed directories" } else { "outside the allowed directories" }
+                    ));
+                }
+
+                // A link's content is sniffed only when it leads somewhere allowed
+                // that exists; otherwise the link itself is all there is to report.
+                // Special files are never opened, and a file that cannot be read
+                // still gets the rest of its info, with a note instead of a MIME type.
+                let content_type = match &file_info.link_target {
+                    Some(link) if link.within_allowed => {
+                        fs_service.get_content_type(Path::new(&self.path)).await.ok().flatten()
+                    }
+                    Some(_) => None,
+                    None if file_info.is_symlink || !file_info.metadata.is_file() => None,
+                    None => match fs_service.get_content_type(Path::new(&self.path)).await {
+                        Ok(content_type) => content_type,
+                        Err(e) => {
+                            info_text.push_str(&format!("MIME Type: not detected, the content could not be read ({})\n", e));
+                            None
+                        }
+                    },
+                };
+                // The extras read the content, so they are left out for links
+                // whose target was not sniffed
+                let is_text = content_type.as_ref().is_some_and(|content_type| content_type.is_text);
+                let readable = content_type.is_some();
+                let line_count = if self.with_line_count && is_text {
+                    Some(fs_service.line_count(Path::new(&self.path)).await.map_err(CallToolError::new)?)
+                } else {
+                    None
+                };
+                let hash = if self.with_hash && readable {
+                    Some(fs_service.checksum(Path::new(&self.path), self.algorithm).await.map_err(CallToolError::new)?)
+                } else {
+                    None
+                };
+                let owner = if self.with_owner { ownership(&file_info.metadata) } else { None };
+
+                let structured = json!({
+                    "path": self.path,
+                    "type": kind.to_lowercase(),
+                    "size": file_info.size,
+                    "permissions": format_permissions(&file_info.metadata),
+                    "is_symlink": file_info.is_symlink,
+                    "link_target": file_info.link_target.as_ref().map(|link| link.target.display().to_string()),
+                    "target_within_allowed": file_info.link_target.as_ref().map(|link| link.within_allowed),
+                    "created": rfc3339(file_info.created),
+                    "modified": rfc3339(file_info.modified),
+                    "accessed": rfc3339(file_info.accessed),
+                    "mime_type": content_type.as_ref().map(|content_type| &content_type.mime_type),
+                    "is_text": content_type.as_ref().map(|content_type| content_type.is_text),
+                    "line_count": line_count,
+                    "hash": hash.as_ref().map(|digest| json!({ "algorithm": self.algorithm.as_str(), "digest": digest })),
+                    "owner": owner,
+                });
+                if let Some(content_type) = content_type {
+                    info_text.push_str(&format!(
+                        "MIME Type: {} (detected by {})\n",
+                        content_type.mime_type,
+                        content_type.source.as_str()
+                    ));
+                    info_text.push_str(&format!(
+                        "Content: {}\n",
+                        if content_type.is_text { "text" } else { "binary" }
+                    ));
+                    info_text.push_str(&format!("Suggested reader: {}\n", content_type.suggested_reader()));
+                }
+                if let Some(line_count) = line_count {
+                    info_text.push_str(&format!("Lines: {}\n", line_count));
+                }
+                if let Some(digest) = &hash {
+                    info_text.push_str(&format!("{}: {}\n", self.algorithm.as_str().to_uppercase(), digest));
+                }
+                if let Some(owner) = &owner {
+                    info_text.push_str(&format!("Owner: {}\n", owner));
+                }
 
                 Ok(CallToolResult {
                     content: vec![Content::Text(TextContent {
                         text: info_text,
                     })],
                     is_error: Some(false),
+                    structured_content: Some(structured),
                 })
             },
             Err(e) => Err(CallToolError::new(e)),
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{Tool, CallToolResult, Content, TextContent, CallToolError};
+use crate::profiler::{clear_slow_operations, slow_operations, slow_threshold, SLOW_LOG_CAPACITY};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetSlowOperationsTool {
+    /// Most recent entries to return (default 20)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Empty the log after reading it
+    #[serde(default)]
+    pub clear: bool,
+    #[serde(default)]
+    pub output_format: Option<String>,
+}
+
+impl GetSlowOperationsTool {
+    pub fn tool_definition() -> Tool {
+        Tool {
+            name: "get_slow_operations".to_string(),
+            description: Some("List recent tool calls that exceeded the slow-operation threshold, with time split between path validation, filesystem work and response formatting. Use it to find out why calls feel sluggish.".to_string()),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "limit": {
+                        "type": "number",
+                        "description": format!("Most recent entries to return (default 20, at most {} are kept)", SLOW_LOG_CAPACITY)
+                    },
+                    "clear": {
+                        "type": "boolean",
+                        "description": "Empty the log after reading it",
+                        "default": false
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["text", "json"],
+                        "default": "text"
+                    }
+                }
+            }),
+            output_schema: None,
+        }
+    }
+
+    pub async fn run_tool(self) -> Result<CallToolResult, CallToolError> {
+        let operations = slow_operations(self.limit.unwrap_or(20));
+        let threshold_ms = slow_threshold().as_millis();
+
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&serde_json::json!({
+                "threshold_ms": threshold_ms,
+                "operations": operations,
+            }))
+            .map_err(CallToolError::new)?,
+            _ if operations.is_empty() => format!("No tool calls have taken longer than {} ms.", threshold_ms),
+            _ => {
+                let mut output = format!("{} slow call(s) (threshold {} ms), newest first:\n", operations.len(), threshold_ms);
+                for op in &operations {
+                    let name = match &op.operation {
+                        Some(operation) => format!("{}.{}", op.tool, operation),
+                        None => op.tool.clone(),
+                    };
+                    writeln!(
+                        output,
+                        "  {}  {}{}  {:.0} ms (validation {:.1}, io {:.1}, formatting {:.1}; {} bytes){}",
+                        op.started_at.format("%H:%M:%S"),
+                        name,
+                        op.path.as_deref().map(|p| format!(" {}", p)).unwrap_or_default(),
+                        op.total_ms,
+                        op.validation_ms,
+                        op.io_ms,
+                        op.formatting_ms,
+                        op.response_bytes,
+                        if op.is_error { " [error]" } else { "" }
+                    )
+                    .map_err(CallToolError::new)?;
+                }
+                output
+            }
+        };
+
+        if self.clear {
+            clear_slow_operations();
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text: content })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
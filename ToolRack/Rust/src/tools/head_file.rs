@@ -23,6 +23,7 @@ impl HeadFile {
                 text: result,
             })],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    hexdump::{format_hexdump, DEFAULT_BYTES_PER_LINE, DEFAULT_HEXDUMP_BYTES, HEXDUMP_MAX_BYTES},
+    FileSystemService,
+};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HexdumpFile {
+    pub path: String,
+    /// First byte to show, or with `from_end` how far before the end to stop
+    pub byte_offset: u64,
+    pub from_end: bool,
+    /// Bytes to show (default 256, at most 64 KiB)
+    pub length: Option<u64>,
+    pub bytes_per_line: Option<usize>,
+    /// Show the printable characters next to the hex (default true)
+    pub ascii: Option<bool>,
+}
+
+impl HexdumpFile {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let length = self.length.unwrap_or(DEFAULT_HEXDUMP_BYTES).min(HEXDUMP_MAX_BYTES);
+        let window = fs_service
+            .read_byte_window(Path::new(&self.path), self.byte_offset, self.from_end, length)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let mut text = if window.bytes.is_empty() {
+            format!("{}: no bytes at offset {} ({} bytes in file)\n", self.path, window.start_byte, window.file_size)
+        } else {
+            format!(
+                "{}: bytes {}-{} of {}\n",
+                self.path,
+                window.start_byte,
+                window.end_byte() - 1,
+                window.file_size
+            )
+        };
+        text.push_str(&format_hexdump(
+            &window,
+            self.bytes_per_line.unwrap_or(DEFAULT_BYTES_PER_LINE),
+            self.ascii.unwrap_or(true),
+        ));
+        if !window.reached_eof() {
+            text.push_str(&format!("More data follows; continue with byte_offset: {}\n", window.end_byte()));
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
@@ -3,16 +3,28 @@ use crate::mcp_types::{CallToolResult, CallToolError};
 use crate::fs_service::FileSystemService;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ListAllowedDirectoriesTool {}
+pub struct ListAllowedDirectoriesTool {
+    /// "json" returns the roots (with file:// URIs) and blocked directories
+    pub output_format: Option<String>,
+}
 
 impl ListAllowedDirectoriesTool {
     
 
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        if self.output_format.as_deref() == Some("json") {
+            let report = serde_json::to_string_pretty(&fs_service.roots()).map_err(CallToolError::new)?;
+            return Ok(CallToolResult {
+                content: vec![crate::mcp_types::Content::Text(crate::mcp_types::TextContent { text: report })],
+                is_error: Some(false),
+                structured_content: None,
+            });
+        }
         let directories: Vec<String> = fs_service
             .allowed_directories()
             .iter()
             .map(|path| path.display().to_string())
+            .chain(fs_service.roots().allowed_patterns)
             .collect();
 
         Ok(CallToolResult {
@@ -20,6 +32,7 @@ impl ListAllowedDirectoriesTool {
                 text: directories.join("\n"),
             })],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
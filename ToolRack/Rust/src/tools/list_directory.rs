@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::ops::FileSystemOps;
 use crate::fs_service::utils::format_bytes;
 use crate::retry::retry_3x;
 use std::path::Path;
@@ -14,9 +14,7 @@ pub struct ListDirectoryTool {
 }
 
 impl ListDirectoryTool {
-
-
-    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+    pub async fn run_tool(self, fs_service: &dyn FileSystemOps) -> Result<CallToolResult, CallToolError> {
         let show_detailed = self.detailed.unwrap_or(false);
 
         // Retry up to 3 times on transient I/O errors
@@ -34,36 +32,26 @@ impl ListDirectoryTool {
                             text: "Directory is empty".to_string(),
                         })],
                         is_error: Some(false),
+                        structured_content: None,
                     });
                 }
 
-                let mut output = Vec::new();
-
-                for entry in entries {
-                    let file_name = entry.file_name().to_string_lossy().to_string();
-
-                    if show_detailed {
-                        if let Ok(metadata) = entry.metadata().await {
-                            let file_type = if metadata.is_dir() { "DIR " } else { "FILE" };
-                            let size = if metadata.is_file() {
-                                format!(" ({}) ", format_bytes(metadata.len()))
-                            } else {
-                                " ".to_string()
-                            };
-                            output.push(format!("{}{}{}", file_type, size, file_name));
-                        } else {
-                            output.push(format!("???? {}", file_name));
-                        }
-                    } else {
-                        output.push(file_name);
-                    }
-                }
+                let output: Vec<String> = entries
+                    .iter()
+                    .map(|entry| match (show_detailed, entry.is_dir, entry.size) {
+                        (false, _, _) => entry.name.clone(),
+                        (true, true, _) => format!("DIR  {}", entry.name),
+                        (true, false, Some(size)) => format!("FILE ({}) {}", format_bytes(size), entry.name),
+                        (true, false, None) => format!("FILE {}", entry.name),
+                    })
+                    .collect();
 
                 Ok(CallToolResult {
                     content: vec![Content::Text(TextContent {
                         text: output.join("\n"),
                     })],
                     is_error: Some(false),
+                    structured_content: None,
                 })
             },
             Err(e) => Err(CallToolError::new(e)),
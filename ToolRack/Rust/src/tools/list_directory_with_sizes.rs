@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::{FileSystemService, utils::format_bytes};
+use crate::fs_service::{FileSystemService, utils::{format_bytes, name_text}};
+use serde_json::{json, Value};
 use std::fmt::Write;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,25 +10,51 @@ pub struct ListDirectoryWithSizes {
 }
 
 impl ListDirectoryWithSizes {
-    
+    /// Schema of the `structuredContent` returned next to the text listing
+    pub fn output_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "entries": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "name": { "type": "string" },
+                            "type": { "type": "string", "enum": ["file", "directory"] },
+                            "size": { "type": "integer", "description": "Bytes; files only" }
+                        },
+                        "required": ["name", "type"]
+                    }
+                },
+                "files": { "type": "integer" },
+                "directories": { "type": "integer" },
+                "total_size": { "type": "integer" }
+            },
+            "required": ["path", "entries", "files", "directories", "total_size"]
+        })
+    }
 
+    // The text listing, and the same listing as structured content
     async fn format_directory_entries(
         &self,
         mut entries: Vec<tokio::fs::DirEntry>,
-    ) -> Result<String, String> {
+    ) -> Result<(String, Value), String> {
         let mut file_count = 0;
         let mut dir_count = 0;
         let mut total_size: u64 = 0;
         // Estimate initial capacity: assume ~50 bytes per entry + summary
         let mut output = String::with_capacity(entries.len() * 50 + 120);
+        let mut listed = Vec::with_capacity(entries.len());
         // Sort entries by file name
         entries.sort_by_key(|a| a.file_name());
         // build the output string
         for entry in &entries {
-            let file_name = entry.file_name();
-            let file_name = file_name.to_string_lossy();
+            let file_name = name_text(&entry.path());
             if entry.path().is_dir() {
                 writeln!(output, "[DIR]  {file_name:<30}").map_err(|e| e.to_string())?;
+                listed.push(json!({ "name": file_name, "type": "directory" }));
                 dir_count += 1;
             } else if entry.path().is_file() {
                 let metadata = entry.metadata().await.map_err(|e| e.to_string())?;
@@ -39,6 +66,7 @@ impl ListDirectoryWithSizes {
                     format_bytes(file_size)
                 )
                 .map_err(|e| e.to_string())?;
+                listed.push(json!({ "name": file_name, "type": "file", "size": file_size }));
                 file_count += 1;
                 total_size += file_size;
             }
@@ -50,7 +78,14 @@ impl ListDirectoryWithSizes {
         )
         .map_err(|e| e.to_string())?;
         writeln!(output, "Total size: {}", format_bytes(total_size)).map_err(|e| e.to_string())?;
-        Ok(output)
+        let structured = json!({
+            "path": self.path,
+            "entries": listed,
+            "files": file_count,
+            "directories": dir_count,
+            "total_size": total_size,
+        });
+        Ok((output, structured))
     }
 
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
@@ -59,7 +94,7 @@ impl ListDirectoryWithSizes {
             .await
             .map_err(CallToolError::new)?;
 
-        let output = self
+        let (output, structured) = self
             .format_directory_entries(entries)
             .await
             .map_err(CallToolError::new)?;
@@ -69,6 +104,7 @@ impl ListDirectoryWithSizes {
                 text: output,
             })],
             is_error: Some(false),
+            structured_content: Some(structured),
         })
     }
 }
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::FileSystemService;
+use std::path::Path;
+
+fn text_result(text: String) -> CallToolResult {
+    CallToolResult {
+        content: vec![Content::Text(TextContent { text })],
+        is_error: Some(false),
+        structured_content: None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcquireLockTool {
+    pub path: String,
+    /// Seconds until the lock goes stale unless renewed (default 300)
+    pub ttl_seconds: Option<u64>,
+    /// Shown to whoever finds the path locked
+    pub owner: Option<String>,
+    pub output_format: Option<String>,
+}
+
+impl AcquireLockTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let grant = fs_service
+            .acquire_lock(Path::new(&self.path), self.owner.as_deref(), self.ttl_seconds)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&grant).map_err(CallToolError::new)?,
+            _ => {
+                let mut output = format!(
+                    "{} {} for {} until {} (lock file {})\n",
+                    if grant.renewed { "Renewed the lock on" } else { "Locked" },
+                    grant.lock.path,
+                    grant.lock.owner,
+                    grant.lock.expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+                    grant.lock_file
+                );
+                if let Some(stale) = &grant.replaced_stale {
+                    output.push_str(&format!(
+                        "Took over a stale lock held by {} that expired at {}\n",
+                        stale.owner,
+                        stale.expires_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+                    ));
+                }
+                output
+            }
+        };
+        Ok(text_result(content))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReleaseLockTool {
+    pub path: String,
+    /// Break a lock held by another session
+    pub force: bool,
+}
+
+impl ReleaseLockTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let released = fs_service
+            .release_lock(Path::new(&self.path), self.force)
+            .await
+            .map_err(CallToolError::new)?;
+        Ok(text_result(match released {
+            Some(lock) => format!("Released the lock on {} held by {}", lock.path, lock.owner),
+            None => format!("{} was not locked", self.path),
+        }))
+    }
+}
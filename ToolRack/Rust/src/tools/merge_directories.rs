@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    merge::{ConflictStrategy, MergeAction, MergePlan},
+    plan::{Plan, PlanDocument},
+    utils::format_bytes,
+    FileSystemService, StepResult,
+};
+use std::{fmt::Write, path::Path};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeDirectories {
+    pub source: String,
+    pub destination: String,
+    /// "keep_newest", "keep_largest", "rename_with_suffix" or "skip" (default)
+    #[serde(default)]
+    pub conflict_strategy: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    /// Only show the plan unless this is explicitly false.
+    /// `output_format: "plan"` never applies; it emits a document for `apply_plan`.
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    pub output_format: Option<String>,
+}
+
+impl MergeDirectories {
+    fn format_plan(plan: &MergePlan, applied: Option<&[StepResult]>, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(&json!({
+                "dry_run": applied.is_none(),
+                "plan": plan,
+                "results": applied,
+            }))
+            .map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let heading = if applied.is_some() { "Merged" } else { "Dry-run plan for merging" };
+                writeln!(
+                    output,
+                    "{} {} into {} ({:?} on conflict): {} directories to create, {} new files, {} overwrites, {} renamed copies, {} skipped; {} to copy",
+                    heading,
+                    plan.source_root,
+                    plan.destination_root,
+                    plan.strategy,
+                    plan.count(MergeAction::CreateDirectory),
+                    plan.count(MergeAction::Copy),
+                    plan.count(MergeAction::Overwrite),
+                    plan.count(MergeAction::CopyRenamed),
+                    plan.count(MergeAction::Skip),
+                    format_bytes(plan.bytes_to_copy)
+                )
+                .map_err(|e| e.to_string())?;
+                for step in &plan.steps {
+                    writeln!(output, "  {:?}: {} -> {} ({})", step.action, step.source, step.destination, step.reason)
+                        .map_err(|e| e.to_string())?;
+                }
+                match applied {
+                    Some(results) => {
+                        let failed = results.iter().filter(|r| !r.ok).count();
+                        writeln!(output, "\nResults: {} succeeded, {} failed", results.len() - failed, failed)
+                            .map_err(|e| e.to_string())?;
+                        for result in results.iter().filter(|r| !r.ok) {
+                            writeln!(output, "  [FAILED] {}: {}", result.path, result.message)
+                                .map_err(|e| e.to_string())?;
+                        }
+                    }
+                    None => output.push_str("\nNo files were changed. Re-run with dry_run: false to apply this plan.\n"),
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let strategy: ConflictStrategy = match self.conflict_strategy.as_deref() {
+            Some(strategy) => strategy.parse().map_err(CallToolError::new)?,
+            None => ConflictStrategy::default(),
+        };
+
+        let plan = fs_service
+            .plan_merge(
+                Path::new(&self.source),
+                Path::new(&self.destination),
+                &self.exclude_patterns.unwrap_or_default(),
+                self.include_defaults_excluded,
+                strategy,
+            )
+            .await
+            .map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let (content, is_error) = if output_format == "plan" {
+            (
+                serde_json::to_string_pretty(&PlanDocument::new(Plan::Merge(plan))).map_err(|e| e.to_string()),
+                false,
+            )
+        } else if self.dry_run == Some(false) {
+            let results = fs_service.apply_merge_plan(&plan).await;
+            let is_error = results.iter().any(|r| !r.ok);
+            (Self::format_plan(&plan, Some(&results), output_format), is_error)
+        } else {
+            (Self::format_plan(&plan, None, output_format), false)
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content.map_err(CallToolError::new)?,
+            })],
+            is_error: Some(is_error),
+            structured_content: None,
+        })
+    }
+}
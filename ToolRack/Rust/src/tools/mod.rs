@@ -20,13 +20,47 @@ pub mod delete_file;
 pub mod calculate_directory_size;
 pub mod find_duplicate_files;
 pub mod find_empty_directories;
+pub mod find_empty_files;
+pub mod find_stale_files;
+pub mod find_recent_changes;
+pub mod directory_profile;
+pub mod find_files_by_size;
+pub mod find_files_by_mtime;
+pub mod audit_permissions;
+pub mod compare_directories;
+pub mod merge_directories;
+pub mod apply_plan;
+pub mod normalize_permissions;
+pub mod batch_operations;
+pub mod run_pipeline;
+pub mod staging;
 pub mod head_file;
 pub mod list_directory_with_sizes;
 pub mod read_file_lines;
 pub mod read_media_file;
+pub mod summarize_file;
+pub mod download_file;
+pub mod diff_sandbox;
+pub mod commit_sandbox;
 pub mod read_multiple_media_files;
 pub mod search_files_content;
 pub mod tail_file;
+pub mod get_disk_space;
+pub mod hexdump_file;
+pub mod split_file;
+pub mod concat_files;
+pub mod locks;
+pub mod temp_files;
+pub mod tags;
+pub mod checksum;
+pub mod compare_files;
+pub mod apply_patch;
+pub mod undo;
+pub mod delete_matching;
+pub mod rename_files;
+pub mod touch_file;
+pub mod create_link;
+pub mod watch_path;
 
 // Dynamic operation mode tools
 pub mod single_file_operations;
@@ -35,11 +69,16 @@ pub mod directory_operations;
 pub mod search_and_analysis;
 pub mod file_management;
 pub mod operation_mode_management;
+pub mod get_slow_operations;
+pub mod server_stats;
+pub mod set_dry_run;
+pub mod reload_policy;
+pub mod flat_tools;
 
 // Note: task_state is accessed directly from crate root
 
 // Individual tool structs (kept for implementation but not exposed)
-pub use edit_operation::EditOperation;
+pub use edit_operation::{EditKind, EditOperation};
 pub use directory_tree::DirectoryTreeTool;
 pub use list_allowed_directories::ListAllowedDirectoriesTool;
 pub use read_file::ReadFileTool;
@@ -60,13 +99,41 @@ pub use delete_file::DeleteFileTool;
 pub use calculate_directory_size::CalculateDirectorySize;
 pub use find_duplicate_files::FindDuplicateFiles;
 pub use find_empty_directories::FindEmptyDirectories;
+pub use find_empty_files::FindEmptyFiles;
+pub use find_stale_files::FindStaleFiles;
+pub use find_recent_changes::FindRecentChanges;
+pub use directory_profile::DirectoryProfileTool;
+pub use find_files_by_size::FindFilesBySize;
+pub use find_files_by_mtime::FindFilesByMtime;
+pub use audit_permissions::AuditPermissions;
+pub use compare_directories::CompareDirectories;
+pub use merge_directories::MergeDirectories;
+pub use apply_plan::ApplyPlan;
+pub use normalize_permissions::NormalizePermissions;
+pub use batch_operations::BatchOperationsTool;
+pub use run_pipeline::RunPipelineTool;
+pub use staging::{CommitStagedTool, ListStagedTool, StageFilesTool, UnstageFilesTool};
 pub use head_file::HeadFile;
 pub use list_directory_with_sizes::ListDirectoryWithSizes;
 pub use read_file_lines::ReadFileLines;
 pub use read_media_file::ReadMediaFile;
+pub use summarize_file::SummarizeFile;
+pub use download_file::DownloadFile;
+pub use diff_sandbox::DiffSandbox;
+pub use commit_sandbox::CommitSandbox;
 pub use read_multiple_media_files::ReadMultipleMediaFiles;
 pub use search_files_content::SearchFilesContent;
 pub use tail_file::TailFile;
+pub use get_disk_space::GetDiskSpace;
+pub use hexdump_file::HexdumpFile;
+pub use split_file::SplitFile;
+pub use concat_files::ConcatFilesTool;
+pub use locks::{AcquireLockTool, ReleaseLockTool};
+pub use temp_files::{CreateTempDirTool, CreateTempFileTool};
+pub use tags::{AnnotatePathTool, FindByTagTool, ListTagsTool, TagPathTool};
+pub use checksum::{ChecksumFileTool, ChecksumFilesTool};
+pub use compare_files::CompareFiles;
+pub use apply_patch::ApplyPatchTool;
 
 // Dynamic operation mode tools
 pub use single_file_operations::SingleFileOperationsTool;
@@ -77,38 +144,89 @@ pub use file_management::FileManagementTool;
 
 // Operation mode management tools
 pub use operation_mode_management::{StartOperationModeTool, CompleteCurrentModeTool, ListAvailableModesTool, GetCurrentModeStatusTool};
+pub use get_slow_operations::GetSlowOperationsTool;
+pub use server_stats::ServerStatsTool;
+pub use set_dry_run::{DryRunMode, SetDryRunTool};
+pub use reload_policy::ReloadPolicyTool;
+pub use undo::{undo_metadata, UndoLastOperationTool, UndoToStepTool};
+pub use delete_matching::DeleteMatchingTool;
+pub use rename_files::RenameFilesTool;
+pub use touch_file::TouchFileTool;
+pub use create_link::CreateLinkTool;
+pub use watch_path::{PollWatchTool, StartWatchTool, StopWatchTool};
+pub use flat_tools::{operation_changes_files, FlatToolCall, ToolStyle};
 
 use crate::mcp_types::*;
 
-// Enum for dynamic operation mode tools (only these are exposed to clients)
+// Enum for dynamic operation mode tools (only these are exposed to clients).
+// The grouped tools carry every operation's parameters, so they are boxed.
 #[derive(Debug, Clone)]
 pub enum FileSystemTools {
-    SingleFileOperationsTool(SingleFileOperationsTool),
-    MultipleFileOperationsTool(MultipleFileOperationsTool),
-    DirectoryOperationsTool(DirectoryOperationsTool),
-    SearchAndAnalysisTool(SearchAndAnalysisTool),
-    FileManagementTool(FileManagementTool),
+    SingleFileOperationsTool(Box<SingleFileOperationsTool>),
+    MultipleFileOperationsTool(Box<MultipleFileOperationsTool>),
+    DirectoryOperationsTool(Box<DirectoryOperationsTool>),
+    SearchAndAnalysisTool(Box<SearchAndAnalysisTool>),
+    FileManagementTool(Box<FileManagementTool>),
     // Operation mode management tools
     StartOperationMode(StartOperationModeTool),
     CompleteCurrentMode(CompleteCurrentModeTool),
     ListAvailableModes(ListAvailableModesTool),
     GetCurrentModeStatus(GetCurrentModeStatusTool),
+    // Diagnostics
+    GetSlowOperations(GetSlowOperationsTool),
+    ServerStats(ServerStatsTool),
+    // Administration
+    SetDryRun(SetDryRunTool),
+    ReloadPolicy(ReloadPolicyTool),
+    UndoLastOperation(UndoLastOperationTool),
+    UndoToStep(UndoToStepTool),
+    // One operation of a grouped tool, called as a tool of its own (--tool-style flat)
+    Flat(FlatToolCall),
 }
 
 impl FileSystemTools {
-    pub fn tools() -> Vec<Tool> {
-        vec![
-            SingleFileOperationsTool::tool_definition(),
-            MultipleFileOperationsTool::tool_definition(),
-            DirectoryOperationsTool::tool_definition(),
-            SearchAndAnalysisTool::tool_definition(),
-            FileManagementTool::tool_definition(),
-            // Operation mode management tools
-            StartOperationModeTool::tool_definition(),
-            CompleteCurrentModeTool::tool_definition(),
-            ListAvailableModesTool::tool_definition(),
-            GetCurrentModeStatusTool::tool_definition(),
-        ]
+    pub fn tools(style: ToolStyle) -> Vec<Tool> {
+        let mut tools = Vec::new();
+        if style.includes_grouped() {
+            tools.extend([
+                SingleFileOperationsTool::tool_definition(),
+                MultipleFileOperationsTool::tool_definition(),
+                DirectoryOperationsTool::tool_definition(),
+                SearchAndAnalysisTool::tool_definition(),
+                FileManagementTool::tool_definition(),
+                // Operation mode management tools
+                StartOperationModeTool::tool_definition(),
+                CompleteCurrentModeTool::tool_definition(),
+                ListAvailableModesTool::tool_definition(),
+                GetCurrentModeStatusTool::tool_definition(),
+            ]);
+        }
+        if style.includes_flat() {
+            tools.extend(flat_tools::flat_tool_definitions());
+        }
+        // Diagnostics
+        tools.push(GetSlowOperationsTool::tool_definition());
+        tools.push(ServerStatsTool::tool_definition());
+        // Administration
+        tools.push(SetDryRunTool::tool_definition());
+        tools.push(ReloadPolicyTool::tool_definition());
+        tools.push(UndoLastOperationTool::tool_definition());
+        tools.push(UndoToStepTool::tool_definition());
+        tools
+    }
+
+    /// Parse a `tools/call` for a tool that `tools(style)` lists
+    pub fn from_call(params: CallToolParams, style: ToolStyle) -> Result<Self, String> {
+        if !Self::tools(style).iter().any(|tool| tool.name == params.name) {
+            return Err(format!("Unknown tool: {}", params.name));
+        }
+        // Flat tools share their names with the operations, not with grouped tools
+        if style.includes_flat() {
+            if let Some(call) = FlatToolCall::parse(&params.name, params.arguments.clone()) {
+                return call.map(Self::Flat);
+            }
+        }
+        Self::try_from(params)
     }
 
     pub fn require_write_access(&self) -> bool {
@@ -117,12 +235,19 @@ impl FileSystemTools {
             | Self::MultipleFileOperationsTool(_)
             | Self::DirectoryOperationsTool(_)
             | Self::SearchAndAnalysisTool(_)
-            | Self::FileManagementTool(_) => true, // These tools can perform write operations
+            | Self::FileManagementTool(_)
+            | Self::UndoLastOperation(_)
+            | Self::UndoToStep(_)
+            | Self::Flat(_) => true, // These tools can perform write operations
             // Operation mode management tools are read-only
             Self::StartOperationMode(_)
             | Self::CompleteCurrentMode(_)
             | Self::ListAvailableModes(_)
-            | Self::GetCurrentModeStatus(_) => false,
+            | Self::GetCurrentModeStatus(_)
+            | Self::GetSlowOperations(_)
+            | Self::ServerStats(_)
+            | Self::SetDryRun(_)
+            | Self::ReloadPolicy(_) => false,
         }
     }
 }
@@ -142,6 +267,14 @@ impl TryFrom<CallToolParams> for FileSystemTools {
             "complete_current_mode" => Ok(Self::CompleteCurrentMode(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
             "list_available_modes" => Ok(Self::ListAvailableModes(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
             "get_current_mode_status" => Ok(Self::GetCurrentModeStatus(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
+            // Diagnostics
+            "get_slow_operations" => Ok(Self::GetSlowOperations(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
+            "server_stats" => Ok(Self::ServerStats(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
+            // Administration
+            "set_dry_run" => Ok(Self::SetDryRun(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
+            "reload_policy" => Ok(Self::ReloadPolicy(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
+            "undo_last_operation" => Ok(Self::UndoLastOperation(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
+            "undo_to_step" => Ok(Self::UndoToStep(serde_json::from_value(params.arguments.unwrap_or_default()).map_err(|e| e.to_string())?)),
             _ => Err(format!("Unknown tool: {}", params.name)),
         }
     }
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::ops::FileSystemOps;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,13 +14,14 @@ pub struct MoveFileTool {
 impl MoveFileTool {
     
 
-    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+    pub async fn run_tool(self, fs_service: &dyn FileSystemOps) -> Result<CallToolResult, CallToolError> {
         match fs_service.move_file(Path::new(&self.source), Path::new(&self.destination)).await {
             Ok(_) => Ok(CallToolResult {
                 content: vec![Content::Text(TextContent {
                     text: format!("Successfully moved {} to {}", self.source, self.destination),
                 })],
                 is_error: Some(false),
+                structured_content: None,
             }),
             Err(e) => Err(CallToolError::new(e)),
         }
@@ -8,6 +8,7 @@ use crate::task_state::{get_current_mode, add_workflow_step};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MultipleFileOperationsTool {
     pub operation: String,
+    #[serde(default)]
     pub paths: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub destination: Option<String>,
@@ -17,45 +18,124 @@ pub struct MultipleFileOperationsTool {
     pub pattern: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_total_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operations: Option<Vec<crate::fs_service::batch::BatchOperation>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<crate::fs_service::checksum::HashAlgorithm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
 }
 
 impl MultipleFileOperationsTool {
     pub fn tool_definition() -> Tool {
         Tool {
             name: "multiple_file_operations".to_string(),
-            description: Some("Perform various operations on multiple files including read, copy, move, zip, unzip, and read media files.".to_string()),
+            description: Some("Perform various operations on multiple files including read, copy, move, zip, unzip, read media files, transactional batches that roll back on failure, declarative pipelines (search -> filter -> copy -> zip) in one call, a staging area for collecting paths across calls before one bulk copy/move, joining files into one, checksums of several files, and renaming many files at once by regex or template.".to_string()),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
                     "operation": {
                         "type": "string",
                         "description": "The operation to perform",
-                        "enum": ["read_multiple_files", "read_multiple_media_files", "copy_files", "move_files", "zip_files", "unzip_file", "zip_directory"]
+                        "enum": ["read_multiple_files", "read_multiple_media_files", "copy_files", "move_files", "zip_files", "unzip_file", "zip_directory", "batch_operations", "run_pipeline", "stage_files", "unstage_files", "list_staged", "commit_staged", "concat_files", "checksum_files", "rename_files"]
                     },
                     "paths": {
                         "type": "array",
                         "items": { "type": "string" },
-                        "description": "Array of file paths to operate on"
+                        "description": "Array of file paths to operate on (not used by batch_operations or run_pipeline). read_multiple_files, read_multiple_media_files and stage_files also accept glob patterns (e.g. 'src/**/*.rs'), capped at 100 files / 10 MB; concat_files joins the paths in order, expanding each glob to its sorted matches (up to 10000 files). For unstage_files, an empty list clears the staging area"
                     },
                     "destination": {
                         "type": "string",
-                        "description": "Destination path for copy/move operations"
+                        "description": "Destination path for copy/move operations; for commit_staged, the directory the staged paths go into"
                     },
                     "output_path": {
                         "type": "string",
-                        "description": "Output path for zip operations"
+                        "description": "Output path for zip operations and the joined file for concat_files"
                     },
                     "pattern": {
                         "type": "string",
-                        "description": "Pattern for zip_directory operation"
+                        "description": "Pattern for zip_directory operation; for rename_files, a regex matched against each file name (e.g. 'IMG_(\\d+)\\.jpeg'), whose first match is replaced"
                     },
                     "max_bytes": {
                         "type": "number",
                         "description": "Maximum file size in bytes for media files"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "description": "Output format for read_multiple_files, batch_operations, run_pipeline, stage_files, list_staged, commit_staged, concat_files, checksum_files and rename_files: 'text' or 'json' with per-step status",
+                        "enum": ["text", "json"]
+                    },
+                    "max_total_bytes": {
+                        "type": "number",
+                        "description": "Combined size budget for read_multiple_files (default 4 MB). Files past the budget are skipped and reported"
+                    },
+                    "operations": {
+                        "type": "array",
+                        "description": "For batch_operations: ordered steps. If step k fails, steps 1..k-1 are rolled back. Once the batch succeeds its deletes and overwrites are final: they skip the trash, --backup and the undo history",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "op": { "type": "string", "enum": ["write", "move", "copy", "delete", "mkdir"] },
+                                "path": { "type": "string", "description": "Target for write, delete and mkdir" },
+                                "content": { "type": "string", "description": "File content for write" },
+                                "source": { "type": "string", "description": "Source for move and copy" },
+                                "destination": { "type": "string", "description": "Destination for move and copy" }
+                            },
+                            "required": ["op"]
+                        }
+                    },
+                    "pipeline": {
+                        "type": ["object", "string"],
+                        "description": "For run_pipeline: {\"variables\": {name: value}, \"stages\": [...]}. Each stage has \"stage\" (search, filter, copy, move, delete, zip), optional \"input\"/\"output\" list names (input defaults to the previous stage), and stage fields: search {path, pattern, match_mode}; filter {pattern, exclude, min_bytes, max_bytes, modified_within_hours}; copy/move {destination}; zip {output_path, base}. String fields expand ${name} variables and ${<output>.count}"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "For run_pipeline: report what copy, move, delete and zip stages would produce without writing. For rename_files: only show the new names unless this is explicitly false",
+                        "default": false
+                    },
+                    "action": {
+                        "type": "string",
+                        "description": "For commit_staged: whether staged paths are copied or moved",
+                        "enum": ["copy", "move"],
+                        "default": "copy"
+                    },
+                    "separator": {
+                        "type": "string",
+                        "description": "For concat_files: text written between consecutive files (e.g. \"\\n\"), not after the last one"
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "For concat_files: replace an existing output_path",
+                        "default": false
+                    },
+                    "algorithm": {
+                        "type": "string",
+                        "description": "For checksum_files: the hash function",
+                        "enum": ["sha256", "sha512", "md5"],
+                        "default": "sha256"
+                    },
+                    "replacement": {
+                        "type": "string",
+                        "description": "For rename_files: the new name. With pattern, a regex replacement using $1 or ${1} for capture groups (e.g. 'photo_${1}.jpg'); without it, a template using {name}, {stem}, {ext} and {n}, the file's 1-based position ({n:3} pads it to 3 digits), e.g. 'scan_{n:3}.{ext}'. Files keep their directory"
                     }
                 },
-                "required": ["operation", "paths"]
+                "required": ["operation"]
             }),
+            output_schema: None,
         }
     }
 
@@ -70,12 +150,43 @@ impl MultipleFileOperationsTool {
                     text: format!("Operation '{}' is not available in the current operation mode. Use 'start_operation_mode' with 'multiple_file_operations' to enable this operation.", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             });
         }
 
-        let result = match self.operation.as_str() {
+        let operation = self.operation.clone();
+        let path_count = self.paths.len();
+        let result = self.run_operation(fs_service).await;
+
+        // Add workflow step if operation was successful
+        if let Ok(ref call_result) = result {
+            if !call_result.is_error.unwrap_or(false) {
+                let result_json = json!({
+                    "operation": operation.clone(),
+                    "path_count": path_count,
+                    "success": true
+                });
+                add_workflow_step(
+                    format!("multiple_file_operations:{}", operation),
+                    result_json,
+                    undo_metadata(fs_service)
+                );
+            }
+        }
+
+        result
+    }
+
+    /// Run the operation without the operation-mode check, for the flat
+    /// tools of `--tool-style flat`
+    pub async fn run_operation(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        match self.operation.as_str() {
             "read_multiple_files" => {
-                let tool = ReadMultipleFilesTool { paths: self.paths.clone() };
+                let tool = ReadMultipleFilesTool {
+                    paths: self.paths.clone(),
+                    output_format: self.output_format.clone(),
+                    max_total_bytes: self.max_total_bytes,
+                };
                 tool.run_tool(fs_service).await
             },
             "read_multiple_media_files" => {
@@ -92,6 +203,7 @@ impl MultipleFileOperationsTool {
                             text: "Destination is required for copy_files operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 // Copy each file to the destination directory
@@ -114,6 +226,7 @@ impl MultipleFileOperationsTool {
                         text: format!("Copy operation completed:\n{}", results.join("\n")),
                     })],
                     is_error: Some(false),
+                    structured_content: None,
                 })
             },
             "move_files" => {
@@ -123,6 +236,7 @@ impl MultipleFileOperationsTool {
                             text: "Destination is required for move_files operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 // Move each file to the destination directory
@@ -145,6 +259,7 @@ impl MultipleFileOperationsTool {
                         text: format!("Move operation completed:\n{}", results.join("\n")),
                     })],
                     is_error: Some(false),
+                    structured_content: None,
                 })
             },
             "zip_files" => {
@@ -154,6 +269,7 @@ impl MultipleFileOperationsTool {
                             text: "Output path is required for zip_files operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = ZipFilesTool {
@@ -169,6 +285,7 @@ impl MultipleFileOperationsTool {
                             text: "Output path is required for unzip_file operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 // For simplicity, we'll assume the first path is the zip file
@@ -178,6 +295,7 @@ impl MultipleFileOperationsTool {
                             text: "At least one zip file path is required".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = UnzipFileTool {
@@ -193,6 +311,7 @@ impl MultipleFileOperationsTool {
                             text: "Output path is required for zip_directory operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 // For simplicity, we'll assume the first path is the directory to zip
@@ -202,6 +321,7 @@ impl MultipleFileOperationsTool {
                             text: "At least one directory path is required".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = ZipDirectoryTool {
@@ -210,30 +330,128 @@ impl MultipleFileOperationsTool {
                 };
                 tool.run_tool(fs_service).await
             },
+            "run_pipeline" => {
+                let Some(pipeline) = self.pipeline.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "pipeline is required for run_pipeline operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = RunPipelineTool {
+                    pipeline,
+                    dry_run: self.dry_run.unwrap_or(false),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "batch_operations" => {
+                let Some(operations) = self.operations.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "operations is required for batch_operations operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = BatchOperationsTool {
+                    operations,
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "stage_files" => {
+                let tool = StageFilesTool {
+                    paths: self.paths.clone(),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "unstage_files" => {
+                let tool = UnstageFilesTool {
+                    paths: self.paths.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "list_staged" => {
+                let tool = ListStagedTool {
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "commit_staged" => {
+                let Some(destination) = self.destination.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "destination is required for commit_staged operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = CommitStagedTool {
+                    destination,
+                    action: self.action.clone(),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "concat_files" => {
+                let Some(output_path) = self.output_path.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "Output path is required for concat_files operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = ConcatFilesTool {
+                    paths: self.paths.clone(),
+                    output_path,
+                    separator: self.separator.clone(),
+                    overwrite: self.overwrite.unwrap_or(false),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "rename_files" => {
+                let Some(replacement) = self.replacement.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "replacement is required for rename_files operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = RenameFilesTool {
+                    paths: self.paths.clone(),
+                    pattern: self.pattern.clone(),
+                    replacement,
+                    dry_run: self.dry_run,
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "checksum_files" => {
+                let tool = ChecksumFilesTool {
+                    paths: self.paths.clone(),
+                    algorithm: self.algorithm.unwrap_or_default(),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
             _ => Ok(CallToolResult {
                 content: vec![Content::Text(TextContent {
                     text: format!("Unknown operation: {}", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             }),
-        };
-
-        // Add workflow step if operation was successful
-        if let Ok(ref call_result) = result {
-            if !call_result.is_error.unwrap_or(false) {
-                let result_json = json!({
-                    "operation": self.operation.clone(),
-                    "path_count": self.paths.len(),
-                    "success": true
-                });
-                add_workflow_step(
-                    format!("multiple_file_operations:{}", self.operation),
-                    result_json,
-                    None
-                );
-            }
         }
-
-        result
     }
 }
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    permissions::{parse_mode, NormalizePermissionsOptions, PermissionReport},
+    FileSystemService,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizePermissions {
+    pub path: String,
+    /// Octal mode for files, e.g. "644"
+    #[serde(default)]
+    pub file_mode: Option<String>,
+    /// Octal mode for directories, e.g. "755"
+    #[serde(default)]
+    pub dir_mode: Option<String>,
+    pub exclude_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
+    /// Only report the changes unless this is explicitly false
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    pub output_format: Option<String>,
+}
+
+impl NormalizePermissions {
+    fn format_output(report: &PermissionReport, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(report).map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let failed = report.changes.iter().filter(|c| c.error.is_some()).count();
+                let heading = if report.dry_run { "Would change" } else { "Changed" };
+                writeln!(
+                    output,
+                    "{} {} entries under {} ({} already correct{})",
+                    heading,
+                    report.changes.len() - failed,
+                    report.root,
+                    report.unchanged,
+                    if failed > 0 { format!(", {} failed", failed) } else { String::new() }
+                )
+                .map_err(|e| e.to_string())?;
+                for change in &report.changes {
+                    let suffix = if change.is_dir { "/" } else { "" };
+                    match &change.error {
+                        Some(error) => writeln!(output, "  [FAILED] {}{}: {}", change.path, suffix, error),
+                        None => writeln!(output, "  {} -> {}  {}{}", change.from, change.to, change.path, suffix),
+                    }
+                    .map_err(|e| e.to_string())?;
+                }
+                if report.dry_run && !report.changes.is_empty() {
+                    output.push_str("\nNo permissions were changed. Re-run with dry_run: false to apply.\n");
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        if self.file_mode.is_none() && self.dir_mode.is_none() {
+            return Err(CallToolError::new("normalize_permissions requires file_mode, dir_mode, or both"));
+        }
+        let options = NormalizePermissionsOptions {
+            file_mode: self.file_mode.as_deref().map(parse_mode).transpose().map_err(CallToolError::new)?,
+            dir_mode: self.dir_mode.as_deref().map(parse_mode).transpose().map_err(CallToolError::new)?,
+            exclude_patterns: self.exclude_patterns.unwrap_or_default(),
+            include_defaults_excluded: self.include_defaults_excluded,
+            dry_run: self.dry_run != Some(false),
+        };
+
+        let report = fs_service
+            .normalize_permissions(std::path::Path::new(&self.path), &options)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&report, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(report.changes.iter().any(|c| c.error.is_some())),
+            structured_content: None,
+        })
+    }
+}
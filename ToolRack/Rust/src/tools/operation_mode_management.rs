@@ -1,8 +1,18 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use crate::mcp_types::{Tool, CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::FileSystemService;
 use crate::task_state::{get_current_mode, add_workflow_step, complete_current_mode, get_available_operation_modes, get_operation_mode_tools, start_operation_mode};
 
+/// Mentions the temporary paths a mode took with it
+fn removed_note(removed: &[String]) -> String {
+    if removed.is_empty() {
+        String::new()
+    } else {
+        format!("\nRemoved {} temporary path(s):\n{}", removed.len(), removed.join("\n"))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StartOperationModeTool {
     pub mode_name: String,
@@ -24,10 +34,11 @@ impl StartOperationModeTool {
                 },
                 "required": ["mode_name"]
             }),
+            output_schema: None,
         }
     }
 
-    pub async fn run_tool(self) -> Result<CallToolResult, CallToolError> {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
         let available_tools = get_operation_mode_tools(&self.mode_name);
 
         if available_tools.is_empty() {
@@ -36,9 +47,12 @@ impl StartOperationModeTool {
                     text: format!("Unknown operation mode: {}", self.mode_name),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             });
         }
 
+        // Starting a mode ends the one before it, temporary paths included
+        let removed = get_current_mode().map(|previous| fs_service.cleanup_temp_for_mode(previous.id)).unwrap_or_default();
         let mode = start_operation_mode(self.mode_name.clone(), available_tools);
 
         let result_json = json!({
@@ -55,13 +69,15 @@ impl StartOperationModeTool {
 
         Ok(CallToolResult {
             content: vec![Content::Text(TextContent {
-                text: format!("Started operation mode '{}' with {} available tools: {}",
+                text: format!("Started operation mode '{}' with {} available tools: {}{}",
                     self.mode_name,
                     mode.available_tools.len(),
-                    mode.available_tools.join(", ")
+                    mode.available_tools.join(", "),
+                    removed_note(&removed)
                 ),
             })],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
@@ -78,14 +94,16 @@ impl CompleteCurrentModeTool {
                 "type": "object",
                 "properties": {}
             }),
+            output_schema: None,
         }
     }
 
-    pub async fn run_tool(self) -> Result<CallToolResult, CallToolError> {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
         let completed_mode = complete_current_mode();
 
         match completed_mode {
             Some(mode) => {
+                let removed = fs_service.cleanup_temp_for_mode(mode.id);
                 let result_json = json!({
                     "mode_completed": mode.name,
                     "duration_seconds": chrono::Utc::now().timestamp() - mode.start_time.timestamp(),
@@ -100,13 +118,15 @@ impl CompleteCurrentModeTool {
 
                 Ok(CallToolResult {
                     content: vec![Content::Text(TextContent {
-                        text: format!("Completed operation mode '{}' after {} steps and {:.1} seconds",
+                        text: format!("Completed operation mode '{}' after {} steps and {:.1} seconds{}",
                             mode.name,
                             mode.workflow_history.len(),
-                            (chrono::Utc::now().timestamp() - mode.start_time.timestamp()) as f64
+                            (chrono::Utc::now().timestamp() - mode.start_time.timestamp()) as f64,
+                            removed_note(&removed)
                         ),
                     })],
                     is_error: Some(false),
+                    structured_content: None,
                 })
             },
             None => Ok(CallToolResult {
@@ -114,6 +134,7 @@ impl CompleteCurrentModeTool {
                     text: "No operation mode was active".to_string(),
                 })],
                 is_error: Some(false),
+                structured_content: None,
             }),
         }
     }
@@ -131,6 +152,7 @@ impl ListAvailableModesTool {
                 "type": "object",
                 "properties": {}
             }),
+            output_schema: None,
         }
     }
 
@@ -148,6 +170,7 @@ impl ListAvailableModesTool {
                 text: format!("Available operation modes:\n\n{}", mode_details.join("\n")),
             })],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
@@ -164,6 +187,7 @@ impl GetCurrentModeStatusTool {
                 "type": "object",
                 "properties": {}
             }),
+            output_schema: None,
         }
     }
 
@@ -190,7 +214,8 @@ impl GetCurrentModeStatusTool {
                                 step.get("step").and_then(|s| s.as_str()),
                                 step.get("timestamp").and_then(|s| s.as_str())
                             ) {
-                                status_text.push_str(&format!("  {}. {} - {}\n", i + 1, step_name, timestamp));
+                                let undo_step = step.get("undo_step").map(|undo| format!(" (undo step {})", undo)).unwrap_or_default();
+                                status_text.push_str(&format!("  {}. {} - {}{}\n", i + 1, step_name, timestamp, undo_step));
                             }
                         }
                     }
@@ -203,6 +228,7 @@ impl GetCurrentModeStatusTool {
                         text: status_text,
                     })],
                     is_error: Some(false),
+                    structured_content: None,
                 })
             },
             None => Ok(CallToolResult {
@@ -210,6 +236,7 @@ impl GetCurrentModeStatusTool {
                     text: "No operation mode is currently active. Use 'start_operation_mode' to begin a new workflow.".to_string(),
                 })],
                 is_error: Some(false),
+                structured_content: None,
             }),
         }
     }
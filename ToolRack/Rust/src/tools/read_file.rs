@@ -1,18 +1,114 @@
 use serde::{Deserialize, Serialize};
-use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::mcp_types::{CallToolResult, Content, ResourceLink, TextContent, CallToolError};
+use crate::fs_service::{
+    chunked::ReadCursor,
+    encoding::TextEncoding,
+    remote::RemoteStorage,
+    resources::MAX_INLINE_READ_BYTES,
+    utils::{format_bytes, number_lines},
+    FileSystemService,
+};
 use crate::retry::retry_3x;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadFileTool {
     pub path: String,
+    /// Prefix each line with its 1-based line number
+    #[serde(default)]
+    pub with_line_numbers: bool,
+    /// Read only the bytes from this position on (default 0 when
+    /// length_bytes is given)
+    #[serde(default)]
+    pub offset_bytes: Option<u64>,
+    /// Read at most this many bytes (default and cap: the inline read limit)
+    #[serde(default)]
+    pub length_bytes: Option<u64>,
+    /// Continue a chunked read where the previous chunk ended
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Decode the file from this encoding (`auto` to guess) instead of
+    /// requiring UTF-8
+    #[serde(default)]
+    pub encoding: Option<String>,
 }
 
-impl ReadFileTool {
-
+/// A resource link in place of the content when `path` is larger than `limit`.
+/// The text part says why, for clients that ignore links.
+pub(crate) async fn oversized_file_link(
+    fs_service: &FileSystemService,
+    path: &Path,
+    limit: u64,
+) -> Result<Option<CallToolResult>, CallToolError> {
+    // Remote files have no file:// URI to link to
+    if RemoteStorage::is_remote(path) {
+        return Ok(None);
+    }
+    let stats = fs_service.get_file_stats(path, true).await.map_err(CallToolError::new)?;
+    if !stats.is_file || stats.size <= limit {
+        return Ok(None);
+    }
+    let link = fs_service.resource_link(path).await.map_err(CallToolError::new)?;
+    let note = format!(
+        "{} is {}, over the {} limit for inline content. Fetch it with resources/read on {}, or read parts of it with read_file_lines or with offset_bytes and length_bytes.",
+        path.display(),
+        format_bytes(link.size),
+        format_bytes(limit),
+        link.uri
+    );
+    Ok(Some(CallToolResult {
+        content: vec![
+            Content::Text(TextContent { text: note }),
+            Content::ResourceLink(ResourceLink {
+                uri: link.uri,
+                name: link.name,
+                description: Some(format!("{} ({})", path.display(), format_bytes(link.size))),
+                mime_type: Some(link.mime_type),
+                size: Some(link.size),
+            }),
+        ],
+        is_error: Some(false),
+        structured_content: None,
+    }))
+}
 
+impl ReadFileTool {
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        if let Some(encoding) = &self.encoding {
+            if self.offset_bytes.is_some() || self.length_bytes.is_some() || self.cursor.is_some() {
+                return Err(CallToolError::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "encoding cannot be combined with offset_bytes, length_bytes or cursor",
+                )));
+            }
+            let encoding = encoding.parse::<TextEncoding>().map_err(CallToolError::new)?;
+            return self.read_decoded(fs_service, encoding).await;
+        }
+        if self.offset_bytes.is_some() || self.length_bytes.is_some() {
+            if self.cursor.is_some() {
+                return Err(CallToolError::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "cursor cannot be combined with offset_bytes or length_bytes",
+                )));
+            }
+            return self.read_range(fs_service).await;
+        }
+        if let Some(cursor) = &self.cursor {
+            let cursor = cursor.parse::<ReadCursor>().map_err(CallToolError::new)?;
+            return self.read_chunk(fs_service, Some(cursor)).await;
+        }
+        // Files too big for one reply are read a chunk at a time, unless they
+        // are over --max-read-bytes too; remote files are read whole by their
+        // backend
+        if !RemoteStorage::is_remote(Path::new(&self.path)) {
+            let stats = fs_service.get_file_stats(Path::new(&self.path), true).await.map_err(CallToolError::new)?;
+            if stats.is_file {
+                fs_service.check_read_size(Path::new(&self.path), stats.size).map_err(CallToolError::new)?;
+            }
+            if stats.is_file && stats.size > MAX_INLINE_READ_BYTES {
+                return self.read_chunk(fs_service, None).await;
+            }
+        }
         // Retry up to 3 times on transient I/O errors
         let path = self.path.clone();
         match retry_3x("read_file", || {
@@ -23,11 +119,124 @@ impl ReadFileTool {
         }).await {
             Ok(content) => Ok(CallToolResult {
                 content: vec![Content::Text(TextContent {
-                    text: content,
+                    text: if self.with_line_numbers { number_lines(content.lines(), 1) } else { content },
                 })],
                 is_error: Some(false),
+                structured_content: None,
             }),
             Err(e) => Err(CallToolError::new(e)),
         }
     }
+
+    /// The whole file decoded from `encoding`, and a note naming the encoding
+    /// it was read in. Such files are not read in chunks, so they must fit in
+    /// one reply.
+    async fn read_decoded(self, fs_service: &FileSystemService, encoding: TextEncoding) -> Result<CallToolResult, CallToolError> {
+        let path = Path::new(&self.path);
+        if !RemoteStorage::is_remote(path) {
+            let stats = fs_service.get_file_stats(path, true).await.map_err(CallToolError::new)?;
+            if stats.is_file && stats.size > MAX_INLINE_READ_BYTES {
+                return Err(CallToolError::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} is {}, over the {} limit for one read; files read with an encoding are not split into chunks",
+                        path.display(),
+                        format_bytes(stats.size),
+                        format_bytes(MAX_INLINE_READ_BYTES)
+                    ),
+                )));
+            }
+        }
+        let decoded = fs_service.read_file_as(path, encoding).await.map_err(CallToolError::new)?;
+        let note = format!("Decoded from {}", decoded.describe());
+        Ok(CallToolResult {
+            content: vec![
+                Content::Text(TextContent {
+                    text: if self.with_line_numbers { number_lines(decoded.text.lines(), 1) } else { decoded.text },
+                }),
+                Content::Text(TextContent { text: note }),
+            ],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+
+    /// One chunk of a file over the inline limit, a note saying which part it
+    /// is and how to get the next, and a link to the whole file
+    async fn read_chunk(self, fs_service: &FileSystemService, cursor: Option<ReadCursor>) -> Result<CallToolResult, CallToolError> {
+        let path = Path::new(&self.path);
+        let chunk = fs_service
+            .read_file_chunk(path, cursor, MAX_INLINE_READ_BYTES)
+            .await
+            .map_err(CallToolError::new)?;
+        let link = fs_service.resource_link(path).await.map_err(CallToolError::new)?;
+
+        let last_line = chunk.first_line + chunk.text.lines().count().max(1) as u64 - 1;
+        let mut note = format!(
+            "{} is {}, over the {} limit for one read. This is bytes {}-{} (lines {}-{}).",
+            path.display(),
+            format_bytes(chunk.file_size),
+            format_bytes(MAX_INLINE_READ_BYTES),
+            chunk.start_byte,
+            chunk.end_byte.saturating_sub(1),
+            chunk.first_line,
+            last_line
+        );
+        match &chunk.next_cursor {
+            Some(cursor) => note.push_str(&format!(" Continue with cursor: \"{}\", or fetch the whole file with resources/read on {}.", cursor, link.uri)),
+            None => note.push_str(" This is the end of the file."),
+        }
+        let text = if self.with_line_numbers { number_lines(chunk.text.lines(), chunk.first_line as usize) } else { chunk.text.clone() };
+
+        Ok(CallToolResult {
+            content: vec![
+                Content::Text(TextContent { text }),
+                Content::Text(TextContent { text: note }),
+                Content::ResourceLink(ResourceLink {
+                    uri: link.uri,
+                    name: link.name,
+                    description: Some(format!("{} ({})", path.display(), format_bytes(link.size))),
+                    mime_type: Some(link.mime_type),
+                    size: Some(link.size),
+                }),
+            ],
+            is_error: Some(false),
+            structured_content: serde_json::to_value(&chunk).ok(),
+        })
+    }
+
+    /// A slice of the file read with a seek, followed by where it sits in the
+    /// file and where the next read should start
+    async fn read_range(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        if self.with_line_numbers {
+            return Err(CallToolError::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "with_line_numbers cannot be combined with offset_bytes or length_bytes; use read_file_lines for numbered lines",
+            )));
+        }
+        let offset = self.offset_bytes.unwrap_or(0);
+        // A few bytes at least, so a single character always fits
+        let length = self.length_bytes.unwrap_or(MAX_INLINE_READ_BYTES).clamp(4, MAX_INLINE_READ_BYTES);
+        let window = fs_service
+            .read_text_window(Path::new(&self.path), offset, length)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let mut note = if window.bytes.is_empty() {
+            format!("No bytes at offset {} ({} bytes in file).", window.start_byte, window.file_size)
+        } else {
+            format!("Bytes {}-{} of {}.", window.start_byte, window.end_byte() - 1, window.file_size)
+        };
+        if !window.reached_eof() {
+            note.push_str(&format!(" More data follows; continue with offset_bytes: {}", window.end_byte()));
+        }
+        Ok(CallToolResult {
+            content: vec![
+                Content::Text(TextContent { text: String::from_utf8_lossy(&window.bytes).into_owned() }),
+                Content::Text(TextContent { text: note }),
+            ],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
 }
\ No newline at end of file
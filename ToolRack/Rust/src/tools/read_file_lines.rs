@@ -1,33 +1,82 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::{utils::number_lines, FileSystemService, LineOrigin};
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadFileLines {
     pub path: String,
+    /// Lines to skip (from the start, from the end with `from_end`, or after `byte_offset`)
+    #[serde(default)]
     pub offset: u64,
     pub limit: Option<u64>,
+    /// Count lines backwards from the end of the file
+    #[serde(default)]
+    pub from_end: bool,
+    /// Start reading at this byte position, e.g. the resume offset of a previous read
+    #[serde(default)]
+    pub byte_offset: Option<u64>,
+    /// Prefix each line with its 1-based line number in the file
+    #[serde(default)]
+    pub with_line_numbers: bool,
 }
 
 impl ReadFileLines {
-    
-
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
-        let result = fs_service
+        if self.from_end && self.byte_offset.is_some() {
+            return Err(CallToolError::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "from_end and byte_offset cannot be combined",
+            )));
+        }
+
+        let origin = match (self.byte_offset, self.from_end) {
+            (Some(byte_offset), _) => LineOrigin::ByteOffset(byte_offset),
+            (None, true) => LineOrigin::End,
+            (None, false) => LineOrigin::Start,
+        };
+        let window = fs_service
             .read_file_lines(
                 Path::new(&self.path),
+                origin,
                 self.offset as usize,
                 self.limit.map(|v| v as usize),
             )
             .await
             .map_err(CallToolError::new)?;
 
+        let mut text = if self.with_line_numbers {
+            let first_line = match origin {
+                LineOrigin::Start => self.offset as usize + 1,
+                _ => fs_service
+                    .line_number_at(Path::new(&self.path), window.start_byte)
+                    .await
+                    .map_err(CallToolError::new)?,
+            };
+            number_lines(window.lines.iter().map(String::as_str), first_line)
+        } else {
+            window.text()
+        };
+        if self.from_end {
+            if window.start_byte > 0 {
+                text.push_str(&format!(
+                    "\n\n[Read bytes {}-{} of {}; earlier lines precede byte {}]",
+                    window.start_byte, window.end_byte, window.file_size, window.start_byte
+                ));
+            }
+        } else if !window.reached_eof() {
+            text.push_str(&format!(
+                "\n\n[Read bytes {}-{} of {}. Continue with byte_offset: {}]",
+                window.start_byte, window.end_byte, window.file_size, window.end_byte
+            ));
+        }
+
         Ok(CallToolResult {
             content: vec![Content::Text(TextContent {
-                text: result,
+                text,
             })],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
@@ -1,19 +1,24 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, AudioContent, ImageContent, CallToolError};
 use crate::error::ServiceError;
-use crate::fs_service::FileSystemService;
+use crate::fs_service::{resources::MAX_INLINE_READ_BYTES, FileSystemService};
+use crate::tools::read_file::oversized_file_link;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadMediaFile {
     pub path: String,
+    /// Larger files are returned as a resource link (default 4 MiB)
     pub max_bytes: Option<u64>,
 }
 
 impl ReadMediaFile {
-    
-
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let limit = self.max_bytes.unwrap_or(MAX_INLINE_READ_BYTES);
+        if let Some(link) = oversized_file_link(fs_service, Path::new(&self.path), limit).await? {
+            return Ok(link);
+        }
+
         let (kind, content) = fs_service
             .read_media_file(
                 Path::new(&self.path),
@@ -1,31 +1,106 @@
-use std::path::Path;
-
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::{utils::format_bytes, FileReadResult, FileSystemService, ReadStatus, READ_MULTIPLE_MAX_TOTAL_BYTES};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadMultipleFilesTool {
+    /// File paths or glob patterns (e.g. `src/**/*.rs`) expanded server-side
     pub paths: Vec<String>,
+    /// "text" (default) or "json" for per-file structured results
+    #[serde(default)]
+    pub output_format: Option<String>,
+    /// Combined size budget in bytes; files past it are skipped, not read
+    #[serde(default)]
+    pub max_total_bytes: Option<u64>,
 }
 
 impl ReadMultipleFilesTool {
-    
+    fn format_output(results: &[FileReadResult], notes: &[String], output_format: &str) -> Result<String, String> {
+        let succeeded = results.iter().filter(|r| r.status == ReadStatus::Ok).count();
+        let skipped = results.iter().filter(|r| r.status == ReadStatus::Skipped).count();
+        let failed = results.len() - succeeded - skipped;
+        let total_bytes: u64 = results.iter().filter_map(|r| r.bytes).sum();
 
-    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
-        let mut results = Vec::new();
-        for path in self.paths {
-            match fs_service.read_file(Path::new(&path)).await {
-                Ok(content) => results.push(format!("=== {} ===\n{}", path, content)),
-                Err(e) => results.push(format!("=== {} ===\nError: {}", path, e)),
+        match output_format {
+            "json" => {
+                let document = json!({
+                    "summary": {
+                        "requested": results.len(),
+                        "succeeded": succeeded,
+                        "failed": failed,
+                        "skipped": skipped,
+                        "total_bytes": total_bytes,
+                    },
+                    "notes": notes,
+                    "files": results,
+                });
+                serde_json::to_string_pretty(&document).map_err(|e| e.to_string())
+            }
+            _ => {
+                let mut sections = Vec::with_capacity(results.len() + 1);
+                for result in results {
+                    match (&result.content, &result.error) {
+                        (Some(content), _) => sections.push(format!(
+                            "=== {} [ok, {} bytes] ===\n{}",
+                            result.path,
+                            result.bytes.unwrap_or_default(),
+                            content
+                        )),
+                        (None, reason) if result.status == ReadStatus::Skipped => sections.push(format!(
+                            "=== {} [skipped] ===\nSkipped: {}",
+                            result.path,
+                            reason.as_deref().unwrap_or("size budget reached")
+                        )),
+                        (None, error) => sections.push(format!(
+                            "=== {} [error] ===\nError: {}",
+                            result.path,
+                            error.as_deref().unwrap_or("unknown error")
+                        )),
+                    }
+                }
+                sections.push(format!(
+                    "Summary: {} of {} files read ({} bytes), {} failed, {} skipped",
+                    succeeded,
+                    results.len(),
+                    total_bytes,
+                    failed,
+                    skipped
+                ));
+                for note in notes {
+                    sections.push(format!("Note: {}", note));
+                }
+                Ok(sections.join("\n\n"))
             }
         }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let expansion = fs_service
+            .expand_path_patterns(&self.paths)
+            .await
+            .map_err(CallToolError::new)?;
+        let max_total_bytes = self.max_total_bytes.unwrap_or(READ_MULTIPLE_MAX_TOTAL_BYTES);
+        let results = fs_service.read_multiple_files(&expansion.paths, max_total_bytes).await;
+
+        let mut notes = expansion.notes;
+        if results.iter().any(|r| r.status == ReadStatus::Skipped) {
+            notes.push(format!(
+                "Combined size budget of {} reached. Narrow the request (fewer paths or a tighter glob), read large files with read_file_lines, or raise max_total_bytes.",
+                format_bytes(max_total_bytes)
+            ));
+        }
+        let all_failed = !results.is_empty() && results.iter().all(|r| r.status != ReadStatus::Ok);
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let output = Self::format_output(&results, &notes, output_format).map_err(CallToolError::new)?;
 
         Ok(CallToolResult {
             content: vec![Content::Text(TextContent {
-                text: results.join("\n\n"),
+                text: output,
             })],
-            is_error: Some(false),
+            is_error: Some(all_failed),
+            structured_content: None,
         })
     }
 }
@@ -1,9 +1,11 @@
+use crate::logging::log_info;
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, AudioContent, ImageContent, CallToolError};
 use crate::fs_service::FileSystemService;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReadMultipleMediaFiles {
+    /// File paths or glob patterns (e.g. `assets/**/*.png`) expanded server-side
     pub paths: Vec<String>,
     pub max_bytes: Option<u64>,
 }
@@ -12,8 +14,16 @@ impl ReadMultipleMediaFiles {
     
 
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let expansion = fs_service
+            .expand_path_patterns(&self.paths)
+            .await
+            .map_err(CallToolError::new)?;
+        for note in &expansion.notes {
+            log_info!("read_multiple_media_files: {}", note);
+        }
+
         let result = fs_service
-            .read_media_files(self.paths, self.max_bytes.map(|v| v as usize))
+            .read_media_files(expansion.paths, self.max_bytes.map(|v| v as usize))
             .await
             .map_err(CallToolError::new)?;
 
@@ -36,6 +46,7 @@ impl ReadMultipleMediaFiles {
         Ok(CallToolResult {
             content,
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
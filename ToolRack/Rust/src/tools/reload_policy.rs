@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use crate::config::Policy;
+use crate::mcp_types::{Tool, CallToolResult, Content, TextContent, CallToolError};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReloadPolicyTool {}
+
+impl ReloadPolicyTool {
+    pub fn tool_definition() -> Tool {
+        Tool {
+            name: "reload_policy".to_string(),
+            description: Some("Re-read the [policy] table of the server's --config file (allowed and blocked directories, IO limit, read-only mode) and apply it without restarting the server or dropping client sessions. Settings missing from the table fall back to the command line. Sending the server SIGHUP does the same.".to_string()),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {}
+            }),
+            output_schema: None,
+        }
+    }
+
+    /// Report the outcome of MyServerHandler::reload_policy
+    pub async fn run_tool(self, reloaded: std::io::Result<Policy>) -> Result<CallToolResult, CallToolError> {
+        let (text, is_error) = match reloaded {
+            Ok(policy) => (format!("Policy reloaded. {}", policy.summary()), false),
+            Err(e) => (format!("Policy not reloaded, the current one stays in effect: {}", e), true),
+        };
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(is_error),
+            structured_content: None,
+        })
+    }
+}
@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    rename::{RenamePlan, RenameRule, RenameStatus},
+    FileSystemService,
+};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameFilesTool {
+    /// Files to rename; glob patterns expand to their sorted matches
+    pub paths: Vec<String>,
+    /// Regex matched against each file name; without it `replacement` is a template
+    #[serde(default)]
+    pub pattern: Option<String>,
+    pub replacement: String,
+    /// Only show the new names unless this is explicitly false
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    pub output_format: Option<String>,
+}
+
+impl RenameFilesTool {
+    fn format_output(plan: &RenamePlan, dry_run: bool, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(plan).map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let renames = plan.renames().count();
+                let problems = plan.problems();
+                let heading = if plan.renamed {
+                    format!("Renamed {} files", renames)
+                } else if problems > 0 {
+                    format!("Nothing was renamed: {} of {} new names collide or are invalid", problems, plan.entries.len())
+                } else {
+                    format!("{} of {} files would be renamed", renames, plan.entries.len())
+                };
+                writeln!(output, "{}:", heading).map_err(|e| e.to_string())?;
+                for entry in &plan.entries {
+                    match &entry.status {
+                        RenameStatus::Rename => writeln!(output, "  {} -> {}", entry.from, entry.to),
+                        RenameStatus::Unchanged => writeln!(output, "  {} (unchanged)", entry.from),
+                        RenameStatus::Collision { with } => {
+                            writeln!(output, "  {} -> {} COLLIDES with {}", entry.from, entry.to, with)
+                        }
+                        RenameStatus::Invalid { reason } => writeln!(output, "  {} INVALID: {}", entry.from, reason),
+                    }
+                    .map_err(|e| e.to_string())?;
+                }
+                if dry_run && problems == 0 && renames > 0 {
+                    output.push_str("\nNo files were renamed. Re-run with dry_run: false to apply these names.\n");
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let rule = RenameRule::new(self.pattern.as_deref(), &self.replacement).map_err(CallToolError::new)?;
+        let dry_run = self.dry_run != Some(false);
+        let plan = fs_service.rename_files(&self.paths, &rule, dry_run).await.map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let text = Self::format_output(&plan, dry_run, output_format).map_err(CallToolError::new)?;
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text })],
+            is_error: Some(plan.problems() > 0),
+            structured_content: Some(json!({
+                "dry_run": dry_run,
+                "renamed": plan.renamed,
+                "entries": plan.entries,
+            })),
+        })
+    }
+}
@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{
+    pipeline::{Pipeline, PipelineReport},
+    FileSystemService,
+};
+use crate::task_state::add_workflow_step;
+use std::fmt::Write;
+
+/// Files listed per stage in text output
+const TEXT_FILES_PER_STAGE: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunPipelineTool {
+    /// Pipeline document, as an object or a JSON string
+    pub pipeline: serde_json::Value,
+    /// Report what copy, move, delete and zip stages would produce without writing
+    #[serde(default)]
+    pub dry_run: bool,
+    pub output_format: Option<String>,
+}
+
+impl RunPipelineTool {
+    fn format_output(report: &PipelineReport, output_format: &str) -> Result<String, String> {
+        match output_format {
+            "json" => serde_json::to_string_pretty(report).map_err(|e| e.to_string()),
+            _ => {
+                let mut output = String::new();
+                let mode = if report.dry_run { " (dry run)" } else { "" };
+                match (&report.failed_stage, &report.error) {
+                    (Some(index), Some(error)) => {
+                        writeln!(output, "Pipeline stopped at stage {}{}: {}", index + 1, mode, error)
+                    }
+                    _ => writeln!(output, "Pipeline completed {} stages{}", report.stages.len(), mode),
+                }
+                .map_err(|e| e.to_string())?;
+                for stage in &report.stages {
+                    writeln!(
+                        output,
+                        "\n{}. {} -> {} ({} files)",
+                        stage.index + 1,
+                        stage.stage,
+                        stage.output,
+                        stage.files.len()
+                    )
+                    .map_err(|e| e.to_string())?;
+                    for file in stage.files.iter().take(TEXT_FILES_PER_STAGE) {
+                        writeln!(output, "  {}", file).map_err(|e| e.to_string())?;
+                    }
+                    if stage.files.len() > TEXT_FILES_PER_STAGE {
+                        writeln!(output, "  ... {} more", stage.files.len() - TEXT_FILES_PER_STAGE)
+                            .map_err(|e| e.to_string())?;
+                    }
+                }
+                Ok(output)
+            }
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let pipeline: Pipeline = match self.pipeline {
+            serde_json::Value::String(text) => serde_json::from_str(&text),
+            other => serde_json::from_value(other),
+        }
+        .map_err(|e| CallToolError::new(format!("Invalid pipeline: {}", e)))?;
+
+        let report = fs_service.run_pipeline(&pipeline, self.dry_run).await;
+        for stage in &report.stages {
+            add_workflow_step(
+                format!("pipeline:{}", stage.stage),
+                json!({ "output": stage.output, "files": stage.files.len(), "dry_run": report.dry_run }),
+                None,
+            );
+        }
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = Self::format_output(&report, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(report.failed_stage.is_some()),
+            structured_content: None,
+        })
+    }
+}
@@ -10,6 +10,10 @@ pub struct SearchAndAnalysisTool {
     pub operation: String,
     pub path: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub paths: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub pattern: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query: Option<String>,
@@ -23,28 +27,77 @@ pub struct SearchAndAnalysisTool {
     pub max_bytes: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub include_content: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_symlinks: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_defaults_excluded: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub action: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub older_than_days: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_field: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub within_hours: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compare_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_all: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_lines: Option<usize>,
 }
 
 impl SearchAndAnalysisTool {
     pub fn tool_definition() -> Tool {
         Tool {
             name: "search_and_analysis".to_string(),
-            description: Some("Perform search and analysis operations including file search, content search, and finding duplicate files.".to_string()),
+            description: Some("Perform search and analysis operations including file search, content search, finding duplicate files, finding files by size or age, profiling file size and age distributions, comparing directories or files, and querying the tag store.".to_string()),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
                     "operation": {
                         "type": "string",
                         "description": "The operation to perform",
-                        "enum": ["search_files", "search_files_content", "find_duplicate_files"]
+                        "enum": ["search_files", "search_files_content", "find_duplicate_files", "find_stale_files", "find_recent_changes", "find_recent_files", "directory_profile", "find_files_by_size", "find_files_by_mtime", "audit_permissions", "compare_directories", "compare_files", "list_tags", "find_by_tag"]
                     },
                     "path": {
                         "type": "string",
                         "description": "The directory path to search in"
                     },
+                    "paths": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "More directories searched together with path by find_duplicate_files, e.g. a backup drive; duplicate groups can span them and the output breaks each group down by root"
+                    },
                     "pattern": {
                         "type": "string",
-                        "description": "File pattern for search operations"
+                        "description": "File pattern for search operations: a glob such as '*.rs' or '**/test_*.py' (patterns with '/' match the path relative to 'path'), or a substring of the file name"
+                    },
+                    "match_mode": {
+                        "type": "string",
+                        "description": "How search_files interprets 'pattern': 'auto' picks glob when the pattern contains *, ? or [. Matching is case-insensitive",
+                        "enum": ["auto", "glob", "substring"],
+                        "default": "auto"
                     },
                     "query": {
                         "type": "string",
@@ -62,20 +115,113 @@ impl SearchAndAnalysisTool {
                     },
                     "min_bytes": {
                         "type": "number",
-                        "description": "Minimum file size for duplicate search"
+                        "description": "Minimum file size in bytes for duplicate, stale and size-range search"
                     },
                     "max_bytes": {
                         "type": "number",
-                        "description": "Maximum file size for duplicate search"
+                        "description": "Maximum file size in bytes for duplicate, stale and size-range search"
                     },
                     "include_content": {
                         "type": "boolean",
                         "description": "Include file content in search",
                         "default": false
+                    },
+                    "follow_symlinks": {
+                        "type": "boolean",
+                        "description": "Follow symbolic links while searching (loops are detected)",
+                        "default": false
+                    },
+                    "include_defaults_excluded": {
+                        "type": "boolean",
+                        "description": "Also traverse .git, node_modules, target, __pycache__ and OS junk files, which are skipped by default",
+                        "default": false
+                    },
+                    "action": {
+                        "type": "string",
+                        "description": "For find_duplicate_files: replace extra copies with hard links or symlinks to the kept file, or delete them. Produces a plan unless dry_run is false",
+                        "enum": ["hardlink", "symlink", "delete_extras"]
+                    },
+                    "keep": {
+                        "type": "string",
+                        "description": "Which copy of each duplicate group to keep when an action is given",
+                        "enum": ["shortest_path", "oldest"],
+                        "default": "shortest_path"
+                    },
+                    "dry_run": {
+                        "type": "boolean",
+                        "description": "With an action, only report the plan. Must be set to false explicitly to modify files",
+                        "default": true
+                    },
+                    "older_than_days": {
+                        "type": "number",
+                        "description": "For find_stale_files: minimum age in days of the chosen timestamp"
+                    },
+                    "time_field": {
+                        "type": "string",
+                        "description": "Timestamp used by find_stale_files",
+                        "enum": ["modified", "accessed"],
+                        "default": "modified"
+                    },
+                    "within_hours": {
+                        "type": "number",
+                        "description": "For find_recent_changes: look back this many hours (default 24)"
+                    },
+                    "since": {
+                        "type": "string",
+                        "description": "For find_recent_changes: start of the window as RFC 3339 or 'YYYY-MM-DD HH:MM:SS' local time; overrides within_hours"
+                    },
+                    "start": {
+                        "type": "string",
+                        "description": "For find_files_by_mtime: start of the window (inclusive) as RFC 3339 or 'YYYY-MM-DD HH:MM:SS' local time"
+                    },
+                    "end": {
+                        "type": "string",
+                        "description": "For find_files_by_mtime and find_recent_changes: end of the window (inclusive), same formats as start"
+                    },
+                    "limit": {
+                        "type": "number",
+                        "description": "For find_recent_changes, find_files_by_size and find_files_by_mtime: maximum number of files to return"
+                    },
+                    "other_path": {
+                        "type": "string",
+                        "description": "For compare_directories: the directory compared against path (B); for compare_files, the file compared against path"
+                    },
+                    "context_lines": {
+                        "type": "number",
+                        "description": "For compare_files: unchanged lines shown around each change in the diff",
+                        "default": 3
+                    },
+                    "tags": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "For find_by_tag: the tags to look for"
+                    },
+                    "match_all": {
+                        "type": "boolean",
+                        "description": "For find_by_tag: return paths carrying every tag instead of any of them",
+                        "default": false
+                    },
+                    "compare_mode": {
+                        "type": "string",
+                        "description": "For compare_directories: compare files by size and modification time, or by size and SHA-256 hash",
+                        "enum": ["metadata", "hash"],
+                        "default": "metadata"
+                    },
+                    "sort": {
+                        "type": "string",
+                        "description": "Result order for find_files_by_size",
+                        "enum": ["size_desc", "size_asc", "modified_desc", "modified_asc", "path"],
+                        "default": "size_desc"
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "description": "Output format for find_duplicate_files, find_stale_files, find_recent_changes, find_files_by_size, find_files_by_mtime, audit_permissions, compare_directories, compare_files, list_tags and find_by_tag. 'plan' (find_duplicate_files with an action) emits a document for file_management apply_plan",
+                        "enum": ["text", "json", "plan"]
                     }
                 },
                 "required": ["operation", "path"]
             }),
+            output_schema: None,
         }
     }
 
@@ -90,10 +236,37 @@ impl SearchAndAnalysisTool {
                     text: format!("Operation '{}' is not available in the current operation mode. Use 'start_operation_mode' with 'search_and_analysis' to enable this operation.", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             });
         }
 
-        let result = match self.operation.as_str() {
+        let operation = self.operation.clone();
+        let path = self.path.clone();
+        let result = self.run_operation(fs_service).await;
+
+        // Add workflow step if operation was successful
+        if let Ok(ref call_result) = result {
+            if !call_result.is_error.unwrap_or(false) {
+                let result_json = json!({
+                    "operation": operation.clone(),
+                    "path": path,
+                    "success": true
+                });
+                add_workflow_step(
+                    format!("search_and_analysis:{}", operation),
+                    result_json,
+                    undo_metadata(fs_service)
+                );
+            }
+        }
+
+        result
+    }
+
+    /// Run the operation without the operation-mode check, for the flat
+    /// tools of `--tool-style flat`
+    pub async fn run_operation(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        match self.operation.as_str() {
             "search_files" => {
                 if self.pattern.is_none() {
                     return Ok(CallToolResult {
@@ -101,12 +274,16 @@ impl SearchAndAnalysisTool {
                             text: "Pattern is required for search_files operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = SearchFilesTool {
                     directory: self.path.clone(),
                     pattern: self.pattern.unwrap(),
                     include_content: Some(self.include_content.unwrap_or(false)),
+                    follow_symlinks: self.follow_symlinks.unwrap_or(false),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    match_mode: self.match_mode.clone(),
                 };
                 tool.run_tool(fs_service).await
             },
@@ -117,6 +294,7 @@ impl SearchAndAnalysisTool {
                             text: "Pattern and query are required for search_files_content operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = SearchFilesContent {
@@ -131,13 +309,164 @@ impl SearchAndAnalysisTool {
                 tool.run_tool(fs_service).await
             },
             "find_duplicate_files" => {
+                let mut root_paths = vec![self.path.clone()];
+                root_paths.extend(self.paths.clone().unwrap_or_default());
                 let tool = FindDuplicateFiles {
-                    root_path: self.path.clone(),
+                    root_paths,
                     pattern: self.pattern.clone(),
                     exclude_patterns: self.exclude_patterns.clone(),
                     min_bytes: self.min_bytes,
                     max_bytes: self.max_bytes,
-                    output_format: Some("text".to_string()),
+                    output_format: self.output_format.clone(),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    action: self.action.clone(),
+                    keep: self.keep.clone(),
+                    dry_run: self.dry_run,
+                };
+                tool.run_tool(fs_service).await
+            },
+            "find_stale_files" => {
+                let Some(older_than_days) = self.older_than_days else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "older_than_days is required for find_stale_files operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = FindStaleFiles {
+                    path: self.path.clone(),
+                    older_than_days,
+                    time_field: self.time_field.clone(),
+                    pattern: self.pattern.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    min_bytes: self.min_bytes,
+                    max_bytes: self.max_bytes,
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "find_recent_changes" | "find_recent_files" => {
+                let tool = FindRecentChanges {
+                    path: self.path.clone(),
+                    within_hours: self.within_hours,
+                    since: self.since.clone(),
+                    end: self.end.clone(),
+                    pattern: self.pattern.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    limit: self.limit,
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "directory_profile" => {
+                let tool = DirectoryProfileTool {
+                    path: self.path.clone(),
+                    pattern: self.pattern.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "find_files_by_size" => {
+                let tool = FindFilesBySize {
+                    path: self.path.clone(),
+                    min_bytes: self.min_bytes,
+                    max_bytes: self.max_bytes,
+                    pattern: self.pattern.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    sort: self.sort.clone(),
+                    limit: self.limit,
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "find_files_by_mtime" => {
+                let tool = FindFilesByMtime {
+                    path: self.path.clone(),
+                    start: self.start.clone(),
+                    end: self.end.clone(),
+                    pattern: self.pattern.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    limit: self.limit,
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "audit_permissions" => {
+                let tool = AuditPermissions {
+                    path: self.path.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "compare_directories" => {
+                let Some(other_path) = self.other_path.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "other_path is required for compare_directories operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = CompareDirectories {
+                    path_a: self.path.clone(),
+                    path_b: other_path,
+                    compare_mode: self.compare_mode.clone(),
+                    exclude_patterns: self.exclude_patterns.clone(),
+                    include_defaults_excluded: self.include_defaults_excluded.unwrap_or(false),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "compare_files" => {
+                let Some(other_path) = self.other_path.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "other_path is required for compare_files operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = CompareFiles {
+                    path_a: self.path.clone(),
+                    path_b: other_path,
+                    context_lines: self.context_lines,
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "list_tags" => {
+                let tool = ListTagsTool {
+                    path: self.path.clone(),
+                    output_format: self.output_format.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "find_by_tag" => {
+                let Some(tags) = self.tags.clone().filter(|tags| !tags.is_empty()) else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "tags is required for find_by_tag operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = FindByTagTool {
+                    path: self.path.clone(),
+                    tags,
+                    match_all: self.match_all.unwrap_or(false),
+                    output_format: self.output_format.clone(),
                 };
                 tool.run_tool(fs_service).await
             },
@@ -146,25 +475,8 @@ impl SearchAndAnalysisTool {
                     text: format!("Unknown operation: {}", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             }),
-        };
-
-        // Add workflow step if operation was successful
-        if let Ok(ref call_result) = result {
-            if !call_result.is_error.unwrap_or(false) {
-                let result_json = json!({
-                    "operation": self.operation.clone(),
-                    "path": self.path.clone(),
-                    "success": true
-                });
-                add_workflow_step(
-                    format!("search_and_analysis:{}", self.operation),
-                    result_json,
-                    None
-                );
-            }
         }
-
-        result
     }
 }
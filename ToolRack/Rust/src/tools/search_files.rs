@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::fs_service::{FileSystemService, SearchFilesOptions, SearchMatchMode};
+use serde_json::{json, Value};
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,38 +10,65 @@ pub struct SearchFilesTool {
     pub pattern: String,
     #[serde(default)]
     pub include_content: Option<bool>,
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    /// "auto" (default), "glob" (e.g. `*.rs`, `**/test_*.py`) or "substring"
+    #[serde(default)]
+    pub match_mode: Option<String>,
+    #[serde(default)]
+    pub include_defaults_excluded: bool,
 }
 
 impl SearchFilesTool {
-    
+    /// Schema of the `structuredContent` returned next to the text list
+    pub fn output_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "files": { "type": "array", "items": { "type": "string" } },
+                "warnings": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["files", "warnings"]
+        })
+    }
 
     pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
-        let include_content = self.include_content.unwrap_or(false);
+        let match_mode = match self.match_mode.as_deref() {
+            Some(mode) => mode.parse::<SearchMatchMode>().map_err(CallToolError::new)?,
+            None => SearchMatchMode::Auto,
+        };
+        let options = SearchFilesOptions {
+            pattern: self.pattern.clone(),
+            match_mode,
+            include_content: self.include_content.unwrap_or(false),
+            follow_symlinks: self.follow_symlinks,
+            include_defaults_excluded: self.include_defaults_excluded,
+        };
 
-        match fs_service.search_files(Path::new(&self.directory), &self.pattern, include_content).await {
-            Ok(results) => {
-                if results.is_empty() {
-                    Ok(CallToolResult {
-                        content: vec![Content::Text(TextContent {
-                            text: format!("No files found matching pattern '{}' in directory '{}'", self.pattern, self.directory),
-                        })],
-                        is_error: Some(false),
-                    })
+        match fs_service.search_files(Path::new(&self.directory), options).await {
+            Ok(outcome) => {
+                let mut output = if outcome.files.is_empty() {
+                    format!("No files found matching pattern '{}' in directory '{}'", self.pattern, self.directory)
                 } else {
-                    let mut output = format!("Found {} file(s) matching pattern '{}':\n\n", results.len(), self.pattern);
-                    for (i, file_path) in results.iter().enumerate() {
+                    let mut output = format!("Found {} file(s) matching pattern '{}':\n\n", outcome.files.len(), self.pattern);
+                    for (i, file_path) in outcome.files.iter().enumerate() {
                         output.push_str(&format!("{}. {}\n", i + 1, file_path));
                     }
-
-                    Ok(CallToolResult {
-                        content: vec![Content::Text(TextContent {
-                            text: output,
-                        })],
-                        is_error: Some(false),
-                    })
+                    output
+                };
+                if !outcome.warnings.is_empty() {
+                    output.push_str(&format!("\nWarnings:\n{}\n", outcome.warnings.join("\n")));
                 }
+
+                Ok(CallToolResult {
+                    content: vec![Content::Text(TextContent {
+                        text: output,
+                    })],
+                    is_error: Some(false),
+                    structured_content: Some(json!({ "files": outcome.files, "warnings": outcome.warnings })),
+                })
             }
             Err(e) => Err(CallToolError::new(e)),
         }
     }
-}
\ No newline at end of file
+}
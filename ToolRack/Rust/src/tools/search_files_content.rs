@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use crate::fs_service::{FileSearchResult, FileSystemService};
+use crate::fs_service::{utils::path_text, FileSearchResult, FileSystemService};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
 use std::fmt::Write;
 
@@ -24,7 +24,7 @@ impl SearchFilesContent {
         let mut output = String::with_capacity(estimated_capacity);
         for file_result in results {
             // Push file path
-            let _ = writeln!(output, "{}", file_result.file_path.display());
+            let _ = writeln!(output, "{}", path_text(&file_result.file_path));
             // Push each match line
             for m in &file_result.matches {
                 // Format: "  line:col: text snippet"
@@ -59,6 +59,7 @@ impl SearchFilesContent {
                     return Ok(CallToolResult {
                         content: vec![],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 Ok(CallToolResult {
@@ -66,11 +67,13 @@ impl SearchFilesContent {
                         text: self.format_result(results),
                     })],
                     is_error: Some(false),
+                    structured_content: None,
                 })
             }
             Err(_err) => Ok(CallToolResult {
                 content: vec![],
                 is_error: Some(true),
+                structured_content: None,
             }),
         }
     }
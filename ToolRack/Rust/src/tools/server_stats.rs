@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use crate::fs_service::utils::format_bytes;
+use crate::mcp_types::{Tool, CallToolResult, Content, TextContent, CallToolError};
+use crate::metrics;
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerStatsTool {
+    /// Start counting from zero after reading the counters
+    #[serde(default)]
+    pub reset: bool,
+    #[serde(default)]
+    pub output_format: Option<String>,
+}
+
+impl ServerStatsTool {
+    pub fn tool_definition() -> Tool {
+        Tool {
+            name: "server_stats".to_string(),
+            description: Some("Report per-tool call counts, error counts, latencies (mean, p50, p95, max) and file bytes read and written since the server started or the counters were last reset.".to_string()),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "reset": {
+                        "type": "boolean",
+                        "description": "Start counting from zero after reading the counters",
+                        "default": false
+                    },
+                    "output_format": {
+                        "type": "string",
+                        "enum": ["text", "json"],
+                        "default": "text"
+                    }
+                }
+            }),
+            output_schema: None,
+        }
+    }
+
+    pub async fn run_tool(self) -> Result<CallToolResult, CallToolError> {
+        let stats = metrics::snapshot();
+
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&stats).map_err(CallToolError::new)?,
+            _ => {
+                let mut output = format!(
+                    "{} call(s), {} error(s) since {}; {} read, {} written\n",
+                    stats.calls,
+                    stats.errors,
+                    stats.since.format("%Y-%m-%d %H:%M:%S UTC"),
+                    format_bytes(stats.bytes_read),
+                    format_bytes(stats.bytes_written)
+                );
+                for tool in &stats.tools {
+                    writeln!(
+                        output,
+                        "  {}  {} call(s), {} error(s); mean {:.1} ms, p50 {:.1}, p95 {:.1}, max {:.1}; {} read, {} written",
+                        tool.name,
+                        tool.calls,
+                        tool.errors,
+                        tool.mean_ms,
+                        tool.p50_ms,
+                        tool.p95_ms,
+                        tool.max_ms,
+                        format_bytes(tool.bytes_read),
+                        format_bytes(tool.bytes_written)
+                    )
+                    .map_err(CallToolError::new)?;
+                }
+                output
+            }
+        };
+
+        if self.reset {
+            metrics::reset();
+        }
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text: content })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
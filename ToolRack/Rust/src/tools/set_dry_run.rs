@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{Tool, CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::FileSystemService;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether calls run against a throwaway sandbox and return a preview of
+/// their changes instead of writing. Started with --dry-run, it stays on.
+#[derive(Debug, Default)]
+pub struct DryRunMode {
+    enabled: AtomicBool,
+    locked: bool,
+}
+
+impl DryRunMode {
+    pub fn new(from_command_line: bool) -> Self {
+        Self { enabled: AtomicBool::new(from_command_line), locked: from_command_line }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetDryRunTool {
+    pub enabled: bool,
+}
+
+impl SetDryRunTool {
+    pub fn tool_definition() -> Tool {
+        Tool {
+            name: "set_dry_run".to_string(),
+            description: Some("Turn server-wide dry-run mode on or off. While it is on, every tool call that would change files returns a preview of the changes (diffs for edits, file lists for deletes, copies and moves) and nothing is written; operations that cannot be previewed are refused. A server started with --dry-run cannot leave it.".to_string()),
+            input_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "enabled": {
+                        "type": "boolean",
+                        "description": "true to only preview changes from now on, false to write them again"
+                    }
+                },
+                "required": ["enabled"]
+            }),
+            output_schema: None,
+        }
+    }
+
+    pub async fn run_tool(self, mode: &DryRunMode, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let refused = if !self.enabled && mode.locked {
+            Some("The server was started with --dry-run, so dry-run mode cannot be turned off.")
+        } else if self.enabled && fs_service.sandbox().is_some() {
+            Some("Dry-run mode is not available in --sandbox mode; use diff_sandbox to review changes instead.")
+        } else {
+            None
+        };
+        if let Some(message) = refused {
+            return Ok(CallToolResult {
+                content: vec![Content::Text(TextContent { text: message.to_string() })],
+                is_error: Some(true),
+                structured_content: None,
+            });
+        }
+
+        let was_enabled = mode.enabled.swap(self.enabled, Ordering::Relaxed);
+        let text = match (was_enabled, self.enabled) {
+            (false, true) => "Dry-run mode is on: changes are previewed and nothing is written to disk.",
+            (true, false) => "Dry-run mode is off: changes are written to disk again.",
+            (_, true) => "Dry-run mode was already on.",
+            (_, false) => "Dry-run mode was already off.",
+        };
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text: text.to_string() })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use crate::mcp_types::{Tool, CallToolResult, Content, TextContent, CallToolError};
+use crate::client::ClientPeer;
 use crate::fs_service::FileSystemService;
 use crate::tools::*;
 use crate::task_state::{get_current_mode, add_workflow_step};
@@ -23,20 +24,82 @@ pub struct SingleFileOperationsTool {
     pub dry_run: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub follow_symlinks: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_end: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_offset: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_line_numbers: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub focus: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_chars: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create_parents: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bytes_per_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ascii: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub destination: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub part_lines: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name_template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remove_tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clear_notes: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<crate::fs_service::checksum::HashAlgorithm>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub patch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuzz: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_line_count: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_hash: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub with_owner: Option<bool>,
 }
 
 impl SingleFileOperationsTool {
     pub fn tool_definition() -> Tool {
         Tool {
             name: "single_file_operations".to_string(),
-            description: Some("Perform various operations on a single file including read, write, edit, get info, head, tail, read lines, and read media files.".to_string()),
+            description: Some("Perform various operations on a single file including read, write, edit, apply a unified diff patch, get info, head, tail, read lines, read media files, summarize a file using the client's model (sampling), download a URL into a file, hexdump binary files, split a file into parts, checksum a file, create an empty file or update its times (touch), and tag or annotate a path in the persistent tag store.".to_string()),
             input_schema: serde_json::json!({
                 "type": "object",
                 "properties": {
                     "operation": {
                         "type": "string",
                         "description": "The operation to perform",
-                        "enum": ["read_file", "write_file", "edit_file", "get_file_info", "head_file", "tail_file", "read_file_lines", "read_media_file"]
+                        "enum": ["read_file", "write_file", "edit_file", "apply_patch", "get_file_info", "head_file", "tail_file", "read_file_lines", "read_media_file", "summarize_file", "download_file", "hexdump_file", "split_file", "checksum_file", "tag_path", "annotate_path", "touch_file"]
                     },
                     "path": {
                         "type": "string",
@@ -52,7 +115,85 @@ impl SingleFileOperationsTool {
                     },
                     "offset": {
                         "type": "number",
-                        "description": "Line offset for read_file_lines operation"
+                        "description": "Lines to skip for read_file_lines operation (from the start, from the end with from_end, or after byte_offset)"
+                    },
+                    "from_end": {
+                        "type": "boolean",
+                        "description": "Count lines backwards from the end of the file (for read_file_lines operation); for hexdump_file, show the bytes that end byte_offset bytes before the end",
+                        "default": false
+                    },
+                    "byte_offset": {
+                        "type": "number",
+                        "description": "Start reading at this byte position, e.g. the offset reported by a previous truncated read (for read_file_lines and hexdump_file operations)"
+                    },
+                    "offset_bytes": {
+                        "type": "number",
+                        "description": "Read only from this byte position on, without loading the rest of the file (for read_file operation); a character cut by the position is skipped"
+                    },
+                    "length_bytes": {
+                        "type": "number",
+                        "description": "Read at most this many bytes, up to 4 MiB (for read_file operation); the reply says where to continue"
+                    },
+                    "cursor": {
+                        "type": "string",
+                        "description": "Continue reading a file over 4 MiB where the previous chunk ended, passing the cursor that read_file returned (for read_file operation)"
+                    },
+                    "encoding": {
+                        "type": "string",
+                        "description": "Text encoding of the file, e.g. utf-16le, latin-1 or shift_jis, or auto to guess (for read_file and edit_file operations; default utf-8). A byte order mark overrides it, and edits are saved in the encoding read"
+                    },
+                    "length": {
+                        "type": "number",
+                        "description": "Bytes to show for hexdump_file operation (default 256, at most 65536)"
+                    },
+                    "bytes_per_line": {
+                        "type": "number",
+                        "description": "Bytes per hexdump_file line (default 16, at most 64)"
+                    },
+                    "ascii": {
+                        "type": "boolean",
+                        "description": "Show printable characters beside the hex for hexdump_file operation",
+                        "default": true
+                    },
+                    "destination": {
+                        "type": "string",
+                        "description": "Directory the parts are written to, created if missing (required for split_file operation)"
+                    },
+                    "part_bytes": {
+                        "type": "number",
+                        "description": "Size of each part in bytes for split_file operation (give this or part_lines)"
+                    },
+                    "part_lines": {
+                        "type": "number",
+                        "description": "Lines per part for split_file operation; parts always end at a newline (give this or part_bytes)"
+                    },
+                    "name_template": {
+                        "type": "string",
+                        "description": "File name of each part for split_file operation, default '{name}.{n}'. {name} is the source file name, {stem} and {ext} its parts, {n} the 1-based part number padded to at least 3 digits"
+                    },
+                    "tags": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Tags to add for tag_path operation, e.g. 'reviewed' or 'generated'; up to 64 characters, no spaces"
+                    },
+                    "remove_tags": {
+                        "type": "array",
+                        "items": { "type": "string" },
+                        "description": "Tags to remove for tag_path operation"
+                    },
+                    "note": {
+                        "type": "string",
+                        "description": "Note to attach for annotate_path operation"
+                    },
+                    "clear_notes": {
+                        "type": "boolean",
+                        "description": "For annotate_path: drop the earlier notes on the path first (with no note, just clears them)",
+                        "default": false
+                    },
+                    "with_line_numbers": {
+                        "type": "boolean",
+                        "description": "Prefix each line with its 1-based line number (for read_file and read_file_lines operations)",
+                        "default": false
                     },
                     "limit": {
                         "type": "number",
@@ -63,29 +204,98 @@ impl SingleFileOperationsTool {
                         "items": {
                             "type": "object",
                             "properties": {
-                                "oldText": {"type": "string", "description": "Text to replace"},
-                                "newText": {"type": "string", "description": "Replacement text"}
-                            },
-                            "required": ["oldText", "newText"]
+                                "type": {
+                                    "type": "string",
+                                    "enum": ["replace", "insert_at_line", "replace_lines", "delete_lines"],
+                                    "default": "replace"
+                                },
+                                "oldText": {"type": "string", "description": "Text to replace (replace edits)"},
+                                "newText": {"type": "string", "description": "Replacement or inserted text"},
+                                "line": {"type": "number", "description": "1-based line to insert before (insert_at_line); one past the last line appends"},
+                                "start": {"type": "number", "description": "First line to replace or delete (replace_lines, delete_lines)"},
+                                "end": {"type": "number", "description": "Last line to replace or delete, inclusive; defaults to start"}
+                            }
                         },
-                        "description": "Array of edit operations for edit_file"
+                        "description": "Array of edit operations for edit_file, applied in order; line numbers count the file as earlier edits left it"
                     },
                     "dry_run": {
                         "type": "boolean",
-                        "description": "Preview changes without applying (for edit_file operation)",
+                        "description": "Preview changes without applying (for edit_file and apply_patch operations)",
                         "default": false
                     },
                     "max_bytes": {
                         "type": "number",
-                        "description": "Maximum file size in bytes for media files"
+                        "description": "Media files larger than this are returned as a resource link for resources/read instead of inline data (for read_media_file operation, default 4 MiB)"
+                    },
+                    "follow_symlinks": {
+                        "type": "boolean",
+                        "description": "Report on the symlink target instead of the link itself (for get_file_info operation)",
+                        "default": false
+                    },
+                    "with_line_count": {
+                        "type": "boolean",
+                        "description": "Also count the lines of a text file (for get_file_info operation)",
+                        "default": false
+                    },
+                    "with_hash": {
+                        "type": "boolean",
+                        "description": "Also hash the file's content with algorithm (for get_file_info operation)",
+                        "default": false
+                    },
+                    "with_owner": {
+                        "type": "boolean",
+                        "description": "Also report the owning user and group, on Unix (for get_file_info operation)",
+                        "default": false
+                    },
+                    "focus": {
+                        "type": "string",
+                        "description": "What the summary should concentrate on (for summarize_file operation)"
+                    },
+                    "chunk_chars": {
+                        "type": "number",
+                        "description": "Characters of the file sent per sampling request (for summarize_file operation, default 24000, at most 20 requests)"
+                    },
+                    "url": {
+                        "type": "string",
+                        "description": "http(s) URL to fetch into path; must match the server's download allowlist (required for download_file operation)"
+                    },
+                    "overwrite": {
+                        "type": "boolean",
+                        "description": "Replace an existing file at path (for download_file operation) or existing parts (for split_file operation)",
+                        "default": false
+                    },
+                    "create_parents": {
+                        "type": "boolean",
+                        "description": "Create the file's missing parent directories first, like mkdir -p (for write_file and touch_file operations); they must be allowed like the file",
+                        "default": false
+                    },
+                    "algorithm": {
+                        "type": "string",
+                        "description": "Hash function for checksum_file operation, and for get_file_info with with_hash",
+                        "enum": ["sha256", "sha512", "md5"],
+                        "default": "sha256"
+                    },
+                    "expected": {
+                        "type": "string",
+                        "description": "Hex digest to compare the file's checksum with, e.g. one published next to a download (for checksum_file operation)"
+                    },
+                    "patch": {
+                        "type": "string",
+                        "description": "Unified diff for this one file, as written by diff -u or git diff (required for apply_patch operation)"
+                    },
+                    "fuzz": {
+                        "type": "number",
+                        "description": "Context lines that may be ignored at each end of a hunk whose context has drifted (for apply_patch operation)",
+                        "default": 2
                     }
                 },
                 "required": ["operation", "path"]
             }),
+            output_schema: None,
         }
     }
 
-    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+    pub async fn run_tool(self, fs_service: &FileSystemService, client: &ClientPeer) -> Result<CallToolResult, CallToolError> {
         let current_mode = get_current_mode();
         let available_tools = current_mode.as_ref().map(|m| m.available_tools.clone()).unwrap_or_default();
 
@@ -96,12 +306,46 @@ impl SingleFileOperationsTool {
                     text: format!("Operation '{}' is not available in the current operation mode. Use 'start_operation_mode' with 'single_file_operations' to enable this operation.", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             });
         }
 
-        let result = match self.operation.as_str() {
+        let operation = self.operation.clone();
+        let path = self.path.clone();
+        let result = self.run_operation(fs_service, client).await;
+
+        // Add workflow step if operation was successful
+        if let Ok(ref call_result) = result {
+            if !call_result.is_error.unwrap_or(false) {
+                let result_json = json!({
+                    "operation": operation.clone(),
+                    "path": path,
+                    "success": true
+                });
+                add_workflow_step(
+                    format!("single_file_operations:{}", operation),
+                    result_json,
+                    undo_metadata(fs_service)
+                );
+            }
+        }
+
+        result
+    }
+
+    /// Run the operation without the operation-mode check, for the flat
+    /// tools of `--tool-style flat`
+    pub async fn run_operation(self, fs_service: &FileSystemService, client: &ClientPeer) -> Result<CallToolResult, CallToolError> {
+        match self.operation.as_str() {
             "read_file" => {
-                let tool = ReadFileTool { path: self.path.clone() };
+                let tool = ReadFileTool {
+                    path: self.path.clone(),
+                    with_line_numbers: self.with_line_numbers.unwrap_or(false),
+                    offset_bytes: self.offset_bytes,
+                    length_bytes: self.length_bytes,
+                    cursor: self.cursor.clone(),
+                    encoding: self.encoding.clone(),
+                };
                 tool.run_tool(fs_service).await
             },
             "write_file" => {
@@ -111,9 +355,14 @@ impl SingleFileOperationsTool {
                             text: "Content is required for write_file operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
-                let tool = WriteFileTool { path: self.path.clone(), content: self.content.unwrap() };
+                let tool = WriteFileTool {
+                    path: self.path.clone(),
+                    content: self.content.unwrap(),
+                    create_parents: self.create_parents.unwrap_or(false),
+                };
                 tool.run_tool(fs_service).await
             },
             "edit_file" => {
@@ -123,17 +372,44 @@ impl SingleFileOperationsTool {
                             text: "Edits array is required for edit_file operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = EditFileTool {
                     path: self.path.clone(),
                     edits: self.edits.unwrap(),
                     dry_run: self.dry_run,
+                    encoding: self.encoding.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "apply_patch" => {
+                let Some(patch) = self.patch.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "Patch is required for apply_patch operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = ApplyPatchTool {
+                    path: self.path.clone(),
+                    patch,
+                    fuzz: self.fuzz,
+                    dry_run: self.dry_run,
                 };
                 tool.run_tool(fs_service).await
             },
             "get_file_info" => {
-                let tool = GetFileInfoTool { path: self.path.clone() };
+                let tool = GetFileInfoTool {
+                    path: self.path.clone(),
+                    follow_symlinks: self.follow_symlinks.unwrap_or(false),
+                    with_line_count: self.with_line_count.unwrap_or(false),
+                    with_hash: self.with_hash.unwrap_or(false),
+                    algorithm: self.algorithm.unwrap_or_default(),
+                    with_owner: self.with_owner.unwrap_or(false),
+                };
                 tool.run_tool(fs_service).await
             },
             "head_file" => {
@@ -143,6 +419,7 @@ impl SingleFileOperationsTool {
                             text: "Lines parameter is required for head_file operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = HeadFile { path: self.path.clone(), lines: self.lines.unwrap() };
@@ -155,24 +432,29 @@ impl SingleFileOperationsTool {
                             text: "Lines parameter is required for tail_file operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = TailFile { path: self.path.clone(), lines: self.lines.unwrap() };
                 tool.run_tool(fs_service).await
             },
             "read_file_lines" => {
-                if self.offset.is_none() {
+                if self.offset.is_none() && self.from_end.is_none() && self.byte_offset.is_none() {
                     return Ok(CallToolResult {
                         content: vec![Content::Text(TextContent {
-                            text: "Offset parameter is required for read_file_lines operation".to_string(),
+                            text: "Offset, from_end, or byte_offset is required for read_file_lines operation".to_string(),
                         })],
                         is_error: Some(true),
+                        structured_content: None,
                     });
                 }
                 let tool = ReadFileLines {
                     path: self.path.clone(),
-                    offset: self.offset.unwrap(),
+                    offset: self.offset.unwrap_or(0),
                     limit: self.limit,
+                    from_end: self.from_end.unwrap_or(false),
+                    byte_offset: self.byte_offset,
+                    with_line_numbers: self.with_line_numbers.unwrap_or(false),
                 };
                 tool.run_tool(fs_service).await
             },
@@ -183,30 +465,110 @@ impl SingleFileOperationsTool {
                 };
                 tool.run_tool(fs_service).await
             },
+            "summarize_file" => {
+                let tool = SummarizeFile {
+                    path: self.path.clone(),
+                    focus: self.focus.clone(),
+                    chunk_chars: self.chunk_chars,
+                };
+                tool.run_tool(fs_service, client).await
+            },
+            "download_file" => {
+                let Some(url) = self.url.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "URL is required for download_file operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = DownloadFile {
+                    url,
+                    path: self.path.clone(),
+                    overwrite: self.overwrite.unwrap_or(false),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "hexdump_file" => {
+                let tool = HexdumpFile {
+                    path: self.path.clone(),
+                    byte_offset: self.byte_offset.unwrap_or(0),
+                    from_end: self.from_end.unwrap_or(false),
+                    length: self.length,
+                    bytes_per_line: self.bytes_per_line,
+                    ascii: self.ascii,
+                };
+                tool.run_tool(fs_service).await
+            },
+            "split_file" => {
+                let Some(destination) = self.destination.clone() else {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "Destination is required for split_file operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                };
+                let tool = SplitFile {
+                    path: self.path.clone(),
+                    destination,
+                    part_bytes: self.part_bytes,
+                    part_lines: self.part_lines,
+                    name_template: self.name_template.clone(),
+                    overwrite: self.overwrite.unwrap_or(false),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "tag_path" => {
+                let tool = TagPathTool {
+                    path: self.path.clone(),
+                    tags: self.tags.clone().unwrap_or_default(),
+                    remove_tags: self.remove_tags.clone().unwrap_or_default(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "checksum_file" => {
+                let tool = ChecksumFileTool {
+                    path: self.path.clone(),
+                    algorithm: self.algorithm.unwrap_or_default(),
+                    expected: self.expected.clone(),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "touch_file" => {
+                let tool = TouchFileTool {
+                    path: self.path.clone(),
+                    create_parents: self.create_parents.unwrap_or(false),
+                };
+                tool.run_tool(fs_service).await
+            },
+            "annotate_path" => {
+                let clear_notes = self.clear_notes.unwrap_or(false);
+                if self.note.is_none() && !clear_notes {
+                    return Ok(CallToolResult {
+                        content: vec![Content::Text(TextContent {
+                            text: "Note is required for annotate_path operation".to_string(),
+                        })],
+                        is_error: Some(true),
+                        structured_content: None,
+                    });
+                }
+                let tool = AnnotatePathTool {
+                    path: self.path.clone(),
+                    note: self.note.clone(),
+                    clear_notes,
+                };
+                tool.run_tool(fs_service).await
+            },
             _ => Ok(CallToolResult {
                 content: vec![Content::Text(TextContent {
                     text: format!("Unknown operation: {}", self.operation),
                 })],
                 is_error: Some(true),
+                structured_content: None,
             }),
-        };
-
-        // Add workflow step if operation was successful
-        if let Ok(ref call_result) = result {
-            if !call_result.is_error.unwrap_or(false) {
-                let result_json = json!({
-                    "operation": self.operation.clone(),
-                    "path": self.path.clone(),
-                    "success": true
-                });
-                add_workflow_step(
-                    format!("single_file_operations:{}", self.operation),
-                    result_json,
-                    None
-                );
-            }
         }
-
-        result
     }
 }
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{split::SplitBy, FileSystemService};
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitFile {
+    pub path: String,
+    /// Directory the parts are written to
+    pub destination: String,
+    pub part_bytes: Option<u64>,
+    pub part_lines: Option<u64>,
+    /// File name of each part, see `split::part_name`
+    pub name_template: Option<String>,
+    pub overwrite: bool,
+}
+
+impl SplitFile {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let split_by = match (self.part_bytes, self.part_lines) {
+            (Some(bytes), None) => SplitBy::Bytes(bytes),
+            (None, Some(lines)) => SplitBy::Lines(lines),
+            _ => {
+                return Ok(CallToolResult {
+                    content: vec![Content::Text(TextContent {
+                        text: "Exactly one of part_bytes or part_lines is required for split_file operation".to_string(),
+                    })],
+                    is_error: Some(true),
+                    structured_content: None,
+                });
+            }
+        };
+
+        let manifest = fs_service
+            .split_file(
+                Path::new(&self.path),
+                Path::new(&self.destination),
+                split_by,
+                self.name_template.as_deref(),
+                self.overwrite,
+            )
+            .await
+            .map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: serde_json::to_string_pretty(&manifest).map_err(CallToolError::new)?,
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
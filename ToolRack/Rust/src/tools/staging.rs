@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{staging::CommitAction, utils::format_bytes, FileSystemService};
+use crate::tools::BatchOperationsTool;
+use std::fmt::Write;
+use std::path::Path;
+
+fn text_result(text: String) -> CallToolResult {
+    CallToolResult {
+        content: vec![Content::Text(TextContent { text })],
+        is_error: None,
+        structured_content: None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageFilesTool {
+    /// Files or directories to stage; glob patterns are expanded
+    pub paths: Vec<String>,
+    pub output_format: Option<String>,
+}
+
+impl StageFilesTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        if self.paths.is_empty() {
+            return Err(CallToolError::new("stage_files requires at least one path"));
+        }
+        let outcome = fs_service.stage_files(&self.paths).await.map_err(CallToolError::new)?;
+
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&outcome).map_err(CallToolError::new)?,
+            _ => {
+                let mut output = format!(
+                    "Staged {} new path(s); {} already staged. {} path(s) staged in total.\n",
+                    outcome.added.len(),
+                    outcome.already_staged.len(),
+                    outcome.total_staged
+                );
+                for path in &outcome.added {
+                    writeln!(output, "  + {}", path).map_err(CallToolError::new)?;
+                }
+                for note in &outcome.notes {
+                    writeln!(output, "Note: {}", note).map_err(CallToolError::new)?;
+                }
+                output
+            }
+        };
+        Ok(text_result(content))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnstageFilesTool {
+    /// Paths to remove from the staging area; empty clears it
+    pub paths: Vec<String>,
+}
+
+impl UnstageFilesTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let removed = fs_service.unstage_files(&self.paths).await.map_err(CallToolError::new)?;
+        let mut output = format!("Unstaged {} path(s).\n", removed.len());
+        for path in &removed {
+            writeln!(output, "  - {}", path).map_err(CallToolError::new)?;
+        }
+        Ok(text_result(output))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListStagedTool {
+    pub output_format: Option<String>,
+}
+
+impl ListStagedTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let staged = fs_service.list_staged().await;
+
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&staged).map_err(CallToolError::new)?,
+            _ if staged.is_empty() => "Nothing is staged.".to_string(),
+            _ => {
+                let total: u64 = staged.iter().filter_map(|f| f.size).sum();
+                let mut output = format!("{} path(s) staged ({}):\n", staged.len(), format_bytes(total));
+                for file in &staged {
+                    let size = match (file.size, file.is_dir) {
+                        (_, true) => "[DIR]".to_string(),
+                        (Some(size), false) => format_bytes(size),
+                        (None, false) => "missing".to_string(),
+                    };
+                    writeln!(output, "  {:>10}  {}", size, file.path).map_err(CallToolError::new)?;
+                }
+                output
+            }
+        };
+        Ok(text_result(content))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitStagedTool {
+    /// Directory the staged paths are copied or moved into
+    pub destination: String,
+    /// "copy" (default) or "move"
+    pub action: Option<String>,
+    pub output_format: Option<String>,
+}
+
+impl CommitStagedTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let action: CommitAction = match self.action.as_deref() {
+            Some(action) => action.parse().map_err(CallToolError::new)?,
+            None => CommitAction::default(),
+        };
+        let report = fs_service
+            .commit_staged(Path::new(&self.destination), action)
+            .await
+            .map_err(CallToolError::new)?;
+
+        let output_format = self.output_format.as_deref().unwrap_or("text");
+        let content = BatchOperationsTool::format_output(&report, output_format).map_err(CallToolError::new)?;
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: content,
+            })],
+            is_error: Some(!report.committed),
+            structured_content: None,
+        })
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+use crate::client::ClientPeer;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{utils::format_bytes, FileSystemService};
+use std::path::Path;
+
+/// Characters of file text sent in one sampling request
+pub const DEFAULT_CHUNK_CHARS: usize = 24_000;
+/// Files needing more chunks than this are refused rather than flooding the client
+pub const MAX_CHUNKS: usize = 20;
+const CHUNK_SUMMARY_TOKENS: u32 = 400;
+const FINAL_SUMMARY_TOKENS: u32 = 800;
+
+const SYSTEM_PROMPT: &str = "You summarize files for another assistant that cannot see them. \
+Be concrete: name the functions, sections, settings, or records that matter, and keep the summary short.";
+
+/// Split `text` into chunks of at most `max_chars` characters, breaking at line
+/// ends where possible
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<&str> {
+    let max_chars = max_chars.max(1);
+    let mut chunks = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let Some((limit, _)) = rest.char_indices().nth(max_chars) else {
+            chunks.push(rest);
+            break;
+        };
+        let end = match rest[..limit].rfind('\n') {
+            Some(newline) if newline > 0 => newline + 1,
+            _ => limit,
+        };
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+    chunks
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummarizeFile {
+    pub path: String,
+    /// What the summary should concentrate on
+    pub focus: Option<String>,
+    /// Characters per sampling request (default 24,000)
+    pub chunk_chars: Option<usize>,
+}
+
+impl SummarizeFile {
+    fn instructions(&self) -> String {
+        match &self.focus {
+            Some(focus) => format!(" Focus on: {}.", focus),
+            None => String::new(),
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService, client: &ClientPeer) -> Result<CallToolResult, CallToolError> {
+        if !client.supports("sampling") {
+            return Err(CallToolError::new(
+                "summarize_file needs a client that supports MCP sampling; use read_file_lines to read the file in parts instead",
+            ));
+        }
+        let text = fs_service.read_file(Path::new(&self.path)).await.map_err(CallToolError::new)?;
+        let chunks = chunk_text(&text, self.chunk_chars.unwrap_or(DEFAULT_CHUNK_CHARS));
+        if chunks.len() > MAX_CHUNKS {
+            return Err(CallToolError::new(format!(
+                "{} would need {} summarization requests (limit {}); raise chunk_chars or summarize a range read with read_file_lines",
+                self.path,
+                chunks.len(),
+                MAX_CHUNKS
+            )));
+        }
+        let name = Path::new(&self.path).file_name().unwrap_or_default().to_string_lossy().into_owned();
+
+        let summary = if chunks.len() <= 1 {
+            let prompt = format!("Summarize the file {}.{}\n\n{}", name, self.instructions(), text);
+            client.create_message(SYSTEM_PROMPT, &prompt, FINAL_SUMMARY_TOKENS).await.map_err(CallToolError::new)?
+        } else {
+            // Chunks go one at a time so the client is never asked to approve a burst of requests
+            let mut partials = Vec::with_capacity(chunks.len());
+            for (index, chunk) in chunks.iter().enumerate() {
+                let prompt = format!(
+                    "This is part {} of {} of the file {}. Summarize this part.{}\n\n{}",
+                    index + 1,
+                    chunks.len(),
+                    name,
+                    self.instructions(),
+                    chunk
+                );
+                let partial = client
+                    .create_message(SYSTEM_PROMPT, &prompt, CHUNK_SUMMARY_TOKENS)
+                    .await
+                    .map_err(|e| CallToolError::new(format!("summarizing part {}: {}", index + 1, e)))?;
+                partials.push(format!("Part {}:\n{}", index + 1, partial));
+            }
+            let prompt = format!(
+                "Combine these summaries of consecutive parts of the file {} into one summary of the whole file.{}\n\n{}",
+                name,
+                self.instructions(),
+                partials.join("\n\n")
+            );
+            client.create_message(SYSTEM_PROMPT, &prompt, FINAL_SUMMARY_TOKENS).await.map_err(CallToolError::new)?
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent {
+                text: format!(
+                    "Summary of {} ({}, {} part(s)):\n\n{}",
+                    self.path,
+                    format_bytes(text.len() as u64),
+                    chunks.len().max(1),
+                    summary
+                ),
+            })],
+            is_error: Some(false),
+            structured_content: None,
+        })
+    }
+}
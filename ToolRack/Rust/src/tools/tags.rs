@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{tags::TaggedPath, FileSystemService};
+use std::path::Path;
+
+fn text_result(text: String) -> CallToolResult {
+    CallToolResult {
+        content: vec![Content::Text(TextContent { text })],
+        is_error: Some(false),
+        structured_content: None,
+    }
+}
+
+fn describe(tagged: &TaggedPath) -> String {
+    let mut output = format!(
+        "{}{}\n  tags: {}\n",
+        tagged.path,
+        if tagged.exists { "" } else { " (missing)" },
+        if tagged.metadata.tags.is_empty() {
+            "-".to_string()
+        } else {
+            tagged.metadata.tags.iter().cloned().collect::<Vec<_>>().join(", ")
+        }
+    );
+    for annotation in &tagged.metadata.notes {
+        output.push_str(&format!(
+            "  note ({}): {}\n",
+            annotation.created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            annotation.note
+        ));
+    }
+    output
+}
+
+fn format_paths(paths: &[TaggedPath], output_format: Option<&str>) -> Result<String, CallToolError> {
+    Ok(match output_format.unwrap_or("text") {
+        "json" => serde_json::to_string_pretty(paths).map_err(CallToolError::new)?,
+        _ if paths.is_empty() => "No tagged paths found".to_string(),
+        _ => paths.iter().map(describe).collect(),
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagPathTool {
+    pub path: String,
+    pub tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+}
+
+impl TagPathTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let tagged = fs_service
+            .tag_path(Path::new(&self.path), &self.tags, &self.remove_tags)
+            .await
+            .map_err(CallToolError::new)?;
+        Ok(text_result(describe(&tagged)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotatePathTool {
+    pub path: String,
+    pub note: Option<String>,
+    /// Drop the earlier notes first
+    pub clear_notes: bool,
+}
+
+impl AnnotatePathTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let tagged = fs_service
+            .annotate_path(Path::new(&self.path), self.note.as_deref().unwrap_or_default(), self.clear_notes)
+            .await
+            .map_err(CallToolError::new)?;
+        Ok(text_result(describe(&tagged)))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TagSummary<'a> {
+    scope: &'a str,
+    /// Number of paths carrying each tag
+    tags: BTreeMap<&'a str, usize>,
+    paths: &'a [TaggedPath],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListTagsTool {
+    /// Only paths at or below this one are counted
+    pub path: String,
+    pub output_format: Option<String>,
+}
+
+impl ListTagsTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let paths = fs_service.tagged_paths(Path::new(&self.path)).await.map_err(CallToolError::new)?;
+        let mut tags = BTreeMap::new();
+        for tag in paths.iter().flat_map(|tagged| &tagged.metadata.tags) {
+            *tags.entry(tag.as_str()).or_insert(0) += 1;
+        }
+
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&TagSummary { scope: &self.path, tags, paths: &paths })
+                .map_err(CallToolError::new)?,
+            _ if paths.is_empty() => format!("No tagged paths under {}", self.path),
+            _ => {
+                let mut output = format!("Tags under {} ({} paths):\n", self.path, paths.len());
+                for (tag, count) in &tags {
+                    output.push_str(&format!("  {} ({})\n", tag, count));
+                }
+                let annotated = paths.iter().filter(|tagged| !tagged.metadata.notes.is_empty()).count();
+                if annotated > 0 {
+                    output.push_str(&format!("{} paths have notes; use find_by_tag or get them with output_format json\n", annotated));
+                }
+                output
+            }
+        };
+        Ok(text_result(content))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FindByTagTool {
+    /// Only paths at or below this one are returned
+    pub path: String,
+    pub tags: Vec<String>,
+    /// Require every tag instead of any of them
+    pub match_all: bool,
+    pub output_format: Option<String>,
+}
+
+impl FindByTagTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let paths = fs_service
+            .find_by_tag(Path::new(&self.path), &self.tags, self.match_all)
+            .await
+            .map_err(CallToolError::new)?;
+        Ok(text_result(format_paths(&paths, self.output_format.as_deref())?))
+    }
+}
@@ -23,6 +23,7 @@ impl TailFile {
                 text: result,
             })],
             is_error: Some(false),
+            structured_content: None,
         })
     }
 }
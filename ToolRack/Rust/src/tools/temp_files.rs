@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{temp::TempEntry, FileSystemService};
+use crate::task_state::get_current_mode;
+
+fn created_result(entry: &TempEntry, what: &str) -> CallToolResult {
+    let lifetime = if entry.mode.is_some() {
+        "removed when the current operation mode completes"
+    } else {
+        "removed when the session ends"
+    };
+    CallToolResult {
+        content: vec![Content::Text(TextContent {
+            text: format!("Created temporary {} {} ({})", what, entry.path, lifetime),
+        })],
+        is_error: Some(false),
+        structured_content: None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTempFileTool {
+    pub prefix: Option<String>,
+    /// Appended to the name, e.g. an extension
+    pub suffix: Option<String>,
+    /// Initial contents; the file is empty without it
+    pub content: Option<String>,
+}
+
+impl CreateTempFileTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let mode = get_current_mode().map(|mode| mode.id);
+        let entry = fs_service
+            .create_temp_file(
+                self.prefix.as_deref().unwrap_or("tmp"),
+                self.suffix.as_deref().unwrap_or_default(),
+                self.content.as_deref(),
+                mode,
+            )
+            .await
+            .map_err(CallToolError::new)?;
+        Ok(created_result(&entry, "file"))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTempDirTool {
+    pub prefix: Option<String>,
+}
+
+impl CreateTempDirTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let mode = get_current_mode().map(|mode| mode.id);
+        let entry = fs_service
+            .create_temp_dir(self.prefix.as_deref().unwrap_or("tmp"), mode)
+            .await
+            .map_err(CallToolError::new)?;
+        Ok(created_result(&entry, "directory"))
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::error::ServiceError;
+use crate::fs_service::FileSystemService;
+use std::path::Path;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TouchFileTool {
+    pub path: String,
+    /// Create missing parent directories first, like `mkdir -p`
+    #[serde(default)]
+    pub create_parents: bool,
+}
+
+impl TouchFileTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        // The parents pass the same allow/block and read-only checks as the file
+        if self.create_parents {
+            if let Some(parent) = Path::new(&self.path).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                match fs_service.create_directory(parent).await {
+                    Ok(()) | Err(ServiceError::DirectoryAlreadyExists) => {}
+                    Err(e) => return Err(CallToolError::new(e)),
+                }
+            }
+        }
+
+        let touched = fs_service.touch(Path::new(&self.path)).await.map_err(CallToolError::new)?;
+        Ok(CallToolResult {
+            content: vec![Content::Text(TextContent { text: touched.describe(&self.path) })],
+            is_error: Some(false),
+            structured_content: Some(json!({ "path": self.path, "result": touched })),
+        })
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::mcp_types::{Tool, CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{undo::UndoStep, FileSystemService};
+use std::collections::HashMap;
+
+/// Workflow step metadata naming the undo journal's newest step, so
+/// undo_to_step can return to the state right after a workflow step
+pub fn undo_metadata(fs_service: &FileSystemService) -> Option<HashMap<String, Value>> {
+    let step = fs_service.undo_journal().last_step()?;
+    Some(HashMap::from([("undo_step".to_string(), json!(step))]))
+}
+
+fn undo_result(undone: &[UndoStep], fs_service: &FileSystemService) -> CallToolResult {
+    let remaining = fs_service.undo_journal().history();
+    let mut text = if undone.is_empty() {
+        "Nothing to undo.\n".to_string()
+    } else {
+        undone.iter().map(|step| format!("Undid {}\n", step.describe())).collect()
+    };
+    match remaining.last() {
+        Some(last) => text.push_str(&format!("{} step(s) left to undo; the newest is {}\n", remaining.len(), last.describe())),
+        None => text.push_str("No steps left to undo\n"),
+    }
+    CallToolResult {
+        content: vec![Content::Text(TextContent { text })],
+        is_error: Some(false),
+        structured_content: Some(json!({ "undone": undone, "last_step": remaining.last().map(|step| step.step) })),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoLastOperationTool {}
+
+impl UndoLastOperationTool {
+    pub fn tool_definition() -> Tool {
+        Tool {
+            name: "undo_last_operation".to_string(),
+            description: Some("Reverse the most recent write_file, edit_file, apply_patch, move_file or delete_file: restore the overwritten content, move the file back, or bring the deleted item back. The server keeps the last --undo-history changes (100 by default).".to_string()),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+            output_schema: None,
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let undone = fs_service.undo_last_operation().await.map_err(CallToolError::new)?;
+        Ok(undo_result(&undone.into_iter().collect::<Vec<_>>(), fs_service))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoToStepTool {
+    pub step: u64,
+}
+
+impl UndoToStepTool {
+    pub fn tool_definition() -> Tool {
+        Tool {
+            name: "undo_to_step".to_string(),
+            description: Some("Reverse every change made after undo step `step`, newest first, stopping at the first that cannot be undone. Each workflow step in get_current_mode_status shows the undo step it left the files at; 0 undoes everything still journaled.".to_string()),
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "step": {
+                        "type": "number",
+                        "description": "Undo step to return to; changes journaled after it are reversed"
+                    }
+                },
+                "required": ["step"]
+            }),
+            output_schema: None,
+        }
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let undone = fs_service.undo_to_step(self.step).await.map_err(CallToolError::new)?;
+        Ok(undo_result(&undone, fs_service))
+    }
+}
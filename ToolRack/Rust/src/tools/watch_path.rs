@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
+use crate::fs_service::{watch::WatchPoll, FileSystemService};
+use std::fmt::Write;
+use std::path::Path;
+
+fn text_result(text: String) -> CallToolResult {
+    CallToolResult {
+        content: vec![Content::Text(TextContent { text })],
+        is_error: Some(false),
+        structured_content: None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartWatchTool {
+    pub path: String,
+    /// Also watch everything below a directory
+    #[serde(default)]
+    pub recursive: bool,
+}
+
+impl StartWatchTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let id = fs_service
+            .start_watch(Path::new(&self.path), self.recursive)
+            .await
+            .map_err(CallToolError::new)?;
+        Ok(CallToolResult {
+            structured_content: Some(json!({ "watch_id": id })),
+            ..text_result(format!(
+                "Watching {}{} as {}; call poll_watch with this watch_id to collect the changes",
+                self.path,
+                if self.recursive { " and everything below it" } else { "" },
+                id
+            ))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollWatchTool {
+    pub watch_id: String,
+    pub output_format: Option<String>,
+}
+
+impl PollWatchTool {
+    fn format_text(poll: &WatchPoll) -> Result<String, std::fmt::Error> {
+        let mut output = String::new();
+        if poll.events.is_empty() {
+            writeln!(output, "No changes under {} since the last poll", poll.path)?;
+        } else {
+            writeln!(output, "{} changes under {} since the last poll:", poll.events.len(), poll.path)?;
+        }
+        for event in &poll.events {
+            writeln!(output, "  {:<6} {} ({})", event.kind.as_str(), event.path, event.time.format("%H:%M:%S%.3f"))?;
+        }
+        if poll.dropped > 0 {
+            writeln!(output, "{} older changes were dropped; poll more often to see them all", poll.dropped)?;
+        }
+        Ok(output)
+    }
+
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        let poll = fs_service.poll_watch(&self.watch_id).map_err(CallToolError::new)?;
+        let content = match self.output_format.as_deref().unwrap_or("text") {
+            "json" => serde_json::to_string_pretty(&poll).map_err(CallToolError::new)?,
+            _ => Self::format_text(&poll).map_err(CallToolError::new)?,
+        };
+        Ok(CallToolResult {
+            structured_content: Some(serde_json::to_value(&poll).map_err(CallToolError::new)?),
+            ..text_result(content)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopWatchTool {
+    pub watch_id: String,
+}
+
+impl StopWatchTool {
+    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
+        fs_service.stop_watch(&self.watch_id).map_err(CallToolError::new)?;
+        Ok(text_result(format!("Stopped {}", self.watch_id)))
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use crate::mcp_types::{CallToolResult, Content, TextContent, CallToolError};
-use crate::fs_service::FileSystemService;
+use crate::error::ServiceError;
+use crate::fs_service::{ops::FileSystemOps, remote::RemoteStorage};
 use crate::retry::retry_3x;
 use std::path::Path;
 
@@ -8,12 +9,24 @@ use std::path::Path;
 pub struct WriteFileTool {
     pub path: String,
     pub content: String,
+    /// Create missing parent directories first, like `mkdir -p`
+    #[serde(default)]
+    pub create_parents: bool,
 }
 
 impl WriteFileTool {
+    pub async fn run_tool(self, fs_service: &dyn FileSystemOps) -> Result<CallToolResult, CallToolError> {
+        // The parents pass the same allow/block and read-only checks as the
+        // file; object stores have no directories to create
+        if self.create_parents && !RemoteStorage::is_remote(Path::new(&self.path)) {
+            if let Some(parent) = Path::new(&self.path).parent().filter(|parent| !parent.as_os_str().is_empty()) {
+                match fs_service.create_directory(parent).await {
+                    Ok(()) | Err(ServiceError::DirectoryAlreadyExists) => {}
+                    Err(e) => return Err(CallToolError::new(e)),
+                }
+            }
+        }
 
-
-    pub async fn run_tool(self, fs_service: &FileSystemService) -> Result<CallToolResult, CallToolError> {
         // Retry up to 3 times on transient I/O errors
         let path = self.path.clone();
         let content = self.content.clone();
@@ -29,6 +42,7 @@ impl WriteFileTool {
                     text: format!("Successfully wrote to file: {}", self.path),
                 })],
                 is_error: Some(false),
+                structured_content: None,
             }),
             Err(e) => Err(CallToolError::new(e)),
         }
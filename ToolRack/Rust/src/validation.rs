@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+
+use jsonschema::{error::ValidationErrorKind, Validator};
+use serde_json::Value;
+
+use crate::{logging::log_warn, mcp_types::Tool};
+
+/// Tool arguments checked against each tool's `input_schema` before dispatch,
+/// so a mistyped or misspelled argument is reported instead of being dropped
+/// by serde
+#[derive(Default)]
+pub struct ArgumentValidator {
+    /// Compiled schema and top-level property names of each tool
+    validators: HashMap<String, (Validator, Vec<String>)>,
+}
+
+/// Give every object schema that lists its `properties` without saying
+/// otherwise `additionalProperties: false`, including those nested in
+/// `properties` and array `items`
+fn close_objects(schema: &mut Value) {
+    let Some(object) = schema.as_object_mut() else {
+        return;
+    };
+    if object.contains_key("properties") && !object.contains_key("additionalProperties") {
+        object.insert("additionalProperties".to_string(), Value::Bool(false));
+    }
+    if let Some(properties) = object.get_mut("properties").and_then(Value::as_object_mut) {
+        properties.values_mut().for_each(close_objects);
+    }
+    if let Some(items) = object.get_mut("items") {
+        close_objects(items);
+    }
+}
+
+impl ArgumentValidator {
+    /// Compile the schema of every tool, with unknown fields disallowed (see
+    /// `close_objects`)
+    pub fn new(tools: &[Tool]) -> Self {
+        let mut validators = HashMap::new();
+        for tool in tools {
+            let mut schema = tool.input_schema.clone();
+            close_objects(&mut schema);
+            let mut properties: Vec<String> = schema["properties"]
+                .as_object()
+                .map(|properties| properties.keys().cloned().collect())
+                .unwrap_or_default();
+            properties.sort();
+            match jsonschema::validator_for(&schema) {
+                Ok(validator) => {
+                    validators.insert(tool.name.clone(), (validator, properties));
+                }
+                // A broken schema should not make its tool unusable
+                Err(e) => log_warn!("input_schema of tool '{}' is not valid JSON Schema, arguments will not be checked: {}", tool.name, e),
+            }
+        }
+        Self { validators }
+    }
+
+    /// One message per problem with `arguments` (missing, mistyped or unknown
+    /// fields), or Ok when they match the schema or the tool has none
+    pub fn validate(&self, tool: &str, arguments: &Value) -> Result<(), Vec<String>> {
+        let Some((validator, properties)) = self.validators.get(tool) else {
+            return Ok(());
+        };
+        let errors: Vec<String> = validator
+            .iter_errors(arguments)
+            .map(|error| {
+                // `/edits/0/oldText` reads as `edits.0.oldText`
+                let location = error.instance_path.to_string();
+                let field = location.trim_start_matches('/').replace('/', ".");
+                match &error.kind {
+                    ValidationErrorKind::AdditionalProperties { unexpected } => {
+                        let unexpected: Vec<String> = unexpected.iter().map(|name| format!("'{}'", name)).collect();
+                        if field.is_empty() {
+                            format!("unknown argument {}; expected one of: {}", unexpected.join(", "), properties.join(", "))
+                        } else {
+                            format!("{}: unknown field {}", field, unexpected.join(", "))
+                        }
+                    }
+                    _ if field.is_empty() => error.to_string(),
+                    _ => format!("{}: {}", field, error),
+                }
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
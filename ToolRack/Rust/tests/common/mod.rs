@@ -0,0 +1,17 @@
+//! Helpers shared by the integration tests that call tools through
+//! `MyServerHandler`. Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, CallToolResult, Content};
+use serde_json::Value;
+
+/// A tools/call request for tool `name`
+pub fn call(name: &str, arguments: Value) -> CallToolRequest {
+    CallToolRequest { params: CallToolParams { name: name.to_string(), arguments: Some(arguments) } }
+}
+
+/// The text of a result's first content item
+pub fn text(result: &CallToolResult) -> &str {
+    let Content::Text(text) = &result.content[0] else { panic!("expected text content") };
+    &text.text
+}
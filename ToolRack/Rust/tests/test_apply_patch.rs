@@ -0,0 +1,94 @@
+use aichemistforge_mcp_server::fs_service::patch::{apply_hunks, parse_patch};
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, Content};
+use aichemistforge_mcp_server::tools::ApplyPatchTool;
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+const ORIGINAL: &str = "one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+
+const PATCH: &str = "--- a/numbers.txt
++++ b/numbers.txt
+@@ -2,5 +2,5 @@
+ two
+ three
+-four
++FOUR
+ five
+ six
+";
+
+fn patch_tool(path: &std::path::Path, patch: &str, dry_run: bool) -> ApplyPatchTool {
+    ApplyPatchTool { path: path.display().to_string(), patch: patch.to_string(), fuzz: None, dry_run: Some(dry_run) }
+}
+
+#[test]
+fn test_hunks_apply_with_offset_and_fuzz() {
+    let hunks = parse_patch(PATCH).unwrap();
+    let (patched, applied) = apply_hunks(ORIGINAL, &hunks, 0).unwrap();
+    assert_eq!(patched, "one\ntwo\nthree\nFOUR\nfive\nsix\nseven\n");
+    assert_eq!((applied[0].line, applied[0].fuzz), (2, 0));
+
+    // Lines added above move the hunk
+    let shifted = format!("zero\nhalf\n{}", ORIGINAL);
+    let (patched, applied) = apply_hunks(&shifted, &hunks, 0).unwrap();
+    assert!(patched.contains("three\nFOUR\nfive"));
+    assert_eq!((applied[0].expected_line, applied[0].line), (2, 4));
+
+    // Changed outer context needs fuzz
+    let drifted = ORIGINAL.replace("two", "2").replace("six", "6");
+    assert!(apply_hunks(&drifted, &hunks, 0).unwrap_err().to_string().contains("hunk 1 of 1"));
+    let (patched, applied) = apply_hunks(&drifted, &hunks, 1).unwrap();
+    assert_eq!(patched, "one\n2\nthree\nFOUR\nfive\n6\nseven\n");
+    assert_eq!(applied[0].fuzz, 1);
+
+    let no_newline = "@@ -1 +1 @@\n-a\n\\ No newline at end of file\n+b\n\\ No newline at end of file\n";
+    assert_eq!(apply_hunks("a", &parse_patch(no_newline).unwrap(), 0).unwrap().0, "b");
+    assert!(parse_patch("just some text").is_err());
+    let two_files = format!("{}--- a/x\n+++ b/x\n@@ -1 +1 @@\n-a\n+b\n", PATCH);
+    assert!(parse_patch(&two_files).unwrap_err().to_string().contains("more than one file"));
+}
+
+#[tokio::test]
+async fn test_apply_patch_writes_only_when_every_hunk_applies() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("numbers.txt");
+    fs::write(&file, ORIGINAL.replace('\n', "\r\n")).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let preview = patch_tool(&file, PATCH, true).run_tool(&fs_service).await.unwrap();
+    let Content::Text(text) = &preview.content[0] else { panic!("expected text content") };
+    assert!(text.text.contains("+FOUR"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), ORIGINAL.replace('\n', "\r\n"));
+
+    // The second hunk does not match, so the first is not written either
+    let failing = format!("{}@@ -6,2 +6,2 @@\n six\n-eight\n+EIGHT\n", PATCH);
+    let error = patch_tool(&file, &failing, false).run_tool(&fs_service).await.unwrap_err();
+    assert!(error.to_string().contains("hunk 2 of 2"), "{}", error);
+    assert_eq!(fs::read_to_string(&file).unwrap(), ORIGINAL.replace('\n', "\r\n"));
+
+    patch_tool(&file, PATCH, false).run_tool(&fs_service).await.unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), ORIGINAL.replace("four", "FOUR").replace('\n', "\r\n"));
+}
+
+#[tokio::test]
+async fn test_apply_patch_flat_tool_requires_patch() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("numbers.txt");
+    fs::write(&file, ORIGINAL).unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", temp_dir.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+
+    let call = |arguments| CallToolRequest {
+        params: CallToolParams { name: "apply_patch".to_string(), arguments: Some(arguments) },
+    };
+    let missing = handler.handle_call_tool(call(json!({ "path": file.display().to_string() }))).await.unwrap();
+    assert_eq!(missing.is_error, Some(true));
+    handler
+        .handle_call_tool(call(json!({ "path": file.display().to_string(), "patch": PATCH })))
+        .await
+        .unwrap();
+    assert!(fs::read_to_string(&file).unwrap().contains("FOUR"));
+}
@@ -0,0 +1,65 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::duplicates::{DedupeAction, DedupePlan, KeeperRule};
+use aichemistforge_mcp_server::fs_service::merge::ConflictStrategy;
+use aichemistforge_mcp_server::fs_service::plan::{Plan, PlanDocument};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_dedupe_plan_round_trips_through_json() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    fs::write(temp_dir.path().join("a.txt"), "same")?;
+    fs::write(temp_dir.path().join("copy_of_a.txt"), "same")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let groups = fs_service
+        .find_duplicate_files(temp_dir.path(), None, None, Some(1), None, false)
+        .await?;
+    let plan = DedupePlan::build(&groups, DedupeAction::DeleteExtras, KeeperRule::ShortestPath)?;
+    let json = serde_json::to_string(&PlanDocument::new(Plan::Dedupe(plan))).unwrap();
+    assert!(json.contains("\"kind\":\"dedupe\""));
+
+    let document = PlanDocument::from_json(serde_json::Value::String(json)).unwrap();
+    let results = fs_service.apply_plan(&document).await;
+
+    assert!(results.iter().all(|r| r.ok), "{:?}", results);
+    assert!(temp_dir.path().join("a.txt").exists());
+    assert!(!temp_dir.path().join("copy_of_a.txt").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_plan_applies_from_document() -> ServiceResult<()> {
+    let (source, destination) = (TempDir::new()?, TempDir::new()?);
+    fs::write(source.path().join("new.txt"), "new")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let plan = fs_service
+        .plan_merge(source.path(), destination.path(), &[], false, ConflictStrategy::Skip)
+        .await?;
+    let value = serde_json::to_value(PlanDocument::new(Plan::Merge(plan))).unwrap();
+    let document = PlanDocument::from_json(value).unwrap();
+    let results = fs_service.apply_plan(&document).await;
+
+    assert!(results.iter().all(|r| r.ok), "{:?}", results);
+    assert_eq!(fs::read_to_string(destination.path().join("new.txt"))?, "new");
+
+    Ok(())
+}
+
+#[test]
+fn test_plan_document_rejects_unknown_version() {
+    let value = serde_json::json!({
+        "version": 99,
+        "created_at": "2024-01-01T00:00:00+00:00",
+        "kind": "dedupe",
+        "action": "delete_extras",
+        "keep_rule": "oldest",
+        "groups": [],
+        "reclaimable_bytes": 0
+    });
+    let error = PlanDocument::from_json(value).unwrap_err();
+    assert!(error.contains("Unsupported plan version"));
+}
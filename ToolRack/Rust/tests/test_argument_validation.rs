@@ -0,0 +1,82 @@
+use aichemistforge_mcp_server::mcp_types::Tool;
+use aichemistforge_mcp_server::validation::ArgumentValidator;
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::{call, text};
+
+#[test]
+fn test_validator_reports_each_field() {
+    let tool = Tool {
+        name: "demo".to_string(),
+        description: None,
+        input_schema: json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "lines": { "type": "number" },
+                "edits": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "oldText": { "type": "string" } },
+                        "required": ["oldText"]
+                    }
+                }
+            },
+            "required": ["path"]
+        }),
+        output_schema: None,
+    };
+    let validator = ArgumentValidator::new(&[tool]);
+
+    assert!(validator.validate("demo", &json!({ "path": "a", "lines": 3 })).is_ok());
+    // Tools without a compiled schema are not checked
+    assert!(validator.validate("other", &json!({ "anything": 1 })).is_ok());
+
+    let errors = validator.validate("demo", &json!({ "lines": "3", "pth": "a" })).unwrap_err();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert!(errors.iter().any(|e| e.contains("\"path\" is a required property")), "{:?}", errors);
+    assert!(errors.iter().any(|e| e.starts_with("lines:") && e.contains("number")), "{:?}", errors);
+    assert!(errors.iter().any(|e| e == "unknown argument 'pth'; expected one of: edits, lines, path"), "{:?}", errors);
+
+    let errors = validator.validate("demo", &json!({ "path": "a", "edits": [{ "oldText": 1, "extra": true }] })).unwrap_err();
+    assert!(errors.iter().any(|e| e.starts_with("edits.0.oldText:")), "{:?}", errors);
+    assert!(errors.iter().any(|e| e == "edits.0: unknown field 'extra'"), "{:?}", errors);
+}
+
+#[tokio::test]
+async fn test_invalid_calls_are_rejected_before_dispatch() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("notes.txt");
+    fs::write(&path, "one\ntwo\n").unwrap();
+    let path = path.to_str().unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "both", temp.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+
+    let result = handler.handle_call_tool(call("head_file", json!({ "path": path, "lines": "1" }))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).starts_with("Invalid arguments for tool 'head_file':\n- lines:"), "{}", text(&result));
+
+    // A misspelled argument would otherwise be ignored
+    let result = handler.handle_call_tool(call("write_file", json!({ "path": path, "contents": "x" }))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("unknown argument 'contents'; expected one of: content, create_parents, path"), "{}", text(&result));
+    assert!(text(&result).contains("\"content\" is a required property"), "{}", text(&result));
+    assert_eq!(fs::read_to_string(path).unwrap(), "one\ntwo\n");
+
+    let result = handler
+        .handle_call_tool(call("single_file_operations", json!({ "operation": "read_everything", "path": path })))
+        .await
+        .unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("operation:"), "{}", text(&result));
+
+    let result = handler.handle_call_tool(call("head_file", json!({ "path": path, "lines": 1 }))).await.unwrap();
+    assert_ne!(result.is_error, Some(true), "{}", text(&result));
+    assert!(text(&result).contains("one"));
+}
@@ -0,0 +1,43 @@
+#![cfg(unix)]
+
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::audit::FindingKind;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs::{self, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_with_mode(path: &Path, mode: u32) {
+    fs::write(path, "x").unwrap();
+    fs::set_permissions(path, Permissions::from_mode(mode)).unwrap();
+}
+
+#[tokio::test]
+async fn test_audit_flags_world_writable_and_setuid() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    write_with_mode(&root.join("ok.txt"), 0o644);
+    write_with_mode(&root.join("shared.txt"), 0o666);
+    write_with_mode(&root.join("tool"), 0o4755);
+    fs::create_dir(root.join("drop")).unwrap();
+    fs::set_permissions(root.join("drop"), Permissions::from_mode(0o777)).unwrap();
+    fs::create_dir(root.join("tmp")).unwrap();
+    fs::set_permissions(root.join("tmp"), Permissions::from_mode(0o1777)).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let audit = fs_service.audit_permissions(root, &[], false).await?;
+
+    let found: Vec<(String, FindingKind)> = audit
+        .findings
+        .iter()
+        .map(|f| (Path::new(&f.path).file_name().unwrap().to_string_lossy().into_owned(), f.kind))
+        .collect();
+    assert!(found.contains(&("shared.txt".to_string(), FindingKind::WorldWritable)));
+    assert!(found.contains(&("tool".to_string(), FindingKind::Setuid)));
+    assert!(found.contains(&("drop".to_string(), FindingKind::WorldWritableNoSticky)));
+    assert!(!found.iter().any(|(name, _)| name == "ok.txt" || name == "tmp"));
+    assert_eq!(audit.scanned_entries, 6);
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use aichemistforge_mcp_server::fs_service::backup::{BackupMode, BACKUP_DIR_NAME};
+use aichemistforge_mcp_server::tools::EditOperation;
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService};
+use clap::Parser;
+use std::fs;
+use tempfile::TempDir;
+
+fn service(mode: BackupMode) -> FileSystemService {
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_backup_mode(mode);
+    fs_service
+}
+
+#[tokio::test]
+async fn test_sibling_backups_keep_the_last_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    let backup = temp_dir.path().join("notes.txt.bak");
+    let fs_service = service(BackupMode::Sibling);
+
+    // A new file has nothing to keep
+    fs_service.write_file(&file, &"first".to_string()).await.unwrap();
+    assert!(!backup.exists());
+    fs_service.write_file(&file, &"second".to_string()).await.unwrap();
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "first");
+
+    let edit = EditOperation { old_text: "second".to_string(), new_text: "third".to_string(), ..Default::default() };
+    fs_service.apply_file_edits(&file, vec![edit], None, None).await.unwrap();
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "second");
+
+    // Deleting moves the file aside
+    fs_service.delete_file(&file).await.unwrap();
+    assert!(!file.exists());
+    assert_eq!(fs::read_to_string(&backup).unwrap(), "third");
+
+    let dir = temp_dir.path().join("build");
+    fs::create_dir(&dir).unwrap();
+    fs::write(dir.join("out.log"), "log").unwrap();
+    fs_service.delete_file(&dir).await.unwrap();
+    assert_eq!(fs::read_to_string(temp_dir.path().join("build.bak/out.log")).unwrap(), "log");
+}
+
+#[tokio::test]
+async fn test_dir_backups_keep_every_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("config.toml");
+    fs::write(&file, "v1").unwrap();
+    let fs_service = service(BackupMode::Dir);
+
+    fs_service.write_file(&file, &"v2".to_string()).await.unwrap();
+    fs_service.write_file(&file, &"v3".to_string()).await.unwrap();
+    fs_service.delete_file(&file).await.unwrap();
+
+    let backups = temp_dir.path().join(BACKUP_DIR_NAME);
+    let mut kept: Vec<String> = fs::read_dir(&backups)
+        .unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect();
+    kept.sort();
+    assert_eq!(kept, ["v1", "v2", "v3"]);
+    assert!(fs::read_dir(&backups).unwrap().all(|entry| entry.unwrap().file_name().to_string_lossy().starts_with("config.toml.")));
+}
+
+#[tokio::test]
+async fn test_backups_are_off_by_default() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("a.txt");
+    fs::write(&file, "old").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.write_file(&file, &"new".to_string()).await.unwrap();
+    fs_service.delete_file(&file).await.unwrap();
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+
+    let args = CommandArguments::parse_from(["server", "--backup", "dir"]);
+    assert_eq!(args.backup, BackupMode::Dir);
+}
@@ -0,0 +1,117 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::batch::BatchOperation;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn path(root: &Path, name: &str) -> String {
+    root.join(name).display().to_string()
+}
+
+fn listing(root: &Path) -> Vec<String> {
+    let mut names: Vec<String> = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .into_iter()
+        .map(|e| e.unwrap().path().strip_prefix(root).unwrap().display().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+#[tokio::test]
+async fn test_batch_commits_all_steps() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    fs::write(root.join("old.txt"), "old")?;
+    fs::write(root.join("obsolete.txt"), "bye")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let operations = vec![
+        BatchOperation::Mkdir { path: path(root, "src/nested") },
+        BatchOperation::Move { source: path(root, "old.txt"), destination: path(root, "src/nested/new.txt") },
+        BatchOperation::Write { path: path(root, "src/nested/new.txt"), content: "rewritten".to_string() },
+        BatchOperation::Copy { source: path(root, "src/nested/new.txt"), destination: path(root, "copy.txt") },
+        BatchOperation::Delete { path: path(root, "obsolete.txt") },
+    ];
+    let report = fs_service.batch_operations(&operations).await?;
+
+    assert!(report.committed, "{:?}", report);
+    assert_eq!(listing(root), vec!["copy.txt", "src", "src/nested", "src/nested/new.txt"]);
+    assert_eq!(fs::read_to_string(root.join("copy.txt"))?, "rewritten");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_rolls_back_on_failure() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    fs::write(root.join("keep.txt"), "original")?;
+    fs::write(root.join("moved.txt"), "moved")?;
+    fs::write(root.join("doomed.txt"), "still here")?;
+    let before = listing(root);
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let operations = vec![
+        BatchOperation::Write { path: path(root, "keep.txt"), content: "changed".to_string() },
+        BatchOperation::Mkdir { path: path(root, "a/b") },
+        BatchOperation::Move { source: path(root, "moved.txt"), destination: path(root, "a/b/moved.txt") },
+        BatchOperation::Delete { path: path(root, "doomed.txt") },
+        BatchOperation::Copy { source: path(root, "missing.txt"), destination: path(root, "a/copy.txt") },
+    ];
+    let report = fs_service.batch_operations(&operations).await?;
+
+    assert!(!report.committed);
+    assert_eq!(report.failed_step, Some(4));
+    assert!(report.rollback_errors.is_empty(), "{:?}", report.rollback_errors);
+    assert_eq!(listing(root), before);
+    assert_eq!(fs::read_to_string(root.join("keep.txt"))?, "original");
+    assert_eq!(fs::read_to_string(root.join("doomed.txt"))?, "still here");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_rejects_disallowed_paths_before_running() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let blocked = temp_dir.path().join("blocked");
+    fs::create_dir(&blocked)?;
+    let fs_service = FileSystemService::try_new(&[], &[blocked.display().to_string()])?;
+
+    let operations = vec![
+        BatchOperation::Write { path: path(temp_dir.path(), "ok.txt"), content: "x".to_string() },
+        BatchOperation::Write { path: path(&blocked, "no.txt"), content: "x".to_string() },
+    ];
+    assert!(fs_service.batch_operations(&operations).await.is_err());
+    assert!(!temp_dir.path().join("ok.txt").exists());
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_batch_copy_follows_linked_directories() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("src/shared"))?;
+    fs::write(root.join("src/shared/lib.txt"), "lib")?;
+    fs::create_dir(root.join("app"))?;
+    std::os::unix::fs::symlink(root.join("src/shared"), root.join("app/shared"))?;
+    let fs_service = FileSystemService::try_new(&[root.display().to_string()], &[])?;
+
+    let operations = vec![BatchOperation::Copy { source: path(root, "app"), destination: path(root, "out") }];
+    let report = fs_service.batch_operations(&operations).await?;
+    assert!(report.committed, "{:?}", report);
+    assert!(!fs::symlink_metadata(root.join("out/shared"))?.file_type().is_symlink());
+    assert_eq!(fs::read_to_string(root.join("out/shared/lib.txt"))?, "lib");
+
+    // A link back up to a directory being copied fails the step
+    std::os::unix::fs::symlink(root.join("app"), root.join("app/again"))?;
+    let operations = vec![BatchOperation::Copy { source: path(root, "app"), destination: path(root, "loop") }];
+    let report = fs_service.batch_operations(&operations).await?;
+    assert!(!report.committed);
+    assert!(!root.join("loop").exists());
+
+    Ok(())
+}
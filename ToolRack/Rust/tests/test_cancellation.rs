@@ -0,0 +1,61 @@
+use aichemistforge_mcp_server::cancel::{self, CancellationToken};
+use aichemistforge_mcp_server::error::ServiceError;
+use aichemistforge_mcp_server::fs_service::{FileSystemService, SearchFilesOptions};
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    for d in 0..5 {
+        let dir = temp_dir.path().join(format!("dir{}", d));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("copy.txt"), "same content").unwrap();
+    }
+    temp_dir
+}
+
+fn cancelled() -> CancellationToken {
+    let token = CancellationToken::new();
+    token.cancel();
+    token
+}
+
+fn is_interrupted(error: &ServiceError) -> bool {
+    matches!(error, ServiceError::Io(e) if e.kind() == std::io::ErrorKind::Interrupted)
+}
+
+#[tokio::test]
+async fn test_cancelled_walks_stop_with_an_error() {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    let root = temp_dir.path();
+
+    let tree = cancel::scope(cancelled(), fs_service.generate_directory_tree(root, false, 0, false, false)).await;
+    assert!(is_interrupted(&tree.unwrap_err()));
+
+    let size = cancel::scope(cancelled(), fs_service.calculate_directory_size(root, false, false)).await;
+    assert!(is_interrupted(&size.unwrap_err()));
+
+    let roots = vec![root.to_path_buf()];
+    let scan = cancel::scope(cancelled(), fs_service.find_duplicates_across_roots(&roots, None, None, None, None, false)).await;
+    assert!(is_interrupted(&scan.unwrap_err()));
+
+    let options = SearchFilesOptions { pattern: "*.txt".to_string(), ..Default::default() };
+    let search = cancel::scope(cancelled(), fs_service.search_files(root, options)).await;
+    assert!(search.unwrap_err().to_string().contains("cancelled"));
+}
+
+#[tokio::test]
+async fn test_uncancelled_scope_runs_to_completion() {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    let roots = vec![temp_dir.path().to_path_buf()];
+
+    let scan = cancel::scope(CancellationToken::new(), fs_service.find_duplicates_across_roots(&roots, None, None, None, None, false))
+        .await
+        .unwrap();
+    assert_eq!(scan.groups.len(), 1);
+    // Outside any call the token is never cancelled
+    assert!(!cancel::current().is_cancelled());
+    assert!(fs_service.calculate_directory_size(temp_dir.path(), false, false).await.is_ok());
+}
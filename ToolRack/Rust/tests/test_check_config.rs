@@ -0,0 +1,70 @@
+use aichemistforge_mcp_server::check_config::check_config;
+use aichemistforge_mcp_server::cli::Command;
+use aichemistforge_mcp_server::CommandArguments;
+use clap::Parser;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_check_config_subcommand_follows_the_directories() {
+    let args = CommandArguments::parse_from(["server", "--sandbox", "/a", "/b", "check-config"]);
+    assert_eq!(args.command, Some(Command::CheckConfig));
+    assert_eq!(args.allowed_directories, ["/a", "/b"]);
+    assert!(args.sandbox);
+
+    let args = CommandArguments::parse_from(["server", "/a"]);
+    assert_eq!(args.command, None);
+}
+
+#[test]
+fn test_check_config_reports_missing_and_overlapping_directories() {
+    let temp = TempDir::new().unwrap();
+    let root = temp.path().join("root");
+    let inner = root.join("inner");
+    fs::create_dir_all(&inner).unwrap();
+    let missing = temp.path().join("missing");
+    let (root, inner, missing) = (root.to_str().unwrap(), inner.to_str().unwrap(), missing.to_str().unwrap());
+
+    let blocked = format!("{},{}", inner, "/no/such/blocked");
+    // --blocked-directories takes every value after it, so it goes last
+    let args = CommandArguments::parse_from(["server", root, inner, missing, "--blocked-directories", &blocked]);
+    let report = check_config(&args);
+
+    assert!(!report.ok);
+    assert!(report.errors.iter().any(|e| e.contains(missing) && e.contains("does not exist")));
+    assert!(report.errors.iter().any(|e| e.contains("inside blocked directory")));
+    assert!(report.warnings.iter().any(|w| w.contains("overlap")));
+    assert!(report.warnings.iter().any(|w| w.contains("/no/such/blocked") && w.contains("has no effect")));
+    assert_eq!(report.allowed_directories[0].resolved, Some(fs::canonicalize(root).unwrap()));
+    assert!(!report.allowed_directories[2].exists);
+}
+
+#[test]
+fn test_check_config_validates_files_and_writable_directories() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path().to_str().unwrap();
+    let config = temp.path().join("server.toml");
+    fs::write(&config, "allowed_commands = []\n[[tools]]\nname = \"lint\"\ncommand = [\"ruff\"]\n").unwrap();
+    let cache = temp.path().join("cache.db");
+
+    let args = CommandArguments::parse_from([
+        "server",
+        "--config",
+        config.to_str().unwrap(),
+        "--metadata-cache",
+        cache.to_str().unwrap(),
+        dir,
+    ]);
+    let report = check_config(&args);
+    assert!(!report.ok);
+    assert!(report.errors.iter().any(|e| e.contains("not in allowed_commands")));
+    assert!(report.writable_directories[0].writable);
+    // Probing must not leave anything behind, nor create the cache itself
+    assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 1);
+
+    fs::write(&config, "allowed_commands = [\"ruff\"]\n[[tools]]\nname = \"lint\"\ncommand = [\"ruff\"]\n").unwrap();
+    let report = check_config(&args);
+    assert!(report.ok, "{:?}", report.errors);
+    assert_eq!(report.settings["external_tools"][0], "lint (ruff)");
+    assert_eq!(report.settings["mode"], "allowlist");
+}
@@ -0,0 +1,67 @@
+use aichemistforge_mcp_server::fs_service::checksum::HashAlgorithm;
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest};
+use aichemistforge_mcp_server::tools::ChecksumFileTool;
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::text;
+
+const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+
+#[tokio::test]
+async fn test_checksum_algorithms() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("abc.txt");
+    fs::write(&file, "abc").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    assert_eq!(fs_service.checksum(&file, HashAlgorithm::Sha256).await.unwrap(), ABC_SHA256);
+    assert_eq!(fs_service.checksum(&file, HashAlgorithm::Md5).await.unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert!(fs_service
+        .checksum(&file, HashAlgorithm::Sha512)
+        .await
+        .unwrap()
+        .starts_with("ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a"));
+    assert!(fs_service.checksum(temp_dir.path(), HashAlgorithm::Sha256).await.is_err());
+
+    let verify = |expected: &str| ChecksumFileTool {
+        path: file.display().to_string(),
+        algorithm: HashAlgorithm::Sha256,
+        expected: Some(expected.to_string()),
+    };
+    let result = verify(&ABC_SHA256.to_uppercase()).run_tool(&fs_service).await.unwrap();
+    assert!(text(&result).starts_with(&format!("{}  {}", ABC_SHA256, file.display())));
+    assert!(text(&result).contains("Matches the expected checksum"));
+    let result = verify("deadbeef").run_tool(&fs_service).await.unwrap();
+    assert!(text(&result).contains("Does NOT match the expected checksum deadbeef"));
+}
+
+#[tokio::test]
+async fn test_checksum_files_reports_each_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("abc.txt");
+    fs::write(&file, "abc").unwrap();
+    let missing = temp_dir.path().join("missing.txt");
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", temp_dir.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+
+    let request = CallToolRequest {
+        params: CallToolParams {
+            name: "checksum_files".to_string(),
+            arguments: Some(json!({
+                "paths": [file.display().to_string(), missing.display().to_string()],
+                "output_format": "json",
+            })),
+        },
+    };
+    let result = handler.handle_call_tool(request).await.unwrap();
+    let report: Value = serde_json::from_str(text(&result)).unwrap();
+    assert_eq!(report["algorithm"], "sha256");
+    assert_eq!(report["files"][0]["checksum"], ABC_SHA256);
+    assert!(report["files"][1]["error"].is_string());
+    assert!(report["files"][1]["checksum"].is_null());
+}
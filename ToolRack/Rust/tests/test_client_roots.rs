@@ -0,0 +1,100 @@
+use aichemistforge_mcp_server::fs_service::roots::file_uri;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::server::{tcp, McpServer};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+type Lines = tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>;
+type Writer = tokio::net::tcp::OwnedWriteHalf;
+
+async fn send(writer: &mut Writer, message: Value) {
+    writer.write_all(format!("{}\n", message).as_bytes()).await.unwrap();
+}
+
+async fn next_message(lines: &mut Lines) -> Value {
+    let line = tokio::time::timeout(Duration::from_secs(10), lines.next_line()).await.unwrap().unwrap().unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+/// Answer the server's roots/list request with `roots`
+async fn answer_roots(lines: &mut Lines, writer: &mut Writer, roots: &[&Path]) {
+    let request = next_message(lines).await;
+    assert_eq!(request["method"], "roots/list", "{}", request);
+    let roots: Vec<Value> = roots.iter().map(|root| json!({ "uri": file_uri(root), "name": "workspace" })).collect();
+    send(writer, json!({ "jsonrpc": "2.0", "id": request["id"], "result": { "roots": roots } })).await;
+}
+
+/// Read `path` until the outcome is `allowed`; roots are applied shortly after
+/// the client answers
+async fn wait_for_access(lines: &mut Lines, writer: &mut Writer, path: &Path, allowed: bool) {
+    for id in 100..150 {
+        let call = json!({ "name": "read_file", "arguments": { "path": path.display().to_string() } });
+        send(writer, json!({ "jsonrpc": "2.0", "id": id, "method": "tools/call", "params": call })).await;
+        let response = next_message(lines).await;
+        if response.to_string().contains("outside allowed directories") != allowed {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("{} never became {}", path.display(), if allowed { "allowed" } else { "refused" });
+}
+
+#[tokio::test]
+async fn test_client_roots_scope_an_unrestricted_server() {
+    let (workspace, other) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let workspace_file = workspace.path().join("inside.txt");
+    let other_file = other.path().join("outside.txt");
+    std::fs::write(&workspace_file, "inside").unwrap();
+    std::fs::write(&other_file, "outside").unwrap();
+
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat"]);
+    let server = McpServer::new(MyServerHandler::new(&args).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(tcp::serve(std::sync::Arc::new(server), listener));
+    let (input, mut output) = TcpStream::connect(address).await.unwrap().into_split();
+    let mut lines = BufReader::new(input).lines();
+
+    send(
+        &mut output,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+            "protocolVersion": "2024-11-05", "capabilities": { "roots": { "listChanged": true } },
+            "clientInfo": { "name": "test", "version": "1" }
+        }}),
+    )
+    .await;
+    next_message(&mut lines).await;
+    send(&mut output, json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })).await;
+    answer_roots(&mut lines, &mut output, &[workspace.path()]).await;
+
+    wait_for_access(&mut lines, &mut output, &other_file, false).await;
+    wait_for_access(&mut lines, &mut output, &workspace_file, true).await;
+
+    // A changed workspace replaces the earlier roots
+    send(&mut output, json!({ "jsonrpc": "2.0", "method": "notifications/roots/list_changed" })).await;
+    answer_roots(&mut lines, &mut output, &[other.path()]).await;
+    wait_for_access(&mut lines, &mut output, &workspace_file, false).await;
+    wait_for_access(&mut lines, &mut output, &other_file, true).await;
+}
+
+#[tokio::test]
+async fn test_client_roots_extend_the_command_line_allowlist() {
+    let (configured, workspace) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let fs_service = FileSystemService::try_new(&[configured.path().display().to_string()], &[]).unwrap();
+    assert!(fs_service.validate_path(&workspace.path().join("a.txt")).await.is_err());
+
+    fs_service.set_client_roots(vec![workspace.path().to_path_buf(), configured.path().to_path_buf()]);
+    assert_eq!(fs_service.allowed_directories(), [configured.path(), workspace.path()]);
+    assert!(fs_service.validate_path(&workspace.path().join("a.txt")).await.is_ok());
+    assert!(fs_service.validate_path(&configured.path().join("b.txt")).await.is_ok());
+    assert!(!fs_service.roots().unrestricted);
+
+    fs_service.set_client_roots(Vec::new());
+    assert!(fs_service.validate_path(&workspace.path().join("a.txt")).await.is_err());
+}
@@ -0,0 +1,77 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::compare::CompareMode;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs::{self, File, FileTimes};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+fn write_at(path: &Path, content: &str, modified: SystemTime) {
+    fs::write(path, content).unwrap();
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_times(FileTimes::new().set_modified(modified))
+        .unwrap();
+}
+
+// a/                        b/
+//   same.txt                  same.txt
+//   touched.txt               touched.txt   (same content, newer mtime)
+//   edited.txt  "abc"         edited.txt    "xyz"
+//   resized.txt               resized.txt   (longer)
+//   only_a/inner.txt          only_b.txt
+fn setup_trees() -> (TempDir, TempDir) {
+    let (a, b) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let then = SystemTime::now() - Duration::from_secs(3600);
+    let now = SystemTime::now();
+    for dir in [a.path(), b.path()] {
+        write_at(&dir.join("same.txt"), "same", then);
+        write_at(&dir.join("edited.txt"), if dir == a.path() { "abc" } else { "xyz" }, then);
+    }
+    write_at(&a.path().join("touched.txt"), "touch", then);
+    write_at(&b.path().join("touched.txt"), "touch", now);
+    write_at(&a.path().join("resized.txt"), "short", then);
+    write_at(&b.path().join("resized.txt"), "much longer", then);
+    fs::create_dir(a.path().join("only_a")).unwrap();
+    write_at(&a.path().join("only_a").join("inner.txt"), "x", then);
+    write_at(&b.path().join("only_b.txt"), "y", then);
+    (a, b)
+}
+
+#[tokio::test]
+async fn test_compare_directories_by_metadata() -> ServiceResult<()> {
+    let (a, b) = setup_trees();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let comparison = fs_service
+        .compare_directories(a.path(), b.path(), &[], false, CompareMode::Metadata)
+        .await?;
+
+    assert_eq!(comparison.only_in_a, vec!["only_a"]);
+    assert_eq!(comparison.only_in_b, vec!["only_b.txt"]);
+    let differing: Vec<_> = comparison.differing.iter().map(|d| (d.path.as_str(), d.reason)).collect();
+    assert_eq!(differing, vec![("resized.txt", "size"), ("touched.txt", "modified")]);
+    assert_eq!(comparison.identical_files, 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_compare_directories_by_hash() -> ServiceResult<()> {
+    let (a, b) = setup_trees();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let comparison = fs_service
+        .compare_directories(a.path(), b.path(), &["only_*".to_string()], false, CompareMode::Hash)
+        .await?;
+
+    assert!(comparison.only_in_a.is_empty());
+    assert!(comparison.only_in_b.is_empty());
+    let differing: Vec<_> = comparison.differing.iter().map(|d| (d.path.as_str(), d.reason)).collect();
+    assert_eq!(differing, vec![("edited.txt", "content"), ("resized.txt", "size")]);
+    assert_eq!(comparison.identical_files, 2);
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_compare_text_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let (a, b) = (temp_dir.path().join("generated.rs"), temp_dir.path().join("existing.rs"));
+    fs::write(&a, "fn main() {\n    println!(\"hi\");\n}\n").unwrap();
+    fs::write(&b, "fn main() {\n    println!(\"hello\");\n}\n").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let comparison = fs_service.compare_files(&a, &b, 3).await.unwrap();
+    assert!(!comparison.identical && !comparison.binary);
+    let diff = comparison.diff.unwrap();
+    assert!(diff.starts_with(&format!("--- {}\n+++ {}\n", a.display(), b.display())), "{}", diff);
+    assert!(diff.contains("-    println!(\"hi\");\n+    println!(\"hello\");\n"), "{}", diff);
+
+    fs::copy(&a, &b).unwrap();
+    let comparison = fs_service.compare_files(&a, &b, 3).await.unwrap();
+    assert!(comparison.identical);
+    assert!(comparison.diff.is_none());
+
+    assert!(fs_service.compare_files(&a, temp_dir.path(), 3).await.is_err());
+}
+
+#[tokio::test]
+async fn test_compare_binary_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let (a, b) = (temp_dir.path().join("a.bin"), temp_dir.path().join("b.bin"));
+    let mut bytes = vec![0u8; 100_000];
+    fs::write(&a, &bytes).unwrap();
+    bytes[70_000] = 1;
+    fs::write(&b, &bytes).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let comparison = fs_service.compare_files(&a, &b, 3).await.unwrap();
+    assert!(comparison.binary && !comparison.identical);
+    assert_eq!(comparison.first_difference, Some(70_000));
+
+    // A prefix differs where the shorter file ends
+    fs::write(&b, &bytes[..50_000]).unwrap();
+    assert_eq!(fs_service.compare_files(&a, &b, 3).await.unwrap().first_difference, Some(50_000));
+
+    fs::copy(&a, &b).unwrap();
+    let comparison = fs_service.compare_files(&a, &b, 3).await.unwrap();
+    assert!(comparison.identical);
+    assert_eq!(comparison.first_difference, None);
+}
@@ -0,0 +1,82 @@
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, Content};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_concat_files_in_order_with_separator() {
+    let temp = TempDir::new().unwrap();
+    let logs = temp.path().join("logs");
+    fs::create_dir(&logs).unwrap();
+    fs::write(logs.join("b.log"), "second").unwrap();
+    fs::write(logs.join("a.log"), "first").unwrap();
+    fs::write(logs.join("c.txt"), "not a log").unwrap();
+    let header = temp.path().join("header.txt");
+    fs::write(&header, "header").unwrap();
+    let service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let output = temp.path().join("joined.log");
+    let paths = [header.display().to_string(), format!("{}/*.log", logs.display())];
+    let report = service.concat_files(&paths, &output, Some("\n"), false).await.unwrap();
+    assert_eq!(fs::read_to_string(&output).unwrap(), "header\nfirst\nsecond");
+    assert_eq!(report.bytes, 19);
+    assert_eq!(report.inputs.iter().map(|i| i.bytes).collect::<Vec<_>>(), [6, 5, 6]);
+    assert!(report.inputs[1].path.ends_with("a.log"));
+    // Nothing but the output is left in the directory
+    assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 3);
+
+    // The output is only replaced when asked, and never joined into itself
+    let err = service.concat_files(&paths, &output, None, false).await.unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+    service.concat_files(&paths, &output, None, true).await.unwrap();
+    assert_eq!(fs::read_to_string(&output).unwrap(), "headerfirstsecond");
+    let err = service
+        .concat_files(&[header.display().to_string()], &header, None, true)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("both an input and the output"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_concat_files_failure_leaves_output_untouched() {
+    let temp = TempDir::new().unwrap();
+    let first = temp.path().join("first.txt");
+    fs::write(&first, "data").unwrap();
+    let output = temp.path().join("out.txt");
+    fs::write(&output, "previous").unwrap();
+    let service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let paths = [first.display().to_string(), temp.path().join("missing.txt").display().to_string()];
+    assert!(service.concat_files(&paths, &output, None, true).await.is_err());
+    assert_eq!(fs::read_to_string(&output).unwrap(), "previous");
+    assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 2);
+}
+
+#[tokio::test]
+async fn test_concat_files_tool() {
+    let temp = TempDir::new().unwrap();
+    fs::write(temp.path().join("part.001"), "abc").unwrap();
+    fs::write(temp.path().join("part.002"), "def").unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", temp.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+
+    let output = temp.path().join("whole.bin");
+    let request = CallToolRequest {
+        params: CallToolParams {
+            name: "concat_files".to_string(),
+            arguments: Some(serde_json::json!({
+                "paths": [format!("{}/part.*", temp.path().display())],
+                "output_path": output,
+                "output_format": "json",
+            })),
+        },
+    };
+    let result = handler.handle_call_tool(request).await.unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    let report: serde_json::Value = serde_json::from_str(&text.text).unwrap();
+    assert_eq!(report["bytes"], 6);
+    assert_eq!(report["inputs"].as_array().unwrap().len(), 2);
+    assert_eq!(fs::read_to_string(&output).unwrap(), "abcdef");
+}
@@ -0,0 +1,80 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::content_type::{detect_content_type, looks_like_text, MimeSource};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn test_detect_png_by_magic_bytes() {
+    let png_header = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D];
+    let content_type = detect_content_type(Path::new("picture.bin"), &png_header);
+    assert_eq!(content_type.mime_type, "image/png");
+    assert_eq!(content_type.source, MimeSource::Magic);
+    assert!(!content_type.is_text);
+    assert_eq!(content_type.suggested_reader(), "read_media_file");
+}
+
+#[test]
+fn test_detect_source_file_by_extension() {
+    let content_type = detect_content_type(Path::new("main.rs"), b"fn main() {}\n");
+    assert_eq!(content_type.mime_type, "text/x-rust");
+    assert_eq!(content_type.source, MimeSource::Extension);
+    assert!(content_type.is_text);
+    assert_eq!(content_type.suggested_reader(), "read_file");
+}
+
+#[test]
+fn test_fallback_classification() {
+    let text = detect_content_type(Path::new("NOTES"), b"plain words");
+    assert_eq!(text.mime_type, "text/plain");
+    assert_eq!(text.source, MimeSource::Fallback);
+
+    let binary = detect_content_type(Path::new("blob"), &[0x01, 0x00, 0xFF, 0x10]);
+    assert_eq!(binary.mime_type, "application/octet-stream");
+    assert!(!binary.is_text);
+}
+
+#[test]
+fn test_looks_like_text_tolerates_truncated_utf8() {
+    // "é" is 0xC3 0xA9; a sample cut after the first byte is still text
+    assert!(looks_like_text(&[b'a', 0xC3]));
+    assert!(!looks_like_text(&[b'a', 0xFF, b'b']));
+    assert!(!looks_like_text(b"a\0b"));
+}
+
+#[tokio::test]
+async fn test_get_content_type_for_file_and_directory() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let file_path = temp_dir.path().join("data.json");
+    std::fs::write(&file_path, "{\"key\": 1}")?;
+
+    let content_type = fs_service.get_content_type(&file_path).await?.unwrap();
+    assert_eq!(content_type.mime_type, "application/json");
+    assert!(content_type.is_text);
+
+    assert!(fs_service.get_content_type(temp_dir.path()).await?.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_file_refuses_binary_content() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let png = temp_dir.path().join("logo.png");
+    std::fs::write(&png, [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0x0D])?;
+    let blob = temp_dir.path().join("blob.dat");
+    std::fs::write(&blob, b"header\0\xff\xfe")?;
+
+    let error = fs_service.read_file(&png).await.unwrap_err().to_string();
+    assert!(error.contains("is binary (image/png)"), "{}", error);
+    assert!(error.contains("use read_media_file or hexdump_file"), "{}", error);
+
+    let error = fs_service.read_file(&blob).await.unwrap_err().to_string();
+    assert!(error.contains("is binary (application/octet-stream), not text; use hexdump_file"), "{}", error);
+
+    let paths = [png.display().to_string()];
+    let results = fs_service.read_multiple_files(&paths, u64::MAX).await;
+    assert!(results[0].error.as_deref().unwrap().contains("is binary (image/png)"));
+    Ok(())
+}
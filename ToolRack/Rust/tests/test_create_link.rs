@@ -0,0 +1,71 @@
+use aichemistforge_mcp_server::fs_service::symlinks::SymlinkPolicy;
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn service(allowed: &Path) -> FileSystemService {
+    FileSystemService::try_new(&[allowed.to_string_lossy().to_string()], &[]).unwrap()
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_create_symlink_keeps_relative_target() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    fs::create_dir(root.join("config")).unwrap();
+    fs::write(root.join("config/base.toml"), "base").unwrap();
+    let fs_service = service(&root);
+
+    let link = root.join("current.toml");
+    fs_service.create_symlink(&link, Path::new("config/base.toml")).await.unwrap();
+    assert_eq!(fs::read_link(&link).unwrap(), Path::new("config/base.toml"));
+    assert_eq!(fs::read_to_string(&link).unwrap(), "base");
+
+    // Undo takes the link away and leaves the target
+    fs_service.undo_last_operation().await.unwrap().unwrap();
+    assert!(fs::symlink_metadata(&link).is_err());
+    assert!(root.join("config/base.toml").exists());
+}
+
+#[tokio::test]
+async fn test_create_symlink_refuses_target_outside_allowed() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap().join("project");
+    fs::create_dir(&root).unwrap();
+    let fs_service = service(&root);
+
+    let link = root.join("escape");
+    assert!(fs_service.create_symlink(&link, Path::new("../secret.txt")).await.is_err());
+    assert!(fs_service.create_symlink(&link, &temp_dir.path().join("secret.txt")).await.is_err());
+    assert!(fs::symlink_metadata(&link).is_err());
+}
+
+#[tokio::test]
+async fn test_create_symlink_refused_under_deny_policy() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    fs::write(root.join("a.txt"), "a").unwrap();
+    let mut fs_service = service(&root);
+    fs_service.set_symlink_policy(SymlinkPolicy::Deny);
+
+    assert!(fs_service.create_symlink(&root.join("b.txt"), Path::new("a.txt")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_create_hardlink_shares_content() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    let target = root.join("data.bin");
+    fs::write(&target, "one").unwrap();
+    let fs_service = service(&root);
+
+    let link = root.join("alias.bin");
+    fs_service.create_hardlink(&link, &target).await.unwrap();
+    fs::write(&target, "two").unwrap();
+    assert_eq!(fs::read_to_string(&link).unwrap(), "two");
+
+    // A directory or a missing file cannot be hard linked
+    assert!(fs_service.create_hardlink(&root.join("dir_link"), &root).await.is_err());
+    assert!(fs_service.create_hardlink(&root.join("x"), &root.join("missing")).await.is_err());
+}
@@ -0,0 +1,81 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::{FileSystemService, SearchFilesOptions};
+use std::fs;
+use tempfile::TempDir;
+
+// root/
+//   src/main.rs (12 bytes)
+//   .git/config (100 bytes)
+//   node_modules/pkg/index.js (100 bytes)
+//   target/debug/main.rs (100 bytes)
+fn setup_project() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join(".git")).unwrap();
+    fs::create_dir_all(root.join("node_modules").join("pkg")).unwrap();
+    fs::create_dir_all(root.join("target").join("debug")).unwrap();
+    fs::write(root.join("src").join("main.rs"), "fn main() {}").unwrap();
+    fs::write(root.join(".git").join("config"), vec![b'x'; 100]).unwrap();
+    fs::write(root.join("node_modules").join("pkg").join("index.js"), vec![b'x'; 100]).unwrap();
+    fs::write(root.join("target").join("debug").join("main.rs"), vec![b'x'; 100]).unwrap();
+    temp_dir
+}
+
+#[tokio::test]
+async fn test_directory_size_skips_default_excludes() -> ServiceResult<()> {
+    let temp_dir = setup_project();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    assert_eq!(fs_service.calculate_directory_size(temp_dir.path(), false, false).await?, 12);
+    assert_eq!(fs_service.calculate_directory_size(temp_dir.path(), false, true).await?, 312);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_directory_tree_skips_default_excludes() -> ServiceResult<()> {
+    let temp_dir = setup_project();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let tree = fs_service.generate_directory_tree(temp_dir.path(), true, 0, false, false).await?;
+    assert!(tree.contains("src/"));
+    assert!(!tree.contains("node_modules"));
+    assert!(!tree.contains(".git"));
+    assert!(!tree.contains("index.js"));
+
+    let tree = fs_service.generate_directory_tree(temp_dir.path(), true, 0, false, true).await?;
+    assert!(tree.contains("node_modules/"));
+    assert!(tree.contains("index.js"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_files_skips_default_excludes() {
+    let temp_dir = setup_project();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let options = SearchFilesOptions { pattern: "main.rs".to_string(), ..Default::default() };
+    let results = fs_service.search_files(temp_dir.path(), options.clone()).await.unwrap().files;
+    assert_eq!(results.len(), 1);
+    assert!(results[0].contains("src"));
+
+    let options = SearchFilesOptions { include_defaults_excluded: true, ..options };
+    let results = fs_service.search_files(temp_dir.path(), options).await.unwrap().files;
+    assert_eq!(results.len(), 2);
+}
+
+#[tokio::test]
+async fn test_excluded_name_as_root_is_still_walked() -> ServiceResult<()> {
+    let temp_dir = setup_project();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    // Explicitly targeting an excluded directory still reports its contents
+    let size = fs_service
+        .calculate_directory_size(&temp_dir.path().join("node_modules"), false, false)
+        .await?;
+    assert_eq!(size, 100);
+
+    Ok(())
+}
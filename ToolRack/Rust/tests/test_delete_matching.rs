@@ -0,0 +1,77 @@
+use aichemistforge_mcp_server::mcp_types::CallToolRequest;
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+
+fn call(arguments: Value) -> CallToolRequest {
+    common::call("delete_matching", arguments)
+}
+
+#[tokio::test]
+async fn test_delete_matching_previews_then_deletes_confirmed_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("work");
+    fs::create_dir_all(root.join("src/nested")).unwrap();
+    fs::write(root.join("a.tmp"), "a").unwrap();
+    fs::write(root.join("src/nested/b.tmp"), "bb").unwrap();
+    fs::write(root.join("src/keep.rs"), "fn main() {}").unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", "--no-trash", root.to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let root_text = root.display().to_string();
+
+    let preview = handler.handle_call_tool(call(json!({ "path": root_text, "pattern": "*.tmp" }))).await.unwrap();
+    let structured = preview.structured_content.unwrap();
+    assert_eq!(structured["dry_run"], true);
+    assert_eq!(structured["files"].as_array().unwrap().len(), 2);
+    assert_eq!(structured["total_bytes"], 3);
+    assert!(root.join("a.tmp").exists());
+    let token = structured["confirm_token"].as_str().unwrap().to_string();
+
+    // Not deleted without the token of a preview
+    let refused = handler
+        .handle_call_tool(call(json!({ "path": root_text, "pattern": "*.tmp", "dry_run": false, "confirm": true })))
+        .await
+        .unwrap();
+    assert_eq!(refused.is_error, Some(true));
+    assert!(root.join("a.tmp").exists());
+
+    let deleted = handler
+        .handle_call_tool(call(
+            json!({ "path": root_text, "pattern": "*.tmp", "dry_run": false, "confirm": true, "confirm_token": token }),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(deleted.is_error, Some(false));
+    assert!(!root.join("a.tmp").exists());
+    assert!(!root.join("src/nested/b.tmp").exists());
+    assert!(root.join("src/keep.rs").exists());
+}
+
+#[tokio::test]
+async fn test_delete_matching_refuses_when_files_changed_since_preview() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("work");
+    fs::create_dir(&root).unwrap();
+    fs::write(root.join("one.log"), "1").unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", "--no-trash", root.to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let root_text = root.display().to_string();
+
+    let preview = handler.handle_call_tool(call(json!({ "path": root_text, "pattern": "*.log" }))).await.unwrap();
+    let token = preview.structured_content.unwrap()["confirm_token"].as_str().unwrap().to_string();
+    fs::write(root.join("two.log"), "2").unwrap();
+
+    let result = handler
+        .handle_call_tool(call(
+            json!({ "path": root_text, "pattern": "*.log", "dry_run": false, "confirm": true, "confirm_token": token }),
+        ))
+        .await;
+    let error = result.unwrap_err().to_string();
+    assert!(error.contains("preview again"), "{}", error);
+    assert!(root.join("one.log").exists());
+    assert!(root.join("two.log").exists());
+}
@@ -0,0 +1,84 @@
+use aichemistforge_mcp_server::fs_service::roots::Access;
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{call, text};
+
+fn suffixed(dir: &Path, access: &str) -> String {
+    format!("{}:{}", dir.display(), access)
+}
+
+// Text of a refused call: an error, or a result reporting the failed items
+async fn refusal(handler: &MyServerHandler, name: &str, arguments: Value) -> String {
+    match handler.handle_call_tool(call(name, arguments)).await {
+        Ok(result) => text(&result).to_string(),
+        Err(e) => e.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_read_only_directory_refuses_changes() {
+    let (source, output) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let original = source.path().join("main.rs");
+    fs::write(&original, "fn main() {}").unwrap();
+    let args = CommandArguments::parse_from([
+        "server".to_string(),
+        "--tool-style".to_string(),
+        "flat".to_string(),
+        suffixed(source.path(), "ro"),
+        suffixed(output.path(), "rw"),
+    ]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let path = |p: &Path| p.display().to_string();
+
+    let read = handler.handle_call_tool(call("read_file", json!({ "path": path(&original) }))).await.unwrap();
+    assert_eq!(text(&read), "fn main() {}");
+
+    let refused = [
+        ("write_file", json!({ "path": path(&source.path().join("new.rs")), "content": "x" })),
+        ("delete_file", json!({ "path": path(&original), "confirm": true })),
+        ("create_directory", json!({ "path": path(&source.path().join("sub")) })),
+        ("move_files", json!({ "paths": [path(&original)], "destination": path(output.path()) })),
+        ("copy_files", json!({ "paths": [path(output.path())], "destination": path(&source.path().join("copy")) })),
+    ];
+    for (name, arguments) in refused {
+        let message = refusal(&handler, name, arguments).await;
+        assert!(message.contains("read-only directory"), "{}: {}", name, message);
+    }
+    assert_eq!(fs::read_to_string(&original).unwrap(), "fn main() {}");
+    assert!(!source.path().join("sub").exists());
+
+    // Copying out of a read-only directory is fine
+    let copy = json!({ "paths": [path(&original)], "destination": path(output.path()) });
+    let copied = handler.handle_call_tool(call("copy_files", copy)).await.unwrap();
+    assert_ne!(copied.is_error, Some(true), "{}", text(&copied));
+    assert!(output.path().join("main.rs").exists());
+}
+
+#[tokio::test]
+async fn test_nested_directory_overrides_its_parent() {
+    let root = TempDir::new().unwrap();
+    let generated = root.path().join("generated");
+    fs::create_dir(&generated).unwrap();
+    let allowed = [suffixed(root.path(), "ro"), suffixed(&generated, "rw")];
+    let fs_service = FileSystemService::try_new(&allowed, &[]).unwrap();
+
+    let (path, access) = fs_service.validate_path_access(&generated.join("out.rs")).await.unwrap();
+    assert_eq!(access, Access::ReadWrite);
+    fs_service.write_file(&path, &"// generated".to_string()).await.unwrap();
+
+    let (path, access) = fs_service.validate_path_access(&root.path().join("lib.rs")).await.unwrap();
+    assert_eq!(access, Access::ReadOnly);
+    let refused = fs_service.write_file(&path, &"// lib".to_string()).await.unwrap_err();
+    assert!(refused.to_string().contains("read-only directory"), "{}", refused);
+
+    let report = fs_service.roots();
+    let accesses: Vec<_> = report.roots.iter().map(|root| root.access).collect();
+    assert_eq!(accesses, [Some(Access::ReadOnly), Some(Access::ReadWrite)]);
+    assert!(report.describe().contains("These directories are read-only:"));
+}
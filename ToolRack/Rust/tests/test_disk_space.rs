@@ -0,0 +1,45 @@
+use aichemistforge_mcp_server::error::{ServiceError, ServiceResult};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_disk_space_for_existing_directory() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let space = fs_service.get_disk_space(temp_dir.path()).await?;
+
+    assert!(space.total > 0);
+    assert!(space.available <= space.total);
+    assert!(space.used() <= space.total);
+    assert_eq!(space.queried_path, temp_dir.path());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_disk_space_for_missing_destination_uses_ancestor() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    // Destination that does not exist yet, e.g. before extracting an archive
+    let destination = temp_dir.path().join("not").join("created").join("yet");
+    let space = fs_service.get_disk_space(&destination).await?;
+
+    assert_eq!(space.queried_path, temp_dir.path());
+    assert!(space.total > 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_disk_space_respects_blocklist() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let blocked = vec![temp_dir.path().display().to_string()];
+    let fs_service = FileSystemService::try_new(&[], &blocked)?;
+
+    let result = fs_service.get_disk_space(temp_dir.path()).await;
+    assert!(matches!(result, Err(ServiceError::PathNotAllowed)));
+
+    Ok(())
+}
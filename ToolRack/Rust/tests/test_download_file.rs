@@ -0,0 +1,95 @@
+#![cfg(feature = "download")]
+
+use aichemistforge_mcp_server::fs_service::download::DownloadPolicy;
+use aichemistforge_mcp_server::FileSystemService;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+#[test]
+fn test_download_policy() {
+    let policy = DownloadPolicy::new(&["example.com".to_string(), "https://files.test/releases/".to_string()], 10);
+    assert!(policy.check("https://example.com/a.zip").is_ok());
+    assert!(policy.check("http://cdn.example.com:8080/a").is_ok());
+    assert!(policy.check("https://badexample.com/a").is_err());
+    assert!(policy.check("https://example.com.evil.net/a").is_err());
+    assert!(policy.check("https://files.test/releases/v1.tar.gz").is_ok());
+    assert!(policy.check("https://files.test/private/key").is_err());
+    assert!(policy.check("ftp://example.com/a").is_err());
+    assert!(policy.check("https://user:pw@example.com/a").is_err());
+
+    let disabled = DownloadPolicy::default();
+    assert!(disabled.check("https://example.com/a").unwrap_err().contains("--download-allow"));
+}
+
+/// Serve each accepted connection one canned response, by request path
+async fn serve(routes: Vec<(&'static str, String)>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![0u8; 4096];
+            let read = socket.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..read]).to_string();
+            let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+            let response = routes
+                .iter()
+                .find(|(route, _)| *route == path)
+                .map(|(_, response)| response.clone())
+                .unwrap_or_else(|| "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string());
+            socket.write_all(response.as_bytes()).await.unwrap();
+            let _ = socket.shutdown().await;
+        }
+    });
+    format!("http://{}", address)
+}
+
+fn ok(body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )
+}
+
+#[tokio::test]
+async fn test_download_file_writes_and_hashes() {
+    let base = serve(vec![
+        ("/hello.txt", ok("hello world")),
+        ("/moved", "HTTP/1.1 302 Found\r\nLocation: /hello.txt\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()),
+        ("/away", "HTTP/1.1 302 Found\r\nLocation: http://example.com/x\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()),
+        ("/big", ok(&"x".repeat(64))),
+    ])
+    .await;
+    let temp_dir = TempDir::new().unwrap();
+    let mut fs_service = FileSystemService::try_new(&[temp_dir.path().display().to_string()], &[]).unwrap();
+    fs_service.set_download_policy(DownloadPolicy::new(std::slice::from_ref(&base), 32));
+    let target = temp_dir.path().join("hello.txt");
+
+    let report = fs_service.download_file(&format!("{}/hello.txt", base), &target, false).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&target).unwrap(), "hello world");
+    assert_eq!(report.bytes, 11);
+    assert_eq!(report.sha256, "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9");
+    assert_eq!(report.content_type.as_deref(), Some("text/plain"));
+
+    // Existing files are kept unless overwrite is set
+    assert!(fs_service.download_file(&format!("{}/hello.txt", base), &target, false).await.is_err());
+    let report = fs_service.download_file(&format!("{}/moved", base), &target, true).await.unwrap();
+    assert_eq!(report.final_url, format!("{}/hello.txt", base));
+
+    // Redirects leaving the allowlist, oversized bodies and error statuses leave nothing behind
+    let other = temp_dir.path().join("other.bin");
+    let error = fs_service.download_file(&format!("{}/away", base), &other, false).await.unwrap_err();
+    assert!(error.to_string().contains("redirect refused"), "{}", error);
+    assert!(fs_service.download_file(&format!("{}/big", base), &other, false).await.is_err());
+    assert!(fs_service.download_file(&format!("{}/missing", base), &other, false).await.is_err());
+    assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+
+    // The destination is validated like any other write
+    let outside = TempDir::new().unwrap();
+    assert!(fs_service
+        .download_file(&format!("{}/hello.txt", base), &outside.path().join("x"), false)
+        .await
+        .is_err());
+}
@@ -0,0 +1,122 @@
+use aichemistforge_mcp_server::mcp_types::{CallToolResult, Content};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::ffi::OsString;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::call;
+
+fn text(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .map(|item| match item {
+            Content::Text(text) => text.text.clone(),
+            _ => String::new(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn handler(extra_args: &[&str], dir: &TempDir) -> MyServerHandler {
+    let mut args = vec!["server", "--tool-style", "flat"];
+    args.extend(extra_args);
+    args.push(dir.path().to_str().unwrap());
+    match MyServerHandler::new(&CommandArguments::parse_from(args)) {
+        Ok(handler) => handler,
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn test_dry_run_flag_and_environment() {
+    assert!(CommandArguments::parse_from(["server", "--dry-run"]).dry_run);
+    assert!(CommandArguments::try_parse_from(["server", "--dry-run", "--sandbox"]).is_err());
+    let parsed = CommandArguments::parse_with_env(["server"], |name| {
+        (name == "AICHEMIST_DRY_RUN").then(|| OsString::from("true"))
+    })
+    .unwrap();
+    assert!(parsed.dry_run);
+}
+
+#[tokio::test]
+async fn test_dry_run_previews_mutations_without_touching_disk() {
+    let temp = TempDir::new().unwrap();
+    let notes = temp.path().join("notes.txt");
+    fs::write(&notes, "alpha\nbeta\n").unwrap();
+    fs::create_dir(temp.path().join("old")).unwrap();
+    fs::write(temp.path().join("old").join("a.log"), "a").unwrap();
+    fs::write(temp.path().join("old").join("b.log"), "b").unwrap();
+    fs::create_dir(temp.path().join("copy")).unwrap();
+    let handler = handler(&["--dry-run"], &temp);
+    assert!(handler.startup_message().contains("Dry run: enabled"));
+
+    let new_file = temp.path().join("new.txt");
+    let result = handler.handle_call_tool(call("write_file", json!({ "path": new_file, "content": "hello\n" }))).await.unwrap();
+    assert!(text(&result).starts_with("Dry run: nothing was written to disk."), "{}", text(&result));
+    assert!(text(&result).contains("[added]") && text(&result).contains("+hello"), "{}", text(&result));
+    assert!(!new_file.exists());
+
+    let edits = json!([{ "oldText": "beta", "newText": "gamma" }]);
+    let result = handler.handle_call_tool(call("edit_file", json!({ "path": notes, "edits": edits }))).await.unwrap();
+    assert!(text(&result).contains("-beta") && text(&result).contains("+gamma"), "{}", text(&result));
+    assert_eq!(fs::read_to_string(&notes).unwrap(), "alpha\nbeta\n");
+
+    let old = temp.path().join("old");
+    let result = handler.handle_call_tool(call("delete_file", json!({ "path": old, "confirm": true }))).await.unwrap();
+    assert!(text(&result).contains("[deleted]"), "{}", text(&result));
+    assert!(text(&result).contains("a.log") && text(&result).contains("b.log"), "{}", text(&result));
+    assert!(old.join("a.log").exists());
+
+    let result = handler
+        .handle_call_tool(call("copy_files", json!({ "paths": [old], "destination": temp.path().join("copy") })))
+        .await
+        .unwrap();
+    assert!(text(&result).contains("[added]") && text(&result).contains("b.log"), "{}", text(&result));
+    assert_eq!(fs::read_dir(temp.path().join("copy")).unwrap().count(), 0);
+
+    // Reads are answered as usual, without a preview
+    let result = handler.handle_call_tool(call("read_file", json!({ "path": notes }))).await.unwrap();
+    assert_eq!(result.content.len(), 1);
+    assert!(!text(&result).contains("Dry run"));
+
+    // Operations that bypass the overlay are refused rather than run
+    let operations = json!([{ "op": "delete", "path": notes }]);
+    let result = handler.handle_call_tool(call("batch_operations", json!({ "operations": operations }))).await;
+    let message = match result {
+        Ok(result) => text(&result),
+        Err(e) => e.to_string(),
+    };
+    assert!(message.contains("cannot be previewed in dry-run mode"), "{}", message);
+    assert!(notes.exists());
+
+    // --dry-run cannot be switched off by a client
+    let result = handler.handle_call_tool(call("set_dry_run", json!({ "enabled": false }))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    handler.handle_call_tool(call("write_file", json!({ "path": new_file, "content": "x" }))).await.unwrap();
+    assert!(!new_file.exists());
+}
+
+#[tokio::test]
+async fn test_set_dry_run_toggles_at_runtime() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("file.txt");
+    let handler = handler(&[], &temp);
+
+    let result = handler.handle_call_tool(call("set_dry_run", json!({ "enabled": true }))).await.unwrap();
+    assert!(text(&result).contains("Dry-run mode is on"));
+    handler.handle_call_tool(call("write_file", json!({ "path": path, "content": "one" }))).await.unwrap();
+    assert!(!path.exists());
+
+    let result = handler.handle_call_tool(call("set_dry_run", json!({ "enabled": false }))).await.unwrap();
+    assert!(text(&result).contains("Dry-run mode is off"));
+    handler.handle_call_tool(call("write_file", json!({ "path": path, "content": "one" }))).await.unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "one");
+
+    let sandboxed = self::handler(&["--sandbox"], &temp);
+    let result = sandboxed.handle_call_tool(call("set_dry_run", json!({ "enabled": true }))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+}
@@ -0,0 +1,89 @@
+use aichemistforge_mcp_server::fs_service::edits::{replace_ignoring_whitespace, EditMatch};
+use aichemistforge_mcp_server::mcp_types::Content;
+use aichemistforge_mcp_server::tools::{EditFileTool, EditOperation};
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use tempfile::TempDir;
+
+const SOURCE: &str = "fn main() {\n    if ready {\n        start();   \n    }\n}\n";
+
+fn replace(old_text: &str, new_text: &str) -> EditOperation {
+    EditOperation { old_text: old_text.to_string(), new_text: new_text.to_string(), ..Default::default() }
+}
+
+#[test]
+fn test_whitespace_tolerant_replacement_keeps_the_file_indentation() {
+    // Pasted without indentation and without the trailing spaces
+    let replaced =
+        replace_ignoring_whitespace(SOURCE, "if ready {\n    start();\n}", "if ready {\n    warm_up();\n    start();\n}").unwrap();
+    assert_eq!(replaced, "fn main() {\n    if ready {\n        warm_up();\n        start();\n    }\n}\n");
+
+    // Deleting the lines outright
+    assert_eq!(replace_ignoring_whitespace(SOURCE, "\tstart();\n", "").unwrap(), "fn main() {\n    if ready {\n    }\n}\n");
+    assert_eq!(replace_ignoring_whitespace(SOURCE, "stop();", "x"), Err(0));
+    assert_eq!(replace_ignoring_whitespace(SOURCE, "  \n", "x"), Err(0));
+    assert_eq!(replace_ignoring_whitespace("a\n  b\na\n b \n", " a\nb", "x"), Err(2));
+}
+
+#[tokio::test]
+async fn test_edit_file_reports_the_fallback_used() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("main.rs");
+    fs::write(&file, SOURCE).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let edits = vec![replace("fn main()", "fn run()"), replace("if ready {\nstart();", "if ready {\n    begin();"), replace("missing", "x")];
+    let outcome = fs_service.apply_file_edits_as(&file, edits.clone(), Some(true), None, Default::default()).await.unwrap();
+    assert_eq!(outcome.matches, [EditMatch::Exact, EditMatch::IgnoringWhitespace, EditMatch::NotFound]);
+
+    // One edit missing fails the call and writes nothing
+    let edit_file = |edits: &[EditOperation]| EditFileTool {
+        path: file.display().to_string(),
+        edits: edits.to_vec(),
+        dry_run: None,
+        encoding: None,
+    };
+    let result = edit_file(&edits).run_tool(&fs_service).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    let Content::Text(text) = &result.content[0] else { panic!("expected text content") };
+    assert!(text.text.contains("Edit 3: oldText not found"), "{}", text.text);
+    let structured = result.structured_content.unwrap();
+    assert_eq!(structured["written"], false);
+    assert_eq!(structured["edits"][1]["status"], "ignoring_whitespace");
+    assert_eq!(structured["edits"][2]["status"], "not_found");
+    assert_eq!(fs::read_to_string(&file).unwrap(), SOURCE);
+
+    let result = edit_file(&edits[..2]).run_tool(&fs_service).await.unwrap();
+    assert_eq!(result.is_error, Some(false));
+    let Content::Text(text) = &result.content[0] else { panic!("expected text content") };
+    assert!(text.text.contains("Edit 2: matched after ignoring indentation and trailing whitespace"), "{}", text.text);
+    assert!(!text.text.contains("Edit 1:"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "fn run() {\n    if ready {\n        begin();\n    }\n}\n");
+}
+
+#[tokio::test]
+async fn test_edit_file_refuses_ambiguous_old_text() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("config.ini");
+    fs::write(&file, "[a]\nenabled=false\n[b]\nenabled=false\n").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let outcome = fs_service
+        .apply_file_edits_as(&file, vec![replace("enabled=false", "enabled=true")], None, None, Default::default())
+        .await
+        .unwrap();
+    assert_eq!(outcome.matches, [EditMatch::Ambiguous { occurrences: 2 }]);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "[a]\nenabled=false\n[b]\nenabled=false\n");
+
+    // The library call turns it into an error naming the edit
+    let error = fs_service
+        .apply_file_edits(&file, vec![replace("[b]\nenabled=false", "[b]\nenabled=true"), replace("[c]", "")], None, None)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("edit 2: oldText not found"), "{}", error);
+    fs_service
+        .apply_file_edits(&file, vec![replace("[b]\nenabled=false", "[b]\nenabled=true")], None, None)
+        .await
+        .unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "[a]\nenabled=false\n[b]\nenabled=true\n");
+}
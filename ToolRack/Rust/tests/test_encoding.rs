@@ -0,0 +1,121 @@
+use aichemistforge_mcp_server::fs_service::encoding::{decode, encode, guess_encoding, TextEncoding};
+use aichemistforge_mcp_server::mcp_types::Content;
+use aichemistforge_mcp_server::tools::{EditFileTool, EditOperation, ReadFileTool};
+use aichemistforge_mcp_server::FileSystemService;
+use encoding_rs::{UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1251, WINDOWS_1252};
+use std::fs;
+use tempfile::TempDir;
+
+fn utf16le(text: &str, bom: bool) -> Vec<u8> {
+    let mut bytes = if bom { vec![0xFF, 0xFE] } else { Vec::new() };
+    bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    bytes
+}
+
+fn read(path: &std::path::Path, encoding: &str) -> ReadFileTool {
+    ReadFileTool {
+        path: path.display().to_string(),
+        with_line_numbers: false,
+        offset_bytes: None,
+        length_bytes: None,
+        cursor: None,
+        encoding: Some(encoding.to_string()),
+    }
+}
+
+fn texts(result: &aichemistforge_mcp_server::mcp_types::CallToolResult) -> Vec<&str> {
+    result
+        .content
+        .iter()
+        .map(|content| match content {
+            Content::Text(text) => text.text.as_str(),
+            _ => panic!("expected text content"),
+        })
+        .collect()
+}
+
+#[test]
+fn test_encoding_labels_and_guesses() {
+    assert_eq!("auto".parse::<TextEncoding>().unwrap(), TextEncoding::Auto);
+    assert_eq!("UTF-16".parse::<TextEncoding>().unwrap(), TextEncoding::Known(UTF_16LE));
+    assert_eq!("latin-1".parse::<TextEncoding>().unwrap(), TextEncoding::Known(WINDOWS_1252));
+    assert!("klingon".parse::<TextEncoding>().unwrap_err().to_string().contains("Unknown encoding 'klingon'"));
+
+    assert_eq!(guess_encoding("plain ünïcode".as_bytes()), UTF_8);
+    assert_eq!(guess_encoding(&utf16le("no mark here", false)), UTF_16LE);
+    let big_endian: Vec<u8> = "no mark here".encode_utf16().flat_map(u16::to_be_bytes).collect();
+    assert_eq!(guess_encoding(&big_endian), UTF_16BE);
+    assert_eq!(guess_encoding(b"caf\xe9"), WINDOWS_1252);
+    let (cyrillic, _, _) = WINDOWS_1251.encode("Привет! Это обычный текст в старой кодировке Windows.");
+    assert_eq!(guess_encoding(&cyrillic), WINDOWS_1251);
+
+    // A code page read from the bytes alone is reported as a guess
+    let decoded = decode(&cyrillic, TextEncoding::Auto).unwrap();
+    assert!(decoded.text.starts_with("Привет!"));
+    assert_eq!(decoded.describe(), "windows-1251 (guessed; pass the encoding if the text looks wrong)");
+    assert!(!decode(&cyrillic, TextEncoding::Known(WINDOWS_1251)).unwrap().guessed);
+
+    // A byte order mark beats the encoding asked for
+    let decoded = decode(&utf16le("hi", true), TextEncoding::Known(UTF_8)).unwrap();
+    assert_eq!((decoded.text.as_str(), decoded.describe().as_str()), ("hi", "UTF-16LE with BOM"));
+    assert!(decode(b"caf\xe9", TextEncoding::default()).unwrap_err().to_string().contains("not valid UTF-8"));
+
+    assert_eq!(encode("hi", UTF_16LE, true).unwrap(), utf16le("hi", true));
+    assert_eq!(encode("café", WINDOWS_1252, false).unwrap(), b"caf\xe9");
+    assert!(encode("日本", WINDOWS_1252, false).is_err());
+}
+
+#[tokio::test]
+async fn test_read_and_edit_a_utf16_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("settings.ini");
+    fs::write(&file, utf16le("[main]\r\nname=old\r\n", true)).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    // Without an encoding the NULs make it look binary
+    assert!(fs_service.read_file(&file).await.is_err());
+
+    let result = read(&file, "auto").run_tool(&fs_service).await.unwrap();
+    assert_eq!(texts(&result), ["[main]\r\nname=old\r\n", "Decoded from UTF-16LE with BOM"]);
+
+    let edit = EditFileTool {
+        path: file.display().to_string(),
+        edits: vec![EditOperation { old_text: "name=old".to_string(), new_text: "name=new".to_string(), ..Default::default() }],
+        dry_run: None,
+        encoding: Some("auto".to_string()),
+    };
+    edit.run_tool(&fs_service).await.unwrap();
+    assert_eq!(fs::read(&file).unwrap(), utf16le("[main]\r\nname=new\r\n", true));
+}
+
+#[tokio::test]
+async fn test_read_and_edit_a_legacy_code_page() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    fs::write(&file, b"caf\xe9 au lait\n").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let result = read(&file, "latin-1").run_tool(&fs_service).await.unwrap();
+    assert_eq!(texts(&result), ["café au lait\n", "Decoded from windows-1252"]);
+    let wrong = read(&file, "utf-8").run_tool(&fs_service).await.unwrap_err();
+    assert!(wrong.to_string().contains("not valid UTF-8"), "{}", wrong);
+    let mixed = ReadFileTool { offset_bytes: Some(0), ..read(&file, "auto") };
+    assert!(mixed.run_tool(&fs_service).await.is_err());
+
+    let edit = |new_text: &str| EditFileTool {
+        path: file.display().to_string(),
+        edits: vec![EditOperation { old_text: "au lait".to_string(), new_text: new_text.to_string(), ..Default::default() }],
+        dry_run: None,
+        encoding: Some("latin-1".to_string()),
+    };
+    edit("crème").run_tool(&fs_service).await.unwrap();
+    assert_eq!(fs::read(&file).unwrap(), b"caf\xe9 cr\xe8me\n");
+
+    // Text the code page cannot hold leaves the file alone
+    let error = EditFileTool { edits: vec![EditOperation { old_text: "crème".to_string(), new_text: "茶".to_string(), ..Default::default() }], ..edit("") }
+        .run_tool(&fs_service)
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("cannot represent"), "{}", error);
+    assert_eq!(fs::read(&file).unwrap(), b"caf\xe9 cr\xe8me\n");
+}
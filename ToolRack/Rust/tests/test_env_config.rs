@@ -0,0 +1,73 @@
+use aichemistforge_mcp_server::tools::ToolStyle;
+use aichemistforge_mcp_server::CommandArguments;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+fn parse(args: &[&str], vars: &[(&str, OsString)]) -> anyhow::Result<CommandArguments> {
+    let vars: HashMap<String, OsString> = vars.iter().map(|(k, v)| (k.to_string(), v.clone())).collect();
+    CommandArguments::parse_with_env(std::iter::once("server").chain(args.iter().copied()), |name| vars.get(name).cloned())
+}
+
+fn dirs(paths: &[&str]) -> OsString {
+    std::env::join_paths(paths).unwrap()
+}
+
+#[test]
+fn test_environment_fills_unset_flags() {
+    let args = parse(
+        &[],
+        &[
+            ("AICHEMIST_ALLOWED_DIRS", dirs(&["/work/a,b", "/work/c"])),
+            ("AICHEMIST_BLOCKED_DIRS", dirs(&["/work/c/secret"])),
+            ("AICHEMIST_CONFIG", "/etc/aichemist.toml".into()),
+            ("AICHEMIST_SLOW_OP_THRESHOLD_MS", "250".into()),
+            ("AICHEMIST_DOWNLOAD_ALLOW", "github.com, example.com".into()),
+            ("AICHEMIST_SANDBOX", "true".into()),
+            ("AICHEMIST_TOOL_STYLE", "flat".into()),
+        ],
+    )
+    .unwrap();
+
+    // Commas are part of a path; entries are split on the platform separator
+    assert_eq!(args.allowed_directories, ["/work/a,b", "/work/c"]);
+    assert_eq!(args.blocked_directories, ["/work/c/secret"]);
+    assert_eq!(args.config, Some(PathBuf::from("/etc/aichemist.toml")));
+    assert_eq!(args.slow_op_threshold_ms, 250);
+    assert_eq!(args.download_allow, ["github.com", "example.com"]);
+    assert!(args.sandbox);
+    assert_eq!(args.tool_style, ToolStyle::Flat);
+}
+
+#[test]
+fn test_command_line_flags_override_environment() {
+    let args = parse(
+        &["--slow-op-threshold-ms", "900", "--tool-style", "both", "/cli/dir"],
+        &[
+            ("AICHEMIST_ALLOWED_DIRS", dirs(&["/env/dir"])),
+            ("AICHEMIST_SLOW_OP_THRESHOLD_MS", "250".into()),
+            ("AICHEMIST_TOOL_STYLE", "flat".into()),
+            ("AICHEMIST_PLUGINS_DIR", "/env/plugins".into()),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(args.allowed_directories, ["/cli/dir"]);
+    assert_eq!(args.slow_op_threshold_ms, 900);
+    assert_eq!(args.tool_style, ToolStyle::Both);
+    assert_eq!(args.plugins_dir, Some(PathBuf::from("/env/plugins")));
+}
+
+#[test]
+fn test_invalid_environment_values_are_reported() {
+    let error = parse(&[], &[("AICHEMIST_DOWNLOAD_MAX_BYTES", "lots".into())]).unwrap_err();
+    assert!(error.to_string().contains("AICHEMIST_DOWNLOAD_MAX_BYTES=lots"));
+
+    let error = parse(&[], &[("AICHEMIST_SANDBOX", "maybe".into())]).unwrap_err();
+    assert!(error.to_string().contains("AICHEMIST_SANDBOX"));
+
+    // Without any variables the defaults apply
+    let args = parse(&[], &[]).unwrap();
+    assert!(args.allowed_directories.is_empty() && !args.sandbox);
+    assert_eq!(args.tool_style, ToolStyle::Grouped);
+}
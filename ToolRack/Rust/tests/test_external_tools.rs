@@ -0,0 +1,140 @@
+#![cfg(unix)]
+
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{call, text};
+
+const CONFIG: &str = r#"
+allowed_commands = ["echo", "pwd", "sleep", "false"]
+
+[[tools]]
+name = "echo_files"
+command = ["echo", "{files}", "--mode={mode}"]
+path_arguments = ["files"]
+
+[[tools]]
+name = "where_am_i"
+command = ["pwd"]
+working_dir = "{dir}"
+path_arguments = ["dir"]
+
+[[tools]]
+name = "nap"
+command = ["sleep", "{seconds}"]
+timeout_secs = 1
+
+[[tools]]
+name = "fail"
+command = ["false"]
+"#;
+
+fn handler(root: &Path, config: &str) -> Result<MyServerHandler, String> {
+    let config_path = root.join("server.toml");
+    fs::write(&config_path, config).unwrap();
+    let allowed = root.join("allowed");
+    fs::create_dir_all(&allowed).unwrap();
+    let args = CommandArguments::parse_from([
+        "server",
+        "--config",
+        config_path.to_str().unwrap(),
+        allowed.to_str().unwrap(),
+    ]);
+    MyServerHandler::new(&args).map_err(|e| e.to_string())
+}
+
+fn config_error(root: &Path, config: &str) -> String {
+    match handler(root, config) {
+        Ok(_) => panic!("config should have been rejected"),
+        Err(e) => e,
+    }
+}
+
+#[tokio::test]
+async fn test_external_tools_are_listed_and_fill_their_templates() {
+    let temp = TempDir::new().unwrap();
+    let handler = handler(temp.path(), CONFIG).unwrap();
+    let allowed = fs::canonicalize(temp.path().join("allowed")).unwrap();
+    fs::create_dir(allowed.join("sub")).unwrap();
+
+    let names: Vec<String> = handler.handle_list_tools().await.unwrap().tools.into_iter().map(|t| t.name).collect();
+    assert!(names.contains(&"echo_files".to_string()) && names.contains(&"fail".to_string()));
+    assert!(handler.startup_message().contains("External tools: echo_files (echo)"));
+
+    let a = allowed.join("a.txt");
+    let b = allowed.join("b.txt");
+    let result = handler
+        .handle_call_tool(call("echo_files", serde_json::json!({ "files": [a, b], "mode": "fast" })))
+        .await
+        .unwrap();
+    assert_eq!(result.is_error, Some(false));
+    assert!(text(&result).contains(&format!("{} {} --mode=fast", a.display(), b.display())));
+    // Working directory defaults to the first allowed directory
+    assert!(text(&result).contains(&format!("Working directory: {}", allowed.display())));
+
+    let result = handler
+        .handle_call_tool(call("where_am_i", serde_json::json!({ "dir": allowed.join("sub") })))
+        .await
+        .unwrap();
+    assert!(text(&result).contains(&format!("--- stdout ---\n{}", allowed.join("sub").display())));
+}
+
+#[tokio::test]
+async fn test_external_tool_arguments_are_checked() {
+    let temp = TempDir::new().unwrap();
+    let handler = handler(temp.path(), CONFIG).unwrap();
+    let outside = temp.path().join("outside.txt");
+
+    // Path arguments and working directories follow the allow/block rules
+    assert!(handler
+        .handle_call_tool(call("echo_files", serde_json::json!({ "files": [outside], "mode": "fast" })))
+        .await
+        .is_err());
+    assert!(handler
+        .handle_call_tool(call("where_am_i", serde_json::json!({ "dir": temp.path() })))
+        .await
+        .is_err());
+    // A standalone value cannot become an option of the command
+    assert!(handler
+        .handle_call_tool(call("nap", serde_json::json!({ "seconds": "--help" })))
+        .await
+        .is_err());
+    // Embedded placeholders are required
+    assert!(handler
+        .handle_call_tool(call("echo_files", serde_json::json!({ "files": [] })))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_external_tools_time_out_and_report_exit_status() {
+    let temp = TempDir::new().unwrap();
+    let handler = handler(temp.path(), CONFIG).unwrap();
+
+    let error = handler
+        .handle_call_tool(call("nap", serde_json::json!({ "seconds": 10 })))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("timed out after 1 seconds"));
+
+    let result = handler.handle_call_tool(call("fail", serde_json::json!({}))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("Exit status: 1"));
+}
+
+#[test]
+fn test_external_tool_config_is_validated() {
+    let temp = TempDir::new().unwrap();
+    let not_allowed = "allowed_commands = []\n[[tools]]\nname = \"list\"\ncommand = [\"ls\"]\n";
+    assert!(config_error(temp.path(), not_allowed).contains("not in allowed_commands"));
+
+    let clash = "allowed_commands = [\"ls\"]\n[[tools]]\nname = \"file_management\"\ncommand = [\"ls\"]\n";
+    assert!(config_error(temp.path(), clash).contains("already exists"));
+
+    let unknown_field = "allowed_commands = [\"ls\"]\n[[tools]]\nname = \"list\"\ncommand = [\"ls\"]\nshell = true\n";
+    assert!(config_error(temp.path(), unknown_field).contains("unknown field"));
+}
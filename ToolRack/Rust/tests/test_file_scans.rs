@@ -0,0 +1,184 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::scan::{parse_timestamp, FileQuery, FileSort, TimeField};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, Content};
+use aichemistforge_mcp_server::tools::{FileSystemTools, ToolStyle};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::fs::{self, File, FileTimes};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+const DAY: u64 = 86_400;
+
+fn write_with_age(path: &Path, content: &str, age_days: u64) {
+    fs::write(path, content).unwrap();
+    let time = SystemTime::now() - Duration::from_secs(age_days * DAY);
+    let times = FileTimes::new().set_modified(time).set_accessed(time);
+    File::options().write(true).open(path).unwrap().set_times(times).unwrap();
+}
+
+// root/
+//   old.log        (400 days, 10 bytes)
+//   older.log      (800 days, 20 bytes)
+//   fresh.log      (1 day)
+//   archive/old.txt (100 days, 5 bytes)
+fn setup_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir(root.join("archive")).unwrap();
+    write_with_age(&root.join("old.log"), "0123456789", 400);
+    write_with_age(&root.join("older.log"), "01234567890123456789", 800);
+    write_with_age(&root.join("fresh.log"), "new", 1);
+    write_with_age(&root.join("archive").join("old.txt"), "12345", 100);
+    temp_dir
+}
+
+fn names(paths: impl IntoIterator<Item = String>) -> Vec<String> {
+    paths
+        .into_iter()
+        .map(|p| Path::new(&p).file_name().unwrap().to_string_lossy().into_owned())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_find_stale_files_oldest_first() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let stale = fs_service
+        .find_stale_files(temp_dir.path(), &FileQuery::default(), 30, TimeField::Modified)
+        .await?;
+    assert_eq!(names(stale.into_iter().map(|r| r.path)), vec!["older.log", "old.log", "old.txt"]);
+
+    let query = FileQuery { pattern: Some("*.log".to_string()), min_bytes: Some(15), ..Default::default() };
+    let stale = fs_service.find_stale_files(temp_dir.path(), &query, 30, TimeField::Accessed).await?;
+    assert_eq!(names(stale.into_iter().map(|r| r.path)), vec!["older.log"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_recent_changes_newest_first() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let since = SystemTime::now() - Duration::from_secs(200 * DAY);
+    let recent = fs_service.find_recent_changes(temp_dir.path(), &FileQuery::default(), since, None).await?;
+    assert_eq!(names(recent.into_iter().map(|r| r.path)), vec!["fresh.log", "old.txt"]);
+
+    let query = FileQuery { exclude_patterns: vec!["archive".to_string()], ..Default::default() };
+    let recent = fs_service.find_recent_changes(temp_dir.path(), &query, since, None).await?;
+    assert_eq!(names(recent.into_iter().map(|r| r.path)), vec!["fresh.log"]);
+
+    // A closed window leaves out what changed after it, still newest first
+    let since = SystemTime::now() - Duration::from_secs(1000 * DAY);
+    let until = SystemTime::now() - Duration::from_secs(50 * DAY);
+    let window = fs_service.find_recent_changes(temp_dir.path(), &FileQuery::default(), since, Some(until)).await?;
+    assert_eq!(names(window.into_iter().map(|r| r.path)), vec!["old.txt", "old.log", "older.log"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_recent_files_is_find_recent_changes_by_another_name() {
+    let temp_dir = setup_tree();
+    let root = temp_dir.path().to_str().unwrap();
+    let handler = MyServerHandler::new(&CommandArguments::parse_from(["server", "--tool-style", "both", root])).unwrap();
+    let grouped = FileSystemTools::tools(ToolStyle::Grouped);
+    let search = grouped.iter().find(|tool| tool.name == "search_and_analysis").unwrap();
+    assert!(search.input_schema["properties"]["operation"]["enum"].as_array().unwrap().contains(&json!("find_recent_files")));
+
+    let since = chrono::Local::now() - chrono::Duration::days(1000);
+    let end = chrono::Local::now() - chrono::Duration::days(50);
+    let arguments = json!({
+        "path": root,
+        "since": since.format("%Y-%m-%d %H:%M:%S").to_string(),
+        "end": end.format("%Y-%m-%d %H:%M:%S").to_string(),
+        "output_format": "json",
+    });
+    let request = CallToolRequest {
+        params: CallToolParams { name: "find_recent_files".to_string(), arguments: Some(arguments) },
+    };
+    let result = handler.handle_call_tool(request).await.unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    assert_ne!(result.is_error, Some(true), "{}", text.text);
+    let report: serde_json::Value = serde_json::from_str(&text.text).unwrap();
+    let files = report["files"].as_array().unwrap().iter().map(|f| f["path"].as_str().unwrap().to_string());
+    assert_eq!(names(files), vec!["old.txt", "old.log", "older.log"]);
+}
+
+#[test]
+fn test_parse_timestamp_formats() {
+    assert!(parse_timestamp("2024-03-01T14:00:00Z").is_ok());
+    assert!(parse_timestamp("2024-03-01 14:00:00").is_ok());
+    assert!(parse_timestamp("2024-03-01").is_ok());
+    assert!(parse_timestamp("yesterday").is_err());
+
+    let rfc = parse_timestamp("2024-03-01T14:00:00+00:00").unwrap();
+    let later = parse_timestamp("2024-03-01T15:30:00+00:00").unwrap();
+    assert_eq!(later.duration_since(rfc).unwrap(), Duration::from_secs(5400));
+}
+
+#[tokio::test]
+async fn test_directory_profile_histograms() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let profile = fs_service.directory_profile(temp_dir.path(), &FileQuery::default()).await?;
+    assert_eq!(profile.file_count, 4);
+    assert_eq!(profile.total_bytes, 38);
+
+    let small = profile.size_histogram.iter().find(|b| b.label == "1 B - 1 KB").unwrap();
+    assert_eq!(small.files, 4);
+
+    let ages: Vec<usize> = profile.modified_age_histogram.iter().map(|b| b.files).collect();
+    assert_eq!(ages, vec![0, 1, 0, 0, 1, 1, 1]);
+
+    assert_eq!(profile.depth.max, 2);
+    assert_eq!(profile.depth.histogram.len(), 2);
+    assert_eq!(profile.depth.histogram[0].files, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_files_by_size_range_and_sort() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let query = FileQuery { min_bytes: Some(5), max_bytes: Some(10), ..Default::default() };
+    let files = fs_service.find_files_by_size(temp_dir.path(), &query, FileSort::SizeDesc).await?;
+    assert_eq!(names(files.into_iter().map(|r| r.path)), vec!["old.log", "old.txt"]);
+
+    let files = fs_service
+        .find_files_by_size(temp_dir.path(), &FileQuery::default(), FileSort::SizeAsc)
+        .await?;
+    assert_eq!(files.first().unwrap().size, 3);
+    assert_eq!(files.last().unwrap().size, 20);
+
+    assert!("largest".parse::<FileSort>().is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_find_files_by_mtime_window() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let days_ago = |days: u64| SystemTime::now() - Duration::from_secs(days * DAY);
+
+    let files = fs_service
+        .find_files_by_mtime(temp_dir.path(), &FileQuery::default(), Some(days_ago(500)), Some(days_ago(50)))
+        .await?;
+    assert_eq!(names(files.into_iter().map(|r| r.path)), vec!["old.log", "old.txt"]);
+
+    let files = fs_service
+        .find_files_by_mtime(temp_dir.path(), &FileQuery::default(), None, Some(days_ago(500)))
+        .await?;
+    assert_eq!(names(files.into_iter().map(|r| r.path)), vec!["older.log"]);
+
+    Ok(())
+}
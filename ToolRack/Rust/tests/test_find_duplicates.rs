@@ -0,0 +1,232 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::duplicates::{DedupeAction, DedupeGroupPlan, DedupePlan, KeeperRule};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::Content;
+use aichemistforge_mcp_server::tools::FindDuplicateFiles;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+// root/
+//   a.txt            "same content"
+//   nested/b.txt     "same content"
+//   nested/deep/c.log "same content"
+//   unique.txt       "different!!!" (same size, different content)
+//   node_modules/d.txt "same content" (default-excluded)
+fn setup_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("nested").join("deep")).unwrap();
+    fs::create_dir_all(root.join("node_modules")).unwrap();
+    fs::write(root.join("a.txt"), "same content").unwrap();
+    fs::write(root.join("nested").join("b.txt"), "same content").unwrap();
+    fs::write(root.join("nested").join("deep").join("c.log"), "same content").unwrap();
+    fs::write(root.join("unique.txt"), "different!!!").unwrap();
+    fs::write(root.join("node_modules").join("d.txt"), "same content").unwrap();
+    temp_dir
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+#[tokio::test]
+async fn test_find_duplicate_files_groups_by_content() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let groups = fs_service
+        .find_duplicate_files(temp_dir.path(), None, None, Some(1), None, false)
+        .await?;
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].len(), 3);
+
+    let groups = fs_service
+        .find_duplicate_files(temp_dir.path(), Some("*.txt".to_string()), None, Some(1), None, true)
+        .await?;
+    assert_eq!(groups[0].len(), 3);
+    assert!(groups[0].iter().any(|p| p.contains("node_modules")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dedupe_plan_keeps_shortest_path() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let groups = fs_service
+        .find_duplicate_files(temp_dir.path(), None, None, Some(1), None, false)
+        .await?;
+
+    let plan = DedupePlan::build(&groups, DedupeAction::DeleteExtras, KeeperRule::ShortestPath)?;
+    assert_eq!(plan.groups[0].keep, display(&temp_dir.path().join("a.txt")));
+    assert_eq!(plan.groups[0].extras.len(), 2);
+    assert_eq!(plan.reclaimable_bytes, 24);
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_apply_hardlink_plan_links_extras() -> ServiceResult<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let groups = fs_service
+        .find_duplicate_files(temp_dir.path(), None, None, Some(1), None, false)
+        .await?;
+    let plan = DedupePlan::build(&groups, DedupeAction::Hardlink, KeeperRule::ShortestPath)?;
+
+    let results = fs_service.apply_dedupe_plan(&plan).await;
+    assert!(results.iter().all(|r| r.ok), "{:?}", results);
+
+    let keep = fs::metadata(temp_dir.path().join("a.txt"))?;
+    let linked = fs::metadata(temp_dir.path().join("nested").join("b.txt"))?;
+    assert_eq!(keep.ino(), linked.ino());
+    assert_eq!(keep.nlink(), 3);
+
+    // Already-linked files are no longer reported as duplicates
+    let groups = fs_service
+        .find_duplicate_files(temp_dir.path(), None, None, Some(1), None, false)
+        .await?;
+    assert!(groups.is_empty());
+
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_linked_extras_are_backed_up_and_undone() -> ServiceResult<()> {
+    use aichemistforge_mcp_server::fs_service::backup::BackupMode;
+    use std::os::unix::fs::MetadataExt;
+
+    let temp_dir = setup_tree();
+    let mut fs_service = FileSystemService::try_new(&[], &[])?;
+    fs_service.set_backup_mode(BackupMode::Sibling);
+    let groups = fs_service
+        .find_duplicate_files(temp_dir.path(), None, None, Some(1), None, false)
+        .await?;
+    let plan = DedupePlan::build(&groups, DedupeAction::Hardlink, KeeperRule::ShortestPath)?;
+
+    let results = fs_service.apply_dedupe_plan(&plan).await;
+    assert!(results.iter().all(|r| r.ok), "{:?}", results);
+    let extra = temp_dir.path().join("nested").join("b.txt");
+    assert_eq!(fs::read_to_string(temp_dir.path().join("nested").join("b.txt.bak"))?, "same content");
+
+    // Undo puts separate files back rather than writing through the links
+    fs_service.undo_last_operation().await?.unwrap();
+    fs_service.undo_last_operation().await?.unwrap();
+    let keep = fs::metadata(temp_dir.path().join("a.txt"))?;
+    assert_eq!(keep.nlink(), 1);
+    assert_ne!(fs::metadata(&extra)?.ino(), keep.ino());
+    assert_eq!(fs::read_to_string(&extra)?, "same content");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_apply_plan_skips_files_changed_since_planning() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let groups = fs_service
+        .find_duplicate_files(temp_dir.path(), None, None, Some(1), None, false)
+        .await?;
+    let plan = DedupePlan::build(&groups, DedupeAction::DeleteExtras, KeeperRule::ShortestPath)?;
+
+    let changed = temp_dir.path().join("nested").join("b.txt");
+    fs::write(&changed, "edited later")?;
+
+    let results = fs_service.apply_dedupe_plan(&plan).await;
+    let changed_result = results.iter().find(|r| r.path == display(&changed)).unwrap();
+    assert!(!changed_result.ok);
+    assert!(changed.exists());
+    assert!(!temp_dir.path().join("nested").join("deep").join("c.log").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_apply_plan_refuses_extras_that_are_the_keeper() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let root = temp_dir.path();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let keep = root.join("a.txt");
+    let mut extras = vec![display(&root.join(".").join("a.txt"))];
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(&keep, root.join("link.txt"))?;
+        fs::hard_link(&keep, root.join("hard.txt"))?;
+        extras.extend([display(&root.join("link.txt")), display(&root.join("hard.txt"))]);
+    }
+
+    for action in [DedupeAction::DeleteExtras, DedupeAction::Symlink] {
+        let plan = DedupePlan {
+            action,
+            keep_rule: KeeperRule::ShortestPath,
+            groups: vec![DedupeGroupPlan { keep: display(&keep), extras: extras.clone(), file_size: 12 }],
+            reclaimable_bytes: 0,
+        };
+        let results = fs_service.apply_dedupe_plan(&plan).await;
+        assert_eq!(results.len(), extras.len());
+        assert!(results.iter().all(|r| !r.ok), "{:?}", results);
+        assert_eq!(fs::read_to_string(&keep)?, "same content");
+        assert!(!fs::symlink_metadata(&keep)?.file_type().is_symlink());
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_duplicates_across_roots_are_broken_down_by_root() -> ServiceResult<()> {
+    let originals = setup_tree();
+    let backups = TempDir::new().unwrap();
+    fs::write(backups.path().join("a.bak"), "same content").unwrap();
+    fs::write(backups.path().join("only-here.txt"), "backup only").unwrap();
+    fs::write(backups.path().join("copy-of-only-here.txt"), "backup only").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    // Overlapping roots do not report a file twice
+    let roots = [originals.path().to_path_buf(), backups.path().to_path_buf(), originals.path().join("nested")];
+    let scan = fs_service.find_duplicates_across_roots(&roots, None, None, Some(1), None, false).await?;
+    assert_eq!(scan.roots.len(), 3);
+    assert_eq!(scan.groups.len(), 2);
+    let spanning = scan.groups.iter().find(|g| g.len() == 4).unwrap();
+    let split = scan.split_by_root(spanning);
+    assert_eq!(split.len(), 3);
+    assert_eq!(split[0].1, [&display(&originals.path().join("a.txt"))]);
+    assert_eq!(split[1].1, [&display(&backups.path().join("a.bak"))]);
+    assert_eq!(split[2].1.len(), 2);
+
+    // Each root is validated individually
+    let missing = [originals.path().to_path_buf(), backups.path().join("missing")];
+    assert!(fs_service.find_duplicates_across_roots(&missing, None, None, Some(1), None, false).await.is_err());
+
+    let tool = FindDuplicateFiles {
+        root_paths: vec![display(originals.path()), display(backups.path())],
+        pattern: None,
+        exclude_patterns: None,
+        min_bytes: None,
+        max_bytes: None,
+        output_format: Some("json".to_string()),
+        include_defaults_excluded: false,
+        action: None,
+        keep: None,
+        dry_run: None,
+    };
+    let result = tool.clone().run_tool(&fs_service).await.unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    let report: serde_json::Value = serde_json::from_str(&text.text).unwrap();
+    assert_eq!(report["roots"][0]["duplicate_files"], 3);
+    assert_eq!(report["roots"][1]["duplicate_files"], 3);
+    assert_eq!(report["roots"][1]["groups_spanning_roots"], 1);
+    let spans: Vec<bool> = report["groups"].as_array().unwrap().iter().map(|g| g["spans_roots"].as_bool().unwrap()).collect();
+    assert_eq!(spans.iter().filter(|s| **s).count(), 1);
+
+    let result = FindDuplicateFiles { output_format: None, ..tool }.run_tool(&fs_service).await.unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    assert!(text.text.starts_with("Found 2 sets of duplicate files across 2 roots (1 spanning more than one root)"), "{}", text.text);
+    assert!(text.text.contains("(spans 2 roots)"));
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs;
+use tempfile::TempDir;
+
+// root/
+//   empty.txt        ""
+//   blank.md         "  \n\t\n"
+//   notes.txt        "content"
+//   scratch/tmp.log  ""
+fn setup_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir(root.join("scratch")).unwrap();
+    fs::write(root.join("empty.txt"), "").unwrap();
+    fs::write(root.join("blank.md"), "  \n\t\n").unwrap();
+    fs::write(root.join("notes.txt"), "content").unwrap();
+    fs::write(root.join("scratch").join("tmp.log"), "").unwrap();
+    temp_dir
+}
+
+#[tokio::test]
+async fn test_find_empty_files_zero_byte_and_whitespace() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let found = fs_service.find_empty_files(temp_dir.path(), None, false, false).await?;
+    assert_eq!(found.len(), 2);
+    assert!(found.iter().all(|p| !p.ends_with("blank.md")));
+
+    let found = fs_service.find_empty_files(temp_dir.path(), None, true, false).await?;
+    assert_eq!(found.len(), 3);
+
+    let found = fs_service
+        .find_empty_files(temp_dir.path(), Some(vec!["scratch".to_string()]), true, false)
+        .await?;
+    assert_eq!(found.len(), 2);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_empty_files_rechecks_before_deleting() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let found = fs_service.find_empty_files(temp_dir.path(), None, false, false).await?;
+
+    // Written to after the scan: must survive the cleanup
+    fs::write(temp_dir.path().join("empty.txt"), "now has data")?;
+
+    let results = fs_service.delete_empty_files(&found, false).await;
+    assert_eq!(results.len(), 2);
+    assert!(temp_dir.path().join("empty.txt").exists());
+    assert!(!temp_dir.path().join("scratch").join("tmp.log").exists());
+    assert_eq!(results.iter().filter(|(_, r)| r.is_ok()).count(), 1);
+
+    Ok(())
+}
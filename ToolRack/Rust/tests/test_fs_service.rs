@@ -336,6 +336,7 @@ async fn test_apply_file_edits() {
     let edits = vec![EditOperation {
         old_text: "line2".to_string(),
         new_text: "line4".to_string(),
+        ..Default::default()
     }];
     let result = service
         .apply_file_edits(&file_path, edits, Some(false), None)
@@ -359,6 +360,7 @@ async fn test_apply_file_edits_dry_run() {
     let edits = vec![EditOperation {
         old_text: "line2".to_string(),
         new_text: "line4".to_string(),
+        ..Default::default()
     }];
     let result = service
         .apply_file_edits(&file_path, edits, Some(true), None)
@@ -382,6 +384,7 @@ async fn test_apply_file_edits_no_match() {
     let edits = vec![EditOperation {
         old_text: "non_existent".to_string(),
         new_text: "line4".to_string(),
+        ..Default::default()
     }];
     let result = service
         .apply_file_edits(&file_path, edits, Some(false), None)
@@ -626,6 +629,7 @@ async fn test_apply_file_edits_mixed_indentation() {
 				},
 			];"#
         .to_string(),
+        ..Default::default()
     }];
 
     let out_file = temp_dir.join("dir1").join("out_indent.txt");
@@ -674,6 +678,7 @@ async fn test_apply_file_edits_mixed_indentation_2() {
 				},
 			];"#
         .to_string(),
+        ..Default::default()
     }];
 
     let out_file = temp_dir.join("dir1").join("out_indent.txt");
@@ -697,6 +702,7 @@ async fn test_exact_match() {
     let edit = EditOperation {
         old_text: "hello world".to_string(),
         new_text: "hello universe".to_string(),
+        ..Default::default()
     };
 
     let result = service
@@ -721,6 +727,7 @@ async fn test_exact_match_edit2() {
     let edits = vec![EditOperation {
         old_text: "hello world\n".into(),
         new_text: "hello Rust\n".into(),
+        ..Default::default()
     }];
 
     let result = service
@@ -744,6 +751,7 @@ async fn test_line_by_line_match_with_indent() {
     let edits = vec![EditOperation {
         old_text: "let x = 42;\nprintln!(\"{}\");\n".into(),
         new_text: "let x = 43;\nprintln!(\"x = {}\", x)".into(),
+        ..Default::default()
     }];
 
     let result = service
@@ -769,6 +777,7 @@ async fn test_dry_run_mode() {
     let edits = vec![EditOperation {
         old_text: "echo hello\n".into(),
         new_text: "echo world\n".into(),
+        ..Default::default()
     }];
 
     let result = service
@@ -794,6 +803,7 @@ async fn test_save_to_different_path() {
     let edits = vec![EditOperation {
         old_text: "foo = 1\n".into(),
         new_text: "foo = 2\n".into(),
+        ..Default::default()
     }];
 
     let result = service
@@ -820,6 +830,7 @@ async fn test_diff_backtick_formatting() {
     let edits = vec![EditOperation {
         old_text: "```\nhello\n```".into(),
         new_text: "```\nworld\n```".into(),
+        ..Default::default()
     }];
 
     let result = service
@@ -862,6 +873,7 @@ async fn test_preserve_windows_line_endings() {
     let edits = vec![EditOperation {
         old_text: "line1\nline2".into(), // normalized format
         new_text: "updated1\nupdated2".into(),
+        ..Default::default()
     }];
 
     let result = service
@@ -885,6 +897,7 @@ async fn test_preserve_unix_line_endings() {
     let edits = vec![EditOperation {
         old_text: "line1\nline2".into(),
         new_text: "updated1\nupdated2".into(),
+        ..Default::default()
     }];
 
     let result = service
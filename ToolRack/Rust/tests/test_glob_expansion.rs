@@ -0,0 +1,83 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::{FileSystemService, GLOB_MAX_FILES};
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_glob_expands_matching_files_in_order() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    fs::create_dir(temp_dir.path().join("nested"))?;
+    fs::write(temp_dir.path().join("b.rs"), "b")?;
+    fs::write(temp_dir.path().join("a.rs"), "a")?;
+    fs::write(temp_dir.path().join("nested").join("c.rs"), "c")?;
+    fs::write(temp_dir.path().join("notes.txt"), "skip")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let pattern = temp_dir.path().join("**").join("*.rs").display().to_string();
+    let plain = temp_dir.path().join("notes.txt").display().to_string();
+    let expansion = fs_service.expand_path_patterns(&[plain.clone(), pattern]).await?;
+
+    let expected = vec![
+        plain,
+        temp_dir.path().join("a.rs").display().to_string(),
+        temp_dir.path().join("b.rs").display().to_string(),
+        temp_dir.path().join("nested").join("c.rs").display().to_string(),
+    ];
+    assert_eq!(expansion.paths, expected);
+    assert!(expansion.notes.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_glob_reports_patterns_without_matches() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let pattern = temp_dir.path().join("*.md").display().to_string();
+    let expansion = fs_service.expand_path_patterns(&[pattern]).await?;
+
+    assert!(expansion.paths.is_empty());
+    assert_eq!(expansion.notes.len(), 1);
+    assert!(expansion.notes[0].contains("matched no files"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_glob_expansion_stops_at_file_cap() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    for i in 0..GLOB_MAX_FILES + 5 {
+        fs::write(temp_dir.path().join(format!("file_{i:03}.txt")), "x")?;
+    }
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let pattern = temp_dir.path().join("*.txt").display().to_string();
+    let expansion = fs_service.expand_path_patterns(&[pattern]).await?;
+
+    assert_eq!(expansion.paths.len(), GLOB_MAX_FILES);
+    assert!(expansion.notes.iter().any(|n| n.contains("limit")));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_glob_skips_blocked_matches() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let blocked_dir = temp_dir.path().join("secret");
+    fs::create_dir(&blocked_dir)?;
+    fs::write(temp_dir.path().join("open.txt"), "ok")?;
+    fs::write(blocked_dir.join("hidden.txt"), "no")?;
+    let blocked = vec![blocked_dir.display().to_string()];
+    let fs_service = FileSystemService::try_new(&[], &blocked)?;
+
+    let pattern = temp_dir.path().join("**").join("*.txt").display().to_string();
+    let expansion = fs_service.expand_path_patterns(&[pattern]).await?;
+
+    assert_eq!(
+        expansion.paths,
+        vec![temp_dir.path().join("open.txt").display().to_string()]
+    );
+
+    Ok(())
+}
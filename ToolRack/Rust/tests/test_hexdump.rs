@@ -0,0 +1,68 @@
+use aichemistforge_mcp_server::fs_service::hexdump::{format_hexdump, ByteWindow};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, Content};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_format_hexdump_aligns_short_lines() {
+    let window = ByteWindow { bytes: b"\x7fELF\x02\x01\x01\x00hello, world!\n".to_vec(), start_byte: 0, file_size: 22 };
+    let dump = format_hexdump(&window, 16, true);
+    let lines: Vec<&str> = dump.lines().collect();
+    assert_eq!(lines[0], "00000000  7f 45 4c 46 02 01 01 00  68 65 6c 6c 6f 2c 20 77  |.ELF....hello, w|");
+    assert!(lines[1].starts_with("00000010  6f 72 6c 64 21 0a   ") && lines[1].ends_with("|orld!.|"));
+    // The ASCII column lines up on a short last line
+    assert_eq!(lines[0].find('|'), lines[1].find('|'));
+    assert_eq!(format_hexdump(&window, 4, false).lines().nth(1), Some("00000004  02 01 01 00"));
+
+    let window = ByteWindow { bytes: vec![0xff], start_byte: 0x1_0000_0000, file_size: 0x1_0000_0001 };
+    // Offsets past 4 GiB get a wider column
+    let dump = format_hexdump(&window, 16, true);
+    assert!(dump.starts_with("100000000  ff  ") && dump.ends_with(" |.|\n"), "{}", dump);
+}
+
+#[tokio::test]
+async fn test_read_byte_window_from_start_and_end() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("data.bin");
+    fs::write(&path, (0u8..=99).collect::<Vec<_>>()).unwrap();
+    let service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let window = service.read_byte_window(&path, 10, false, 4).await.unwrap();
+    assert_eq!((window.start_byte, window.bytes.as_slice()), (10, &[10, 11, 12, 13][..]));
+    assert!(!window.reached_eof());
+
+    let window = service.read_byte_window(&path, 0, true, 3).await.unwrap();
+    assert_eq!((window.start_byte, window.bytes.as_slice()), (97, &[97, 98, 99][..]));
+    assert!(window.reached_eof());
+
+    // Windows are cut at either end of the file
+    let window = service.read_byte_window(&path, 98, false, 10).await.unwrap();
+    assert_eq!(window.bytes, [98, 99]);
+    let window = service.read_byte_window(&path, 95, true, 10).await.unwrap();
+    assert_eq!((window.start_byte, window.bytes.len()), (0, 5));
+    assert!(service.read_byte_window(&path, 101, false, 1).await.is_err());
+}
+
+#[tokio::test]
+async fn test_hexdump_file_tool() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("image.png");
+    let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+    data.resize(300, 0);
+    fs::write(&path, &data).unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", temp.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+
+    let request = CallToolRequest {
+        params: CallToolParams { name: "hexdump_file".to_string(), arguments: Some(serde_json::json!({ "path": path })) },
+    };
+    let result = handler.handle_call_tool(request).await.unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    assert!(text.text.contains("bytes 0-255 of 300"), "{}", text.text);
+    assert!(text.text.contains("00000000  89 50 4e 47 0d 0a 1a 0a  00 00"), "{}", text.text);
+    assert!(text.text.contains("|.PNG............|"), "{}", text.text);
+    assert!(text.text.ends_with("continue with byte_offset: 256\n"), "{}", text.text);
+}
@@ -0,0 +1,42 @@
+use aichemistforge_mcp_server::fs_service::duplicates::hash_file;
+use aichemistforge_mcp_server::fs_service::throttle::{copy, io_limit, set_io_limit, ThrottledReader};
+use std::fs;
+use std::io::Read;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+// The limit is process-wide, so everything that changes it runs in one test
+#[test]
+fn test_io_limit_slows_hashing_and_copies() {
+    let temp = TempDir::new().unwrap();
+    let source = temp.path().join("data.bin");
+    let data: Vec<u8> = (0..512 * 1024).map(|i| (i % 251) as u8).collect();
+    fs::write(&source, &data).unwrap();
+    let expected_hash = hash_file(&source).unwrap();
+
+    set_io_limit(Some(1024 * 1024));
+    assert_eq!(io_limit(), Some(1024 * 1024));
+
+    // 512 KiB at 1 MiB/s takes about half a second; the first piece is free
+    let started = Instant::now();
+    assert_eq!(hash_file(&source).unwrap(), expected_hash);
+    assert!(started.elapsed() >= Duration::from_millis(350), "{:?}", started.elapsed());
+
+    let started = Instant::now();
+    let destination = temp.path().join("copy.bin");
+    assert_eq!(copy(&source, &destination).unwrap(), data.len() as u64);
+    assert!(started.elapsed() >= Duration::from_millis(350), "{:?}", started.elapsed());
+    assert_eq!(fs::read(&destination).unwrap(), data);
+
+    let mut read = Vec::new();
+    ThrottledReader::new(fs::File::open(&source).unwrap()).read_to_end(&mut read).unwrap();
+    assert_eq!(read, data);
+
+    // 0 and None both lift the cap
+    set_io_limit(Some(0));
+    assert_eq!(io_limit(), None);
+    let started = Instant::now();
+    copy(&source, &temp.path().join("fast.bin")).unwrap();
+    hash_file(&source).unwrap();
+    assert!(started.elapsed() < Duration::from_millis(300), "{:?}", started.elapsed());
+}
@@ -0,0 +1,63 @@
+use aichemistforge_mcp_server::tools::{EditKind, EditOperation};
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use tempfile::TempDir;
+
+fn line_edit(kind: EditKind, start: usize, end: Option<usize>, new_text: &str) -> EditOperation {
+    EditOperation {
+        kind,
+        new_text: new_text.to_string(),
+        line: Some(start),
+        start: Some(start),
+        end,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_line_addressed_edits() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("list.txt");
+    fs::write(&file, "x\nx\nx\nx\n").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let edits = vec![
+        line_edit(EditKind::ReplaceLines, 2, None, "second"),
+        line_edit(EditKind::InsertAtLine, 1, None, "header\n"),
+        // Line 5 is the last x once the header is in
+        line_edit(EditKind::DeleteLines, 4, Some(5), ""),
+        line_edit(EditKind::InsertAtLine, 4, None, "footer"),
+    ];
+    let diff = fs_service.apply_file_edits(&file, edits, Some(false), None).await.unwrap();
+    assert!(diff.contains("+second"));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "header\nx\nsecond\nfooter\n");
+
+    // Without a final newline the file keeps ending without one
+    fs::write(&file, "a\nb").unwrap();
+    let edits = vec![line_edit(EditKind::InsertAtLine, 3, None, "c"), line_edit(EditKind::ReplaceLines, 1, None, "A")];
+    fs_service.apply_file_edits(&file, edits, Some(false), None).await.unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "A\nb\nc");
+}
+
+#[tokio::test]
+async fn test_line_edits_out_of_range_change_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("short.txt");
+    fs::write(&file, "one\ntwo\n").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let edits = vec![line_edit(EditKind::DeleteLines, 1, None, ""), line_edit(EditKind::ReplaceLines, 2, Some(3), "x")];
+    let error = fs_service.apply_file_edits(&file, edits, Some(false), None).await.unwrap_err();
+    assert!(error.to_string().contains("edit 2: lines 2-3 are outside the file, which has 1 lines"), "{}", error);
+    assert!(fs_service
+        .apply_file_edits(&file, vec![line_edit(EditKind::InsertAtLine, 0, None, "x")], Some(false), None)
+        .await
+        .is_err());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "one\ntwo\n");
+
+    // A replace edit still needs oldText
+    let edit: EditOperation = serde_json::from_value(serde_json::json!({ "newText": "x" })).unwrap();
+    assert!(fs_service.apply_file_edits(&file, vec![edit], Some(false), None).await.is_err());
+    let edit: EditOperation = serde_json::from_value(serde_json::json!({ "type": "delete_lines", "start": 2 })).unwrap();
+    assert_eq!(edit.kind, EditKind::DeleteLines);
+}
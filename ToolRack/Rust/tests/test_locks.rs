@@ -0,0 +1,120 @@
+use aichemistforge_mcp_server::fs_service::locks::{LockInfo, LockManager};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, Content};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use chrono::{Duration, Utc};
+use clap::Parser;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+// A lock left by another session, expiring `expires_in` from now
+fn foreign_lock(path: &Path, expires_in: Duration) {
+    let lock = LockInfo {
+        path: path.display().to_string(),
+        owner: "other agent".to_string(),
+        session: "other-session".to_string(),
+        pid: 1,
+        acquired_at: Utc::now() - Duration::minutes(1),
+        expires_at: Utc::now() + expires_in,
+    };
+    fs::write(LockManager::lock_path(path), serde_json::to_string(&lock).unwrap()).unwrap();
+}
+
+#[tokio::test]
+async fn test_acquire_renew_and_release() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("notes.md");
+    let service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let grant = service.acquire_lock(&path, Some("agent-1"), Some(60)).await.unwrap();
+    assert!(!grant.renewed);
+    assert_eq!(grant.lock.owner, "agent-1");
+    assert!(temp.path().join(".notes.md.agent-lock").exists());
+
+    let renewed = service.acquire_lock(&path, Some("agent-1"), Some(600)).await.unwrap();
+    assert!(renewed.renewed);
+    assert_eq!(renewed.lock.acquired_at, grant.lock.acquired_at);
+    assert!(renewed.lock.expires_at > grant.lock.expires_at);
+
+    assert_eq!(service.release_lock(&path, false).await.unwrap().unwrap().owner, "agent-1");
+    assert!(service.release_lock(&path, false).await.unwrap().is_none());
+    assert!(!LockManager::lock_path(&path).exists());
+    assert!(service.acquire_lock(&path, None, Some(0)).await.is_err());
+}
+
+#[tokio::test]
+async fn test_foreign_and_stale_locks() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("data.csv");
+    let service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    foreign_lock(&path, Duration::minutes(5));
+    let err = service.acquire_lock(&path, None, None).await.unwrap_err();
+    assert!(err.to_string().contains("locked by other agent"), "{}", err);
+    let err = service.release_lock(&path, false).await.unwrap_err();
+    assert!(err.to_string().contains("force"), "{}", err);
+    assert!(service.release_lock(&path, true).await.unwrap().is_some());
+
+    // An expired lock is taken over
+    foreign_lock(&path, Duration::seconds(-1));
+    let grant = service.acquire_lock(&path, None, None).await.unwrap();
+    assert_eq!(grant.replaced_stale.unwrap().owner, "other agent");
+    assert_eq!(grant.lock.session, service.locks().session());
+
+    // A lock file that does not parse is never removed for us
+    fs::write(LockManager::lock_path(&path), "garbage").unwrap();
+    assert!(service.acquire_lock(&path, None, None).await.is_err());
+    assert_eq!(fs::read_to_string(LockManager::lock_path(&path)).unwrap(), "garbage");
+}
+
+#[tokio::test]
+async fn test_respect_locks_blocks_writes() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("shared.txt");
+    fs::write(&path, "original").unwrap();
+    foreign_lock(&path, Duration::minutes(5));
+
+    // Locks are advisory unless the server respects them
+    let service = FileSystemService::try_new(&[], &[]).unwrap();
+    service.write_file(&path, &"unchecked".to_string()).await.unwrap();
+
+    let mut service = FileSystemService::try_new(&[], &[]).unwrap();
+    service.set_respect_locks(true);
+    let err = service.write_file(&path, &"blocked".to_string()).await.unwrap_err();
+    assert!(err.to_string().contains("locked by other agent"), "{}", err);
+    assert!(service.delete_file(&path).await.is_err());
+    assert!(service.move_file(&path, &temp.path().join("moved.txt")).await.is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "unchecked");
+
+    // Our own lock does not get in our way
+    service.release_lock(&path, true).await.unwrap();
+    service.acquire_lock(&path, None, None).await.unwrap();
+    service.write_file(&path, &"mine".to_string()).await.unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "mine");
+}
+
+#[tokio::test]
+async fn test_lock_tools() {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("plan.md");
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", "--respect-locks", temp.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let call = |name: &str, arguments: serde_json::Value| CallToolRequest {
+        params: CallToolParams { name: name.to_string(), arguments: Some(arguments) },
+    };
+
+    let result = handler
+        .handle_call_tool(call("acquire_lock", serde_json::json!({ "path": path, "owner": "planner", "ttl_seconds": 30 })))
+        .await
+        .unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    assert!(text.text.starts_with(&format!("Locked {} for planner until", path.display())), "{}", text.text);
+
+    let result = handler
+        .handle_call_tool(call("release_lock", serde_json::json!({ "path": path })))
+        .await
+        .unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    assert!(text.text.contains("held by planner"), "{}", text.text);
+}
@@ -0,0 +1,153 @@
+use aichemistforge_mcp_server::logging::LogLevel;
+use aichemistforge_mcp_server::CommandArguments;
+use clap::Parser;
+use std::ffi::OsString;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tempfile::TempDir;
+
+const SECRET: &str = "the-file-contents-42";
+
+fn parse(args: &[&str], vars: &[(&str, &str)]) -> anyhow::Result<CommandArguments> {
+    CommandArguments::parse_with_env(std::iter::once("server").chain(args.iter().copied()), |name| {
+        vars.iter().find(|(k, _)| *k == name).map(|(_, v)| OsString::from(v))
+    })
+}
+
+/// Run the server on a read_file call and return what it wrote to stderr
+fn server_stderr(extra_args: &[&str], env: &[(&str, &str)]) -> String {
+    let temp = TempDir::new().unwrap();
+    let path = temp.path().join("data.txt");
+    fs::write(&path, SECRET).unwrap();
+    let messages = [
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2024-11-05", "capabilities": {}, "clientInfo": { "name": "test", "version": "1" } } }),
+        serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+        serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": { "name": "read_file", "arguments": { "path": path } } }),
+    ];
+
+    let mut command = Command::new(env!("CARGO_BIN_EXE_aichemistforge-mcp-server"));
+    command
+        .args(["--tool-style", "flat"])
+        .args(extra_args)
+        .arg(temp.path())
+        .env_remove("RUST_LOG")
+        .env_remove("AICHEMIST_LOG_LEVEL")
+        .envs(env.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn().unwrap();
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        for message in &messages {
+            writeln!(stdin, "{}", message).unwrap();
+        }
+    }
+    let output = child.wait_with_output().unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains(SECRET), "the call itself should succeed");
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn test_log_level_flags_and_environment() {
+    assert_eq!(parse(&[], &[]).unwrap().log_level(), LogLevel::Info);
+    assert_eq!(parse(&["--quiet"], &[]).unwrap().log_level(), LogLevel::Error);
+    assert_eq!(parse(&["--log-level", "trace"], &[]).unwrap().log_level(), LogLevel::Trace);
+    assert_eq!(parse(&[], &[("AICHEMIST_LOG_LEVEL", "debug")]).unwrap().log_level(), LogLevel::Debug);
+    // RUST_LOG is a fallback, and only plain levels are understood
+    assert_eq!(parse(&[], &[("RUST_LOG", "warn")]).unwrap().log_level(), LogLevel::Warn);
+    assert_eq!(parse(&[], &[("RUST_LOG", "my_crate=debug")]).unwrap().log_level(), LogLevel::Info);
+    assert_eq!(
+        parse(&["--log-level", "error"], &[("AICHEMIST_LOG_LEVEL", "trace")]).unwrap().log_level(),
+        LogLevel::Error
+    );
+    assert!(parse(&[], &[("AICHEMIST_LOG_LEVEL", "loud")]).is_err());
+    assert!(CommandArguments::try_parse_from(["server", "--quiet", "--log-level", "debug"]).is_err());
+}
+
+#[test]
+fn test_payloads_only_reach_stderr_at_trace_level() {
+    let info = server_stderr(&[], &[]);
+    assert!(info.contains("[INFO] Starting"));
+    assert!(!info.contains(SECRET) && !info.contains("[DEBUG]"));
+
+    let debug = server_stderr(&["--log-level", "debug"], &[]);
+    assert!(debug.contains("[DEBUG] Received: tools/call \"read_file\" (id 2)"), "{}", debug);
+    assert!(debug.contains("[DEBUG] Sending: result (id 2)"), "{}", debug);
+    assert!(!debug.contains(SECRET));
+
+    let trace = server_stderr(&[], &[("AICHEMIST_LOG_LEVEL", "trace")]);
+    assert!(trace.contains("[TRACE] Sending:") && trace.contains(SECRET));
+
+    let quiet = server_stderr(&["--quiet"], &[]);
+    assert!(quiet.is_empty(), "{}", quiet);
+}
+
+#[test]
+fn test_log_file_gets_lines_with_request_fields() {
+    let temp = TempDir::new().unwrap();
+    let log = temp.path().join("server.log");
+    let stderr = server_stderr(&["--log-level", "debug", "--log-file", log.to_str().unwrap()], &[]);
+    assert!(stderr.is_empty(), "{}", stderr);
+
+    let log = fs::read_to_string(&log).unwrap();
+    assert!(log.starts_with("20") && log.lines().next().unwrap().contains("Z [INFO] Starting"), "{}", log);
+    let finished = log.lines().find(|line| line.contains("[DEBUG] Tool call finished")).unwrap();
+    for field in ["outcome=ok", "tool=read_file", "request_id=2", "elapsed_ms="] {
+        assert!(finished.contains(field), "{}", finished);
+    }
+}
+
+/// Run the server on `messages` and return the JSON messages it wrote to stdout
+fn server_stdout(messages: &[serde_json::Value]) -> Vec<serde_json::Value> {
+    let temp = TempDir::new().unwrap();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_aichemistforge-mcp-server"))
+        .arg(temp.path())
+        .env_remove("RUST_LOG")
+        .env_remove("AICHEMIST_LOG_LEVEL")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    {
+        let mut stdin = child.stdin.take().unwrap();
+        for message in messages {
+            writeln!(stdin, "{}", message).unwrap();
+        }
+    }
+    let output = child.wait_with_output().unwrap();
+    String::from_utf8(output.stdout).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect()
+}
+
+#[test]
+fn test_log_messages_are_forwarded_to_the_client() {
+    let initialized = serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+    let set_level = |id: u32, level: &str| {
+        serde_json::json!({ "jsonrpc": "2.0", "id": id, "method": "logging/setLevel", "params": { "level": level } })
+    };
+    let output = server_stdout(&[
+        serde_json::json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "protocolVersion": "2024-11-05", "capabilities": {}, "clientInfo": { "name": "test", "version": "1" } } }),
+        // Nothing is forwarded before the client picks a level
+        initialized.clone(),
+        set_level(2, "info"),
+        initialized.clone(),
+        set_level(3, "warning"),
+        initialized,
+        set_level(4, "verbose"),
+    ]);
+
+    assert_eq!(output[0]["id"], 1);
+    assert!(output[0]["result"]["capabilities"]["logging"].is_object());
+    let forwarded: Vec<&serde_json::Value> =
+        output.iter().filter(|message| message["method"] == "notifications/message").collect();
+    assert_eq!(forwarded.len(), 1, "{:?}", output);
+    assert_eq!(forwarded[0]["params"]["level"], "info");
+    assert!(forwarded[0]["params"]["data"].as_str().unwrap().contains("Allowed directories"));
+
+    let responses: Vec<&serde_json::Value> = output.iter().filter(|message| message.get("id").is_some()).collect();
+    assert_eq!(responses[1]["result"], serde_json::json!({}));
+    assert_eq!(responses[2]["result"], serde_json::json!({}));
+    assert_eq!(responses[3]["error"]["code"], -32602);
+}
@@ -0,0 +1,78 @@
+use aichemistforge_mcp_server::fs_service::{FileSystemService, ReadStatus, READ_MULTIPLE_MAX_TOTAL_BYTES};
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest};
+use aichemistforge_mcp_server::tools::ReadFileTool;
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::text;
+
+fn read(path: &Path, offset_bytes: Option<u64>, length_bytes: Option<u64>) -> ReadFileTool {
+    ReadFileTool { path: path.display().to_string(), with_line_numbers: false, offset_bytes, length_bytes, cursor: None, encoding: None }
+}
+
+#[tokio::test]
+async fn test_reads_over_the_limit_are_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let (big, small) = (temp_dir.path().join("big.log"), temp_dir.path().join("small.txt"));
+    fs::write(&big, "x".repeat(2048)).unwrap();
+    fs::write(&small, "fine").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_max_read_bytes(Some(1024));
+
+    let error = fs_service.read_file(&big).await.unwrap_err().to_string();
+    assert!(error.contains("is 2.0 KB, limit is 1.0 KB"), "{}", error);
+    assert!(error.contains("read_file_lines"), "{}", error);
+    assert_eq!(fs_service.read_file(&small).await.unwrap(), "fine");
+
+    let error = read(&big, None, None).run_tool(&fs_service).await.unwrap_err();
+    assert!(error.to_string().contains("limit is 1.0 KB"), "{}", error);
+
+    // Ranges stay readable, which is what the error points at
+    let result = read(&big, Some(0), Some(10)).run_tool(&fs_service).await.unwrap();
+    assert_eq!(text(&result), "x".repeat(10));
+
+    let paths = [big.display().to_string(), small.display().to_string()];
+    let results = fs_service.read_multiple_files(&paths, READ_MULTIPLE_MAX_TOTAL_BYTES).await;
+    assert_eq!(results[0].status, ReadStatus::Error);
+    assert!(results[0].error.as_deref().unwrap().contains("limit is 1.0 KB"));
+    assert_eq!(results[1].status, ReadStatus::Ok);
+
+    let error = fs_service.read_media_file(&big, None).await.unwrap_err().to_string();
+    assert!(error.contains("limit is 1.0 KB"), "{}", error);
+
+    fs_service.set_max_read_bytes(Some(0));
+    assert_eq!(fs_service.max_read_bytes(), None);
+    assert_eq!(fs_service.read_file(&big).await.unwrap().len(), 2048);
+}
+
+#[tokio::test]
+async fn test_max_read_bytes_reloads_with_the_policy() {
+    let (files, settings) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let big = files.path().join("big.log");
+    fs::write(&big, "x".repeat(2048)).unwrap();
+    let config = settings.path().join("aichemist.toml");
+    fs::write(&config, "").unwrap();
+
+    let config_arg = config.display().to_string();
+    let args = CommandArguments::parse_from([
+        "server", "--tool-style", "flat", "--max-read-bytes", "4096", "--config", &config_arg,
+        files.path().to_str().unwrap(),
+    ]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    assert!(handler.startup_message().contains("Read limit: 4.0 KB"));
+    let request = || CallToolRequest {
+        params: CallToolParams { name: "read_file".to_string(), arguments: Some(json!({ "path": big.display().to_string() })) },
+    };
+    let result = handler.handle_call_tool(request()).await.unwrap();
+    assert_ne!(result.is_error, Some(true), "{}", text(&result));
+
+    fs::write(&config, "[policy]\nmax_read_bytes = 1024\n").unwrap();
+    assert_eq!(handler.reload_policy().unwrap().max_read_bytes, Some(1024));
+    let error = handler.handle_call_tool(request()).await.unwrap_err();
+    assert!(error.to_string().contains("limit is 1.0 KB"), "{}", error);
+}
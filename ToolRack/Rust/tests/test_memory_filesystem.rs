@@ -0,0 +1,86 @@
+use aichemistforge_mcp_server::error::ServiceError;
+use aichemistforge_mcp_server::tools::{
+    CopyFileTool, CreateDirectoryTool, DeleteFileTool, ListDirectoryTool, MoveFileTool, WriteFileTool,
+};
+use aichemistforge_mcp_server::{FileSystemOps, MemoryFileSystem};
+use std::path::{Path, PathBuf};
+
+mod common;
+use common::text;
+
+#[tokio::test]
+async fn test_tools_run_against_memory_filesystem() {
+    let fs = MemoryFileSystem::new(&["/work".to_string()], &[]);
+
+    CreateDirectoryTool { path: "/work/src".to_string() }.run_tool(&fs).await.unwrap();
+    WriteFileTool { path: "/work/src/main.rs".to_string(), content: "fn main() {}".to_string(), create_parents: false }
+        .run_tool(&fs)
+        .await
+        .unwrap();
+    CopyFileTool { source: "/work/src".to_string(), destination: "/work/backup".to_string() }
+        .run_tool(&fs)
+        .await
+        .unwrap();
+    MoveFileTool { source: "/work/backup/main.rs".to_string(), destination: "/work/old.rs".to_string() }
+        .run_tool(&fs)
+        .await
+        .unwrap();
+
+    let listing = ListDirectoryTool { path: "/work".to_string(), detailed: Some(true) }.run_tool(&fs).await.unwrap();
+    assert_eq!(text(&listing), "DIR  backup\nFILE (12 B) old.rs\nDIR  src");
+    assert_eq!(fs.read_file(Path::new("/work/old.rs")).await.unwrap(), "fn main() {}");
+
+    DeleteFileTool { path: "/work/src".to_string(), confirm: Some(true), permanent: None, secure: None }.run_tool(&fs).await.unwrap();
+    let files: Vec<PathBuf> = fs.files().into_iter().map(|(path, _)| path).collect();
+    assert_eq!(files, vec![PathBuf::from("/work/old.rs")]);
+}
+
+#[tokio::test]
+async fn test_memory_filesystem_enforces_access_rules() {
+    let fs = MemoryFileSystem::new(&["/work".to_string()], &["/work/secret".to_string()]);
+    fs.add_file("/etc/passwd", "root").unwrap();
+    fs.add_file("/work/secret/key", "k").unwrap();
+
+    fn denied<T>(result: Result<T, ServiceError>) -> bool {
+        matches!(result, Err(ServiceError::PathNotAllowed))
+    }
+    assert!(denied(fs.read_file(Path::new("/etc/passwd")).await));
+    assert!(denied(fs.read_file(Path::new("/work/../etc/passwd")).await));
+    assert!(denied(fs.read_file(Path::new("/work/secret/key")).await));
+    assert!(denied(fs.write_file(Path::new("/tmp/x"), "x").await));
+    let write = |path: &str, create_parents| WriteFileTool { path: path.to_string(), content: String::new(), create_parents };
+    // Refusals are not retried with a backoff like transient I/O errors
+    let started = std::time::Instant::now();
+    assert!(write("/etc/evil", false).run_tool(&fs).await.is_err());
+    assert!(started.elapsed() < std::time::Duration::from_millis(500));
+
+    // Same failure modes as on disk
+    assert!(matches!(fs.read_file(Path::new("/work/missing")).await, Err(ServiceError::FileNotFound(_))));
+    assert!(fs.write_file(Path::new("/work/no/such/dir.txt"), "x").await.is_err());
+    fs.create_directory(Path::new("/work/a/b")).await.unwrap();
+    assert!(matches!(fs.create_directory(Path::new("/work/a")).await, Err(ServiceError::DirectoryAlreadyExists)));
+    assert!(fs.copy_file(Path::new("/work/a"), Path::new("/work/a/b/c")).await.is_err());
+
+    let metadata = fs.metadata(Path::new("/work/a")).await.unwrap();
+    assert!(metadata.is_dir && !metadata.is_file);
+    assert_eq!(fs.allowed_directories(), vec![PathBuf::from("/work")]);
+}
+
+#[tokio::test]
+async fn test_write_file_can_create_parent_directories() {
+    let fs = MemoryFileSystem::new(&["/work".to_string()], &["/work/secret".to_string()]);
+    let write = |path: &str, create_parents| WriteFileTool { path: path.to_string(), content: "x".to_string(), create_parents };
+
+    assert!(write("/work/new/deep/file.txt", false).run_tool(&fs).await.is_err());
+    write("/work/new/deep/file.txt", true).run_tool(&fs).await.unwrap();
+    assert_eq!(fs.read_file(Path::new("/work/new/deep/file.txt")).await.unwrap(), "x");
+    assert!(fs.metadata(Path::new("/work/new")).await.unwrap().is_dir);
+
+    // Existing parents are fine
+    write("/work/new/other.txt", true).run_tool(&fs).await.unwrap();
+
+    // Parents are checked like the file itself
+    assert!(write("/tmp/outside/file.txt", true).run_tool(&fs).await.is_err());
+    assert!(write("/work/secret/keys/key", true).run_tool(&fs).await.is_err());
+    assert!(fs.metadata(Path::new("/work/secret/keys")).await.is_err());
+}
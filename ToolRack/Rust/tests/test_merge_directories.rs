@@ -0,0 +1,103 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::merge::{ConflictStrategy, MergeAction, MergePlan};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs::{self, File, FileTimes};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+fn write_at(path: &Path, content: &str, age_secs: u64) {
+    fs::write(path, content).unwrap();
+    let modified = SystemTime::now() - Duration::from_secs(age_secs);
+    File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_times(FileTimes::new().set_modified(modified))
+        .unwrap();
+}
+
+// src/                          dest/
+//   new.txt                       
+//   same.txt   "same"             same.txt  "same"
+//   notes.txt  "fresh" (newer)    notes.txt "old notes" (older, larger)
+//   sub/inner.txt
+fn setup_trees() -> (TempDir, TempDir) {
+    let (source, destination) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    write_at(&source.path().join("new.txt"), "new", 60);
+    write_at(&source.path().join("same.txt"), "same", 60);
+    write_at(&destination.path().join("same.txt"), "same", 60);
+    write_at(&source.path().join("notes.txt"), "fresh", 60);
+    write_at(&destination.path().join("notes.txt"), "old notes", 3600);
+    fs::create_dir(source.path().join("sub")).unwrap();
+    write_at(&source.path().join("sub").join("inner.txt"), "inner", 60);
+    (source, destination)
+}
+
+fn actions(plan: &MergePlan) -> Vec<(String, MergeAction)> {
+    plan.steps
+        .iter()
+        .map(|s| (Path::new(&s.destination).file_name().unwrap().to_string_lossy().into_owned(), s.action))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_merge_plan_keep_newest_then_apply() -> ServiceResult<()> {
+    let (source, destination) = setup_trees();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let plan = fs_service
+        .plan_merge(source.path(), destination.path(), &[], false, ConflictStrategy::KeepNewest)
+        .await?;
+    assert_eq!(
+        actions(&plan),
+        vec![
+            ("new.txt".to_string(), MergeAction::Copy),
+            ("notes.txt".to_string(), MergeAction::Overwrite),
+            ("same.txt".to_string(), MergeAction::Skip),
+            ("sub".to_string(), MergeAction::CreateDirectory),
+            ("inner.txt".to_string(), MergeAction::Copy),
+        ]
+    );
+    assert!(!destination.path().join("new.txt").exists());
+
+    let results = fs_service.apply_merge_plan(&plan).await;
+    assert!(results.iter().all(|r| r.ok), "{:?}", results);
+    assert_eq!(fs::read_to_string(destination.path().join("notes.txt"))?, "fresh");
+    assert_eq!(fs::read_to_string(destination.path().join("sub").join("inner.txt"))?, "inner");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_merge_plan_conflict_strategies() -> ServiceResult<()> {
+    let (source, destination) = setup_trees();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let notes_action = |plan: &MergePlan| {
+        plan.steps.iter().find(|s| s.source.ends_with("notes.txt")).cloned().unwrap()
+    };
+
+    let plan = fs_service
+        .plan_merge(source.path(), destination.path(), &[], false, ConflictStrategy::KeepLargest)
+        .await?;
+    assert_eq!(notes_action(&plan).action, MergeAction::Skip);
+
+    let plan = fs_service
+        .plan_merge(source.path(), destination.path(), &[], false, ConflictStrategy::RenameWithSuffix)
+        .await?;
+    let step = notes_action(&plan);
+    assert_eq!(step.action, MergeAction::CopyRenamed);
+    assert!(step.destination.ends_with("notes.merged-1.txt"));
+
+    fs_service.apply_merge_plan(&plan).await;
+    assert_eq!(fs::read_to_string(destination.path().join("notes.txt"))?, "old notes");
+    assert_eq!(fs::read_to_string(destination.path().join("notes.merged-1.txt"))?, "fresh");
+
+    let nested = source.path().join("sub");
+    assert!(fs_service
+        .plan_merge(source.path(), &nested, &[], false, ConflictStrategy::Skip)
+        .await
+        .is_err());
+
+    Ok(())
+}
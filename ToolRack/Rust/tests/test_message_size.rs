@@ -0,0 +1,50 @@
+use aichemistforge_mcp_server::server::{tcp, McpServer};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+type Lines = tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>;
+
+async fn next_message(lines: &mut Lines) -> Value {
+    let line = tokio::time::timeout(Duration::from_secs(10), lines.next_line()).await.unwrap().unwrap().unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+#[tokio::test]
+async fn test_oversized_messages_are_refused() {
+    let dir = TempDir::new().unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", dir.path().to_str().unwrap()]);
+    let server = McpServer::new(MyServerHandler::new(&args).unwrap()).with_max_message_bytes(4096);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(tcp::serve(std::sync::Arc::new(server), listener));
+    let (input, mut output) = TcpStream::connect(address).await.unwrap().into_split();
+    let mut lines = BufReader::new(input).lines();
+
+    let path = dir.path().join("big.txt").display().to_string();
+    let write = |id: Value| {
+        json!({ "jsonrpc": "2.0", "id": id, "method": "tools/call",
+            "params": { "name": "write_file", "arguments": { "path": path, "content": "x".repeat(1024 * 1024) } } })
+    };
+    output.write_all(format!("{}\n", write(json!(7))).as_bytes()).await.unwrap();
+    let response = next_message(&mut lines).await;
+    assert_eq!(response["id"], 7, "{}", response);
+    assert_eq!(response["error"]["code"], -32600);
+    assert_eq!(response["error"]["data"]["limit"], 4096);
+    assert!(!dir.path().join("big.txt").exists());
+
+    // An id nested in the params is not mistaken for the request's
+    let nested = format!("{{\"jsonrpc\":\"2.0\",\"method\":\"tools/call\",\"params\":{{\"id\":3,\"pad\":\"{}\"}}}}\n", "y".repeat(8192));
+    output.write_all(nested.as_bytes()).await.unwrap();
+    assert_eq!(next_message(&mut lines).await["id"], Value::Null);
+
+    // The connection carries on with the next message
+    output.write_all(b"{\"jsonrpc\":\"2.0\",\"id\":8,\"method\":\"tools/list\"}\n").await.unwrap();
+    let response = next_message(&mut lines).await;
+    assert_eq!(response["id"], 8);
+    assert!(response["result"]["tools"].is_array());
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "metadata-cache")]
+
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::cache::MetadataCache;
+use aichemistforge_mcp_server::fs_service::duplicates::{content_hash, hash_file};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+#[test]
+fn test_cached_hash_follows_size_and_mtime() -> std::io::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file = temp_dir.path().join("data.bin");
+    fs::write(&file, "aaaa")?;
+    let mtime = SystemTime::now() - Duration::from_secs(3600);
+    fs::File::options().write(true).open(&file)?.set_modified(mtime)?;
+    let cache = MetadataCache::open(&temp_dir.path().join("cache/meta.db"))?;
+    assert!(cache.is_enabled());
+
+    let original = cache.hash_path(&file)?;
+    assert_eq!(original, hash_file(&file)?);
+
+    // Same size and mtime: the recorded hash is trusted without rereading
+    fs::write(&file, "bbbb")?;
+    fs::File::options().write(true).open(&file)?.set_modified(mtime)?;
+    assert_eq!(cache.hash_path(&file)?, original);
+
+    // A new mtime invalidates the entry
+    fs::File::options().write(true).open(&file)?.set_modified(SystemTime::now())?;
+    assert_eq!(cache.hash_path(&file)?, hash_file(&file)?);
+    assert_ne!(cache.hash_path(&file)?, original);
+
+    Ok(())
+}
+
+#[test]
+fn test_content_hashes_are_cached_apart_from_sha256() -> std::io::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file = temp_dir.path().join("data.bin");
+    fs::write(&file, "aaaa")?;
+    let mtime = SystemTime::now() - Duration::from_secs(3600);
+    fs::File::options().write(true).open(&file)?.set_modified(mtime)?;
+    let cache = MetadataCache::open(&temp_dir.path().join("meta.db"))?;
+
+    let sha256 = cache.hash_path(&file)?;
+    let blake3 = cache.content_hash_path(&file)?;
+    assert_eq!(blake3, content_hash(&file)?);
+    assert_ne!(blake3, sha256);
+
+    fs::write(&file, "bbbb")?;
+    fs::File::options().write(true).open(&file)?.set_modified(mtime)?;
+    assert_eq!(cache.content_hash_path(&file)?, blake3);
+    assert_eq!(cache.hash_path(&file)?, sha256);
+
+    Ok(())
+}
+
+#[test]
+fn test_cache_persists_between_opens() -> std::io::Result<()> {
+    let temp_dir = TempDir::new()?;
+    let file = temp_dir.path().join("data.txt");
+    let db = temp_dir.path().join("meta.db");
+    fs::write(&file, "content")?;
+    let mtime = SystemTime::now() - Duration::from_secs(60);
+    fs::File::options().write(true).open(&file)?.set_modified(mtime)?;
+
+    let original = MetadataCache::open(&db)?.hash_path(&file)?;
+    fs::write(&file, "CONTENT")?;
+    fs::File::options().write(true).open(&file)?.set_modified(mtime)?;
+
+    assert_eq!(MetadataCache::open(&db)?.hash_path(&file)?, original);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_duplicates_with_cache_match_uncached() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path().join("tree");
+    fs::create_dir_all(root.join("sub"))?;
+    fs::write(root.join("a.txt"), "same")?;
+    fs::write(root.join("sub/b.txt"), "same")?;
+    fs::write(root.join("c.txt"), "diff")?;
+
+    let plain = FileSystemService::try_new(&[], &[])?;
+    let mut cached = FileSystemService::try_new(&[], &[])?;
+    cached.set_metadata_cache(MetadataCache::open(&temp_dir.path().join("meta.db"))?);
+
+    let expected = plain.find_duplicate_files(&root, None, None, None, None, false).await?;
+    assert_eq!(expected.len(), 1);
+    for _ in 0..2 {
+        assert_eq!(cached.find_duplicate_files(&root, None, None, None, None, false).await?, expected);
+    }
+
+    Ok(())
+}
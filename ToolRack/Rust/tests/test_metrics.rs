@@ -0,0 +1,73 @@
+use aichemistforge_mcp_server::mcp_types::Content;
+use aichemistforge_mcp_server::{metrics, CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+mod common;
+use common::call;
+
+async fn server_stats(handler: &MyServerHandler, reset: bool) -> Value {
+    let result = handler
+        .handle_call_tool(call("server_stats", json!({ "output_format": "json", "reset": reset })))
+        .await
+        .unwrap();
+    let Content::Text(text) = &result.content[0] else {
+        panic!("expected text content");
+    };
+    serde_json::from_str(&text.text).unwrap()
+}
+
+fn tool<'a>(stats: &'a Value, name: &str) -> &'a Value {
+    stats["tools"].as_array().unwrap().iter().find(|tool| tool["name"] == name).unwrap_or_else(|| panic!("{} in {}", name, stats))
+}
+
+// The registry is process-wide, so everything is checked in one test
+#[tokio::test]
+async fn test_server_stats_counts_calls_errors_and_bytes() {
+    let dir = TempDir::new().unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "both", dir.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let file = dir.path().join("a.txt").display().to_string();
+    metrics::reset();
+
+    handler.handle_call_tool(call("write_file", json!({ "path": file, "content": "0123456789" }))).await.unwrap();
+    for _ in 0..2 {
+        handler.handle_call_tool(call("read_file", json!({ "path": file }))).await.unwrap();
+    }
+    let missing = dir.path().join("missing.txt").display().to_string();
+    let failed = handler.handle_call_tool(call("read_file", json!({ "path": missing }))).await;
+    assert!(failed.map_or(true, |result| result.is_error == Some(true)));
+    // Grouped tools are counted per operation
+    let mode = json!({ "mode_name": "single_file_operations" });
+    handler.handle_call_tool(call("start_operation_mode", mode)).await.unwrap();
+    let grouped = json!({ "operation": "read_file", "path": file });
+    handler.handle_call_tool(call("single_file_operations", grouped)).await.unwrap();
+
+    let stats = server_stats(&handler, true).await;
+    assert_eq!(stats["calls"], 6, "{}", stats);
+    assert_eq!(stats["errors"], 1);
+    assert_eq!(stats["bytes_written"], 10);
+    assert_eq!(stats["bytes_read"], 30);
+
+    let reads = tool(&stats, "read_file");
+    assert_eq!(reads["calls"], 3);
+    assert_eq!(reads["errors"], 1);
+    assert_eq!(reads["bytes_read"], 20);
+    assert!(reads["p95_ms"].as_f64().unwrap() <= reads["max_ms"].as_f64().unwrap());
+    assert_eq!(tool(&stats, "write_file")["bytes_written"], 10);
+    assert_eq!(tool(&stats, "single_file_operations.read_file")["bytes_read"], 10);
+    // Busiest tools first
+    assert_eq!(stats["tools"][0]["name"], "read_file");
+
+    // The reset happened after the snapshot was taken
+    let after = server_stats(&handler, false).await;
+    assert_eq!(after["calls"], 1, "{}", after);
+    assert_eq!(tool(&after, "server_stats")["calls"], 1);
+    assert_eq!(after["bytes_read"], 0);
+
+    let snapshot = dir.path().join("metrics.json");
+    metrics::write_snapshot(&snapshot).unwrap();
+    let written: Value = serde_json::from_str(&std::fs::read_to_string(&snapshot).unwrap()).unwrap();
+    assert_eq!(written["calls"], 2);
+}
@@ -0,0 +1,40 @@
+use aichemistforge_mcp_server::fs_service::mmap::{scan_range, MMAP_THRESHOLD};
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use tempfile::TempDir;
+
+/// A sparse file just over the mapping threshold with a newline at each of `offsets`
+fn sparse_file(dir: &TempDir, offsets: &[u64]) -> std::io::Result<File> {
+    let path = dir.path().join("large.bin");
+    let mut file = File::options().create(true).truncate(true).read(true).write(true).open(&path)?;
+    file.set_len(MMAP_THRESHOLD + 4096)?;
+    for offset in offsets {
+        file.seek(SeekFrom::Start(*offset))?;
+        file.write_all(b"\n")?;
+    }
+    Ok(file)
+}
+
+fn count_newlines(file: &File, range: std::ops::Range<u64>) -> std::io::Result<(usize, u64)> {
+    let (mut newlines, mut bytes) = (0, 0);
+    scan_range(file, range, |chunk| {
+        newlines += chunk.iter().filter(|b| **b == b'\n').count();
+        bytes += chunk.len() as u64;
+    })?;
+    Ok((newlines, bytes))
+}
+
+#[test]
+fn test_scan_range_covers_large_files() -> std::io::Result<()> {
+    let dir = TempDir::new()?;
+    let offsets = [0, 4095, MMAP_THRESHOLD / 2, MMAP_THRESHOLD + 4095];
+    let file = sparse_file(&dir, &offsets)?;
+
+    assert_eq!(count_newlines(&file, 0..u64::MAX)?, (4, MMAP_THRESHOLD + 4096));
+    // Unaligned start offsets and ranges below the threshold
+    assert_eq!(count_newlines(&file, 1..MMAP_THRESHOLD + 4095)?, (2, MMAP_THRESHOLD + 4094));
+    assert_eq!(count_newlines(&file, 4000..8000)?, (1, 4000));
+    assert_eq!(count_newlines(&file, MMAP_THRESHOLD + 5000..MMAP_THRESHOLD + 6000)?, (0, 0));
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+// Linux file systems accept any bytes in names; macOS and Windows do not
+#![cfg(target_os = "linux")]
+
+use aichemistforge_mcp_server::fs_service::ops::FileSystemOps;
+use aichemistforge_mcp_server::fs_service::roots::{file_uri, path_from_file_uri};
+use aichemistforge_mcp_server::fs_service::utils::{name_text, path_text};
+use aichemistforge_mcp_server::fs_service::{FileSystemService, SearchFilesOptions};
+use std::ffi::OsStr;
+use std::fs;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+// `café.txt` in Latin-1: the é is the lone byte 0xE9
+fn latin1_name() -> &'static OsStr {
+    OsStr::from_bytes(b"caf\xe9.txt")
+}
+
+#[test]
+fn test_non_utf8_paths_round_trip_through_uris() {
+    let path = Path::new("/data").join(latin1_name());
+    let text = path_text(&path);
+    assert_eq!(text, "file:///data/caf%E9.txt");
+    assert_eq!(name_text(&path), text);
+    assert_eq!(path_from_file_uri(&text).unwrap(), path);
+
+    // Valid names are shown as they are
+    assert_eq!(path_text(Path::new("/data/café.txt")), "/data/café.txt");
+    assert_eq!(name_text(Path::new("/data/café.txt")), "café.txt");
+    assert_eq!(path_from_file_uri(&file_uri(Path::new("/data/café.txt"))).unwrap(), Path::new("/data/café.txt"));
+}
+
+#[tokio::test]
+async fn test_listed_names_can_be_passed_back() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join(latin1_name());
+    fs::write(&path, "bonjour").unwrap();
+    let fs_service = FileSystemService::try_new(&[dir.path().display().to_string()], &[]).unwrap();
+
+    let listing = FileSystemOps::list_directory(&fs_service, dir.path()).await.unwrap();
+    assert_eq!(listing.len(), 1);
+    let listed = &listing[0].name;
+    assert!(listed.starts_with("file://") && listed.ends_with("caf%E9.txt"), "{}", listed);
+    assert_eq!(fs_service.read_file(Path::new(listed)).await.unwrap(), "bonjour");
+
+    let options = SearchFilesOptions { pattern: "caf".to_string(), ..Default::default() };
+    let found = fs_service.search_files(dir.path(), options).await.unwrap().files;
+    assert_eq!(found.len(), 1);
+    assert_eq!(&found[0], listed);
+
+    let tree = fs_service.generate_directory_tree(dir.path(), false, 0, false, false).await.unwrap();
+    assert!(tree.contains(listed.as_str()), "{}", tree);
+
+    // Writing back through the URI changes the original file, not a mangled copy
+    fs_service.write_file(Path::new(listed), &"salut".to_string()).await.unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "salut");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+    // URIs are checked against the policy like any path
+    assert!(fs_service.read_file(Path::new("file:///etc/passwd")).await.is_err());
+}
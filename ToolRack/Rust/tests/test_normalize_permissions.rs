@@ -0,0 +1,80 @@
+#![cfg(unix)]
+
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::permissions::{parse_mode, NormalizePermissionsOptions};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs::{self, Permissions};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn mode(path: &Path) -> u32 {
+    fs::metadata(path).unwrap().permissions().mode() & 0o7777
+}
+
+fn setup_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("sub")).unwrap();
+    fs::write(root.join("a.txt"), "a").unwrap();
+    fs::write(root.join("sub").join("b.txt"), "b").unwrap();
+    fs::set_permissions(root.join("a.txt"), Permissions::from_mode(0o666)).unwrap();
+    fs::set_permissions(root.join("sub").join("b.txt"), Permissions::from_mode(0o600)).unwrap();
+    fs::set_permissions(root.join("sub"), Permissions::from_mode(0o777)).unwrap();
+    fs::set_permissions(root, Permissions::from_mode(0o755)).unwrap();
+    temp_dir
+}
+
+#[tokio::test]
+async fn test_normalize_permissions_dry_run_then_apply() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let root = temp_dir.path();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let mut options = NormalizePermissionsOptions {
+        file_mode: Some(0o644),
+        dir_mode: Some(0o750),
+        dry_run: true,
+        ..Default::default()
+    };
+
+    let report = fs_service.normalize_permissions(root, &options).await?;
+    assert_eq!(report.changes.len(), 4);
+    assert_eq!(mode(&root.join("a.txt")), 0o666);
+
+    options.dry_run = false;
+    let report = fs_service.normalize_permissions(root, &options).await?;
+    assert!(report.changes.iter().all(|c| c.error.is_none()));
+    assert_eq!(mode(&root.join("a.txt")), 0o644);
+    assert_eq!(mode(&root.join("sub").join("b.txt")), 0o644);
+    assert_eq!(mode(&root.join("sub")), 0o750);
+    assert_eq!(mode(root), 0o750);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_normalize_permissions_respects_excludes_and_file_only() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let root = temp_dir.path();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let options = NormalizePermissionsOptions {
+        file_mode: Some(0o640),
+        exclude_patterns: vec!["sub".to_string()],
+        ..Default::default()
+    };
+
+    fs_service.normalize_permissions(root, &options).await?;
+    assert_eq!(mode(&root.join("a.txt")), 0o640);
+    assert_eq!(mode(&root.join("sub").join("b.txt")), 0o600);
+    assert_eq!(mode(&root.join("sub")), 0o777);
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_mode() {
+    assert_eq!(parse_mode("644"), Ok(0o644));
+    assert_eq!(parse_mode("0o2775"), Ok(0o2775));
+    assert!(parse_mode("999").is_err());
+    assert!(parse_mode("17777").is_err());
+}
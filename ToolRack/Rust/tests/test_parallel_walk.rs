@@ -0,0 +1,54 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs;
+use tempfile::TempDir;
+
+/// 20 directories of 10 files each, plus a hidden directory
+fn wide_tree() -> std::io::Result<TempDir> {
+    let temp_dir = TempDir::new()?;
+    for d in 0..20 {
+        let dir = temp_dir.path().join(format!("dir{:02}", d));
+        fs::create_dir_all(dir.join("nested"))?;
+        for f in 0..10 {
+            fs::write(dir.join(format!("file{}.txt", f)), vec![b'x'; d + 1])?;
+        }
+        fs::write(dir.join("nested/deep.txt"), "1234")?;
+    }
+    fs::create_dir(temp_dir.path().join(".hidden"))?;
+    fs::write(temp_dir.path().join(".hidden/secret.txt"), "abc")?;
+    Ok(temp_dir)
+}
+
+#[tokio::test]
+async fn test_parallel_size_matches_file_total() -> ServiceResult<()> {
+    let temp_dir = wide_tree()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    // 10 files of d+1 bytes per directory, one 4-byte file each, and the hidden file
+    let expected: u64 = (1..=20).map(|n| 10 * n + 4).sum::<u64>() + 3;
+    assert_eq!(fs_service.calculate_directory_size(temp_dir.path(), false, false).await?, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_parallel_tree_is_sorted_and_bounded() -> ServiceResult<()> {
+    let temp_dir = wide_tree()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let tree = fs_service.generate_directory_tree(temp_dir.path(), false, 0, false, false).await?;
+    let lines: Vec<&str> = tree.lines().collect();
+    assert_eq!(lines[1], "  ├── dir00/");
+    assert_eq!(lines[2], "    ├── file0.txt");
+    assert_eq!(lines[11], "    ├── file9.txt");
+    assert_eq!(lines[12], "    ├── nested/");
+    assert_eq!(lines[13], "      ├── deep.txt");
+    assert_eq!(lines.len(), 1 + 20 * 13);
+    assert!(!tree.contains("secret.txt"));
+
+    let shallow = fs_service.generate_directory_tree(temp_dir.path(), true, 1, false, false).await?;
+    assert_eq!(shallow.lines().count(), 1 + 21);
+    assert!(shallow.contains(".hidden/"));
+
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+async fn allowed(fs_service: &FileSystemService, path: &Path) -> bool {
+    fs_service.validate_path(path).await.is_ok()
+}
+
+#[tokio::test]
+async fn test_blocked_patterns_match_anywhere_in_the_tree() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("app/secrets")).unwrap();
+    fs::write(root.join("app/.env"), "TOKEN=1").unwrap();
+    fs::write(root.join("app/secrets/key.pem"), "key").unwrap();
+    fs::write(root.join("app/main.rs"), "fn main() {}").unwrap();
+
+    let blocked = ["**/.env".to_string(), "**/secrets".to_string()];
+    let fs_service = FileSystemService::try_new(&[root.display().to_string()], &blocked).unwrap();
+
+    assert!(!allowed(&fs_service, &root.join("app/.env")).await);
+    assert!(!allowed(&fs_service, &root.join(".env")).await);
+    // A blocked directory's contents are blocked with it
+    assert!(!allowed(&fs_service, &root.join("app/secrets")).await);
+    assert!(!allowed(&fs_service, &root.join("app/secrets/key.pem")).await);
+    assert!(allowed(&fs_service, &root.join("app/main.rs")).await);
+    assert!(allowed(&fs_service, &root.join("app/.env.example")).await);
+
+    let read = fs_service.read_file(&root.join("app/.env")).await;
+    assert!(read.is_err());
+
+    let report = fs_service.roots();
+    assert_eq!(report.blocked_patterns, blocked);
+    assert!(report.blocked.is_empty());
+    assert!(report.describe().contains("Paths matching these patterns are always blocked: **/.env, **/secrets."));
+}
+
+#[tokio::test]
+async fn test_allowed_patterns_extend_the_allowlist() {
+    let (docs, other) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let pattern = format!("{}/**/*.md", docs.path().display());
+    let fs_service = FileSystemService::try_new(&[pattern.clone(), other.path().display().to_string()], &[]).unwrap();
+
+    assert!(allowed(&fs_service, &docs.path().join("guide/intro.md")).await);
+    assert!(!allowed(&fs_service, &docs.path().join("guide/intro.txt")).await);
+    assert!(allowed(&fs_service, &other.path().join("anything.txt")).await);
+
+    let report = fs_service.roots();
+    assert!(!report.unrestricted);
+    assert_eq!(report.allowed_patterns, [pattern]);
+    assert_eq!(report.roots.len(), 1);
+}
+
+#[test]
+fn test_invalid_patterns_are_refused() {
+    let invalid = FileSystemService::try_new(&[], &["**/[.env".to_string()]).err().unwrap();
+    assert!(invalid.to_string().contains("Invalid glob pattern '**/[.env'"), "{}", invalid);
+
+    let suffixed = FileSystemService::try_new(&["/srv/**/*.md:ro".to_string()], &[]).err().unwrap();
+    assert!(suffixed.to_string().contains("apply to directories, not patterns"), "{}", suffixed);
+
+    // A failed reload keeps the policy that was in effect
+    let fs_service = FileSystemService::try_new(&[], &["**/.env".to_string()]).unwrap();
+    assert!(fs_service.set_directory_policy(&[], &["**/[".to_string()]).is_err());
+    assert_eq!(fs_service.roots().blocked_patterns, ["**/.env"]);
+}
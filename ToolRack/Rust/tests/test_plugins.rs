@@ -0,0 +1,125 @@
+#![cfg(feature = "plugins")]
+
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, CallToolResult, Content};
+use aichemistforge_mcp_server::plugins::PluginRegistry;
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{call, text};
+
+// wasmtime also accepts the text format, so test plugins are written as WAT
+const ECHO_PLUGIN: &str = r#"(module
+  (import "wasi_snapshot_preview1" "fd_read" (func $fd_read (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (func (export "_start")
+    (i32.store (i32.const 0) (i32.const 4096))
+    (i32.store (i32.const 4) (i32.const 4096))
+    (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))
+    (i32.store (i32.const 4) (i32.load (i32.const 8)))
+    (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))))"#;
+
+/// Prints the file at `path` using the read_file host function, or the host
+/// error on stderr with exit status 1
+fn reader_plugin(path: &Path) -> String {
+    let path = path.display().to_string();
+    format!(
+        r#"(module
+  (import "wasi_snapshot_preview1" "fd_write" (func $fd_write (param i32 i32 i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "proc_exit" (func $proc_exit (param i32)))
+  (import "aichemistforge" "read_file" (func $read_file (param i32 i32 i32 i32) (result i64)))
+  (import "aichemistforge" "last_error" (func $last_error (param i32 i32) (result i64)))
+  (memory (export "memory") 1)
+  (data (i32.const 1024) "{path}")
+  (func $print (param $fd i32) (param $ptr i32) (param $len i32)
+    (i32.store (i32.const 0) (local.get $ptr))
+    (i32.store (i32.const 4) (local.get $len))
+    (drop (call $fd_write (local.get $fd) (i32.const 0) (i32.const 1) (i32.const 8))))
+  (func (export "_start")
+    (local $n i64)
+    (local.set $n (call $read_file (i32.const 1024) (i32.const {len}) (i32.const 4096) (i32.const 4096)))
+    (if (i64.lt_s (local.get $n) (i64.const 0))
+      (then
+        (local.set $n (call $last_error (i32.const 4096) (i32.const 4096)))
+        (call $print (i32.const 2) (i32.const 4096) (i32.wrap_i64 (local.get $n)))
+        (call $proc_exit (i32.const 1))))
+    (call $print (i32.const 1) (i32.const 4096) (i32.wrap_i64 (local.get $n)))))"#,
+        path = path,
+        len = path.len()
+    )
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_plugins_are_exposed_as_tools() {
+    let allowed = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    let plugins = TempDir::new().unwrap();
+    fs::write(allowed.path().join("notes.txt"), "plugin-visible").unwrap();
+    fs::write(outside.path().join("secret.txt"), "hidden").unwrap();
+
+    fs::write(plugins.path().join("echo.wasm"), ECHO_PLUGIN).unwrap();
+    fs::write(
+        plugins.path().join("echo.json"),
+        r#"{"description": "Echo the arguments", "input_schema": {"type": "object", "properties": {"message": {"type": "string"}}}}"#,
+    )
+    .unwrap();
+    fs::write(plugins.path().join("cat_notes.wasm"), reader_plugin(&allowed.path().join("notes.txt"))).unwrap();
+    fs::write(plugins.path().join("cat_notes.json"), r#"{"capabilities": ["read"]}"#).unwrap();
+    // Same module without the read capability, and one reaching outside the allowlist
+    fs::write(plugins.path().join("no_caps.wasm"), reader_plugin(&allowed.path().join("notes.txt"))).unwrap();
+    fs::write(plugins.path().join("escape.wasm"), reader_plugin(&outside.path().join("secret.txt"))).unwrap();
+    fs::write(plugins.path().join("escape.json"), r#"{"name": "read_secret", "capabilities": ["read"]}"#).unwrap();
+
+    let args = CommandArguments::parse_from([
+        "server",
+        "--plugins-dir",
+        plugins.path().to_str().unwrap(),
+        allowed.path().to_str().unwrap(),
+    ]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    assert!(handler.startup_message().contains("Plugins: cat_notes (read), echo, read_secret (read), no_caps"));
+
+    let tools = handler.handle_list_tools().await.unwrap().tools;
+    let echo = tools.iter().find(|tool| tool.name == "echo").unwrap();
+    assert_eq!(echo.description.as_deref(), Some("Echo the arguments"));
+    assert_eq!(echo.input_schema["properties"]["message"]["type"], "string");
+
+    let result = handler.handle_call_tool(call("echo", serde_json::json!({"message": "hi"}))).await.unwrap();
+    assert_eq!(text(&result), r#"{"message":"hi"}"#);
+
+    let result = handler.handle_call_tool(call("cat_notes", serde_json::json!({}))).await.unwrap();
+    assert_eq!(result.is_error, Some(false));
+    assert_eq!(text(&result), "plugin-visible");
+
+    let result = handler.handle_call_tool(call("no_caps", serde_json::json!({}))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("'read' capability is not granted"), "{}", text(&result));
+
+    let result = handler.handle_call_tool(call("read_secret", serde_json::json!({}))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(!text(&result).contains("hidden"));
+}
+
+#[test]
+fn test_bad_plugins_fail_the_load() {
+    let reserved = vec!["file_management".to_string()];
+    let load = |files: &[(&str, &str)]| {
+        let dir = TempDir::new().unwrap();
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+        }
+        PluginRegistry::load_dir(dir.path(), &reserved).map(|registry| registry.describe())
+    };
+
+    assert_eq!(load(&[("echo.wasm", ECHO_PLUGIN), ("readme.txt", "ignored")]).unwrap(), vec!["echo"]);
+    assert!(load(&[("file_management.wasm", ECHO_PLUGIN)]).unwrap_err().to_string().contains("already exists"));
+    assert!(load(&[("a.wasm", ECHO_PLUGIN), ("b.wasm", ECHO_PLUGIN), ("b.json", r#"{"name": "a"}"#)]).is_err());
+    assert!(load(&[("echo.wasm", ECHO_PLUGIN), ("echo.json", r#"{"capabilities": ["network"]}"#)]).is_err());
+    assert!(load(&[("lib.wasm", "(module (func (export \"run\")))")]).unwrap_err().to_string().contains("_start"));
+    let unknown_import = r#"(module (import "env" "system" (func)) (func (export "_start")))"#;
+    assert!(load(&[("shell.wasm", unknown_import)]).is_err());
+}
@@ -0,0 +1,72 @@
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+mod common;
+use common::{call, text};
+
+fn write_policy(config: &Path, allowed: &Path) {
+    let allowed = allowed.display().to_string().replace('\\', "\\\\");
+    fs::write(config, format!("[policy]\nallowed_directories = [\"{}\"]\n", allowed)).unwrap();
+}
+
+async fn can_read(handler: &MyServerHandler, path: &Path) -> bool {
+    let result = handler.handle_call_tool(call("read_file", json!({ "path": path.display().to_string() }))).await;
+    result.is_ok_and(|result| result.is_error != Some(true))
+}
+
+#[tokio::test]
+async fn test_reload_policy_swaps_the_allowed_directories() {
+    let (first, second, settings) = (TempDir::new().unwrap(), TempDir::new().unwrap(), TempDir::new().unwrap());
+    let (first_file, second_file) = (first.path().join("a.txt"), second.path().join("b.txt"));
+    fs::write(&first_file, "first").unwrap();
+    fs::write(&second_file, "second").unwrap();
+    let config = settings.path().join("aichemist.toml");
+    write_policy(&config, first.path());
+
+    // The table overrides the directory given on the command line
+    let config_arg = config.display().to_string();
+    let args = CommandArguments::parse_from([
+        "server", "--tool-style", "flat", "--config", &config_arg, second.path().to_str().unwrap(),
+    ]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    assert!(can_read(&handler, &first_file).await);
+    assert!(!can_read(&handler, &second_file).await);
+
+    write_policy(&config, second.path());
+    let result = handler.handle_call_tool(call("reload_policy", json!({}))).await.unwrap();
+    assert_eq!(result.is_error, Some(false), "{}", text(&result));
+    assert!(text(&result).contains(&second.path().display().to_string()));
+    assert!(!can_read(&handler, &first_file).await);
+    assert!(can_read(&handler, &second_file).await);
+
+    // A broken file leaves the current policy in place
+    fs::write(&config, "[policy]\nallowed_directories = \"not a list\"\n").unwrap();
+    let result = handler.handle_call_tool(call("reload_policy", json!({}))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(can_read(&handler, &second_file).await);
+
+    // Without the table the command line applies again
+    fs::write(&config, "").unwrap();
+    assert!(handler.reload_policy().is_ok());
+    assert!(can_read(&handler, &second_file).await);
+    assert!(!can_read(&handler, &first_file).await);
+
+    assert!(!handler.is_readonly());
+    fs::write(&config, "[policy]\nreadonly = true\n").unwrap();
+    assert!(handler.reload_policy().unwrap().readonly);
+    assert!(handler.is_readonly());
+}
+
+#[tokio::test]
+async fn test_reload_policy_needs_a_config_file() {
+    let dir = TempDir::new().unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", dir.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let result = handler.handle_call_tool(call("reload_policy", json!({}))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("--config"));
+}
@@ -0,0 +1,50 @@
+use aichemistforge_mcp_server::profiler::slow_operations;
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use tempfile::TempDir;
+
+mod common;
+use common::call;
+
+// One test, since the threshold and the log are process-wide
+#[tokio::test]
+async fn test_slow_calls_are_logged_with_phases() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    std::fs::write(&file, "hello").unwrap();
+    let args = CommandArguments::parse_from(["server", "--slow-op-threshold-ms", "0"]);
+    let handler = MyServerHandler::new(&args).unwrap();
+
+    handler
+        .handle_call_tool(call("start_operation_mode", json!({ "mode_name": "single_file_operations" })))
+        .await
+        .unwrap();
+    let result = handler
+        .handle_call_tool(call("single_file_operations", json!({ "operation": "read_file", "path": file })))
+        .await
+        .unwrap();
+    assert!(!result.is_error.unwrap_or(false));
+
+    let logged = slow_operations(10);
+    let read = logged
+        .iter()
+        .find(|op| op.operation.as_deref() == Some("read_file"))
+        .expect("read_file call was not logged");
+    assert_eq!(read.tool, "single_file_operations");
+    assert_eq!(read.path.as_deref(), Some(file.to_str().unwrap()));
+    assert!(read.response_bytes > 0);
+    assert!(read.total_ms >= read.validation_ms + read.formatting_ms);
+    // Newest first
+    assert_eq!(logged[0].operation.as_deref(), Some("read_file"));
+
+    let report = handler
+        .handle_call_tool(call("get_slow_operations", json!({ "output_format": "json", "clear": true })))
+        .await
+        .unwrap();
+    assert!(!report.is_error.unwrap_or(false));
+    // Only the get_slow_operations call itself, logged after it cleared the rest
+    let remaining = slow_operations(10);
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].tool, "get_slow_operations");
+}
@@ -0,0 +1,62 @@
+use aichemistforge_mcp_server::mcp_types::{Content, GetPromptParams, INVALID_PARAMS};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::collections::HashMap;
+
+fn handler(style: &str) -> MyServerHandler {
+    MyServerHandler::new(&CommandArguments::parse_from(["server", "--tool-style", style])).unwrap()
+}
+
+fn params(name: &str, arguments: &[(&str, &str)]) -> GetPromptParams {
+    GetPromptParams {
+        name: name.to_string(),
+        arguments: arguments.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>(),
+    }
+}
+
+async fn prompt_text(handler: &MyServerHandler, params: GetPromptParams) -> String {
+    let result = handler.handle_get_prompt(params).await.unwrap();
+    assert_eq!(result.messages.len(), 1);
+    assert_eq!(result.messages[0].role, "user");
+    match &result.messages[0].content {
+        Content::Text(text) => text.text.clone(),
+        other => panic!("expected text, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_prompts_are_listed_with_arguments() {
+    let prompts = handler("flat").handle_list_prompts().await.unwrap().prompts;
+    let names: Vec<&str> = prompts.iter().map(|prompt| prompt.name.as_str()).collect();
+    assert_eq!(names, ["summarize_directory", "clean_up_duplicates", "find_large_files", "review_recent_changes"]);
+    for prompt in &prompts {
+        assert!(prompt.arguments.iter().any(|argument| argument.name == "path" && argument.required), "{}", prompt.name);
+    }
+}
+
+#[tokio::test]
+async fn test_prompt_names_tools_for_the_tool_style() {
+    let flat = prompt_text(&handler("flat"), params("clean_up_duplicates", &[("path", "/data/photos")])).await;
+    assert!(flat.contains("/data/photos"));
+    assert!(flat.contains("`find_duplicate_files`"));
+    assert!(flat.contains("action delete_extras"));
+
+    let grouped = prompt_text(&handler("grouped"), params("find_large_files", &[("path", "/data"), ("min_size_mb", "1")])).await;
+    assert!(grouped.contains("`search_and_analysis` with operation `find_files_by_size`"));
+    assert!(grouped.contains("min_bytes 1048576"));
+    assert!(grouped.contains("start_operation_mode"));
+}
+
+#[tokio::test]
+async fn test_prompt_argument_errors() {
+    let handler = handler("flat");
+    for params in [
+        params("summarize_directory", &[]),
+        params("no_such_prompt", &[("path", "/data")]),
+        params("clean_up_duplicates", &[("path", "/data"), ("action", "shred")]),
+        params("review_recent_changes", &[("path", "/data"), ("within_hours", "yesterday")]),
+    ] {
+        let error = handler.handle_get_prompt(params).await.unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS, "{}", error.message);
+    }
+}
@@ -0,0 +1,41 @@
+use aichemistforge_mcp_server::mcp_types::{
+    negotiate_protocol_version, ClientInfo, InitializeParams, InitializeRequest, INVALID_PARAMS,
+    LATEST_PROTOCOL_VERSION,
+};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::collections::HashMap;
+
+fn initialize(version: &str) -> InitializeRequest {
+    InitializeRequest {
+        params: InitializeParams {
+            protocol_version: version.to_string(),
+            capabilities: HashMap::new(),
+            client_info: ClientInfo { name: "test".to_string(), version: "0".to_string() },
+        },
+    }
+}
+
+#[test]
+fn test_negotiate_protocol_version() {
+    assert_eq!(negotiate_protocol_version("2024-11-05"), Ok("2024-11-05"));
+    assert_eq!(negotiate_protocol_version("2025-03-26"), Ok("2025-03-26"));
+    assert_eq!(negotiate_protocol_version("2099-01-01"), Ok(LATEST_PROTOCOL_VERSION));
+    assert!(negotiate_protocol_version("2024-10-07").unwrap_err().contains("older than"));
+    assert!(negotiate_protocol_version("1.0").is_err());
+    assert!(negotiate_protocol_version("2025-6-18").is_err());
+}
+
+#[tokio::test]
+async fn test_initialize_negotiates_the_version() {
+    let handler = MyServerHandler::new(&CommandArguments::parse_from(["server", "/srv/data"])).unwrap();
+
+    let result = handler.handle_initialize(initialize("2025-03-26")).await.unwrap();
+    assert_eq!(result.protocol_version, "2025-03-26");
+    let result = handler.handle_initialize(initialize("2030-01-01")).await.unwrap();
+    assert_eq!(result.protocol_version, LATEST_PROTOCOL_VERSION);
+
+    let error = handler.handle_initialize(initialize("2023-01-01")).await.unwrap_err();
+    assert_eq!(error.code, INVALID_PARAMS);
+    assert_eq!(error.data.unwrap()["supported"][0], "2024-11-05");
+}
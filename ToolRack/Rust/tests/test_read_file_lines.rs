@@ -0,0 +1,101 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::{utils::number_lines, FileSystemService, LineOrigin};
+use std::fs;
+use tempfile::TempDir;
+
+fn numbered_lines(count: usize) -> String {
+    (1..=count).map(|i| format!("line {i}\n")).collect()
+}
+
+#[tokio::test]
+async fn test_read_file_lines_from_start_reports_resume_offset() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().join("lines.txt");
+    fs::write(&path, numbered_lines(5))?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let window = fs_service.read_file_lines(&path, LineOrigin::Start, 1, Some(2)).await?;
+    assert_eq!(window.lines, vec!["line 2", "line 3"]);
+    assert_eq!(window.start_byte, 7);
+    assert_eq!(window.end_byte, 21);
+    assert!(!window.reached_eof());
+
+    // Resuming at end_byte continues with the next line
+    let resumed = fs_service
+        .read_file_lines(&path, LineOrigin::ByteOffset(window.end_byte), 0, None)
+        .await?;
+    assert_eq!(resumed.lines, vec!["line 4", "line 5"]);
+    assert!(resumed.reached_eof());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_file_lines_from_end_across_chunks() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().join("large.txt");
+    // Larger than one reverse-read chunk so several seeks are needed
+    fs::write(&path, numbered_lines(5000))?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let window = fs_service.read_file_lines(&path, LineOrigin::End, 10, Some(3)).await?;
+    assert_eq!(window.lines, vec!["line 4988", "line 4989", "line 4990"]);
+
+    let content = fs::read_to_string(&path)?;
+    let start = window.start_byte as usize;
+    let end = window.end_byte as usize;
+    assert_eq!(&content[start..end], "line 4988\nline 4989\nline 4990\n");
+
+    let whole = fs_service.read_file_lines(&path, LineOrigin::End, 4998, None).await?;
+    assert_eq!(whole.lines, vec!["line 1", "line 2"]);
+    assert_eq!(whole.start_byte, 0);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_file_lines_from_end_without_trailing_newline() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().join("crlf.txt");
+    fs::write(&path, "first\r\nsecond\r\nthird")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let window = fs_service.read_file_lines(&path, LineOrigin::End, 0, Some(2)).await?;
+    assert_eq!(window.lines, vec!["second", "third"]);
+    assert!(window.reached_eof());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_file_lines_rejects_offset_past_eof() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().join("short.txt");
+    fs::write(&path, "tiny\n")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let result = fs_service.read_file_lines(&path, LineOrigin::ByteOffset(100), 0, None).await;
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_line_number_at_byte_offset() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let path = temp_dir.path().join("lines.txt");
+    fs::write(&path, numbered_lines(12))?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let window = fs_service.read_file_lines(&path, LineOrigin::End, 0, Some(2)).await?;
+    assert_eq!(fs_service.line_number_at(&path, window.start_byte).await?, 11);
+    assert_eq!(fs_service.line_number_at(&path, 0).await?, 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_number_lines_aligns_to_widest_number() {
+    let numbered = number_lines(["a", "b", "c"], 9);
+    assert_eq!(numbered, " 9\ta\n10\tb\n11\tc");
+}
@@ -0,0 +1,145 @@
+use aichemistforge_mcp_server::fs_service::resources::MAX_INLINE_READ_BYTES;
+use aichemistforge_mcp_server::mcp_types::{CallToolResult, Content};
+use aichemistforge_mcp_server::tools::ReadFileTool;
+use aichemistforge_mcp_server::FileSystemService;
+use tempfile::TempDir;
+
+fn range(path: &std::path::Path, offset_bytes: Option<u64>, length_bytes: Option<u64>) -> ReadFileTool {
+    ReadFileTool { path: path.display().to_string(), with_line_numbers: false, offset_bytes, length_bytes, cursor: None, encoding: None }
+}
+
+fn texts(result: &CallToolResult) -> (&str, &str) {
+    let (Content::Text(content), Content::Text(note)) = (&result.content[0], &result.content[1]) else {
+        panic!("expected the content and a note");
+    };
+    (&content.text, &note.text)
+}
+
+#[tokio::test]
+async fn test_byte_range_of_a_large_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let log = temp_dir.path().join("big.log");
+    let mut content = "x".repeat(MAX_INLINE_READ_BYTES as usize);
+    content.push_str("ERROR disk full\n");
+    std::fs::write(&log, &content).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    // Too big to read whole, but a slice comes back inline
+    let result = range(&log, Some(MAX_INLINE_READ_BYTES), Some(5)).run_tool(&fs_service).await.unwrap();
+    let (text, note) = texts(&result);
+    assert_eq!(text, "ERROR");
+    let (start, total) = (MAX_INLINE_READ_BYTES, content.len());
+    assert_eq!(note, format!("Bytes {}-{} of {}. More data follows; continue with offset_bytes: {}", start, start + 4, total, start + 5));
+
+    let result = range(&log, Some(start + 5), None).run_tool(&fs_service).await.unwrap();
+    let (text, note) = texts(&result);
+    assert_eq!(text, " disk full\n");
+    assert!(!note.contains("More data follows"), "{}", note);
+
+    let past_end = range(&log, Some(total as u64 + 1), None).run_tool(&fs_service).await;
+    assert!(past_end.is_err());
+}
+
+#[tokio::test]
+async fn test_byte_ranges_keep_characters_whole() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("notes.txt");
+    // é and ü are two bytes each
+    std::fs::write(&path, "café über").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    // Cutting é in half leaves it for the next read
+    let result = range(&path, None, Some(4)).run_tool(&fs_service).await.unwrap();
+    let (text, note) = texts(&result);
+    assert_eq!(text, "caf");
+    assert!(note.ends_with("continue with offset_bytes: 3"), "{}", note);
+    let result = range(&path, Some(3), Some(4)).run_tool(&fs_service).await.unwrap();
+    assert_eq!(texts(&result).0, "é ");
+
+    // Starting inside é skips to the next character
+    let result = range(&path, Some(4), None).run_tool(&fs_service).await.unwrap();
+    let (text, note) = texts(&result);
+    assert_eq!(text, " über");
+    assert!(note.starts_with("Bytes 5-10 of 11."), "{}", note);
+
+    let mut numbered = range(&path, Some(0), None);
+    numbered.with_line_numbers = true;
+    assert!(numbered.run_tool(&fs_service).await.is_err());
+}
+
+fn chunk(path: &std::path::Path, cursor: Option<&str>) -> ReadFileTool {
+    ReadFileTool {
+        path: path.display().to_string(),
+        with_line_numbers: false,
+        offset_bytes: None,
+        length_bytes: None,
+        cursor: cursor.map(str::to_string),
+        encoding: None,
+    }
+}
+
+fn next_cursor(result: &CallToolResult) -> Option<String> {
+    let structured = result.structured_content.as_ref().unwrap();
+    structured["next_cursor"].as_str().map(str::to_string)
+}
+
+#[tokio::test]
+async fn test_large_files_are_read_in_chunks() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("huge.log");
+    let content: String = (1..=60_000).map(|n| format!("{:08} {}\n", n, "x".repeat(90))).collect();
+    assert!(content.len() as u64 > MAX_INLINE_READ_BYTES);
+    std::fs::write(&path, &content).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let mut read = String::new();
+    let mut cursor = None;
+    let mut chunks = 0;
+    loop {
+        let result = chunk(&path, cursor.as_deref()).run_tool(&fs_service).await.unwrap();
+        let (text, note) = texts(&result);
+        assert!(text.len() as u64 <= MAX_INLINE_READ_BYTES);
+        // Every chunk ends at a line break
+        assert!(text.ends_with('\n'));
+        read.push_str(text);
+        chunks += 1;
+        cursor = next_cursor(&result);
+        match &cursor {
+            Some(cursor) => assert!(note.contains(&format!("Continue with cursor: \"{}\"", cursor)), "{}", note),
+            None => {
+                assert!(note.ends_with("This is the end of the file."), "{}", note);
+                break;
+            }
+        }
+    }
+    assert_eq!(chunks, 2);
+    assert_eq!(read, content);
+}
+
+#[tokio::test]
+async fn test_chunk_cursors_carry_line_numbers_and_expire() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("huge.log");
+    let content: String = (1..=60_000).map(|n| format!("{:08} {}\n", n, "x".repeat(90))).collect();
+    std::fs::write(&path, &content).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let first = chunk(&path, None).run_tool(&fs_service).await.unwrap();
+    let cursor = next_cursor(&first).unwrap();
+    let lines_in_first = texts(&first).0.lines().count();
+
+    let mut numbered = chunk(&path, Some(&cursor));
+    numbered.with_line_numbers = true;
+    let second = numbered.run_tool(&fs_service).await.unwrap();
+    let first_line = texts(&second).0.lines().next().unwrap();
+    let expected = format!("{:08}", lines_in_first + 1);
+    assert!(first_line.trim_start().starts_with(&format!("{}\t{}", lines_in_first + 1, expected)), "{}", first_line);
+
+    assert!(chunk(&path, Some("not a cursor")).run_tool(&fs_service).await.is_err());
+
+    // A cursor into a file that changed since is refused
+    std::thread::sleep(std::time::Duration::from_millis(20));
+    std::fs::write(&path, format!("new first line\n{}", content)).unwrap();
+    let stale = chunk(&path, Some(&cursor)).run_tool(&fs_service).await.unwrap_err();
+    assert!(stale.to_string().contains("has changed since the cursor was issued"), "{}", stale);
+}
@@ -0,0 +1,83 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::{FileSystemService, ReadStatus, READ_MULTIPLE_MAX_TOTAL_BYTES};
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_read_multiple_files_preserves_order_and_reports_failures() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let mut paths = Vec::new();
+    for i in 0..20 {
+        let path = temp_dir.path().join(format!("file{i}.txt"));
+        fs::write(&path, format!("content {i}"))?;
+        paths.push(path.display().to_string());
+    }
+    let missing = temp_dir.path().join("missing.txt").display().to_string();
+    paths.insert(3, missing.clone());
+
+    let results = fs_service.read_multiple_files(&paths, READ_MULTIPLE_MAX_TOTAL_BYTES).await;
+
+    assert_eq!(results.len(), 21);
+    for (result, path) in results.iter().zip(&paths) {
+        assert_eq!(&result.path, path);
+    }
+    assert_eq!(results[3].status, ReadStatus::Error);
+    assert!(results[3].error.as_deref().unwrap().contains("not found"));
+    assert_eq!(results[0].status, ReadStatus::Ok);
+    assert_eq!(results[0].content.as_deref(), Some("content 0"));
+    assert_eq!(results[0].bytes, Some(9));
+    assert_eq!(results[20].content.as_deref(), Some("content 19"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_multiple_files_blocked_path_is_per_file_error() -> ServiceResult<()> {
+    let allowed_dir = TempDir::new()?;
+    let other_dir = TempDir::new()?;
+    let allowed = vec![allowed_dir.path().display().to_string()];
+    let fs_service = FileSystemService::try_new(&allowed, &[])?;
+
+    let inside = allowed_dir.path().join("inside.txt");
+    let outside = other_dir.path().join("outside.txt");
+    fs::write(&inside, "ok")?;
+    fs::write(&outside, "secret")?;
+
+    let results = fs_service
+        .read_multiple_files(&[inside.display().to_string(), outside.display().to_string()], READ_MULTIPLE_MAX_TOTAL_BYTES)
+        .await;
+
+    assert_eq!(results[0].status, ReadStatus::Ok);
+    assert_eq!(results[1].status, ReadStatus::Error);
+    assert!(results[1].content.is_none());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_multiple_files_skips_files_past_size_budget() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let paths: Vec<String> = ["a.txt", "b.txt", "c.txt"]
+        .iter()
+        .map(|name| {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, "0123456789").unwrap();
+            path.display().to_string()
+        })
+        .collect();
+
+    // Room for two 10-byte files; the third is skipped rather than read
+    let results = fs_service.read_multiple_files(&paths, 25).await;
+
+    assert_eq!(results[0].status, ReadStatus::Ok);
+    assert_eq!(results[1].status, ReadStatus::Ok);
+    assert_eq!(results[2].status, ReadStatus::Skipped);
+    assert!(results[2].content.is_none());
+    assert!(results[2].error.as_deref().unwrap().contains("budget"));
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::call;
+
+fn setup() -> (TempDir, MyServerHandler) {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.txt"), "original").unwrap();
+    let args = CommandArguments::parse_from(["server", "--readonly", "--tool-style", "both", dir.path().to_str().unwrap()]);
+    (dir, MyServerHandler::new(&args).unwrap())
+}
+
+#[tokio::test]
+async fn test_readonly_refuses_changes() {
+    let (dir, handler) = setup();
+    let path = |name: &str| dir.path().join(name).display().to_string();
+    assert!(handler.is_readonly());
+    assert!(handler.startup_message().contains("\"read-only\" mode"));
+
+    let refused = [
+        call("write_file", json!({ "path": path("a.txt"), "content": "changed" })),
+        call("edit_file", json!({ "path": path("a.txt"), "edits": [{ "oldText": "original", "newText": "changed" }] })),
+        call("delete_file", json!({ "path": path("a.txt") })),
+        call("move_files", json!({ "paths": [path("a.txt")], "destination": path("b.txt") })),
+        call("copy_files", json!({ "paths": [path("a.txt")], "destination": path("b.txt") })),
+        call("zip_files", json!({ "paths": [path("a.txt")], "output_path": path("a.zip") })),
+        call("unzip_file", json!({ "paths": [path("a.zip")], "output_path": path("out") })),
+        call("create_directory", json!({ "path": path("sub") })),
+        call("find_empty_files", json!({ "path": path(""), "cleanup": true })),
+        call("file_management", json!({ "operation": "delete_file", "path": path("a.txt") })),
+    ];
+    for request in refused {
+        let name = request.params.name.clone();
+        let error = handler.handle_call_tool(request).await.expect_err(&name);
+        assert!(error.message.contains("read-only mode"), "{}: {}", name, error.message);
+    }
+    assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "original");
+    assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+}
+
+#[tokio::test]
+async fn test_readonly_allows_reads_and_previews() {
+    let (dir, handler) = setup();
+    let file = dir.path().join("a.txt").display().to_string();
+
+    let allowed = [
+        call("read_file", json!({ "path": file })),
+        call("list_directory", json!({ "path": dir.path().display().to_string() })),
+        call("get_file_info", json!({ "path": file })),
+        call("edit_file", json!({ "path": file, "edits": [{ "oldText": "original", "newText": "changed" }], "dry_run": true })),
+        call("find_empty_files", json!({ "path": dir.path().display().to_string() })),
+    ];
+    for request in allowed {
+        let name = request.params.name.clone();
+        let result = handler.handle_call_tool(request).await.unwrap_or_else(|e| panic!("{}: {}", name, e));
+        assert_ne!(result.is_error, Some(true), "{}", name);
+    }
+    assert_eq!(fs::read_to_string(dir.path().join("a.txt")).unwrap(), "original");
+}
@@ -0,0 +1,137 @@
+use aichemistforge_mcp_server::fs_service::remote::{
+    RemoteEntry, RemoteStorage, RemoteStorageConfig, StorageBackend,
+};
+use aichemistforge_mcp_server::mcp_types::Content;
+use aichemistforge_mcp_server::tools::ListDirectoryTool;
+use aichemistforge_mcp_server::FileSystemService;
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+/// Objects keyed by path, listed like an S3 bucket
+#[derive(Default)]
+struct Bucket(Mutex<BTreeMap<String, Vec<u8>>>);
+
+#[async_trait]
+impl StorageBackend for Bucket {
+    async fn read(&self, path: &str) -> io::Result<Vec<u8>> {
+        self.0.lock().unwrap().get(path).cloned().ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    async fn write(&self, path: &str, data: Vec<u8>) -> io::Result<()> {
+        self.0.lock().unwrap().insert(path.to_string(), data);
+        Ok(())
+    }
+
+    async fn list(&self, path: &str) -> io::Result<Vec<RemoteEntry>> {
+        let prefix = if path.is_empty() { String::new() } else { format!("{}/", path) };
+        let mut entries: Vec<RemoteEntry> = Vec::new();
+        for (key, data) in self.0.lock().unwrap().iter() {
+            let Some(rest) = key.strip_prefix(&prefix) else { continue };
+            let entry = match rest.split_once('/') {
+                Some((dir, _)) => RemoteEntry { name: dir.to_string(), is_dir: true, size: None },
+                None => RemoteEntry { name: rest.to_string(), is_dir: false, size: Some(data.len() as u64) },
+            };
+            if !entries.contains(&entry) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    async fn delete(&self, path: &str) -> io::Result<()> {
+        self.0.lock().unwrap().remove(path).map(|_| ()).ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+}
+
+fn service_with_bucket(allowed: &Path) -> (FileSystemService, Arc<Bucket>) {
+    let bucket = Arc::new(Bucket::default());
+    let mut remote = RemoteStorage::default();
+    remote.mount("s3", "artifacts", Some("builds"), false, bucket.clone()).unwrap();
+    remote.mount("s3", "releases", None, true, bucket.clone()).unwrap();
+    let mut fs_service = FileSystemService::try_new(&[allowed.display().to_string()], &[]).unwrap();
+    fs_service.set_remote_storage(remote);
+    (fs_service, bucket)
+}
+
+#[test]
+fn test_remote_path_resolution() {
+    let (fs_service, _) = service_with_bucket(Path::new("/srv"));
+    let remote = fs_service.remote_storage();
+    assert!(RemoteStorage::is_remote(Path::new("s3://artifacts/builds/a")));
+    assert!(!RemoteStorage::is_remote(Path::new("/srv/s3://x")));
+    assert!(!RemoteStorage::is_remote(Path::new("https://example.com/a")));
+
+    let resolved = remote.resolve(Path::new("s3://artifacts/builds//v1/./app.zip"), true).unwrap().unwrap();
+    assert_eq!(resolved.key, "builds/v1/app.zip");
+    assert!(remote.resolve(Path::new("/srv/local.txt"), false).is_none());
+
+    let error = |path: &str, write: bool| remote.resolve(Path::new(path), write).unwrap().err().unwrap();
+    assert_eq!(error("s3://artifacts/other/a", false).kind(), io::ErrorKind::PermissionDenied);
+    assert_eq!(error("s3://artifacts/builds/../other", false).kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(error("s3://unknown/a", false).kind(), io::ErrorKind::NotFound);
+    assert!(error("s3://releases/a", true).to_string().contains("read-only"));
+    assert!(remote.resolve(Path::new("s3://releases/a"), false).unwrap().is_ok());
+
+    assert_eq!(remote.describe(), vec!["s3://artifacts/builds", "s3://releases (read-only)"]);
+}
+
+#[tokio::test]
+async fn test_remote_read_write_copy_delete() {
+    let temp_dir = TempDir::new().unwrap();
+    let (fs_service, bucket) = service_with_bucket(temp_dir.path());
+    let local = temp_dir.path().join("report.txt");
+    std::fs::write(&local, "build ok").unwrap();
+
+    // Local to remote and back
+    fs_service.copy_file(&local, Path::new("s3://artifacts/builds/v1/report.txt")).await.unwrap();
+    assert_eq!(bucket.0.lock().unwrap()["builds/v1/report.txt"], b"build ok");
+    let fetched = temp_dir.path().join("fetched.txt");
+    fs_service.copy_file(Path::new("s3://artifacts/builds/v1/report.txt"), &fetched).await.unwrap();
+    assert_eq!(std::fs::read_to_string(&fetched).unwrap(), "build ok");
+
+    fs_service
+        .write_file(Path::new("s3://artifacts/builds/notes.md"), &"# Notes".to_string())
+        .await
+        .unwrap();
+    assert_eq!(fs_service.read_file(Path::new("s3://artifacts/builds/notes.md")).await.unwrap(), "# Notes");
+
+    let tool = ListDirectoryTool { path: "s3://artifacts/builds".to_string(), detailed: Some(true) };
+    let result = tool.run_tool(&fs_service).await.unwrap();
+    let Content::Text(listing) = &result.content[0] else { panic!("expected text") };
+    assert_eq!(listing.text, "FILE (7 B) notes.md\nDIR  v1");
+
+    fs_service.delete_file(Path::new("s3://artifacts/builds/notes.md")).await.unwrap();
+    assert!(fs_service.read_file(Path::new("s3://artifacts/builds/notes.md")).await.is_err());
+
+    // Read-only stores refuse writes, and local validation still applies to the other end
+    assert!(fs_service.copy_file(&local, Path::new("s3://releases/report.txt")).await.is_err());
+    let outside = TempDir::new().unwrap();
+    assert!(fs_service
+        .copy_file(Path::new("s3://artifacts/builds/v1/report.txt"), &outside.path().join("x"))
+        .await
+        .is_err());
+}
+
+#[test]
+fn test_remote_config_parsing() {
+    let config: RemoteStorageConfig = serde_json::from_str(
+        r#"{"backends": [
+            {"type": "s3", "bucket": "artifacts", "region": "eu-west-1", "read_only": true},
+            {"type": "sftp", "host": "build.example.com", "port": 2222, "username": "ci", "root": "/srv/drop"}
+        ]}"#,
+    )
+    .unwrap();
+    assert_eq!(config.backends.len(), 2);
+
+    let empty = RemoteStorage::from_config(RemoteStorageConfig { backends: Vec::new() }).unwrap();
+    assert!(empty.is_empty());
+    #[cfg(not(feature = "s3"))]
+    {
+        let error = RemoteStorage::from_config(config).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,118 @@
+use aichemistforge_mcp_server::fs_service::rename::{RenameRule, RenameStatus};
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest};
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_rename_files_by_regex_previews_then_renames() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("IMG_0001.jpeg"), "1").unwrap();
+    fs::write(dir.join("IMG_0002.jpeg"), "2").unwrap();
+    fs::write(dir.join("notes.txt"), "n").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    let rule = RenameRule::new(Some(r"IMG_(\d+)\.jpeg"), "photo_${1}.jpg").unwrap();
+    let paths = vec![format!("{}/*", dir.display())];
+
+    let plan = fs_service.rename_files(&paths, &rule, true).await.unwrap();
+    assert!(!plan.renamed);
+    assert_eq!(plan.renames().count(), 2);
+    assert_eq!(plan.entries.iter().filter(|e| e.status == RenameStatus::Unchanged).count(), 1);
+    assert!(dir.join("IMG_0001.jpeg").exists());
+
+    let plan = fs_service.rename_files(&paths, &rule, false).await.unwrap();
+    assert!(plan.renamed);
+    assert_eq!(fs::read_to_string(dir.join("photo_0001.jpg")).unwrap(), "1");
+    assert_eq!(fs::read_to_string(dir.join("photo_0002.jpg")).unwrap(), "2");
+    assert!(dir.join("notes.txt").exists());
+
+    // Each rename is its own undo step
+    fs_service.undo_last_operation().await.unwrap();
+    assert!(dir.join("IMG_0002.jpeg").exists());
+}
+
+#[tokio::test]
+async fn test_rename_files_template_and_swap() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("a.txt"), "a").unwrap();
+    fs::write(dir.join("b.txt"), "b").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let rule = RenameRule::new(None, "part_{n:3}.{ext}").unwrap();
+    let paths = vec![format!("{}/*.txt", dir.display())];
+    fs_service.rename_files(&paths, &rule, false).await.unwrap();
+    assert_eq!(fs::read_to_string(dir.join("part_001.txt")).unwrap(), "a");
+    assert_eq!(fs::read_to_string(dir.join("part_002.txt")).unwrap(), "b");
+
+    // Listed in reverse, {n} swaps the two names, through temporary names
+    let swap = RenameRule::new(None, "part_{n:3}.txt").unwrap();
+    let paths = ["part_002.txt", "part_001.txt"].map(|name| dir.join(name).display().to_string());
+    let plan = fs_service.rename_files(&paths, &swap, false).await.unwrap();
+    assert_eq!(plan.problems(), 0);
+    assert_eq!(fs::read_to_string(dir.join("part_001.txt")).unwrap(), "b");
+    assert_eq!(fs::read_to_string(dir.join("part_002.txt")).unwrap(), "a");
+
+    assert!(RenameRule::new(None, "{size}").is_err());
+}
+
+#[tokio::test]
+async fn test_rename_files_detects_collisions() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("a-1.log"), "a").unwrap();
+    fs::write(dir.join("b-1.log"), "b").unwrap();
+    fs::write(dir.join("c.txt"), "c").unwrap();
+    fs::write(dir.join("c.log"), "existing").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    // Two files would both become 1.log
+    let rule = RenameRule::new(Some(r"^[ab]-"), "").unwrap();
+    let plan = fs_service
+        .rename_files(&[format!("{}/*-1.log", dir.display())], &rule, false)
+        .await
+        .unwrap();
+    assert!(!plan.renamed);
+    assert_eq!(plan.problems(), 1);
+    assert!(dir.join("a-1.log").exists() && dir.join("b-1.log").exists());
+
+    // c.log already exists and is not being renamed
+    let rule = RenameRule::new(Some(r"\.txt$"), ".log").unwrap();
+    let plan = fs_service.rename_files(&[dir.join("c.txt").display().to_string()], &rule, false).await.unwrap();
+    assert!(matches!(plan.entries[0].status, RenameStatus::Collision { .. }));
+    assert_eq!(fs::read_to_string(dir.join("c.log")).unwrap(), "existing");
+
+    // A separator in the new name is refused
+    let rule = RenameRule::new(Some(r"^c"), "sub/c").unwrap();
+    let plan = fs_service.rename_files(&[dir.join("c.txt").display().to_string()], &rule, false).await.unwrap();
+    assert!(matches!(plan.entries[0].status, RenameStatus::Invalid { .. }));
+}
+
+#[tokio::test]
+async fn test_rename_files_tool() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::write(dir.join("one.md"), "1").unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", dir.to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let call = |dry_run: bool| CallToolRequest {
+        params: CallToolParams {
+            name: "rename_files".to_string(),
+            arguments: Some(json!({
+                "paths": [dir.join("one.md").display().to_string()],
+                "replacement": "{stem}.markdown",
+                "dry_run": dry_run,
+            })),
+        },
+    };
+
+    let preview = handler.handle_call_tool(call(true)).await.unwrap();
+    assert_eq!(preview.structured_content.unwrap()["entries"][0]["status"], "rename");
+    assert!(dir.join("one.md").exists());
+    let result = handler.handle_call_tool(call(false)).await.unwrap();
+    assert_eq!(result.structured_content.unwrap()["renamed"], true);
+    assert!(dir.join("one.markdown").exists());
+}
@@ -0,0 +1,62 @@
+use aichemistforge_mcp_server::fs_service::resources::MAX_INLINE_READ_BYTES;
+use aichemistforge_mcp_server::fs_service::roots::file_uri;
+use aichemistforge_mcp_server::mcp_types::Content;
+use aichemistforge_mcp_server::tools::{ReadFileTool, ReadMediaFile};
+use aichemistforge_mcp_server::FileSystemService;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_read_file_links_oversized_files_beside_the_first_chunk() {
+    let temp_dir = TempDir::new().unwrap();
+    let big = temp_dir.path().join("big.log");
+    std::fs::write(&big, "x".repeat(MAX_INLINE_READ_BYTES as usize + 1)).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let tool = ReadFileTool { path: big.display().to_string(), with_line_numbers: false, offset_bytes: None, length_bytes: None, cursor: None, encoding: None };
+    let result = tool.run_tool(&fs_service).await.unwrap();
+    assert_eq!(result.content.len(), 3);
+    let Content::Text(note) = &result.content[1] else { panic!("expected text") };
+    assert!(note.text.contains("resources/read"));
+    let Content::ResourceLink(link) = &result.content[2] else { panic!("expected resource link") };
+    assert_eq!(link.uri, file_uri(&big));
+    assert_eq!(link.name, "big.log");
+    assert_eq!(link.size, Some(MAX_INLINE_READ_BYTES + 1));
+    assert_eq!(link.mime_type.as_deref(), Some("text/plain"));
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert_eq!(json["content"][2]["type"], "resource_link");
+    assert_eq!(json["content"][2]["mimeType"], "text/plain");
+}
+
+#[tokio::test]
+async fn test_read_file_small_files_inline() {
+    let temp_dir = TempDir::new().unwrap();
+    let small = temp_dir.path().join("small.txt");
+    std::fs::write(&small, "hello").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let tool = ReadFileTool { path: small.display().to_string(), with_line_numbers: false, offset_bytes: None, length_bytes: None, cursor: None, encoding: None };
+    let result = tool.run_tool(&fs_service).await.unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    assert_eq!(text.text, "hello");
+}
+
+#[tokio::test]
+async fn test_read_media_file_respects_max_bytes() {
+    let temp_dir = TempDir::new().unwrap();
+    let image = temp_dir.path().join("pixel.png");
+    // PNG signature followed by padding
+    let mut data = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    data.resize(64, 0);
+    std::fs::write(&image, &data).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let tool = ReadMediaFile { path: image.display().to_string(), max_bytes: Some(32) };
+    let result = tool.run_tool(&fs_service).await.unwrap();
+    let Content::ResourceLink(link) = &result.content[1] else { panic!("expected resource link") };
+    assert_eq!(link.mime_type.as_deref(), Some("image/png"));
+
+    let tool = ReadMediaFile { path: image.display().to_string(), max_bytes: None };
+    let result = tool.run_tool(&fs_service).await.unwrap();
+    assert!(matches!(result.content[0], Content::ImageContent(_)));
+}
@@ -0,0 +1,86 @@
+use aichemistforge_mcp_server::fs_service::roots::file_uri;
+use aichemistforge_mcp_server::server::{tcp, McpServer};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+type Lines = tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>;
+
+async fn send(writer: &mut tokio::net::tcp::OwnedWriteHalf, message: Value) {
+    writer.write_all(format!("{}\n", message).as_bytes()).await.unwrap();
+}
+
+async fn next_message(lines: &mut Lines) -> Value {
+    let line = tokio::time::timeout(Duration::from_secs(10), lines.next_line()).await.unwrap().unwrap().unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+async fn connect(dir: &TempDir) -> (Lines, tokio::net::tcp::OwnedWriteHalf) {
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", dir.path().to_str().unwrap()]);
+    let server = McpServer::new(MyServerHandler::new(&args).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(tcp::serve(std::sync::Arc::new(server), listener));
+
+    let (input, mut output) = TcpStream::connect(address).await.unwrap().into_split();
+    let mut lines = BufReader::new(input).lines();
+    send(
+        &mut output,
+        json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+            "protocolVersion": "2024-11-05", "capabilities": {}, "clientInfo": { "name": "test", "version": "1" }
+        }}),
+    )
+    .await;
+    let response = next_message(&mut lines).await;
+    assert_eq!(response["result"]["capabilities"]["resources"]["subscribe"], true, "{}", response);
+    (lines, output)
+}
+
+#[tokio::test]
+async fn test_subscribed_file_change_is_notified() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().canonicalize().unwrap().join("watched.txt");
+    std::fs::write(&path, "before").unwrap();
+    let uri = file_uri(&path);
+    let (mut lines, mut output) = connect(&dir).await;
+
+    send(&mut output, json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/subscribe", "params": { "uri": uri } })).await;
+    let response = next_message(&mut lines).await;
+    assert_eq!(response["result"], json!({}), "{}", response);
+
+    std::fs::write(&path, "after").unwrap();
+    let notification = next_message(&mut lines).await;
+    assert_eq!(notification["method"], "notifications/resources/updated");
+    assert_eq!(notification["params"]["uri"], uri.as_str());
+
+    send(&mut output, json!({ "jsonrpc": "2.0", "id": 3, "method": "resources/unsubscribe", "params": { "uri": uri } })).await;
+    // Events from the write above may still be arriving
+    loop {
+        let message = next_message(&mut lines).await;
+        if message["id"] == 3 {
+            assert_eq!(message["result"], json!({}), "{}", message);
+            break;
+        }
+        assert_eq!(message["method"], "notifications/resources/updated");
+    }
+}
+
+#[tokio::test]
+async fn test_subscribe_to_missing_file_fails() {
+    let dir = TempDir::new().unwrap();
+    let uri = file_uri(&dir.path().canonicalize().unwrap().join("missing.txt"));
+    let (mut lines, mut output) = connect(&dir).await;
+
+    send(&mut output, json!({ "jsonrpc": "2.0", "id": 2, "method": "resources/subscribe", "params": { "uri": uri } })).await;
+    let response = next_message(&mut lines).await;
+    assert_eq!(response["error"]["code"], -32002, "{}", response);
+
+    // Unknown subscriptions are quietly ignored
+    send(&mut output, json!({ "jsonrpc": "2.0", "id": 3, "method": "resources/unsubscribe", "params": { "uri": uri } })).await;
+    let response = next_message(&mut lines).await;
+    assert_eq!(response["result"], json!({}), "{}", response);
+}
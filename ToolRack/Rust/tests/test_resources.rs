@@ -0,0 +1,62 @@
+use aichemistforge_mcp_server::fs_service::resources::ResourceBody;
+use aichemistforge_mcp_server::fs_service::roots::{file_uri, path_from_file_uri};
+use aichemistforge_mcp_server::mcp_types::{ReadResourceParams, INVALID_PARAMS, RESOURCE_NOT_FOUND};
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+#[test]
+fn test_path_from_file_uri() {
+    assert_eq!(path_from_file_uri("file:///home/me/My%20Project"), Some(PathBuf::from("/home/me/My Project")));
+    assert_eq!(path_from_file_uri("file://localhost/tmp/a"), Some(PathBuf::from("/tmp/a")));
+    assert_eq!(path_from_file_uri("file:///C:/Users/me"), Some(PathBuf::from("C:/Users/me")));
+    assert_eq!(path_from_file_uri("file://server/share/a"), None);
+    assert_eq!(path_from_file_uri("https://example.com/a"), None);
+    assert_eq!(path_from_file_uri("file:///bad%zz"), None);
+
+    let path = Path::new("/tmp/odd name #1/ü.txt");
+    assert_eq!(path_from_file_uri(&file_uri(path)).as_deref(), Some(path));
+}
+
+#[tokio::test]
+async fn test_read_resource_text_and_binary() {
+    let temp_dir = TempDir::new().unwrap();
+    let text = temp_dir.path().join("notes.md");
+    let binary = temp_dir.path().join("data.bin");
+    std::fs::write(&text, "# Notes\n").unwrap();
+    std::fs::write(&binary, [0u8, 159, 146, 150]).unwrap();
+    let fs_service = FileSystemService::try_new(&[temp_dir.path().display().to_string()], &[]).unwrap();
+
+    let resource = fs_service.read_resource(&file_uri(&text)).await.unwrap();
+    assert_eq!(resource.mime_type, "text/markdown");
+    assert_eq!(resource.body, ResourceBody::Text("# Notes\n".to_string()));
+
+    let resource = fs_service.read_resource(&file_uri(&binary)).await.unwrap();
+    assert_eq!(resource.body, ResourceBody::Blob("AJ+Slg==".to_string()));
+
+    assert!(fs_service.read_resource(&file_uri(temp_dir.path())).await.is_err());
+}
+
+#[tokio::test]
+async fn test_read_resource_is_validated() {
+    let allowed = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    let secret = outside.path().join("secret.txt");
+    std::fs::write(&secret, "secret").unwrap();
+    let args = CommandArguments::parse_from(["server", allowed.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+
+    let read = |uri: String| handler.handle_read_resource(ReadResourceParams { uri });
+    assert_eq!(read(file_uri(&secret)).await.unwrap_err().code, INVALID_PARAMS);
+    assert_eq!(read(file_uri(&allowed.path().join("missing"))).await.unwrap_err().code, RESOURCE_NOT_FOUND);
+    assert_eq!(read("http://example.com/x".to_string()).await.unwrap_err().code, INVALID_PARAMS);
+
+    std::fs::write(allowed.path().join("a.txt"), "hello").unwrap();
+    let result = read(file_uri(&allowed.path().join("a.txt"))).await.unwrap();
+    assert_eq!(result.contents[0].text.as_deref(), Some("hello"));
+    assert_eq!(result.contents[0].mime_type.as_deref(), Some("text/plain"));
+
+    let templates = handler.handle_list_resource_templates().await.unwrap();
+    assert_eq!(templates.resource_templates[0].uri_template, "file:///{path}");
+}
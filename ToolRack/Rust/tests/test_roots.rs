@@ -0,0 +1,49 @@
+use aichemistforge_mcp_server::fs_service::roots::file_uri;
+use aichemistforge_mcp_server::mcp_types::{ClientInfo, InitializeParams, InitializeRequest};
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[test]
+fn test_file_uri_encoding() {
+    assert_eq!(file_uri(Path::new("/home/me/My Project")), "file:///home/me/My%20Project");
+    assert_eq!(file_uri(Path::new("/tmp/a#b")), "file:///tmp/a%23b");
+    assert_eq!(file_uri(Path::new("C:\\Users\\me")), "file:///C:/Users/me");
+    assert_eq!(file_uri(Path::new("\\\\server\\share\\docs")), "file://server/share/docs");
+}
+
+#[test]
+fn test_roots_report() {
+    let fs_service = FileSystemService::try_new(&["/srv/data".to_string()], &["/srv/data/secret".to_string()]).unwrap();
+    let report = fs_service.roots();
+    assert!(!report.unrestricted);
+    assert_eq!(report.roots.len(), 1);
+    assert_eq!(report.roots[0].uri, "file:///srv/data");
+    assert_eq!(report.roots[0].name, "data");
+    assert_eq!(report.blocked[0].path, "/srv/data/secret");
+    assert!(report.describe().contains("limited to these directories: /srv/data."));
+
+    let open = FileSystemService::try_new(&[], &[]).unwrap().roots();
+    assert!(open.unrestricted);
+    assert!(open.roots.is_empty());
+}
+
+#[tokio::test]
+async fn test_initialize_advertises_roots() {
+    let args = CommandArguments::parse_from(["server", "/srv/data"]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let request = InitializeRequest {
+        params: InitializeParams {
+            protocol_version: "2024-11-05".to_string(),
+            capabilities: HashMap::new(),
+            client_info: ClientInfo { name: "test".to_string(), version: "0".to_string() },
+        },
+    };
+    let result = handler.handle_initialize(request).await.unwrap();
+
+    let roots = &result.capabilities["experimental"]["roots"];
+    assert_eq!(roots["unrestricted"], false);
+    assert_eq!(roots["roots"][0]["uri"], "file:///srv/data");
+    assert!(result.instructions.unwrap().contains("/srv/data"));
+}
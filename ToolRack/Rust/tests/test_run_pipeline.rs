@@ -0,0 +1,88 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::pipeline::Pipeline;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+fn setup_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("src").join("nested")).unwrap();
+    fs::write(root.join("src").join("main.rs"), "fn main() {}").unwrap();
+    fs::write(root.join("src").join("nested").join("lib.rs"), "pub fn lib() {}").unwrap();
+    fs::write(root.join("src").join("nested").join("empty_test.rs"), "").unwrap();
+    fs::write(root.join("README.md"), "readme").unwrap();
+    temp_dir
+}
+
+fn pipeline(root: &std::path::Path) -> Pipeline {
+    serde_json::from_value(json!({
+        "variables": { "root": root.display().to_string() },
+        "stages": [
+            { "stage": "search", "path": "${root}/src", "pattern": "*.rs", "output": "sources" },
+            { "stage": "filter", "exclude": ["*_test.rs"], "min_bytes": 1, "output": "kept" },
+            { "stage": "copy", "destination": "${root}/out/${kept.count}-files", "output": "copied" },
+            { "stage": "zip", "input": "copied", "output_path": "${root}/bundle.zip" }
+        ]
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_pipeline_search_filter_copy_zip() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let root = temp_dir.path();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let report = fs_service.run_pipeline(&pipeline(root), false).await;
+
+    assert_eq!(report.error, None);
+    let counts: Vec<usize> = report.stages.iter().map(|s| s.files.len()).collect();
+    assert_eq!(counts, vec![3, 2, 2, 1]);
+    assert!(root.join("out").join("2-files").join("lib.rs").exists());
+    assert!(root.join("out").join("2-files").join("main.rs").exists());
+
+    let archive = zip::ZipArchive::new(fs::File::open(root.join("bundle.zip"))?).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(names, vec!["lib.rs", "main.rs"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pipeline_dry_run_writes_nothing() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let root = temp_dir.path();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let report = fs_service.run_pipeline(&pipeline(root), true).await;
+
+    assert_eq!(report.error, None);
+    assert_eq!(report.stages.len(), 4);
+    assert!(!root.join("out").exists());
+    assert!(!root.join("bundle.zip").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pipeline_reports_unknown_variable() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let pipeline: Pipeline = serde_json::from_value(json!({
+        "stages": [
+            { "stage": "search", "path": temp_dir.path().display().to_string(), "pattern": "*.md" },
+            { "stage": "copy", "destination": "${missing}/out" }
+        ]
+    }))
+    .unwrap();
+
+    let report = fs_service.run_pipeline(&pipeline, false).await;
+
+    assert_eq!(report.failed_stage, Some(1));
+    assert!(report.error.unwrap().contains("missing"));
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use aichemistforge_mcp_server::fs_service::batch::BatchOperation;
+use aichemistforge_mcp_server::fs_service::sandbox::{ChangeKind, Sandbox};
+use aichemistforge_mcp_server::tools::{EditOperation, ListDirectoryTool};
+use aichemistforge_mcp_server::mcp_types::Content;
+use aichemistforge_mcp_server::fs_service::LineOrigin;
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn sandboxed_service(root: &Path, overlay: &TempDir) -> FileSystemService {
+    let mut service = FileSystemService::try_new(&[root.display().to_string()], &[]).unwrap();
+    service.set_sandbox(Sandbox::create_in(&overlay.path().join("overlay")).unwrap());
+    service
+}
+
+#[tokio::test]
+async fn test_changes_stay_in_sandbox_until_committed() {
+    let temp_dir = TempDir::new().unwrap();
+    let overlay = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("main.rs"), "fn main() {\n    old();\n}\n").unwrap();
+    fs::write(root.join("obsolete.txt"), "bye").unwrap();
+    fs::create_dir(root.join("docs")).unwrap();
+    fs::write(root.join("docs/guide.md"), "# Guide").unwrap();
+    let service = sandboxed_service(root, &overlay);
+
+    service.write_file(&root.join("new.txt"), &"hello".to_string()).await.unwrap();
+    let edit = EditOperation { old_text: "old();".to_string(), new_text: "new();".to_string(), ..Default::default() };
+    service.apply_file_edits(&root.join("main.rs"), vec![edit], None, None).await.unwrap();
+    service.delete_file(&root.join("obsolete.txt")).await.unwrap();
+    service.create_directory(&root.join("src/bin")).await.unwrap();
+    service.move_file(&root.join("docs"), &root.join("src/docs")).await.unwrap();
+
+    // The disk is untouched, but reads see the sandbox
+    assert_eq!(fs::read_to_string(root.join("main.rs")).unwrap(), "fn main() {\n    old();\n}\n");
+    assert!(root.join("obsolete.txt").exists() && root.join("docs/guide.md").exists());
+    assert!(!root.join("new.txt").exists() && !root.join("src").exists());
+    assert_eq!(service.read_file(&root.join("new.txt")).await.unwrap(), "hello");
+    assert_eq!(service.read_file(&root.join("src/docs/guide.md")).await.unwrap(), "# Guide");
+    assert!(service.read_file(&root.join("obsolete.txt")).await.is_err());
+    assert_eq!(service.get_file_stats(&root.join("new.txt"), true).await.unwrap().size, 5);
+
+    let listing = ListDirectoryTool { path: root.display().to_string(), detailed: None }.run_tool(&service).await.unwrap();
+    let Content::Text(listing) = &listing.content[0] else { panic!("expected text") };
+    assert_eq!(listing.text, "main.rs\nnew.txt\nsrc");
+
+    let changes = service.diff_sandbox().await.unwrap();
+    let summary: Vec<(String, ChangeKind)> = changes
+        .iter()
+        .map(|c| (Path::new(&c.path).strip_prefix(root).unwrap().display().to_string(), c.kind))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            ("docs".to_string(), ChangeKind::Deleted),
+            ("main.rs".to_string(), ChangeKind::Modified),
+            ("new.txt".to_string(), ChangeKind::Added),
+            ("obsolete.txt".to_string(), ChangeKind::Deleted),
+            ("src".to_string(), ChangeKind::AddedDirectory),
+            ("src/bin".to_string(), ChangeKind::AddedDirectory),
+            ("src/docs".to_string(), ChangeKind::AddedDirectory),
+            ("src/docs/guide.md".to_string(), ChangeKind::Added),
+        ]
+    );
+    let diff = changes[1].diff.as_deref().unwrap();
+    assert!(diff.contains("-    old();") && diff.contains("+    new();"));
+
+    let report = service.commit_sandbox(&[]).await.unwrap();
+    assert_eq!(report.committed.len(), 8);
+    assert!(report.failed.is_none());
+    assert_eq!(fs::read_to_string(root.join("main.rs")).unwrap(), "fn main() {\n    new();\n}\n");
+    assert_eq!(fs::read_to_string(root.join("new.txt")).unwrap(), "hello");
+    assert_eq!(fs::read_to_string(root.join("src/docs/guide.md")).unwrap(), "# Guide");
+    assert!(root.join("src/bin").is_dir());
+    assert!(!root.join("obsolete.txt").exists() && !root.join("docs").exists());
+    assert!(service.diff_sandbox().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_commit_selected_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let overlay = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let service = sandboxed_service(root, &overlay);
+
+    service.create_directory(&root.join("a/b")).await.unwrap();
+    service.write_file(&root.join("a/b/keep.txt"), &"keep".to_string()).await.unwrap();
+    service.write_file(&root.join("later.txt"), &"later".to_string()).await.unwrap();
+    // Rewriting a file with its current contents is not a change
+    service.copy_file(&root.join("later.txt"), &root.join("copy.txt")).await.unwrap();
+
+    let report = service.commit_sandbox(&[root.join("a/b/keep.txt").display().to_string()]).await.unwrap();
+    assert_eq!(report.committed.len(), 3, "the new parent directories are committed too");
+    assert_eq!(report.remaining, 2);
+    assert_eq!(fs::read_to_string(root.join("a/b/keep.txt")).unwrap(), "keep");
+    assert!(!root.join("later.txt").exists());
+
+    service.write_file(&root.join("a/b/keep.txt"), &"keep".to_string()).await.unwrap();
+    let pending: Vec<String> = service.diff_sandbox().await.unwrap().into_iter().map(|c| c.path).collect();
+    assert_eq!(pending, vec![root.join("copy.txt").display().to_string(), root.join("later.txt").display().to_string()]);
+}
+
+#[tokio::test]
+async fn test_unsandboxed_writes_are_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let overlay = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    let service = sandboxed_service(root, &overlay);
+
+    let operations = vec![BatchOperation::Mkdir { path: root.join("x").display().to_string() }];
+    let error = service.batch_operations(&operations).await.unwrap_err();
+    assert!(error.to_string().contains("--sandbox"), "{}", error);
+    assert!(!root.join("x").exists());
+
+    let plain = FileSystemService::try_new(&[root.display().to_string()], &[]).unwrap();
+    assert!(plain.diff_sandbox().await.is_err());
+    assert!(plain.commit_sandbox(&[]).await.is_err());
+}
+
+#[tokio::test]
+async fn test_line_reads_see_overlay_contents() {
+    let temp_dir = TempDir::new().unwrap();
+    let overlay = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::write(root.join("edited.txt"), "disk one\ndisk two\n").unwrap();
+    let service = sandboxed_service(root, &overlay);
+
+    let created = root.join("created.txt");
+    service.write_file(&created, &"one\ntwo\nthree\n".to_string()).await.unwrap();
+    let window = service.read_file_lines(&created, LineOrigin::Start, 1, Some(2)).await.unwrap();
+    assert_eq!(window.lines, ["two", "three"]);
+    assert_eq!(service.line_number_at(&created, window.start_byte).await.unwrap(), 2);
+
+    let edited = root.join("edited.txt");
+    service.write_file(&edited, &"a\nb\nc\nd\n".to_string()).await.unwrap();
+    let window = service.read_file_lines(&edited, LineOrigin::End, 0, Some(1)).await.unwrap();
+    assert_eq!(window.lines, ["d"]);
+    assert_eq!(window.file_size, 8);
+    assert_eq!(service.line_number_at(&edited, 6).await.unwrap(), 4);
+}
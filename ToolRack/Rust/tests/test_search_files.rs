@@ -0,0 +1,82 @@
+use aichemistforge_mcp_server::fs_service::{FileSystemService, SearchFilesOptions, SearchMatchMode};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+// root/
+//   main.rs
+//   README.md
+//   src/lib.rs
+//   tests/test_api.py
+//   tests/helpers/Test_Utils.PY
+fn setup_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join("tests").join("helpers")).unwrap();
+    fs::write(root.join("main.rs"), "fn main() {}").unwrap();
+    fs::write(root.join("README.md"), "Project notes mentioning Widget").unwrap();
+    fs::write(root.join("src").join("lib.rs"), "pub fn lib() {}").unwrap();
+    fs::write(root.join("tests").join("test_api.py"), "def test(): pass").unwrap();
+    fs::write(root.join("tests").join("helpers").join("Test_Utils.PY"), "").unwrap();
+    temp_dir
+}
+
+fn file_names(results: &[String]) -> Vec<String> {
+    results
+        .iter()
+        .map(|p| Path::new(p).file_name().unwrap().to_string_lossy().into_owned())
+        .collect()
+}
+
+async fn search(root: &Path, pattern: &str, match_mode: SearchMatchMode, include_content: bool) -> Vec<String> {
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    let options = SearchFilesOptions {
+        pattern: pattern.to_string(),
+        match_mode,
+        include_content,
+        ..Default::default()
+    };
+    file_names(&fs_service.search_files(root, options).await.unwrap().files)
+}
+
+#[tokio::test]
+async fn test_glob_matches_file_names_at_any_depth() {
+    let temp_dir = setup_tree();
+    let results = search(temp_dir.path(), "*.rs", SearchMatchMode::Auto, false).await;
+    assert_eq!(results, vec!["main.rs", "lib.rs"]);
+}
+
+#[tokio::test]
+async fn test_glob_with_separator_matches_relative_path_case_insensitively() {
+    let temp_dir = setup_tree();
+    let results = search(temp_dir.path(), "**/test_*.py", SearchMatchMode::Auto, false).await;
+    assert_eq!(results, vec!["Test_Utils.PY", "test_api.py"]);
+
+    let results = search(temp_dir.path(), "tests/*.py", SearchMatchMode::Glob, false).await;
+    assert_eq!(results, vec!["test_api.py"]);
+}
+
+#[tokio::test]
+async fn test_substring_mode_is_case_insensitive() {
+    let temp_dir = setup_tree();
+    let results = search(temp_dir.path(), "TEST", SearchMatchMode::Auto, false).await;
+    assert_eq!(results, vec!["Test_Utils.PY", "test_api.py"]);
+
+    // Forced substring mode treats glob characters literally
+    let results = search(temp_dir.path(), "*.rs", SearchMatchMode::Substring, false).await;
+    assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn test_include_content_matches_file_bodies() {
+    let temp_dir = setup_tree();
+    let results = search(temp_dir.path(), "widget", SearchMatchMode::Auto, true).await;
+    assert_eq!(results, vec!["README.md"]);
+}
+
+#[test]
+fn test_match_mode_parsing() {
+    assert_eq!("glob".parse::<SearchMatchMode>().unwrap(), SearchMatchMode::Glob);
+    assert!("regex".parse::<SearchMatchMode>().is_err());
+}
@@ -0,0 +1,77 @@
+use aichemistforge_mcp_server::fs_service::trash::{DeleteMode, Trash};
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest};
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_secure_delete_keeps_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    let trash = temp_dir.path().join("Trash");
+    let file = temp_dir.path().join("credentials.env");
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_trash(Some(Trash::Freedesktop(trash.clone())));
+
+    fs_service.write_file(&file, &"TOKEN=one".to_string()).await.unwrap();
+    fs_service.write_file(&file, &"TOKEN=two".to_string()).await.unwrap();
+    assert_eq!(fs_service.undo_journal().history().len(), 2);
+
+    assert!(!fs_service.delete_path(&file, DeleteMode::Secure).await.unwrap());
+    assert!(!file.exists());
+    assert!(!trash.exists());
+    // The journal's copy of TOKEN=one is gone with the steps
+    assert!(fs_service.undo_journal().history().is_empty());
+    assert!(fs_service.undo_last_operation().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_secure_delete_of_directory_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path().join("secrets");
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    fs::write(dir.join("a.key"), vec![7u8; 200_000]).unwrap();
+    fs::write(dir.join("nested/b.key"), "b").unwrap();
+    fs::write(dir.join("nested/empty"), "").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    fs_service.delete_path(&dir, DeleteMode::Secure).await.unwrap();
+    assert!(!dir.exists());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_secure_delete_refuses_hard_linked_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("a.txt");
+    let other = temp_dir.path().join("b.txt");
+    fs::write(&file, "shared").unwrap();
+    fs::hard_link(&file, &other).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let error = fs_service.delete_path(&file, DeleteMode::Secure).await.unwrap_err().to_string();
+    assert!(error.contains("hard link"), "{}", error);
+    assert_eq!(fs::read_to_string(&other).unwrap(), "shared");
+}
+
+#[tokio::test]
+async fn test_delete_file_tool_secure_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("id_rsa");
+    fs::write(&file, "private").unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", "--no-trash", temp_dir.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+
+    let result = handler
+        .handle_call_tool(CallToolRequest {
+            params: CallToolParams {
+                name: "delete_file".to_string(),
+                arguments: Some(json!({ "path": file.display().to_string(), "confirm": true, "secure": true })),
+            },
+        })
+        .await
+        .unwrap();
+    assert!(serde_json::to_string(&result.content).unwrap().contains("Overwrote and deleted"));
+    assert!(!file.exists());
+}
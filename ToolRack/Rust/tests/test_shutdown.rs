@@ -0,0 +1,81 @@
+#![cfg(unix)]
+
+use aichemistforge_mcp_server::server::{tcp, McpServer};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio_util::sync::CancellationToken;
+
+type Lines = tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>;
+
+/// A FIFO, whose reads block until something writes to it: a tool call that
+/// runs for as long as the test wants
+fn fifo(dir: &Path) -> PathBuf {
+    let path = dir.join("pipe");
+    assert!(std::process::Command::new("mkfifo").arg(&path).status().unwrap().success());
+    path
+}
+
+async fn start(dir: &TempDir, grace: Duration) -> (Lines, tokio::net::tcp::OwnedWriteHalf, CancellationToken, tokio::task::JoinHandle<()>) {
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", dir.path().to_str().unwrap()]);
+    let server = McpServer::new(MyServerHandler::new(&args).unwrap()).with_shutdown_grace(grace);
+    let shutdown = server.shutdown_token();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let serving = tokio::spawn(async move { tcp::serve(std::sync::Arc::new(server), listener).await.unwrap() });
+    let (input, output) = TcpStream::connect(address).await.unwrap().into_split();
+    (BufReader::new(input).lines(), output, shutdown, serving)
+}
+
+async fn read_fifo(output: &mut tokio::net::tcp::OwnedWriteHalf, path: &Path) {
+    let call = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/call",
+        "params": { "name": "read_file", "arguments": { "path": path.display().to_string() } } });
+    output.write_all(format!("{}\n", call).as_bytes()).await.unwrap();
+    // Let the call start before shutting down
+    tokio::time::sleep(Duration::from_millis(200)).await;
+}
+
+async fn next_line(lines: &mut Lines) -> Option<String> {
+    tokio::time::timeout(Duration::from_secs(10), lines.next_line()).await.unwrap().unwrap()
+}
+
+#[tokio::test]
+async fn test_shutdown_waits_for_running_calls() {
+    let dir = TempDir::new().unwrap();
+    let pipe = fifo(&dir.path().canonicalize().unwrap());
+    let (mut lines, mut output, shutdown, serving) = start(&dir, Duration::from_secs(10)).await;
+    read_fifo(&mut output, &pipe).await;
+
+    shutdown.cancel();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(!serving.is_finished());
+
+    // The call finishes within the grace period and is still answered
+    std::fs::write(&pipe, "drained").unwrap();
+    let response: Value = serde_json::from_str(&next_line(&mut lines).await.unwrap()).unwrap();
+    assert_eq!(response["id"], 1);
+    assert!(response.to_string().contains("drained"), "{}", response);
+    assert_eq!(next_line(&mut lines).await, None);
+    tokio::time::timeout(Duration::from_secs(10), serving).await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_cancels_calls_after_the_grace_period() {
+    let dir = TempDir::new().unwrap();
+    let pipe = fifo(&dir.path().canonicalize().unwrap());
+    let (mut lines, mut output, shutdown, serving) = start(&dir, Duration::from_millis(200)).await;
+    read_fifo(&mut output, &pipe).await;
+
+    shutdown.cancel();
+    // Cancelled calls are not answered; the connection just closes
+    assert_eq!(next_line(&mut lines).await, None);
+    tokio::time::timeout(Duration::from_secs(10), serving).await.unwrap().unwrap();
+
+    // Unblock the read still waiting on its thread
+    std::fs::write(&pipe, "late").unwrap();
+}
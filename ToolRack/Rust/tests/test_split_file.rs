@@ -0,0 +1,86 @@
+use aichemistforge_mcp_server::fs_service::split::{part_name, SplitBy};
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, Content};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn test_part_name_template() {
+    let source = Path::new("/data/archive.tar.gz");
+    assert_eq!(part_name("{name}.{n}", source, 7, 3).unwrap(), "archive.tar.gz.007");
+    assert_eq!(part_name("{stem}-part{n}{ext}", source, 12, 3).unwrap(), "archive.tar-part012.gz");
+    assert!(part_name("{name}.part", source, 1, 3).is_err());
+    assert!(part_name("../{n}", source, 1, 3).is_err());
+}
+
+#[tokio::test]
+async fn test_split_by_bytes_and_lines_rejoins_to_source() {
+    let temp = TempDir::new().unwrap();
+    let source = temp.path().join("log.txt");
+    let data: String = (1..=10).map(|i| format!("line {}\n", i)).collect();
+    fs::write(&source, &data).unwrap();
+    let service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let parts_dir = temp.path().join("bytes");
+    let manifest = service.split_file(&source, &parts_dir, SplitBy::Bytes(25), None, false).await.unwrap();
+    assert_eq!(manifest.source_bytes, data.len() as u64);
+    assert_eq!(manifest.parts.len(), data.len().div_ceil(25));
+    assert!(manifest.parts[0].path.ends_with("log.txt.001"));
+    assert_eq!(manifest.parts.iter().map(|p| p.bytes).collect::<Vec<_>>(), [25, 25, 21]);
+    let joined: Vec<u8> = manifest.parts.iter().flat_map(|p| fs::read(&p.path).unwrap()).collect();
+    assert_eq!(joined, data.as_bytes());
+
+    let parts_dir = temp.path().join("lines");
+    let manifest = service
+        .split_file(&source, &parts_dir, SplitBy::Lines(4), Some("{stem}_{n}{ext}"), false)
+        .await
+        .unwrap();
+    assert_eq!(manifest.parts.iter().map(|p| p.lines).collect::<Vec<_>>(), [Some(4), Some(4), Some(2)]);
+    assert_eq!(fs::read_to_string(parts_dir.join("log_003.txt")).unwrap(), "line 9\nline 10\n");
+    let joined: String = manifest.parts.iter().map(|p| fs::read_to_string(&p.path).unwrap()).collect();
+    assert_eq!(joined, data);
+
+    // Existing parts are left alone unless overwrite is set
+    let err = service
+        .split_file(&source, &parts_dir, SplitBy::Lines(4), Some("{stem}_{n}{ext}"), false)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("already exists"), "{}", err);
+    service
+        .split_file(&source, &parts_dir, SplitBy::Lines(5), Some("{stem}_{n}{ext}"), true)
+        .await
+        .unwrap();
+    assert_eq!(fs::read_to_string(parts_dir.join("log_002.txt")).unwrap().lines().count(), 5);
+}
+
+#[tokio::test]
+async fn test_split_file_tool() {
+    let temp = TempDir::new().unwrap();
+    let source = temp.path().join("data.bin");
+    fs::write(&source, vec![7u8; 10]).unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", temp.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let call = |arguments: serde_json::Value| CallToolRequest {
+        params: CallToolParams { name: "split_file".to_string(), arguments: Some(arguments) },
+    };
+
+    let destination = temp.path().join("parts");
+    let result = handler
+        .handle_call_tool(call(serde_json::json!({ "path": source, "destination": destination, "part_bytes": 4 })))
+        .await
+        .unwrap();
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    let manifest: serde_json::Value = serde_json::from_str(&text.text).unwrap();
+    assert_eq!(manifest["split_by"], serde_json::json!({ "bytes": 4 }));
+    assert_eq!(manifest["parts"].as_array().unwrap().len(), 3);
+    assert_eq!(fs::read(destination.join("data.bin.003")).unwrap(), [7, 7]);
+
+    let result = handler
+        .handle_call_tool(call(serde_json::json!({ "path": source, "destination": destination })))
+        .await
+        .unwrap();
+    assert_eq!(result.is_error, Some(true));
+}
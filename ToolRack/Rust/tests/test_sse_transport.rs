@@ -0,0 +1,87 @@
+#![cfg(feature = "http")]
+
+use aichemistforge_mcp_server::server::{sse, McpServer};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+async fn start_server(dir: &TempDir) -> SocketAddr {
+    let args = CommandArguments::parse_from(["server", dir.path().to_str().unwrap()]);
+    let server = McpServer::new(MyServerHandler::new(&args).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(sse::serve(std::sync::Arc::new(server), listener));
+    address
+}
+
+// Read from `stream` until `received` contains `needle`
+async fn read_until(stream: &mut TcpStream, received: &mut String, needle: &str) {
+    let mut buffer = [0u8; 4096];
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !received.contains(needle) {
+            let read = stream.read(&mut buffer).await.unwrap();
+            assert!(read > 0, "connection closed before {:?} in {}", needle, received);
+            received.push_str(&String::from_utf8_lossy(&buffer[..read]));
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("no {:?} in {}", needle, received));
+}
+
+async fn request(address: SocketAddr, head: &str, body: &str) -> String {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    let request = format!("{}\r\nHost: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", head, address, body.len(), body);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test]
+async fn test_sse_session_round_trip() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("hello.txt"), "hello over sse").unwrap();
+    let address = start_server(&dir).await;
+
+    let mut events = TcpStream::connect(address).await.unwrap();
+    events
+        .write_all(format!("GET /sse HTTP/1.1\r\nHost: {}\r\nAccept: text/event-stream\r\n\r\n", address).as_bytes())
+        .await
+        .unwrap();
+    let mut received = String::new();
+    read_until(&mut events, &mut received, "event: endpoint").await;
+    read_until(&mut events, &mut received, "\n\n").await;
+    let endpoint = received
+        .split("data: ")
+        .nth(1)
+        .and_then(|rest| rest.lines().next())
+        .unwrap()
+        .trim()
+        .to_string();
+    assert!(endpoint.starts_with("/message?sessionId="), "{}", endpoint);
+
+    let initialize = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{},"clientInfo":{"name":"test","version":"1"}}}"#;
+    let response = request(address, &format!("POST {} HTTP/1.1\r\nContent-Type: application/json", endpoint), initialize).await;
+    assert!(response.starts_with("HTTP/1.1 202"), "{}", response);
+    read_until(&mut events, &mut received, "protocolVersion").await;
+
+    let call = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"list_allowed_directories","arguments":{}}}"#;
+    request(address, &format!("POST {} HTTP/1.1\r\nContent-Type: application/json", endpoint), call).await;
+    read_until(&mut events, &mut received, r#""id":2"#).await;
+}
+
+#[tokio::test]
+async fn test_sse_rejects_unknown_sessions_and_foreign_origins() {
+    let dir = TempDir::new().unwrap();
+    let address = start_server(&dir).await;
+
+    let response = request(address, "POST /message?sessionId=nope HTTP/1.1\r\nContent-Type: application/json", "{}").await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{}", response);
+
+    let response = request(address, "GET /sse HTTP/1.1\r\nOrigin: http://attacker.example", "").await;
+    assert!(response.starts_with("HTTP/1.1 403"), "{}", response);
+}
@@ -0,0 +1,92 @@
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::staging::CommitAction;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn path(root: &Path, name: &str) -> String {
+    root.join(name).display().to_string()
+}
+
+#[tokio::test]
+async fn test_stage_accumulates_across_calls() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    fs::write(root.join("a.log"), "a")?;
+    fs::write(root.join("b.log"), "bb")?;
+    fs::write(root.join("notes.txt"), "n")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let first = fs_service.stage_files(&[path(root, "notes.txt")]).await?;
+    assert_eq!(first.added.len(), 1);
+
+    let second = fs_service.stage_files(&[path(root, "*.log"), path(root, "notes.txt")]).await?;
+    assert_eq!(second.added.len(), 2);
+    assert_eq!(second.already_staged, vec![path(root, "notes.txt")]);
+    assert_eq!(second.total_staged, 3);
+
+    let removed = fs_service.unstage_files(&[path(root, "a.log")]).await?;
+    assert_eq!(removed, vec![path(root, "a.log")]);
+    let staged: Vec<String> = fs_service.list_staged().await.into_iter().map(|f| f.path).collect();
+    assert_eq!(staged, vec![path(root, "b.log"), path(root, "notes.txt")]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stage_rejects_missing_files() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    assert!(fs_service.stage_files(&[path(temp_dir.path(), "missing.txt")]).await.is_err());
+    assert!(fs_service.list_staged().await.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_commit_staged_moves_and_clears() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    fs::create_dir(root.join("src"))?;
+    fs::write(root.join("src/one.txt"), "1")?;
+    fs::write(root.join("two.txt"), "2")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    fs_service.stage_files(&[path(root, "src/one.txt"), path(root, "two.txt")]).await?;
+
+    let report = fs_service.commit_staged(&root.join("out"), CommitAction::Move).await?;
+
+    assert!(report.committed, "{:?}", report);
+    assert_eq!(fs::read_to_string(root.join("out/one.txt"))?, "1");
+    assert_eq!(fs::read_to_string(root.join("out/two.txt"))?, "2");
+    assert!(!root.join("two.txt").exists());
+    assert!(fs_service.list_staged().await.is_empty());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_commit_staged_refuses_name_clashes() -> ServiceResult<()> {
+    let temp_dir = TempDir::new()?;
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("x"))?;
+    fs::create_dir_all(root.join("y"))?;
+    fs::create_dir_all(root.join("out"))?;
+    fs::write(root.join("x/same.txt"), "x")?;
+    fs::write(root.join("y/same.txt"), "y")?;
+    fs::write(root.join("z.txt"), "z")?;
+    fs::write(root.join("out/z.txt"), "existing")?;
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    fs_service.stage_files(&[path(root, "x/same.txt"), path(root, "y/same.txt")]).await?;
+    assert!(fs_service.commit_staged(&root.join("out"), CommitAction::Copy).await.is_err());
+
+    fs_service.unstage_files(&[]).await?;
+    fs_service.stage_files(&[path(root, "z.txt")]).await?;
+    assert!(fs_service.commit_staged(&root.join("out"), CommitAction::Copy).await.is_err());
+    assert_eq!(fs::read_to_string(root.join("out/z.txt"))?, "existing");
+    assert_eq!(fs_service.list_staged().await.len(), 1);
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "http")]
+
+use aichemistforge_mcp_server::server::{streamable, McpServer};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::net::SocketAddr;
+use tempfile::TempDir;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const INITIALIZE: &str = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"test","version":"1"}}}"#;
+
+async fn start_server(dir: &TempDir) -> SocketAddr {
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", dir.path().to_str().unwrap()]);
+    let server = McpServer::new(MyServerHandler::new(&args).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(streamable::serve(std::sync::Arc::new(server), listener));
+    address
+}
+
+/// Send one request on its own connection and return the whole response
+async fn request(address: SocketAddr, method: &str, headers: &[(&str, &str)], body: &str) -> String {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    let mut request = format!("{} /mcp HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nContent-Length: {}\r\n", method, address, body.len());
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    request.push_str("\r\n");
+    request.push_str(body);
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+fn session_id(response: &str) -> String {
+    response
+        .lines()
+        .find_map(|line| line.to_ascii_lowercase().strip_prefix("mcp-session-id: ").map(str::to_string))
+        .unwrap_or_else(|| panic!("no session header in {}", response))
+        .trim()
+        .to_string()
+}
+
+const BOTH: (&str, &str) = ("Accept", "application/json, text/event-stream");
+const JSON: (&str, &str) = ("Content-Type", "application/json");
+
+#[tokio::test]
+async fn test_streamable_http_session() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("hello.txt"), "hello over http").unwrap();
+    let address = start_server(&dir).await;
+
+    let response = request(address, "POST", &[BOTH, JSON], INITIALIZE).await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(response.contains("protocolVersion"), "{}", response);
+    let session = session_id(&response);
+    let with_session = [BOTH, JSON, ("Mcp-Session-Id", session.as_str())];
+
+    let initialized = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+    let response = request(address, "POST", &with_session, initialized).await;
+    assert!(response.starts_with("HTTP/1.1 202"), "{}", response);
+
+    // Requests without tool calls are answered with JSON
+    let response = request(address, "POST", &with_session, r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#).await;
+    assert!(response.contains("application/json") && response.contains("read_file"), "{}", response);
+
+    // Tool calls stream their answer as events
+    let path = dir.path().join("hello.txt").display().to_string().replace('\\', "\\\\");
+    let call = format!(
+        r#"[{{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{{"name":"read_file","arguments":{{"path":"{}"}}}}}}]"#,
+        path
+    );
+    let response = request(address, "POST", &with_session, &call).await;
+    assert!(response.contains("text/event-stream"), "{}", response);
+    assert!(response.contains("event: message") && response.contains("hello over http"), "{}", response);
+
+    let response = request(address, "DELETE", &with_session, "").await;
+    assert!(response.starts_with("HTTP/1.1 204"), "{}", response);
+    let response = request(address, "POST", &with_session, r#"{"jsonrpc":"2.0","id":4,"method":"tools/list"}"#).await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{}", response);
+}
+
+#[tokio::test]
+async fn test_streamable_http_requires_a_session() {
+    let dir = TempDir::new().unwrap();
+    let address = start_server(&dir).await;
+
+    let response = request(address, "POST", &[BOTH, JSON], r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).await;
+    assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+    let response = request(address, "POST", &[BOTH, JSON, ("Mcp-Session-Id", "made-up")], r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#).await;
+    assert!(response.starts_with("HTTP/1.1 404"), "{}", response);
+    let response = request(address, "POST", &[BOTH, JSON, ("Origin", "http://attacker.example")], INITIALIZE).await;
+    assert!(response.starts_with("HTTP/1.1 403"), "{}", response);
+}
@@ -0,0 +1,119 @@
+use aichemistforge_mcp_server::mcp_types::{CallToolResult, Content};
+use aichemistforge_mcp_server::tools::{FileSystemTools, ToolStyle};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::call;
+
+fn setup() -> (TempDir, MyServerHandler) {
+    let dir = TempDir::new().unwrap();
+    fs::write(dir.path().join("a.txt"), "same content").unwrap();
+    fs::write(dir.path().join("b.txt"), "same content").unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", dir.path().to_str().unwrap()]);
+    (dir, MyServerHandler::new(&args).unwrap())
+}
+
+fn structured(result: &CallToolResult) -> &Value {
+    assert_ne!(result.is_error, Some(true));
+    result.structured_content.as_ref().expect("structured content")
+}
+
+#[test]
+fn test_flat_tools_declare_output_schemas() {
+    let tools = FileSystemTools::tools(ToolStyle::Both);
+    let schema = |name: &str| tools.iter().find(|tool| tool.name == name).unwrap().output_schema.clone();
+    for name in ["get_file_info", "list_directory_with_sizes", "search_files", "find_duplicate_files"] {
+        assert_eq!(schema(name).unwrap()["type"], "object", "{}", name);
+    }
+    // Grouped tools mix operations with different results
+    assert!(schema("search_and_analysis").is_none());
+    assert!(schema("read_file").is_none());
+
+    let listed = serde_json::to_value(&tools).unwrap();
+    assert!(listed.to_string().contains("\"outputSchema\""));
+}
+
+#[tokio::test]
+async fn test_results_carry_structured_content() {
+    let (dir, handler) = setup();
+    let root = dir.path().display().to_string();
+
+    let result = handler.handle_call_tool(call("list_directory_with_sizes", json!({ "path": root }))).await.unwrap();
+    let listing = structured(&result);
+    assert_eq!(listing["files"], 2);
+    assert_eq!(listing["directories"], 1);
+    assert_eq!(listing["total_size"], 24);
+    assert_eq!(listing["entries"][0], json!({ "name": "a.txt", "type": "file", "size": 12 }));
+
+    let path = dir.path().join("a.txt").display().to_string();
+    let result = handler.handle_call_tool(call("get_file_info", json!({ "path": path }))).await.unwrap();
+    let info = structured(&result);
+    assert_eq!(info["type"], "file");
+    assert_eq!(info["size"], 12);
+    assert_eq!(info["is_text"], true);
+    assert!(info["modified"].as_str().unwrap().ends_with('Z'));
+
+    let result = handler.handle_call_tool(call("find_duplicate_files", json!({ "path": root }))).await.unwrap();
+    let groups = structured(&result)["groups"].as_array().unwrap();
+    assert_eq!(groups.len(), 1);
+    assert_eq!(groups[0].as_array().unwrap().len(), 2);
+
+    let result = handler.handle_call_tool(call("search_files", json!({ "path": root, "pattern": "*.txt" }))).await.unwrap();
+    assert_eq!(structured(&result)["files"].as_array().unwrap().len(), 2);
+
+    // The wire format uses the MCP field name
+    assert!(serde_json::to_value(&result).unwrap().get("structuredContent").is_some());
+}
+
+#[tokio::test]
+async fn test_file_info_extras_on_request() {
+    let (dir, handler) = setup();
+    let path = dir.path().join("lines.txt");
+    fs::write(&path, "one\ntwo\nthree").unwrap();
+    let path = path.display().to_string();
+
+    let result = handler.handle_call_tool(call("get_file_info", json!({ "path": path }))).await.unwrap();
+    let info = structured(&result);
+    assert!(info["line_count"].is_null());
+    assert!(info["hash"].is_null());
+    assert!(info["owner"].is_null());
+
+    let arguments = json!({ "path": path, "with_line_count": true, "with_hash": true, "algorithm": "md5", "with_owner": true });
+    let result = handler.handle_call_tool(call("get_file_info", arguments)).await.unwrap();
+    let info = structured(&result);
+    assert_eq!(info["line_count"], 3);
+    assert_eq!(info["hash"], json!({ "algorithm": "md5", "digest": "af80be2e6d697f29a9d85b87a4999205" }));
+    #[cfg(unix)]
+    assert!(info["owner"]["uid"].is_u64());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_file_info_without_readable_content() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (dir, handler) = setup();
+    // A FIFO is never opened, which would block until something wrote to it
+    let fifo = dir.path().join("pipe");
+    assert!(std::process::Command::new("mkfifo").arg(&fifo).status().unwrap().success());
+    let result = handler.handle_call_tool(call("get_file_info", json!({ "path": fifo }))).await.unwrap();
+    assert!(structured(&result)["mime_type"].is_null());
+
+    let locked = dir.path().join("locked.txt");
+    fs::write(&locked, "secret").unwrap();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+    // Root reads it anyway
+    if fs::File::open(&locked).is_ok() {
+        return;
+    }
+    let result = handler.handle_call_tool(call("get_file_info", json!({ "path": locked }))).await.unwrap();
+    assert_eq!(structured(&result)["size"], 6);
+    assert!(structured(&result)["mime_type"].is_null());
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    assert!(text.text.contains("MIME Type: not detected"), "{}", text.text);
+}
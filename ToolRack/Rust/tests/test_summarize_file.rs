@@ -0,0 +1,76 @@
+use aichemistforge_mcp_server::client::ClientPeer;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::Content;
+use aichemistforge_mcp_server::tools::summarize_file::chunk_text;
+use aichemistforge_mcp_server::tools::SummarizeFile;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use tokio::sync::mpsc;
+
+#[test]
+fn test_chunk_text_breaks_at_lines() {
+    let text = "aaaa\nbbbb\ncccc\n";
+    assert_eq!(chunk_text(text, 11), vec!["aaaa\nbbbb\n", "cccc\n"]);
+    assert_eq!(chunk_text(text, 100), vec![text]);
+    // A single long line is split at the limit
+    assert_eq!(chunk_text("abcdefgh", 3), vec!["abc", "def", "gh"]);
+    assert!(chunk_text("", 10).is_empty());
+}
+
+/// A client that answers every sampling request, recording the prompts
+fn fake_client(peer: Arc<ClientPeer>) -> Arc<Mutex<Vec<String>>> {
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let (sender, mut receiver) = mpsc::unbounded_channel::<Value>();
+    peer.connect(sender);
+    peer.set_capabilities(HashMap::from([("sampling".to_string(), json!({}))]));
+    let seen = prompts.clone();
+    tokio::spawn(async move {
+        while let Some(request) = receiver.recv().await {
+            assert_eq!(request["method"], "sampling/createMessage");
+            let prompt = request["params"]["messages"][0]["content"]["text"].as_str().unwrap().to_string();
+            let answer = if prompt.starts_with("Combine") { "whole file" } else { "one part" };
+            seen.lock().unwrap().push(prompt);
+            peer.handle_response(&json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": { "role": "assistant", "content": { "type": "text", "text": answer }, "model": "test" }
+            }));
+        }
+    });
+    prompts
+}
+
+#[tokio::test]
+async fn test_summarize_file_chunks_and_combines() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("log.txt");
+    std::fs::write(&file, "line\n".repeat(100)).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    let peer = Arc::new(ClientPeer::default());
+    let prompts = fake_client(peer.clone());
+
+    let tool = SummarizeFile { path: file.display().to_string(), focus: Some("errors".to_string()), chunk_chars: Some(200) };
+    let result = tool.run_tool(&fs_service, &peer).await.unwrap();
+
+    let Content::Text(text) = &result.content[0] else { panic!("expected text") };
+    assert!(text.text.contains("3 part(s)"), "{}", text.text);
+    assert!(text.text.ends_with("whole file"));
+    let prompts = prompts.lock().unwrap();
+    assert_eq!(prompts.len(), 4);
+    assert!(prompts[0].starts_with("This is part 1 of 3"));
+    assert!(prompts.iter().all(|p| p.contains("Focus on: errors.")));
+}
+
+#[tokio::test]
+async fn test_summarize_file_requires_sampling() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("a.txt");
+    std::fs::write(&file, "text").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let tool = SummarizeFile { path: file.display().to_string(), focus: None, chunk_chars: None };
+    let error = tool.run_tool(&fs_service, &ClientPeer::default()).await.unwrap_err();
+    assert!(error.message.contains("sampling"));
+}
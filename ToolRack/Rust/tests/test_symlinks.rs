@@ -0,0 +1,234 @@
+#![cfg(unix)]
+
+use aichemistforge_mcp_server::error::ServiceResult;
+use aichemistforge_mcp_server::fs_service::symlinks::SymlinkPolicy;
+use aichemistforge_mcp_server::fs_service::{FileSystemService, SearchFilesOptions};
+use std::fs;
+use std::os::unix::fs::symlink;
+use tempfile::TempDir;
+
+// root/
+//   data/file.txt (5 bytes)
+//   data/loop -> root        (cycle)
+//   link.txt -> data/file.txt
+fn setup_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let data = temp_dir.path().join("data");
+    fs::create_dir(&data).unwrap();
+    fs::write(data.join("file.txt"), "hello").unwrap();
+    symlink(temp_dir.path(), data.join("loop")).unwrap();
+    symlink(data.join("file.txt"), temp_dir.path().join("link.txt")).unwrap();
+    temp_dir
+}
+
+#[tokio::test]
+async fn test_file_stats_reports_link_unless_following() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+    let link = temp_dir.path().join("link.txt");
+
+    let info = fs_service.get_file_stats(&link, false).await?;
+    assert!(info.is_symlink);
+    assert!(!info.is_file);
+
+    let info = fs_service.get_file_stats(&link, true).await?;
+    assert!(!info.is_symlink);
+    assert!(info.is_file);
+    assert_eq!(info.size, 5);
+    assert!(info.link_target.is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_stats_reports_link_target_and_whether_it_is_allowed() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let root = temp_dir.path().canonicalize()?;
+    let outside = TempDir::new().unwrap();
+    fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+    symlink("data/file.txt", root.join("relative.txt")).unwrap();
+    symlink(outside.path().join("secret.txt"), root.join("escape.txt")).unwrap();
+    let fs_service = FileSystemService::try_new(&[root.to_string_lossy().to_string()], &[])?;
+
+    let info = fs_service.get_file_stats(&root.join("relative.txt"), false).await?;
+    let link = info.link_target.expect("a link reports its target");
+    assert_eq!(link.target, std::path::Path::new("data/file.txt"));
+    assert!(link.within_allowed);
+
+    let info = fs_service.get_file_stats(&root.join("escape.txt"), false).await?;
+    let link = info.link_target.expect("a link reports its target");
+    assert_eq!(link.target, outside.path().join("secret.txt"));
+    assert!(!link.within_allowed);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_directory_size_ignores_links_by_default() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let size = fs_service.calculate_directory_size(temp_dir.path(), false, false).await?;
+    assert_eq!(size, 5);
+
+    // Following counts link.txt's target too, but the cycle through data/loop terminates
+    let size = fs_service.calculate_directory_size(temp_dir.path(), true, false).await?;
+    assert_eq!(size, 10);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_directory_tree_reports_symlink_loop() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let tree = fs_service.generate_directory_tree(temp_dir.path(), false, 0, false, false).await?;
+    assert!(tree.contains("link.txt -> "));
+    assert!(!tree.contains("symlink loop"));
+
+    let tree = fs_service.generate_directory_tree(temp_dir.path(), false, 0, true, false).await?;
+    assert!(tree.contains("loop/ -> [symlink loop to"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_search_files_terminates_on_cycle() {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let options = SearchFilesOptions { pattern: "file".to_string(), ..Default::default() };
+    let results = fs_service.search_files(temp_dir.path(), options.clone()).await.unwrap().files;
+    assert_eq!(results.len(), 1);
+
+    let options = SearchFilesOptions { follow_symlinks: true, ..options };
+    let outcome = fs_service.search_files(temp_dir.path(), options).await.unwrap();
+    assert_eq!(outcome.files.len(), 1);
+    assert_eq!(outcome.warnings.len(), 1);
+    assert!(outcome.warnings[0].contains("symlink loop"));
+}
+
+#[tokio::test]
+async fn test_copy_directory_breaks_symlink_cycle() -> ServiceResult<()> {
+    let temp_dir = setup_tree();
+    let dest_root = TempDir::new()?;
+    let dest = dest_root.path().join("copy");
+    let fs_service = FileSystemService::try_new(&[], &[])?;
+
+    let warnings = fs_service.copy_file(temp_dir.path(), &dest).await?;
+
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("symlink loop"));
+    assert_eq!(fs::read_to_string(dest.join("data").join("file.txt"))?, "hello");
+    assert_eq!(fs::read_to_string(dest.join("link.txt"))?, "hello");
+    assert!(!dest.join("data").join("loop").exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_copy_directory_into_itself_is_rejected() {
+    let temp_dir = setup_tree();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    let result = fs_service.copy_file(temp_dir.path(), &temp_dir.path().join("nested")).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_symlinks_cannot_escape_allowed_directories() {
+    let (allowed, outside) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+    fs::create_dir(allowed.path().join("sub")).unwrap();
+    fs::write(allowed.path().join("sub/own.txt"), "own").unwrap();
+    symlink(outside.path(), allowed.path().join("escape")).unwrap();
+    symlink(allowed.path().join("sub"), allowed.path().join("inside")).unwrap();
+    let fs_service = FileSystemService::try_new(&[allowed.path().display().to_string()], &[]).unwrap();
+
+    // Existing targets, new files below a link, and `..` after a link all resolve outside
+    let escapes = [
+        allowed.path().join("escape/secret.txt"),
+        allowed.path().join("escape/new.txt"),
+        allowed.path().join("escape/deeper/new.txt"),
+        allowed.path().join("inside/../escape/secret.txt"),
+        allowed.path().join("missing/../../").join(outside.path().file_name().unwrap()).join("new.txt"),
+    ];
+    for path in &escapes {
+        assert!(fs_service.validate_path(path).await.is_err(), "{} was allowed", path.display());
+    }
+    assert!(fs_service.write_file(&escapes[1], &"x".to_string()).await.is_err());
+    assert!(!outside.path().join("new.txt").exists());
+
+    // Links that stay inside keep working
+    assert_eq!(fs_service.read_file(&allowed.path().join("inside/own.txt")).await.unwrap(), "own");
+    fs_service.write_file(&allowed.path().join("inside/new.txt"), &"new".to_string()).await.unwrap();
+    assert_eq!(fs::read_to_string(allowed.path().join("sub/new.txt")).unwrap(), "new");
+}
+
+fn service_with_policy(root: &TempDir, policy: SymlinkPolicy) -> FileSystemService {
+    let mut fs_service = FileSystemService::try_new(&[root.path().display().to_string()], &[]).unwrap();
+    fs_service.set_symlink_policy(policy);
+    fs_service
+}
+
+#[tokio::test]
+async fn test_deny_policy_refuses_links() {
+    let temp_dir = setup_tree();
+    let fs_service = service_with_policy(&temp_dir, SymlinkPolicy::Deny);
+
+    let refused = fs_service.read_file(&temp_dir.path().join("link.txt")).await.unwrap_err();
+    assert!(refused.to_string().contains("symlink policy (deny)"), "{}", refused);
+    assert!(fs_service.read_file(&temp_dir.path().join("data/loop/data/file.txt")).await.is_err());
+    assert_eq!(fs_service.read_file(&temp_dir.path().join("data/file.txt")).await.unwrap(), "hello");
+
+    let tree = fs_service.generate_directory_tree(temp_dir.path(), false, 0, true, false).await.unwrap();
+    assert!(!tree.contains("link.txt"), "{}", tree);
+    assert!(!tree.contains("loop"), "{}", tree);
+
+    let dest = temp_dir.path().join("copy");
+    let warnings = fs_service.copy_file(&temp_dir.path().join("data"), &dest).await.unwrap();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("symbolic links are refused"));
+    assert!(dest.join("file.txt").exists());
+    assert!(fs::symlink_metadata(dest.join("loop")).is_err());
+}
+
+#[tokio::test]
+async fn test_no_follow_policy_works_on_links_themselves() {
+    let temp_dir = setup_tree();
+    let fs_service = service_with_policy(&temp_dir, SymlinkPolicy::NoFollow);
+    let link = temp_dir.path().join("link.txt");
+
+    assert!(fs_service.read_file(&link).await.is_err());
+    assert!(fs_service.write_file(&link, &"x".to_string()).await.is_err());
+    let info = fs_service.get_file_stats(&link, false).await.unwrap();
+    assert!(info.is_symlink);
+
+    let copy = temp_dir.path().join("copy.txt");
+    fs_service.copy_file(&link, &copy).await.unwrap();
+    assert!(fs::symlink_metadata(&copy).unwrap().file_type().is_symlink());
+
+    fs_service.delete_file(&link).await.unwrap();
+    assert!(fs::symlink_metadata(&link).is_err());
+    assert_eq!(fs::read_to_string(temp_dir.path().join("data/file.txt")).unwrap(), "hello");
+}
+
+#[tokio::test]
+async fn test_follow_within_root_keeps_outside_links_as_links() {
+    let (root, outside) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+    fs::create_dir(root.path().join("sub")).unwrap();
+    fs::write(root.path().join("sub/own.txt"), "own").unwrap();
+    symlink(outside.path(), root.path().join("escape")).unwrap();
+    symlink(root.path().join("sub"), root.path().join("inside")).unwrap();
+    symlink(outside.path().join("secret.txt"), root.path().join("sub/secret.txt")).unwrap();
+    let fs_service = service_with_policy(&root, SymlinkPolicy::FollowWithinRoot);
+
+    let tree = fs_service.generate_directory_tree(root.path(), false, 0, true, false).await.unwrap();
+    assert!(tree.contains("escape -> "), "{}", tree);
+    assert_eq!(tree.matches("── secret.txt -> ").count(), 2, "{}", tree);
+    assert_eq!(tree.matches("own.txt").count(), 2, "{}", tree);
+
+    // Copying recreates the outside link instead of copying its target
+    let dest = root.path().join("copy");
+    fs_service.copy_file(&root.path().join("sub"), &dest).await.unwrap();
+    assert_eq!(fs::read_to_string(dest.join("own.txt")).unwrap(), "own");
+    assert!(fs::symlink_metadata(dest.join("secret.txt")).unwrap().file_type().is_symlink());
+    assert!(fs_service.read_file(&root.path().join("sub/secret.txt")).await.is_err());
+}
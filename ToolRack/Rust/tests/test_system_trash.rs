@@ -0,0 +1,30 @@
+// Points XDG_DATA_HOME at a temporary directory, so this stays in a test
+// binary of its own
+#![cfg(target_os = "linux")]
+
+use aichemistforge_mcp_server::fs_service::trash::Trash;
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_delete_moves_to_user_trash_and_undo_restores() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_home = temp_dir.path().join("data");
+    std::env::set_var("XDG_DATA_HOME", &data_home);
+    let file = temp_dir.path().join("notes.txt");
+    fs::write(&file, "keep me").unwrap();
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_trash(Some(Trash::System));
+
+    fs_service.delete_file(&file).await.unwrap();
+    assert!(!file.exists());
+    let trash = data_home.join("Trash");
+    assert_eq!(fs::read_to_string(trash.join("files/notes.txt")).unwrap(), "keep me");
+    assert!(trash.join("info/notes.txt.trashinfo").exists());
+
+    let undone = fs_service.undo_last_operation().await.unwrap().unwrap();
+    assert_eq!(undone.operation, "delete_file");
+    assert_eq!(fs::read_to_string(&file).unwrap(), "keep me");
+    assert!(!trash.join("info/notes.txt.trashinfo").exists());
+}
@@ -0,0 +1,112 @@
+use aichemistforge_mcp_server::fs_service::tags::TagStore;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, Content};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::fs;
+use tempfile::TempDir;
+
+fn service_with_store(root: &TempDir, store: &TempDir) -> FileSystemService {
+    let mut service = FileSystemService::try_new(&[root.path().display().to_string()], &[]).unwrap();
+    service.set_tag_store(TagStore::new(store.path().join("tags.json")));
+    service
+}
+
+#[tokio::test]
+async fn test_tags_persist_and_are_queryable() {
+    let root = TempDir::new().unwrap();
+    let store = TempDir::new().unwrap();
+    fs::create_dir(root.path().join("src")).unwrap();
+    fs::write(root.path().join("src/gen.rs"), "").unwrap();
+    fs::write(root.path().join("src/lib.rs"), "").unwrap();
+
+    let service = service_with_store(&root, &store);
+    let gen = root.path().join("src/gen.rs");
+    let lib = root.path().join("src/lib.rs");
+    service.tag_path(&gen, &["generated".into(), "reviewed".into()], &[]).await.unwrap();
+    service.tag_path(&lib, &["reviewed".into(), "needs-refactor".into()], &[]).await.unwrap();
+    let tagged = service.tag_path(&lib, &[], &["needs-refactor".into()]).await.unwrap();
+    assert_eq!(tagged.metadata.tags.iter().collect::<Vec<_>>(), ["reviewed"]);
+    service.annotate_path(&gen, "produced by build.rs; do not edit", false).await.unwrap();
+    drop(service);
+
+    // A later session reading the same store sees everything
+    let service = service_with_store(&root, &store);
+    let reviewed = service.find_by_tag(root.path(), &["reviewed".into()], false).await.unwrap();
+    assert_eq!(reviewed.len(), 2);
+    let both = service.find_by_tag(root.path(), &["reviewed".into(), "generated".into()], true).await.unwrap();
+    assert_eq!(both.len(), 1);
+    assert!(both[0].path.ends_with("gen.rs"));
+    assert_eq!(both[0].metadata.notes[0].note, "produced by build.rs; do not edit");
+
+    fs::remove_file(&gen).unwrap();
+    let all = service.tagged_paths(&root.path().join("src")).await.unwrap();
+    assert_eq!(all.iter().filter(|tagged| !tagged.exists).count(), 1);
+}
+
+#[tokio::test]
+async fn test_tags_are_validated() {
+    let root = TempDir::new().unwrap();
+    let store = TempDir::new().unwrap();
+    fs::write(root.path().join("a.txt"), "").unwrap();
+    let service = service_with_store(&root, &store);
+
+    let err = service.tag_path(&root.path().join("a.txt"), &["two words".into()], &[]).await.unwrap_err();
+    assert!(err.to_string().contains("not a valid tag"), "{}", err);
+    assert!(service.tag_path(&root.path().join("missing.txt"), &["x".into()], &[]).await.is_err());
+    let outside = TempDir::new().unwrap();
+    fs::write(outside.path().join("b.txt"), "").unwrap();
+    assert!(service.tag_path(&outside.path().join("b.txt"), &["x".into()], &[]).await.is_err());
+    assert!(!store.path().join("tags.json").exists());
+}
+
+#[tokio::test]
+async fn test_flat_tag_tools() {
+    let root = TempDir::new().unwrap();
+    let store = TempDir::new().unwrap();
+    fs::write(root.path().join("notes.md"), "").unwrap();
+    let tags_file = store.path().join("tags.json");
+    let args = CommandArguments::parse_from([
+        "server",
+        "--tool-style",
+        "flat",
+        "--tags-file",
+        tags_file.to_str().unwrap(),
+        root.path().to_str().unwrap(),
+    ]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let call = |name: &str, arguments: serde_json::Value| CallToolRequest {
+        params: CallToolParams { name: name.to_string(), arguments: Some(arguments) },
+    };
+    let text = |content: &Content| match content {
+        Content::Text(text) => text.text.clone(),
+        _ => panic!("expected text"),
+    };
+    let path = root.path().join("notes.md").display().to_string();
+
+    handler
+        .handle_call_tool(call("tag_path", serde_json::json!({ "path": path, "tags": ["draft"] })))
+        .await
+        .unwrap();
+    handler
+        .handle_call_tool(call("annotate_path", serde_json::json!({ "path": path, "note": "waiting on review" })))
+        .await
+        .unwrap();
+    assert!(tags_file.exists());
+
+    let result = handler
+        .handle_call_tool(call("list_tags", serde_json::json!({ "path": root.path().display().to_string() })))
+        .await
+        .unwrap();
+    assert!(text(&result.content[0]).contains("draft (1)"), "{}", text(&result.content[0]));
+
+    let result = handler
+        .handle_call_tool(call(
+            "find_by_tag",
+            serde_json::json!({ "path": root.path().display().to_string(), "tags": ["draft"] }),
+        ))
+        .await
+        .unwrap();
+    let found = text(&result.content[0]);
+    assert!(found.contains("notes.md") && found.contains("waiting on review"), "{}", found);
+}
@@ -0,0 +1,51 @@
+use aichemistforge_mcp_server::server::{tcp, McpServer};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tempfile::TempDir;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+type Lines = tokio::io::Lines<BufReader<tokio::net::tcp::OwnedReadHalf>>;
+
+async fn exchange(lines: &mut Lines, writer: &mut tokio::net::tcp::OwnedWriteHalf, message: Value) -> Value {
+    writer.write_all(format!("{}\n", message).as_bytes()).await.unwrap();
+    let line = tokio::time::timeout(Duration::from_secs(10), lines.next_line()).await.unwrap().unwrap().unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+#[tokio::test]
+async fn test_tcp_serves_each_connection() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("remote.txt"), "served over tcp").unwrap();
+    let args = CommandArguments::parse_from(["server", "--tool-style", "flat", dir.path().to_str().unwrap()]);
+    let server = McpServer::new(MyServerHandler::new(&args).unwrap());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(tcp::serve(std::sync::Arc::new(server), listener));
+
+    let path = dir.path().join("remote.txt").display().to_string();
+    for client in 0..2 {
+        let (input, mut output) = TcpStream::connect(address).await.unwrap().into_split();
+        let mut lines = BufReader::new(input).lines();
+        let response = exchange(
+            &mut lines,
+            &mut output,
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {
+                "protocolVersion": "2024-11-05", "capabilities": {}, "clientInfo": { "name": "test", "version": "1" }
+            }}),
+        )
+        .await;
+        assert!(response["result"]["protocolVersion"].is_string(), "client {}: {}", client, response);
+
+        let response = exchange(
+            &mut lines,
+            &mut output,
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/call", "params": { "name": "read_file", "arguments": { "path": path } } }),
+        )
+        .await;
+        assert_eq!(response["id"], 2);
+        assert!(response.to_string().contains("served over tcp"), "{}", response);
+    }
+}
@@ -0,0 +1,94 @@
+use aichemistforge_mcp_server::fs_service::temp::TempKind;
+use aichemistforge_mcp_server::fs_service::FileSystemService;
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest, Content};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_temp_paths_live_in_a_session_directory() {
+    let temp = TempDir::new().unwrap();
+    let allowed = temp.path().display().to_string();
+    let service = FileSystemService::try_new(std::slice::from_ref(&allowed), &[]).unwrap();
+    assert_eq!(service.scratch_dir(), temp.path().join(".aichemist-scratch"));
+
+    let file = service.create_temp_file("report-", ".json", Some("{}"), Some(7)).await.unwrap();
+    let dir = service.create_temp_dir("work-", None).await.unwrap();
+    let other = service.create_temp_file("report-", ".json", None, Some(8)).await.unwrap();
+    assert_ne!(file.path, other.path);
+    assert_eq!(fs::read_to_string(&file.path).unwrap(), "{}");
+    assert_eq!(dir.kind, TempKind::Directory);
+    let name = Path::new(&file.path).file_name().unwrap().to_string_lossy().into_owned();
+    assert!(name.starts_with("report-") && name.ends_with(".json"), "{}", name);
+    let session_dir = Path::new(&file.path).parent().unwrap().to_path_buf();
+    assert!(session_dir.starts_with(temp.path().join(".aichemist-scratch")));
+
+    // Completing a mode removes only what it made
+    assert_eq!(service.cleanup_temp_for_mode(7), std::slice::from_ref(&file.path));
+    assert!(!Path::new(&file.path).exists());
+    assert!(Path::new(&other.path).exists() && Path::new(&dir.path).is_dir());
+    assert_eq!(service.temp_entries().len(), 2);
+
+    // The rest goes with the session
+    drop(service);
+    assert!(!session_dir.exists());
+    assert!(service_names_rejected().await);
+}
+
+async fn service_names_rejected() -> bool {
+    let service = FileSystemService::try_new(&[], &[]).unwrap();
+    service.create_temp_file("../escape", "", None, None).await.is_err()
+}
+
+#[tokio::test]
+async fn test_scratch_dir_must_be_allowed() {
+    let allowed = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    let mut service = FileSystemService::try_new(&[allowed.path().display().to_string()], &[]).unwrap();
+    service.set_scratch_dir(outside.path().to_path_buf());
+    let err = service.create_temp_dir("tmp", None).await.unwrap_err();
+    assert!(err.to_string().contains("outside the allowed directories"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_temp_files_removed_when_mode_completes() {
+    let temp = TempDir::new().unwrap();
+    let args = CommandArguments::parse_from(["server", temp.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let call = |name: &str, arguments: serde_json::Value| CallToolRequest {
+        params: CallToolParams { name: name.to_string(), arguments: Some(arguments) },
+    };
+    let text = |content: &Content| match content {
+        Content::Text(text) => text.text.clone(),
+        _ => panic!("expected text"),
+    };
+
+    handler
+        .handle_call_tool(call("start_operation_mode", serde_json::json!({ "mode_name": "file_management" })))
+        .await
+        .unwrap();
+    let result = handler
+        .handle_call_tool(call(
+            "file_management",
+            serde_json::json!({ "operation": "create_temp_file", "suffix": ".txt", "content": "scratch" }),
+        ))
+        .await
+        .unwrap();
+    let created = text(&result.content[0]);
+    assert!(created.contains("removed when the current operation mode completes"), "{}", created);
+    let path = created
+        .strip_prefix("Created temporary file ")
+        .and_then(|rest| rest.split(" (").next())
+        .unwrap()
+        .to_string();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "scratch");
+
+    let complete = CallToolRequest {
+        params: CallToolParams { name: "complete_current_mode".to_string(), arguments: None },
+    };
+    let result = handler.handle_call_tool(complete).await.unwrap();
+    assert!(text(&result.content[0]).contains("Removed 1 temporary path(s)"));
+    assert!(!Path::new(&path).exists());
+}
@@ -0,0 +1,100 @@
+use aichemistforge_mcp_server::tools::{FileSystemTools, ToolStyle};
+use aichemistforge_mcp_server::{CommandArguments, MyServerHandler};
+use clap::Parser;
+use std::fs;
+use tempfile::TempDir;
+
+mod common;
+use common::{call, text};
+
+fn names(style: ToolStyle) -> Vec<String> {
+    FileSystemTools::tools(style).into_iter().map(|tool| tool.name).collect()
+}
+
+#[test]
+fn test_tool_styles_list_grouped_flat_or_both() {
+    let grouped = names(ToolStyle::Grouped);
+    let flat = names(ToolStyle::Flat);
+    let both = names(ToolStyle::Both);
+
+    assert!(grouped.contains(&"single_file_operations".to_string()));
+    assert!(grouped.contains(&"start_operation_mode".to_string()));
+    assert!(!grouped.contains(&"read_file".to_string()));
+
+    assert!(flat.contains(&"read_file".to_string()) && flat.contains(&"compare_directories".to_string()));
+    assert!(!flat.contains(&"single_file_operations".to_string()));
+    assert!(!flat.contains(&"start_operation_mode".to_string()));
+    assert!(flat.contains(&"get_slow_operations".to_string()));
+    assert!(flat.contains(&"server_stats".to_string()) && grouped.contains(&"server_stats".to_string()));
+    assert!(flat.contains(&"set_dry_run".to_string()) && grouped.contains(&"set_dry_run".to_string()));
+
+    // get_slow_operations, server_stats, set_dry_run, reload_policy and the two
+    // undo tools are listed in every style
+    assert_eq!(both.len(), grouped.len() + flat.len() - 6);
+    let mut unique = both.clone();
+    unique.sort();
+    unique.dedup();
+    assert_eq!(unique.len(), both.len());
+}
+
+#[test]
+fn test_flat_tool_schemas_only_name_known_parameters() {
+    for tool in FileSystemTools::tools(ToolStyle::Flat) {
+        let properties = tool.input_schema["properties"].as_object().unwrap();
+        assert!(!properties.contains_key("operation"), "{}", tool.name);
+        for required in tool.input_schema["required"].as_array().into_iter().flatten() {
+            assert!(properties.contains_key(required.as_str().unwrap()), "{}: {}", tool.name, required);
+        }
+    }
+    let write_file = FileSystemTools::tools(ToolStyle::Flat).into_iter().find(|t| t.name == "write_file").unwrap();
+    let mut properties: Vec<&String> = write_file.input_schema["properties"].as_object().unwrap().keys().collect();
+    properties.sort();
+    assert_eq!(properties, ["content", "create_parents", "path"]);
+}
+
+#[tokio::test]
+async fn test_flat_tools_run_without_operation_modes() {
+    let temp = TempDir::new().unwrap();
+    let dir = temp.path().to_str().unwrap();
+    let handler = MyServerHandler::new(&CommandArguments::parse_from(["server", "--tool-style", "flat", dir])).unwrap();
+    let path = temp.path().join("notes.txt");
+
+    let listed: Vec<String> = handler.handle_list_tools().await.unwrap().tools.into_iter().map(|t| t.name).collect();
+    assert!(listed.contains(&"write_file".to_string()) && !listed.contains(&"file_management".to_string()));
+
+    let result = handler
+        .handle_call_tool(call("write_file", serde_json::json!({ "path": path, "content": "hello\nworld\n" })))
+        .await
+        .unwrap();
+    assert_ne!(result.is_error, Some(true), "{}", text(&result));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "hello\nworld\n");
+
+    let result = handler
+        .handle_call_tool(call("head_file", serde_json::json!({ "path": path, "lines": 1 })))
+        .await
+        .unwrap();
+    assert!(text(&result).contains("hello") && !text(&result).contains("world"));
+
+    // Flat schemas mark their operation's arguments as required
+    let result = handler.handle_call_tool(call("write_file", serde_json::json!({ "path": path }))).await.unwrap();
+    assert_eq!(result.is_error, Some(true));
+    assert!(text(&result).contains("\"content\" is a required property"), "{}", text(&result));
+
+    // Grouped tools are not available in flat mode
+    assert!(handler
+        .handle_call_tool(call("single_file_operations", serde_json::json!({ "operation": "read_file", "path": path })))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_grouped_style_rejects_flat_tool_names() {
+    let temp = TempDir::new().unwrap();
+    let handler =
+        MyServerHandler::new(&CommandArguments::parse_from(["server", temp.path().to_str().unwrap()])).unwrap();
+    let error = handler
+        .handle_call_tool(call("read_file", serde_json::json!({ "path": temp.path().join("x") })))
+        .await
+        .unwrap_err();
+    assert!(error.to_string().contains("Unknown tool: read_file"));
+}
@@ -0,0 +1,59 @@
+use aichemistforge_mcp_server::fs_service::sandbox::Sandbox;
+use aichemistforge_mcp_server::fs_service::touch::{set_times, Touched};
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_touch_creates_empty_file_and_undo_removes_it() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.md");
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    assert_eq!(fs_service.touch(&file).await.unwrap(), Touched::Created);
+    assert_eq!(fs::read(&file).unwrap(), b"");
+
+    fs_service.undo_last_operation().await.unwrap().unwrap();
+    assert!(!file.exists());
+}
+
+#[tokio::test]
+async fn test_touch_keeps_content_and_bumps_times() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("build.log");
+    fs::write(&file, "compiled").unwrap();
+    let old = SystemTime::now() - Duration::from_secs(3600);
+    set_times(&file, old).unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    assert_eq!(fs_service.touch(&file).await.unwrap(), Touched::Updated);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "compiled");
+    let modified = fs::metadata(&file).unwrap().modified().unwrap();
+    assert!(modified > old + Duration::from_secs(3000));
+    // Only a created file is journaled
+    assert!(fs_service.undo_journal().history().is_empty());
+}
+
+#[tokio::test]
+async fn test_touch_refuses_missing_parent() {
+    let temp_dir = TempDir::new().unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    assert!(fs_service.touch(&temp_dir.path().join("missing/file.txt")).await.is_err());
+}
+
+#[tokio::test]
+async fn test_touch_in_sandbox_stays_in_overlay() {
+    let temp_dir = TempDir::new().unwrap();
+    let existing = temp_dir.path().join("existing.txt");
+    let created = temp_dir.path().join("created.txt");
+    fs::write(&existing, "keep").unwrap();
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_sandbox(Sandbox::create().unwrap());
+
+    assert_eq!(fs_service.touch(&created).await.unwrap(), Touched::Created);
+    assert_eq!(fs_service.touch(&existing).await.unwrap(), Touched::Unchanged);
+    assert!(!created.exists());
+    assert_eq!(fs_service.diff_sandbox().await.unwrap().len(), 1);
+}
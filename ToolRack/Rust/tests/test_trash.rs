@@ -0,0 +1,156 @@
+use aichemistforge_mcp_server::fs_service::trash::{DeleteMode, Trash};
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest};
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_delete_moves_to_freedesktop_trash_and_undo_restores() {
+    let temp_dir = TempDir::new().unwrap();
+    let trash = temp_dir.path().join("Trash");
+    let file = temp_dir.path().join("my notes.txt");
+    fs::write(&file, "keep me").unwrap();
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_trash(Some(Trash::Freedesktop(trash.clone())));
+
+    fs_service.delete_file(&file).await.unwrap();
+    assert!(!file.exists());
+    assert_eq!(fs::read_to_string(trash.join("files/my notes.txt")).unwrap(), "keep me");
+    let info = fs::read_to_string(trash.join("info/my notes.txt.trashinfo")).unwrap();
+    assert!(info.starts_with("[Trash Info]\n"), "{}", info);
+    assert!(info.contains("my%20notes.txt\n"), "{}", info);
+    assert!(info.contains("DeletionDate="), "{}", info);
+
+    // A second item of the same name does not replace the first
+    fs::write(&file, "newer").unwrap();
+    fs_service.delete_file(&file).await.unwrap();
+    assert_eq!(fs::read_to_string(trash.join("files/my notes.txt.2")).unwrap(), "newer");
+
+    let undone = fs_service.undo_last_operation().await.unwrap().unwrap();
+    assert_eq!(undone.operation, "delete_file");
+    assert_eq!(fs::read_to_string(&file).unwrap(), "newer");
+    assert!(!trash.join("info/my notes.txt.2.trashinfo").exists());
+    assert!(trash.join("files/my notes.txt").exists());
+}
+
+#[tokio::test]
+async fn test_permanent_delete_skips_trash() {
+    let temp_dir = TempDir::new().unwrap();
+    let trash = temp_dir.path().join("Trash");
+    let dir = temp_dir.path().join("build");
+    fs::create_dir(&dir).unwrap();
+    fs::write(dir.join("out.o"), "x").unwrap();
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_trash(Some(Trash::Freedesktop(trash.clone())));
+    fs_service.set_undo_history(0);
+
+    assert!(!fs_service.delete_path(&dir, DeleteMode::Permanent).await.unwrap());
+    assert!(!dir.exists());
+    assert!(!trash.join("files/build").exists());
+}
+
+#[tokio::test]
+async fn test_failing_to_trash_never_deletes() {
+    let temp_dir = TempDir::new().unwrap();
+    // A file where the trash directory should be
+    let trash = temp_dir.path().join("Trash");
+    fs::write(&trash, "").unwrap();
+    let file = temp_dir.path().join("a.txt");
+    fs::write(&file, "a").unwrap();
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_trash(Some(Trash::Freedesktop(trash)));
+
+    let error = fs_service.delete_file(&file).await.unwrap_err().to_string();
+    assert!(error.contains("permanent: true"), "{}", error);
+    assert!(file.exists());
+}
+
+#[tokio::test]
+async fn test_delete_file_tool_uses_trash_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().join("work");
+    let trash = temp_dir.path().join("Trash");
+    fs::create_dir(&root).unwrap();
+    fs::write(root.join("a.txt"), "a").unwrap();
+    fs::write(root.join("b.txt"), "b").unwrap();
+    let args = CommandArguments::parse_from([
+        "server",
+        "--tool-style",
+        "flat",
+        "--trash-dir",
+        trash.to_str().unwrap(),
+        root.to_str().unwrap(),
+    ]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let call = |arguments: Value| CallToolRequest {
+        params: CallToolParams { name: "delete_file".to_string(), arguments: Some(arguments) },
+    };
+
+    let result = handler
+        .handle_call_tool(call(json!({ "path": root.join("a.txt").display().to_string(), "confirm": true })))
+        .await
+        .unwrap();
+    assert!(serde_json::to_string(&result.content).unwrap().contains("Moved to the trash"));
+    assert!(trash.join("files/a.txt").exists());
+
+    handler
+        .handle_call_tool(call(json!({ "path": root.join("b.txt").display().to_string(), "confirm": true, "permanent": true })))
+        .await
+        .unwrap();
+    assert!(!root.join("b.txt").exists());
+    assert!(!trash.join("files/b.txt").exists());
+}
+
+// Needs a second file system; /dev/shm is a tmpfs on most Linux systems
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_trash_on_another_file_system_is_copied_in() {
+    use std::os::unix::fs::MetadataExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let Ok(other) = TempDir::new_in("/dev/shm") else { return };
+    if fs::metadata(other.path()).unwrap().dev() == fs::metadata(temp_dir.path()).unwrap().dev() {
+        return;
+    }
+    let trash = temp_dir.path().join("Trash");
+    let dir = other.path().join("build");
+    fs::create_dir_all(dir.join("obj")).unwrap();
+    fs::write(dir.join("obj").join("out.o"), "x").unwrap();
+    std::os::unix::fs::symlink("obj/out.o", dir.join("latest")).unwrap();
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_trash(Some(Trash::Freedesktop(trash.clone())));
+
+    assert!(fs_service.delete_path(&dir, DeleteMode::Trash).await.unwrap());
+    assert!(!dir.exists());
+    assert_eq!(fs::read_to_string(trash.join("files/build/obj/out.o")).unwrap(), "x");
+    assert_eq!(fs::read_link(trash.join("files/build/latest")).unwrap(), std::path::Path::new("obj/out.o"));
+
+    fs_service.undo_last_operation().await.unwrap().unwrap();
+    assert_eq!(fs::read_to_string(dir.join("latest")).unwrap(), "x");
+    assert!(!trash.join("files/build").exists());
+}
+
+// Linux file systems accept any bytes in names
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_non_utf8_name_is_trashed_under_its_own_name() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let trash = temp_dir.path().join("Trash");
+    let file = temp_dir.path().join(OsStr::from_bytes(b"caf\xe9.txt"));
+    fs::write(&file, "x").unwrap();
+    let mut fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_trash(Some(Trash::Freedesktop(trash.clone())));
+
+    fs_service.delete_file(&file).await.unwrap();
+    assert_eq!(fs::read_to_string(trash.join("files").join(OsStr::from_bytes(b"caf\xe9.txt"))).unwrap(), "x");
+    let info = fs::read_to_string(trash.join("info").join(OsStr::from_bytes(b"caf\xe9.txt.trashinfo"))).unwrap();
+    assert!(info.contains("caf%E9.txt\n"), "{}", info);
+
+    fs_service.undo_last_operation().await.unwrap().unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "x");
+}
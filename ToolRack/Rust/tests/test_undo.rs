@@ -0,0 +1,132 @@
+use aichemistforge_mcp_server::mcp_types::{CallToolParams, CallToolRequest};
+use aichemistforge_mcp_server::tools::EditOperation;
+use aichemistforge_mcp_server::{CommandArguments, FileSystemService, MyServerHandler};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::fs;
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_undo_reverses_writes_edits_moves_and_deletes() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    let moved = temp_dir.path().join("moved.txt");
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    fs_service.write_file(&file, &"one".to_string()).await.unwrap();
+    let created = fs_service.undo_journal().last_step().unwrap();
+    fs_service.write_file(&file, &"two".to_string()).await.unwrap();
+    let edit = EditOperation { old_text: "two".to_string(), new_text: "three".to_string(), ..Default::default() };
+    fs_service.apply_file_edits(&file, vec![edit], None, None).await.unwrap();
+    fs_service.move_file(&file, &moved).await.unwrap();
+    fs_service.delete_file(&moved).await.unwrap();
+    assert!(!moved.exists());
+    assert_eq!(fs_service.undo_journal().history().len(), 5);
+
+    let undone = fs_service.undo_last_operation().await.unwrap().unwrap();
+    assert_eq!(undone.operation, "delete_file");
+    assert_eq!(fs::read_to_string(&moved).unwrap(), "three");
+    fs_service.undo_last_operation().await.unwrap();
+    assert!(!moved.exists());
+    assert_eq!(fs::read_to_string(&file).unwrap(), "three");
+
+    // Back to just after the file was created
+    let undone = fs_service.undo_to_step(created).await.unwrap();
+    assert_eq!(undone.iter().map(|step| step.operation.as_str()).collect::<Vec<_>>(), ["edit_file", "write_file"]);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "one");
+    fs_service.undo_last_operation().await.unwrap();
+    assert!(!file.exists());
+    assert!(fs_service.undo_last_operation().await.unwrap().is_none());
+}
+
+// Needs a file system other than the temp directory's; /dev/shm is a tmpfs
+// on most Linux systems
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn test_deletes_kept_beside_the_original_are_removed_when_forgotten() {
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(other) = TempDir::new_in("/dev/shm") else { return };
+    if fs::metadata(other.path()).unwrap().dev() == fs::metadata(std::env::temp_dir()).unwrap().dev() {
+        return;
+    }
+    let hidden = |dir: &std::path::Path| {
+        fs::read_dir(dir).unwrap().filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().contains(".undo-")).count()
+    };
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+    fs_service.set_undo_history(1);
+    for name in ["a.txt", "b"] {
+        let path = other.path().join(name);
+        if name == "b" {
+            fs::create_dir(&path).unwrap();
+            fs::write(path.join("c.txt"), "c").unwrap();
+        } else {
+            fs::write(&path, "a").unwrap();
+        }
+        fs_service.delete_file(&path).await.unwrap();
+        assert!(!path.exists());
+    }
+    // The first delete dropped out of the history, and what it kept with it
+    assert_eq!(hidden(other.path()), 1);
+
+    drop(fs_service);
+    assert_eq!(hidden(other.path()), 0);
+}
+
+#[tokio::test]
+async fn test_undo_refuses_to_clobber_and_can_be_turned_off() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("a.txt");
+    fs::write(&file, "old").unwrap();
+    let fs_service = FileSystemService::try_new(&[], &[]).unwrap();
+
+    fs_service.delete_file(&file).await.unwrap();
+    fs::write(&file, "new").unwrap();
+    let error = fs_service.undo_last_operation().await.unwrap_err();
+    assert!(error.to_string().contains("exists again"), "{}", error);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "new");
+    // The step stays for another try
+    fs::remove_file(&file).unwrap();
+    fs_service.undo_last_operation().await.unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "old");
+
+    fs_service.set_undo_history(0);
+    assert!(fs_service.undo_journal().history().is_empty());
+    fs_service.delete_file(&file).await.unwrap();
+    assert!(fs_service.undo_last_operation().await.unwrap().is_none());
+    assert!(!file.exists());
+}
+
+#[tokio::test]
+async fn test_undo_tools_and_workflow_steps() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("b.txt");
+    let args = CommandArguments::parse_from(["server", temp_dir.path().to_str().unwrap()]);
+    let handler = MyServerHandler::new(&args).unwrap();
+    let call = |name: &str, arguments: Value| CallToolRequest {
+        params: CallToolParams { name: name.to_string(), arguments: Some(arguments) },
+    };
+
+    handler
+        .handle_call_tool(call("start_operation_mode", json!({ "mode_name": "single_file_operations" })))
+        .await
+        .unwrap();
+    let write = |content: &str| {
+        call(
+            "single_file_operations",
+            json!({ "operation": "write_file", "path": file.display().to_string(), "content": content }),
+        )
+    };
+    handler.handle_call_tool(write("first")).await.unwrap();
+    handler.handle_call_tool(write("second")).await.unwrap();
+    let status_call = CallToolRequest { params: CallToolParams { name: "get_current_mode_status".to_string(), arguments: None } };
+    let status = handler.handle_call_tool(status_call).await.unwrap();
+    let text = serde_json::to_string(&status.content).unwrap();
+    assert!(text.contains("(undo step"), "{}", text);
+
+    let result = handler.handle_call_tool(call("undo_last_operation", json!({}))).await.unwrap();
+    assert_eq!(result.structured_content.unwrap()["undone"][0]["operation"], "write_file");
+    assert_eq!(fs::read_to_string(&file).unwrap(), "first");
+    handler.handle_call_tool(call("undo_to_step", json!({ "step": 0 }))).await.unwrap();
+    assert!(!file.exists());
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "watch")]
+
+use aichemistforge_mcp_server::fs_service::watch::{WatchEventKind, WatchPoll};
+use aichemistforge_mcp_server::FileSystemService;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use tempfile::TempDir;
+
+// Poll until an event for `path` of `kind` shows up, collecting everything seen
+async fn wait_for(fs_service: &FileSystemService, id: &str, path: &Path, kind: WatchEventKind) -> Vec<WatchPoll> {
+    let mut polls = Vec::new();
+    for _ in 0..100 {
+        let poll = fs_service.poll_watch(id).unwrap();
+        let found = poll.events.iter().any(|event| event.kind == kind && Path::new(&event.path) == path);
+        polls.push(poll);
+        if found {
+            return polls;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("no {:?} event for {} in {:?}", kind, path.display(), polls);
+}
+
+#[tokio::test]
+async fn test_watch_reports_created_and_deleted_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    let fs_service = FileSystemService::try_new(&[root.to_string_lossy().to_string()], &[]).unwrap();
+
+    let id = fs_service.start_watch(&root, false).await.unwrap();
+    let file = root.join("out.o");
+    fs::write(&file, "object").unwrap();
+    wait_for(&fs_service, &id, &file, WatchEventKind::Create).await;
+
+    fs::remove_file(&file).unwrap();
+    wait_for(&fs_service, &id, &file, WatchEventKind::Delete).await;
+
+    fs_service.stop_watch(&id).unwrap();
+    assert!(fs_service.poll_watch(&id).is_err());
+    assert!(fs_service.stop_watch(&id).is_err());
+}
+
+#[tokio::test]
+async fn test_recursive_watch_leaves_out_blocked_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().canonicalize().unwrap();
+    fs::create_dir_all(root.join("build")).unwrap();
+    fs::create_dir_all(root.join("secrets")).unwrap();
+    let fs_service = FileSystemService::try_new(
+        &[root.to_string_lossy().to_string()],
+        &[root.join("secrets").to_string_lossy().to_string()],
+    )
+    .unwrap();
+
+    let id = fs_service.start_watch(&root, true).await.unwrap();
+    fs::write(root.join("secrets/key"), "hidden").unwrap();
+    let artifact = root.join("build/app");
+    fs::write(&artifact, "binary").unwrap();
+    let polls = wait_for(&fs_service, &id, &artifact, WatchEventKind::Create).await;
+
+    assert!(polls.iter().flat_map(|poll| &poll.events).all(|event| !event.path.contains("secrets")));
+}
+
+#[tokio::test]
+async fn test_watch_refuses_paths_outside_allowed() {
+    let allowed = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    let fs_service = FileSystemService::try_new(&[allowed.path().to_string_lossy().to_string()], &[]).unwrap();
+
+    assert!(fs_service.start_watch(outside.path(), true).await.is_err());
+}
@@ -0,0 +1,69 @@
+use aichemistforge_mcp_server::fs_service::utils::{is_glob_pattern, long_path, normalize_path, strip_verbatim, to_verbatim};
+use aichemistforge_mcp_server::FileSystemService;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+#[test]
+fn test_verbatim_prefix_round_trip() {
+    let cases = [
+        (r"C:\Users\me\project", r"\\?\C:\Users\me\project"),
+        (r"\\server\share\docs", r"\\?\UNC\server\share\docs"),
+    ];
+    for (plain, verbatim) in cases {
+        assert_eq!(to_verbatim(Path::new(plain)), PathBuf::from(verbatim));
+        assert_eq!(strip_verbatim(Path::new(verbatim)), PathBuf::from(plain));
+    }
+    assert_eq!(to_verbatim(Path::new("C:/Users/me")), PathBuf::from(r"\\?\C:\Users\me"));
+    // Relative, already prefixed and device paths are left alone
+    for path in [r"docs\readme.md", r"\\?\C:\dir", r"\\.\pipe\name"] {
+        assert_eq!(to_verbatim(Path::new(path)), PathBuf::from(path));
+    }
+    assert_eq!(strip_verbatim(Path::new(r"\\?\Volume{1234}\dir")), PathBuf::from(r"\\?\Volume{1234}\dir"));
+}
+
+#[test]
+fn test_verbatim_paths_are_not_patterns() {
+    assert!(!is_glob_pattern(r"\\?\C:\projects"));
+    assert!(!is_glob_pattern(r"\\server\share"));
+    assert!(is_glob_pattern(r"\\?\C:\projects\*.rs"));
+    assert!(is_glob_pattern(r"\\server\share\**"));
+}
+
+#[test]
+fn test_long_paths_keep_working() {
+    let dir = TempDir::new().unwrap();
+    let mut deep = dir.path().to_path_buf();
+    while deep.as_os_str().len() < 300 {
+        deep.push("a-fairly-long-directory-name");
+    }
+    std::fs::create_dir_all(&deep).unwrap();
+    // Long paths are cleaned of `..` before any prefix is added
+    let dotted = deep.join("..").join("a-fairly-long-directory-name").join("file.txt");
+    let cleaned = long_path(&dotted);
+    if cfg!(windows) {
+        assert_eq!(cleaned, to_verbatim(&deep.join("file.txt")));
+    } else {
+        assert_eq!(cleaned, dotted);
+    }
+    assert_eq!(normalize_path(&dotted), normalize_path(&deep.join("file.txt")));
+}
+
+#[tokio::test]
+async fn test_unreachable_paths_cannot_climb_out_with_dot_dot() {
+    let dir = TempDir::new().unwrap();
+    let root = dir.path().join("missing-share");
+    let fs_service = FileSystemService::try_new(&[root.display().to_string()], &[]).unwrap();
+
+    // Nothing below the root exists, so `..` is applied without the disk
+    let escape = root.join("..").join("..").join("etc").join("passwd");
+    assert!(fs_service.validate_path(&escape).await.is_err());
+    assert!(fs_service.validate_path(&root.join("sub").join("..").join("file.txt")).await.is_ok());
+
+    let deep = root.join("x".repeat(200)).join("y".repeat(100));
+    let validated = fs_service.validate_path(&deep).await.unwrap();
+    if cfg!(windows) {
+        assert!(validated.to_string_lossy().starts_with(r"\\?\"));
+    } else {
+        assert_eq!(validated, deep);
+    }
+}